@@ -1,13 +1,15 @@
 use super::*;
 use crate::std::untrusted::path::PathEx;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::ffi::CString;
 use std::io::Read;
 use std::mem::size_of;
+use std::net::IpAddr;
 use std::path::{Path, PathBuf};
 use std::sgxfs::SgxFile;
 
+use crate::util::hosts_parser_util;
 use crate::util::mem_util::from_user;
 
 lazy_static! {
@@ -104,6 +106,8 @@ pub struct Config {
     pub process: ConfigProcess,
     pub env: ConfigEnv,
     pub app: Vec<ConfigApp>,
+    pub network: ConfigNetwork,
+    pub async_io: Option<ConfigAsyncIo>,
 }
 
 #[derive(Debug)]
@@ -125,6 +129,66 @@ pub struct ConfigEnv {
     pub untrusted: HashSet<String>,
 }
 
+#[derive(Debug)]
+pub struct ConfigNetwork {
+    // The network interfaces seen by the LibOS. Use a snapshot of the host
+    // interfaces if not given.
+    pub interfaces: Option<Vec<ConfigInterface>>,
+    // The allowlists of the outbound and inbound traffic of the host sockets.
+    // Nothing is restricted if not given.
+    pub egress: Option<Vec<ConfigNetRule>>,
+    pub ingress: Option<Vec<ConfigNetRule>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConfigNetRule {
+    // Any host if empty
+    pub hosts: Vec<ConfigNetHost>,
+    // The inclusive port ranges. Any port if empty.
+    pub ports: Vec<(u16, u16)>,
+    // Any protocol if empty
+    pub protocols: Vec<ConfigNetProtocol>,
+    // The path prefixes of the executables the rule applies to. Any
+    // executable if empty.
+    pub entry_points: Vec<PathBuf>,
+    // The path prefixes of the unix sockets bridged to the host. A rule with
+    // paths applies only to the unix sockets, and the other rules never match
+    // them.
+    pub paths: Vec<PathBuf>,
+}
+
+// The host names are resolved with the `hosts` map of the network section
+// when parsing, since /etc/hosts in the enclave cannot be trusted.
+#[derive(Clone, Debug)]
+pub enum ConfigNetHost {
+    Prefix(IpAddr, u8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigNetProtocol {
+    Tcp,
+    Udp,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConfigInterface {
+    pub name: String,
+    pub mtu: Option<u32>,
+    pub mac: Option<[u8; 6]>,
+    pub addresses: Vec<(IpAddr, u8)>,
+}
+
+#[derive(Debug)]
+pub struct ConfigAsyncIo {
+    // The number of the entries of the I/O ring
+    pub ring_size: u32,
+    // The number of the host worker threads serving the I/O ring
+    pub num_workers: u32,
+    // Whether the host stream or datagram sockets use the I/O ring
+    pub stream_sockets: bool,
+    pub dgram_sockets: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct ConfigMount {
     pub type_: ConfigMountFsType,
@@ -149,11 +213,15 @@ pub enum ConfigMountFsType {
     TYPE_UNIONFS,
     TYPE_DEVFS,
     TYPE_PROCFS,
+    TYPE_SYSFS,
+    TYPE_ENCFS,
 }
 
 impl ConfigMountFsType {
     pub fn from_input(input: &str) -> Result<ConfigMountFsType> {
-        const ALL_FS_TYPES: [&str; 6] = ["sefs", "hostfs", "ramfs", "unionfs", "devfs", "procfs"];
+        const ALL_FS_TYPES: [&str; 8] = [
+            "sefs", "hostfs", "ramfs", "unionfs", "devfs", "procfs", "sysfs", "encfs",
+        ];
 
         let type_ = match input {
             "sefs" => ConfigMountFsType::TYPE_SEFS,
@@ -162,12 +230,27 @@ impl ConfigMountFsType {
             "unionfs" => ConfigMountFsType::TYPE_UNIONFS,
             "devfs" => ConfigMountFsType::TYPE_DEVFS,
             "procfs" => ConfigMountFsType::TYPE_PROCFS,
+            "sysfs" => ConfigMountFsType::TYPE_SYSFS,
+            "encfs" => ConfigMountFsType::TYPE_ENCFS,
             _ => {
                 return_errno!(EINVAL, "Unsupported file system type");
             }
         };
         Ok(type_)
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfigMountFsType::TYPE_SEFS => "sefs",
+            ConfigMountFsType::TYPE_HOSTFS => "hostfs",
+            ConfigMountFsType::TYPE_RAMFS => "ramfs",
+            ConfigMountFsType::TYPE_UNIONFS => "unionfs",
+            ConfigMountFsType::TYPE_DEVFS => "devfs",
+            ConfigMountFsType::TYPE_PROCFS => "procfs",
+            ConfigMountFsType::TYPE_SYSFS => "sysfs",
+            ConfigMountFsType::TYPE_ENCFS => "encfs",
+        }
+    }
 }
 
 #[derive(Clone, Default, Debug)]
@@ -177,6 +260,11 @@ pub struct ConfigMountOptions {
     pub temporary: bool,
     pub cache_size: Option<u64>,
     pub index: u32,
+    pub confined: Option<bool>,
+    pub obfuscate_names: bool,
+    pub size: Option<usize>,
+    pub nr_inodes: Option<usize>,
+    pub async_io: bool,
 }
 
 impl Config {
@@ -184,6 +272,12 @@ impl Config {
         let resource_limits = ConfigResourceLimits::from_input(&input.resource_limits)?;
         let process = ConfigProcess::from_input(&input.process)?;
         let env = ConfigEnv::from_input(&input.env)?;
+        let network = ConfigNetwork::from_input(&input.network)?;
+        let async_io = if let Some(input_async_io) = &input.async_io {
+            Some(ConfigAsyncIo::from_input(input_async_io)?)
+        } else {
+            None
+        };
 
         let app = {
             let mut app = Vec::new();
@@ -198,6 +292,8 @@ impl Config {
             process,
             env,
             app,
+            network,
+            async_io,
         })
     }
 
@@ -245,6 +341,267 @@ impl ConfigEnv {
     }
 }
 
+impl ConfigNetwork {
+    fn from_input(input: &InputConfigNetwork) -> Result<ConfigNetwork> {
+        let interfaces = if let Some(input_interfaces) = &input.interfaces {
+            let mut interfaces = Vec::new();
+            for input_interface in input_interfaces {
+                let interface = ConfigInterface::from_input(input_interface)?;
+                if interfaces
+                    .iter()
+                    .any(|i: &ConfigInterface| i.name == interface.name)
+                {
+                    return_errno!(EINVAL, "duplicated network interface name");
+                }
+                interfaces.push(interface);
+            }
+            Some(interfaces)
+        } else {
+            None
+        };
+        let hosts = parse_net_hosts(&input.hosts)?;
+        let egress = if let Some(input_rules) = &input.egress {
+            Some(ConfigNetRule::from_input_rules(input_rules, &hosts)?)
+        } else {
+            None
+        };
+        let ingress = if let Some(input_rules) = &input.ingress {
+            Some(ConfigNetRule::from_input_rules(input_rules, &hosts)?)
+        } else {
+            None
+        };
+        Ok(ConfigNetwork {
+            interfaces,
+            egress,
+            ingress,
+        })
+    }
+}
+
+/// Parse the static map from the host names to their addresses.
+fn parse_net_hosts(input: &HashMap<String, Vec<String>>) -> Result<HashMap<String, Vec<IpAddr>>> {
+    let mut hosts = HashMap::new();
+    for (name, input_ips) in input {
+        if !hosts_parser_util::is_valid_hostname(name) {
+            return_errno!(EINVAL, "invalid host name in network hosts");
+        }
+        let mut ips = Vec::new();
+        for ip_str in input_ips {
+            let ip = ip_str
+                .parse::<IpAddr>()
+                .map_err(|_| errno!(EINVAL, "invalid address in network hosts"))?;
+            ips.push(ip);
+        }
+        hosts.insert(name.to_string(), ips);
+    }
+    Ok(hosts)
+}
+
+impl ConfigNetRule {
+    fn from_input_rules(
+        input_rules: &[InputConfigNetRule],
+        net_hosts: &HashMap<String, Vec<IpAddr>>,
+    ) -> Result<Vec<ConfigNetRule>> {
+        let mut rules = Vec::new();
+        for input_rule in input_rules {
+            rules.push(ConfigNetRule::from_input(input_rule, net_hosts)?);
+        }
+        Ok(rules)
+    }
+
+    fn from_input(
+        input: &InputConfigNetRule,
+        net_hosts: &HashMap<String, Vec<IpAddr>>,
+    ) -> Result<ConfigNetRule> {
+        let hosts = {
+            let mut hosts = Vec::new();
+            for host_str in &input.hosts {
+                hosts.extend(ConfigNetHost::from_input(host_str, net_hosts)?);
+            }
+            hosts
+        };
+        let ports = {
+            let mut ports = Vec::new();
+            for input_ports in &input.ports {
+                ports.push(input_ports.to_range()?);
+            }
+            ports
+        };
+        let protocols = {
+            let mut protocols = Vec::new();
+            for protocol_str in &input.protocols {
+                let protocol = match protocol_str.as_str() {
+                    "tcp" => ConfigNetProtocol::Tcp,
+                    "udp" => ConfigNetProtocol::Udp,
+                    _ => return_errno!(EINVAL, "unsupported protocol of network rule"),
+                };
+                protocols.push(protocol);
+            }
+            protocols
+        };
+        let entry_points = {
+            let mut entry_points = Vec::new();
+            for ep in &input.entry_points {
+                let ep_path = Path::new(ep).to_path_buf();
+                if !ep_path.is_absolute() {
+                    return_errno!(EINVAL, "entry point must be an absolute path")
+                }
+                entry_points.push(ep_path);
+            }
+            entry_points
+        };
+        let paths = {
+            let mut paths = Vec::new();
+            for path in &input.paths {
+                let path = Path::new(path).to_path_buf();
+                if !path.is_absolute() {
+                    return_errno!(EINVAL, "unix socket path must be an absolute path")
+                }
+                paths.push(path);
+            }
+            paths
+        };
+        if !paths.is_empty() && !(hosts.is_empty() && ports.is_empty() && protocols.is_empty()) {
+            return_errno!(
+                EINVAL,
+                "network rule with paths cannot have hosts, ports or protocols"
+            );
+        }
+        Ok(ConfigNetRule {
+            hosts,
+            ports,
+            protocols,
+            entry_points,
+            paths,
+        })
+    }
+}
+
+impl ConfigNetHost {
+    fn from_input(
+        host_str: &str,
+        net_hosts: &HashMap<String, Vec<IpAddr>>,
+    ) -> Result<Vec<ConfigNetHost>> {
+        if host_str.contains('/') {
+            let (ip, prefix_len) = parse_ip_prefix(host_str)?;
+            return Ok(vec![ConfigNetHost::Prefix(ip, prefix_len)]);
+        }
+        if let Ok(ip) = host_str.parse::<IpAddr>() {
+            return Ok(vec![ConfigNetHost::from_ip(ip)]);
+        }
+        match net_hosts.get(host_str) {
+            Some(ips) => Ok(ips.iter().map(|ip| ConfigNetHost::from_ip(*ip)).collect()),
+            None => return_errno!(
+                EINVAL,
+                "the host name of network rule is not in network hosts"
+            ),
+        }
+    }
+
+    fn from_ip(ip: IpAddr) -> ConfigNetHost {
+        let prefix_len = if ip.is_ipv4() { 32 } else { 128 };
+        ConfigNetHost::Prefix(ip, prefix_len)
+    }
+}
+
+impl ConfigAsyncIo {
+    fn from_input(input: &InputConfigAsyncIo) -> Result<ConfigAsyncIo> {
+        if !input.ring_size.is_power_of_two() || input.ring_size < 2 || input.ring_size > 4096 {
+            return_errno!(
+                EINVAL,
+                "ring_size must be a power of two between 2 and 4096"
+            );
+        }
+        if input.num_workers == 0 || input.num_workers > 64 {
+            return_errno!(EINVAL, "num_workers must be between 1 and 64");
+        }
+        let mut stream_sockets = false;
+        let mut dgram_sockets = false;
+        for socket_type in &input.socket_types {
+            match socket_type.as_str() {
+                "stream" => stream_sockets = true,
+                "dgram" => dgram_sockets = true,
+                _ => return_errno!(EINVAL, "unsupported socket type for async I/O"),
+            }
+        }
+        Ok(ConfigAsyncIo {
+            ring_size: input.ring_size,
+            num_workers: input.num_workers,
+            stream_sockets,
+            dgram_sockets,
+        })
+    }
+}
+
+impl ConfigInterface {
+    fn from_input(input: &InputConfigInterface) -> Result<ConfigInterface> {
+        // Same as IFNAMSIZ - 1
+        if input.name.is_empty()
+            || input.name.len() > 15
+            || !input
+                .name
+                .bytes()
+                .all(|b| b.is_ascii_graphic() && b != b'/')
+        {
+            return_errno!(EINVAL, "invalid network interface name");
+        }
+        let mac = if let Some(mac_str) = &input.mac {
+            Some(parse_hwaddr(mac_str)?)
+        } else {
+            None
+        };
+        let addresses = {
+            let mut addresses = Vec::new();
+            for addr_str in &input.addresses {
+                addresses.push(parse_ip_prefix(addr_str)?);
+            }
+            addresses
+        };
+        Ok(ConfigInterface {
+            name: input.name.clone(),
+            mtu: input.mtu,
+            mac,
+            addresses,
+        })
+    }
+}
+
+/// Parse a hardware address like "02:42:ac:11:00:02".
+pub fn parse_hwaddr(hwaddr_str: &str) -> Result<[u8; 6]> {
+    let bytes_str_vec: Vec<&str> = hwaddr_str.split(":").collect();
+    if bytes_str_vec.len() != 6 {
+        return_errno!(
+            EINVAL,
+            "The length or format of hardware address is invalid"
+        );
+    }
+
+    let mut hwaddr = [0; 6];
+    for (byte_i, byte_str) in bytes_str_vec.iter().enumerate() {
+        hwaddr[byte_i] = u8::from_str_radix(byte_str, 16).map_err(|e| errno!(e))?;
+    }
+    Ok(hwaddr)
+}
+
+/// Parse an IP address with its prefix length like "192.168.0.2/24".
+pub fn parse_ip_prefix(prefix_str: &str) -> Result<(IpAddr, u8)> {
+    let (ip_str, len_str) = match prefix_str.split_once('/') {
+        Some(pair) => pair,
+        None => return_errno!(EINVAL, "no prefix length"),
+    };
+    let ip: IpAddr = ip_str
+        .parse()
+        .map_err(|_| errno!(EINVAL, "invalid IP address"))?;
+    let prefix_len: u8 = len_str
+        .parse()
+        .map_err(|_| errno!(EINVAL, "invalid prefix length"))?;
+    let max_len = if ip.is_ipv4() { 32 } else { 128 };
+    if prefix_len > max_len {
+        return_errno!(EINVAL, "prefix length is too long");
+    }
+    Ok((ip, prefix_len))
+}
+
 impl ConfigApp {
     fn from_input(input: &InputConfigApp) -> Result<ConfigApp> {
         let stage = input.stage.clone();
@@ -323,17 +680,27 @@ impl ConfigMountOptions {
         } else {
             None
         };
+        let size = if input.size.is_some() {
+            Some(parse_memory_size(input.size.as_ref().unwrap())?)
+        } else {
+            None
+        };
         Ok(ConfigMountOptions {
             mac,
             layers,
             temporary: input.temporary,
             cache_size,
             index: input.index,
+            confined: input.confined,
+            obfuscate_names: input.obfuscate_names,
+            size,
+            nr_inodes: input.nr_inodes,
+            async_io: input.async_io,
         })
     }
 }
 
-fn parse_memory_size(mem_str: &str) -> Result<usize> {
+pub fn parse_memory_size(mem_str: &str) -> Result<usize> {
     const UNIT2FACTOR: [(&str, usize); 5] = [
         ("KB", 1024),
         ("MB", 1024 * 1024),
@@ -369,6 +736,10 @@ struct InputConfig {
     pub env: InputConfigEnv,
     #[serde(default)]
     pub app: Vec<InputConfigApp>,
+    #[serde(default)]
+    pub network: InputConfigNetwork,
+    #[serde(default)]
+    pub async_io: Option<InputConfigAsyncIo>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -450,6 +821,103 @@ impl Default for InputConfigEnv {
     }
 }
 
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct InputConfigNetwork {
+    #[serde(default)]
+    pub interfaces: Option<Vec<InputConfigInterface>>,
+    // The static addresses of the host names used in the rules
+    #[serde(default)]
+    pub hosts: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub egress: Option<Vec<InputConfigNetRule>>,
+    #[serde(default)]
+    pub ingress: Option<Vec<InputConfigNetRule>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct InputConfigNetRule {
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    #[serde(default)]
+    pub ports: Vec<InputConfigPorts>,
+    #[serde(default)]
+    pub protocols: Vec<String>,
+    #[serde(default)]
+    pub entry_points: Vec<String>,
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+// A port like 443, or a port range like "8000-8080"
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum InputConfigPorts {
+    Port(u16),
+    Range(String),
+}
+
+impl InputConfigPorts {
+    fn to_range(&self) -> Result<(u16, u16)> {
+        let range = match self {
+            InputConfigPorts::Port(port) => (*port, *port),
+            InputConfigPorts::Range(range_str) => {
+                let parse_port = |port_str: &str| {
+                    port_str
+                        .trim()
+                        .parse::<u16>()
+                        .map_err(|_| errno!(EINVAL, "invalid port of network rule"))
+                };
+                match range_str.split_once('-') {
+                    Some((start, end)) => (parse_port(start)?, parse_port(end)?),
+                    None => {
+                        let port = parse_port(range_str)?;
+                        (port, port)
+                    }
+                }
+            }
+        };
+        if range.0 > range.1 {
+            return_errno!(EINVAL, "invalid port range of network rule");
+        }
+        Ok(range)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct InputConfigAsyncIo {
+    #[serde(default = "InputConfigAsyncIo::get_ring_size")]
+    pub ring_size: u32,
+    #[serde(default = "InputConfigAsyncIo::get_num_workers")]
+    pub num_workers: u32,
+    #[serde(default)]
+    pub socket_types: Vec<String>,
+}
+
+impl InputConfigAsyncIo {
+    fn get_ring_size() -> u32 {
+        256
+    }
+
+    fn get_num_workers() -> u32 {
+        2
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct InputConfigInterface {
+    pub name: String,
+    #[serde(default)]
+    pub mtu: Option<u32>,
+    #[serde(default)]
+    pub mac: Option<String>,
+    #[serde(default)]
+    pub addresses: Vec<String>,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
 struct InputConfigMount {
@@ -475,6 +943,16 @@ struct InputConfigMountOptions {
     pub cache_size: Option<String>,
     #[serde(default)]
     pub index: u32,
+    #[serde(default)]
+    pub confined: Option<bool>,
+    #[serde(default)]
+    pub obfuscate_names: bool,
+    #[serde(default)]
+    pub size: Option<String>,
+    #[serde(default)]
+    pub nr_inodes: Option<usize>,
+    #[serde(default)]
+    pub async_io: bool,
 }
 
 #[derive(Deserialize, Debug)]
@@ -610,14 +1088,13 @@ impl ConfigApp {
 
         if upper_layer.is_some() {
             let layer_mount_configs = root_mount_config.options.layers.as_mut().unwrap();
-            // container SEFS in layers
+            // container SEFS in layers, which is the uppermost layer
             let root_container_sefs_mount_config = layer_mount_configs
-                .iter_mut()
-                .find(|m| {
+                .last_mut()
+                .filter(|m| {
                     m.target == Path::new("/")
                         && m.type_ == ConfigMountFsType::TYPE_SEFS
                         && m.options.mac.is_none()
-                        && m.options.index == 0
                 })
                 .ok_or_else(|| {
                     errno!(Errno::ENOENT, "the container SEFS in layers is not valid")
@@ -321,10 +321,12 @@ fn do_exec_thread(libos_tid: pid_t, host_tid: pid_t) -> Result<i32> {
     // Idle process should reap all zombie children
     idle_reap_zombie_children()?;
 
-    // sync file system
-    // TODO: only sync when all processes exit
-    use rcore_fs::vfs::FileSystem;
-    crate::fs::ROOT_FS.read().unwrap().sync()?;
+    // Sync file system when all processes exit. The running processes can
+    // make their data durable by fsync, syncfs or sync themselves.
+    if process::table::get_all_processes().is_empty() {
+        use rcore_fs::vfs::FileSystem;
+        crate::fs::ROOT_FS.read().unwrap().sync()?;
+    }
 
     // Not to be confused with the return value of a main function.
     // The exact meaning of status is described in wait(2) man page.
@@ -474,5 +476,13 @@ fn parse_host_files(file_buffer: *const host_file_buffer) -> Result<i32> {
         }
     }
 
+    let interfaces_ptr = unsafe { (*file_buffer).interfaces_buf };
+    if !interfaces_ptr.is_null() {
+        let interfaces_str = unsafe { CStr::from_ptr(interfaces_ptr) }.to_string_lossy();
+        if let Err(e) = net::init_host_interfaces(&interfaces_str) {
+            error!("failed to parse host network interfaces: {}", e.backtrace());
+        }
+    }
+
     Ok(0)
 }
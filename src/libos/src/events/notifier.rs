@@ -15,6 +15,7 @@ struct Subscriber<E: Event, F: EventFilter<E>> {
     observer: Weak<dyn Observer<E>>,
     filter: Option<F>,
     metadata: Option<Weak<dyn Any + Send + Sync>>,
+    exclusive: bool,
 }
 
 impl<E: Event, F: EventFilter<E>> Notifier<E, F> {
@@ -30,12 +31,38 @@ impl<E: Event, F: EventFilter<E>> Notifier<E, F> {
         observer: Weak<dyn Observer<E>>,
         filter: Option<F>,
         metadata: Option<Weak<dyn Any + Send + Sync>>,
+    ) {
+        self.do_register(observer, filter, metadata, false);
+    }
+
+    /// Register an exclusive observer with its interesting events and metadata.
+    ///
+    /// An event is delivered to the exclusive observers that are interested in
+    /// it one by one, in a round-robin manner, until one of them wakes up some
+    /// thread, like Linux does for EPOLLEXCLUSIVE. The non-exclusive observers
+    /// always receive the event.
+    pub fn register_exclusive(
+        &self,
+        observer: Weak<dyn Observer<E>>,
+        filter: Option<F>,
+        metadata: Option<Weak<dyn Any + Send + Sync>>,
+    ) {
+        self.do_register(observer, filter, metadata, true);
+    }
+
+    fn do_register(
+        &self,
+        observer: Weak<dyn Observer<E>>,
+        filter: Option<F>,
+        metadata: Option<Weak<dyn Any + Send + Sync>>,
+        exclusive: bool,
     ) {
         let mut subscribers = self.subscribers.lock().unwrap();
         subscribers.push_back(Subscriber {
             observer,
             filter,
             metadata,
+            exclusive,
         });
     }
 
@@ -45,22 +72,47 @@ impl<E: Event, F: EventFilter<E>> Notifier<E, F> {
         subscribers.retain(|subscriber| !Weak::ptr_eq(&subscriber.observer, observer));
     }
 
+    /// Return whether any observer is registered.
+    pub fn has_observers(&self) -> bool {
+        let subscribers = self.subscribers.lock().unwrap();
+        !subscribers.is_empty()
+    }
+
     /// Broadcast an event to all registered observers.
     pub fn broadcast(&self, event: &E) {
-        let subscribers = self.subscribers.lock().unwrap();
-        for subscriber in subscribers.iter() {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        // Remove the subscribers whose observers have been freed
+        subscribers.retain(|subscriber| subscriber.observer.strong_count() > 0);
+
+        let mut exclusive_idx = None;
+        for (idx, subscriber) in subscribers.iter().enumerate() {
             if let Some(filter) = subscriber.filter.as_ref() {
                 if !filter.filter(event) {
                     continue;
                 }
             }
+            if subscriber.exclusive && exclusive_idx.is_some() {
+                continue;
+            }
             let observer = match subscriber.observer.upgrade() {
-                // TODO: should remove subscribers whose observers have been freed
-                None => return,
+                None => continue,
                 Some(observer) => observer,
             };
 
-            observer.on_event(event, &subscriber.metadata);
+            if subscriber.exclusive {
+                if observer.on_exclusive_event(event, &subscriber.metadata) {
+                    exclusive_idx = Some(idx);
+                }
+            } else {
+                observer.on_event(event, &subscriber.metadata);
+            }
+        }
+
+        // Move the exclusive subscriber that has woken up a thread to the
+        // back, so that the next event goes to another one.
+        if let Some(idx) = exclusive_idx {
+            let subscriber = subscribers.remove(idx).unwrap();
+            subscribers.push_back(subscriber);
         }
     }
 }
@@ -17,4 +17,20 @@ pub trait Observer<E: Event>: Send + Sync {
     /// the odds of deadlocks, the `on_event` method should be written short
     /// and sweet.
     fn on_event(&self, event: &E, metadata: &Option<Weak<dyn Any + Send + Sync>>) -> ();
+
+    /// The callback that will be executed when an event is delivered to this
+    /// observer registered exclusively. It returns whether any thread has been
+    /// woken up by the event, and if not, the notifier delivers the event to
+    /// the next exclusive observer.
+    ///
+    /// By default, the event is handled by `on_event` and is considered to
+    /// have woken up some thread.
+    fn on_exclusive_event(
+        &self,
+        event: &E,
+        metadata: &Option<Weak<dyn Any + Send + Sync>>,
+    ) -> bool {
+        self.on_event(event, metadata);
+        true
+    }
 }
@@ -18,11 +18,11 @@ struct CpuIdInput {
 
 #[repr(C)]
 #[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
-struct CpuIdResult {
-    eax: u32,
-    ebx: u32,
-    ecx: u32,
-    edx: u32,
+pub struct CpuIdResult {
+    pub eax: u32,
+    pub ebx: u32,
+    pub ecx: u32,
+    pub edx: u32,
 }
 
 struct CpuIdCache {
@@ -240,6 +240,14 @@ pub fn setup_cpuid_info() {
     let max_basic_leaf = CPUID.get_max_basic_leaf();
 }
 
+/// Get the CPUID result of the leaf and subleaf from the cache.
+///
+/// It never does OCalls, so it is safe to be called anywhere after the
+/// CPUID info is set up.
+pub fn get_cpuid(leaf: u32, subleaf: u32) -> CpuIdResult {
+    CPUID.get_cpuid_info(leaf, subleaf)
+}
+
 pub fn handle_cpuid_exception(user_context: &mut CpuContext) -> Result<isize> {
     debug!("handle CPUID exception");
     let leaf = user_context.rax as u32;
@@ -1,6 +1,8 @@
 //! Exception handling subsystem.
 
 use self::cpuid::{handle_cpuid_exception, setup_cpuid_info, CPUID_OPCODE};
+
+pub use self::cpuid::{get_cpuid, CpuIdResult};
 use self::rdtsc::{handle_rdtsc_exception, RDTSC_OPCODE};
 use self::syscall::{handle_syscall_exception, SYSCALL_OPCODE};
 use super::*;
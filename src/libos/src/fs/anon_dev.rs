@@ -0,0 +1,28 @@
+//! Device IDs of the file systems without backing devices.
+//!
+//! Like the anonymous devices of Linux, each mounted instance of the pseudo
+//! file systems, e.g., procfs, sysfs and DevFS, is given a unique device ID of
+//! major 0, so that a file can be identified by the pair of (st_dev, st_ino)
+//! across file systems.
+use super::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Skip the small minors, which are likely to be used by the file systems on
+// the host, to avoid conflicts with the device IDs reported by HostFS.
+const FIRST_ANON_MINOR: usize = 0x100;
+
+static NEXT_ANON_MINOR: AtomicUsize = AtomicUsize::new(FIRST_ANON_MINOR);
+
+/// Allocate a new anonymous device ID.
+pub fn alloc_anon_dev() -> usize {
+    let minor = NEXT_ANON_MINOR.fetch_add(1, Ordering::Relaxed);
+    makedev(0, minor)
+}
+
+/// Encode the major and minor numbers into a device ID as glibc and musl do.
+fn makedev(major: usize, minor: usize) -> usize {
+    ((major & 0xffff_f000) << 32)
+        | ((major & 0x0000_0fff) << 8)
+        | ((minor & 0xffff_ff00) << 12)
+        | (minor & 0x0000_00ff)
+}
@@ -1,5 +1,6 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Weak;
+use std::time::Duration;
 
 use ringbuf::{Consumer as RbConsumer, Producer as RbProducer, RingBuffer};
 
@@ -188,6 +189,10 @@ macro_rules! impl_end_point_type {
 // below.
 macro_rules! waiter_loop {
     ($loop_body: block, $waiter_queue: expr) => {
+        let mut timeout: Option<&mut Duration> = None;
+        waiter_loop!($loop_body, $waiter_queue, timeout);
+    };
+    ($loop_body: block, $waiter_queue: expr, $timeout: ident) => {
         // Try without creating a waiter. This saves some CPU cycles if the
         // first attempt succeeds.
         {
@@ -204,7 +209,7 @@ macro_rules! waiter_loop {
                 $loop_body
             }
 
-            waiter.wait(None)?;
+            waiter.wait_mut($timeout.as_deref_mut())?;
         }
     };
 }
@@ -276,6 +281,22 @@ impl<I> Producer<I> {
         self.observer.waiter_queue().dequeue_and_wake_all();
     }
 
+    /// Reopen a producer that has been shutdown.
+    ///
+    /// This is needed by named pipes, whose writers may come and go while
+    /// the channel is kept alive by the readers.
+    pub fn reopen(&self) {
+        {
+            let inner = self.inner.lock().unwrap();
+            if !self.state.is_producer_shutdown() {
+                return;
+            }
+            self.state.clear_producer_shutdown();
+        }
+
+        self.trigger_peer_events(&IoEvents::IN);
+    }
+
     pub fn is_self_shutdown(&self) -> bool {
         self.state.is_producer_shutdown()
     }
@@ -291,6 +312,55 @@ impl<I: Copy> Producer<I> {
     }
 
     pub fn push_slices(&self, item_slices: &[&[I]]) -> Result<usize> {
+        self.do_push_slices(item_slices, self.is_nonblocking(), None)
+    }
+
+    /// Push slices of items without blocking, no matter whether the endpoint
+    /// is non-blocking or not.
+    pub fn try_push_slices(&self, item_slices: &[&[I]]) -> Result<usize> {
+        self.do_push_slices(item_slices, true, None)
+    }
+
+    /// Push slices of items, blocking until the timeout if the endpoint is
+    /// blocking. The timeout is updated to the remaining time.
+    pub fn push_slices_timeout(
+        &self,
+        item_slices: &[&[I]],
+        timeout: Option<&mut Duration>,
+    ) -> Result<usize> {
+        self.do_push_slices(item_slices, self.is_nonblocking(), timeout)
+    }
+
+    /// Resize the channel, keeping the items in it. The consumer must be the
+    /// peer of this producer.
+    ///
+    /// The channel is never shrunk to hold less than the items in it.
+    pub fn resize(&self, consumer: &Consumer<I>, capacity: usize) {
+        assert!(Arc::ptr_eq(&self.state, &consumer.state));
+        {
+            let mut rb_producer = self.inner.lock().unwrap();
+            let mut rb_consumer = consumer.inner.lock().unwrap();
+            let capacity = capacity.max(rb_consumer.len());
+            if capacity == rb_consumer.capacity() {
+                return;
+            }
+
+            let (mut new_rb_producer, new_rb_consumer) = RingBuffer::new(capacity).split();
+            rb_consumer.move_to(&mut new_rb_producer, None);
+            *rb_producer = new_rb_producer;
+            *rb_consumer = new_rb_consumer;
+        }
+
+        // Wake the threads that are blocked on pushing to a full channel
+        self.notifier.broadcast(&IoEvents::OUT);
+    }
+
+    fn do_push_slices(
+        &self,
+        item_slices: &[&[I]],
+        nonblocking: bool,
+        mut timeout: Option<&mut Duration>,
+    ) -> Result<usize> {
         let len: usize = item_slices.iter().map(|slice| slice.len()).sum();
         if len == 0 {
             return Ok(0);
@@ -320,11 +390,12 @@ impl<I: Copy> Producer<I> {
                     return Ok(total_count);
                 }
 
-                if self.is_nonblocking() {
+                if nonblocking {
                     return_errno!(EAGAIN, "try again later");
                 }
             },
-            self.observer.waiter_queue()
+            self.observer.waiter_queue(),
+            timeout
         );
     }
 }
@@ -402,6 +473,22 @@ impl<I> Consumer<I> {
         self.observer.waiter_queue().dequeue_and_wake_all();
     }
 
+    /// Reopen a consumer that has been shutdown.
+    ///
+    /// This is needed by named pipes, whose readers may come and go while
+    /// the channel is kept alive by the writers.
+    pub fn reopen(&self) {
+        {
+            let inner = self.inner.lock().unwrap();
+            if !self.state.is_consumer_shutdown() {
+                return;
+            }
+            self.state.clear_consumer_shutdown();
+        }
+
+        self.trigger_peer_events(&IoEvents::OUT);
+    }
+
     pub fn is_self_shutdown(&self) -> bool {
         self.state.is_consumer_shutdown()
     }
@@ -436,6 +523,50 @@ impl<I: Copy> Consumer<I> {
     }
 
     pub fn pop_slices(&self, item_slices: &mut [&mut [I]]) -> Result<usize> {
+        self.do_pop_slices(item_slices, self.is_nonblocking(), None, false)
+    }
+
+    /// Pop slices of items without blocking, no matter whether the endpoint
+    /// is non-blocking or not.
+    pub fn try_pop_slices(&self, item_slices: &mut [&mut [I]]) -> Result<usize> {
+        self.do_pop_slices(item_slices, true, None, false)
+    }
+
+    /// Pop slices of items, blocking until the timeout if the endpoint is
+    /// blocking. The timeout is updated to the remaining time.
+    pub fn pop_slices_timeout(
+        &self,
+        item_slices: &mut [&mut [I]],
+        timeout: Option<&mut Duration>,
+    ) -> Result<usize> {
+        self.do_pop_slices(item_slices, self.is_nonblocking(), timeout, false)
+    }
+
+    /// Copy slices of items without popping them and without blocking, no
+    /// matter whether the endpoint is non-blocking or not.
+    ///
+    /// The peeked items still take up the channel until they are popped.
+    pub fn try_peek_slices(&self, item_slices: &mut [&mut [I]]) -> Result<usize> {
+        self.do_pop_slices(item_slices, true, None, true)
+    }
+
+    /// Copy slices of items without popping them, blocking until the timeout
+    /// if the endpoint is blocking. The timeout is updated to the remaining time.
+    pub fn peek_slices_timeout(
+        &self,
+        item_slices: &mut [&mut [I]],
+        timeout: Option<&mut Duration>,
+    ) -> Result<usize> {
+        self.do_pop_slices(item_slices, self.is_nonblocking(), timeout, true)
+    }
+
+    fn do_pop_slices(
+        &self,
+        item_slices: &mut [&mut [I]],
+        nonblocking: bool,
+        mut timeout: Option<&mut Duration>,
+        peek: bool,
+    ) -> Result<usize> {
         let len: usize = item_slices.iter().map(|slice| slice.len()).sum();
         if len == 0 {
             return Ok(0);
@@ -449,34 +580,62 @@ impl<I: Copy> Consumer<I> {
                 }
 
                 let mut total_count = 0;
-                for items in item_slices.iter_mut() {
-                    let count = rb_consumer.pop_slice(items);
-                    total_count += count;
-                    if count < items.len() {
-                        break;
-                    } else {
-                        continue;
+                if peek {
+                    total_count = peek_slices(&rb_consumer, item_slices);
+                } else {
+                    for items in item_slices.iter_mut() {
+                        let count = rb_consumer.pop_slice(items);
+                        total_count += count;
+                        if count < items.len() {
+                            break;
+                        } else {
+                            continue;
+                        }
                     }
                 }
 
                 if total_count > 0 {
                     drop(rb_consumer);
-                    self.trigger_peer_events(&IoEvents::OUT);
+                    // No room is made by peeking
+                    if !peek {
+                        self.trigger_peer_events(&IoEvents::OUT);
+                    }
                     return Ok(total_count);
                 };
 
                 if self.is_peer_shutdown() {
                     return Ok(0);
                 }
-                if self.is_nonblocking() {
+                if nonblocking {
                     return_errno!(EAGAIN, "try again later");
                 }
             },
-            self.observer.waiter_queue()
+            self.observer.waiter_queue(),
+            timeout
         );
     }
 }
 
+/// Copy the items in the ring buffer to the slices, return the number of the
+/// items copied.
+fn peek_slices<I: Copy>(rb_consumer: &RbConsumer<I>, item_slices: &mut [&mut [I]]) -> usize {
+    let mut slice_idx = 0;
+    let mut offset = 0;
+    let mut count = 0;
+    rb_consumer.for_each(|item| {
+        while slice_idx < item_slices.len() && offset == item_slices[slice_idx].len() {
+            slice_idx += 1;
+            offset = 0;
+        }
+        if slice_idx < item_slices.len() {
+            item_slices[slice_idx][offset] = *item;
+            offset += 1;
+            count += 1;
+        }
+    });
+    count
+}
+
 impl<I> Drop for Consumer<I> {
     fn drop(&mut self) {
         self.shutdown();
@@ -512,4 +671,12 @@ impl State {
     pub fn set_consumer_shutdown(&self) {
         self.is_consumer_shutdown.store(true, Ordering::Release)
     }
+
+    pub fn clear_producer_shutdown(&self) {
+        self.is_producer_shutdown.store(false, Ordering::Release)
+    }
+
+    pub fn clear_consumer_shutdown(&self) {
+        self.is_consumer_shutdown.store(false, Ordering::Release)
+    }
 }
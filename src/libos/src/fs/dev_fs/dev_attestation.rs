@@ -0,0 +1,506 @@
+//! The attestation pseudo-files (/dev/attestation).
+//!
+//! The files allow the apps written in any language to attest the enclave with
+//! the plain file I/O, instead of the ioctls of /dev/sgx:
+//!
+//! * `user_report_data`: the 64-byte report data embedded in the report and
+//! the quote, which is readable and writable;
+//! * `target_info`: the target info of the enclave which verifies the report,
+//! which is readable and writable;
+//! * `my_target_info`: the target info of this enclave;
+//! * `report`: the report of this enclave targeting `target_info`;
+//! * `quote`: the quote of this enclave, generated by DCAP or EPID;
+//! * `attestation_type`: the type of the quote, i.e., "dcap", "epid" or "none";
+//! * `spid` and `linkable`: the SPID and the signature type of the EPID quote,
+//! which are readable and writable;
+//! * `ratls_cert` and `ratls_key`: the RA-TLS certificate in PEM with a new
+//! key pair and a DCAP quote, and the private key of the certificate in PEM.
+//!
+//! The report, the quote and the RA-TLS certificate are generated when they are
+//! read from the beginning of an opened file, and the subsequent reads of the
+//! same opened file get the same one. The writable files and the `ratls_key`
+//! are kept per process, the `ratls_key` is the private key of the RA-TLS
+//! certificate generated last by the process, which is readable only by it.
+//! If `ratls_key` is read before `ratls_cert`, the certificate generated along
+//! with the key is kept for the next read of `ratls_cert` by the process.
+//!
+//! Generating a quote can take seconds, so the report, the quote and the
+//! RA-TLS certificate are generated without holding any lock, and published
+//! to the opened file and the process after they are generated.
+#[cfg(feature = "dcap")]
+use super::dev_sgx::SGX_DCAP_QUOTE_GENERATOR;
+use super::dev_sgx::SGX_EPID_ATTEST_AGENT;
+use super::*;
+use crate::misc;
+use crate::process::Process;
+use crate::util::mem_util::zeroize;
+use std::sync::Weak;
+use util::sgx::*;
+
+/// The attestation directory
+pub struct DevAttestation {
+    parent: Arc<dyn INode>,
+    dev: usize,
+}
+
+/// A file in the attestation directory
+struct AttestationFile {
+    entry: AttestationEntry,
+    dev: usize,
+    // The report, the quote or the RA-TLS certificate generated by the last
+    // read from the beginning of this opened file
+    generated: SgxMutex<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AttestationEntry {
+    UserReportData,
+    TargetInfo,
+    MyTargetInfo,
+    Report,
+    Quote,
+    AttestationType,
+    Spid,
+    Linkable,
+    RaTlsCert,
+    RaTlsKey,
+}
+
+const ATTESTATION_ENTRIES: [(&str, AttestationEntry); 10] = [
+    ("user_report_data", AttestationEntry::UserReportData),
+    ("target_info", AttestationEntry::TargetInfo),
+    ("my_target_info", AttestationEntry::MyTargetInfo),
+    ("report", AttestationEntry::Report),
+    ("quote", AttestationEntry::Quote),
+    ("attestation_type", AttestationEntry::AttestationType),
+    ("spid", AttestationEntry::Spid),
+    ("linkable", AttestationEntry::Linkable),
+    ("ratls_cert", AttestationEntry::RaTlsCert),
+    ("ratls_key", AttestationEntry::RaTlsKey),
+];
+
+/// The attestation state of a process.
+#[derive(Default)]
+struct AttestationState {
+    input: AttestationInput,
+    // The private key in PEM of the last generated RA-TLS certificate
+    ratls_key: Vec<u8>,
+    // The certificate of `ratls_key` generated by reading `ratls_key`, which
+    // is not read yet
+    unread_ratls_cert: Option<Vec<u8>>,
+}
+
+impl AttestationState {
+    fn set_ratls_key(&mut self, ratls_key: Vec<u8>, unread_ratls_cert: Option<Vec<u8>>) {
+        zeroize(&mut self.ratls_key);
+        self.ratls_key = ratls_key;
+        self.unread_ratls_cert = unread_ratls_cert;
+    }
+}
+
+impl Drop for AttestationState {
+    fn drop(&mut self) {
+        zeroize(&mut self.ratls_key);
+    }
+}
+
+/// The values written by a process, which the report and the quote are
+/// generated with.
+#[derive(Default, Clone, Copy)]
+struct AttestationInput {
+    user_report_data: sgx_report_data_t,
+    target_info: sgx_target_info_t,
+    spid: sgx_spid_t,
+    linkable: bool,
+}
+
+lazy_static! {
+    /// The attestation states indexed by the pids, along with the processes.
+    static ref ATTESTATION_STATES: SgxMutex<HashMap<pid_t, (Weak<Process>, AttestationState)>> =
+        SgxMutex::new(HashMap::new());
+}
+
+/// Run `f` with the attestation state of the current process.
+fn with_state<T>(f: impl FnOnce(&mut AttestationState) -> Result<T>) -> Result<T> {
+    let process = current!().process().clone();
+    let mut states = ATTESTATION_STATES.lock().unwrap();
+    // The states of the exited processes are dropped, so the pids can be reused
+    states.retain(|_, (process, _)| process.strong_count() > 0);
+    let (_, state) = states
+        .entry(process.pid())
+        .or_insert_with(|| (Arc::downgrade(&process), AttestationState::default()));
+    f(state)
+}
+
+impl AttestationInput {
+    fn attestation_type(&self) -> &'static str {
+        #[cfg(feature = "dcap")]
+        {
+            if SGX_DCAP_QUOTE_GENERATOR.is_some() {
+                return "dcap";
+            }
+        }
+        if self.spid.id.iter().any(|&byte| byte != 0) {
+            return "epid";
+        }
+        "none"
+    }
+
+    fn generate_quote(&self) -> Result<Vec<u8>> {
+        #[cfg(feature = "dcap")]
+        {
+            if let Some(generator) = SGX_DCAP_QUOTE_GENERATOR.as_ref() {
+                return generator.generate_quote(&self.user_report_data);
+            }
+        }
+        if self.attestation_type() != "epid" {
+            return_errno!(
+                ENODEV,
+                "no quote backend is available, or the SPID is not set"
+            );
+        }
+        let quote_type = if self.linkable {
+            sgx_quote_sign_type_t::SGX_LINKABLE_SIGNATURE
+        } else {
+            sgx_quote_sign_type_t::SGX_UNLINKABLE_SIGNATURE
+        };
+        let mut nonce = sgx_quote_nonce_t::default();
+        misc::get_random(&mut nonce.rand)?;
+        let quote = SGX_EPID_ATTEST_AGENT.lock().unwrap().generate_quote(
+            None,
+            &self.user_report_data,
+            quote_type,
+            &self.spid,
+            &nonce,
+        )?;
+        let mut quote_buf = vec![0; quote.get_size()];
+        quote.dump_to_buf(&mut quote_buf)?;
+        Ok(quote_buf)
+    }
+}
+
+/// Generate a RA-TLS certificate and its private key, both in PEM.
+fn generate_ratls_pem() -> Result<(Vec<u8>, Vec<u8>)> {
+    #[cfg(feature = "dcap")]
+    {
+        if let Some(generator) = SGX_DCAP_QUOTE_GENERATOR.as_ref() {
+            let ratls_cert = generate_ratls_cert(generator)?;
+            return Ok((
+                ratls_cert.cert_pem.into_bytes(),
+                ratls_cert.key_pem.into_bytes(),
+            ));
+        }
+    }
+    return_errno!(ENODEV, "RA-TLS requires DCAP")
+}
+
+impl AttestationEntry {
+    fn is_writable(&self) -> bool {
+        match self {
+            AttestationEntry::UserReportData
+            | AttestationEntry::TargetInfo
+            | AttestationEntry::Spid
+            | AttestationEntry::Linkable => true,
+            _ => false,
+        }
+    }
+
+    fn ino(&self) -> usize {
+        let idx = ATTESTATION_ENTRIES
+            .iter()
+            .position(|(_, entry)| entry == self)
+            .unwrap();
+        DEV_ATTESTATION_FIRST_INO + idx
+    }
+
+    /// The size of the file, zero if the size is variable.
+    fn size(&self) -> usize {
+        match self {
+            AttestationEntry::UserReportData => std::mem::size_of::<sgx_report_data_t>(),
+            AttestationEntry::TargetInfo | AttestationEntry::MyTargetInfo => {
+                std::mem::size_of::<sgx_target_info_t>()
+            }
+            AttestationEntry::Report => std::mem::size_of::<sgx_report_t>(),
+            AttestationEntry::Spid => std::mem::size_of::<sgx_spid_t>(),
+            _ => 0,
+        }
+    }
+}
+
+impl AttestationFile {
+    fn new(entry: AttestationEntry, dev: usize) -> Self {
+        Self {
+            entry,
+            dev,
+            generated: SgxMutex::new(Vec::new()),
+        }
+    }
+
+    /// Get the content of the file, `is_from_start` is true if the file is
+    /// read from the beginning.
+    fn content(&self, is_from_start: bool) -> Result<Vec<u8>> {
+        let input = with_state(|state| Ok(state.input))?;
+        let content = match self.entry {
+            AttestationEntry::UserReportData => as_bytes(&input.user_report_data).to_vec(),
+            AttestationEntry::TargetInfo => as_bytes(&input.target_info).to_vec(),
+            AttestationEntry::MyTargetInfo => as_bytes(&get_self_target()?).to_vec(),
+            AttestationEntry::Report | AttestationEntry::Quote | AttestationEntry::RaTlsCert => {
+                self.generated_content(is_from_start, &input)?
+            }
+            AttestationEntry::AttestationType => input.attestation_type().as_bytes().to_vec(),
+            AttestationEntry::RaTlsKey => {
+                let ratls_key = with_state(|state| Ok(state.ratls_key.clone()))?;
+                if ratls_key.is_empty() {
+                    let (ratls_cert, mut ratls_key) = generate_ratls_pem()?;
+                    with_state(|state| {
+                        // Keep the key generated by another thread meanwhile
+                        if state.ratls_key.is_empty() {
+                            state.set_ratls_key(ratls_key, Some(ratls_cert));
+                        } else {
+                            zeroize(&mut ratls_key);
+                        }
+                        Ok(state.ratls_key.clone())
+                    })?
+                } else {
+                    ratls_key
+                }
+            }
+            AttestationEntry::Spid => as_bytes(&input.spid).to_vec(),
+            AttestationEntry::Linkable => {
+                let linkable = if input.linkable { b"1" } else { b"0" };
+                linkable.to_vec()
+            }
+        };
+        Ok(content)
+    }
+
+    /// Get the report, the quote or the RA-TLS certificate, which is generated
+    /// with `input` if the file is read from the beginning.
+    fn generated_content(&self, is_from_start: bool, input: &AttestationInput) -> Result<Vec<u8>> {
+        if !is_from_start {
+            let generated = self.generated.lock().unwrap();
+            if !generated.is_empty() {
+                return Ok(generated.clone());
+            }
+        }
+
+        // No lock is held here
+        let (content, ratls_key) = match self.entry {
+            AttestationEntry::Report => {
+                let report =
+                    create_report(Some(&input.target_info), Some(&input.user_report_data))?;
+                (as_bytes(&report).to_vec(), None)
+            }
+            AttestationEntry::Quote => (input.generate_quote()?, None),
+            AttestationEntry::RaTlsCert => {
+                let unread_ratls_cert = with_state(|state| Ok(state.unread_ratls_cert.take()))?;
+                match unread_ratls_cert {
+                    Some(ratls_cert) => (ratls_cert, None),
+                    None => {
+                        let (ratls_cert, ratls_key) = generate_ratls_pem()?;
+                        (ratls_cert, Some(ratls_key))
+                    }
+                }
+            }
+            _ => unreachable!(),
+        };
+
+        let mut generated = self.generated.lock().unwrap();
+        // Publish the certificate and its key together
+        if let Some(ratls_key) = ratls_key {
+            with_state(|state| {
+                state.set_ratls_key(ratls_key, None);
+                Ok(())
+            })?;
+        }
+        *generated = content.clone();
+        Ok(content)
+    }
+
+    fn write_content(&self, offset: usize, buf: &[u8]) -> Result<usize> {
+        with_state(|state| {
+            let dst = match self.entry {
+                AttestationEntry::UserReportData => as_bytes_mut(&mut state.input.user_report_data),
+                AttestationEntry::TargetInfo => as_bytes_mut(&mut state.input.target_info),
+                AttestationEntry::Spid => as_bytes_mut(&mut state.input.spid),
+                AttestationEntry::Linkable => {
+                    state.input.linkable = match buf.first() {
+                        Some(b'0') => false,
+                        Some(b'1') => true,
+                        _ => return_errno!(EINVAL, "linkable must be 0 or 1"),
+                    };
+                    return Ok(buf.len());
+                }
+                _ => return_errno!(EPERM, "the attestation file is read-only"),
+            };
+            let end = offset
+                .checked_add(buf.len())
+                .filter(|&end| end <= dst.len())
+                .ok_or_else(|| errno!(EINVAL, "too many bytes to write"))?;
+            // A write from the beginning resets the whole content
+            if offset == 0 {
+                dst.iter_mut().for_each(|byte| *byte = 0);
+            }
+            dst[offset..end].copy_from_slice(buf);
+            Ok(buf.len())
+        })
+    }
+}
+
+impl INode for AttestationFile {
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
+        let mut content = self.content(offset == 0).map_err(|e| {
+            error!("failed to read /dev/attestation: {}", e.backtrace());
+            to_fs_error(&e)
+        })?;
+        let start = offset.min(content.len());
+        let len = buf.len().min(content.len() - start);
+        buf[..len].copy_from_slice(&content[start..start + len]);
+        if self.entry == AttestationEntry::RaTlsKey {
+            zeroize(&mut content);
+        }
+        Ok(len)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> vfs::Result<usize> {
+        self.write_content(offset, buf).map_err(|e| to_fs_error(&e))
+    }
+
+    fn resize(&self, len: usize) -> vfs::Result<()> {
+        // Allow to open the writable files with O_TRUNC
+        if !self.entry.is_writable() {
+            return Err(FsError::PermError);
+        }
+        Ok(())
+    }
+
+    fn metadata(&self) -> vfs::Result<Metadata> {
+        Ok(Metadata {
+            dev: self.dev,
+            inode: self.entry.ino(),
+            size: self.entry.size(),
+            blk_size: 0,
+            blocks: 0,
+            atime: Timespec { sec: 0, nsec: 0 },
+            mtime: Timespec { sec: 0, nsec: 0 },
+            ctime: Timespec { sec: 0, nsec: 0 },
+            type_: vfs::FileType::File,
+            mode: match self.entry {
+                AttestationEntry::RaTlsKey => 0o400,
+                entry if entry.is_writable() => 0o666,
+                _ => 0o444,
+            },
+            nlinks: 1,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+        })
+    }
+
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl DevAttestation {
+    pub fn new(parent: Arc<dyn INode>, dev: usize) -> Self {
+        Self { parent, dev }
+    }
+
+    fn entries(&self) -> Vec<(String, usize, vfs::FileType)> {
+        let mut entries = vec![
+            (String::from("."), DEV_ATTESTATION_INO, vfs::FileType::Dir),
+            (String::from(".."), DEV_ROOT_INO, vfs::FileType::Dir),
+        ];
+        for (name, entry) in ATTESTATION_ENTRIES.iter() {
+            entries.push((name.to_string(), entry.ino(), vfs::FileType::File));
+        }
+        entries
+    }
+}
+
+impl INode for DevAttestation {
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
+        Err(FsError::IsDir)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> vfs::Result<usize> {
+        Err(FsError::IsDir)
+    }
+
+    fn metadata(&self) -> vfs::Result<Metadata> {
+        Ok(Metadata {
+            dev: self.dev,
+            inode: DEV_ATTESTATION_INO,
+            size: 0,
+            blk_size: 0,
+            blocks: 0,
+            atime: Timespec { sec: 0, nsec: 0 },
+            mtime: Timespec { sec: 0, nsec: 0 },
+            ctime: Timespec { sec: 0, nsec: 0 },
+            type_: vfs::FileType::Dir,
+            mode: 0o755,
+            nlinks: 2,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+        })
+    }
+
+    fn find(&self, name: &str) -> vfs::Result<Arc<dyn INode>> {
+        match name {
+            "." => Ok(Arc::new(DevAttestation::new(self.parent.clone(), self.dev))),
+            ".." => Ok(self.parent.clone()),
+            name => {
+                let (_, entry) = ATTESTATION_ENTRIES
+                    .iter()
+                    .find(|(entry_name, _)| *entry_name == name)
+                    .ok_or(FsError::EntryNotFound)?;
+                Ok(Arc::new(AttestationFile::new(*entry, self.dev)))
+            }
+        }
+    }
+
+    fn get_entry(&self, id: usize) -> vfs::Result<String> {
+        self.entries()
+            .into_iter()
+            .nth(id)
+            .map(|(name, _, _)| name)
+            .ok_or(FsError::EntryNotFound)
+    }
+
+    fn iterate_entries(&self, ctx: &mut DirentWriterContext) -> vfs::Result<usize> {
+        let idx = ctx.pos();
+        for (name, ino, type_) in self.entries().into_iter().skip(idx) {
+            if let Err(e) = ctx.write_entry(&name, ino as u64, type_) {
+                if ctx.written_len() == 0 {
+                    return Err(e);
+                } else {
+                    break;
+                }
+            };
+        }
+        Ok(ctx.written_len())
+    }
+
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn to_fs_error(e: &Error) -> FsError {
+    match e.errno() {
+        EINVAL => FsError::InvalidParam,
+        EPERM => FsError::PermError,
+        EBUSY => FsError::Busy,
+        _ => FsError::NoDevice,
+    }
+}
+
+fn as_bytes<T: Copy>(val: &T) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(val as *const T as *const u8, std::mem::size_of::<T>()) }
+}
+
+fn as_bytes_mut<T: Copy>(val: &mut T) -> &mut [u8] {
+    unsafe { std::slice::from_raw_parts_mut(val as *mut T as *mut u8, std::mem::size_of::<T>()) }
+}
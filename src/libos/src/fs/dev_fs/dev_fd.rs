@@ -1,5 +1,13 @@
 use super::*;
-pub struct DevFd;
+pub struct DevFd {
+    dev: usize,
+}
+
+impl DevFd {
+    pub fn new(dev: usize) -> Self {
+        Self { dev }
+    }
+}
 
 // Implement /dev/fd as a symlink to /proc/self/fd
 impl INode for DevFd {
@@ -17,8 +25,8 @@ impl INode for DevFd {
 
     fn metadata(&self) -> vfs::Result<Metadata> {
         Ok(Metadata {
-            dev: 1,
-            inode: 0,
+            dev: self.dev,
+            inode: DEV_FD_INO,
             size: 0,
             blk_size: 0,
             blocks: 0,
@@ -1,7 +1,15 @@
 use super::*;
 
 #[derive(Debug)]
-pub struct DevNull;
+pub struct DevNull {
+    dev: usize,
+}
+
+impl DevNull {
+    pub fn new(dev: usize) -> Self {
+        Self { dev }
+    }
+}
 
 impl INode for DevNull {
     fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
@@ -14,8 +22,8 @@ impl INode for DevNull {
 
     fn metadata(&self) -> vfs::Result<Metadata> {
         Ok(Metadata {
-            dev: 1,
-            inode: 0,
+            dev: self.dev,
+            inode: DEV_NULL_INO,
             size: 0,
             blk_size: 0,
             blocks: 0,
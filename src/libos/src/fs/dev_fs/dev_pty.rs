@@ -0,0 +1,225 @@
+use super::*;
+use crate::fs::pty::{ptmx_rdev, pts_rdev, tty_rdev};
+
+/// The multiplexer of PTYs, opening which creates a new PTY
+#[derive(Debug)]
+pub struct DevPtmx {
+    dev: usize,
+}
+
+/// The controlling terminal of the process
+#[derive(Debug)]
+pub struct DevTty {
+    dev: usize,
+}
+
+/// The directory of the slaves of PTYs
+pub struct DevPts {
+    parent: Arc<dyn INode>,
+    dev: usize,
+}
+
+/// The slave of a PTY in /dev/pts
+#[derive(Debug)]
+struct DevPtsSlave {
+    index: u32,
+    dev: usize,
+}
+
+impl DevPtmx {
+    pub fn new(dev: usize) -> Self {
+        Self { dev }
+    }
+}
+
+impl DevTty {
+    pub fn new(dev: usize) -> Self {
+        Self { dev }
+    }
+}
+
+// The data of terminals are read and written by the opened files of PTYs
+impl INode for DevPtmx {
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
+        Err(vfs::FsError::NotSupported)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> vfs::Result<usize> {
+        Err(vfs::FsError::NotSupported)
+    }
+
+    fn metadata(&self) -> vfs::Result<Metadata> {
+        Ok(char_device_metadata(
+            self.dev,
+            DEV_PTMX_INO,
+            0o666,
+            ptmx_rdev(),
+        ))
+    }
+
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl INode for DevTty {
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
+        Err(vfs::FsError::NotSupported)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> vfs::Result<usize> {
+        Err(vfs::FsError::NotSupported)
+    }
+
+    fn metadata(&self) -> vfs::Result<Metadata> {
+        Ok(char_device_metadata(
+            self.dev,
+            DEV_TTY_INO,
+            0o666,
+            tty_rdev(),
+        ))
+    }
+
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl INode for DevPtsSlave {
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
+        Err(vfs::FsError::NotSupported)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> vfs::Result<usize> {
+        Err(vfs::FsError::NotSupported)
+    }
+
+    fn metadata(&self) -> vfs::Result<Metadata> {
+        Ok(char_device_metadata(
+            self.dev,
+            DEV_PTS_FIRST_INO + self.index as usize,
+            0o620,
+            pts_rdev(self.index),
+        ))
+    }
+
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl DevPts {
+    pub fn new(parent: Arc<dyn INode>, dev: usize) -> Self {
+        Self { parent, dev }
+    }
+
+    /// The names of the entries, which are the indexes of the alive PTYs.
+    fn entries(&self) -> Vec<(String, usize, vfs::FileType)> {
+        let mut entries = vec![
+            (String::from("."), DEV_PTS_INO, vfs::FileType::Dir),
+            (String::from(".."), DEV_ROOT_INO, vfs::FileType::Dir),
+        ];
+        for pty in Pty::all() {
+            entries.push((
+                pty.index().to_string(),
+                DEV_PTS_FIRST_INO + pty.index() as usize,
+                vfs::FileType::CharDevice,
+            ));
+        }
+        entries
+    }
+}
+
+impl INode for DevPts {
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
+        Err(vfs::FsError::IsDir)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> vfs::Result<usize> {
+        Err(vfs::FsError::IsDir)
+    }
+
+    fn metadata(&self) -> vfs::Result<Metadata> {
+        Ok(Metadata {
+            dev: self.dev,
+            inode: DEV_PTS_INO,
+            size: 0,
+            blk_size: 0,
+            blocks: 0,
+            atime: Timespec { sec: 0, nsec: 0 },
+            mtime: Timespec { sec: 0, nsec: 0 },
+            ctime: Timespec { sec: 0, nsec: 0 },
+            type_: vfs::FileType::Dir,
+            mode: 0o755,
+            nlinks: 2,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+        })
+    }
+
+    fn find(&self, name: &str) -> vfs::Result<Arc<dyn INode>> {
+        match name {
+            "." => Ok(Arc::new(DevPts::new(self.parent.clone(), self.dev))),
+            ".." => Ok(self.parent.clone()),
+            name => {
+                let index = name
+                    .parse::<u32>()
+                    .map_err(|_| vfs::FsError::EntryNotFound)?;
+                Pty::all()
+                    .into_iter()
+                    .find(|pty| pty.index() == index)
+                    .ok_or(vfs::FsError::EntryNotFound)?;
+                Ok(Arc::new(DevPtsSlave {
+                    index,
+                    dev: self.dev,
+                }))
+            }
+        }
+    }
+
+    fn get_entry(&self, id: usize) -> vfs::Result<String> {
+        self.entries()
+            .into_iter()
+            .nth(id)
+            .map(|(name, _, _)| name)
+            .ok_or(vfs::FsError::EntryNotFound)
+    }
+
+    fn iterate_entries(&self, ctx: &mut DirentWriterContext) -> vfs::Result<usize> {
+        let idx = ctx.pos();
+        for (name, ino, type_) in self.entries().into_iter().skip(idx) {
+            if let Err(e) = ctx.write_entry(&name, ino as u64, type_) {
+                if ctx.written_len() == 0 {
+                    return Err(e);
+                } else {
+                    break;
+                }
+            };
+        }
+        Ok(ctx.written_len())
+    }
+
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn char_device_metadata(dev: usize, ino: usize, mode: u16, rdev: usize) -> Metadata {
+    Metadata {
+        dev,
+        inode: ino,
+        size: 0,
+        blk_size: 0,
+        blocks: 0,
+        atime: Timespec { sec: 0, nsec: 0 },
+        mtime: Timespec { sec: 0, nsec: 0 },
+        ctime: Timespec { sec: 0, nsec: 0 },
+        type_: vfs::FileType::CharDevice,
+        mode,
+        nlinks: 1,
+        uid: 0,
+        gid: 0,
+        rdev,
+    }
+}
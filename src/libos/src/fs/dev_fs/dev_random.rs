@@ -2,7 +2,15 @@ use super::*;
 use crate::misc;
 
 #[derive(Debug)]
-pub struct DevRandom;
+pub struct DevRandom {
+    dev: usize,
+}
+
+impl DevRandom {
+    pub fn new(dev: usize) -> Self {
+        Self { dev }
+    }
+}
 
 impl INode for DevRandom {
     fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
@@ -24,8 +32,8 @@ impl INode for DevRandom {
 
     fn metadata(&self) -> vfs::Result<Metadata> {
         Ok(Metadata {
-            dev: 1,
-            inode: 0,
+            dev: self.dev,
+            inode: DEV_RANDOM_INO,
             size: 0,
             blk_size: 0,
             blocks: 0,
@@ -34,7 +42,7 @@ impl INode for DevRandom {
             ctime: Timespec { sec: 0, nsec: 0 },
             type_: vfs::FileType::CharDevice,
             mode: 0o444,
-            nlinks: 1,
+            nlinks: DEV_RANDOM_NLINKS,
             uid: 0,
             gid: 0,
             rdev: 0,
@@ -80,3 +80,19 @@ pub const SGX_CMD_NUM_KEY: u32 = StructuredIoctlNum::new::<IoctlGetKeyArg>(
 
 /// A magical number that distinguishes SGX ioctls for other ioctls
 const SGX_MAGIC_CHAR: u8 = 's' as u8;
+
+/// Ioctl to seal data into a sgx_sealed_data_t blob
+pub const SGX_CMD_NUM_SEAL_DATA: u32 = StructuredIoctlNum::new::<IoctlSealDataArg>(
+    12,
+    SGX_MAGIC_CHAR,
+    StructuredIoctlArgType::InputOutput,
+)
+.as_u32();
+
+/// Ioctl to unseal a sgx_sealed_data_t blob
+pub const SGX_CMD_NUM_UNSEAL_DATA: u32 = StructuredIoctlNum::new::<IoctlUnsealDataArg>(
+    13,
+    SGX_MAGIC_CHAR,
+    StructuredIoctlArgType::InputOutput,
+)
+.as_u32();
@@ -6,6 +6,7 @@ mod consts;
 
 use self::consts::*;
 use util::mem_util::from_user::*;
+use util::mem_util::zeroize;
 use util::sgx::*;
 
 extern "C" {
@@ -13,7 +14,15 @@ extern "C" {
 }
 
 #[derive(Debug)]
-pub struct DevSgx;
+pub struct DevSgx {
+    dev: usize,
+}
+
+impl DevSgx {
+    pub fn new(dev: usize) -> Self {
+        Self { dev }
+    }
+}
 
 impl INode for DevSgx {
     fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
@@ -26,8 +35,8 @@ impl INode for DevSgx {
 
     fn metadata(&self) -> vfs::Result<Metadata> {
         Ok(Metadata {
-            dev: 1,
-            inode: 0,
+            dev: self.dev,
+            inode: DEV_SGX_INO,
             size: 0,
             blk_size: 0,
             blocks: 0,
@@ -138,6 +147,12 @@ impl DevSgx {
             }
             SGX_CMD_NUM_DETECT_DCAP_DRIVER => {
                 let arg = nonbuiltin_cmd.arg_mut::<i32>()?;
+                // The simulated DCAP quotes do not rely on the DCAP driver
+                #[cfg(feature = "sim_quote")]
+                {
+                    *arg = 1;
+                }
+                #[cfg(not(feature = "sim_quote"))]
                 unsafe {
                     let sgx_status = occlum_ocall_detect_dcap_driver(arg);
                     assert_eq!(sgx_status, sgx_status_t::SGX_SUCCESS);
@@ -260,6 +275,54 @@ impl DevSgx {
                 };
                 *key = get_key(key_request)?;
             }
+            SGX_CMD_NUM_SEAL_DATA => {
+                let arg = nonbuiltin_cmd.arg_mut::<IoctlSealDataArg>()?;
+                check_array(arg.plaintext, arg.plaintext_len as usize)?;
+                let plaintext = unsafe {
+                    std::slice::from_raw_parts(arg.plaintext, arg.plaintext_len as usize)
+                };
+                let additional_mac_text = if !arg.additional_mac_text.is_null() {
+                    let len = arg.additional_mac_text_len as usize;
+                    check_array(arg.additional_mac_text, len)?;
+                    unsafe { std::slice::from_raw_parts(arg.additional_mac_text, len) }
+                } else {
+                    &[]
+                };
+
+                // Only get the size of the sealed data if the output buffer is NULL
+                if arg.sealed_data.is_null() {
+                    check_mut_ptr(arg.sealed_data_len)?;
+                    let size = sealed_data_size(plaintext.len(), additional_mac_text.len())?;
+                    unsafe {
+                        *arg.sealed_data_len = size as u32;
+                    }
+                } else {
+                    let sealed_data = seal_data(arg.key_policy, plaintext, additional_mac_text)?;
+                    copy_to_user_buf(arg.sealed_data, arg.sealed_data_len, &sealed_data)?;
+                }
+            }
+            SGX_CMD_NUM_UNSEAL_DATA => {
+                let arg = nonbuiltin_cmd.arg_mut::<IoctlUnsealDataArg>()?;
+                let sealed_data_len = arg.sealed_data_len as usize;
+                check_array(arg.sealed_data, sealed_data_len)?;
+                // Copy the sealed data to avoid being modified during unsealing
+                let sealed_data =
+                    unsafe { std::slice::from_raw_parts(arg.sealed_data, sealed_data_len) }
+                        .to_vec();
+
+                let (mut plaintext, additional_mac_text) = unseal_data(&sealed_data)?;
+                let res = copy_to_user_buf(arg.plaintext, arg.plaintext_len, &plaintext);
+                // Leave no copy of the secret in the enclave
+                zeroize(&mut plaintext);
+                res?;
+                if !arg.additional_mac_text_len.is_null() {
+                    copy_to_user_buf(
+                        arg.additional_mac_text,
+                        arg.additional_mac_text_len,
+                        &additional_mac_text,
+                    )?;
+                }
+            }
             _ => {
                 return_errno!(ENOSYS, "unknown ioctl cmd for /dev/sgx");
             }
@@ -268,6 +331,25 @@ impl DevSgx {
     }
 }
 
+/// Copy the data to the user buffer, whose length is given by `buf_len` and
+/// updated to the length of the data. If the buffer is NULL, only the length
+/// is updated.
+fn copy_to_user_buf(buf: *mut u8, buf_len: *mut u32, data: &[u8]) -> Result<()> {
+    check_mut_ptr(buf_len)?;
+    if !buf.is_null() {
+        if (unsafe { *buf_len } as usize) < data.len() {
+            return_errno!(EINVAL, "provided buffer is too small");
+        }
+        check_mut_array(buf, data.len())?;
+        let user_buf = unsafe { std::slice::from_raw_parts_mut(buf, data.len()) };
+        user_buf.copy_from_slice(data);
+    }
+    unsafe {
+        *buf_len = data.len() as u32;
+    }
+    Ok(())
+}
+
 lazy_static! {
     pub static ref SGX_EPID_ATTEST_AGENT: SgxMutex<SgxEPIDAttestationAgent> =
         { SgxMutex::new(SgxEPIDAttestationAgent::new()) };
@@ -324,3 +406,24 @@ struct IoctlGetKeyArg {
     key_request: *const sgx_key_request_t, // Input
     key: *mut sgx_key_128bit_t,            // Output
 }
+
+#[repr(C)]
+struct IoctlSealDataArg {
+    key_policy: u16,                // Input
+    plaintext: *const u8,           // Input
+    plaintext_len: u32,             // Input
+    additional_mac_text: *const u8, // Input (optional)
+    additional_mac_text_len: u32,   // Input (optional)
+    sealed_data: *mut u8,           // Output (optional)
+    sealed_data_len: *mut u32,      // Input/output
+}
+
+#[repr(C)]
+struct IoctlUnsealDataArg {
+    sealed_data: *const u8,            // Input
+    sealed_data_len: u32,              // Input
+    plaintext: *mut u8,                // Output (optional)
+    plaintext_len: *mut u32,           // Input/output
+    additional_mac_text: *mut u8,      // Output (optional)
+    additional_mac_text_len: *mut u32, // Input/output (optional)
+}
@@ -1,7 +1,15 @@
 use super::*;
 
 #[derive(Debug)]
-pub struct DevShm;
+pub struct DevShm {
+    dev: usize,
+}
+
+impl DevShm {
+    pub fn new(dev: usize) -> Self {
+        Self { dev }
+    }
+}
 
 impl INode for DevShm {
     fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
@@ -14,8 +22,8 @@ impl INode for DevShm {
 
     fn metadata(&self) -> vfs::Result<Metadata> {
         Ok(Metadata {
-            dev: 0,
-            inode: 2,
+            dev: self.dev,
+            inode: DEV_SHM_INO,
             size: 0,
             blk_size: 0,
             blocks: 0,
@@ -24,7 +32,7 @@ impl INode for DevShm {
             ctime: Timespec { sec: 0, nsec: 0 },
             type_: vfs::FileType::Dir,
             mode: 0o777,
-            nlinks: 1,
+            nlinks: 2,
             uid: 0,
             gid: 0,
             rdev: 0,
@@ -0,0 +1,53 @@
+use super::*;
+
+/// The standard I/O devices, i.e., /dev/stdin, /dev/stdout and /dev/stderr
+#[derive(Debug)]
+pub struct DevStdio {
+    fd: FileDesc,
+    dev: usize,
+}
+
+impl DevStdio {
+    pub fn new(fd: FileDesc, dev: usize) -> Self {
+        Self { fd, dev }
+    }
+}
+
+// Implement /dev/stdin, /dev/stdout and /dev/stderr as symlinks to
+// /proc/self/fd/0, /proc/self/fd/1 and /proc/self/fd/2
+impl INode for DevStdio {
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
+        let proc_fd = format!("/proc/self/fd/{}", self.fd);
+        for (tgt, src) in buf.iter_mut().zip(proc_fd.as_bytes().iter()) {
+            *tgt = *src;
+        }
+        Ok(proc_fd.len())
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> vfs::Result<usize> {
+        Err(vfs::FsError::PermError)
+    }
+
+    fn metadata(&self) -> vfs::Result<Metadata> {
+        Ok(Metadata {
+            dev: self.dev,
+            inode: DEV_STDIN_INO + self.fd as usize,
+            size: 0,
+            blk_size: 0,
+            blocks: 0,
+            atime: Timespec { sec: 0, nsec: 0 },
+            mtime: Timespec { sec: 0, nsec: 0 },
+            ctime: Timespec { sec: 0, nsec: 0 },
+            type_: vfs::FileType::SymLink,
+            mode: 0o777,
+            nlinks: 1,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+        })
+    }
+
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
+}
@@ -1,7 +1,15 @@
 use super::*;
 
 #[derive(Debug)]
-pub struct DevZero;
+pub struct DevZero {
+    dev: usize,
+}
+
+impl DevZero {
+    pub fn new(dev: usize) -> Self {
+        Self { dev }
+    }
+}
 
 impl INode for DevZero {
     fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
@@ -17,8 +25,8 @@ impl INode for DevZero {
 
     fn metadata(&self) -> vfs::Result<Metadata> {
         Ok(Metadata {
-            dev: 1,
-            inode: 0,
+            dev: self.dev,
+            inode: DEV_ZERO_INO,
             size: 0,
             blk_size: 0,
             blocks: 0,
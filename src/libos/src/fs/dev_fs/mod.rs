@@ -1,51 +1,94 @@
+use super::anon_dev::alloc_anon_dev;
 use super::rootfs::mount_fs_at;
+use super::sized_ramfs::SizedRamFS;
 use super::*;
 
 use rcore_fs::vfs;
 use rcore_fs_devfs::DevFS;
 use rcore_fs_mountfs::MountFS;
-use rcore_fs_ramfs::RamFS;
 
+use self::dev_attestation::DevAttestation;
 use self::dev_fd::DevFd;
 use self::dev_null::DevNull;
+use self::dev_pty::{DevPtmx, DevPts, DevTty};
 use self::dev_random::DevRandom;
 use self::dev_sgx::DevSgx;
 use self::dev_shm::DevShm;
+use self::dev_stdio::DevStdio;
 use self::dev_zero::DevZero;
 
+mod dev_attestation;
 mod dev_fd;
 mod dev_null;
+mod dev_pty;
 mod dev_random;
 mod dev_sgx;
 mod dev_shm;
+mod dev_stdio;
 mod dev_zero;
 
-/// API to initialize the DevFS
-pub fn init_devfs() -> Result<Arc<MountFS>> {
+// The fixed inode numbers of the files in DevFS
+const DEV_ROOT_INO: usize = 1;
+const DEV_SHM_INO: usize = 2;
+const DEV_NULL_INO: usize = 3;
+const DEV_ZERO_INO: usize = 4;
+const DEV_RANDOM_INO: usize = 5;
+const DEV_SGX_INO: usize = 6;
+const DEV_FD_INO: usize = 7;
+const DEV_PTMX_INO: usize = 8;
+const DEV_PTS_INO: usize = 9;
+const DEV_TTY_INO: usize = 10;
+// The inode numbers of stdin, stdout and stderr are DEV_STDIN_INO + fd
+const DEV_STDIN_INO: usize = 11;
+const DEV_ATTESTATION_INO: usize = 14;
+// The inode numbers of the files in /dev/attestation start from DEV_ATTESTATION_FIRST_INO
+const DEV_ATTESTATION_FIRST_INO: usize = 15;
+// The inode number of /dev/pts/<index> is DEV_PTS_FIRST_INO + index
+const DEV_PTS_FIRST_INO: usize = 0x1000;
+
+// The random device is linked as random, urandom and arandom
+const DEV_RANDOM_NLINKS: usize = 3;
+
+/// API to initialize the DevFS, the limits are applied to the RamFS at '/shm'
+///
+/// Each DevFS has its own device ID, which is shared by all the files in it.
+pub fn init_devfs(shm_size: Option<usize>, shm_nr_inodes: Option<usize>) -> Result<Arc<MountFS>> {
+    let dev = alloc_anon_dev();
     let devfs = DevFS::new();
-    let dev_null = Arc::new(DevNull) as _;
+    let dev_null = Arc::new(DevNull::new(dev)) as _;
     devfs.add("null", dev_null)?;
-    let dev_zero = Arc::new(DevZero) as _;
+    let dev_zero = Arc::new(DevZero::new(dev)) as _;
     devfs.add("zero", dev_zero)?;
-    let dev_random = Arc::new(DevRandom) as _;
+    let dev_random = Arc::new(DevRandom::new(dev)) as _;
     devfs.add("random", Arc::clone(&dev_random))?;
     devfs.add("urandom", Arc::clone(&dev_random))?;
     devfs.add("arandom", Arc::clone(&dev_random))?;
-    let dev_sgx = Arc::new(DevSgx) as _;
+    let dev_sgx = Arc::new(DevSgx::new(dev)) as _;
     devfs.add("sgx", dev_sgx)?;
-    let dev_shm = Arc::new(DevShm) as _;
+    let dev_shm = Arc::new(DevShm::new(dev)) as _;
     devfs.add("shm", dev_shm)?;
-    let dev_fd = Arc::new(DevFd) as _;
+    let dev_fd = Arc::new(DevFd::new(dev)) as _;
     devfs.add("fd", dev_fd);
+    let dev_ptmx = Arc::new(DevPtmx::new(dev)) as _;
+    devfs.add("ptmx", dev_ptmx)?;
+    let dev_pts = Arc::new(DevPts::new(devfs.root_inode(), dev)) as _;
+    devfs.add("pts", dev_pts)?;
+    let dev_tty = Arc::new(DevTty::new(dev)) as _;
+    devfs.add("tty", dev_tty)?;
+    for (fd, name) in ["stdin", "stdout", "stderr"].iter().enumerate() {
+        let dev_stdio = Arc::new(DevStdio::new(fd as FileDesc, dev)) as _;
+        devfs.add(name, dev_stdio)?;
+    }
+    let dev_attestation = Arc::new(DevAttestation::new(devfs.root_inode(), dev)) as _;
+    devfs.add("attestation", dev_attestation)?;
     let mountable_devfs = MountFS::new(devfs);
     // Mount the ramfs at '/shm'
-    let ramfs = RamFS::new();
+    let ramfs = SizedRamFS::new(shm_size, shm_nr_inodes)?;
     mount_fs_at(
         ramfs,
         &mountable_devfs.root_inode(),
         &Path::new("/shm"),
         true,
     )?;
-    // TODO: Add stdio(stdin, stdout, stderr) into DevFS
     Ok(mountable_devfs)
 }
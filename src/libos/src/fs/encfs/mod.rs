@@ -0,0 +1,714 @@
+//! A file system to store the encrypted files in a host directory.
+//!
+//! Unlike SEFS, which stores the files with opaque IDs, the EncFS keeps the
+//! directory tree of the files on the host, so the host tools, e.g., backup
+//! and rsync, can still work on it. The contents of the regular files are
+//! encrypted and integrity-protected by the SGX protected file, with the
+//! given key or the sealing key. The names of the files are optionally
+//! obfuscated, the symlink targets are obfuscated along with them.
+//!
+//! The directory tree is accessed through a confined HostFS. The protected
+//! files are opened through the fds of their parent directories, and every
+//! protected file starts with the hash of its relative path, so the host
+//! could not swap the files of the same name in different directories.
+//!
+//! The host files not created by the EncFS are hidden, i.e., the names not
+//! obfuscated by it, or the regular files which are not protected files if the
+//! names are not obfuscated.
+use super::hostfs::{HNode, HostFS, HostFd, IntoFsError};
+use super::sefs::LockedFile;
+use super::*;
+use alloc::sync::{Arc, Weak};
+use rcore_fs::vfs;
+use rcore_fs_sefs::dev::File as DevFile;
+use std::path::PathBuf;
+use std::sgxfs::OpenOptions;
+
+use self::name_cipher::NameCipher;
+use crate::util::sgx::get_key;
+
+mod name_cipher;
+
+// The maximum length of the names on the host
+const NAME_MAX: usize = 255;
+// The length of the path hash at the start of the protected files
+const PATH_HASH_LEN: usize = 32;
+// The maximum number of the cached sizes of the files not opened
+const MAX_CACHED_SIZES: usize = 4096;
+// The magic number at the start of the protected files, i.e., "SGX_FILE"
+const SGX_FILE_ID: u64 = 0x5347_585F_4649_4C45;
+
+/// Encrypted file system at host
+pub struct EncFS {
+    host_fs: Arc<HostFS>,
+    key: Option<sgx_key_128bit_t>,
+    name_cipher: Option<NameCipher>,
+    /// The opened protected files, indexed by the relative paths on the host.
+    ///
+    /// The protected file is opened exclusively on the host, so all the
+    /// ENodes of the same file must share the opened one.
+    opened_files: SgxMutex<HashMap<PathBuf, Weak<EncFile>>>,
+    /// The sizes of the protected files got by `stat`, indexed by the relative
+    /// paths on the host, along with the modification times and the sizes on
+    /// the host, which tell whether the files are changed since then.
+    file_sizes: SgxMutex<HashMap<PathBuf, ((vfs::Timespec, usize), usize)>>,
+    self_ref: Weak<EncFS>,
+}
+
+/// INode for `EncFS`
+pub struct ENode {
+    inner: Arc<dyn INode>,
+    type_: vfs::FileType,
+    file: SgxMutex<Option<Arc<EncFile>>>,
+    fs: Arc<EncFS>,
+}
+
+/// The opened protected file, whose contents follow the path hash.
+struct EncFile {
+    file: LockedFile,
+    // The protected file is opened by a path through the parent directory,
+    // which is also used to write the recovery file, so keep it opened.
+    _dir_fd: HostFd,
+}
+
+impl vfs::FileSystem for EncFS {
+    fn sync(&self) -> vfs::Result<()> {
+        let opened_files: Vec<Arc<EncFile>> = self
+            .opened_files
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|file| file.upgrade())
+            .collect();
+        for file in opened_files {
+            file.flush()?;
+        }
+        self.host_fs.sync()
+    }
+
+    fn root_inode(&self) -> Arc<dyn INode> {
+        Arc::new(ENode {
+            inner: self.host_fs.root_inode(),
+            type_: vfs::FileType::Dir,
+            file: SgxMutex::new(None),
+            fs: self.self_ref.upgrade().unwrap(),
+        })
+    }
+
+    fn info(&self) -> vfs::FsInfo {
+        self.host_fs.info()
+    }
+}
+
+impl EncFS {
+    /// Create a new `EncFS` from host `path`
+    ///
+    /// If `key` is `None`, the files are encrypted with the sealing key.
+    pub fn new(
+        path: impl AsRef<Path>,
+        key: &Option<sgx_key_128bit_t>,
+        obfuscate_names: bool,
+    ) -> Result<Arc<EncFS>> {
+        let host_fs = HostFS::new(path, true, false)?;
+        let name_cipher = if obfuscate_names {
+            let name_key = match key {
+                Some(key) => *key,
+                None => get_name_sealing_key()?,
+            };
+            Some(NameCipher::new(&name_key)?)
+        } else {
+            None
+        };
+        Ok(EncFS {
+            host_fs,
+            key: *key,
+            name_cipher,
+            opened_files: SgxMutex::new(HashMap::new()),
+            file_sizes: SgxMutex::new(HashMap::new()),
+            self_ref: Weak::default(),
+        }
+        .wrap())
+    }
+
+    /// Wrap pure `EncFS` with Arc
+    /// Used in constructors
+    fn wrap(self) -> Arc<Self> {
+        // Create an Arc, make a Weak from it, then put it into the struct.
+        // It's a little tricky.
+        let fs = Arc::new(self);
+        let weak = Arc::downgrade(&fs);
+        let ptr = Arc::into_raw(fs) as *mut Self;
+        unsafe {
+            (*ptr).self_ref = weak;
+        }
+        unsafe { Arc::from_raw(ptr) }
+    }
+
+    /// Convert the name in EncFS to the one on the host.
+    fn host_name(&self, name: &str) -> vfs::Result<String> {
+        let host_name = match self.name_cipher.as_ref() {
+            Some(cipher) => cipher
+                .encrypt(name)
+                .map_err(|_| vfs::FsError::InvalidParam)?,
+            None => return Ok(name.to_string()),
+        };
+        if host_name.len() > NAME_MAX {
+            return Err(vfs::FsError::NameTooLong);
+        }
+        Ok(host_name)
+    }
+
+    /// Convert the name on the host to the one in EncFS.
+    ///
+    /// Returns `None` if the name is not obfuscated by this EncFS.
+    fn name(&self, host_name: &str) -> Option<String> {
+        match self.name_cipher.as_ref() {
+            Some(cipher) => cipher.decrypt(host_name),
+            None => Some(host_name.to_string()),
+        }
+    }
+
+    /// Convert the name of the host entry in `dir` to the one in EncFS.
+    ///
+    /// Returns `None` if the entry is not created by this EncFS.
+    fn entry_name(&self, dir: &HNode, host_name: &str, type_: vfs::FileType) -> Option<String> {
+        let name = self.name(host_name)?;
+        // Any name is valid if not obfuscated, so check the regular file itself
+        if self.name_cipher.is_none() && type_ == vfs::FileType::File {
+            let inner = dir.find(host_name).ok()?;
+            if !is_protected_file(inner.downcast_ref::<HNode>()?) {
+                return None;
+            }
+        }
+        Some(name)
+    }
+
+    /// Get the opened protected file of `hnode`, open it if not opened.
+    ///
+    /// If `create` is true, create the protected file in the empty host file.
+    fn open_file(&self, hnode: &HNode, create: bool) -> vfs::Result<Arc<EncFile>> {
+        let rel_path = hnode.rel_path();
+        let mut opened_files = self.opened_files.lock().unwrap();
+        if !create {
+            if let Some(file) = opened_files.get(rel_path).and_then(|file| file.upgrade()) {
+                return Ok(file);
+            }
+        }
+
+        // The parent directory is opened by the confined HostFS, so the host
+        // could not redirect the path with symlinks.
+        let (dir_fd, name) = hnode.open_parent()?;
+        let pfs_path = PathBuf::from(format!("/proc/self/fd/{}", dir_fd.as_raw())).join(name);
+        let open = |read_only: bool| {
+            let mut options = OpenOptions::new();
+            if create {
+                options.write(true).update(true);
+            } else if read_only {
+                options.read(true);
+            } else {
+                options.read(true).update(true);
+            }
+            options.open_with(&pfs_path, self.key.as_ref(), None)
+        };
+        let sgx_file = match open(false) {
+            Ok(sgx_file) => sgx_file,
+            // The host file is read-only
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied && !create => {
+                open(true).map_err(|e| e.into_fs_error())?
+            }
+            Err(e) => return Err(e.into_fs_error()),
+        };
+        let file = Arc::new(EncFile {
+            file: LockedFile::new(sgx_file),
+            _dir_fd: dir_fd,
+        });
+        if create {
+            file.bind_path(rel_path)?;
+        } else if file.path_hash()? != hash_path(rel_path)? {
+            return Err(vfs::FsError::NoIntegrity);
+        }
+        opened_files.retain(|_, file| file.strong_count() > 0);
+        opened_files.insert(rel_path.to_path_buf(), Arc::downgrade(&file));
+        Ok(file)
+    }
+
+    /// Get the size of the protected file of `hnode`.
+    ///
+    /// The size is cached if the file is not opened, so the file needs not to
+    /// be opened again by `stat` until it is changed on the host.
+    fn file_size(&self, hnode: &HNode, host_metadata: &vfs::Metadata) -> vfs::Result<usize> {
+        let rel_path = hnode.rel_path();
+        let opened_file = self
+            .opened_files
+            .lock()
+            .unwrap()
+            .get(rel_path)
+            .and_then(|file| file.upgrade());
+        if let Some(file) = opened_file {
+            return file.len();
+        }
+
+        let host_stamp = (host_metadata.mtime, host_metadata.size);
+        if let Some((stamp, size)) = self.file_sizes.lock().unwrap().get(rel_path) {
+            if *stamp == host_stamp {
+                return Ok(*size);
+            }
+        }
+        let size = self.open_file(hnode, false)?.len()?;
+        let mut file_sizes = self.file_sizes.lock().unwrap();
+        if file_sizes.len() >= MAX_CACHED_SIZES {
+            file_sizes.clear();
+        }
+        file_sizes.insert(rel_path.to_path_buf(), (host_stamp, size));
+        Ok(size)
+    }
+
+    /// Bind the protected files in the directory to the relative paths under
+    /// `new_dir_path`, before the directory is renamed to it.
+    ///
+    /// If `best_effort` is true, e.g., to roll back a failed rebinding, the
+    /// files failed to rebind are skipped, and the first error is returned
+    /// after all the other files are rebound.
+    fn rebind_dir(&self, dir: &HNode, new_dir_path: &Path, best_effort: bool) -> vfs::Result<()> {
+        let mut result = Ok(());
+        for (host_name, _, type_) in dir.entries()? {
+            // Skip the files not created by this EncFS
+            if self.entry_name(dir, &host_name, type_).is_none() {
+                continue;
+            }
+            let new_path = new_dir_path.join(&host_name);
+            if let Err(e) = self.rebind_entry(dir, &host_name, type_, &new_path, best_effort) {
+                if !best_effort {
+                    return Err(e);
+                }
+                warn!("EncFS: failed to rebind {:?}: {:?}", new_path, e);
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
+    fn rebind_entry(
+        &self,
+        dir: &HNode,
+        host_name: &str,
+        type_: vfs::FileType,
+        new_path: &Path,
+        best_effort: bool,
+    ) -> vfs::Result<()> {
+        match type_ {
+            vfs::FileType::File => {
+                let inner = dir.find(host_name)?;
+                let file = self.open_file(inner.downcast_ref::<HNode>().unwrap(), false)?;
+                file.bind_path(new_path)?;
+                file.flush()
+            }
+            vfs::FileType::Dir => {
+                let inner = dir.find(host_name)?;
+                self.rebind_dir(
+                    inner.downcast_ref::<HNode>().unwrap(),
+                    new_path,
+                    best_effort,
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Forget the cached sizes of the file at the relative path, or the files
+    /// under it.
+    fn forget_file_sizes(&self, path: &Path) {
+        self.file_sizes
+            .lock()
+            .unwrap()
+            .retain(|cached_path, _| !cached_path.starts_with(path));
+    }
+
+    /// Update the relative paths of the opened files after renaming.
+    fn rename_opened_files(&self, old_path: &Path, new_path: &Path) {
+        self.forget_file_sizes(old_path);
+        self.forget_file_sizes(new_path);
+        let mut opened_files = self.opened_files.lock().unwrap();
+        let renamed_paths: Vec<PathBuf> = opened_files
+            .keys()
+            .filter(|path| path.starts_with(old_path))
+            .cloned()
+            .collect();
+        for path in renamed_paths {
+            let file = opened_files.remove(&path).unwrap();
+            let path = new_path.join(path.strip_prefix(old_path).unwrap());
+            opened_files.insert(path, file);
+        }
+    }
+}
+
+impl INode for ENode {
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> vfs::Result<usize> {
+        match self.type_ {
+            vfs::FileType::SymLink => {
+                let target = self.read_link()?;
+                let target = target.as_bytes();
+                let start = target.len().min(offset);
+                let end = target.len().min(offset + buf.len());
+                buf[..end - start].copy_from_slice(&target[start..end]);
+                Ok(end - start)
+            }
+            vfs::FileType::File => self.file()?.read_at(buf, offset),
+            _ => Err(vfs::FsError::NotFile),
+        }
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> vfs::Result<usize> {
+        match self.type_ {
+            vfs::FileType::SymLink => {
+                // The target of the symlink is written at once when it is created
+                if offset != 0 {
+                    return Err(vfs::FsError::InvalidParam);
+                }
+                let target = std::str::from_utf8(buf).map_err(|_| vfs::FsError::InvalidParam)?;
+                let host_target = match self.fs.name_cipher.as_ref() {
+                    Some(cipher) => cipher
+                        .encrypt(target)
+                        .map_err(|_| vfs::FsError::InvalidParam)?,
+                    None => target.to_string(),
+                };
+                self.inner.write_at(0, host_target.as_bytes())?;
+                Ok(buf.len())
+            }
+            vfs::FileType::File => self.file()?.write_at(buf, offset),
+            _ => Err(vfs::FsError::NotFile),
+        }
+    }
+
+    fn metadata(&self) -> vfs::Result<vfs::Metadata> {
+        let mut metadata = self.inner.metadata()?;
+        // The size on the host is the size of the ciphertext
+        match metadata.type_ {
+            vfs::FileType::File => metadata.size = self.file_size(&metadata)?,
+            vfs::FileType::SymLink if self.fs.name_cipher.is_some() => {
+                metadata.size = self.read_link()?.len()
+            }
+            _ => {}
+        }
+        Ok(metadata)
+    }
+
+    fn set_metadata(&self, metadata: &vfs::Metadata) -> vfs::Result<()> {
+        self.inner.set_metadata(metadata)
+    }
+
+    fn sync_all(&self) -> vfs::Result<()> {
+        if let Some(file) = self.file.lock().unwrap().as_ref() {
+            file.flush()?;
+        }
+        self.inner.sync_all()
+    }
+
+    fn sync_data(&self) -> vfs::Result<()> {
+        if let Some(file) = self.file.lock().unwrap().as_ref() {
+            file.flush()?;
+        }
+        self.inner.sync_data()
+    }
+
+    fn resize(&self, len: usize) -> vfs::Result<()> {
+        match self.type_ {
+            vfs::FileType::SymLink => Ok(()),
+            vfs::FileType::File => self.file()?.set_len(len),
+            _ => Err(vfs::FsError::NotFile),
+        }
+    }
+
+    fn create(&self, name: &str, type_: vfs::FileType, mode: u16) -> vfs::Result<Arc<dyn INode>> {
+        let inner = self.inner.create(&self.fs.host_name(name)?, type_, mode)?;
+        let new_node = ENode {
+            inner,
+            type_,
+            file: SgxMutex::new(None),
+            fs: self.fs.clone(),
+        };
+        if type_ == vfs::FileType::File {
+            // Turn the empty host file into a protected file
+            let file = self.fs.open_file(new_node.hnode(), true)?;
+            *new_node.file.lock().unwrap() = Some(file);
+        }
+        Ok(Arc::new(new_node))
+    }
+
+    fn link(&self, name: &str, other: &Arc<dyn INode>) -> vfs::Result<()> {
+        // The name and the path of the protected file are recorded in it, so
+        // a file can not have two names.
+        Err(vfs::FsError::PermError)
+    }
+
+    fn unlink(&self, name: &str) -> vfs::Result<()> {
+        let host_name = self.fs.host_name(name)?;
+        self.find_host(&host_name)?;
+        self.inner.unlink(&host_name)?;
+        // The unlinked file is still usable for the ENodes opened it
+        let path = self.hnode().rel_path().join(&host_name);
+        self.fs.opened_files.lock().unwrap().remove(&path);
+        self.fs.forget_file_sizes(&path);
+        Ok(())
+    }
+
+    fn move_(&self, old_name: &str, target: &Arc<dyn INode>, new_name: &str) -> vfs::Result<()> {
+        let target = target
+            .downcast_ref::<Self>()
+            .ok_or(vfs::FsError::NotSameFs)?;
+        if !Arc::ptr_eq(&self.fs, &target.fs) {
+            return Err(vfs::FsError::NotSameFs);
+        }
+        let old_host_name = self.fs.host_name(old_name)?;
+        let new_host_name = self.fs.host_name(new_name)?;
+        let old_path = self.hnode().rel_path().join(&old_host_name);
+        let new_path = target.hnode().rel_path().join(&new_host_name);
+
+        let old_inner = self.find_host(&old_host_name)?;
+        let old_hnode = old_inner.downcast_ref::<HNode>().unwrap();
+        let old_type = old_inner.metadata()?.type_;
+        // The name and the path recorded in the protected files must be
+        // changed as well
+        let file = match old_type {
+            vfs::FileType::File => {
+                let file = self.fs.open_file(old_hnode, false)?;
+                file.rename_meta(&old_host_name, &new_host_name)?;
+                file.bind_path(&new_path)?;
+                file.flush()?;
+                Some(file)
+            }
+            vfs::FileType::Dir => {
+                if let Err(e) = self.fs.rebind_dir(old_hnode, &new_path, false) {
+                    let _ = self.fs.rebind_dir(old_hnode, &old_path, true);
+                    return Err(e);
+                }
+                None
+            }
+            _ => None,
+        };
+        if let Err(e) = self
+            .inner
+            .move_(&old_host_name, &target.inner, &new_host_name)
+        {
+            // Roll back as much as possible, and return the error of moving
+            match file {
+                Some(file) => {
+                    let rollback = file
+                        .rename_meta(&new_host_name, &old_host_name)
+                        .and_then(|_| file.bind_path(&old_path))
+                        .and_then(|_| file.flush());
+                    if let Err(rollback_e) = rollback {
+                        warn!("EncFS: failed to rebind {:?}: {:?}", old_path, rollback_e);
+                    }
+                }
+                None if old_type == vfs::FileType::Dir => {
+                    let _ = self.fs.rebind_dir(old_hnode, &old_path, true);
+                }
+                None => {}
+            }
+            return Err(e);
+        }
+        self.fs.rename_opened_files(&old_path, &new_path);
+        Ok(())
+    }
+
+    fn find(&self, name: &str) -> vfs::Result<Arc<dyn INode>> {
+        let inner = match name {
+            "." | ".." => self.inner.find(name)?,
+            name => self.find_host(&self.fs.host_name(name)?)?,
+        };
+        let type_ = inner.metadata()?.type_;
+        Ok(Arc::new(ENode {
+            inner,
+            type_,
+            file: SgxMutex::new(None),
+            fs: self.fs.clone(),
+        }))
+    }
+
+    fn get_entry(&self, id: usize) -> vfs::Result<String> {
+        self.entries()?
+            .into_iter()
+            .nth(id)
+            .map(|(name, _, _)| name)
+            .ok_or(vfs::FsError::EntryNotFound)
+    }
+
+    fn iterate_entries(&self, ctx: &mut vfs::DirentWriterContext) -> vfs::Result<usize> {
+        let idx = ctx.pos();
+        for (name, ino, type_) in self.entries()?.into_iter().skip(idx) {
+            if let Err(e) = ctx.write_entry(&name, ino, type_) {
+                if ctx.written_len() == 0 {
+                    return Err(e);
+                } else {
+                    break;
+                }
+            };
+        }
+        Ok(ctx.written_len())
+    }
+
+    fn io_control(&self, cmd: u32, data: usize) -> vfs::Result<()> {
+        warn!("EncFS: io_control is unimplemented");
+        Ok(())
+    }
+
+    fn fs(&self) -> Arc<dyn vfs::FileSystem> {
+        self.fs.clone()
+    }
+
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl ENode {
+    fn hnode(&self) -> &HNode {
+        self.inner.downcast_ref::<HNode>().unwrap()
+    }
+
+    /// Get the info of the host file system, see `HNode::fs_info`.
+    pub fn fs_info(&self) -> Result<vfs::FsInfo> {
+        self.hnode().fs_info()
+    }
+
+    /// Find the host entry created by this EncFS in the directory.
+    fn find_host(&self, host_name: &str) -> vfs::Result<Arc<dyn INode>> {
+        let inner = self.inner.find(host_name)?;
+        let type_ = inner.metadata()?.type_;
+        if self.fs.entry_name(self.hnode(), host_name, type_).is_none() {
+            return Err(vfs::FsError::EntryNotFound);
+        }
+        Ok(inner)
+    }
+
+    /// Get the protected file, open it if not opened.
+    fn file(&self) -> vfs::Result<Arc<EncFile>> {
+        let mut file = self.file.lock().unwrap();
+        if file.is_none() {
+            *file = Some(self.fs.open_file(self.hnode(), false)?);
+        }
+        Ok(file.as_ref().unwrap().clone())
+    }
+
+    /// Get the size of the protected file without opening it if possible.
+    fn file_size(&self, host_metadata: &vfs::Metadata) -> vfs::Result<usize> {
+        if let Some(file) = self.file.lock().unwrap().as_ref() {
+            return file.len();
+        }
+        self.fs.file_size(self.hnode(), host_metadata)
+    }
+
+    /// Read the target of the symlink.
+    fn read_link(&self) -> vfs::Result<String> {
+        let mut buf = vec![0u8; PATH_MAX];
+        let len = self.inner.read_at(0, &mut buf)?;
+        let host_target =
+            std::str::from_utf8(&buf[..len]).map_err(|_| vfs::FsError::InvalidParam)?;
+        match self.fs.name_cipher.as_ref() {
+            Some(cipher) => cipher.decrypt(host_target).ok_or(vfs::FsError::NoIntegrity),
+            None => Ok(host_target.to_string()),
+        }
+    }
+
+    /// Read all the entries of the directory, except "." and "..".
+    ///
+    /// The entries not created by this EncFS are skipped.
+    fn entries(&self) -> vfs::Result<Vec<(String, u64, vfs::FileType)>> {
+        let entries = self
+            .hnode()
+            .entries()?
+            .into_iter()
+            .filter_map(|(host_name, ino, type_)| {
+                self.fs
+                    .entry_name(self.hnode(), &host_name, type_)
+                    .map(|name| (name, ino, type_))
+            })
+            .collect();
+        Ok(entries)
+    }
+}
+
+impl EncFile {
+    fn read_at(&self, buf: &mut [u8], offset: usize) -> vfs::Result<usize> {
+        Ok(self.file.read_at(buf, PATH_HASH_LEN + offset)?)
+    }
+
+    fn write_at(&self, buf: &[u8], offset: usize) -> vfs::Result<usize> {
+        Ok(self.file.write_at(buf, PATH_HASH_LEN + offset)?)
+    }
+
+    fn len(&self) -> vfs::Result<usize> {
+        Ok(self.file.len()?.saturating_sub(PATH_HASH_LEN))
+    }
+
+    fn set_len(&self, len: usize) -> vfs::Result<()> {
+        Ok(self.file.set_len(PATH_HASH_LEN + len)?)
+    }
+
+    fn flush(&self) -> vfs::Result<()> {
+        Ok(self.file.flush()?)
+    }
+
+    fn rename_meta(&self, old_name: &str, new_name: &str) -> vfs::Result<()> {
+        Ok(self.file.rename_meta(old_name, new_name)?)
+    }
+
+    /// Read the hash of the path the file is bound to.
+    fn path_hash(&self) -> vfs::Result<[u8; PATH_HASH_LEN]> {
+        let mut path_hash = [0u8; PATH_HASH_LEN];
+        if self.file.read_at(&mut path_hash, 0)? != PATH_HASH_LEN {
+            return Err(vfs::FsError::NoIntegrity);
+        }
+        Ok(path_hash)
+    }
+
+    /// Bind the file to the relative path on the host.
+    fn bind_path(&self, rel_path: &Path) -> vfs::Result<()> {
+        self.file.write_at(&hash_path(rel_path)?, 0)?;
+        Ok(())
+    }
+}
+
+/// Whether the host file is a protected file.
+///
+/// Only the magic number is checked, so a tampered protected file is still
+/// reported when it is opened.
+fn is_protected_file(hnode: &HNode) -> bool {
+    let mut file_id = [0u8; 8];
+    match hnode.read_at(0, &mut file_id) {
+        Ok(len) => len == file_id.len() && u64::from_le_bytes(file_id) == SGX_FILE_ID,
+        Err(_) => false,
+    }
+}
+
+fn hash_path(rel_path: &Path) -> vfs::Result<[u8; PATH_HASH_LEN]> {
+    sgx_tcrypto::rsgx_sha256_slice(rel_path.to_string_lossy().as_bytes())
+        .map_err(|_| vfs::FsError::InvalidParam)
+}
+
+/// Get the sealing key to obfuscate the names.
+///
+/// The key is derived from MRSIGNER with the lowest SVNs, so it remains the
+/// same after the enclave is upgraded.
+fn get_name_sealing_key() -> Result<sgx_key_128bit_t> {
+    let key_id = {
+        let hash = sgx_tcrypto::rsgx_sha256_slice(b"Occlum EncFS name key")
+            .map_err(|_| errno!(EINVAL, "failed to calculate SHA256"))?;
+        sgx_key_id_t { id: hash }
+    };
+    let key_request = sgx_key_request_t {
+        key_name: SGX_KEYSELECT_SEAL,
+        key_policy: SGX_KEYPOLICY_MRSIGNER,
+        attribute_mask: sgx_attributes_t {
+            flags: TSEAL_DEFAULT_FLAGSMASK,
+            xfrm: 0,
+        },
+        misc_mask: TSEAL_DEFAULT_MISCMASK,
+        key_id,
+        ..Default::default()
+    };
+    get_key(&key_request)
+}
@@ -0,0 +1,101 @@
+//! The cipher to obfuscate the names on the host.
+//!
+//! A name is encrypted with AES-GCM, whose IV is derived from the HMAC-SHA256
+//! of the name. So the same name is always encrypted to the same one, which
+//! can be looked up on the host directly, while the encrypted names can still
+//! be decrypted to list the directories.
+use super::*;
+use crate::util::base64;
+use sgx_tcrypto::{rsgx_rijndael128GCM_decrypt, rsgx_rijndael128GCM_encrypt, rsgx_sha256_slice};
+
+const IV_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+pub struct NameCipher {
+    mac_key: [u8; 32],
+    enc_key: sgx_aes_gcm_128bit_key_t,
+}
+
+impl NameCipher {
+    pub fn new(key: &sgx_key_128bit_t) -> Result<Self> {
+        let mac_key = hmac_sha256(key, b"encfs name mac key")?;
+        let enc_key = {
+            let mut enc_key = sgx_aes_gcm_128bit_key_t::default();
+            let hash = hmac_sha256(key, b"encfs name enc key")?;
+            enc_key.copy_from_slice(&hash[..enc_key.len()]);
+            enc_key
+        };
+        Ok(Self { mac_key, enc_key })
+    }
+
+    pub fn encrypt(&self, name: &str) -> Result<String> {
+        let iv = self.synthetic_iv(name.as_bytes())?;
+        let mut ciphertext = vec![0u8; name.len()];
+        let mut tag = sgx_aes_gcm_128bit_tag_t::default();
+        rsgx_rijndael128GCM_encrypt(
+            &self.enc_key,
+            name.as_bytes(),
+            &iv,
+            &[],
+            &mut ciphertext,
+            &mut tag,
+        )
+        .map_err(|_| errno!(EINVAL, "failed to encrypt the name"))?;
+
+        let mut blob = Vec::with_capacity(IV_LEN + ciphertext.len() + TAG_LEN);
+        blob.extend_from_slice(&iv);
+        blob.extend_from_slice(&ciphertext);
+        blob.extend_from_slice(&tag);
+        Ok(base64::encode_url_safe(&blob))
+    }
+
+    /// Decrypt the name, return `None` if it is not encrypted by this cipher.
+    pub fn decrypt(&self, enc_name: &str) -> Option<String> {
+        let blob = base64::decode_url_safe(enc_name)?;
+        if blob.len() <= IV_LEN + TAG_LEN {
+            return None;
+        }
+        let (iv, rest) = blob.split_at(IV_LEN);
+        let (ciphertext, tag) = rest.split_at(rest.len() - TAG_LEN);
+        let tag: sgx_aes_gcm_128bit_tag_t = tag.try_into().ok()?;
+        let mut plaintext = vec![0u8; ciphertext.len()];
+        rsgx_rijndael128GCM_decrypt(&self.enc_key, ciphertext, iv, &[], &tag, &mut plaintext)
+            .ok()?;
+        // The IV must be the one derived from the name
+        if self.synthetic_iv(&plaintext).ok()?[..] != iv[..] {
+            return None;
+        }
+        String::from_utf8(plaintext).ok()
+    }
+
+    fn synthetic_iv(&self, name: &[u8]) -> Result<[u8; IV_LEN]> {
+        let mac = hmac_sha256(&self.mac_key, name)?;
+        let mut iv = [0u8; IV_LEN];
+        iv.copy_from_slice(&mac[..IV_LEN]);
+        Ok(iv)
+    }
+}
+
+fn hmac_sha256(key: &[u8], msg: &[u8]) -> Result<[u8; 32]> {
+    const BLOCK_SIZE: usize = 64;
+
+    let sha256 = |data: &[u8]| {
+        rsgx_sha256_slice(data).map_err(|_| errno!(EINVAL, "failed to calculate SHA256"))
+    };
+    let mut key_block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        key_block[..32].copy_from_slice(&sha256(key)?);
+    } else {
+        key_block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Vec::with_capacity(BLOCK_SIZE + msg.len());
+    inner.extend(key_block.iter().map(|b| b ^ 0x36));
+    inner.extend_from_slice(msg);
+    let inner_hash = sha256(&inner)?;
+
+    let mut outer = Vec::with_capacity(BLOCK_SIZE + inner_hash.len());
+    outer.extend(key_block.iter().map(|b| b ^ 0x5c));
+    outer.extend_from_slice(&inner_hash);
+    sha256(&outer)
+}
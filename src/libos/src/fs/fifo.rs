@@ -0,0 +1,375 @@
+//! Named pipes (FIFOs).
+//!
+//! A FIFO is an inode of type `FileType::NamedPipe` in a filesystem. All the
+//! files opened from the same FIFO inode attach to a single pipe, which is
+//! kept in the extension of the inode. The pipe is created when the FIFO is
+//! opened for the first time and released when the last file is closed.
+use atomic::{Atomic, Ordering};
+use rcore_fs::vfs::AnyExt;
+
+use super::channel::{Channel, Consumer, Producer};
+use super::pipe::{resize_pipe, PIPE_BUF_SIZE};
+use super::*;
+use crate::events::{Waiter, WaiterQueue};
+
+/// The state of a FIFO shared by all the files opened from the FIFO inode.
+pub struct Fifo {
+    inner: SgxMutex<FifoInner>,
+    // Threads blocked in opening the FIFO, waiting for their peers
+    open_waiters: WaiterQueue,
+}
+
+struct FifoInner {
+    readers: usize,
+    writers: usize,
+    // The number of times that the FIFO has been opened for read (write).
+    // A blocking open compares the counters to tell whether a peer has
+    // opened the FIFO while it was sleeping, even if the peer has closed
+    // the FIFO before the sleeping thread is waken up.
+    read_opens: usize,
+    write_opens: usize,
+    pipe: Option<(Arc<Producer<u8>>, Arc<Consumer<u8>>)>,
+}
+
+impl Fifo {
+    pub fn new() -> Self {
+        Self {
+            inner: SgxMutex::new(FifoInner {
+                readers: 0,
+                writers: 0,
+                read_opens: 0,
+                write_opens: 0,
+                pipe: None,
+            }),
+            open_waiters: WaiterQueue::new(),
+        }
+    }
+
+    /// Attach a new opened file with the access mode to the pipe.
+    ///
+    /// Return the endpoints of the pipe that the file should use.
+    fn attach(&self, access_mode: &AccessMode) -> Result<(Arc<Producer<u8>>, Arc<Consumer<u8>>)> {
+        let endpoints = {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.pipe.is_none() {
+                let (producer, consumer) = Channel::new(PIPE_BUF_SIZE)?.split();
+                inner.pipe = Some((Arc::new(producer), Arc::new(consumer)));
+            }
+            if access_mode.readable() {
+                inner.readers += 1;
+                inner.read_opens += 1;
+            }
+            if access_mode.writable() {
+                inner.writers += 1;
+                inner.write_opens += 1;
+            }
+            inner.update_pipe_state();
+            inner.pipe.clone().unwrap()
+        };
+        self.open_waiters.dequeue_and_wake_all();
+        Ok(endpoints)
+    }
+
+    /// Detach a closed file with the access mode from the pipe.
+    fn detach(&self, access_mode: &AccessMode) {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if access_mode.readable() {
+                inner.readers -= 1;
+            }
+            if access_mode.writable() {
+                inner.writers -= 1;
+            }
+            if inner.readers == 0 && inner.writers == 0 {
+                // The data in the pipe are discarded once all files are closed
+                inner.pipe = None;
+            } else {
+                inner.update_pipe_state();
+            }
+        }
+        self.open_waiters.dequeue_and_wake_all();
+    }
+
+    /// Block the current thread until the FIFO is opened by a peer.
+    ///
+    /// The peer is a writer if `is_reader` is true, otherwise a reader.
+    fn wait_for_peer(&self, is_reader: bool) -> Result<()> {
+        let initial_opens = {
+            let inner = self.inner.lock().unwrap();
+            inner.peer_opens(is_reader)
+        };
+        let has_peer = || {
+            let inner = self.inner.lock().unwrap();
+            inner.peer_count(is_reader) > 0 || inner.peer_opens(is_reader) != initial_opens
+        };
+
+        if has_peer() {
+            return Ok(());
+        }
+        let waiter = Waiter::new();
+        loop {
+            self.open_waiters.reset_and_enqueue(&waiter);
+            if has_peer() {
+                return Ok(());
+            }
+            waiter.wait(None)?;
+        }
+    }
+
+    fn has_readers(&self) -> bool {
+        self.inner.lock().unwrap().readers > 0
+    }
+}
+
+impl FifoInner {
+    // Shutdown the endpoints that have no files attached, so that the peers
+    // get EOF or EPIPE, and reopen the endpoints that have files attached again.
+    fn update_pipe_state(&self) {
+        let (producer, consumer) = match &self.pipe {
+            Some(pipe) => pipe,
+            None => return,
+        };
+        if self.writers == 0 {
+            producer.shutdown();
+        } else {
+            producer.reopen();
+        }
+        if self.readers == 0 {
+            consumer.shutdown();
+        } else {
+            consumer.reopen();
+        }
+    }
+
+    fn peer_count(&self, is_reader: bool) -> usize {
+        if is_reader {
+            self.writers
+        } else {
+            self.readers
+        }
+    }
+
+    fn peer_opens(&self, is_reader: bool) -> usize {
+        if is_reader {
+            self.write_opens
+        } else {
+            self.read_opens
+        }
+    }
+}
+
+impl Default for Fifo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnyExt for Fifo {}
+
+/// A file opened from a FIFO inode.
+pub struct FifoFile {
+    inode: Arc<dyn INode>,
+    abs_path: String,
+    fifo: Arc<Fifo>,
+    producer: Arc<Producer<u8>>,
+    consumer: Arc<Consumer<u8>>,
+    access_mode: AccessMode,
+    status_flags: Atomic<StatusFlags>,
+}
+
+impl FifoFile {
+    /// Open a FIFO inode, following the POSIX semantics.
+    ///
+    /// Opening a FIFO for read only (write only) blocks until the FIFO is opened
+    /// for write (read) by a peer, unless `O_NONBLOCK` is given. Opening a FIFO
+    /// for write only with `O_NONBLOCK` fails with `ENXIO` if there is no reader.
+    /// Opening a FIFO for both read and write never blocks.
+    pub fn open(inode: Arc<dyn INode>, abs_path: &str, flags: u32) -> Result<Self> {
+        let access_mode = AccessMode::from_u32(flags)?;
+        if access_mode.readable() && !inode.allow_read()? {
+            return_errno!(EACCES, "File not readable");
+        }
+        if access_mode.writable() && !inode.allow_write()? {
+            return_errno!(EACCES, "File not writable");
+        }
+        let status_flags = StatusFlags::from_bits_truncate(flags) & STATUS_FLAGS_MASK;
+        let is_nonblocking = status_flags.contains(StatusFlags::O_NONBLOCK);
+
+        let fifo = {
+            let ext = inode
+                .ext()
+                .ok_or_else(|| errno!(EOPNOTSUPP, "the filesystem does not support FIFO"))?;
+            match ext.get::<Fifo>() {
+                Some(fifo) => fifo,
+                None => ext.get_or_put_default::<Fifo>(),
+            }
+        };
+
+        if let AccessMode::O_WRONLY = access_mode {
+            if is_nonblocking && !fifo.has_readers() {
+                return_errno!(ENXIO, "no reader of the FIFO");
+            }
+        }
+        let (producer, consumer) = fifo.attach(&access_mode)?;
+        let file = Self {
+            inode,
+            abs_path: abs_path.to_owned(),
+            fifo,
+            producer,
+            consumer,
+            access_mode,
+            status_flags: Atomic::new(status_flags),
+        };
+
+        // If the waiting is interrupted, the file is dropped and thus detached
+        match file.access_mode {
+            AccessMode::O_RDONLY if !is_nonblocking => file.fifo.wait_for_peer(true)?,
+            AccessMode::O_WRONLY => file.fifo.wait_for_peer(false)?,
+            _ => {}
+        }
+        Ok(file)
+    }
+
+    pub fn inode(&self) -> &Arc<dyn INode> {
+        &self.inode
+    }
+
+    pub fn abs_path(&self) -> &str {
+        &self.abs_path
+    }
+
+    /// Get the buffer size of the pipe shared by the files of the FIFO.
+    pub fn pipe_size(&self) -> usize {
+        self.consumer.capacity()
+    }
+
+    /// Set the buffer size of the pipe shared by the files of the FIFO.
+    pub fn set_pipe_size(&self, size: usize) -> Result<usize> {
+        resize_pipe(&self.producer, &self.consumer, size)
+    }
+
+    fn is_nonblocking(&self) -> bool {
+        self.status_flags
+            .load(Ordering::Acquire)
+            .contains(StatusFlags::O_NONBLOCK)
+    }
+}
+
+impl File for FifoFile {
+    fn read(&self, buf: &mut [u8]) -> Result<usize> {
+        self.readv(&mut [buf])
+    }
+
+    fn readv(&self, bufs: &mut [&mut [u8]]) -> Result<usize> {
+        if !self.access_mode.readable() {
+            return_errno!(EBADF, "File not readable");
+        }
+        if self.is_nonblocking() {
+            self.consumer.try_pop_slices(bufs)
+        } else {
+            self.consumer.pop_slices(bufs)
+        }
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize> {
+        self.writev(&[buf])
+    }
+
+    fn writev(&self, bufs: &[&[u8]]) -> Result<usize> {
+        if !self.access_mode.writable() {
+            return_errno!(EBADF, "File not writable");
+        }
+        if self.is_nonblocking() {
+            self.producer.try_push_slices(bufs)
+        } else {
+            self.producer.push_slices(bufs)
+        }
+    }
+
+    fn seek(&self, pos: SeekFrom) -> Result<off_t> {
+        return_errno!(ESPIPE, "FIFO does not support seek")
+    }
+
+    fn metadata(&self) -> Result<Metadata> {
+        let metadata = self.inode.metadata()?;
+        Ok(metadata)
+    }
+
+    fn set_metadata(&self, metadata: &Metadata) -> Result<()> {
+        self.inode.set_metadata(metadata)?;
+        Ok(())
+    }
+
+    fn access_mode(&self) -> Result<AccessMode> {
+        Ok(self.access_mode.clone())
+    }
+
+    fn status_flags(&self) -> Result<StatusFlags> {
+        Ok(self.status_flags.load(Ordering::Acquire))
+    }
+
+    fn set_status_flags(&self, new_status_flags: StatusFlags) -> Result<()> {
+        self.status_flags
+            .store(new_status_flags & STATUS_FLAGS_MASK, Ordering::Release);
+        Ok(())
+    }
+
+    fn poll_new(&self) -> IoEvents {
+        let mut events = IoEvents::empty();
+        if self.access_mode.readable() {
+            events |= self.consumer.poll();
+        }
+        if self.access_mode.writable() {
+            events |= self.producer.poll();
+        }
+        events
+    }
+
+    fn notifier(&self) -> Option<&IoNotifier> {
+        // TODO: broadcast the events of both endpoints for a FIFO opened for
+        // read and write.
+        if self.access_mode.readable() {
+            Some(self.consumer.notifier())
+        } else {
+            Some(self.producer.notifier())
+        }
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<i32> {
+        match cmd {
+            IoctlCmd::TCGETS(_) => return_errno!(ENOTTY, "not tty device"),
+            IoctlCmd::TCSETS(_) => return_errno!(ENOTTY, "not tty device"),
+            IoctlCmd::FIONREAD(arg) => {
+                let ready_len = self.consumer.ready_len().min(std::i32::MAX as usize) as i32;
+                **arg = ready_len;
+                return Ok(0);
+            }
+            _ => return_errno!(ENOSYS, "not supported"),
+        };
+        unreachable!();
+    }
+
+    fn fs(&self) -> Result<Arc<dyn FileSystem>> {
+        Ok(self.inode.fs())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Drop for FifoFile {
+    fn drop(&mut self) {
+        self.fifo.detach(&self.access_mode);
+    }
+}
+
+impl Debug for FifoFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FifoFile")
+            .field("abs_path", &self.abs_path)
+            .field("access_mode", &self.access_mode)
+            .field("status_flags", &self.status_flags)
+            .finish()
+    }
+}
@@ -0,0 +1,99 @@
+use super::*;
+
+pub fn do_fadvise(fd: FileDesc, offset: usize, len: usize, advice: FadviseAdvice) -> Result<()> {
+    debug!(
+        "fadvise: fd: {}, offset: {}, len: {}, advice: {:?}",
+        fd, offset, len, advice
+    );
+    let file_ref = current!().file(fd)?;
+    let inode_file = file_ref
+        .as_inode_file()
+        .map_err(|_| errno!(ESPIPE, "not a regular file or directory"))?;
+    match advice {
+        FadviseAdvice::WillNeed => prefetch(inode_file.inode(), offset, len)?,
+        // The other advices are only hints for the page cache, which the
+        // file systems in LibOS do not have.
+        _ => {}
+    }
+    Ok(())
+}
+
+pub fn do_readahead(fd: FileDesc, offset: usize, count: usize) -> Result<()> {
+    debug!(
+        "readahead: fd: {}, offset: {}, count: {}",
+        fd, offset, count
+    );
+    let file_ref = current!().file(fd)?;
+    if !file_ref.access_mode()?.readable() {
+        return_errno!(EBADF, "file is not readable");
+    }
+    let inode_file = file_ref
+        .as_inode_file()
+        .map_err(|_| errno!(EINVAL, "not a regular file"))?;
+    if inode_file.inode().metadata()?.type_ != FileType::File {
+        return_errno!(EINVAL, "not a regular file");
+    }
+    // Zero count reads nothing, unlike the zero len of fadvise
+    if count == 0 {
+        return Ok(());
+    }
+    prefetch(inode_file.inode(), offset, count)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(i32)]
+pub enum FadviseAdvice {
+    Normal = 0,
+    Random = 1,
+    Sequential = 2,
+    WillNeed = 3,
+    DontNeed = 4,
+    NoReuse = 5,
+}
+
+impl FadviseAdvice {
+    pub fn from_i32(raw: i32) -> Result<Self> {
+        Ok(match raw {
+            0 => Self::Normal,
+            1 => Self::Random,
+            2 => Self::Sequential,
+            3 => Self::WillNeed,
+            4 => Self::DontNeed,
+            5 => Self::NoReuse,
+            _ => return_errno!(EINVAL, "invalid advice"),
+        })
+    }
+}
+
+/// Read the range of the regular file ahead, so that the following reads hit
+/// the cache of the file system, e.g., the cache of SGX protected files in SEFS.
+///
+/// The zero `len` means to the end of the file.
+fn prefetch(inode: &Arc<dyn INode>, offset: usize, len: usize) -> Result<()> {
+    // The cache of the protected file is limited, reading more is in vain
+    const MAX_PREFETCH_SIZE: usize = 2 * 1024 * 1024;
+    const BUF_SIZE: usize = 64 * 1024;
+
+    let metadata = inode.metadata()?;
+    if metadata.type_ != FileType::File || offset >= metadata.size {
+        return Ok(());
+    }
+    let end = if len == 0 {
+        metadata.size
+    } else {
+        offset.saturating_add(len).min(metadata.size)
+    };
+    let end = end.min(offset + MAX_PREFETCH_SIZE);
+
+    let mut buf = vec![0u8; BUF_SIZE.min(end - offset)];
+    let mut pos = offset;
+    while pos < end {
+        let read_len = buf.len().min(end - pos);
+        let len = inode.read_at(pos, &mut buf[..read_len])?;
+        if len == 0 {
+            break;
+        }
+        pos += len;
+    }
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use super::locks::range_lock::c_flock;
 use super::*;
+use crate::fs::pipe::{do_get_pipe_size, do_set_pipe_size};
 use util::mem_util::from_user;
 
 #[derive(Debug)]
@@ -24,8 +25,15 @@ pub enum FcntlCmd<'a> {
     SetLk(&'a c_flock),
     /// The blocking version of SetLK
     SetLkWait(&'a c_flock),
+    /// Get the buffer size of a pipe
+    GetPipeSz(),
+    /// Set the buffer size of a pipe
+    SetPipeSz(usize),
 }
 
+const F_SETPIPE_SZ: c_int = 1031;
+const F_GETPIPE_SZ: c_int = 1032;
+
 impl<'a> FcntlCmd<'a> {
     #[deny(unreachable_patterns)]
     pub fn from_raw(cmd: u32, arg: u64) -> Result<FcntlCmd<'a>> {
@@ -54,6 +62,13 @@ impl<'a> FcntlCmd<'a> {
                 let lock_c = unsafe { &*lock_ptr };
                 FcntlCmd::SetLkWait(lock_c)
             }
+            F_GETPIPE_SZ => FcntlCmd::GetPipeSz(),
+            F_SETPIPE_SZ => {
+                if arg as c_int <= 0 {
+                    return_errno!(EINVAL, "invalid pipe size");
+                }
+                FcntlCmd::SetPipeSz(arg as c_int as usize)
+            }
             _ => return_errno!(EINVAL, "unsupported command"),
         })
     }
@@ -135,6 +150,14 @@ pub fn do_fcntl(fd: FileDesc, cmd: &mut FcntlCmd) -> Result<isize> {
             file.set_advisory_lock(&lock, is_nonblocking)?;
             0
         }
+        FcntlCmd::GetPipeSz() => {
+            let file = file_table.get(fd)?;
+            do_get_pipe_size(&file)? as isize
+        }
+        FcntlCmd::SetPipeSz(size) => {
+            let file = file_table.get(fd)?;
+            do_set_pipe_size(&file, *size)? as isize
+        }
     };
     Ok(ret)
 }
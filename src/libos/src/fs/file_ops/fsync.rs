@@ -16,6 +16,45 @@ pub fn do_fdatasync(fd: FileDesc) -> Result<()> {
     Ok(())
 }
 
+pub fn do_sync_file_range(
+    fd: FileDesc,
+    offset: usize,
+    nbytes: usize,
+    flags: SyncFileRangeFlags,
+) -> Result<()> {
+    debug!(
+        "sync_file_range: fd: {}, offset: {}, nbytes: {}, flags: {:?}",
+        fd, offset, nbytes, flags
+    );
+    let file_ref = current!().file(fd)?;
+    if file_ref.as_inode_file().is_err() {
+        return_errno!(ESPIPE, "not a regular file or directory");
+    }
+    // There is no writeback in progress to wait for, only the write is needed
+    if !flags.contains(SyncFileRangeFlags::SYNC_FILE_RANGE_WRITE) {
+        return Ok(());
+    }
+    // The SGX protected file can not be flushed partially, so the
+    // whole file is flushed to make the range durable.
+    flush_vm_backed_by(&file_ref);
+    file_ref.sync_data()?;
+    Ok(())
+}
+
+bitflags! {
+    pub struct SyncFileRangeFlags: u32 {
+        const SYNC_FILE_RANGE_WAIT_BEFORE = 1;
+        const SYNC_FILE_RANGE_WRITE = 2;
+        const SYNC_FILE_RANGE_WAIT_AFTER = 4;
+    }
+}
+
+impl SyncFileRangeFlags {
+    pub fn from_u32(raw: u32) -> Result<Self> {
+        Self::from_bits(raw).ok_or_else(|| errno!(EINVAL, "invalid flags"))
+    }
+}
+
 fn flush_vm_backed_by(file: &FileRef) {
     current!().vm().msync_by_file(file);
 }
@@ -29,6 +29,16 @@ impl_ioctl_nums_and_cmds! {
     // Get terminal attributes
     TCGETS => (0x5401, mut KernelTermios), // ignore
     TCSETS => (0x5402, KernelTermios),
+    // Set terminal attributes after all output has been transmitted
+    TCSETSW => (0x5403, KernelTermios),
+    // Set terminal attributes after flushing the pending input
+    TCSETSF => (0x5404, KernelTermios),
+    // Make the given terminal the controlling terminal of the calling process
+    TIOCSCTTY => (0x540E, ()),
+    // Get the foreground process group ID of the terminal
+    TIOCGPGRP => (0x540F, mut i32),
+    // Set the foreground process group ID of the terminal
+    TIOCSPGRP => (0x5410, i32),
     // Get window size
     TIOCGWINSZ => (0x5413, mut WinSize),
     // Set window size
@@ -46,6 +56,10 @@ impl_ioctl_nums_and_cmds! {
     FIONCLEX => (0x5450, ()),
     // Set close on exec
     FIOCLEX => (0x5451, ()),
+    // Get the index of the pseudo-terminal
+    TIOCGPTN => (0x80045430, mut u32),
+    // Lock or unlock the slave of the pseudo-terminal
+    TIOCSPTLCK => (0x40045431, i32),
     // Low-level access to Linux network devices on man7/netdevice.7
     // Only non-privileged operations are supported for now
     SIOCGIFNAME => (0x8910, mut IfReq),
@@ -0,0 +1,45 @@
+use super::*;
+
+// The file type bits of the mode of mknod
+const S_IFMT: u32 = 0o170000;
+const S_IFSOCK: u32 = 0o140000;
+const S_IFREG: u32 = 0o100000;
+const S_IFBLK: u32 = 0o060000;
+const S_IFCHR: u32 = 0o020000;
+const S_IFIFO: u32 = 0o010000;
+
+pub fn do_mknodat(fs_path: &FsPath, mode: u32, dev: u64) -> Result<()> {
+    debug!(
+        "mknodat: fs_path: {:?}, mode: {:#o}, dev: {:#x}",
+        fs_path, mode, dev
+    );
+
+    let type_ = match mode & S_IFMT {
+        // A zero file type is equivalent to S_IFREG
+        0 | S_IFREG => FileType::File,
+        S_IFIFO => FileType::NamedPipe,
+        S_IFCHR | S_IFBLK => return_errno!(EPERM, "device files cannot be created"),
+        S_IFSOCK => return_errno!(EPERM, "socket files cannot be created"),
+        _ => return_errno!(EINVAL, "invalid file type"),
+    };
+
+    let path = fs_path.to_abs_path()?;
+    let (dir_path, file_name) = split_path(&path);
+    if file_name.ends_with('/') {
+        return_errno!(EISDIR, "path refers to a directory");
+    }
+    let current = current!();
+    let dir_inode = {
+        let fs = current.fs().read().unwrap();
+        fs.lookup_inode(dir_path)?
+    };
+    if dir_inode.find(file_name).is_ok() {
+        return_errno!(EEXIST, "file exists");
+    }
+    if !dir_inode.allow_write()? {
+        return_errno!(EPERM, "file cannot be created");
+    }
+    let masked_mode = FileMode::from_bits_truncate(mode as u16) & !current.process().umask();
+    dir_inode.create(file_name, type_, masked_mode.bits())?;
+    Ok(())
+}
@@ -6,20 +6,22 @@ pub use self::chmod::{do_fchmod, do_fchmodat, FileMode};
 pub use self::chown::{do_fchown, do_fchownat, ChownFlags};
 pub use self::close::do_close;
 pub use self::dup::{do_dup, do_dup2, do_dup3};
+pub use self::fadvise::{do_fadvise, do_readahead, FadviseAdvice};
 pub use self::fallocate::{do_fallocate, FallocateFlags};
 pub use self::fcntl::{do_fcntl, FcntlCmd};
 pub use self::file_flags::{AccessMode, CreationFlags, StatusFlags, STATUS_FLAGS_MASK};
 pub use self::flock::do_flock;
 pub use self::fspath::{get_abs_path_by_fd, FsPath, AT_FDCWD};
-pub use self::fsync::{do_fdatasync, do_fsync};
+pub use self::fsync::{do_fdatasync, do_fsync, do_sync_file_range, SyncFileRangeFlags};
 pub use self::getdents::{do_getdents, do_getdents64};
 pub use self::ioctl::{
-    do_ioctl, occlum_ocall_ioctl, BuiltinIoctlNum, IfConf, IoctlCmd, StructuredIoctlArgType,
-    StructuredIoctlNum,
+    do_ioctl, occlum_ocall_ioctl, BuiltinIoctlNum, IfConf, IoctlCmd, KernelTermios,
+    StructuredIoctlArgType, StructuredIoctlNum, WinSize,
 };
 pub use self::link::{do_linkat, LinkFlags};
 pub use self::lseek::do_lseek;
 pub use self::mkdir::do_mkdirat;
+pub use self::mknod::do_mknodat;
 pub use self::open::do_openat;
 pub use self::read::{do_pread, do_preadv, do_read, do_readv};
 pub use self::rename::do_renameat;
@@ -39,6 +41,7 @@ mod chmod;
 mod chown;
 mod close;
 mod dup;
+mod fadvise;
 mod fallocate;
 mod fcntl;
 mod file_flags;
@@ -50,6 +53,7 @@ mod ioctl;
 mod link;
 mod lseek;
 mod mkdir;
+mod mknod;
 mod open;
 mod read;
 mod rename;
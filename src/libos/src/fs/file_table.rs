@@ -1,15 +1,12 @@
 use super::*;
 
-use crate::events::{Event, Notifier};
-
 pub type FileDesc = u32;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[repr(C)]
 pub struct FileTable {
     table: Vec<Option<FileTableEntry>>,
     num_fds: usize,
-    notifier: FileTableNotifier,
 }
 
 impl FileTable {
@@ -17,7 +14,6 @@ impl FileTable {
         FileTable {
             table: Vec::with_capacity(4),
             num_fds: 0,
-            notifier: FileTableNotifier::new(),
         }
     }
 
@@ -99,6 +95,19 @@ impl FileTable {
         table_entry.map(|entry| entry.file.clone())
     }
 
+    /// Replace the file at `fd` with a new one, keeping the close-on-spawn flag.
+    ///
+    /// Fails if the file at `fd` is no longer `old_file`, e.g., it has been
+    /// closed by another thread.
+    pub fn replace(&mut self, fd: FileDesc, old_file: &FileRef, new_file: FileRef) -> Result<()> {
+        let entry = self.get_entry_mut(fd)?;
+        if !Arc::ptr_eq(entry.get_file(), old_file) {
+            return_errno!(EBADF, "the file descriptor has been reused");
+        }
+        *entry.get_file_mut() = new_file;
+        Ok(())
+    }
+
     pub fn fds(&self) -> Vec<FileDesc> {
         let table = &self.table;
         table
@@ -149,7 +158,6 @@ impl FileTable {
         match del_table_entry {
             Some(del_table_entry) => {
                 self.num_fds -= 1;
-                self.broadcast_del(fd);
                 Ok(del_table_entry.file)
             }
             None => return_errno!(EBADF, "Invalid file descriptor"),
@@ -158,30 +166,18 @@ impl FileTable {
 
     /// Remove all the file descriptors
     pub fn del_all(&mut self) -> Vec<FileRef> {
-        let mut deleted_fds = Vec::new();
         let mut deleted_files = Vec::new();
-        for (fd, entry) in self
-            .table
-            .iter_mut()
-            .filter(|entry| entry.is_some())
-            .enumerate()
-        {
-            deleted_files.push(entry.as_ref().unwrap().file.clone());
-            *entry = None;
-            deleted_fds.push(fd as FileDesc);
+        for entry in self.table.iter_mut().filter(|entry| entry.is_some()) {
+            deleted_files.push(entry.take().unwrap().file);
         }
         self.num_fds = 0;
-        for fd in deleted_fds {
-            self.broadcast_del(fd);
-        }
         deleted_files
     }
 
     /// Remove file descriptors that are close-on-spawn
     pub fn close_on_spawn(&mut self) -> Vec<FileRef> {
-        let mut deleted_fds = Vec::new();
         let mut deleted_files = Vec::new();
-        for (fd, entry) in self.table.iter_mut().enumerate() {
+        for entry in self.table.iter_mut() {
             let need_close = if let Some(entry) = entry {
                 entry.close_on_spawn
             } else {
@@ -190,35 +186,11 @@ impl FileTable {
             if need_close {
                 deleted_files.push(entry.as_ref().unwrap().file.clone());
                 *entry = None;
-                deleted_fds.push(fd as FileDesc);
                 self.num_fds -= 1;
             }
         }
-
-        for fd in deleted_fds {
-            self.broadcast_del(fd);
-        }
         deleted_files
     }
-
-    pub fn notifier(&self) -> &FileTableNotifier {
-        &self.notifier
-    }
-
-    fn broadcast_del(&self, fd: FileDesc) {
-        let del_event = FileTableEvent::Del(fd);
-        self.notifier.broadcast(&del_event);
-    }
-}
-
-impl Clone for FileTable {
-    fn clone(&self) -> Self {
-        FileTable {
-            table: self.table.clone(),
-            num_fds: self.num_fds,
-            notifier: FileTableNotifier::new(),
-        }
-    }
 }
 
 impl Default for FileTable {
@@ -227,15 +199,6 @@ impl Default for FileTable {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum FileTableEvent {
-    Del(FileDesc),
-}
-
-impl Event for FileTableEvent {}
-
-pub type FileTableNotifier = Notifier<FileTableEvent>;
-
 #[derive(Debug, Clone)]
 pub struct FileTableEntry {
     file: FileRef,
@@ -6,7 +6,7 @@ pub use self::mount::{
     do_mount, do_mount_rootfs, do_umount, MountFlags, MountOptions, UmountFlags,
 };
 pub use self::statfs::{do_fstatfs, do_statfs, fetch_host_statfs, Statfs};
-pub use self::sync::do_sync;
+pub use self::sync::{do_sync, do_syncfs};
 
 mod chdir;
 mod getcwd;
@@ -1,10 +1,13 @@
-use config::{parse_key, parse_mac, ConfigMount, ConfigMountFsType, ConfigMountOptions};
+use config::{
+    parse_key, parse_mac, parse_memory_size, ConfigMount, ConfigMountFsType, ConfigMountOptions,
+};
 use rcore_fs_mountfs::MNode;
 use std::path::PathBuf;
 use std::sync::Once;
 use util::host_file_util::{write_host_file, HostFile};
 use util::mem_util::from_user;
 
+use super::mount_table::MOUNT_TABLE;
 use super::rootfs::{mount_nonroot_fs_according_to, open_root_fs_according_to, umount_nonroot_fs};
 use super::*;
 
@@ -29,6 +32,7 @@ pub fn do_mount_rootfs(
         let mut rootfs = ROOT_FS.write().unwrap();
         rootfs.sync().expect("failed to sync old rootfs");
         *rootfs = new_rootfs;
+        MOUNT_TABLE.write().unwrap().reset(mount_config);
         *ENTRY_POINTS.write().unwrap() = user_app_config.entry_points.to_owned();
     });
 
@@ -82,25 +86,26 @@ pub fn do_mount(
     let (mount_configs, user_key) = match options {
         MountOptions::UnionFS(unionfs_options) => {
             let mc = {
-                let image_mc = ConfigMount {
-                    type_: ConfigMountFsType::TYPE_SEFS,
-                    target: target.clone(),
-                    source: Some(unionfs_options.lower_dir.clone()),
-                    options: Default::default(),
-                };
-                let container_mc = ConfigMount {
-                    type_: ConfigMountFsType::TYPE_SEFS,
-                    target: target.clone(),
-                    source: Some(unionfs_options.upper_dir.clone()),
-                    options: Default::default(),
-                };
+                // The layers are listed from the lowest to the uppermost
+                let layer_mcs = unionfs_options
+                    .lower_layers
+                    .iter()
+                    .rev()
+                    .chain(std::iter::once(&unionfs_options.upper_layer))
+                    .map(|layer| ConfigMount {
+                        type_: layer.type_.clone(),
+                        target: target.clone(),
+                        source: layer.dir.clone(),
+                        options: Default::default(),
+                    })
+                    .collect();
 
                 ConfigMount {
                     type_: ConfigMountFsType::TYPE_UNIONFS,
                     target,
                     source: None,
                     options: ConfigMountOptions {
-                        layers: Some(vec![image_mc, container_mc]),
+                        layers: Some(layer_mcs),
                         ..Default::default()
                     },
                 }
@@ -119,21 +124,41 @@ pub fn do_mount(
             };
             (vec![mc], sefs_options.key)
         }
-        MountOptions::HostFS(dir) => {
+        MountOptions::HostFS(hostfs_options) => {
             let mc = ConfigMount {
                 type_: ConfigMountFsType::TYPE_HOSTFS,
                 target,
-                source: Some(dir.clone()),
-                options: Default::default(),
+                source: Some(hostfs_options.dir.clone()),
+                options: ConfigMountOptions {
+                    confined: Some(hostfs_options.confined),
+                    async_io: hostfs_options.async_io,
+                    ..Default::default()
+                },
             };
             (vec![mc], None)
         }
-        MountOptions::RamFS => {
+        MountOptions::EncFS(encfs_options) => {
+            let mc = ConfigMount {
+                type_: ConfigMountFsType::TYPE_ENCFS,
+                target,
+                source: Some(encfs_options.dir.clone()),
+                options: ConfigMountOptions {
+                    obfuscate_names: encfs_options.obfuscate_names,
+                    ..Default::default()
+                },
+            };
+            (vec![mc], encfs_options.key)
+        }
+        MountOptions::RamFS(ramfs_options) => {
             let mc = ConfigMount {
                 type_: ConfigMountFsType::TYPE_RAMFS,
                 target,
                 source: None,
-                options: Default::default(),
+                options: ConfigMountOptions {
+                    size: ramfs_options.size,
+                    nr_inodes: ramfs_options.nr_inodes,
+                    ..Default::default()
+                },
             };
             (vec![mc], None)
         }
@@ -149,6 +174,10 @@ pub fn do_mount(
         &user_key,
         follow_symlink,
     )?;
+    let mut mount_table = MOUNT_TABLE.write().unwrap();
+    for mc in &mount_configs {
+        mount_table.add(mc);
+    }
     Ok(())
 }
 
@@ -170,6 +199,7 @@ pub fn do_umount(target: &str, flags: UmountFlags) -> Result<()> {
     rootfs.sync()?;
     let follow_symlink = !flags.contains(UmountFlags::UMOUNT_NOFOLLOW);
     umount_nonroot_fs(&rootfs.root_inode(), &target, follow_symlink)?;
+    MOUNT_TABLE.write().unwrap().remove(Path::new(&target));
     Ok(())
 }
 
@@ -213,8 +243,9 @@ bitflags! {
 pub enum MountOptions {
     UnionFS(UnionFSMountOptions),
     SEFS(SEFSMountOptions),
-    HostFS(PathBuf),
-    RamFS,
+    HostFS(HostFSMountOptions),
+    EncFS(EncFSMountOptions),
+    RamFS(RamFSMountOptions),
 }
 
 impl MountOptions {
@@ -239,20 +270,35 @@ impl MountOptions {
                 Self::UnionFS(unionfs_mount_options)
             }
             ConfigMountFsType::TYPE_HOSTFS => {
-                let options = from_user::clone_cstring_safely(options)?
-                    .to_string_lossy()
-                    .into_owned();
-                let dir = {
-                    let options: Vec<&str> = options.split(",").collect();
-                    let dir = options
-                        .iter()
-                        .find_map(|s| s.strip_prefix("dir="))
-                        .ok_or_else(|| errno!(EINVAL, "no dir options"))?;
-                    PathBuf::from(dir)
+                let hostfs_mount_options = {
+                    let options = from_user::clone_cstring_safely(options)?
+                        .to_string_lossy()
+                        .into_owned();
+                    HostFSMountOptions::from_input(options.as_str())?
+                };
+                Self::HostFS(hostfs_mount_options)
+            }
+            ConfigMountFsType::TYPE_ENCFS => {
+                let encfs_mount_options = {
+                    let options = from_user::clone_cstring_safely(options)?
+                        .to_string_lossy()
+                        .into_owned();
+                    EncFSMountOptions::from_input(options.as_str())?
+                };
+                Self::EncFS(encfs_mount_options)
+            }
+            ConfigMountFsType::TYPE_RAMFS => {
+                // The options are optional for RamFS
+                let ramfs_mount_options = if options.is_null() {
+                    RamFSMountOptions::default()
+                } else {
+                    let options = from_user::clone_cstring_safely(options)?
+                        .to_string_lossy()
+                        .into_owned();
+                    RamFSMountOptions::from_input(options.as_str())?
                 };
-                Self::HostFS(dir)
+                Self::RamFS(ramfs_mount_options)
             }
-            ConfigMountFsType::TYPE_RAMFS => Self::RamFS,
             _ => {
                 return_errno!(EINVAL, "unsupported fs type");
             }
@@ -262,36 +308,89 @@ impl MountOptions {
 
 #[derive(Debug)]
 pub struct UnionFSMountOptions {
-    lower_dir: PathBuf,
-    upper_dir: PathBuf,
+    /// The read-only layers, from the uppermost to the lowest
+    lower_layers: Vec<UnionFSLayer>,
+    /// The writable layer
+    upper_layer: UnionFSLayer,
     key: Option<sgx_key_128bit_t>,
 }
 
+#[derive(Debug)]
+struct UnionFSLayer {
+    type_: ConfigMountFsType,
+    dir: Option<PathBuf>,
+}
+
 impl UnionFSMountOptions {
+    /// Parse the options like "lowerdir=<dir1>:<dir2>,lowertype=<type1>:<type2>,
+    /// upperdir=<dir>,uppertype=<type>,key=<key>". Same as OverlayFS, the lower
+    /// dirs are listed from the uppermost to the lowest. The types are SEFS by
+    /// default, and the dir can be empty for RamFS.
     pub fn from_input(input: &str) -> Result<Self> {
         let options: Vec<&str> = input.split(",").collect();
 
-        let lower_dir = options
+        let lower_dirs: Vec<&str> = options
             .iter()
             .find_map(|s| s.strip_prefix("lowerdir="))
-            .ok_or_else(|| errno!(EINVAL, "no lowerdir options"))?;
+            .ok_or_else(|| errno!(EINVAL, "no lowerdir options"))?
+            .split(":")
+            .collect();
+        let lower_types: Vec<&str> = match options.iter().find_map(|s| s.strip_prefix("lowertype="))
+        {
+            Some(types_str) => types_str.split(":").collect(),
+            None => vec!["sefs"; lower_dirs.len()],
+        };
+        if lower_types.len() != lower_dirs.len() {
+            return_errno!(EINVAL, "the number of lowertype and lowerdir are not equal");
+        }
+        let lower_layers = lower_types
+            .iter()
+            .zip(lower_dirs.iter())
+            .map(|(type_str, dir)| UnionFSLayer::new(type_str, dir))
+            .collect::<Result<Vec<_>>>()?;
+
+        let upper_type = options
+            .iter()
+            .find_map(|s| s.strip_prefix("uppertype="))
+            .unwrap_or("sefs");
         let upper_dir = options
             .iter()
             .find_map(|s| s.strip_prefix("upperdir="))
-            .ok_or_else(|| errno!(EINVAL, "no upperdir options"))?;
+            .unwrap_or("");
+        let upper_layer = UnionFSLayer::new(upper_type, upper_dir)?;
+
         let key = match options.iter().find_map(|s| s.strip_prefix("key=")) {
             Some(key_str) => Some(parse_key(key_str)?),
             None => None,
         };
 
         Ok(Self {
-            lower_dir: PathBuf::from(lower_dir),
-            upper_dir: PathBuf::from(upper_dir),
+            lower_layers,
+            upper_layer,
             key,
         })
     }
 }
 
+impl UnionFSLayer {
+    fn new(type_str: &str, dir: &str) -> Result<Self> {
+        let type_ = ConfigMountFsType::from_input(type_str)?;
+        let dir = match type_ {
+            ConfigMountFsType::TYPE_RAMFS => None,
+            ConfigMountFsType::TYPE_SEFS
+            | ConfigMountFsType::TYPE_HOSTFS
+            | ConfigMountFsType::TYPE_ENCFS => {
+                if dir.is_empty() {
+                    return_errno!(EINVAL, "no dir for the layer");
+                }
+                Some(PathBuf::from(dir))
+            }
+            _ => return_errno!(EINVAL, "unsupported fs type of the layer"),
+        };
+        Ok(Self { type_, dir })
+    }
+}
+
 #[derive(Debug)]
 pub struct SEFSMountOptions {
     dir: PathBuf,
@@ -324,6 +423,115 @@ impl SEFSMountOptions {
     }
 }
 
+#[derive(Debug)]
+pub struct HostFSMountOptions {
+    dir: PathBuf,
+    confined: bool,
+    async_io: bool,
+}
+
+impl HostFSMountOptions {
+    pub fn from_input(input: &str) -> Result<Self> {
+        let options: Vec<&str> = input.split(",").collect();
+
+        let dir = options
+            .iter()
+            .find_map(|s| s.strip_prefix("dir="))
+            .ok_or_else(|| errno!(EINVAL, "no dir options"))?;
+        // The HostFS is confined unless "noconfined" is specified
+        let confined = match options
+            .iter()
+            .rev()
+            .find(|s| **s == "confined" || **s == "noconfined")
+        {
+            Some(s) => *s == "confined",
+            None => true,
+        };
+        let async_io = options.iter().any(|s| *s == "async_io");
+
+        Ok(Self {
+            dir: PathBuf::from(dir),
+            confined,
+            async_io,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct EncFSMountOptions {
+    dir: PathBuf,
+    key: Option<sgx_key_128bit_t>,
+    obfuscate_names: bool,
+}
+
+impl EncFSMountOptions {
+    pub fn from_input(input: &str) -> Result<Self> {
+        let options: Vec<&str> = input.split(",").collect();
+
+        let dir = options
+            .iter()
+            .find_map(|s| s.strip_prefix("dir="))
+            .ok_or_else(|| errno!(EINVAL, "no dir options"))?;
+        let key = match options.iter().find_map(|s| s.strip_prefix("key=")) {
+            Some(key_str) => Some(parse_key(key_str)?),
+            None => None,
+        };
+        let obfuscate_names = options.iter().any(|s| *s == "obfuscate_names");
+
+        Ok(Self {
+            dir: PathBuf::from(dir),
+            key,
+            obfuscate_names,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RamFSMountOptions {
+    size: Option<usize>,
+    nr_inodes: Option<usize>,
+}
+
+impl RamFSMountOptions {
+    pub fn from_input(input: &str) -> Result<Self> {
+        let options: Vec<&str> = input.split(",").collect();
+
+        let size = match options.iter().find_map(|s| s.strip_prefix("size=")) {
+            Some(size_str) => Some(parse_ramfs_size(size_str)?),
+            None => None,
+        };
+        let nr_inodes = match options.iter().find_map(|s| s.strip_prefix("nr_inodes=")) {
+            Some(nr_inodes_str) => Some(
+                nr_inodes_str
+                    .parse::<usize>()
+                    .map_err(|_| errno!(EINVAL, "invalid nr_inodes options"))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self { size, nr_inodes })
+    }
+}
+
+/// Parse the size in the format of tmpfs, e.g., "65536", "64k", "16m" and "1g",
+/// or in the format of Occlum.json, e.g., "64KB" and "16MB".
+fn parse_ramfs_size(size_str: &str) -> Result<usize> {
+    if size_str.ends_with('B') {
+        return parse_memory_size(size_str);
+    }
+    let (number_str, factor) = match size_str.char_indices().last() {
+        Some((i, 'k')) | Some((i, 'K')) => (&size_str[..i], 1024),
+        Some((i, 'm')) | Some((i, 'M')) => (&size_str[..i], 1024 * 1024),
+        Some((i, 'g')) | Some((i, 'G')) => (&size_str[..i], 1024 * 1024 * 1024),
+        _ => (size_str, 1),
+    };
+    number_str
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| number.checked_mul(factor))
+        .ok_or_else(|| errno!(EINVAL, "invalid size options"))
+}
+
 bitflags! {
     pub struct UmountFlags: u32 {
         const MNT_FORCE = 1;
@@ -1,4 +1,6 @@
 use super::*;
+use crate::fs::encfs::ENode;
+use crate::fs::hostfs::HNode;
 use rcore_fs::vfs::FsInfo;
 use std::convert::TryFrom;
 use std::ffi::CString;
@@ -8,7 +10,10 @@ pub fn do_fstatfs(fd: FileDesc) -> Result<Statfs> {
 
     let file_ref = current!().file(fd)?;
     let statfs = {
-        let fs_info = file_ref.fs()?.info();
+        let fs_info = match file_ref.as_inode_file() {
+            Ok(inode_file) => fetch_fs_info(inode_file.inode())?,
+            Err(_) => file_ref.fs()?.info(),
+        };
         Statfs::try_from(fs_info)?
     };
     trace!("fstatfs result: {:?}", statfs);
@@ -24,13 +29,27 @@ pub fn do_statfs(path: &str) -> Result<Statfs> {
         fs.lookup_inode(path)?
     };
     let statfs = {
-        let fs_info = inode.fs().info();
+        let fs_info = fetch_fs_info(&inode)?;
         Statfs::try_from(fs_info)?
     };
     trace!("statfs result: {:?}", statfs);
     Ok(statfs)
 }
 
+/// Get the info of the file system where the inode is.
+///
+/// The host statfs of HostFS and EncFS may fail, which can not be returned by
+/// `FileSystem::info`, so it is fetched from the inode directly.
+fn fetch_fs_info(inode: &Arc<dyn INode>) -> Result<FsInfo> {
+    if let Some(hnode) = inode.downcast_ref::<HNode>() {
+        return hnode.fs_info();
+    }
+    if let Some(enode) = inode.downcast_ref::<ENode>() {
+        return enode.fs_info();
+    }
+    Ok(inode.fs().info())
+}
+
 #[derive(Default, Debug)]
 #[repr(C)]
 pub struct Statfs {
@@ -142,12 +161,14 @@ pub fn fetch_host_statfs(path: &str) -> Result<Statfs> {
     let host_dir = CString::new(path.as_bytes()).unwrap();
     let sgx_status = unsafe { occlum_ocall_statfs(&mut ret, host_dir.as_ptr(), &mut statfs) };
     assert!(sgx_status == sgx_status_t::SGX_SUCCESS);
-    assert!(ret == 0 || libc::errno() == Errno::EINTR as i32);
     if ret != 0 {
-        return_errno!(EINTR, "failed to get host statfs");
+        return_errno!(
+            Errno::from(libc::errno() as u32),
+            "failed to get host statfs"
+        );
     }
 
     // do sanity check
-    statfs.validate().expect("invalid statfs");
+    statfs.validate()?;
     Ok(statfs)
 }
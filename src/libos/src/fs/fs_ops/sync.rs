@@ -5,3 +5,14 @@ pub fn do_sync() -> Result<()> {
     ROOT_FS.read().unwrap().sync()?;
     Ok(())
 }
+
+pub fn do_syncfs(fd: FileDesc) -> Result<()> {
+    debug!("syncfs: fd: {}", fd);
+    let file_ref = current!().file(fd)?;
+    // Only sync the file system containing the file. The pseudo files,
+    // e.g., pipes and sockets, have nothing to sync.
+    if let Ok(inode_file) = file_ref.as_inode_file() {
+        inode_file.inode().fs().sync()?;
+    }
+    Ok(())
+}
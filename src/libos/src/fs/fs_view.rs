@@ -121,6 +121,21 @@ impl FsView {
             }
         };
         let abs_path = self.convert_to_abs_path(&path);
+        let status_flags = StatusFlags::from_bits_truncate(flags);
+        let type_ = inode.metadata()?.type_;
+        if type_ == FileType::NamedPipe && !status_flags.is_fast_open() {
+            // The FIFOs on the host cannot be shared with the host processes
+            // through the pipes in the enclave
+            if inode.downcast_ref::<HNode>().is_some() {
+                return_errno!(ENXIO, "the FIFOs in HostFS are not supported");
+            }
+            return Ok(Arc::new(FifoFile::open(inode, &abs_path, flags)?));
+        }
+        if type_ == FileType::CharDevice && !status_flags.is_fast_open() {
+            if let Some(file) = pty::open_tty_device(&inode, &abs_path, flags)? {
+                return Ok(file);
+            }
+        }
         Ok(Arc::new(INodeFile::open(inode, &abs_path, flags)?))
     }
 
@@ -0,0 +1,355 @@
+//! The operations on the host files, which are relative to the directory fds.
+//!
+//! All the paths passed to the host are resolved by the `*at` family of the
+//! syscalls, so the host could never redirect a path out of the directory.
+
+use super::*;
+use rcore_fs::dev::DevError;
+use sgx_trts::libc::{self, c_void};
+use sgx_types::sgx_status_t;
+use std::ffi::CString;
+
+/// The file descriptor of a host file, which is closed when dropped.
+pub struct HostFd(i32);
+
+impl HostFd {
+    pub fn as_raw(&self) -> i32 {
+        self.0
+    }
+}
+
+impl Drop for HostFd {
+    fn drop(&mut self) {
+        unsafe {
+            libc::ocall::close(self.0);
+        }
+    }
+}
+
+// Call the OCall which propagates the errno, return the FsError if failed
+macro_rules! try_ocall {
+    ($ocall: ident ( $($arg: expr),* )) => {{
+        let mut ret = 0;
+        let sgx_status = unsafe { $ocall(&mut ret, $($arg),*) };
+        assert!(sgx_status == sgx_status_t::SGX_SUCCESS);
+        if ret < 0 {
+            return Err(host_errno_to_fs_error(unsafe { libc::errno() }));
+        }
+        ret
+    }};
+}
+
+// Call the OCall provided by the SDK, return the FsError if failed
+macro_rules! try_libc_ocall {
+    ($ret: expr) => {{
+        let ret = unsafe { $ret };
+        if ret < 0 {
+            return Err(host_errno_to_fs_error(unsafe { libc::errno() }));
+        }
+        ret
+    }};
+}
+
+pub fn openat(dirfd: i32, path: &str, flags: i32, mode: u32) -> Result<HostFd> {
+    let path = to_cstring(path)?;
+    let fd = try_ocall!(occlum_ocall_openat(dirfd, path.as_ptr(), flags, mode));
+    Ok(HostFd(fd))
+}
+
+pub fn fstatat(dirfd: i32, path: &str, flags: i32) -> Result<libc::stat> {
+    let path = to_cstring(path)?;
+    let mut stat_buf: libc::stat = unsafe { std::mem::zeroed() };
+    try_ocall!(occlum_ocall_fstatat(
+        dirfd,
+        path.as_ptr(),
+        &mut stat_buf as *mut _ as *mut c_void,
+        std::mem::size_of::<libc::stat>(),
+        flags
+    ));
+    Ok(stat_buf)
+}
+
+pub fn fstat(fd: &HostFd) -> Result<libc::stat> {
+    fstatat(fd.as_raw(), "", libc::AT_EMPTY_PATH)
+}
+
+pub fn readlinkat(dirfd: i32, path: &str) -> Result<String> {
+    let path = to_cstring(path)?;
+    let mut buf = vec![0u8; libc::PATH_MAX as usize];
+    let len = try_ocall!(occlum_ocall_readlinkat(
+        dirfd,
+        path.as_ptr(),
+        buf.as_mut_ptr() as *mut i8,
+        buf.len()
+    )) as usize;
+    // The target may be truncated
+    if len == buf.len() {
+        return Err(FsError::NameTooLong);
+    }
+    buf.truncate(len);
+    String::from_utf8(buf).map_err(|_| FsError::InvalidParam)
+}
+
+pub fn mkdirat(dirfd: i32, path: &str, mode: u32) -> Result<()> {
+    let path = to_cstring(path)?;
+    try_ocall!(occlum_ocall_mkdirat(dirfd, path.as_ptr(), mode));
+    Ok(())
+}
+
+pub fn unlinkat(dirfd: i32, path: &str, flags: i32) -> Result<()> {
+    let path = to_cstring(path)?;
+    try_ocall!(occlum_ocall_unlinkat(dirfd, path.as_ptr(), flags));
+    Ok(())
+}
+
+pub fn renameat(old_dirfd: i32, old_path: &str, new_dirfd: i32, new_path: &str) -> Result<()> {
+    let old_path = to_cstring(old_path)?;
+    let new_path = to_cstring(new_path)?;
+    try_ocall!(occlum_ocall_renameat(
+        old_dirfd,
+        old_path.as_ptr(),
+        new_dirfd,
+        new_path.as_ptr()
+    ));
+    Ok(())
+}
+
+pub fn linkat(old_dirfd: i32, old_path: &str, new_dirfd: i32, new_path: &str) -> Result<()> {
+    let old_path = to_cstring(old_path)?;
+    let new_path = to_cstring(new_path)?;
+    // The flags is zero, so the symlink is never followed
+    try_ocall!(occlum_ocall_linkat(
+        old_dirfd,
+        old_path.as_ptr(),
+        new_dirfd,
+        new_path.as_ptr(),
+        0
+    ));
+    Ok(())
+}
+
+pub fn symlinkat(target: &str, new_dirfd: i32, link_path: &str) -> Result<()> {
+    let target = to_cstring(target)?;
+    let link_path = to_cstring(link_path)?;
+    try_ocall!(occlum_ocall_symlinkat(
+        target.as_ptr(),
+        new_dirfd,
+        link_path.as_ptr()
+    ));
+    Ok(())
+}
+
+pub fn fchmod(fd: &HostFd, mode: u32) -> Result<()> {
+    try_libc_ocall!(libc::ocall::fchmod(fd.as_raw(), mode));
+    Ok(())
+}
+
+pub fn fchmodat(dirfd: i32, path: &str, mode: u32) -> Result<()> {
+    let path = to_cstring(path)?;
+    try_ocall!(occlum_ocall_fchmodat(dirfd, path.as_ptr(), mode));
+    Ok(())
+}
+
+/// Read all the entries of the directory opened by `fd`, except "." and "..".
+///
+/// Returns the name, inode number and `d_type` of each entry.
+pub fn read_dir_entries(fd: &HostFd) -> Result<Vec<(String, u64, u8)>> {
+    // The header of struct linux_dirent64: d_ino, d_off, d_reclen and d_type
+    const DIRENT_HEADER_LEN: usize = 19;
+
+    let mut entries = Vec::new();
+    let mut buf = vec![0u8; 4096];
+    loop {
+        let len = try_ocall!(occlum_ocall_getdents64(
+            fd.as_raw(),
+            buf.as_mut_ptr() as *mut c_void,
+            buf.len()
+        )) as usize;
+        if len == 0 {
+            break;
+        }
+        // Never trust the dirents returned by the host
+        if len > buf.len() {
+            return Err(FsError::InvalidParam);
+        }
+        let mut offset = 0;
+        while offset < len {
+            if offset + DIRENT_HEADER_LEN > len {
+                return Err(FsError::InvalidParam);
+            }
+            let dirent = &buf[offset..len];
+            let ino = u64::from_ne_bytes(dirent[0..8].try_into().unwrap());
+            let reclen = u16::from_ne_bytes(dirent[16..18].try_into().unwrap()) as usize;
+            let type_ = dirent[18];
+            if reclen <= DIRENT_HEADER_LEN || reclen > dirent.len() {
+                return Err(FsError::InvalidParam);
+            }
+            let name_bytes = &dirent[DIRENT_HEADER_LEN..reclen];
+            let name_len = name_bytes
+                .iter()
+                .position(|&b| b == 0)
+                .ok_or(FsError::InvalidParam)?;
+            let name =
+                std::str::from_utf8(&name_bytes[..name_len]).map_err(|_| FsError::InvalidParam)?;
+            if name != "." && name != ".." {
+                if name.is_empty() || name.contains('/') {
+                    return Err(FsError::InvalidParam);
+                }
+                entries.push((name.to_string(), ino, type_));
+            }
+            offset += reclen;
+        }
+    }
+    Ok(entries)
+}
+
+pub fn pread(fd: &HostFd, buf: &mut [u8], offset: usize) -> Result<usize> {
+    let len = try_libc_ocall!(libc::ocall::pread64(
+        fd.as_raw(),
+        buf.as_mut_ptr() as *mut c_void,
+        buf.len(),
+        offset as i64
+    )) as usize;
+    // The host may return a length larger than the buffer
+    Ok(len.min(buf.len()))
+}
+
+pub fn pwrite(fd: &HostFd, buf: &[u8], offset: usize) -> Result<usize> {
+    let len = try_libc_ocall!(libc::ocall::pwrite64(
+        fd.as_raw(),
+        buf.as_ptr() as *const c_void,
+        buf.len(),
+        offset as i64
+    )) as usize;
+    Ok(len.min(buf.len()))
+}
+
+/// Same as `pread`, but done through the I/O ring, or by the OCall if the ring
+/// is busy.
+pub fn async_pread(fd: &HostFd, buf: &mut [u8], offset: usize) -> Result<usize> {
+    let ring = IoRing::get().unwrap();
+    match ring.pread(fd.as_raw(), buf, offset) {
+        Ok(Some(len)) => Ok(len),
+        Ok(None) => pread(fd, buf, offset),
+        // The ring fails only with the errno of the host, same as the OCall
+        Err(e) => Err(host_errno_to_fs_error(e.errno() as i32)),
+    }
+}
+
+/// Same as `pwrite`, but done through the I/O ring, or by the OCall if the
+/// ring is busy.
+pub fn async_pwrite(fd: &HostFd, buf: &[u8], offset: usize) -> Result<usize> {
+    let ring = IoRing::get().unwrap();
+    match ring.pwrite(fd.as_raw(), buf, offset) {
+        Ok(Some(len)) => Ok(len),
+        Ok(None) => pwrite(fd, buf, offset),
+        // The ring fails only with the errno of the host, same as the OCall
+        Err(e) => Err(host_errno_to_fs_error(e.errno() as i32)),
+    }
+}
+
+pub fn ftruncate(fd: &HostFd, len: usize) -> Result<()> {
+    try_libc_ocall!(libc::ocall::ftruncate64(fd.as_raw(), len as i64));
+    Ok(())
+}
+
+pub fn fsync(fd: &HostFd) -> Result<()> {
+    try_libc_ocall!(libc::ocall::fsync(fd.as_raw()));
+    Ok(())
+}
+
+pub fn fdatasync(fd: &HostFd) -> Result<()> {
+    try_libc_ocall!(libc::ocall::fdatasync(fd.as_raw()));
+    Ok(())
+}
+
+fn to_cstring(path: &str) -> Result<CString> {
+    CString::new(path).map_err(|_| FsError::InvalidParam)
+}
+
+fn host_errno_to_fs_error(errno: i32) -> FsError {
+    match errno {
+        libc::ENOENT => FsError::EntryNotFound,
+        libc::EEXIST => FsError::EntryExist,
+        libc::ENOTDIR => FsError::NotDir,
+        libc::EISDIR => FsError::IsDir,
+        libc::ENOTEMPTY => FsError::DirNotEmpty,
+        libc::EXDEV => FsError::NotSameFs,
+        libc::EACCES | libc::EPERM => FsError::PermError,
+        libc::ELOOP => FsError::SymLoop,
+        libc::ENAMETOOLONG => FsError::NameTooLong,
+        libc::ENOSPC => FsError::NoDeviceSpace,
+        // There is no FsError for EINTR, so let the caller retry
+        libc::EAGAIN | libc::EINTR => FsError::Again,
+        libc::EBUSY => FsError::Busy,
+        libc::EROFS => FsError::WrProtected,
+        libc::EFBIG => FsError::FileTooBig,
+        libc::EINVAL => FsError::InvalidParam,
+        libc::EOPNOTSUPP => FsError::OpNotSupported,
+        libc::ENOSYS => FsError::NotSupported,
+        libc::ENODEV | libc::ENXIO => FsError::NoDevice,
+        // Report the other errors, e.g., EIO, EBADF and ENOMEM, as I/O errors
+        _ => FsError::from(DevError(errno)),
+    }
+}
+
+extern "C" {
+    fn occlum_ocall_openat(
+        ret: *mut i32,
+        dirfd: i32,
+        path: *const i8,
+        flags: i32,
+        mode: u32,
+    ) -> sgx_status_t;
+    fn occlum_ocall_fstatat(
+        ret: *mut i32,
+        dirfd: i32,
+        path: *const i8,
+        buf: *mut c_void,
+        buf_len: usize,
+        flags: i32,
+    ) -> sgx_status_t;
+    fn occlum_ocall_readlinkat(
+        ret: *mut isize,
+        dirfd: i32,
+        path: *const i8,
+        buf: *mut i8,
+        buf_len: usize,
+    ) -> sgx_status_t;
+    fn occlum_ocall_mkdirat(ret: *mut i32, dirfd: i32, path: *const i8, mode: u32) -> sgx_status_t;
+    fn occlum_ocall_unlinkat(
+        ret: *mut i32,
+        dirfd: i32,
+        path: *const i8,
+        flags: i32,
+    ) -> sgx_status_t;
+    fn occlum_ocall_renameat(
+        ret: *mut i32,
+        old_dirfd: i32,
+        old_path: *const i8,
+        new_dirfd: i32,
+        new_path: *const i8,
+    ) -> sgx_status_t;
+    fn occlum_ocall_linkat(
+        ret: *mut i32,
+        old_dirfd: i32,
+        old_path: *const i8,
+        new_dirfd: i32,
+        new_path: *const i8,
+        flags: i32,
+    ) -> sgx_status_t;
+    fn occlum_ocall_symlinkat(
+        ret: *mut i32,
+        target: *const i8,
+        new_dirfd: i32,
+        link_path: *const i8,
+    ) -> sgx_status_t;
+    fn occlum_ocall_fchmodat(ret: *mut i32, dirfd: i32, path: *const i8, mode: u32)
+        -> sgx_status_t;
+    fn occlum_ocall_getdents64(
+        ret: *mut isize,
+        fd: i32,
+        buf: *mut c_void,
+        buf_len: usize,
+    ) -> sgx_status_t;
+}
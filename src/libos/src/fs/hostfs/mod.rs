@@ -0,0 +1,728 @@
+pub use self::host_ops::HostFd;
+use crate::fs::fs_ops::{fetch_host_statfs, Statfs};
+use crate::untrusted::IoRing;
+use alloc::string::String;
+use alloc::sync::{Arc, Weak};
+use core::any::Any;
+use rcore_fs::vfs::*;
+use sgx_trts::libc;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{SgxMutex as Mutex, SgxMutexGuard as MutexGuard};
+use std::untrusted::fs;
+
+mod host_ops;
+
+// workaround for unable to `impl From<std::io::Error> for FsError`
+macro_rules! try_std {
+    ($ret: expr) => {
+        $ret.map_err(|e| e.into_fs_error())?
+    };
+}
+
+/// Untrusted file system at host
+///
+/// All the paths are resolved component by component relative to the fd of
+/// the root directory, so the ".." can never escape from the root. If the
+/// HostFS is confined, the symlinks on the host are never followed either,
+/// i.e., the host could not redirect the accesses out of the root by
+/// replacing the path components with symlinks.
+pub struct HostFS {
+    path: PathBuf,
+    root_fd: HostFd,
+    confined: bool,
+    /// Whether to read and write the host files through the I/O ring
+    async_io: bool,
+    /// The host files opened for writing, which are synced by `sync`.
+    opened_files: Mutex<Vec<Weak<HostFd>>>,
+    self_ref: Weak<HostFS>,
+}
+
+/// INode for `HostFS`
+pub struct HNode {
+    /// The path relative to the root of HostFS
+    path: PathBuf,
+    file: Mutex<Option<HostFile>>,
+    type_: FileType,
+    fs: Arc<HostFS>,
+}
+
+/// The host file opened by `HNode`.
+///
+/// The file is opened lazily with the least access mode needed by the
+/// operations on it, and reopened through the fd held if more access is
+/// needed later. So the
+/// read-only host files can be read without any error.
+struct HostFile {
+    fd: Arc<HostFd>,
+    readable: bool,
+    writable: bool,
+}
+
+impl FileSystem for HostFS {
+    fn sync(&self) -> Result<()> {
+        let opened_files: Vec<Arc<HostFd>> = {
+            let mut opened_files = self.opened_files.lock().unwrap();
+            opened_files.retain(|fd| fd.strong_count() > 0);
+            opened_files.iter().filter_map(|fd| fd.upgrade()).collect()
+        };
+        for fd in opened_files {
+            host_ops::fsync(&fd)?;
+        }
+        Ok(())
+    }
+
+    fn root_inode(&self) -> Arc<dyn INode> {
+        Arc::new(HNode {
+            path: PathBuf::new(),
+            file: Mutex::new(None),
+            type_: FileType::Dir,
+            fs: self.self_ref.upgrade().unwrap(),
+        })
+    }
+
+    fn info(&self) -> FsInfo {
+        // The error can not be returned here, the syscalls use `statfs` instead
+        self.statfs().unwrap_or_else(|e| {
+            warn!("failed to get the host statfs: {:?}", e);
+            Statfs::default().into()
+        })
+    }
+}
+
+impl HostFS {
+    /// Create a new `HostFS` from host `path`
+    ///
+    /// If `confined` is true, the symlinks on the host are never followed. If
+    /// `async_io` is true, the host files are read and written through the I/O
+    /// ring when it is enabled.
+    pub fn new(path: impl AsRef<Path>, confined: bool, async_io: bool) -> Result<Arc<HostFS>> {
+        let path = try_std!(fs::canonicalize(path.as_ref()));
+        let root_fd = host_ops::openat(
+            libc::AT_FDCWD,
+            &path.to_string_lossy(),
+            libc::O_PATH | libc::O_DIRECTORY,
+            0,
+        )?;
+        Ok(HostFS {
+            path,
+            root_fd,
+            confined,
+            async_io: async_io && IoRing::get().is_some(),
+            opened_files: Mutex::new(Vec::new()),
+            self_ref: Weak::default(),
+        }
+        .wrap())
+    }
+
+    /// Get the info of the host file system where the root is.
+    pub fn statfs(&self) -> crate::error::Result<FsInfo> {
+        let statfs = fetch_host_statfs(&self.path.to_string_lossy())?;
+        Ok(statfs.into())
+    }
+
+    /// Track the opened host file to sync it later if it is writable.
+    fn track_opened_file(&self, fd: HostFd, writable: bool) -> Arc<HostFd> {
+        let fd = Arc::new(fd);
+        if writable {
+            let mut opened_files = self.opened_files.lock().unwrap();
+            opened_files.retain(|fd| fd.strong_count() > 0);
+            opened_files.push(Arc::downgrade(&fd));
+        }
+        fd
+    }
+
+    /// Wrap pure `HostFS` with Arc
+    /// Used in constructors
+    fn wrap(self) -> Arc<Self> {
+        // Create an Arc, make a Weak from it, then put it into the struct.
+        // It's a little tricky.
+        let fs = Arc::new(self);
+        let weak = Arc::downgrade(&fs);
+        let ptr = Arc::into_raw(fs) as *mut Self;
+        unsafe {
+            (*ptr).self_ref = weak;
+        }
+        unsafe { Arc::from_raw(ptr) }
+    }
+
+    /// Open the directory at `rel_path` relative to the root with `flags`.
+    ///
+    /// The path is resolved component by component, every component must be
+    /// a directory. If the HostFS is confined, the symlink is not followed.
+    fn open_dir(&self, rel_path: &Path, flags: i32) -> Result<HostFd> {
+        let mut dir_fd: Option<HostFd> = None;
+        let mut components = rel_path.components().peekable();
+        while let Some(component) = components.next() {
+            let name = match component {
+                Component::Normal(name) => name.to_str().ok_or(FsError::InvalidParam)?,
+                // The path is always normalized, so it must be an escape
+                _ => return Err(FsError::PermError),
+            };
+            let flags = if components.peek().is_some() {
+                libc::O_PATH | libc::O_DIRECTORY
+            } else {
+                flags | libc::O_DIRECTORY
+            };
+            let dirfd = dir_fd.as_ref().unwrap_or(&self.root_fd).as_raw();
+            dir_fd = Some(host_ops::openat(
+                dirfd,
+                name,
+                flags | self.nofollow_open_flag(),
+                0,
+            )?);
+        }
+        match dir_fd {
+            Some(dir_fd) => Ok(dir_fd),
+            None => host_ops::openat(self.root_fd.as_raw(), ".", flags | libc::O_DIRECTORY, 0),
+        }
+    }
+
+    fn nofollow_open_flag(&self) -> i32 {
+        if self.confined {
+            libc::O_NOFOLLOW
+        } else {
+            0
+        }
+    }
+
+    fn nofollow_stat_flag(&self) -> i32 {
+        if self.confined {
+            libc::AT_SYMLINK_NOFOLLOW
+        } else {
+            0
+        }
+    }
+}
+
+impl INode for HNode {
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize> {
+        if self.is_symlink() {
+            let target = self.read_link()?;
+            let target = target.as_bytes();
+            let start = target.len().min(offset);
+            let end = target.len().min(offset + buf.len());
+            buf[..end - start].copy_from_slice(&target[start..end]);
+            return Ok(end - start);
+        }
+        if !self.is_file() {
+            return Err(FsError::NotFile);
+        }
+        let guard = self.open_file(true, false)?;
+        let fd = &guard.as_ref().unwrap().fd;
+        if self.fs.async_io {
+            return host_ops::async_pread(fd, buf, offset);
+        }
+        host_ops::pread(fd, buf, offset)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize> {
+        if self.is_symlink() {
+            // The symlink is created on the host once its target is written
+            if offset != 0 {
+                return Err(FsError::InvalidParam);
+            }
+            let target = std::str::from_utf8(buf).map_err(|_| FsError::InvalidParam)?;
+            let (parent_fd, name) = self.open_parent()?;
+            host_ops::symlinkat(target, parent_fd.as_raw(), name)?;
+            return Ok(buf.len());
+        }
+        if !self.is_file() {
+            return Err(FsError::NotFile);
+        }
+        let guard = self.open_file(false, true)?;
+        let fd = &guard.as_ref().unwrap().fd;
+        if self.fs.async_io {
+            return host_ops::async_pwrite(fd, buf, offset);
+        }
+        host_ops::pwrite(fd, buf, offset)
+    }
+
+    fn metadata(&self) -> Result<Metadata> {
+        let stat_buf = match self.file.lock().unwrap().as_ref() {
+            Some(host_file) => host_ops::fstat(&host_file.fd)?,
+            None => self.stat()?,
+        };
+        stat_buf.into_fs_metadata()
+    }
+
+    fn set_metadata(&self, metadata: &Metadata) -> Result<()> {
+        warn!(
+            "HostFS: set_metadata() only support chmod: {:#o}",
+            metadata.mode
+        );
+        let mode = metadata.mode as u32 & 0o7777;
+        if self.path.as_os_str().is_empty() {
+            return host_ops::fchmodat(self.fs.root_fd.as_raw(), ".", mode);
+        }
+        let (parent_fd, name) = self.open_parent()?;
+        // The fchmodat always follows the symlink, so check it in advance
+        if self.fs.confined {
+            let stat_buf = host_ops::fstatat(parent_fd.as_raw(), name, libc::AT_SYMLINK_NOFOLLOW)?;
+            if stat_buf.st_mode & libc::S_IFMT == libc::S_IFLNK {
+                return Err(FsError::PermError);
+            }
+        }
+        host_ops::fchmodat(parent_fd.as_raw(), name, mode)
+    }
+
+    fn sync_all(&self) -> Result<()> {
+        if self.is_file() {
+            // Nothing to sync if the file has never been opened
+            if let Some(host_file) = self.file.lock().unwrap().as_ref() {
+                host_ops::fsync(&host_file.fd)?;
+            }
+        } else {
+            warn!("no sync_all method about dir, do nothing");
+        }
+        Ok(())
+    }
+
+    fn sync_data(&self) -> Result<()> {
+        if self.is_file() {
+            if let Some(host_file) = self.file.lock().unwrap().as_ref() {
+                host_ops::fdatasync(&host_file.fd)?;
+            }
+        } else {
+            warn!("no sync_data method about dir, do nothing");
+        }
+        Ok(())
+    }
+
+    fn resize(&self, len: usize) -> Result<()> {
+        if self.is_symlink() {
+            // The size of a symlink is decided by its target
+            return Ok(());
+        }
+        if !self.is_file() {
+            return Err(FsError::NotFile);
+        }
+        let guard = self.open_file(false, true)?;
+        let fd = &guard.as_ref().unwrap().fd;
+        host_ops::ftruncate(fd, len)
+    }
+
+    fn create(&self, name: &str, type_: FileType, mode: u16) -> Result<Arc<dyn INode>> {
+        check_name(name)?;
+        let dir_fd = self.open_self_dir()?;
+        let dirfd = dir_fd.as_raw();
+        // Do not follow the symlink, which may point to a nonexistent file
+        if host_ops::fstatat(dirfd, name, libc::AT_SYMLINK_NOFOLLOW).is_ok() {
+            return Err(FsError::EntryExist);
+        }
+        let mode = mode as u32 & 0o7777;
+        let file = match type_ {
+            FileType::File => {
+                let fd = host_ops::openat(
+                    dirfd,
+                    name,
+                    libc::O_RDWR | libc::O_CREAT | libc::O_EXCL | libc::O_NOFOLLOW,
+                    mode,
+                )?;
+                // The mode is masked by the umask of the host
+                host_ops::fchmod(&fd, mode)?;
+                Some(HostFile {
+                    fd: self.fs.track_opened_file(fd, true),
+                    readable: true,
+                    writable: true,
+                })
+            }
+            FileType::Dir => {
+                host_ops::mkdirat(dirfd, name, mode)?;
+                host_ops::fchmodat(dirfd, name, mode)?;
+                None
+            }
+            // The symlink is created on the host when its target is written
+            FileType::SymLink => None,
+            _ => {
+                warn!("only support creating regular file, directory or symlink in HostFS");
+                return Err(FsError::PermError);
+            }
+        };
+
+        Ok(Arc::new(HNode {
+            path: self.path.join(name),
+            file: Mutex::new(file),
+            type_,
+            fs: self.fs.clone(),
+        }))
+    }
+
+    fn link(&self, name: &str, other: &Arc<dyn INode>) -> Result<()> {
+        check_name(name)?;
+        let other = other.downcast_ref::<Self>().ok_or(FsError::NotSameFs)?;
+        if !Arc::ptr_eq(&self.fs, &other.fs) {
+            return Err(FsError::NotSameFs);
+        }
+        let (other_parent_fd, other_name) = other.open_parent()?;
+        let dir_fd = self.open_self_dir()?;
+        host_ops::linkat(other_parent_fd.as_raw(), other_name, dir_fd.as_raw(), name)
+    }
+
+    fn unlink(&self, name: &str) -> Result<()> {
+        check_name(name)?;
+        let dir_fd = self.open_self_dir()?;
+        let stat_buf = host_ops::fstatat(dir_fd.as_raw(), name, libc::AT_SYMLINK_NOFOLLOW)?;
+        let flags = if stat_buf.st_mode & libc::S_IFMT == libc::S_IFDIR {
+            libc::AT_REMOVEDIR
+        } else {
+            0
+        };
+        host_ops::unlinkat(dir_fd.as_raw(), name, flags)
+    }
+
+    fn move_(&self, old_name: &str, target: &Arc<dyn INode>, new_name: &str) -> Result<()> {
+        check_name(old_name)?;
+        check_name(new_name)?;
+        let target = target.downcast_ref::<Self>().ok_or(FsError::NotSameFs)?;
+        if !Arc::ptr_eq(&self.fs, &target.fs) {
+            return Err(FsError::NotSameFs);
+        }
+        let old_dir_fd = self.open_self_dir()?;
+        let new_dir_fd = target.open_self_dir()?;
+        host_ops::renameat(old_dir_fd.as_raw(), old_name, new_dir_fd.as_raw(), new_name)
+    }
+
+    fn find(&self, name: &str) -> Result<Arc<dyn INode>> {
+        if !self.is_dir() {
+            return Err(FsError::NotDir);
+        }
+        let new_path = match name {
+            "." => self.path.clone(),
+            // The parent of the root is the root itself
+            ".." => self.path.parent().unwrap_or(&self.path).to_path_buf(),
+            name => {
+                check_name(name)?;
+                self.path.join(name)
+            }
+        };
+        let new_node = HNode {
+            path: new_path,
+            file: Mutex::new(None),
+            type_: FileType::Dir,
+            fs: self.fs.clone(),
+        };
+        // The symlink is not followed on the host, but resolved by the LibOS
+        let stat_buf = new_node.stat()?;
+        let type_ = stat_buf.into_fs_metadata()?.type_;
+
+        Ok(Arc::new(HNode { type_, ..new_node }))
+    }
+
+    fn get_entry(&self, id: usize) -> Result<String> {
+        if !self.is_dir() {
+            return Err(FsError::NotDir);
+        }
+        let dir_fd = self.fs.open_dir(&self.path, libc::O_RDONLY)?;
+        if let Some((name, _, _)) = host_ops::read_dir_entries(&dir_fd)?.into_iter().nth(id) {
+            Ok(name)
+        } else {
+            return Err(FsError::EntryNotFound);
+        }
+    }
+
+    fn iterate_entries(&self, ctx: &mut DirentWriterContext) -> Result<usize> {
+        let idx = ctx.pos();
+        for (name, ino, type_) in self.entries()?.into_iter().skip(idx) {
+            if let Err(e) = ctx.write_entry(&name, ino, type_) {
+                if ctx.written_len() == 0 {
+                    return Err(e);
+                } else {
+                    break;
+                }
+            };
+        }
+        Ok(ctx.written_len())
+    }
+
+    fn io_control(&self, cmd: u32, data: usize) -> Result<()> {
+        warn!("HostFS: io_control is unimplemented");
+        Ok(())
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.clone()
+    }
+
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl HNode {
+    /// Open the host file with the access needed by the file opened in LibOS.
+    ///
+    /// It does nothing if the HNode is not for a regular file.
+    pub fn open(&self, read: bool, write: bool) -> Result<()> {
+        if self.is_file() {
+            self.open_file(read, write)?;
+        }
+        Ok(())
+    }
+
+    /// Get the info of the host file system, the failure of the host is
+    /// returned rather than ignored as `FileSystem::info` does.
+    pub fn fs_info(&self) -> crate::error::Result<FsInfo> {
+        self.fs.statfs()
+    }
+
+    /// Call `f` with a host path of the entry `name` in this directory.
+    ///
+    /// The path refers to the directory through an fd opened from the root of
+    /// HostFS, e.g., "/proc/self/fd/N/name", so the host does not resolve the
+    /// directory by its path, which may escape from the root via the symlinks
+    /// on the host. The fd is kept open until `f` returns.
+    pub fn with_entry_host_path<T, F>(&self, name: &str, f: F) -> crate::error::Result<T>
+    where
+        F: FnOnce(&Path) -> crate::error::Result<T>,
+    {
+        let dir_fd = self.open_self_dir()?;
+        let path = PathBuf::from(format!("/proc/self/fd/{}", dir_fd.as_raw())).join(name);
+        f(&path)
+    }
+
+    /// Returns the path of this HNode on the host.
+    pub fn host_path(&self) -> PathBuf {
+        self.fs.path.join(&self.path)
+    }
+
+    /// Returns the path of this HNode relative to the root of HostFS.
+    pub fn rel_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read all the entries of the directory, except "." and "..".
+    ///
+    /// Returns the name, inode number and file type of each entry.
+    pub fn entries(&self) -> Result<Vec<(String, u64, FileType)>> {
+        if !self.is_dir() {
+            return Err(FsError::NotDir);
+        }
+        let dir_fd = self.fs.open_dir(&self.path, libc::O_RDONLY)?;
+        let mut entries = Vec::new();
+        for (name, ino, d_type) in host_ops::read_dir_entries(&dir_fd)? {
+            let type_ = match d_type_to_fs_filetype(d_type) {
+                Some(type_) => type_,
+                // The file type is unknown, get it by stat
+                None => {
+                    let stat_buf =
+                        host_ops::fstatat(dir_fd.as_raw(), &name, libc::AT_SYMLINK_NOFOLLOW)?;
+                    stat_buf.into_fs_metadata()?.type_
+                }
+            };
+            entries.push((name, ino, type_));
+        }
+        Ok(entries)
+    }
+
+    /// Ensure to open the file with the access needed and store a `HostFile`
+    /// into `self.file`, return the `MutexGuard`.
+    fn open_file(&self, read: bool, write: bool) -> Result<MutexGuard<Option<HostFile>>> {
+        let mut maybe_file = self.file.lock().unwrap();
+        let (readable, writable) = match maybe_file.as_ref() {
+            Some(host_file) => (host_file.readable, host_file.writable),
+            None => (false, false),
+        };
+        if (read && !readable) || (write && !writable) {
+            // Keep the access of the opened file
+            let (read, write) = (read || readable, write || writable);
+            let access_flags = match (read, write) {
+                (true, false) => libc::O_RDONLY,
+                (false, true) => libc::O_WRONLY,
+                _ => libc::O_RDWR,
+            };
+            let fd = match maybe_file.as_ref() {
+                // Reopen the file held rather than the path, which may refer to
+                // another file after a rename on the host
+                Some(host_file) => {
+                    let fd_path = format!("/proc/self/fd/{}", host_file.fd.as_raw());
+                    host_ops::openat(libc::AT_FDCWD, &fd_path, access_flags, 0)?
+                }
+                None => {
+                    let (parent_fd, name) = self.open_parent()?;
+                    host_ops::openat(
+                        parent_fd.as_raw(),
+                        name,
+                        access_flags | self.fs.nofollow_open_flag(),
+                        0,
+                    )?
+                }
+            };
+            *maybe_file = Some(HostFile {
+                fd: self.fs.track_opened_file(fd, write),
+                readable: read,
+                writable: write,
+            });
+        }
+        Ok(maybe_file)
+    }
+
+    /// Open the parent directory, return the fd and the name of this HNode.
+    ///
+    /// The root of HostFS has no parent, so it is not allowed.
+    pub fn open_parent(&self) -> Result<(HostFd, &str)> {
+        let parent = self.path.parent().ok_or(FsError::InvalidParam)?;
+        let name = self
+            .path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or(FsError::InvalidParam)?;
+        let parent_fd = self.fs.open_dir(parent, libc::O_PATH)?;
+        Ok((parent_fd, name))
+    }
+
+    /// Open the directory of this HNode to operate on its entries.
+    fn open_self_dir(&self) -> Result<HostFd> {
+        if !self.is_dir() {
+            return Err(FsError::NotDir);
+        }
+        self.fs.open_dir(&self.path, libc::O_PATH)
+    }
+
+    /// Get the stat of this HNode without opening it.
+    fn stat(&self) -> Result<libc::stat> {
+        if self.path.as_os_str().is_empty() {
+            return host_ops::fstat(&self.fs.root_fd);
+        }
+        let (parent_fd, name) = self.open_parent()?;
+        host_ops::fstatat(parent_fd.as_raw(), name, self.fs.nofollow_stat_flag())
+    }
+
+    /// Read the target of the symlink.
+    ///
+    /// The symlinks in HostFS are never followed by the host, they are resolved
+    /// by the LibOS within the mount. So an absolute target pointing into the
+    /// host directory of the mount is converted to a relative one, which is
+    /// still valid after the directory is mounted in the LibOS.
+    fn read_link(&self) -> Result<String> {
+        let (parent_fd, name) = self.open_parent()?;
+        let target = PathBuf::from(host_ops::readlinkat(parent_fd.as_raw(), name)?);
+        let target = if target.is_absolute() {
+            match target.strip_prefix(&self.fs.path) {
+                Ok(rel_target) => {
+                    let depth = self
+                        .path
+                        .parent()
+                        .map(|rel_dir| rel_dir.components().count())
+                        .unwrap_or(0);
+                    let mut rel_path = PathBuf::new();
+                    for _ in 0..depth {
+                        rel_path.push("..");
+                    }
+                    rel_path.push(rel_target);
+                    if rel_path.as_os_str().is_empty() {
+                        rel_path.push(".");
+                    }
+                    rel_path
+                }
+                Err(_) => target,
+            }
+        } else {
+            target
+        };
+        target
+            .into_os_string()
+            .into_string()
+            .map_err(|_| FsError::InvalidParam)
+    }
+
+    /// Returns `true` if this HNode is for a regular file.
+    fn is_file(&self) -> bool {
+        self.type_ == FileType::File
+    }
+
+    /// Returns `true` if this HNode is for a directory.
+    fn is_dir(&self) -> bool {
+        self.type_ == FileType::Dir
+    }
+
+    /// Returns `true` if this HNode is for a symlink.
+    fn is_symlink(&self) -> bool {
+        self.type_ == FileType::SymLink
+    }
+}
+
+/// Check the name of a directory entry, reject the ones may escape from the
+/// directory.
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+        return Err(FsError::InvalidParam);
+    }
+    Ok(())
+}
+
+pub trait IntoFsError {
+    fn into_fs_error(self) -> FsError;
+}
+
+impl IntoFsError for std::io::Error {
+    fn into_fs_error(self) -> FsError {
+        use std::io::ErrorKind;
+        match self.kind() {
+            ErrorKind::NotFound => FsError::EntryNotFound,
+            ErrorKind::AlreadyExists => FsError::EntryExist,
+            ErrorKind::WouldBlock => FsError::Again,
+            ErrorKind::InvalidInput => FsError::InvalidParam,
+            ErrorKind::InvalidData => FsError::InvalidParam,
+            ErrorKind::PermissionDenied => FsError::PermError,
+            _ => FsError::NotSupported,
+        }
+    }
+}
+
+fn d_type_to_fs_filetype(d_type: u8) -> Option<FileType> {
+    let type_ = match d_type {
+        libc::DT_FIFO => FileType::NamedPipe,
+        libc::DT_CHR => FileType::CharDevice,
+        libc::DT_DIR => FileType::Dir,
+        libc::DT_BLK => FileType::BlockDevice,
+        libc::DT_REG => FileType::File,
+        libc::DT_LNK => FileType::SymLink,
+        libc::DT_SOCK => FileType::Socket,
+        _ => return None,
+    };
+    Some(type_)
+}
+
+trait IntoFsMetadata {
+    fn into_fs_metadata(self) -> Result<Metadata>;
+}
+
+impl IntoFsMetadata for libc::stat {
+    fn into_fs_metadata(self) -> Result<Metadata> {
+        Ok(Metadata {
+            dev: self.st_dev as usize,
+            inode: self.st_ino as usize,
+            size: self.st_size as usize,
+            blk_size: self.st_blksize as usize,
+            blocks: self.st_blocks as usize,
+            atime: Timespec {
+                sec: self.st_atime,
+                nsec: self.st_atime_nsec,
+            },
+            mtime: Timespec {
+                sec: self.st_mtime,
+                nsec: self.st_mtime_nsec,
+            },
+            ctime: Timespec {
+                sec: self.st_ctime,
+                nsec: self.st_ctime_nsec,
+            },
+            // The file type from the host may be invalid
+            type_: match self.st_mode & libc::S_IFMT {
+                libc::S_IFCHR => FileType::CharDevice,
+                libc::S_IFBLK => FileType::BlockDevice,
+                libc::S_IFDIR => FileType::Dir,
+                libc::S_IFREG => FileType::File,
+                libc::S_IFLNK => FileType::SymLink,
+                libc::S_IFSOCK => FileType::Socket,
+                libc::S_IFIFO => FileType::NamedPipe,
+                _ => return Err(FsError::InvalidParam),
+            },
+            mode: self.st_mode as u16 & 0o777,
+            nlinks: self.st_nlink as usize,
+            uid: self.st_uid as usize,
+            gid: self.st_gid as usize,
+            rdev: self.st_rdev as usize,
+        })
+    }
+}
@@ -1,5 +1,5 @@
+use super::hostfs::HNode;
 use super::*;
-use crate::net::PollEventFlags;
 use crate::process::do_getuid;
 use rcore_fs::vfs::FallocateMode;
 use rcore_fs_sefs::dev::SefsMac;
@@ -320,6 +320,16 @@ impl INodeFile {
         if access_mode.writable() && inode.metadata()?.type_ == FileType::Dir {
             return_errno!(EISDIR, "Directory cannot be open to write");
         }
+        // Open the host file in advance, so that the access denied by the host
+        // is reported at the open time
+        if let Some(hnode) = inode.downcast_ref::<HNode>() {
+            hnode
+                .open(access_mode.readable(), access_mode.writable())
+                .map_err(|e| match e {
+                    FsError::PermError => errno!(EACCES, "host file not accessible"),
+                    e => errno!(e),
+                })?;
+        }
         let creation_flags = CreationFlags::from_bits_truncate(flags);
         if creation_flags.should_truncate()
             && inode.metadata()?.type_ == FileType::File
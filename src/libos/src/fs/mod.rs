@@ -17,31 +17,38 @@ use crate::config::ConfigMount;
 
 pub use self::event_file::{AsEvent, EventCreationFlags, EventFile};
 pub use self::events::{AtomicIoEvents, IoEvents, IoNotifier};
+pub use self::fifo::FifoFile;
 pub use self::file::{File, FileRef};
 pub use self::file_ops::{
     occlum_ocall_ioctl, utimbuf_t, AccessMode, BuiltinIoctlNum, CreationFlags, FallocateFlags,
     FileMode, IfConf, IoctlCmd, Stat, StatusFlags, StructuredIoctlArgType, StructuredIoctlNum,
     STATUS_FLAGS_MASK,
 };
-pub use self::file_table::{FileDesc, FileTable, FileTableEvent, FileTableNotifier};
+pub use self::file_table::{FileDesc, FileTable};
 pub use self::fs_ops::Statfs;
 pub use self::fs_view::FsView;
 pub use self::host_fd::HostFd;
+pub use self::hostfs::HNode;
 pub use self::inode_file::{AsINodeFile, INodeExt, INodeFile};
 pub use self::locks::flock::{Flock, FlockList, FlockOps, FlockType};
 pub use self::locks::range_lock::{
     FileRange, RangeLock, RangeLockBuilder, RangeLockList, RangeLockType, OFFSET_MAX,
 };
+pub use self::mount_table::{normalize_path, MOUNT_TABLE};
 pub use self::pipe::PipeType;
+pub use self::pty::{Pty, PtyMaster, PtySlave};
 pub use self::rootfs::ROOT_FS;
 pub use self::stdio::{HostStdioFds, StdinFile, StdoutFile};
 pub use self::syscalls::*;
 pub use self::timer_file::{AsTimer, TimerCreationFlags, TimerFile};
 
+mod anon_dev;
 pub mod channel;
 mod dev_fs;
+mod encfs;
 mod event_file;
 mod events;
+mod fifo;
 mod file;
 mod file_ops;
 mod file_table;
@@ -51,12 +58,16 @@ mod host_fd;
 mod hostfs;
 mod inode_file;
 mod locks;
+mod mount_table;
 mod pipe;
 mod procfs;
+mod pty;
 mod rootfs;
 mod sefs;
+mod sized_ramfs;
 mod stdio;
 mod syscalls;
+mod sysfs;
 mod timer_file;
 
 /// Split a `path` to (`dir_path`, `file_name`).
@@ -0,0 +1,165 @@
+//! The table of mounted file systems.
+//!
+//! The mount table records the mount points in the order of mounting. It is
+//! maintained along with the `ROOT_FS`, and is used to generate the contents
+//! of /proc/mounts and /proc/[pid]/mountinfo.
+use super::*;
+use config::ConfigMountFsType;
+use std::path::{Component, PathBuf};
+
+lazy_static! {
+    pub static ref MOUNT_TABLE: RwLock<MountTable> = RwLock::new(MountTable::new());
+}
+
+// The ID of the root mount, the parent ID of the root mount is itself
+const ROOT_MOUNT_ID: usize = 1;
+
+#[derive(Debug, Clone)]
+pub struct MountEntry {
+    id: usize,
+    parent_id: usize,
+    source: String,
+    target: PathBuf,
+    fs_type: ConfigMountFsType,
+}
+
+impl MountEntry {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn parent_id(&self) -> usize {
+        self.parent_id
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    pub fn fs_type(&self) -> &ConfigMountFsType {
+        &self.fs_type
+    }
+}
+
+#[derive(Debug)]
+pub struct MountTable {
+    entries: Vec<MountEntry>,
+    next_id: usize,
+}
+
+impl MountTable {
+    fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            next_id: ROOT_MOUNT_ID,
+        }
+    }
+
+    /// Reset the table with the mount configs which are used to build a new rootfs.
+    pub fn reset(&mut self, mount_configs: &Vec<ConfigMount>) {
+        self.entries.clear();
+        self.next_id = ROOT_MOUNT_ID;
+        // The root mount must be the first entry
+        if let Some(root_mc) = mount_configs.iter().find(|mc| mc.target == Path::new("/")) {
+            self.add(root_mc);
+        }
+        for mc in mount_configs
+            .iter()
+            .filter(|mc| mc.target != Path::new("/"))
+        {
+            self.add(mc);
+        }
+    }
+
+    /// Add a new mount point to the table.
+    ///
+    /// The target is normalized lexically, the same as the one to remove.
+    pub fn add(&mut self, mc: &ConfigMount) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let target = normalize_path(&mc.target);
+        // The parent is the latest mount whose target is the longest prefix of the new one
+        let parent_id = self
+            .entries
+            .iter()
+            .filter(|entry| target.starts_with(&entry.target) && entry.target != target)
+            .max_by_key(|entry| entry.target.as_os_str().len())
+            .map(|entry| entry.id)
+            .unwrap_or(id);
+        let source = match mc.type_ {
+            ConfigMountFsType::TYPE_SEFS
+            | ConfigMountFsType::TYPE_HOSTFS
+            | ConfigMountFsType::TYPE_ENCFS => mc
+                .source
+                .as_ref()
+                .map(|source| source.to_string_lossy().into_owned())
+                .unwrap_or_else(|| mc.type_.as_str().to_owned()),
+            _ => mc.type_.as_str().to_owned(),
+        };
+        self.entries.push(MountEntry {
+            id,
+            parent_id,
+            source,
+            target,
+            fs_type: mc.type_.clone(),
+        });
+    }
+
+    /// Remove the latest mount point on the target from the table.
+    ///
+    /// The target is normalized lexically, so "/mnt/" and "/mnt/../mnt" are
+    /// the same as "/mnt".
+    pub fn remove(&mut self, target: &Path) {
+        let target = normalize_path(target);
+        if let Some(idx) = self
+            .entries
+            .iter()
+            .rposition(|entry| entry.target == target)
+        {
+            self.entries.remove(idx);
+        }
+    }
+
+    pub fn entries(&self) -> &[MountEntry] {
+        &self.entries
+    }
+
+    /// Translate the absolute path into the path on the host if it is in a
+    /// HostFS mount, e.g., "/host/run/app.sock" is translated into
+    /// "<source>/run/app.sock" if "/host" is mounted from "<source>".
+    ///
+    /// The path is normalized lexically, i.e., the symlinks are not followed.
+    pub fn hostfs_path(&self, path: &Path) -> Option<PathBuf> {
+        let normalized = normalize_path(path);
+        // The latest mount shadows the earlier ones on the same target
+        let entry = self
+            .entries
+            .iter()
+            .filter(|entry| normalized.starts_with(&entry.target))
+            .max_by_key(|entry| entry.target.as_os_str().len())?;
+        if entry.fs_type != ConfigMountFsType::TYPE_HOSTFS {
+            return None;
+        }
+        let rest = normalized.strip_prefix(&entry.target).ok()?;
+        Some(Path::new(&entry.source).join(rest))
+    }
+}
+
+/// Resolve the "." and ".." components of the absolute path lexically.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::from("/");
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => normalized.push(name),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => {}
+        }
+    }
+    normalized
+}
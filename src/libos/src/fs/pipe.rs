@@ -1,15 +1,94 @@
 use atomic::{Atomic, Ordering};
+use std::sync::atomic::AtomicUsize;
+use std::sync::Weak;
 
 use super::channel::{Channel, Consumer, Producer};
 use super::*;
-use net::PollEventFlags;
+use crate::vm::PAGE_SIZE;
 
-// TODO: Add F_SETPIPE_SZ in fcntl to dynamically change the size of pipe
-// to improve memory efficiency. This value is got from /proc/sys/fs/pipe-max-size on linux.
-pub const PIPE_BUF_SIZE: usize = 1024 * 1024;
+// The buffer size of the new pipes, same with Linux. It can be changed by
+// F_SETPIPE_SZ in fcntl up to /proc/sys/fs/pipe-max-size.
+pub const PIPE_BUF_SIZE: usize = 64 * 1024;
+
+// The default of /proc/sys/fs/pipe-max-size, same with Linux
+const PIPE_MAX_SIZE_DEFAULT: usize = 1024 * 1024;
+// The buffer of a pipe is allocated from the enclave heap, so the size of the
+// buffer is limited to avoid exhausting the heap.
+const PIPE_MAX_SIZE_LIMIT: usize = 16 * 1024 * 1024;
+
+static PIPE_MAX_SIZE: AtomicUsize = AtomicUsize::new(PIPE_MAX_SIZE_DEFAULT);
+
+/// Get the max buffer size that F_SETPIPE_SZ can set, which is shown in
+/// /proc/sys/fs/pipe-max-size.
+pub fn pipe_max_size() -> usize {
+    PIPE_MAX_SIZE.load(Ordering::Relaxed)
+}
+
+/// Set the max buffer size that F_SETPIPE_SZ can set.
+///
+/// Same with Linux, the size is rounded up to a power of two pages.
+pub fn set_pipe_max_size(size: usize) -> Result<()> {
+    if size == 0 || size > PIPE_MAX_SIZE_LIMIT {
+        return_errno!(EINVAL, "invalid pipe size");
+    }
+    let size = size.max(PAGE_SIZE).next_power_of_two();
+    PIPE_MAX_SIZE.store(size, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Resize the buffer of a pipe or FIFO for F_SETPIPE_SZ, returns the new size.
+///
+/// Same with Linux, the size is rounded up to a power of two pages, and it
+/// can not be less than the data in the buffer.
+pub fn resize_pipe(producer: &Producer<u8>, consumer: &Consumer<u8>, size: usize) -> Result<usize> {
+    if size > pipe_max_size() {
+        return_errno!(EPERM, "the pipe size exceeds pipe-max-size");
+    }
+    let size = size.max(PAGE_SIZE).next_power_of_two();
+    if size < consumer.items_to_consume() {
+        return_errno!(EBUSY, "the pipe size is less than the data in the pipe");
+    }
+    producer.resize(consumer, size);
+    Ok(size)
+}
+
+/// Get the buffer size of the pipe or FIFO for F_GETPIPE_SZ.
+pub fn do_get_pipe_size(file: &FileRef) -> Result<usize> {
+    if let Ok(reader) = file.as_pipe_reader() {
+        Ok(reader.consumer.capacity())
+    } else if let Ok(writer) = file.as_pipe_writer() {
+        Ok(writer.producer.capacity())
+    } else if let Some(fifo_file) = file.as_any().downcast_ref::<FifoFile>() {
+        Ok(fifo_file.pipe_size())
+    } else {
+        return_errno!(EBADF, "not a pipe");
+    }
+}
+
+/// Set the buffer size of the pipe or FIFO for F_SETPIPE_SZ.
+pub fn do_set_pipe_size(file: &FileRef, size: usize) -> Result<usize> {
+    if let Ok(reader) = file.as_pipe_reader() {
+        match reader.producer.upgrade() {
+            Some(producer) => resize_pipe(&producer, &reader.consumer, size),
+            // No one writes to the pipe any more
+            None => Ok(reader.consumer.capacity()),
+        }
+    } else if let Ok(writer) = file.as_pipe_writer() {
+        match writer.consumer.upgrade() {
+            Some(consumer) => resize_pipe(&writer.producer, &consumer, size),
+            // No one reads from the pipe any more
+            None => Ok(writer.producer.capacity()),
+        }
+    } else if let Some(fifo_file) = file.as_any().downcast_ref::<FifoFile>() {
+        fifo_file.set_pipe_size(size)
+    } else {
+        return_errno!(EBADF, "not a pipe");
+    }
+}
 
 pub fn pipe(flags: StatusFlags) -> Result<(PipeReader, PipeWriter)> {
     let (producer, consumer) = Channel::new(PIPE_BUF_SIZE)?.split();
+    let (producer, consumer) = (Arc::new(producer), Arc::new(consumer));
 
     // Only O_NONBLOCK and O_DIRECT can be applied during pipe creation
     let valid_flags = flags & (StatusFlags::O_NONBLOCK | StatusFlags::O_DIRECT);
@@ -20,18 +99,23 @@ pub fn pipe(flags: StatusFlags) -> Result<(PipeReader, PipeWriter)> {
 
     Ok((
         PipeReader {
+            producer: Arc::downgrade(&producer),
             consumer: consumer,
             status_flags: Atomic::new(valid_flags),
         },
         PipeWriter {
             producer: producer,
+            consumer: Arc::downgrade(&consumer),
             status_flags: Atomic::new(valid_flags),
         },
     ))
 }
 
 pub struct PipeReader {
-    consumer: Consumer<u8>,
+    // The peer is only used to resize the pipe, which should not keep the
+    // pipe open for writing
+    producer: Weak<Producer<u8>>,
+    consumer: Arc<Consumer<u8>>,
     status_flags: Atomic<StatusFlags>,
 }
 
@@ -89,12 +173,6 @@ impl File for PipeReader {
         Ok(())
     }
 
-    fn poll(&self) -> Result<PollEventFlags> {
-        warn!("poll is not supported for pipe");
-        let events = PollEventFlags::empty();
-        Ok(events)
-    }
-
     fn poll_new(&self) -> IoEvents {
         self.consumer.poll()
     }
@@ -129,7 +207,9 @@ impl PipeReader {
 }
 
 pub struct PipeWriter {
-    producer: Producer<u8>,
+    producer: Arc<Producer<u8>>,
+    // The peer is only used to resize the pipe
+    consumer: Weak<Consumer<u8>>,
     status_flags: Atomic<StatusFlags>,
 }
 
@@ -191,12 +271,6 @@ impl File for PipeWriter {
         Ok(())
     }
 
-    fn poll(&self) -> Result<PollEventFlags> {
-        warn!("poll is not supported for pipe");
-        let events = PollEventFlags::empty();
-        Ok(events)
-    }
-
     fn poll_new(&self) -> IoEvents {
         self.producer.poll()
     }
@@ -4,8 +4,8 @@ use std::untrusted::fs;
 pub struct CpuInfoINode;
 
 impl CpuInfoINode {
-    pub fn new() -> Arc<dyn INode> {
-        Arc::new(File::new(Self))
+    pub fn new(ids: &InodeIds) -> Arc<dyn INode> {
+        Arc::new(File::new(Self, ids))
     }
 }
 
@@ -0,0 +1,38 @@
+use super::*;
+use crate::process::table::{get_all_processes, get_all_threads};
+use crate::process::ThreadStatus;
+
+/// The load averages are not tracked by LibOS, so the number of the running
+/// threads at the moment is used as the load averages of all the periods.
+pub struct LoadAvgINode;
+
+impl LoadAvgINode {
+    pub fn new(ids: &InodeIds) -> Arc<dyn INode> {
+        Arc::new(File::new(Self, ids))
+    }
+}
+
+impl ProcINode for LoadAvgINode {
+    fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
+        let threads = get_all_threads();
+        let running = threads
+            .iter()
+            .filter(|thread| thread.status() == ThreadStatus::Running)
+            .count();
+        let last_pid = get_all_processes()
+            .iter()
+            .map(|process| process.pid())
+            .max()
+            .unwrap_or(0);
+        Ok(format!(
+            "{}.00 {}.00 {}.00 {}/{} {}\n",
+            running,
+            running,
+            running,
+            running,
+            threads.len(),
+            last_pid
+        )
+        .into_bytes())
+    }
+}
@@ -6,8 +6,8 @@ pub struct MemInfoINode;
 const KB: usize = 1024;
 
 impl MemInfoINode {
-    pub fn new() -> Arc<dyn INode> {
-        Arc::new(File::new(Self))
+    pub fn new(ids: &InodeIds) -> Arc<dyn INode> {
+        Arc::new(File::new(Self, ids))
     }
 }
 
@@ -1,31 +1,43 @@
 use super::*;
 use alloc::sync::{Arc, Weak};
 use rcore_fs::vfs;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use super::anon_dev::alloc_anon_dev;
 use crate::process::pid_t;
 use crate::process::table::get_all_processes;
 
 use self::cpuinfo::CpuInfoINode;
+use self::loadavg::LoadAvgINode;
 use self::meminfo::MemInfoINode;
-use self::pid::LockedPidDirINode;
-use self::proc_inode::{Dir, DirProcINode, File, ProcINode, SymLink};
+use self::mounts::MountsSymINode;
+use self::pid::{pid_entry_ino, LockedPidDirINode, PidEntry};
+use self::proc_inode::{
+    Dir, DirProcINode, File, InodeIds, LockedStaticDirINode, ProcINode, SymLink,
+};
 use self::self_::SelfSymINode;
 use self::stat::StatINode;
+use self::sys::init_sys_dir;
+use self::uptime::UptimeINode;
+use self::version::VersionINode;
 
 mod cpuinfo;
+mod loadavg;
 mod meminfo;
+mod mounts;
 mod pid;
-mod proc_inode;
+pub(super) mod proc_inode;
 mod self_;
 mod stat;
+mod sys;
+mod uptime;
+mod version;
 
 // Same with the procfs on Linux
 const PROC_SUPER_MAGIC: usize = 0x9fa0;
 
-// Use the same inode number for all the inodes in procfs, the value is
-// arbitrarily chosen, and it should not be zero.
-// TODO: Assign different inode numbers for different inodes
-pub const PROC_INO: usize = 0x63fd_40e5;
+// Same with the procfs on Linux
+const PROC_ROOT_INO: usize = 1;
 
 /// Proc file system
 pub struct ProcFS {
@@ -58,20 +70,28 @@ impl FileSystem for ProcFS {
 }
 
 impl ProcFS {
-    /// Create a new `ProcFS`
+    /// Create a new `ProcFS`, which has its own device ID.
     pub fn new() -> Arc<ProcFS> {
+        let ids = InodeIds::new(PROC_ROOT_INO + 1);
         let fs = {
-            let root = Arc::new(Dir::new(LockedProcRootINode(RwLock::new(ProcRootINode {
-                non_volatile_entries: HashMap::new(),
-                this: Weak::default(),
-            }))));
+            let root = Arc::new(Dir::new_with_id(
+                LockedProcRootINode(
+                    RwLock::new(ProcRootINode {
+                        non_volatile_entries: HashMap::new(),
+                        this: Weak::default(),
+                    }),
+                    AtomicUsize::new(0),
+                ),
+                ids.dev(),
+                PROC_ROOT_INO,
+            ));
             ProcFS {
                 root,
                 self_ref: Weak::default(),
             }
             .wrap()
         };
-        fs.root.inner().init(&fs);
+        fs.root.inner().init(&fs, &ids);
         fs
     }
 
@@ -88,7 +108,12 @@ impl ProcFS {
     }
 }
 
-struct LockedProcRootINode(RwLock<ProcRootINode>);
+struct LockedProcRootINode(
+    RwLock<ProcRootINode>,
+    // The number of the non volatile subdirectories, which is kept out of
+    // the lock like the one of `LockedStaticDirINode`.
+    AtomicUsize,
+);
 
 struct ProcRootINode {
     non_volatile_entries: HashMap<String, Arc<dyn INode>>,
@@ -96,23 +121,50 @@ struct ProcRootINode {
 }
 
 impl LockedProcRootINode {
-    fn init(&self, fs: &Arc<ProcFS>) {
+    fn init(&self, fs: &Arc<ProcFS>, ids: &InodeIds) {
         let mut file = self.0.write().unwrap();
         file.this = Arc::downgrade(&fs.root);
         // TODO: Add more entries for root.
         // All [pid] entries are lazy-initialized at the find() step.
-        let cpuinfo_inode = CpuInfoINode::new();
+        let cpuinfo_inode = CpuInfoINode::new(ids);
         file.non_volatile_entries
             .insert(String::from("cpuinfo"), cpuinfo_inode);
-        let meminfo_inode = MemInfoINode::new();
+        let meminfo_inode = MemInfoINode::new(ids);
         file.non_volatile_entries
             .insert(String::from("meminfo"), meminfo_inode);
-        let self_inode = SelfSymINode::new();
+        let self_inode = SelfSymINode::new(ids);
         file.non_volatile_entries
             .insert(String::from("self"), self_inode);
-        let stat_inode = StatINode::new();
+        let stat_inode = StatINode::new(ids);
         file.non_volatile_entries
             .insert(String::from("stat"), stat_inode);
+        let mounts_inode = MountsSymINode::new(ids);
+        file.non_volatile_entries
+            .insert(String::from("mounts"), mounts_inode);
+        let uptime_inode = UptimeINode::new(ids);
+        file.non_volatile_entries
+            .insert(String::from("uptime"), uptime_inode);
+        let loadavg_inode = LoadAvgINode::new(ids);
+        file.non_volatile_entries
+            .insert(String::from("loadavg"), loadavg_inode);
+        let version_inode = VersionINode::new(ids);
+        file.non_volatile_entries
+            .insert(String::from("version"), version_inode);
+        let sys_inode = init_sys_dir(&(Arc::clone(&fs.root) as _), ids);
+        file.non_volatile_entries
+            .insert(String::from("sys"), sys_inode);
+
+        let subdirs = file
+            .non_volatile_entries
+            .values()
+            .filter(|inode| {
+                inode
+                    .metadata()
+                    .map(|metadata| metadata.type_ == vfs::FileType::Dir)
+                    .unwrap_or(false)
+            })
+            .count();
+        self.1.store(subdirs, Ordering::Relaxed);
     }
 }
 
@@ -127,7 +179,8 @@ impl DirProcINode for LockedProcRootINode {
         }
 
         if let Ok(pid) = name.parse::<pid_t>() {
-            let pid_inode = LockedPidDirINode::new(pid, file.this.upgrade().unwrap())?;
+            let this = file.this.upgrade().unwrap();
+            let pid_inode = LockedPidDirINode::new(pid, this.dev(), this)?;
             Ok(pid_inode)
         } else if let Some(inode) = file.non_volatile_entries.get(name) {
             Ok(Arc::clone(inode))
@@ -190,11 +243,15 @@ impl DirProcINode for LockedProcRootINode {
             write_entry!(
                 &mut ctx,
                 &process.pid().to_string(),
-                PROC_INO,
+                pid_entry_ino(process.pid(), PidEntry::Dir),
                 vfs::FileType::Dir
             );
         }
 
         Ok(ctx.written_len())
     }
+
+    fn nlinks(&self) -> usize {
+        2 + self.1.load(Ordering::Relaxed) + get_all_processes().len()
+    }
 }
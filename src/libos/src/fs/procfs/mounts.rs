@@ -0,0 +1,16 @@
+use super::*;
+
+/// The /proc/mounts is a symbolic link to /proc/self/mounts, same with Linux.
+pub struct MountsSymINode;
+
+impl MountsSymINode {
+    pub fn new(ids: &InodeIds) -> Arc<dyn INode> {
+        Arc::new(SymLink::new(Self, ids))
+    }
+}
+
+impl ProcINode for MountsSymINode {
+    fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
+        Ok(Vec::from("self/mounts"))
+    }
+}
@@ -3,8 +3,12 @@ use super::*;
 pub struct ProcCwdSymINode(ProcessRef);
 
 impl ProcCwdSymINode {
-    pub fn new(process_ref: &ProcessRef) -> Arc<dyn INode> {
-        Arc::new(SymLink::new(Self(Arc::clone(process_ref))))
+    pub fn new(process_ref: &ProcessRef, dev: usize) -> Arc<dyn INode> {
+        Arc::new(SymLink::new_with_id(
+            Self(Arc::clone(process_ref)),
+            dev,
+            pid_entry_ino(process_ref.pid(), PidEntry::Cwd),
+        ))
     }
 }
 
@@ -0,0 +1,29 @@
+use super::*;
+
+pub struct ProcEnvironINode(ProcessRef);
+
+impl ProcEnvironINode {
+    pub fn new(process_ref: &ProcessRef, dev: usize) -> Arc<dyn INode> {
+        Arc::new(File::new_with_id(
+            Self(Arc::clone(process_ref)),
+            dev,
+            pid_entry_ino(process_ref.pid(), PidEntry::Environ),
+        ))
+    }
+}
+
+impl ProcINode for ProcEnvironINode {
+    fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
+        let environ = if let ProcessStatus::Zombie = self.0.status() {
+            Vec::new()
+        } else {
+            // Null-terminated bytes of each environment variable
+            self.0
+                .environ()
+                .iter()
+                .flat_map(|env| env.as_bytes_with_nul().iter().cloned())
+                .collect()
+        };
+        Ok(environ)
+    }
+}
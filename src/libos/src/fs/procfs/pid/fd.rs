@@ -9,12 +9,16 @@ struct ProcFdDirINode {
 }
 
 impl LockedProcFdDirINode {
-    pub fn new(process_ref: &ProcessRef, parent: Arc<dyn INode>) -> Arc<dyn INode> {
-        let inode = Arc::new(Dir::new(Self(RwLock::new(ProcFdDirINode {
-            process_ref: Arc::clone(process_ref),
-            this: Weak::default(),
-            parent: Arc::clone(&parent),
-        }))));
+    pub fn new(process_ref: &ProcessRef, dev: usize, parent: Arc<dyn INode>) -> Arc<dyn INode> {
+        let inode = Arc::new(Dir::new_with_id(
+            Self(RwLock::new(ProcFdDirINode {
+                process_ref: Arc::clone(process_ref),
+                this: Weak::default(),
+                parent: Arc::clone(&parent),
+            })),
+            dev,
+            pid_entry_ino(process_ref.pid(), PidEntry::FdDir),
+        ));
         inode.inner().0.write().unwrap().this = Arc::downgrade(&inode);
         inode
     }
@@ -32,7 +36,8 @@ impl DirProcINode for LockedProcFdDirINode {
         let fd = name
             .parse::<FileDesc>()
             .map_err(|_| FsError::EntryNotFound)?;
-        let fd_inode = FdSymINode::new(&file.process_ref, fd)?;
+        let dev = file.this.upgrade().unwrap().dev();
+        let fd_inode = FdSymINode::new(&file.process_ref, fd, dev)?;
         Ok(fd_inode)
     }
 
@@ -69,8 +74,14 @@ impl DirProcINode for LockedProcFdDirINode {
             }
         };
         let fds = main_thread.files().lock().unwrap().fds();
+        let pid = file.process_ref.pid();
         for fd in fds.iter().skip(skipped) {
-            write_entry!(&mut ctx, &fd.to_string(), PROC_INO, vfs::FileType::SymLink);
+            write_entry!(
+                &mut ctx,
+                &fd.to_string(),
+                fd_ino(pid, *fd),
+                vfs::FileType::SymLink
+            );
         }
         Ok(ctx.written_len())
     }
@@ -79,10 +90,14 @@ impl DirProcINode for LockedProcFdDirINode {
 struct FdSymINode(FileRef);
 
 impl FdSymINode {
-    pub fn new(process_ref: &ProcessRef, fd: FileDesc) -> vfs::Result<Arc<dyn INode>> {
+    pub fn new(process_ref: &ProcessRef, fd: FileDesc, dev: usize) -> vfs::Result<Arc<dyn INode>> {
         let main_thread = process_ref.main_thread().ok_or(FsError::EntryNotFound)?;
         let file_ref = main_thread.file(fd).map_err(|_| FsError::EntryNotFound)?;
-        Ok(Arc::new(SymLink::new(Self(Arc::clone(&file_ref)))))
+        Ok(Arc::new(SymLink::new_with_id(
+            Self(Arc::clone(&file_ref)),
+            dev,
+            fd_ino(process_ref.pid(), fd),
+        )))
     }
 }
 
@@ -90,6 +105,10 @@ impl ProcINode for FdSymINode {
     fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
         let path = if let Ok(inode_file) = self.0.as_inode_file() {
             inode_file.abs_path().to_owned()
+        } else if let Some(pty_master) = self.0.as_any().downcast_ref::<PtyMaster>() {
+            pty_master.abs_path().to_owned()
+        } else if let Some(pty_slave) = self.0.as_any().downcast_ref::<PtySlave>() {
+            pty_slave.abs_path().to_owned()
         } else {
             // TODO: Support other file types
             // For file descriptors for pipes and sockets,
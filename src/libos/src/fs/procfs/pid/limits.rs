@@ -0,0 +1,80 @@
+use super::*;
+
+use crate::misc::resource_t;
+
+// This file is to implement /proc/self(pid)/limits file system.
+//
+// Example:
+// - cat /proc/self/limits
+// Limit                     Soft Limit           Hard Limit           Units
+// Max cpu time              unlimited            unlimited            seconds
+// Max file size             unlimited            unlimited            bytes
+// Max data size             8388608              unlimited            bytes
+// ...
+
+pub struct ProcLimitsINode(ProcessRef);
+
+impl ProcLimitsINode {
+    pub fn new(process_ref: &ProcessRef, dev: usize) -> Arc<dyn INode> {
+        Arc::new(File::new_with_id(
+            Self(Arc::clone(process_ref)),
+            dev,
+            pid_entry_ino(process_ref.pid(), PidEntry::Limits),
+        ))
+    }
+}
+
+impl ProcINode for ProcLimitsINode {
+    fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
+        let main_thread = self.0.main_thread().ok_or(FsError::EntryNotFound)?;
+        let rlimits = main_thread.rlimits().lock().unwrap().clone();
+
+        let mut result = format!(
+            "{:<26}{:<21}{:<21}{:<10}\n",
+            "Limit", "Soft Limit", "Hard Limit", "Units"
+        );
+        for (resource, name, units) in LIMITS.iter() {
+            let rlimit = rlimits.get(*resource);
+            result += &format!(
+                "{:<26}{:<21}{:<21}{:<10}\n",
+                name,
+                limit_to_string(rlimit.get_cur()),
+                limit_to_string(rlimit.get_max()),
+                units
+            );
+        }
+        Ok(result.into_bytes())
+    }
+}
+
+fn limit_to_string(limit: u64) -> String {
+    if limit == u64::max_value() {
+        String::from("unlimited")
+    } else {
+        limit.to_string()
+    }
+}
+
+// The resources in the same order with Linux
+const LIMITS: [(resource_t, &str, &str); 16] = [
+    (resource_t::RLIMIT_CPU, "Max cpu time", "seconds"),
+    (resource_t::RLIMIT_FSIZE, "Max file size", "bytes"),
+    (resource_t::RLIMIT_DATA, "Max data size", "bytes"),
+    (resource_t::RLIMIT_STACK, "Max stack size", "bytes"),
+    (resource_t::RLIMIT_CORE, "Max core file size", "bytes"),
+    (resource_t::RLIMIT_RSS, "Max resident set", "bytes"),
+    (resource_t::RLIMIT_NPROC, "Max processes", "processes"),
+    (resource_t::RLIMIT_NOFILE, "Max open files", "files"),
+    (resource_t::RLIMIT_MEMLOCK, "Max locked memory", "bytes"),
+    (resource_t::RLIMIT_AS, "Max address space", "bytes"),
+    (resource_t::RLIMIT_LOCKS, "Max file locks", "locks"),
+    (
+        resource_t::RLIMIT_SIGPENDING,
+        "Max pending signals",
+        "signals",
+    ),
+    (resource_t::RLIMIT_MSGQUEUE, "Max msgqueue size", "bytes"),
+    (resource_t::RLIMIT_NICE, "Max nice priority", ""),
+    (resource_t::RLIMIT_RTPRIO, "Max realtime priority", ""),
+    (resource_t::RLIMIT_RTTIME, "Max realtime timeout", "us"),
+];
@@ -41,8 +41,12 @@ use crate::vm::{ChunkType, VMArea, VMPerms, VMRange};
 pub struct ProcMapsINode(ProcessRef);
 
 impl ProcMapsINode {
-    pub fn new(process_ref: &ProcessRef) -> Arc<dyn INode> {
-        Arc::new(File::new(Self(Arc::clone(process_ref))))
+    pub fn new(process_ref: &ProcessRef, dev: usize) -> Arc<dyn INode> {
+        Arc::new(File::new_with_id(
+            Self(Arc::clone(process_ref)),
+            dev,
+            pid_entry_ino(process_ref.pid(), PidEntry::Maps),
+        ))
     }
 }
 
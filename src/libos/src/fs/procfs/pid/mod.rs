@@ -5,20 +5,81 @@ use crate::process::{ProcessRef, ProcessStatus};
 use self::cmdline::ProcCmdlineINode;
 use self::comm::ProcCommINode;
 use self::cwd::ProcCwdSymINode;
+use self::environ::ProcEnvironINode;
 use self::exe::ProcExeSymINode;
 use self::fd::LockedProcFdDirINode;
+use self::limits::ProcLimitsINode;
 use self::maps::ProcMapsINode;
+use self::mounts::{ProcMountInfoINode, ProcMountsINode};
 use self::root::ProcRootSymINode;
 use self::stat::ProcStatINode;
+use self::status::ProcStatusINode;
+use self::task::LockedProcTaskDirINode;
 
 mod cmdline;
 mod comm;
 mod cwd;
+mod environ;
 mod exe;
 mod fd;
+mod limits;
 mod maps;
+mod mounts;
 mod root;
 mod stat;
+mod status;
+mod task;
+
+// The directory entries which are lazy-initialized at the find() step
+const LAZY_DIR_ENTRIES: [(&str, PidEntry); 2] =
+    [("fd", PidEntry::FdDir), ("task", PidEntry::TaskDir)];
+
+/// The entries in /proc/[pid] and /proc/[pid]/task/[tid].
+///
+/// The inode number of an entry is derived from the pid (or tid) and the
+/// entry, so that it is stable no matter how many times the inode is created.
+#[derive(Clone, Copy, Debug)]
+#[repr(u32)]
+pub enum PidEntry {
+    Dir = 0,
+    Cmdline,
+    Cwd,
+    Exe,
+    Root,
+    Comm,
+    Stat,
+    Maps,
+    Status,
+    Environ,
+    Limits,
+    Mounts,
+    MountInfo,
+    FdDir,
+    TaskDir,
+    // The entries of /proc/[pid]/task/[tid], which are numbered with the tid
+    TidDir,
+    TidComm,
+    TidStatus,
+    // The symlinks in /proc/[pid]/fd, whose inode numbers are the fd plus this
+    Fd = 0x1000_0000,
+}
+
+// The inode numbers of the entries in /proc/[pid] have the highest bit set to
+// avoid conflicts with the ones of the static inodes.
+//
+// | 63 | 62 ~ 32 | 31 ~ 0 |
+// | 1  |   pid   | entry  |
+const PID_INO_FLAG: usize = 1 << 63;
+
+/// Get the inode number of the entry of the pid (or tid).
+pub fn pid_entry_ino(pid: pid_t, entry: PidEntry) -> usize {
+    PID_INO_FLAG | ((pid as usize) << 32) | entry as usize
+}
+
+/// Get the inode number of the symlink of the fd in /proc/[pid]/fd.
+fn fd_ino(pid: pid_t, fd: FileDesc) -> usize {
+    pid_entry_ino(pid, PidEntry::Fd) + fd as usize
+}
 
 pub struct LockedPidDirINode(RwLock<PidDirINode>);
 
@@ -30,13 +91,17 @@ struct PidDirINode {
 }
 
 impl LockedPidDirINode {
-    pub fn new(pid: pid_t, parent: Arc<dyn INode>) -> vfs::Result<Arc<dyn INode>> {
-        let inode = Arc::new(Dir::new(Self(RwLock::new(PidDirINode {
-            process_ref: get_process(pid).map_err(|_| FsError::EntryNotFound)?,
-            this: Weak::default(),
-            parent: Arc::clone(&parent),
-            entries: HashMap::new(),
-        }))));
+    pub fn new(pid: pid_t, dev: usize, parent: Arc<dyn INode>) -> vfs::Result<Arc<dyn INode>> {
+        let inode = Arc::new(Dir::new_with_id(
+            Self(RwLock::new(PidDirINode {
+                process_ref: get_process(pid).map_err(|_| FsError::EntryNotFound)?,
+                this: Weak::default(),
+                parent: Arc::clone(&parent),
+                entries: HashMap::new(),
+            })),
+            dev,
+            pid_entry_ino(pid, PidEntry::Dir),
+        ));
         inode.inner().0.write().unwrap().this = Arc::downgrade(&inode);
         inode.inner().init_entries()?;
         Ok(inode)
@@ -44,27 +109,45 @@ impl LockedPidDirINode {
 
     fn init_entries(&self) -> vfs::Result<()> {
         let mut file = self.0.write().unwrap();
+        let dev = file.this.upgrade().unwrap().dev();
         // cmdline
-        let cmdline_inode = ProcCmdlineINode::new(&file.process_ref);
+        let cmdline_inode = ProcCmdlineINode::new(&file.process_ref, dev);
         file.entries.insert(String::from("cmdline"), cmdline_inode);
         // cwd
-        let cwd_inode = ProcCwdSymINode::new(&file.process_ref);
+        let cwd_inode = ProcCwdSymINode::new(&file.process_ref, dev);
         file.entries.insert(String::from("cwd"), cwd_inode);
         // exe
-        let exe_inode = ProcExeSymINode::new(&file.process_ref);
+        let exe_inode = ProcExeSymINode::new(&file.process_ref, dev);
         file.entries.insert(String::from("exe"), exe_inode);
         // root
-        let root_inode = ProcRootSymINode::new(&file.process_ref);
+        let root_inode = ProcRootSymINode::new(&file.process_ref, dev);
         file.entries.insert(String::from("root"), root_inode);
         // comm
-        let comm_inode = ProcCommINode::new(&file.process_ref);
+        let comm_inode = ProcCommINode::new(&file.process_ref, dev);
         file.entries.insert(String::from("comm"), comm_inode);
         // stat
-        let stat_inode = ProcStatINode::new(&file.process_ref);
+        let stat_inode = ProcStatINode::new(&file.process_ref, dev);
         file.entries.insert(String::from("stat"), stat_inode);
         // maps
-        let maps_inode = ProcMapsINode::new(&file.process_ref);
+        let maps_inode = ProcMapsINode::new(&file.process_ref, dev);
         file.entries.insert(String::from("maps"), maps_inode);
+        // status
+        let status_inode = ProcStatusINode::new(&file.process_ref, dev);
+        file.entries.insert(String::from("status"), status_inode);
+        // environ
+        let environ_inode = ProcEnvironINode::new(&file.process_ref, dev);
+        file.entries.insert(String::from("environ"), environ_inode);
+        // limits
+        let limits_inode = ProcLimitsINode::new(&file.process_ref, dev);
+        file.entries.insert(String::from("limits"), limits_inode);
+        // mounts
+        let pid = file.process_ref.pid();
+        let mounts_inode = ProcMountsINode::new(pid, dev);
+        file.entries.insert(String::from("mounts"), mounts_inode);
+        // mountinfo
+        let mountinfo_inode = ProcMountInfoINode::new(pid, dev);
+        file.entries
+            .insert(String::from("mountinfo"), mountinfo_inode);
 
         Ok(())
     }
@@ -79,15 +162,20 @@ impl DirProcINode for LockedPidDirINode {
         if name == ".." {
             return Ok(Arc::clone(&file.parent));
         }
-        // The 'fd' entry holds 1 Arc of LockedPidDirINode, so the LockedPidDirINode
-        // ifself will hold 2 Arcs. This makes it cannot be dropped automatically.
-        // We initialize the 'fd' here to avoid this.
+        // The 'fd' and 'task' entries hold 1 Arc of LockedPidDirINode, so the
+        // LockedPidDirINode ifself will hold 2 Arcs. This makes it cannot be dropped
+        // automatically. We initialize the 'fd' and 'task' here to avoid this.
         // TODO:: Try to find a better solution.
         if name == "fd" {
-            let fd_inode =
-                LockedProcFdDirINode::new(&file.process_ref, file.this.upgrade().unwrap());
+            let this = file.this.upgrade().unwrap();
+            let fd_inode = LockedProcFdDirINode::new(&file.process_ref, this.dev(), this);
             return Ok(fd_inode);
         }
+        if name == "task" {
+            let this = file.this.upgrade().unwrap();
+            let task_inode = LockedProcTaskDirINode::new(&file.process_ref, this.dev(), this);
+            return Ok(task_inode);
+        }
 
         if let Some(inode) = file.entries.get(name) {
             Ok(Arc::clone(inode))
@@ -104,8 +192,8 @@ impl DirProcINode for LockedPidDirINode {
                 let file = self.0.read().unwrap();
                 if let Some(name) = file.entries.keys().nth(i - 2) {
                     Ok(name.to_owned())
-                } else if i == file.entries.len() + 2 {
-                    Ok(String::from("fd"))
+                } else if let Some((name, _)) = LAZY_DIR_ENTRIES.get(i - 2 - file.entries.len()) {
+                    Ok(String::from(*name))
                 } else {
                     Err(FsError::EntryNotFound)
                 }
@@ -126,10 +214,26 @@ impl DirProcINode for LockedPidDirINode {
             write_inode_entry!(&mut ctx, name, inode);
         }
 
-        // Write the fd entry
-        if idx <= 2 + file.entries.len() {
-            write_entry!(&mut ctx, "fd", PROC_INO, vfs::FileType::Dir);
+        // Write the fd and task entries
+        let skipped = if idx < 2 + file.entries.len() {
+            0
+        } else {
+            idx - 2 - file.entries.len()
+        };
+        let pid = file.process_ref.pid();
+        for (name, entry) in LAZY_DIR_ENTRIES.iter().skip(skipped) {
+            write_entry!(
+                &mut ctx,
+                name,
+                pid_entry_ino(pid, *entry),
+                vfs::FileType::Dir
+            );
         }
         Ok(ctx.written_len())
     }
+
+    fn nlinks(&self) -> usize {
+        // The fd and task are the only subdirectories
+        2 + LAZY_DIR_ENTRIES.len()
+    }
 }
@@ -0,0 +1,83 @@
+use super::*;
+
+use crate::fs::mount_table::MOUNT_TABLE;
+
+// This file is to implement /proc/self(pid)/mounts and /proc/self(pid)/mountinfo
+// file system. All the processes share the same mount table in Occlum.
+//
+// Example:
+// - cat /proc/self/mounts
+// unionfs / unionfs rw,relatime 0 0
+// /path/to/occlum_instance/image host hostfs rw,relatime 0 0
+//
+// - cat /proc/self/mountinfo
+// 1 1 0:1 / / rw,relatime - unionfs unionfs rw
+// 2 1 0:2 / /host rw,relatime - hostfs /path/to/occlum_instance/image rw
+//
+// Known limitation:
+// - Mount options are not provided
+
+pub struct ProcMountsINode;
+
+impl ProcMountsINode {
+    pub fn new(pid: pid_t, dev: usize) -> Arc<dyn INode> {
+        Arc::new(File::new_with_id(
+            Self,
+            dev,
+            pid_entry_ino(pid, PidEntry::Mounts),
+        ))
+    }
+}
+
+impl ProcINode for ProcMountsINode {
+    fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
+        let mount_table = MOUNT_TABLE.read().unwrap();
+        let result = mount_table
+            .entries()
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{} {} {} rw,relatime 0 0\n",
+                    entry.source(),
+                    entry.target().display(),
+                    entry.fs_type().as_str()
+                )
+            })
+            .fold(String::new(), |acc, line| acc + &line);
+        Ok(result.into_bytes())
+    }
+}
+
+pub struct ProcMountInfoINode;
+
+impl ProcMountInfoINode {
+    pub fn new(pid: pid_t, dev: usize) -> Arc<dyn INode> {
+        Arc::new(File::new_with_id(
+            Self,
+            dev,
+            pid_entry_ino(pid, PidEntry::MountInfo),
+        ))
+    }
+}
+
+impl ProcINode for ProcMountInfoINode {
+    fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
+        let mount_table = MOUNT_TABLE.read().unwrap();
+        let result = mount_table
+            .entries()
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{} {} 0:{} / {} rw,relatime - {} {} rw\n",
+                    entry.id(),
+                    entry.parent_id(),
+                    entry.id(),
+                    entry.target().display(),
+                    entry.fs_type().as_str(),
+                    entry.source()
+                )
+            })
+            .fold(String::new(), |acc, line| acc + &line);
+        Ok(result.into_bytes())
+    }
+}
@@ -0,0 +1,143 @@
+use super::*;
+
+use crate::process::ThreadRef;
+
+// This file is to implement /proc/self(pid)/status file system.
+//
+// The fields are a subset of the fields in Linux, filled with the LibOS's
+// information. Occlum is a single user environment, so the IDs of the user
+// and the group are always 0.
+//
+// Example:
+// - cat /proc/self/status
+// Name:   cat
+// State:  R (running)
+// Tgid:   2
+// Ngid:   0
+// Pid:    2
+// PPid:   1
+// TracerPid:      0
+// Uid:    0       0       0       0
+// Gid:    0       0       0       0
+// FDSize: 64
+// Groups: 0
+// VmPeak:   614400 kB
+// VmSize:   614400 kB
+// VmRSS:     24592 kB
+// Threads:        1
+// SigPnd: 0000000000000000
+// ShdPnd: 0000000000000000
+// SigBlk: 0000000000000000
+
+pub struct ProcStatusINode {
+    process_ref: ProcessRef,
+    // The thread shown in the file, the main thread is shown if it is None
+    tid: Option<pid_t>,
+}
+
+impl ProcStatusINode {
+    pub fn new(process_ref: &ProcessRef, dev: usize) -> Arc<dyn INode> {
+        Arc::new(File::new_with_id(
+            Self {
+                process_ref: Arc::clone(process_ref),
+                tid: None,
+            },
+            dev,
+            pid_entry_ino(process_ref.pid(), PidEntry::Status),
+        ))
+    }
+
+    pub fn new_for_thread(process_ref: &ProcessRef, tid: pid_t, dev: usize) -> Arc<dyn INode> {
+        Arc::new(File::new_with_id(
+            Self {
+                process_ref: Arc::clone(process_ref),
+                tid: Some(tid),
+            },
+            dev,
+            pid_entry_ino(tid, PidEntry::TidStatus),
+        ))
+    }
+
+    fn thread(&self) -> Option<ThreadRef> {
+        match self.tid {
+            Some(tid) => self
+                .process_ref
+                .threads()
+                .into_iter()
+                .find(|thread| thread.tid() == tid),
+            None => self.process_ref.main_thread(),
+        }
+    }
+}
+
+impl ProcINode for ProcStatusINode {
+    fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
+        let process = &self.process_ref;
+        let thread = self.thread().ok_or(FsError::EntryNotFound)?;
+        let name = String::from_utf8_lossy(thread.name().as_c_str().to_bytes()).into_owned();
+        let state = match process.status() {
+            ProcessStatus::Running => "R (running)",
+            ProcessStatus::Stopped => "T (stopped)",
+            ProcessStatus::Zombie => "Z (zombie)",
+        };
+        let tgid = process.pid();
+        let pid = thread.tid();
+        let ppid = process.parent().pid();
+        let fd_size = thread.files().lock().unwrap().table().len();
+        let vm_size = thread.vm().get_process_range().size();
+        let vm_rss = get_vm_rss(&thread);
+        let threads = process.threads().len();
+        let sig_pnd = thread.sig_queues().read().unwrap().pending().as_u64();
+        let shd_pnd = process.sig_queues().read().unwrap().pending().as_u64();
+        let sig_blk = thread.sig_mask().read().unwrap().as_u64();
+
+        let result = format!(
+            "Name:\t{}\n\
+             State:\t{}\n\
+             Tgid:\t{}\n\
+             Ngid:\t0\n\
+             Pid:\t{}\n\
+             PPid:\t{}\n\
+             TracerPid:\t0\n\
+             Uid:\t0\t0\t0\t0\n\
+             Gid:\t0\t0\t0\t0\n\
+             FDSize:\t{}\n\
+             Groups:\t0\n\
+             VmPeak:\t{:>8} kB\n\
+             VmSize:\t{:>8} kB\n\
+             VmRSS:\t{:>8} kB\n\
+             Threads:\t{}\n\
+             SigPnd:\t{:016x}\n\
+             ShdPnd:\t{:016x}\n\
+             SigBlk:\t{:016x}\n",
+            name,
+            state,
+            tgid,
+            pid,
+            ppid,
+            fd_size,
+            vm_size / KB,
+            vm_size / KB,
+            vm_rss / KB,
+            threads,
+            sig_pnd,
+            shd_pnd,
+            sig_blk,
+        )
+        .into_bytes();
+        Ok(result)
+    }
+}
+
+const KB: usize = 1024;
+
+// The memory of a process is committed once it is mapped in SGX, so the
+// resident size is approximately the size of the used memory chunks.
+fn get_vm_rss(thread: &ThreadRef) -> usize {
+    let process_vm = thread.vm();
+    let mem_chunks = process_vm.mem_chunks().read().unwrap();
+    mem_chunks
+        .iter()
+        .map(|chunk| chunk.range().size() - chunk.free_size())
+        .sum()
+}
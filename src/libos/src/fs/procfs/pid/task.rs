@@ -0,0 +1,196 @@
+use super::*;
+
+use crate::process::ThreadRef;
+
+use super::status::ProcStatusINode;
+
+pub struct LockedProcTaskDirINode(RwLock<ProcTaskDirINode>);
+
+struct ProcTaskDirINode {
+    process_ref: ProcessRef,
+    this: Weak<Dir<LockedProcTaskDirINode>>,
+    parent: Arc<dyn INode>,
+}
+
+impl LockedProcTaskDirINode {
+    pub fn new(process_ref: &ProcessRef, dev: usize, parent: Arc<dyn INode>) -> Arc<dyn INode> {
+        let inode = Arc::new(Dir::new_with_id(
+            Self(RwLock::new(ProcTaskDirINode {
+                process_ref: Arc::clone(process_ref),
+                this: Weak::default(),
+                parent: Arc::clone(&parent),
+            })),
+            dev,
+            pid_entry_ino(process_ref.pid(), PidEntry::TaskDir),
+        ));
+        inode.inner().0.write().unwrap().this = Arc::downgrade(&inode);
+        inode
+    }
+}
+
+impl DirProcINode for LockedProcTaskDirINode {
+    fn find(&self, name: &str) -> vfs::Result<Arc<dyn INode>> {
+        let file = self.0.read().unwrap();
+        if name == "." {
+            return Ok(file.this.upgrade().unwrap());
+        }
+        if name == ".." {
+            return Ok(Arc::clone(&file.parent));
+        }
+        let tid = name.parse::<pid_t>().map_err(|_| FsError::EntryNotFound)?;
+        let thread = file
+            .process_ref
+            .threads()
+            .into_iter()
+            .find(|thread| thread.tid() == tid)
+            .ok_or(FsError::EntryNotFound)?;
+        let this = file.this.upgrade().unwrap();
+        let tid_inode = LockedTidDirINode::new(&file.process_ref, &thread, this.dev(), this);
+        Ok(tid_inode)
+    }
+
+    fn get_entry(&self, id: usize) -> vfs::Result<String> {
+        match id {
+            0 => Ok(String::from(".")),
+            1 => Ok(String::from("..")),
+            i => {
+                let file = self.0.read().unwrap();
+                let threads = file.process_ref.threads();
+                let thread = threads.iter().nth(i - 2).ok_or(FsError::EntryNotFound)?;
+                Ok(thread.tid().to_string())
+            }
+        }
+    }
+
+    fn iterate_entries(&self, mut ctx: &mut DirentWriterContext) -> vfs::Result<usize> {
+        let file = self.0.read().unwrap();
+        let idx = ctx.pos();
+
+        // Write first two special entries
+        write_first_two_entries!(idx, &mut ctx, &file);
+
+        // Write the tid entries
+        let skipped = if idx < 2 { 0 } else { idx - 2 };
+        for thread in file.process_ref.threads().iter().skip(skipped) {
+            write_entry!(
+                &mut ctx,
+                &thread.tid().to_string(),
+                pid_entry_ino(thread.tid(), PidEntry::TidDir),
+                vfs::FileType::Dir
+            );
+        }
+        Ok(ctx.written_len())
+    }
+
+    fn nlinks(&self) -> usize {
+        let file = self.0.read().unwrap();
+        2 + file.process_ref.threads().len()
+    }
+}
+
+struct LockedTidDirINode(RwLock<TidDirINode>);
+
+struct TidDirINode {
+    this: Weak<Dir<LockedTidDirINode>>,
+    parent: Arc<dyn INode>,
+    entries: HashMap<String, Arc<dyn INode>>,
+}
+
+impl LockedTidDirINode {
+    fn new(
+        process_ref: &ProcessRef,
+        thread: &ThreadRef,
+        dev: usize,
+        parent: Arc<dyn INode>,
+    ) -> Arc<dyn INode> {
+        let inode = Arc::new(Dir::new_with_id(
+            Self(RwLock::new(TidDirINode {
+                this: Weak::default(),
+                parent: Arc::clone(&parent),
+                entries: HashMap::new(),
+            })),
+            dev,
+            pid_entry_ino(thread.tid(), PidEntry::TidDir),
+        ));
+        {
+            let mut file = inode.inner().0.write().unwrap();
+            file.this = Arc::downgrade(&inode);
+            // comm
+            let comm_inode = ThreadCommINode::new(thread, dev);
+            file.entries.insert(String::from("comm"), comm_inode);
+            // status
+            let status_inode = ProcStatusINode::new_for_thread(process_ref, thread.tid(), dev);
+            file.entries.insert(String::from("status"), status_inode);
+        }
+        inode
+    }
+}
+
+impl DirProcINode for LockedTidDirINode {
+    fn find(&self, name: &str) -> vfs::Result<Arc<dyn INode>> {
+        let file = self.0.read().unwrap();
+        if name == "." {
+            return Ok(file.this.upgrade().unwrap());
+        }
+        if name == ".." {
+            return Ok(Arc::clone(&file.parent));
+        }
+        if let Some(inode) = file.entries.get(name) {
+            Ok(Arc::clone(inode))
+        } else {
+            Err(FsError::EntryNotFound)
+        }
+    }
+
+    fn get_entry(&self, id: usize) -> vfs::Result<String> {
+        match id {
+            0 => Ok(String::from(".")),
+            1 => Ok(String::from("..")),
+            i => {
+                let file = self.0.read().unwrap();
+                let name = file
+                    .entries
+                    .keys()
+                    .nth(i - 2)
+                    .ok_or(FsError::EntryNotFound)?;
+                Ok(name.to_owned())
+            }
+        }
+    }
+
+    fn iterate_entries(&self, mut ctx: &mut DirentWriterContext) -> vfs::Result<usize> {
+        let file = self.0.read().unwrap();
+        let idx = ctx.pos();
+
+        // Write first two special entries
+        write_first_two_entries!(idx, &mut ctx, &file);
+
+        // Write the normal entries
+        let skipped = if idx < 2 { 0 } else { idx - 2 };
+        for (name, inode) in file.entries.iter().skip(skipped) {
+            write_inode_entry!(&mut ctx, name, inode);
+        }
+        Ok(ctx.written_len())
+    }
+}
+
+struct ThreadCommINode(ThreadRef);
+
+impl ThreadCommINode {
+    pub fn new(thread: &ThreadRef, dev: usize) -> Arc<dyn INode> {
+        Arc::new(File::new_with_id(
+            Self(Arc::clone(thread)),
+            dev,
+            pid_entry_ino(thread.tid(), PidEntry::TidComm),
+        ))
+    }
+}
+
+impl ProcINode for ThreadCommINode {
+    fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
+        let mut comm = self.0.name().as_c_str().to_bytes().to_vec();
+        // Add '\n' at the end to make the result same with Linux
+        comm.push(b'\n');
+        Ok(comm)
+    }
+}
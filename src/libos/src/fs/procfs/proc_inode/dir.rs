@@ -2,16 +2,32 @@ use super::*;
 
 pub struct Dir<T: DirProcINode> {
     inner: T,
+    dev: usize,
+    ino: usize,
 }
 
 impl<T: DirProcINode> Dir<T> {
-    pub fn new(inner: T) -> Self {
-        Self { inner }
+    /// Create a directory which lives as long as the file system of `ids`.
+    pub fn new(inner: T, ids: &InodeIds) -> Self {
+        Self::new_with_id(inner, ids.dev(), ids.alloc_ino())
+    }
+
+    /// Create a directory with the given device ID and inode number.
+    pub fn new_with_id(inner: T, dev: usize, ino: usize) -> Self {
+        Self { inner, dev, ino }
     }
 
     pub fn inner(&self) -> &T {
         &self.inner
     }
+
+    pub fn ino(&self) -> usize {
+        self.ino
+    }
+
+    pub fn dev(&self) -> usize {
+        self.dev
+    }
 }
 
 impl<T> INode for Dir<T>
@@ -28,8 +44,8 @@ where
 
     fn metadata(&self) -> vfs::Result<Metadata> {
         Ok(Metadata {
-            dev: 0,
-            inode: PROC_INO,
+            dev: self.dev,
+            inode: self.ino,
             size: 0,
             blk_size: 0,
             blocks: 0,
@@ -38,7 +54,7 @@ where
             ctime: Timespec { sec: 0, nsec: 0 },
             type_: vfs::FileType::Dir,
             mode: 0o555,
-            nlinks: 1,
+            nlinks: self.inner.nlinks(),
             uid: 0,
             gid: 0,
             rdev: 0,
@@ -2,11 +2,19 @@ use super::*;
 
 pub struct File<T: ProcINode> {
     inner: T,
+    dev: usize,
+    ino: usize,
 }
 
 impl<T: ProcINode> File<T> {
-    pub fn new(inner: T) -> Self {
-        Self { inner }
+    /// Create a file which lives as long as the file system of `ids`.
+    pub fn new(inner: T, ids: &InodeIds) -> Self {
+        Self::new_with_id(inner, ids.dev(), ids.alloc_ino())
+    }
+
+    /// Create a file with the given device ID and inode number.
+    pub fn new_with_id(inner: T, dev: usize, ino: usize) -> Self {
+        Self { inner, dev, ino }
     }
 
     pub fn inner(&self) -> &T {
@@ -20,8 +28,8 @@ where
 {
     fn metadata(&self) -> vfs::Result<Metadata> {
         Ok(Metadata {
-            dev: 0,
-            inode: PROC_INO,
+            dev: self.dev,
+            inode: self.ino,
             size: 0,
             blk_size: 0,
             blocks: 0,
@@ -29,7 +37,11 @@ where
             mtime: Timespec { sec: 0, nsec: 0 },
             ctime: Timespec { sec: 0, nsec: 0 },
             type_: vfs::FileType::File,
-            mode: 0o444,
+            mode: if self.inner.is_writable() {
+                0o644
+            } else {
+                0o444
+            },
             nlinks: 1,
             uid: 0,
             gid: 0,
@@ -2,20 +2,65 @@ use super::*;
 
 pub use self::dir::Dir;
 pub use self::file::File;
+pub use self::static_dir::LockedStaticDirINode;
 pub use self::symlink::SymLink;
 
 mod dir;
 mod file;
+mod static_dir;
 mod symlink;
 
 pub trait ProcINode {
     fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>>;
+
+    /// Update the data with the bytes written to the inode.
+    ///
+    /// Only the writable inodes need to implement it.
+    fn set_data_from_bytes(&self, data: &[u8]) -> vfs::Result<()> {
+        Err(vfs::FsError::PermError)
+    }
+
+    fn is_writable(&self) -> bool {
+        false
+    }
 }
 
 pub trait DirProcINode {
     fn find(&self, name: &str) -> vfs::Result<Arc<dyn INode>>;
     fn get_entry(&self, id: usize) -> vfs::Result<String>;
     fn iterate_entries(&self, ctx: &mut DirentWriterContext) -> vfs::Result<usize>;
+
+    /// The number of hard links, i.e., 2 plus the number of subdirectories.
+    fn nlinks(&self) -> usize {
+        2
+    }
+}
+
+/// The device ID of a mounted procfs (or sysfs) instance, and the inode
+/// numbers of its inodes which live as long as the instance.
+pub struct InodeIds {
+    dev: usize,
+    next_ino: AtomicUsize,
+}
+
+impl InodeIds {
+    /// Allocate a new device ID for an instance, whose inode numbers are
+    /// allocated from `first_ino`.
+    pub fn new(first_ino: usize) -> Self {
+        Self {
+            dev: alloc_anon_dev(),
+            next_ino: AtomicUsize::new(first_ino),
+        }
+    }
+
+    pub fn dev(&self) -> usize {
+        self.dev
+    }
+
+    /// Allocate a new inode number for the inode which lives as long as the instance.
+    pub fn alloc_ino(&self) -> usize {
+        self.next_ino.fetch_add(1, Ordering::Relaxed)
+    }
 }
 
 #[macro_export]
@@ -31,7 +76,22 @@ macro_rules! impl_inode_for_file_or_symlink {
         }
 
         fn write_at(&self, offset: usize, buf: &[u8]) -> vfs::Result<usize> {
-            Err(vfs::FsError::PermError)
+            // The data must be written at once
+            if offset != 0 {
+                return Err(vfs::FsError::InvalidParam);
+            }
+            self.inner().set_data_from_bytes(buf)?;
+            Ok(buf.len())
+        }
+
+        fn resize(&self, len: usize) -> vfs::Result<()> {
+            // Truncating is allowed for the writable inodes, since the data
+            // is always replaced by the new written one.
+            if self.inner().is_writable() {
+                Ok(())
+            } else {
+                Err(vfs::FsError::PermError)
+            }
         }
 
         fn set_metadata(&self, metadata: &Metadata) -> vfs::Result<()> {
@@ -71,8 +131,10 @@ macro_rules! write_first_two_entries {
         let file = $file;
 
         if idx == 0 {
+            // Avoid getting the metadata of this inode, which may acquire the
+            // lock of this inode again to count the links
             let this_inode = file.this.upgrade().unwrap();
-            write_inode_entry!($ctx, ".", &this_inode);
+            write_entry!($ctx, ".", this_inode.ino(), vfs::FileType::Dir);
         }
         if idx <= 1 {
             write_inode_entry!($ctx, "..", &file.parent);
@@ -0,0 +1,117 @@
+use super::*;
+
+/// A directory whose entries are added at the initialization and never change.
+pub struct LockedStaticDirINode(
+    RwLock<StaticDirINode>,
+    // The number of subdirectories. It is kept out of the lock, so that the
+    // metadata of the directory can be got when the lock is held.
+    AtomicUsize,
+);
+
+struct StaticDirINode {
+    this: Weak<Dir<LockedStaticDirINode>>,
+    // Use the weak reference to avoid the reference cycle, since the
+    // static directories are never released.
+    parent: Weak<dyn INode>,
+    entries: HashMap<String, Arc<dyn INode>>,
+}
+
+impl LockedStaticDirINode {
+    /// Create a directory on the file system of `ids`, which is the one of its parent.
+    pub fn new(parent: Weak<dyn INode>, ids: &InodeIds) -> Arc<Dir<LockedStaticDirINode>> {
+        let inode = Arc::new(Dir::new(
+            Self(
+                RwLock::new(StaticDirINode {
+                    this: Weak::default(),
+                    parent,
+                    entries: HashMap::new(),
+                }),
+                AtomicUsize::new(0),
+            ),
+            ids,
+        ));
+        inode.inner().0.write().unwrap().this = Arc::downgrade(&inode);
+        inode
+    }
+
+    /// Create a root directory on the file system of `ids`, whose parent is itself.
+    pub fn new_root(ids: &InodeIds) -> Arc<Dir<LockedStaticDirINode>> {
+        let inode = Self::new(Weak::<Dir<LockedStaticDirINode>>::new() as _, ids);
+        {
+            let mut file = inode.inner().0.write().unwrap();
+            file.parent = Arc::downgrade(&inode) as _;
+        }
+        inode
+    }
+
+    pub fn add_entry(&self, name: &str, inode: Arc<dyn INode>) {
+        let is_dir = inode
+            .metadata()
+            .map(|metadata| metadata.type_ == vfs::FileType::Dir)
+            .unwrap_or(false);
+        let mut file = self.0.write().unwrap();
+        file.entries.insert(String::from(name), inode);
+        if is_dir {
+            self.1.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl DirProcINode for LockedStaticDirINode {
+    fn find(&self, name: &str) -> vfs::Result<Arc<dyn INode>> {
+        let file = self.0.read().unwrap();
+        if name == "." {
+            return Ok(file.this.upgrade().unwrap());
+        }
+        if name == ".." {
+            return file.parent.upgrade().ok_or(FsError::EntryNotFound);
+        }
+        if let Some(inode) = file.entries.get(name) {
+            Ok(Arc::clone(inode))
+        } else {
+            Err(FsError::EntryNotFound)
+        }
+    }
+
+    fn get_entry(&self, id: usize) -> vfs::Result<String> {
+        match id {
+            0 => Ok(String::from(".")),
+            1 => Ok(String::from("..")),
+            i => {
+                let file = self.0.read().unwrap();
+                let name = file
+                    .entries
+                    .keys()
+                    .nth(i - 2)
+                    .ok_or(FsError::EntryNotFound)?;
+                Ok(name.to_owned())
+            }
+        }
+    }
+
+    fn iterate_entries(&self, mut ctx: &mut DirentWriterContext) -> vfs::Result<usize> {
+        let file = self.0.read().unwrap();
+        let idx = ctx.pos();
+
+        // Write first two special entries
+        if idx == 0 {
+            let this_inode = file.this.upgrade().unwrap();
+            write_inode_entry!(&mut ctx, ".", &this_inode);
+        }
+        if idx <= 1 {
+            let parent_inode = file.parent.upgrade().ok_or(FsError::EntryNotFound)?;
+            write_inode_entry!(&mut ctx, "..", &parent_inode);
+        }
+
+        // Write the normal entries
+        let skipped = if idx < 2 { 0 } else { idx - 2 };
+        for (name, inode) in file.entries.iter().skip(skipped) {
+            write_inode_entry!(&mut ctx, name, inode);
+        }
+        Ok(ctx.written_len())
+    }
+
+    fn nlinks(&self) -> usize {
+        2 + self.1.load(Ordering::Relaxed)
+    }
+}
@@ -2,11 +2,19 @@ use super::*;
 
 pub struct SymLink<T: ProcINode> {
     inner: T,
+    dev: usize,
+    ino: usize,
 }
 
 impl<T: ProcINode> SymLink<T> {
-    pub fn new(inner: T) -> Self {
-        Self { inner }
+    /// Create a symlink which lives as long as the file system of `ids`.
+    pub fn new(inner: T, ids: &InodeIds) -> Self {
+        Self::new_with_id(inner, ids.dev(), ids.alloc_ino())
+    }
+
+    /// Create a symlink with the given device ID and inode number.
+    pub fn new_with_id(inner: T, dev: usize, ino: usize) -> Self {
+        Self { inner, dev, ino }
     }
 
     pub fn inner(&self) -> &T {
@@ -20,8 +28,8 @@ where
 {
     fn metadata(&self) -> vfs::Result<Metadata> {
         Ok(Metadata {
-            dev: 0,
-            inode: PROC_INO,
+            dev: self.dev,
+            inode: self.ino,
             size: 0,
             blk_size: 0,
             blocks: 0,
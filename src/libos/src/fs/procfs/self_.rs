@@ -4,8 +4,8 @@ use crate::process::do_getpid;
 pub struct SelfSymINode;
 
 impl SelfSymINode {
-    pub fn new() -> Arc<dyn INode> {
-        Arc::new(SymLink::new(Self))
+    pub fn new(ids: &InodeIds) -> Arc<dyn INode> {
+        Arc::new(SymLink::new(Self, ids))
     }
 }
 
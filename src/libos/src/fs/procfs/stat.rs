@@ -7,8 +7,8 @@ use std::untrusted::fs;
 pub struct StatINode;
 
 impl StatINode {
-    pub fn new() -> Arc<dyn INode> {
-        Arc::new(File::new(Self))
+    pub fn new(ids: &InodeIds) -> Arc<dyn INode> {
+        Arc::new(File::new(Self, ids))
     }
 }
 
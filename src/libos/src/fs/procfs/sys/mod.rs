@@ -0,0 +1,52 @@
+use super::*;
+
+use self::sysctl::SysCtlINode;
+
+mod sysctl;
+
+// This file is to implement the /proc/sys file system.
+//
+// The files in /proc/sys show the tunables of LibOS, and some of them are
+// writable to change the behavior of LibOS at runtime, e.g.,
+// - echo 65536 > /proc/sys/fs/pipe-max-size
+// - echo 1024 > /proc/sys/fs/sgx-file-cache-max
+// - echo 4194304 > /proc/sys/net/core/rmem_max
+
+/// Create the /proc/sys directory with all the sysctl files in it.
+pub fn init_sys_dir(parent: &Arc<dyn INode>, ids: &InodeIds) -> Arc<dyn INode> {
+    let sys_dir = LockedStaticDirINode::new(Arc::downgrade(parent), ids);
+
+    let kernel_dir = LockedStaticDirINode::new(Arc::downgrade(&sys_dir) as _, ids);
+    let kernel = kernel_dir.inner();
+    kernel.add_entry("hostname", SysCtlINode::new_hostname(ids));
+    kernel.add_entry("ostype", SysCtlINode::new_ostype(ids));
+    kernel.add_entry("osrelease", SysCtlINode::new_osrelease(ids));
+    kernel.add_entry("pid_max", SysCtlINode::new_pid_max(ids));
+    kernel.add_entry("shmmax", SysCtlINode::new_shmmax(ids));
+    kernel.add_entry("shmmni", SysCtlINode::new_shmmni(ids));
+    sys_dir.inner().add_entry("kernel", kernel_dir);
+
+    let fs_dir = LockedStaticDirINode::new(Arc::downgrade(&sys_dir) as _, ids);
+    let fs = fs_dir.inner();
+    fs.add_entry("pipe-max-size", SysCtlINode::new_pipe_max_size(ids));
+    fs.add_entry(
+        "sgx-file-cache-max",
+        SysCtlINode::new_sgx_file_cache_max(ids),
+    );
+    fs.add_entry(
+        "sgx-file-cache-state",
+        SysCtlINode::new_sgx_file_cache_state(ids),
+    );
+    sys_dir.inner().add_entry("fs", fs_dir);
+
+    let net_dir = LockedStaticDirINode::new(Arc::downgrade(&sys_dir) as _, ids);
+    let net_core_dir = LockedStaticDirINode::new(Arc::downgrade(&net_dir) as _, ids);
+    let net_core = net_core_dir.inner();
+    net_core.add_entry("somaxconn", SysCtlINode::new_somaxconn(ids));
+    net_core.add_entry("wmem_max", SysCtlINode::new_wmem_max(ids));
+    net_core.add_entry("rmem_max", SysCtlINode::new_rmem_max(ids));
+    net_dir.inner().add_entry("core", net_core_dir);
+    sys_dir.inner().add_entry("net", net_dir);
+
+    sys_dir
+}
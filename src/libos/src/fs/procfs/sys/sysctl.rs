@@ -0,0 +1,131 @@
+use super::*;
+use crate::fs::pipe::{pipe_max_size, set_pipe_max_size};
+use crate::fs::sefs::{set_sgx_file_cache_max, sgx_file_cache_max, SGX_FILE_CACHE_STATS};
+use crate::ipc::{set_shmmax, shmmax, shmmni};
+use crate::misc::{get_nodename, get_release, get_sysname, set_nodename};
+use crate::net::{rmem_max, set_rmem_max, set_somaxconn, set_wmem_max, somaxconn, wmem_max};
+use crate::process::{pid_max, set_pid_max};
+
+/// A file in /proc/sys, which shows the value of a LibOS tunable.
+///
+/// If the setter is given, the file is writable to change the tunable.
+pub struct SysCtlINode {
+    getter: fn() -> String,
+    setter: Option<fn(&str) -> Result<()>>,
+}
+
+impl SysCtlINode {
+    fn new(
+        ids: &InodeIds,
+        getter: fn() -> String,
+        setter: Option<fn(&str) -> Result<()>>,
+    ) -> Arc<dyn INode> {
+        Arc::new(File::new(Self { getter, setter }, ids))
+    }
+
+    pub fn new_hostname(ids: &InodeIds) -> Arc<dyn INode> {
+        Self::new(ids, get_nodename, Some(set_nodename))
+    }
+
+    pub fn new_ostype(ids: &InodeIds) -> Arc<dyn INode> {
+        Self::new(ids, get_sysname, None)
+    }
+
+    pub fn new_osrelease(ids: &InodeIds) -> Arc<dyn INode> {
+        Self::new(ids, get_release, None)
+    }
+
+    pub fn new_pid_max(ids: &InodeIds) -> Arc<dyn INode> {
+        Self::new(
+            ids,
+            || pid_max().to_string(),
+            Some(|val| set_pid_max(parse_value(val)?)),
+        )
+    }
+
+    pub fn new_shmmax(ids: &InodeIds) -> Arc<dyn INode> {
+        Self::new(
+            ids,
+            || shmmax().to_string(),
+            Some(|val| set_shmmax(parse_value(val)?)),
+        )
+    }
+
+    pub fn new_shmmni(ids: &InodeIds) -> Arc<dyn INode> {
+        Self::new(ids, || shmmni().to_string(), None)
+    }
+
+    pub fn new_pipe_max_size(ids: &InodeIds) -> Arc<dyn INode> {
+        Self::new(
+            ids,
+            || pipe_max_size().to_string(),
+            Some(|val| set_pipe_max_size(parse_value(val)?)),
+        )
+    }
+
+    pub fn new_sgx_file_cache_max(ids: &InodeIds) -> Arc<dyn INode> {
+        Self::new(
+            ids,
+            || sgx_file_cache_max().to_string(),
+            Some(|val| set_sgx_file_cache_max(parse_value(val)?)),
+        )
+    }
+
+    pub fn new_sgx_file_cache_state(ids: &InodeIds) -> Arc<dyn INode> {
+        Self::new(ids, || SGX_FILE_CACHE_STATS.to_string(), None)
+    }
+
+    pub fn new_somaxconn(ids: &InodeIds) -> Arc<dyn INode> {
+        Self::new(
+            ids,
+            || somaxconn().to_string(),
+            Some(|val| set_somaxconn(parse_value(val)?)),
+        )
+    }
+
+    pub fn new_wmem_max(ids: &InodeIds) -> Arc<dyn INode> {
+        Self::new(
+            ids,
+            || wmem_max().to_string(),
+            Some(|val| set_wmem_max(parse_value(val)?)),
+        )
+    }
+
+    pub fn new_rmem_max(ids: &InodeIds) -> Arc<dyn INode> {
+        Self::new(
+            ids,
+            || rmem_max().to_string(),
+            Some(|val| set_rmem_max(parse_value(val)?)),
+        )
+    }
+}
+
+impl ProcINode for SysCtlINode {
+    fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
+        let mut data = (self.getter)().into_bytes();
+        data.push(b'\n');
+        Ok(data)
+    }
+
+    fn set_data_from_bytes(&self, data: &[u8]) -> vfs::Result<()> {
+        let setter = self.setter.ok_or(FsError::PermError)?;
+        let value = std::str::from_utf8(data)
+            .map_err(|_| FsError::InvalidParam)?
+            .trim_end_matches('\n');
+        setter(value).map_err(|e| {
+            warn!("failed to set the sysctl value: {}", e);
+            FsError::InvalidParam
+        })
+    }
+
+    fn is_writable(&self) -> bool {
+        self.setter.is_some()
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(value: &str) -> Result<T> {
+    value
+        .trim()
+        .parse::<T>()
+        .map_err(|_| errno!(EINVAL, "invalid sysctl value"))
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+/// It returns the time since the LibOS boots, and the idle time which is
+/// always 0 since the idle time of CPUs is not tracked by LibOS.
+pub struct UptimeINode;
+
+impl UptimeINode {
+    pub fn new(ids: &InodeIds) -> Arc<dyn INode> {
+        Arc::new(File::new(Self, ids))
+    }
+}
+
+impl ProcINode for UptimeINode {
+    fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
+        let uptime = crate::time::up_time::get().unwrap_or_default();
+        Ok(format!(
+            "{}.{:02} 0.00\n",
+            uptime.as_secs(),
+            uptime.subsec_millis() / 10
+        )
+        .into_bytes())
+    }
+}
@@ -0,0 +1,16 @@
+use super::*;
+use crate::misc::get_version_string;
+
+pub struct VersionINode;
+
+impl VersionINode {
+    pub fn new(ids: &InodeIds) -> Arc<dyn INode> {
+        Arc::new(File::new(Self, ids))
+    }
+}
+
+impl ProcINode for VersionINode {
+    fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
+        Ok(get_version_string().into_bytes())
+    }
+}
@@ -0,0 +1,432 @@
+//! The line discipline of the pseudo-terminals.
+//!
+//! It is a subset of the N_TTY line discipline of Linux, which supports the
+//! canonical mode (line editing), echo, the translations of CR and NL, and the
+//! signal characters.
+use super::*;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// The size of the input buffer, same as N_TTY_BUF_SIZE of Linux
+pub const TTY_BUF_SIZE: usize = 4096;
+
+bitflags! {
+    pub struct InputFlags: u32 {
+        const IGNBRK = 0o1;
+        const BRKINT = 0o2;
+        const IGNPAR = 0o4;
+        const PARMRK = 0o10;
+        const INPCK = 0o20;
+        const ISTRIP = 0o40;
+        const INLCR = 0o100;
+        const IGNCR = 0o200;
+        const ICRNL = 0o400;
+        const IUCLC = 0o1000;
+        const IXON = 0o2000;
+        const IXANY = 0o4000;
+        const IXOFF = 0o10000;
+        const IMAXBEL = 0o20000;
+        const IUTF8 = 0o40000;
+    }
+}
+
+bitflags! {
+    pub struct OutputFlags: u32 {
+        const OPOST = 0o1;
+        const OLCUC = 0o2;
+        const ONLCR = 0o4;
+        const OCRNL = 0o10;
+        const ONOCR = 0o20;
+        const ONLRET = 0o40;
+    }
+}
+
+bitflags! {
+    pub struct LocalFlags: u32 {
+        const ISIG = 0o1;
+        const ICANON = 0o2;
+        const XCASE = 0o4;
+        const ECHO = 0o10;
+        const ECHOE = 0o20;
+        const ECHOK = 0o40;
+        const ECHONL = 0o100;
+        const NOFLSH = 0o200;
+        const TOSTOP = 0o400;
+        const ECHOCTL = 0o1000;
+        const ECHOPRT = 0o2000;
+        const ECHOKE = 0o4000;
+        const FLUSHO = 0o10000;
+        const PENDIN = 0o40000;
+        const IEXTEN = 0o100000;
+    }
+}
+
+// The indexes of the control characters
+const VINTR: usize = 0;
+const VQUIT: usize = 1;
+const VERASE: usize = 2;
+const VKILL: usize = 3;
+const VEOF: usize = 4;
+const VTIME: usize = 5;
+const VMIN: usize = 6;
+const VSUSP: usize = 10;
+const VEOL: usize = 11;
+const VWERASE: usize = 14;
+const VEOL2: usize = 16;
+
+// The default control characters, same as INIT_C_CC of Linux
+const DEFAULT_C_CC: [u8; 17] = [
+    0o003, 0o034, 0o177, 0o025, 0o004, 0, 1, 0, 0o021, 0o023, 0o032, 0, 0o022, 0o017, 0o027, 0o026,
+    0,
+];
+
+// B38400 | CS8 | CREAD | HUPCL
+const DEFAULT_CFLAG: u32 = 0o17 | 0o60 | 0o200 | 0o2000;
+
+pub struct LineDiscipline {
+    termios: KernelTermios,
+    // The line being edited in the canonical mode
+    line: Vec<u8>,
+    // The data which is ready to read
+    read_buf: VecDeque<u8>,
+    // The lengths of the completed lines in `read_buf` in the canonical mode,
+    // a zero-length line stands for the end of file
+    line_lens: VecDeque<usize>,
+}
+
+impl LineDiscipline {
+    pub fn new() -> Self {
+        let mut termios = KernelTermios {
+            c_iflag: (InputFlags::ICRNL | InputFlags::IXON).bits(),
+            c_oflag: (OutputFlags::OPOST | OutputFlags::ONLCR).bits(),
+            c_cflag: DEFAULT_CFLAG,
+            c_lflag: (LocalFlags::ISIG
+                | LocalFlags::ICANON
+                | LocalFlags::ECHO
+                | LocalFlags::ECHOE
+                | LocalFlags::ECHOK
+                | LocalFlags::ECHOCTL
+                | LocalFlags::ECHOKE
+                | LocalFlags::IEXTEN)
+                .bits(),
+            ..Default::default()
+        };
+        termios.c_cc[..DEFAULT_C_CC.len()].copy_from_slice(&DEFAULT_C_CC);
+        Self {
+            termios,
+            line: Vec::new(),
+            read_buf: VecDeque::new(),
+            line_lens: VecDeque::new(),
+        }
+    }
+
+    pub fn termios(&self) -> &KernelTermios {
+        &self.termios
+    }
+
+    pub fn set_termios(&mut self, termios: &KernelTermios) {
+        let was_canonical = self.is_canonical();
+        self.termios = *termios;
+        match (was_canonical, self.is_canonical()) {
+            // The edited line becomes readable
+            (true, false) => {
+                self.read_buf.extend(self.line.drain(..));
+                self.line_lens.clear();
+            }
+            // The data which is not read yet is treated as a completed line
+            (false, true) => {
+                if !self.read_buf.is_empty() {
+                    self.line_lens.push_back(self.read_buf.len());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Discard the data which is received but not read yet.
+    pub fn flush_input(&mut self) {
+        self.line.clear();
+        self.read_buf.clear();
+        self.line_lens.clear();
+    }
+
+    pub fn is_canonical(&self) -> bool {
+        self.local_flags().contains(LocalFlags::ICANON)
+    }
+
+    /// Whether there is data or the end of file to read.
+    pub fn is_readable(&self) -> bool {
+        if self.is_canonical() {
+            !self.line_lens.is_empty()
+        } else {
+            !self.read_buf.is_empty()
+        }
+    }
+
+    /// The minimum number of the bytes and the timeout of a read in the
+    /// non-canonical mode, i.e., MIN and TIME (in deciseconds). `None` means
+    /// no timeout.
+    pub fn read_min_time(&self) -> (usize, Option<Duration>) {
+        let time = match self.termios.c_cc[VTIME] {
+            0 => None,
+            time => Some(Duration::from_millis(time as u64 * 100)),
+        };
+        (self.termios.c_cc[VMIN] as usize, time)
+    }
+
+    /// The number of the bytes which are ready to read.
+    pub fn readable_len(&self) -> usize {
+        if self.is_canonical() {
+            self.line_lens.iter().sum()
+        } else {
+            self.read_buf.len()
+        }
+    }
+
+    /// Whether it can receive more input.
+    pub fn can_receive(&self) -> bool {
+        self.read_buf.len() < TTY_BUF_SIZE
+    }
+
+    /// Read the data received, at most one line in the canonical mode.
+    pub fn read(&mut self, buf: &mut [u8]) -> usize {
+        let len = if self.is_canonical() {
+            let line_len = match self.line_lens.front_mut() {
+                Some(line_len) => line_len,
+                None => return 0,
+            };
+            let len = buf.len().min(*line_len);
+            *line_len -= len;
+            // The end of file is consumed too if the line is read entirely
+            if *line_len == 0 {
+                self.line_lens.pop_front();
+            }
+            len
+        } else {
+            buf.len().min(self.read_buf.len())
+        };
+        for (dst, src) in buf.iter_mut().zip(self.read_buf.drain(..len)) {
+            *dst = src;
+        }
+        len
+    }
+
+    /// Receive the input characters until the input buffer is full, return
+    /// the number of the characters received.
+    ///
+    /// The characters to echo are pushed into `echo_buf`, and the signals
+    /// triggered by the special characters are pushed into `signals`.
+    pub fn receive(
+        &mut self,
+        input: &[u8],
+        echo_buf: &mut Vec<u8>,
+        signals: &mut Vec<SigNum>,
+    ) -> usize {
+        let mut received = 0;
+        for &c in input {
+            if !self.can_receive() {
+                break;
+            }
+            self.receive_char(c, echo_buf, signals);
+            received += 1;
+        }
+        received
+    }
+
+    fn receive_char(&mut self, mut c: u8, echo_buf: &mut Vec<u8>, signals: &mut Vec<SigNum>) {
+        let iflags = self.input_flags();
+        let lflags = self.local_flags();
+        let cc = self.termios.c_cc;
+
+        if iflags.contains(InputFlags::ISTRIP) {
+            c &= 0x7f;
+        }
+        if c == b'\r' {
+            if iflags.contains(InputFlags::IGNCR) {
+                return;
+            }
+            if iflags.contains(InputFlags::ICRNL) {
+                c = b'\n';
+            }
+        } else if c == b'\n' && iflags.contains(InputFlags::INLCR) {
+            c = b'\r';
+        }
+
+        if lflags.contains(LocalFlags::ISIG) {
+            let signal = if is_special_char(c, cc[VINTR]) {
+                Some(SIGINT)
+            } else if is_special_char(c, cc[VQUIT]) {
+                Some(SIGQUIT)
+            } else if is_special_char(c, cc[VSUSP]) {
+                Some(SIGTSTP)
+            } else {
+                None
+            };
+            if let Some(signal) = signal {
+                if !lflags.contains(LocalFlags::NOFLSH) {
+                    self.flush_input();
+                }
+                self.echo_char(c, echo_buf);
+                signals.push(signal);
+                return;
+            }
+        }
+
+        if lflags.contains(LocalFlags::ICANON) {
+            if is_special_char(c, cc[VERASE]) {
+                if let Some(erased) = self.line.pop() {
+                    self.echo_erase(erased, echo_buf);
+                }
+                return;
+            }
+            if is_special_char(c, cc[VWERASE]) && lflags.contains(LocalFlags::IEXTEN) {
+                // Erase the trailing spaces and then the word
+                while let Some(&last) = self.line.last() {
+                    if !last.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.line.pop();
+                    self.echo_erase(last, echo_buf);
+                }
+                while let Some(&last) = self.line.last() {
+                    if last.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.line.pop();
+                    self.echo_erase(last, echo_buf);
+                }
+                return;
+            }
+            if is_special_char(c, cc[VKILL]) {
+                if lflags.contains(LocalFlags::ECHOKE) && lflags.contains(LocalFlags::ECHOE) {
+                    while let Some(erased) = self.line.pop() {
+                        self.echo_erase(erased, echo_buf);
+                    }
+                } else {
+                    self.line.clear();
+                    self.echo_char(c, echo_buf);
+                    if lflags.contains(LocalFlags::ECHOK) && lflags.contains(LocalFlags::ECHO) {
+                        echo_buf.push(b'\n');
+                    }
+                }
+                return;
+            }
+            if is_special_char(c, cc[VEOF]) {
+                // The end of file is a line without the EOF character
+                self.complete_line();
+                return;
+            }
+            let is_eol =
+                c == b'\n' || is_special_char(c, cc[VEOL]) || is_special_char(c, cc[VEOL2]);
+            if is_eol {
+                if lflags.contains(LocalFlags::ECHO)
+                    || (c == b'\n' && lflags.contains(LocalFlags::ECHONL))
+                {
+                    echo_buf.push(c);
+                }
+                self.line.push(c);
+                self.complete_line();
+                return;
+            }
+            // Keep one byte for the end of the line, and discard the others like Linux
+            if self.line.len() < TTY_BUF_SIZE - 1 {
+                self.line.push(c);
+                self.echo_char(c, echo_buf);
+            }
+            return;
+        }
+
+        self.read_buf.push_back(c);
+        self.echo_char(c, echo_buf);
+    }
+
+    fn complete_line(&mut self) {
+        self.line_lens.push_back(self.line.len());
+        self.read_buf.extend(self.line.drain(..));
+    }
+
+    fn echo_char(&self, c: u8, echo_buf: &mut Vec<u8>) {
+        let lflags = self.local_flags();
+        if !lflags.contains(LocalFlags::ECHO) {
+            return;
+        }
+        if is_echoed_as_caret(c) && lflags.contains(LocalFlags::ECHOCTL) {
+            echo_buf.extend_from_slice(&[b'^', c ^ 0x40]);
+        } else {
+            echo_buf.push(c);
+        }
+    }
+
+    fn echo_erase(&self, erased: u8, echo_buf: &mut Vec<u8>) {
+        let lflags = self.local_flags();
+        if !lflags.contains(LocalFlags::ECHO) {
+            return;
+        }
+        if !lflags.contains(LocalFlags::ECHOE) {
+            echo_buf.push(self.termios.c_cc[VERASE]);
+            return;
+        }
+        let width = if is_echoed_as_caret(erased) && lflags.contains(LocalFlags::ECHOCTL) {
+            2
+        } else {
+            1
+        };
+        for _ in 0..width {
+            echo_buf.extend_from_slice(b"\x08 \x08");
+        }
+    }
+
+    /// Process the output characters, return the number of the characters
+    /// processed.
+    ///
+    /// The processed characters are pushed into `output_buf`, which can grow
+    /// to `max_len` at most.
+    pub fn process_output(
+        &self,
+        output: &[u8],
+        output_buf: &mut VecDeque<u8>,
+        max_len: usize,
+    ) -> usize {
+        let oflags = self.output_flags();
+        let mut processed = 0;
+        for &c in output {
+            let translated: &[u8] = if !oflags.contains(OutputFlags::OPOST) {
+                &[c]
+            } else if c == b'\n' && oflags.contains(OutputFlags::ONLCR) {
+                b"\r\n"
+            } else if c == b'\r' && oflags.contains(OutputFlags::OCRNL) {
+                b"\n"
+            } else {
+                &[c]
+            };
+            if output_buf.len() + translated.len() > max_len {
+                break;
+            }
+            output_buf.extend(translated);
+            processed += 1;
+        }
+        processed
+    }
+
+    fn input_flags(&self) -> InputFlags {
+        InputFlags::from_bits_truncate(self.termios.c_iflag)
+    }
+
+    fn output_flags(&self) -> OutputFlags {
+        OutputFlags::from_bits_truncate(self.termios.c_oflag)
+    }
+
+    fn local_flags(&self) -> LocalFlags {
+        LocalFlags::from_bits_truncate(self.termios.c_lflag)
+    }
+}
+
+/// The special character is disabled if it is zero (_POSIX_VDISABLE).
+fn is_special_char(c: u8, special_char: u8) -> bool {
+    special_char != 0 && c == special_char
+}
+
+/// The control characters except TAB and NL are echoed as "^X" with ECHOCTL.
+fn is_echoed_as_caret(c: u8) -> bool {
+    (c < 0x20 && c != b'\t' && c != b'\n') || c == 0x7f
+}
@@ -0,0 +1,136 @@
+use atomic::{Atomic, Ordering};
+
+use super::*;
+
+/// The master of a PTY, which is opened from /dev/ptmx.
+pub struct PtyMaster {
+    pty: Arc<Pty>,
+    inode: Arc<dyn INode>,
+    abs_path: String,
+    access_mode: AccessMode,
+    status_flags: Atomic<StatusFlags>,
+}
+
+impl PtyMaster {
+    /// Create a new PTY and open the master of it.
+    pub fn open(inode: Arc<dyn INode>, abs_path: &str, flags: u32) -> Result<Self> {
+        let access_mode = AccessMode::from_u32(flags)?;
+        let status_flags = StatusFlags::from_bits_truncate(flags) & STATUS_FLAGS_MASK;
+        Ok(Self {
+            pty: Pty::new()?,
+            inode,
+            abs_path: abs_path.to_owned(),
+            access_mode,
+            status_flags: Atomic::new(status_flags),
+        })
+    }
+
+    pub fn inode(&self) -> &Arc<dyn INode> {
+        &self.inode
+    }
+
+    pub fn abs_path(&self) -> &str {
+        &self.abs_path
+    }
+
+    fn is_nonblocking(&self) -> bool {
+        self.status_flags
+            .load(Ordering::Acquire)
+            .contains(StatusFlags::O_NONBLOCK)
+    }
+}
+
+impl File for PtyMaster {
+    fn read(&self, buf: &mut [u8]) -> Result<usize> {
+        if !self.access_mode.readable() {
+            return_errno!(EBADF, "File not readable");
+        }
+        self.pty
+            .wait_for(self.is_nonblocking(), || self.pty.master_read(buf))
+    }
+
+    fn readv(&self, bufs: &mut [&mut [u8]]) -> Result<usize> {
+        read_into_bufs(bufs, |buf| self.read(buf))
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize> {
+        if !self.access_mode.writable() {
+            return_errno!(EBADF, "File not writable");
+        }
+        self.pty
+            .wait_for(self.is_nonblocking(), || self.pty.master_write(buf))
+    }
+
+    fn writev(&self, bufs: &[&[u8]]) -> Result<usize> {
+        write_from_bufs(bufs, |buf| self.write(buf))
+    }
+
+    fn seek(&self, pos: SeekFrom) -> Result<off_t> {
+        return_errno!(ESPIPE, "PTY does not support seek")
+    }
+
+    fn metadata(&self) -> Result<Metadata> {
+        let metadata = self.inode.metadata()?;
+        Ok(metadata)
+    }
+
+    fn access_mode(&self) -> Result<AccessMode> {
+        Ok(self.access_mode.clone())
+    }
+
+    fn status_flags(&self) -> Result<StatusFlags> {
+        Ok(self.status_flags.load(Ordering::Acquire))
+    }
+
+    fn set_status_flags(&self, new_status_flags: StatusFlags) -> Result<()> {
+        self.status_flags
+            .store(new_status_flags & STATUS_FLAGS_MASK, Ordering::Release);
+        Ok(())
+    }
+
+    fn poll_new(&self) -> IoEvents {
+        self.pty.master_poll()
+    }
+
+    fn notifier(&self) -> Option<&IoNotifier> {
+        Some(&self.pty.master_notifier)
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<i32> {
+        self.pty.ioctl(cmd, true)
+    }
+
+    fn fs(&self) -> Result<Arc<dyn FileSystem>> {
+        Ok(self.inode.fs())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Drop for PtyMaster {
+    fn drop(&mut self) {
+        let fg_pgid = {
+            let mut inner = self.pty.inner.lock().unwrap();
+            inner.is_master_closed = true;
+            inner.fg_pgid
+        };
+        // Hang up the foreground process group like Linux
+        if let Some(pgid) = fg_pgid {
+            send_signal_to_pgrp(pgid, SIGHUP);
+        }
+        self.pty.update_events();
+    }
+}
+
+impl Debug for PtyMaster {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PtyMaster")
+            .field("pty", &self.pty)
+            .field("abs_path", &self.abs_path)
+            .field("access_mode", &self.access_mode)
+            .field("status_flags", &self.status_flags)
+            .finish()
+    }
+}
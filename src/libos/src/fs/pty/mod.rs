@@ -0,0 +1,480 @@
+//! Pseudo-terminals (PTYs).
+//!
+//! A PTY is a pair of the master and the slave. Opening /dev/ptmx creates a
+//! new PTY and returns the master, then the slave can be opened as
+//! /dev/pts/<index> once it is unlocked. The data written to the master goes
+//! through the line discipline and is read from the slave, and the data
+//! written to the slave is read from the master.
+//!
+//! A process can make the slave its controlling terminal with TIOCSCTTY, then
+//! the slave can also be opened as /dev/tty, and the signals generated by the
+//! special characters (e.g., ^C) are sent to the foreground process group.
+use std::collections::{BTreeMap, VecDeque};
+
+use super::file_ops::{KernelTermios, WinSize};
+use super::*;
+use crate::events::{Waiter, WaiterQueue};
+use crate::process::{table, ProcessRef, ProcessStatus};
+use crate::signal::{KernelSignal, SigNum, SIGHUP, SIGINT, SIGQUIT, SIGTSTP, SIGWINCH};
+
+use self::ldisc::LineDiscipline;
+pub use self::master::PtyMaster;
+pub use self::slave::PtySlave;
+
+mod ldisc;
+mod master;
+mod slave;
+
+/// The size of the output buffer, i.e., the data written to the slave but not
+/// read from the master yet
+const PTY_BUF_SIZE: usize = 65536;
+
+/// The max number of PTYs, same as the default value of kernel.pty.max in Linux
+const MAX_NR_PTYS: u32 = 4096;
+
+// The device numbers, same as Linux
+const TTY_MAJOR: u32 = 5;
+const TTY_MINOR: u32 = 0;
+const PTMX_MINOR: u32 = 2;
+const PTS_MAJOR: u32 = 136;
+
+lazy_static! {
+    /// All the PTYs indexed by the index of PTY
+    static ref PTY_TABLE: SgxMutex<BTreeMap<u32, Weak<Pty>>> = SgxMutex::new(BTreeMap::new());
+}
+
+/// The device number of /dev/tty
+pub fn tty_rdev() -> usize {
+    makedev(TTY_MAJOR, TTY_MINOR)
+}
+
+/// The device number of /dev/ptmx
+pub fn ptmx_rdev() -> usize {
+    makedev(TTY_MAJOR, PTMX_MINOR)
+}
+
+/// The device number of /dev/pts/<index>
+pub fn pts_rdev(index: u32) -> usize {
+    makedev(PTS_MAJOR, index)
+}
+
+/// Same as the makedev of glibc
+fn makedev(major: u32, minor: u32) -> usize {
+    let (major, minor) = (major as usize, minor as usize);
+    (minor & 0xff) | ((major & 0xfff) << 8) | ((minor & !0xff) << 12) | ((major & !0xfff) << 32)
+}
+
+/// Open the terminal device if the inode is /dev/ptmx, /dev/tty or
+/// /dev/pts/<index>, otherwise return `None`.
+pub fn open_tty_device(
+    inode: &Arc<dyn INode>,
+    abs_path: &str,
+    flags: u32,
+) -> Result<Option<Arc<dyn File>>> {
+    let rdev = inode.metadata()?.rdev;
+    let file: Arc<dyn File> = if rdev == ptmx_rdev() {
+        Arc::new(PtyMaster::open(inode.clone(), abs_path, flags)?)
+    } else if rdev == tty_rdev() {
+        let pty = current!()
+            .process()
+            .ctty()
+            .ok_or_else(|| errno!(ENXIO, "no controlling terminal"))?;
+        Arc::new(PtySlave::open(pty, inode.clone(), abs_path, flags)?)
+    } else if let Some(pty) = Pty::all()
+        .into_iter()
+        .find(|pty| pts_rdev(pty.index()) == rdev)
+    {
+        Arc::new(PtySlave::open(pty, inode.clone(), abs_path, flags)?)
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(file))
+}
+
+/// The state of a PTY shared by the master and the slaves.
+pub struct Pty {
+    index: u32,
+    inner: SgxMutex<PtyInner>,
+    // Threads blocked in reading or writing the master or the slaves
+    waiters: WaiterQueue,
+    master_notifier: IoNotifier,
+    slave_notifier: IoNotifier,
+}
+
+struct PtyInner {
+    ldisc: LineDiscipline,
+    // The output of the slave, which is read from the master
+    output: VecDeque<u8>,
+    winsize: WinSize,
+    // The foreground process group
+    fg_pgid: Option<pid_t>,
+    // The slave can not be opened when locked
+    is_locked: bool,
+    is_master_closed: bool,
+    nr_slaves: usize,
+    // The master gets EIO or POLLHUP after all the opened slaves are closed
+    is_slave_closed: bool,
+}
+
+impl Pty {
+    /// Create a new PTY with the lowest free index.
+    fn new() -> Result<Arc<Self>> {
+        let mut table = PTY_TABLE.lock().unwrap();
+        let index = (0..MAX_NR_PTYS)
+            .find(|index| !table.contains_key(index))
+            .ok_or_else(|| errno!(ENOSPC, "too many PTYs"))?;
+        let pty = Arc::new(Self {
+            index,
+            inner: SgxMutex::new(PtyInner {
+                ldisc: LineDiscipline::new(),
+                output: VecDeque::new(),
+                winsize: WinSize::default(),
+                fg_pgid: None,
+                is_locked: true,
+                is_master_closed: false,
+                nr_slaves: 0,
+                is_slave_closed: false,
+            }),
+            waiters: WaiterQueue::new(),
+            master_notifier: IoNotifier::new(),
+            slave_notifier: IoNotifier::new(),
+        });
+        table.insert(index, Arc::downgrade(&pty));
+        Ok(pty)
+    }
+
+    /// Get all the PTYs ordered by the indexes.
+    pub fn all() -> Vec<Arc<Self>> {
+        // Do not upgrade with the table locked, since dropping the upgraded
+        // reference may remove the PTY from the table
+        let ptys: Vec<Weak<Self>> = PTY_TABLE.lock().unwrap().values().cloned().collect();
+        ptys.iter().filter_map(|pty| pty.upgrade()).collect()
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn master_read(&self, buf: &mut [u8]) -> Result<usize> {
+        let len = {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.output.is_empty() {
+                if inner.is_slave_closed {
+                    return_errno!(EIO, "the slave is closed");
+                }
+                return_errno!(EAGAIN, "no data to read");
+            }
+            let len = buf.len().min(inner.output.len());
+            for (dst, src) in buf.iter_mut().zip(inner.output.drain(..len)) {
+                *dst = src;
+            }
+            len
+        };
+        self.update_events();
+        Ok(len)
+    }
+
+    fn master_write(&self, buf: &[u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut signals = Vec::new();
+        let (len, fg_pgid) = {
+            let mut inner = self.inner.lock().unwrap();
+            if !inner.ldisc.can_receive() {
+                return_errno!(EAGAIN, "the input buffer is full");
+            }
+            let mut echo_buf = Vec::new();
+            let inner = &mut *inner;
+            let len = inner.ldisc.receive(buf, &mut echo_buf, &mut signals);
+            // The echo is discarded if there is no space
+            inner
+                .ldisc
+                .process_output(&echo_buf, &mut inner.output, PTY_BUF_SIZE);
+            (len, inner.fg_pgid)
+        };
+        if let Some(pgid) = fg_pgid {
+            for signum in signals {
+                send_signal_to_pgrp(pgid, signum);
+            }
+        }
+        self.update_events();
+        Ok(len)
+    }
+
+    /// Read the slave, which blocks unless `is_nonblocking` is true.
+    ///
+    /// In the non-canonical mode, the read is completed according to MIN and
+    /// TIME like Linux, see termios(3).
+    fn slave_read_blocking(&self, buf: &mut [u8], is_nonblocking: bool) -> Result<usize> {
+        let (min, time) = {
+            let inner = self.inner.lock().unwrap();
+            if inner.ldisc.is_canonical() {
+                drop(inner);
+                return self.wait_for(is_nonblocking, || self.slave_read(buf));
+            }
+            inner.ldisc.read_min_time()
+        };
+        // Return what is available if MIN == 0 and TIME == 0
+        if buf.is_empty() || (min == 0 && time.is_none()) {
+            return match self.slave_read(buf) {
+                Err(e) if e.errno() == EAGAIN => Ok(0),
+                ret => ret,
+            };
+        }
+        if is_nonblocking {
+            return self.slave_read(buf);
+        }
+
+        // TIME is the timeout of the whole read if MIN == 0, otherwise the
+        // timer between the bytes, which starts after the first byte
+        let is_timer_started = |len: usize| min == 0 || len > 0;
+        // The read is completed once MIN bytes are read, or any bytes are read
+        // if MIN == 0
+        let min = min.clamp(1, buf.len());
+        let mut timeout = time;
+        let mut len = 0;
+        let waiter = Waiter::new();
+        loop {
+            self.waiters.reset_and_enqueue(&waiter);
+            match self.slave_read(&mut buf[len..]) {
+                // The master is closed
+                Ok(0) => return Ok(len),
+                Ok(read_len) => {
+                    len += read_len;
+                    timeout = time;
+                }
+                Err(e) if e.errno() == EAGAIN => {}
+                Err(e) => return Err(e),
+            }
+            if len >= min {
+                return Ok(len);
+            }
+            let timeout = if is_timer_started(len) {
+                timeout.as_mut()
+            } else {
+                None
+            };
+            if let Err(e) = waiter.wait_mut(timeout) {
+                if e.errno() == ETIMEDOUT || len > 0 {
+                    return Ok(len);
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    fn slave_read(&self, buf: &mut [u8]) -> Result<usize> {
+        let len = {
+            let mut inner = self.inner.lock().unwrap();
+            if !inner.ldisc.is_readable() {
+                if inner.is_master_closed {
+                    return Ok(0);
+                }
+                return_errno!(EAGAIN, "no data to read");
+            }
+            inner.ldisc.read(buf)
+        };
+        self.update_events();
+        Ok(len)
+    }
+
+    fn slave_write(&self, buf: &[u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let len = {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.is_master_closed {
+                return_errno!(EIO, "the master is closed");
+            }
+            let inner = &mut *inner;
+            let len = inner
+                .ldisc
+                .process_output(buf, &mut inner.output, PTY_BUF_SIZE);
+            if len == 0 {
+                return_errno!(EAGAIN, "the output buffer is full");
+            }
+            len
+        };
+        self.update_events();
+        Ok(len)
+    }
+
+    fn master_poll(&self) -> IoEvents {
+        let inner = self.inner.lock().unwrap();
+        let mut events = IoEvents::empty();
+        if !inner.output.is_empty() {
+            events |= IoEvents::IN;
+        }
+        if inner.ldisc.can_receive() {
+            events |= IoEvents::OUT;
+        }
+        if inner.is_slave_closed {
+            events |= IoEvents::HUP;
+        }
+        events
+    }
+
+    fn slave_poll(&self) -> IoEvents {
+        let inner = self.inner.lock().unwrap();
+        let mut events = IoEvents::empty();
+        if inner.is_master_closed {
+            return IoEvents::IN | IoEvents::HUP;
+        }
+        if inner.ldisc.is_readable() {
+            events |= IoEvents::IN;
+        }
+        if inner.output.len() < PTY_BUF_SIZE {
+            events |= IoEvents::OUT;
+        }
+        events
+    }
+
+    /// Notify the pollers and wake the blocked threads after the state changes.
+    fn update_events(&self) {
+        self.master_notifier.broadcast(&self.master_poll());
+        self.slave_notifier.broadcast(&self.slave_poll());
+        self.waiters.dequeue_and_wake_all();
+    }
+
+    /// Try the operation until it does not fail with `EAGAIN`, unless
+    /// `is_nonblocking` is true.
+    fn wait_for<T>(
+        &self,
+        is_nonblocking: bool,
+        mut try_op: impl FnMut() -> Result<T>,
+    ) -> Result<T> {
+        let is_again = |ret: &Result<T>| matches!(ret, Err(e) if e.errno() == EAGAIN);
+
+        let ret = try_op();
+        if is_nonblocking || !is_again(&ret) {
+            return ret;
+        }
+        let waiter = Waiter::new();
+        loop {
+            self.waiters.reset_and_enqueue(&waiter);
+            let ret = try_op();
+            if !is_again(&ret) {
+                return ret;
+            }
+            waiter.wait(None)?;
+        }
+    }
+
+    /// Handle the ioctls shared by the master and the slaves.
+    fn ioctl(&self, cmd: &mut IoctlCmd, is_master: bool) -> Result<i32> {
+        let mut inner = self.inner.lock().unwrap();
+        match cmd {
+            IoctlCmd::TCGETS(termios) => {
+                **termios = *inner.ldisc.termios();
+            }
+            IoctlCmd::TCSETS(termios) | IoctlCmd::TCSETSW(termios) => {
+                inner.ldisc.set_termios(*termios);
+            }
+            IoctlCmd::TCSETSF(termios) => {
+                inner.ldisc.flush_input();
+                inner.ldisc.set_termios(*termios);
+            }
+            IoctlCmd::TIOCGWINSZ(winsize) => {
+                **winsize = inner.winsize;
+            }
+            IoctlCmd::TIOCSWINSZ(winsize) => {
+                if inner.winsize != **winsize {
+                    inner.winsize = **winsize;
+                    if let Some(pgid) = inner.fg_pgid {
+                        drop(inner);
+                        send_signal_to_pgrp(pgid, SIGWINCH);
+                    }
+                }
+                return Ok(0);
+            }
+            IoctlCmd::FIONREAD(nread) => {
+                let len = if is_master {
+                    inner.output.len()
+                } else {
+                    inner.ldisc.readable_len()
+                };
+                **nread = len.min(std::i32::MAX as usize) as i32;
+            }
+            IoctlCmd::TIOCGPTN(index) if is_master => {
+                **index = self.index;
+            }
+            IoctlCmd::TIOCSPTLCK(is_locked) if is_master => {
+                inner.is_locked = **is_locked != 0;
+            }
+            IoctlCmd::TIOCGPGRP(pgid) => {
+                **pgid = inner.fg_pgid.unwrap_or(0);
+            }
+            IoctlCmd::TIOCSPGRP(pgid) => {
+                let pgid = **pgid;
+                if pgid < 0 {
+                    return_errno!(EINVAL, "invalid process group ID");
+                }
+                if table::get_pgrp(pgid).is_err() {
+                    return_errno!(ESRCH, "no such process group");
+                }
+                inner.fg_pgid = Some(pgid);
+            }
+            _ => return_errno!(ENOTTY, "unknown ioctl cmd for PTY"),
+        }
+        Ok(0)
+    }
+
+    fn set_fg_pgid(&self, pgid: Option<pid_t>) {
+        self.inner.lock().unwrap().fg_pgid = pgid;
+    }
+}
+
+impl Drop for Pty {
+    fn drop(&mut self) {
+        PTY_TABLE.lock().unwrap().remove(&self.index);
+    }
+}
+
+impl Debug for Pty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Pty").field("index", &self.index).finish()
+    }
+}
+
+fn send_signal_to_pgrp(pgid: pid_t, signum: SigNum) {
+    let pgrp = match table::get_pgrp(pgid) {
+        Ok(pgrp) => pgrp,
+        // The process group has exited
+        Err(_) => return,
+    };
+    for process in pgrp.get_all_processes() {
+        if process.status() == ProcessStatus::Zombie {
+            continue;
+        }
+        let signal = Box::new(KernelSignal::new(signum));
+        process.sig_queues().write().unwrap().enqueue(signal);
+    }
+}
+
+/// Read into the buffers with the `read` function, which reads a single buffer.
+fn read_into_bufs(
+    bufs: &mut [&mut [u8]],
+    mut read: impl FnMut(&mut [u8]) -> Result<usize>,
+) -> Result<usize> {
+    let total_len = bufs.iter().map(|buf| buf.len()).sum();
+    let mut data = vec![0; total_len];
+    let len = read(&mut data)?;
+    let mut copied = 0;
+    for buf in bufs.iter_mut() {
+        if copied == len {
+            break;
+        }
+        let copy_len = buf.len().min(len - copied);
+        buf[..copy_len].copy_from_slice(&data[copied..copied + copy_len]);
+        copied += copy_len;
+    }
+    Ok(len)
+}
+
+/// Write the buffers with the `write` function, which writes a single buffer.
+fn write_from_bufs(bufs: &[&[u8]], mut write: impl FnMut(&[u8]) -> Result<usize>) -> Result<usize> {
+    let data = bufs.concat();
+    write(&data)
+}
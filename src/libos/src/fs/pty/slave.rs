@@ -0,0 +1,178 @@
+use atomic::{Atomic, Ordering};
+
+use super::*;
+
+/// The slave of a PTY, which is opened from /dev/pts/<index> or /dev/tty.
+pub struct PtySlave {
+    pty: Arc<Pty>,
+    inode: Arc<dyn INode>,
+    abs_path: String,
+    access_mode: AccessMode,
+    status_flags: Atomic<StatusFlags>,
+}
+
+impl PtySlave {
+    /// Open the slave of the PTY, fail with `EIO` if the slave is locked or
+    /// the master is closed.
+    pub fn open(pty: Arc<Pty>, inode: Arc<dyn INode>, abs_path: &str, flags: u32) -> Result<Self> {
+        let access_mode = AccessMode::from_u32(flags)?;
+        let status_flags = StatusFlags::from_bits_truncate(flags) & STATUS_FLAGS_MASK;
+        {
+            let mut inner = pty.inner.lock().unwrap();
+            if inner.is_locked {
+                return_errno!(EIO, "the slave is locked");
+            }
+            if inner.is_master_closed {
+                return_errno!(EIO, "the master is closed");
+            }
+            inner.nr_slaves += 1;
+            inner.is_slave_closed = false;
+        }
+        pty.update_events();
+        Ok(Self {
+            pty,
+            inode,
+            abs_path: abs_path.to_owned(),
+            access_mode,
+            status_flags: Atomic::new(status_flags),
+        })
+    }
+
+    pub fn inode(&self) -> &Arc<dyn INode> {
+        &self.inode
+    }
+
+    pub fn abs_path(&self) -> &str {
+        &self.abs_path
+    }
+
+    fn is_nonblocking(&self) -> bool {
+        self.status_flags
+            .load(Ordering::Acquire)
+            .contains(StatusFlags::O_NONBLOCK)
+    }
+
+    fn is_ctty_of(&self, process: &ProcessRef) -> bool {
+        process
+            .ctty()
+            .map_or(false, |ctty| Arc::ptr_eq(&ctty, &self.pty))
+    }
+}
+
+impl File for PtySlave {
+    fn read(&self, buf: &mut [u8]) -> Result<usize> {
+        if !self.access_mode.readable() {
+            return_errno!(EBADF, "File not readable");
+        }
+        self.pty.slave_read_blocking(buf, self.is_nonblocking())
+    }
+
+    fn readv(&self, bufs: &mut [&mut [u8]]) -> Result<usize> {
+        read_into_bufs(bufs, |buf| self.read(buf))
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize> {
+        if !self.access_mode.writable() {
+            return_errno!(EBADF, "File not writable");
+        }
+        self.pty
+            .wait_for(self.is_nonblocking(), || self.pty.slave_write(buf))
+    }
+
+    fn writev(&self, bufs: &[&[u8]]) -> Result<usize> {
+        write_from_bufs(bufs, |buf| self.write(buf))
+    }
+
+    fn seek(&self, pos: SeekFrom) -> Result<off_t> {
+        return_errno!(ESPIPE, "PTY does not support seek")
+    }
+
+    fn metadata(&self) -> Result<Metadata> {
+        let metadata = self.inode.metadata()?;
+        Ok(metadata)
+    }
+
+    fn access_mode(&self) -> Result<AccessMode> {
+        Ok(self.access_mode.clone())
+    }
+
+    fn status_flags(&self) -> Result<StatusFlags> {
+        Ok(self.status_flags.load(Ordering::Acquire))
+    }
+
+    fn set_status_flags(&self, new_status_flags: StatusFlags) -> Result<()> {
+        self.status_flags
+            .store(new_status_flags & STATUS_FLAGS_MASK, Ordering::Release);
+        Ok(())
+    }
+
+    fn poll_new(&self) -> IoEvents {
+        self.pty.slave_poll()
+    }
+
+    fn notifier(&self) -> Option<&IoNotifier> {
+        Some(&self.pty.slave_notifier)
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<i32> {
+        let process = current!().process().clone();
+        match cmd {
+            IoctlCmd::TIOCSCTTY(_) => {
+                // There are no sessions, so any process without a controlling
+                // terminal can acquire one
+                if self.is_ctty_of(&process) {
+                    return Ok(0);
+                }
+                if process.ctty().is_some() {
+                    return_errno!(EPERM, "the process has a controlling terminal");
+                }
+                process.set_ctty(Some(self.pty.clone()));
+                self.pty.set_fg_pgid(Some(process.pgid()));
+                Ok(0)
+            }
+            IoctlCmd::TIOCNOTTY(_) => {
+                if !self.is_ctty_of(&process) {
+                    return_errno!(ENOTTY, "not the controlling terminal");
+                }
+                process.set_ctty(None);
+                Ok(0)
+            }
+            IoctlCmd::TIOCGPGRP(_) | IoctlCmd::TIOCSPGRP(_) if !self.is_ctty_of(&process) => {
+                return_errno!(ENOTTY, "not the controlling terminal");
+            }
+            _ => self.pty.ioctl(cmd, false),
+        }
+    }
+
+    fn fs(&self) -> Result<Arc<dyn FileSystem>> {
+        Ok(self.inode.fs())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Drop for PtySlave {
+    fn drop(&mut self) {
+        {
+            let mut inner = self.pty.inner.lock().unwrap();
+            inner.nr_slaves -= 1;
+            if inner.nr_slaves == 0 {
+                inner.is_slave_closed = true;
+            }
+        }
+        self.pty.update_events();
+    }
+}
+
+impl Debug for PtySlave {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PtySlave")
+            .field("pty", &self.pty)
+            .field("abs_path", &self.abs_path)
+            .field("access_mode", &self.access_mode)
+            .field("status_flags", &self.status_flags)
+            .finish()
+    }
+}
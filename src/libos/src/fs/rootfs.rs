@@ -1,7 +1,11 @@
 use super::dev_fs;
+use super::encfs::EncFS;
 use super::hostfs::HostFS;
+use super::mount_table::MOUNT_TABLE;
 use super::procfs::ProcFS;
 use super::sefs::{SgxStorage, SgxUuidProvider};
+use super::sized_ramfs::SizedRamFS;
+use super::sysfs::SysFS;
 use super::*;
 use config::{ConfigApp, ConfigMountFsType};
 use std::mem::size_of;
@@ -9,7 +13,6 @@ use std::path::{Path, PathBuf};
 use std::untrusted::path::PathEx;
 
 use rcore_fs_mountfs::{MNode, MountFS};
-use rcore_fs_ramfs::RamFS;
 use rcore_fs_sefs::dev::*;
 use rcore_fs_sefs::SEFS;
 use rcore_fs_unionfs::UnionFS;
@@ -23,6 +26,7 @@ lazy_static! {
             let mount_config = &config::LIBOS_CONFIG.get_app_config("init").unwrap().mount;
             let rootfs = open_root_fs_according_to(mount_config, &None)?;
             mount_nonroot_fs_according_to(&rootfs.root_inode(), mount_config, &None, true)?;
+            MOUNT_TABLE.write().unwrap().reset(mount_config);
             Ok(rootfs)
         }
 
@@ -45,36 +49,83 @@ pub fn open_root_fs_according_to(
     if root_mount_config.options.layers.is_none() {
         return_errno!(EINVAL, "the root UnionFS must be given the layers");
     }
-    let layer_mount_configs = root_mount_config.options.layers.as_ref().unwrap();
-    // image SEFS in layers
-    let root_image_sefs_mount_config = layer_mount_configs
-        .iter()
-        .find(|m| {
-            m.target == Path::new("/")
-                && m.type_ == ConfigMountFsType::TYPE_SEFS
-                && (m.options.mac.is_some() || m.options.index == 1)
-        })
-        .ok_or_else(|| errno!(Errno::ENOENT, "the image SEFS in layers is not valid"))?;
-    let root_image_sefs =
-        open_or_create_sefs_according_to(&root_image_sefs_mount_config, user_key)?;
-    // container SEFS in layers
-    let root_container_sefs_mount_config = layer_mount_configs
-        .iter()
-        .find(|m| {
-            m.target == Path::new("/")
-                && m.type_ == ConfigMountFsType::TYPE_SEFS
-                && m.options.mac.is_none()
-                && m.options.index == 0
-        })
-        .ok_or_else(|| errno!(Errno::ENOENT, "the container SEFS in layers is not valid"))?;
-    let root_container_sefs =
-        open_or_create_sefs_according_to(&root_container_sefs_mount_config, user_key)?;
-    // create UnionFS
-    let root_unionfs = UnionFS::new(vec![root_container_sefs, root_image_sefs])?;
+    let root_unionfs = open_unionfs_according_to(root_mount_config, user_key)?;
     let root_mountable_unionfs = MountFS::new(root_unionfs);
     Ok(root_mountable_unionfs)
 }
 
+/// Open the UnionFS with the layers listed from the lowest to the uppermost.
+///
+/// The uppermost layer is the only writable one, all the changes (including the
+/// whiteouts and opaque directories) are made in it, while the lower layers are
+/// read-only and can be of any type except UnionFS.
+fn open_unionfs_according_to(
+    mc: &ConfigMount,
+    user_key: &Option<sgx_key_128bit_t>,
+) -> Result<Arc<UnionFS>> {
+    assert!(mc.type_ == ConfigMountFsType::TYPE_UNIONFS);
+
+    let layer_mcs = mc
+        .options
+        .layers
+        .as_ref()
+        .ok_or_else(|| errno!(EINVAL, "Invalid layers for unionfs"))?;
+    let (container_mc, image_mcs) = layer_mcs
+        .split_last()
+        .ok_or_else(|| errno!(EINVAL, "Invalid container layer"))?;
+    if image_mcs.is_empty() {
+        return_errno!(EINVAL, "Invalid image layer");
+    }
+    if container_mc.type_ == ConfigMountFsType::TYPE_SEFS && container_mc.options.mac.is_some() {
+        return_errno!(
+            EINVAL,
+            "Integrity protected SEFS cannot be the container layer"
+        );
+    }
+
+    // The container layer is the first one in UnionFS, followed by the images from top to bottom
+    let mut layers = Vec::with_capacity(layer_mcs.len());
+    for layer_mc in layer_mcs.iter().rev() {
+        layers.push(open_layer_fs_according_to(layer_mc, user_key)?);
+    }
+    let unionfs = UnionFS::new(layers)?;
+    Ok(unionfs)
+}
+
+/// Open the file system which can be used as a layer of UnionFS, or be mounted directly.
+fn open_layer_fs_according_to(
+    mc: &ConfigMount,
+    user_key: &Option<sgx_key_128bit_t>,
+) -> Result<Arc<dyn FileSystem>> {
+    use self::ConfigMountFsType::*;
+    let fs: Arc<dyn FileSystem> = match mc.type_ {
+        TYPE_SEFS => open_or_create_sefs_according_to(mc, user_key)?,
+        TYPE_HOSTFS => {
+            let source_path = mc
+                .source
+                .as_ref()
+                .filter(|source| source.is_dir())
+                .ok_or_else(|| errno!(EINVAL, "Source is expected for HostFS"))?;
+            // The HostFS is confined by default
+            let confined = mc.options.confined.unwrap_or(true);
+            HostFS::new(source_path, confined, mc.options.async_io)?
+        }
+        TYPE_ENCFS => {
+            let source_path = mc
+                .source
+                .as_ref()
+                .filter(|source| source.is_dir())
+                .ok_or_else(|| errno!(EINVAL, "Source is expected for EncFS"))?;
+            EncFS::new(source_path, user_key, mc.options.obfuscate_names)?
+        }
+        TYPE_RAMFS => SizedRamFS::new(mc.options.size, mc.options.nr_inodes)?,
+        _ => {
+            return_errno!(EINVAL, "Unsupported fs type inside unionfs");
+        }
+    };
+    Ok(fs)
+}
+
 pub fn umount_nonroot_fs(
     root: &Arc<dyn INode>,
     abs_path: &str,
@@ -113,63 +164,30 @@ pub fn mount_nonroot_fs_according_to(
 
         use self::ConfigMountFsType::*;
         match mc.type_ {
-            TYPE_SEFS => {
-                let sefs = open_or_create_sefs_according_to(&mc, user_key)?;
-                mount_fs_at(sefs, root, &mc.target, follow_symlink)?;
-            }
-            TYPE_HOSTFS => {
-                let source_path =
-                    mc.source.as_ref().and_then(
-                        |source| {
-                            if source.is_dir() {
-                                Some(source)
-                            } else {
-                                None
-                            }
-                        },
-                    );
-                if source_path.is_none() {
-                    return_errno!(EINVAL, "Source is expected for HostFS");
-                }
-
-                let hostfs = HostFS::new(source_path.unwrap());
-                mount_fs_at(hostfs, root, &mc.target, follow_symlink)?;
-            }
-            TYPE_RAMFS => {
-                let ramfs = RamFS::new();
-                mount_fs_at(ramfs, root, &mc.target, follow_symlink)?;
+            TYPE_SEFS | TYPE_HOSTFS | TYPE_ENCFS | TYPE_RAMFS => {
+                let fs = open_layer_fs_according_to(mc, user_key)?;
+                mount_fs_at(fs, root, &mc.target, follow_symlink)?;
             }
             TYPE_DEVFS => {
-                let devfs = dev_fs::init_devfs()?;
+                // The size limits of DevFS are applied to the RamFS at /dev/shm
+                let devfs = dev_fs::init_devfs(mc.options.size, mc.options.nr_inodes)?;
                 mount_fs_at(devfs, root, &mc.target, follow_symlink)?;
             }
             TYPE_PROCFS => {
                 let procfs = ProcFS::new();
                 mount_fs_at(procfs, root, &mc.target, follow_symlink)?;
             }
+            TYPE_SYSFS => {
+                // The images created by the older versions have no /sys
+                if let Err(e) = create_mount_point_if_missing(root, &mc.target) {
+                    warn!("skip mounting sysfs at {:?}: {}", mc.target, e);
+                    continue;
+                }
+                let sysfs = SysFS::new();
+                mount_fs_at(sysfs, root, &mc.target, follow_symlink)?;
+            }
             TYPE_UNIONFS => {
-                let layer_mcs = mc
-                    .options
-                    .layers
-                    .as_ref()
-                    .ok_or_else(|| errno!(EINVAL, "Invalid layers for unionfs"))?;
-                let image_fs_mc = layer_mcs
-                    .get(0)
-                    .ok_or_else(|| errno!(EINVAL, "Invalid image layer"))?;
-                let container_fs_mc = layer_mcs
-                    .get(1)
-                    .ok_or_else(|| errno!(EINVAL, "Invalid container layer"))?;
-                let unionfs = match (&image_fs_mc.type_, &container_fs_mc.type_) {
-                    (TYPE_SEFS, TYPE_SEFS) => {
-                        let image_sefs = open_or_create_sefs_according_to(image_fs_mc, user_key)?;
-                        let container_sefs =
-                            open_or_create_sefs_according_to(container_fs_mc, user_key)?;
-                        UnionFS::new(vec![container_sefs, image_sefs])?
-                    }
-                    (_, _) => {
-                        return_errno!(EINVAL, "Unsupported fs type inside unionfs");
-                    }
-                };
+                let unionfs = open_unionfs_according_to(mc, user_key)?;
                 mount_fs_at(unionfs, root, &mc.target, follow_symlink)?;
             }
         }
@@ -202,6 +220,21 @@ pub fn mount_fs_at(
     Ok(())
 }
 
+fn create_mount_point_if_missing(root: &Arc<dyn INode>, path: &Path) -> Result<()> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| errno!(EINVAL, "invalid path"))?;
+    match root.lookup_follow(path, MAX_SYMLINKS) {
+        Err(FsError::EntryNotFound) => {
+            let (dir_path, file_name) = split_path(path);
+            root.lookup_follow(dir_path, MAX_SYMLINKS)?
+                .create(file_name, FileType::Dir, 0o555)?;
+            Ok(())
+        }
+        res => res.map(|_| ()).map_err(Into::into),
+    }
+}
+
 fn open_or_create_sefs_according_to(
     mc: &ConfigMount,
     user_key: &Option<sgx_key_128bit_t>,
@@ -1,7 +1,9 @@
 use super::{sgx_aes_gcm_128bit_tag_t, sgx_key_128bit_t};
 
-pub use self::sgx_storage::SgxStorage;
+pub use self::sgx_file_cache::{set_sgx_file_cache_max, sgx_file_cache_max, SGX_FILE_CACHE_STATS};
+pub use self::sgx_storage::{LockedFile, SgxStorage};
 pub use self::sgx_uuid_provider::SgxUuidProvider;
 
+mod sgx_file_cache;
 mod sgx_storage;
 mod sgx_uuid_provider;
@@ -0,0 +1,192 @@
+use super::sgx_storage::{LockedFile, WeakLockedFile};
+use super::*;
+use crate::error::*;
+use rcore_fs_sefs::dev::File;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// The default max number of the cached files in each `SgxStorage`.
+const DEFAULT_MAX_CACHED_FILES: usize = 256;
+
+static MAX_CACHED_FILES: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_CACHED_FILES);
+
+/// Get the max number of the cached files, which is shown in
+/// /proc/sys/fs/sgx-file-cache-max.
+pub fn sgx_file_cache_max() -> usize {
+    MAX_CACHED_FILES.load(Ordering::Relaxed)
+}
+
+/// Set the max number of the cached files, the caches are shrunk on the next access.
+pub fn set_sgx_file_cache_max(max: usize) -> Result<()> {
+    if max == 0 {
+        return_errno!(EINVAL, "the SGX file cache must hold one file at least");
+    }
+    MAX_CACHED_FILES.store(max, Ordering::Relaxed);
+    Ok(())
+}
+
+/// The statistics of all the SGX file caches, which is shown in
+/// /proc/sys/fs/sgx-file-cache-state.
+pub struct SgxFileCacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    cached_files: AtomicUsize,
+}
+
+pub static SGX_FILE_CACHE_STATS: SgxFileCacheStats = SgxFileCacheStats {
+    hits: AtomicU64::new(0),
+    misses: AtomicU64::new(0),
+    evictions: AtomicU64::new(0),
+    cached_files: AtomicUsize::new(0),
+};
+
+/// Show the stats in the format of "<hits> <misses> <evictions> <cached files>".
+impl fmt::Display for SgxFileCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}",
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+            self.evictions.load(Ordering::Relaxed),
+            self.cached_files.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// The LRU cache of the opened SGX protected files, indexed by the file IDs.
+///
+/// The protected file can not be opened twice, so the evicted file which is
+/// still used by others is kept weakly until it is closed, and it is taken
+/// back to the cache if accessed again before then.
+pub struct SgxFileCache {
+    files: HashMap<String, CachedFile>,
+    // The file IDs ordered by the last access time
+    lru_list: BTreeMap<u64, String>,
+    // The evicted files which may be still in use
+    evicted: HashMap<String, WeakLockedFile>,
+    next_stamp: u64,
+}
+
+struct CachedFile {
+    file: LockedFile,
+    stamp: u64,
+}
+
+impl SgxFileCache {
+    pub fn new() -> Self {
+        Self {
+            files: HashMap::new(),
+            lru_list: BTreeMap::new(),
+            evicted: HashMap::new(),
+            next_stamp: 0,
+        }
+    }
+
+    /// Get the cached file, and mark it as the most recently used one.
+    pub fn get(&mut self, file_id: &str) -> Option<LockedFile> {
+        let stamp = self.new_stamp();
+        let cached_file = match self.files.get_mut(file_id) {
+            Some(cached_file) => cached_file,
+            None => {
+                let file = self
+                    .evicted
+                    .remove(file_id)
+                    .and_then(|weak_file| weak_file.upgrade());
+                if let Some(file) = file.as_ref() {
+                    SGX_FILE_CACHE_STATS.hits.fetch_add(1, Ordering::Relaxed);
+                    self.insert(file_id, file.clone());
+                } else {
+                    SGX_FILE_CACHE_STATS.misses.fetch_add(1, Ordering::Relaxed);
+                }
+                return file;
+            }
+        };
+        SGX_FILE_CACHE_STATS.hits.fetch_add(1, Ordering::Relaxed);
+        let file_id = self.lru_list.remove(&cached_file.stamp).unwrap();
+        self.lru_list.insert(stamp, file_id);
+        cached_file.stamp = stamp;
+        Some(cached_file.file.clone())
+    }
+
+    /// Add the opened file to the cache, evict the least recently used ones if full.
+    pub fn insert(&mut self, file_id: &str, file: LockedFile) {
+        self.remove(file_id);
+        let stamp = self.new_stamp();
+        self.files
+            .insert(file_id.to_string(), CachedFile { file, stamp });
+        self.lru_list.insert(stamp, file_id.to_string());
+        SGX_FILE_CACHE_STATS
+            .cached_files
+            .fetch_add(1, Ordering::Relaxed);
+        self.shrink(sgx_file_cache_max());
+    }
+
+    /// Invalidate the cached file, e.g., when the file is removed.
+    pub fn remove(&mut self, file_id: &str) -> Option<LockedFile> {
+        if let Some(weak_file) = self.evicted.remove(file_id) {
+            return weak_file.upgrade();
+        }
+        let cached_file = self.files.remove(file_id)?;
+        self.lru_list.remove(&cached_file.stamp);
+        SGX_FILE_CACHE_STATS
+            .cached_files
+            .fetch_sub(1, Ordering::Relaxed);
+        Some(cached_file.file)
+    }
+
+    /// Invalidate all the cached files.
+    pub fn clear(&mut self) {
+        SGX_FILE_CACHE_STATS
+            .cached_files
+            .fetch_sub(self.files.len(), Ordering::Relaxed);
+        self.files.clear();
+        self.lru_list.clear();
+        self.evicted.clear();
+    }
+
+    /// Evict the least recently used files until the number of the cached
+    /// files is not larger than `max`.
+    fn shrink(&mut self, max: usize) {
+        self.evicted.retain(|_, weak_file| weak_file.is_alive());
+        if self.files.len() <= max {
+            return;
+        }
+        let evicted_ids: Vec<String> = self
+            .lru_list
+            .values()
+            .take(self.files.len() - max)
+            .cloned()
+            .collect();
+        for file_id in evicted_ids {
+            let file = self.remove(&file_id).unwrap();
+            // The file is closed once no longer used, flush it to catch the error
+            if let Err(e) = file.flush() {
+                error!(
+                    "failed to flush the evicted SGX file {}: errno = {}",
+                    file_id, e.0
+                );
+            }
+            if file.is_shared() {
+                self.evicted.insert(file_id, file.downgrade());
+            }
+            SGX_FILE_CACHE_STATS
+                .evictions
+                .fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn new_stamp(&mut self) -> u64 {
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        stamp
+    }
+}
+
+impl Drop for SgxFileCache {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
@@ -1,15 +1,13 @@
+use super::sgx_file_cache::SgxFileCache;
 use super::*;
 use crate::error::*;
 use rcore_fs::dev::{DevError, DevResult};
 use rcore_fs_sefs::dev::{File, SefsMac, Storage};
 use std::boxed::Box;
-use std::collections::hash_map::DefaultHasher;
-use std::collections::BTreeMap;
-use std::hash::{Hash, Hasher};
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sgxfs::{remove, OpenOptions, SgxFile};
-use std::sync::{Arc, SgxMutex as Mutex};
+use std::sync::{Arc, SgxMutex as Mutex, Weak};
 use std::untrusted::fs;
 
 /// A helper macro to automatically convert a block of code that returns `std::result::Result<T, E1>`
@@ -29,7 +27,7 @@ macro_rules! convert_result {
 pub struct SgxStorage {
     path: PathBuf,
     encrypt_mode: EncryptMode,
-    file_cache: Mutex<BTreeMap<u64, LockedFile>>,
+    file_cache: Mutex<SgxFileCache>,
     cache_size: Option<u64>,
 }
 
@@ -45,7 +43,7 @@ impl SgxStorage {
         Ok(SgxStorage {
             path: path.as_ref().to_path_buf(),
             encrypt_mode: EncryptMode::new(key, root_mac),
-            file_cache: Mutex::new(BTreeMap::new()),
+            file_cache: Mutex::new(SgxFileCache::new()),
             cache_size,
         })
     }
@@ -59,24 +57,18 @@ impl SgxStorage {
         open_fn: impl FnOnce(&Self) -> Result<LockedFile>,
     ) -> Result<LockedFile> {
         // query cache
-        let key = self.calculate_hash(file_id);
         let mut caches = self.file_cache.lock().unwrap();
-        if let Some(locked_file) = caches.get(&key) {
+        if let Some(locked_file) = caches.get(file_id) {
             // hit, return
-            return Ok(locked_file.clone());
+            return Ok(locked_file);
         }
         // miss, open one
         let locked_file = open_fn(self)?;
         // add to cache
-        caches.insert(key, locked_file.clone());
+        caches.insert(file_id, locked_file.clone());
         Ok(locked_file)
     }
 
-    fn calculate_hash(&self, t: &str) -> u64 {
-        let mut s = DefaultHasher::new();
-        t.hash(&mut s);
-        s.finish()
-    }
     /// Get file by `file_id` without cache.
     #[cfg(not(feature = "sgx_file_cache"))]
     fn get(
@@ -87,6 +79,38 @@ impl SgxStorage {
         open_fn(self)
     }
 
+    fn open_file(&self, file_id: &str) -> Result<LockedFile> {
+        let mut path = self.path.to_path_buf();
+        path.push(file_id);
+        let options = {
+            let mut options = OpenOptions::new();
+            options.read(true).update(true);
+            options
+        };
+        let file = match self.encrypt_mode {
+            EncryptMode::IntegrityOnly(_) => options.open_integrity_only(path)?,
+            EncryptMode::EncryptWithIntegrity(key, _) | EncryptMode::Encrypt(key) => {
+                options.open_with(path, Some(&key), self.cache_size)?
+            }
+            EncryptMode::EncryptAutoKey => options.open_with(path, None, self.cache_size)?,
+        };
+
+        // Check the MAC of the root file against the given root MAC of the storage
+        if file_id == "metadata" && self.protect_integrity() {
+            let root_file_mac = file.get_mac().expect("Failed to get mac");
+            if root_file_mac != self.encrypt_mode.root_mac().unwrap() {
+                error!(
+                    "MAC validation for metadata file failed: expected = {:#?}, found = {:?}",
+                    self.encrypt_mode.root_mac().unwrap(),
+                    root_file_mac
+                );
+                return_errno!(EACCES);
+            }
+        }
+
+        Ok(LockedFile(Arc::new(Mutex::new(file))))
+    }
+
     fn check_cache_size(cache_size: &Option<u64>) -> Result<()> {
         const PAGE_SIZE: u64 = 0x1000;
         const DEFAULT_CACHE_SIZE: u64 = 48 * PAGE_SIZE;
@@ -105,37 +129,7 @@ impl SgxStorage {
 
 impl Storage for SgxStorage {
     fn open(&self, file_id: &str) -> DevResult<Box<dyn File>> {
-        let locked_file = self.get(file_id, |this| {
-            let mut path = this.path.to_path_buf();
-            path.push(file_id);
-            let options = {
-                let mut options = OpenOptions::new();
-                options.read(true).update(true);
-                options
-            };
-            let file = match self.encrypt_mode {
-                EncryptMode::IntegrityOnly(_) => options.open_integrity_only(path)?,
-                EncryptMode::EncryptWithIntegrity(key, _) | EncryptMode::Encrypt(key) => {
-                    options.open_with(path, Some(&key), self.cache_size)?
-                }
-                EncryptMode::EncryptAutoKey => options.open_with(path, None, self.cache_size)?,
-            };
-
-            // Check the MAC of the root file against the given root MAC of the storage
-            if file_id == "metadata" && self.protect_integrity() {
-                let root_file_mac = file.get_mac().expect("Failed to get mac");
-                if root_file_mac != self.encrypt_mode.root_mac().unwrap() {
-                    error!(
-                        "MAC validation for metadata file failed: expected = {:#?}, found = {:?}",
-                        self.encrypt_mode.root_mac().unwrap(),
-                        root_file_mac
-                    );
-                    return_errno!(EACCES);
-                }
-            }
-
-            Ok(LockedFile(Arc::new(Mutex::new(file))))
-        })?;
+        let locked_file = self.get(file_id, |this| this.open_file(file_id))?;
         Ok(Box::new(locked_file))
     }
 
@@ -160,15 +154,17 @@ impl Storage for SgxStorage {
         Ok(Box::new(locked_file))
     }
 
+    // The files are never renamed in the storage, since a rename of SEFS only
+    // changes the directory entries, and the IDs are random UUIDs never reused.
+    // The file replaced by a rename is removed here when it is no longer used,
+    // so the cache never serves the content of a replaced file.
     fn remove(&self, file_id: &str) -> DevResult<()> {
         convert_result!({
+            // remove from cache before the file is removed
+            self.file_cache.lock().unwrap().remove(file_id);
             let mut path = self.path.to_path_buf();
             path.push(file_id);
             remove(path)?;
-            // remove from cache
-            let key = self.calculate_hash(file_id);
-            let mut caches = self.file_cache.lock().unwrap();
-            caches.remove(&key);
             Ok(())
         })
     }
@@ -182,13 +178,12 @@ impl Storage for SgxStorage {
 
     fn clear(&self) -> DevResult<()> {
         convert_result!({
+            // clear cache before the files are removed
+            self.file_cache.lock().unwrap().clear();
             for child in fs::read_dir(&self.path)? {
                 let child = child?;
                 remove(&child.path())?;
             }
-            // clear cache
-            let mut caches = self.file_cache.lock().unwrap();
-            caches.clear();
             Ok(())
         })
     }
@@ -231,6 +226,48 @@ pub struct LockedFile(Arc<Mutex<SgxFile>>);
 unsafe impl Send for LockedFile {}
 unsafe impl Sync for LockedFile {}
 
+impl LockedFile {
+    pub fn new(file: SgxFile) -> Self {
+        LockedFile(Arc::new(Mutex::new(file)))
+    }
+
+    /// Whether the file is used by others besides this one.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
+
+    /// Get a weak reference, which does not keep the file opened.
+    pub fn downgrade(&self) -> WeakLockedFile {
+        WeakLockedFile(Arc::downgrade(&self.0))
+    }
+
+    /// Get the size of the plaintext in the file.
+    pub fn len(&self) -> DevResult<usize> {
+        convert_result!({
+            let mut file = self.0.lock().unwrap();
+            let file_size = file.seek(SeekFrom::End(0))? as usize;
+            Ok(file_size)
+        })
+    }
+}
+
+pub struct WeakLockedFile(Weak<Mutex<SgxFile>>);
+
+impl WeakLockedFile {
+    pub fn upgrade(&self) -> Option<LockedFile> {
+        self.0.upgrade().map(LockedFile)
+    }
+
+    /// Whether the file is still opened.
+    pub fn is_alive(&self) -> bool {
+        self.0.strong_count() > 0
+    }
+}
+
+// Same as `LockedFile`
+unsafe impl Send for WeakLockedFile {}
+unsafe impl Sync for WeakLockedFile {}
+
 impl File for LockedFile {
     fn read_at(&self, buf: &mut [u8], offset: usize) -> DevResult<usize> {
         convert_result!({
@@ -0,0 +1,385 @@
+//! A RamFS with the limits of the size and the number of inodes, like tmpfs.
+//!
+//! The data of RamFS is stored in the enclave heap, so a RamFS without limits
+//! could exhaust the heap and crash the whole LibOS. The `SizedRamFS` charges
+//! the size of every file, in blocks, to the file system before it grows, and
+//! fails with `ENOSPC` if the limits are exceeded. Same as tmpfs, a limit of
+//! zero means unlimited.
+//!
+//! The size an operation may grow a file to is reserved before it, and the
+//! reservation is replaced by the actual size after it. The reservations of
+//! the concurrent operations on the same file are added up, so they could not
+//! take the space reserved by each other.
+//!
+//! The inode is freed by RamFS when it is unlinked and no longer used, then
+//! the charges of it are reclaimed lazily, i.e., when the limits are reached,
+//! the usage is queried or the freed inodes may outnumber the live ones. The
+//! usage is not tracked at all if there are no limits.
+use crate::prelude::align_up;
+use alloc::string::String;
+use alloc::sync::{Arc, Weak};
+use core::any::Any;
+use rcore_fs::vfs::*;
+use rcore_fs_ramfs::{RamFS, RAMFS_MAGIC};
+use std::collections::HashMap;
+use std::sync::SgxMutex as Mutex;
+
+const BLOCK_SIZE: usize = 4096;
+// The number of the tracked inodes below which the freed ones are not
+// reclaimed until the limits are reached
+const MIN_RECLAIM_INODES: usize = 64;
+
+/// RamFS with the limits
+pub struct SizedRamFS {
+    inner: Arc<RamFS>,
+    /// The max size in bytes, `None` means unlimited
+    max_size: Option<usize>,
+    /// The max number of inodes, `None` means unlimited
+    max_inodes: Option<usize>,
+    usage: Mutex<Usage>,
+    self_ref: Weak<SizedRamFS>,
+}
+
+/// INode for `SizedRamFS`
+pub struct SizedRamFSINode {
+    inner: Arc<dyn INode>,
+    fs: Arc<SizedRamFS>,
+}
+
+/// The usage of the file system.
+#[derive(Default)]
+struct Usage {
+    /// The total size charged by the inodes, in bytes
+    size: usize,
+    /// The charges of every inode, indexed by the address of the inode
+    inodes: HashMap<usize, Charge>,
+    /// The number of the inodes left after the last reclaiming
+    reclaimed_len: usize,
+}
+
+/// The charges of an inode.
+struct Charge {
+    inode: Weak<dyn INode>,
+    /// The size of the inode, in bytes aligned to blocks
+    size: usize,
+    /// The size reserved by the operations in progress
+    reserved: usize,
+}
+
+impl Charge {
+    fn new(inode: &Arc<dyn INode>) -> Self {
+        Self {
+            inode: Arc::downgrade(inode),
+            size: 0,
+            reserved: 0,
+        }
+    }
+}
+
+impl Usage {
+    /// Reclaim the charges of the freed inodes.
+    fn reclaim(&mut self) {
+        let mut reclaimed_size = 0;
+        self.inodes.retain(|_, charge| {
+            let is_alive = charge.inode.strong_count() > 0;
+            if !is_alive {
+                reclaimed_size += charge.size + charge.reserved;
+            }
+            is_alive
+        });
+        self.size -= reclaimed_size;
+        self.reclaimed_len = self.inodes.len();
+    }
+
+    /// Reclaim the charges if the tracked inodes have doubled since the last
+    /// reclaiming, so the freed inodes are not kept forever.
+    fn reclaim_if_grown(&mut self) {
+        if self.inodes.len() >= MIN_RECLAIM_INODES.max(self.reclaimed_len * 2) {
+            self.reclaim();
+        }
+    }
+}
+
+impl FileSystem for SizedRamFS {
+    fn sync(&self) -> Result<()> {
+        self.inner.sync()
+    }
+
+    fn root_inode(&self) -> Arc<dyn INode> {
+        Arc::new(SizedRamFSINode {
+            inner: self.inner.root_inode(),
+            fs: self.self_ref.upgrade().unwrap(),
+        })
+    }
+
+    fn info(&self) -> FsInfo {
+        let (used_size, used_inodes) = {
+            let mut usage = self.usage.lock().unwrap();
+            usage.reclaim();
+            (usage.size, usage.inodes.len())
+        };
+        // Same as tmpfs, the unlimited numbers are shown as zeros
+        let blocks = self.max_size.map_or(0, |max_size| max_size / BLOCK_SIZE);
+        let bfree = blocks.saturating_sub(used_size / BLOCK_SIZE);
+        let files = self.max_inodes.unwrap_or(0);
+        let ffree = files.saturating_sub(used_inodes);
+        FsInfo {
+            magic: RAMFS_MAGIC,
+            bsize: BLOCK_SIZE,
+            frsize: BLOCK_SIZE,
+            blocks,
+            bfree,
+            bavail: bfree,
+            files,
+            ffree,
+            namemax: 255,
+        }
+    }
+}
+
+impl SizedRamFS {
+    /// Create a new `SizedRamFS` with the limits.
+    pub fn new(max_size: Option<usize>, max_inodes: Option<usize>) -> Result<Arc<Self>> {
+        let inner = RamFS::new();
+        let mut usage = Usage::default();
+        let root = inner.root_inode();
+        usage.inodes.insert(inode_key(&root), Charge::new(&root));
+        // Same as tmpfs, zero means unlimited
+        let max_size = max_size
+            .filter(|&size| size != 0)
+            .map(|size| align_up(size, BLOCK_SIZE));
+        let max_inodes = max_inodes.filter(|&max_inodes| max_inodes != 0);
+        if max_inodes.map_or(false, |max_inodes| max_inodes < usage.inodes.len()) {
+            return Err(FsError::InvalidParam);
+        }
+        Ok(SizedRamFS {
+            inner,
+            max_size,
+            max_inodes,
+            usage: Mutex::new(usage),
+            self_ref: Weak::default(),
+        }
+        .wrap())
+    }
+
+    /// Wrap pure `SizedRamFS` with Arc
+    /// Used in constructors
+    fn wrap(self) -> Arc<Self> {
+        // Create an Arc, make a Weak from it, then put it into the struct.
+        // It's a little tricky.
+        let fs = Arc::new(self);
+        let weak = Arc::downgrade(&fs);
+        let ptr = Arc::into_raw(fs) as *mut Self;
+        unsafe {
+            (*ptr).self_ref = weak;
+        }
+        unsafe { Arc::from_raw(ptr) }
+    }
+
+    fn is_limited(&self) -> bool {
+        self.max_size.is_some() || self.max_inodes.is_some()
+    }
+
+    /// Check if a new inode can be created.
+    fn check_free_inode(&self, usage: &mut Usage) -> Result<()> {
+        if let Some(max_inodes) = self.max_inodes {
+            if usage.inodes.len() >= max_inodes {
+                usage.reclaim();
+                if usage.inodes.len() >= max_inodes {
+                    return Err(FsError::NoDeviceSpace);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Charge a new inode, fail if there are too many inodes.
+    fn charge_inode(&self, inode: &Arc<dyn INode>) -> Result<()> {
+        if !self.is_limited() {
+            return Ok(());
+        }
+        let mut usage = self.usage.lock().unwrap();
+        usage.reclaim_if_grown();
+        self.check_free_inode(&mut usage)?;
+        usage.inodes.insert(inode_key(inode), Charge::new(inode));
+        Ok(())
+    }
+
+    /// Add the charge for the inode to grow to `size`, fail if there is no
+    /// enough space. Returns the size reserved, which is dropped by
+    /// `settle_size` after the inode grows.
+    fn charge_size(&self, inode: &Arc<dyn INode>, size: usize) -> Result<usize> {
+        let key = inode_key(inode);
+        let mut usage = self.usage.lock().unwrap();
+        let charged_size = usage.inodes.get(&key).map_or(0, |charge| charge.size);
+        let reserved = align_up(size, BLOCK_SIZE).saturating_sub(charged_size);
+        if let Some(max_size) = self.max_size {
+            if usage.size + reserved > max_size {
+                usage.reclaim();
+                if usage.size + reserved > max_size {
+                    return Err(FsError::NoDeviceSpace);
+                }
+            }
+        }
+        usage.size += reserved;
+        usage
+            .inodes
+            .entry(key)
+            .or_insert_with(|| Charge::new(inode))
+            .reserved += reserved;
+        Ok(reserved)
+    }
+
+    /// Drop the size reserved by `charge_size`, and charge the actual size of
+    /// the inode.
+    fn settle_size(&self, inode: &Arc<dyn INode>, reserved: usize, size: usize) {
+        let mut usage = self.usage.lock().unwrap();
+        let charge = usage
+            .inodes
+            .entry(inode_key(inode))
+            .or_insert_with(|| Charge::new(inode));
+        let old_size = charge.size;
+        let new_size = align_up(size, BLOCK_SIZE);
+        charge.size = new_size;
+        charge.reserved -= reserved;
+        usage.size = usage.size + new_size - old_size - reserved;
+    }
+}
+
+impl SizedRamFSINode {
+    fn new(inner: Arc<dyn INode>, fs: &Arc<SizedRamFS>) -> Arc<dyn INode> {
+        Arc::new(Self {
+            inner,
+            fs: fs.clone(),
+        })
+    }
+
+    /// Charge the size the inode may grow to before the operation, and
+    /// charge the actual size after it.
+    fn with_size_charged<T>(&self, max_size: usize, op: impl FnOnce() -> Result<T>) -> Result<T> {
+        if !self.fs.is_limited() {
+            return op();
+        }
+        let reserved = self.fs.charge_size(&self.inner, max_size)?;
+        let ret = op();
+        // Charge the reserved size if the actual one is unknown
+        let size = self
+            .inner
+            .metadata()
+            .map_or(max_size, |metadata| metadata.size);
+        self.fs.settle_size(&self.inner, reserved, size);
+        ret
+    }
+}
+
+impl INode for SizedRamFSINode {
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize> {
+        self.inner.read_at(offset, buf)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize> {
+        let end = offset.checked_add(buf.len()).ok_or(FsError::InvalidParam)?;
+        self.with_size_charged(end, || self.inner.write_at(offset, buf))
+    }
+
+    fn poll(&self) -> Result<PollStatus> {
+        self.inner.poll()
+    }
+
+    fn metadata(&self) -> Result<Metadata> {
+        self.inner.metadata()
+    }
+
+    fn set_metadata(&self, metadata: &Metadata) -> Result<()> {
+        self.inner.set_metadata(metadata)
+    }
+
+    fn sync_all(&self) -> Result<()> {
+        self.inner.sync_all()
+    }
+
+    fn sync_data(&self) -> Result<()> {
+        self.inner.sync_data()
+    }
+
+    fn resize(&self, len: usize) -> Result<()> {
+        self.with_size_charged(len, || self.inner.resize(len))
+    }
+
+    fn fallocate(&self, mode: &FallocateMode, offset: usize, len: usize) -> Result<()> {
+        let end = offset.checked_add(len).ok_or(FsError::InvalidParam)?;
+        let max_size = match mode {
+            FallocateMode::InsertRange => self.inner.metadata()?.size.saturating_add(len),
+            _ => end,
+        };
+        self.with_size_charged(max_size, || self.inner.fallocate(mode, offset, len))
+    }
+
+    fn create(&self, name: &str, type_: FileType, mode: u16) -> Result<Arc<dyn INode>> {
+        // Check the limit before creating, then charge the created one
+        self.fs
+            .check_free_inode(&mut self.fs.usage.lock().unwrap())?;
+        let inode = self.inner.create(name, type_, mode)?;
+        if let Err(e) = self.fs.charge_inode(&inode) {
+            self.inner.unlink(name)?;
+            return Err(e);
+        }
+        Ok(SizedRamFSINode::new(inode, &self.fs))
+    }
+
+    fn link(&self, name: &str, other: &Arc<dyn INode>) -> Result<()> {
+        let other = other.downcast_ref::<Self>().ok_or(FsError::NotSameFs)?;
+        if !Arc::ptr_eq(&self.fs, &other.fs) {
+            return Err(FsError::NotSameFs);
+        }
+        self.inner.link(name, &other.inner)
+    }
+
+    fn unlink(&self, name: &str) -> Result<()> {
+        // The charges are reclaimed after the inode is freed
+        self.inner.unlink(name)
+    }
+
+    fn move_(&self, old_name: &str, target: &Arc<dyn INode>, new_name: &str) -> Result<()> {
+        let target = target.downcast_ref::<Self>().ok_or(FsError::NotSameFs)?;
+        if !Arc::ptr_eq(&self.fs, &target.fs) {
+            return Err(FsError::NotSameFs);
+        }
+        self.inner.move_(old_name, &target.inner, new_name)
+    }
+
+    fn find(&self, name: &str) -> Result<Arc<dyn INode>> {
+        let inode = self.inner.find(name)?;
+        Ok(SizedRamFSINode::new(inode, &self.fs))
+    }
+
+    fn get_entry(&self, id: usize) -> Result<String> {
+        self.inner.get_entry(id)
+    }
+
+    fn iterate_entries(&self, ctx: &mut DirentWriterContext) -> Result<usize> {
+        self.inner.iterate_entries(ctx)
+    }
+
+    fn io_control(&self, cmd: u32, data: usize) -> Result<()> {
+        self.inner.io_control(cmd, data)
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.clone()
+    }
+
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
+
+    fn ext(&self) -> Option<&Extension> {
+        self.inner.ext()
+    }
+}
+
+/// The key of the inode, i.e., the address of it. The address can not be
+/// reused as long as the weak reference to the inode is kept in the usage.
+fn inode_key(inode: &Arc<dyn INode>) -> usize {
+    Arc::as_ptr(inode) as *const u8 as usize
+}
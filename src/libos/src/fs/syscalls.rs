@@ -2,7 +2,8 @@ use super::event_file::EventCreationFlags;
 use super::file_ops;
 use super::file_ops::{
     get_abs_path_by_fd, get_utimes, AccessibilityCheckFlags, AccessibilityCheckMode, ChownFlags,
-    FcntlCmd, FsPath, LinkFlags, StatFlags, UnlinkFlags, Utime, UtimeFlags, AT_FDCWD, UTIME_OMIT,
+    FadviseAdvice, FcntlCmd, FsPath, LinkFlags, StatFlags, SyncFileRangeFlags, UnlinkFlags, Utime,
+    UtimeFlags, AT_FDCWD, UTIME_OMIT,
 };
 use super::fs_ops;
 use super::fs_ops::{MountFlags, MountOptions, UmountFlags};
@@ -347,6 +348,32 @@ pub fn do_fdatasync(fd: FileDesc) -> Result<isize> {
     Ok(0)
 }
 
+pub fn do_sync_file_range(fd: FileDesc, offset: off_t, nbytes: off_t, flags: u32) -> Result<isize> {
+    if offset < 0 || nbytes < 0 || offset.checked_add(nbytes).is_none() {
+        return_errno!(EINVAL, "invalid offset or nbytes");
+    }
+    let flags = SyncFileRangeFlags::from_u32(flags)?;
+    file_ops::do_sync_file_range(fd, offset as usize, nbytes as usize, flags)?;
+    Ok(0)
+}
+
+pub fn do_fadvise64(fd: FileDesc, offset: off_t, len: off_t, advice: i32) -> Result<isize> {
+    if offset < 0 || len < 0 {
+        return_errno!(EINVAL, "offset or len was less than 0");
+    }
+    let advice = FadviseAdvice::from_i32(advice)?;
+    file_ops::do_fadvise(fd, offset as usize, len as usize, advice)?;
+    Ok(0)
+}
+
+pub fn do_readahead(fd: FileDesc, offset: off_t, count: usize) -> Result<isize> {
+    if offset < 0 {
+        return_errno!(EINVAL, "offset was less than 0");
+    }
+    file_ops::do_readahead(fd, offset as usize, count)?;
+    Ok(0)
+}
+
 pub fn do_truncate(path: *const i8, len: usize) -> Result<isize> {
     let path = from_user::clone_cstring_safely(path)?
         .to_string_lossy()
@@ -383,6 +410,11 @@ pub fn do_sync() -> Result<isize> {
     Ok(0)
 }
 
+pub fn do_syncfs(fd: FileDesc) -> Result<isize> {
+    fs_ops::do_syncfs(fd)?;
+    Ok(0)
+}
+
 pub fn do_pipe(fds_u: *mut i32) -> Result<isize> {
     do_pipe2(fds_u, 0)
 }
@@ -486,6 +518,19 @@ pub fn do_mkdirat(dirfd: i32, path: *const i8, mode: u16) -> Result<isize> {
     Ok(0)
 }
 
+pub fn do_mknod(path: *const i8, mode: u32, dev: u64) -> Result<isize> {
+    self::do_mknodat(AT_FDCWD, path, mode, dev)
+}
+
+pub fn do_mknodat(dirfd: i32, path: *const i8, mode: u32, dev: u64) -> Result<isize> {
+    let path = from_user::clone_cstring_safely(path)?
+        .to_string_lossy()
+        .into_owned();
+    let fs_path = FsPath::new(&path, dirfd, false)?;
+    file_ops::do_mknodat(&fs_path, mode, dev)?;
+    Ok(0)
+}
+
 pub fn do_rmdir(path: *const i8) -> Result<isize> {
     let path = from_user::clone_cstring_safely(path)?
         .to_string_lossy()
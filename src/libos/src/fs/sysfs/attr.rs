@@ -0,0 +1,36 @@
+use super::*;
+
+/// A read-only attribute file in sysfs.
+///
+/// The content is generated by the getter every time the file is read, and a
+/// newline is appended as Linux does.
+pub struct AttrINode {
+    getter: Box<dyn Fn() -> String + Send + Sync>,
+}
+
+impl AttrINode {
+    pub fn new(
+        ids: &InodeIds,
+        getter: impl Fn() -> String + Send + Sync + 'static,
+    ) -> Arc<dyn INode> {
+        Arc::new(File::new(
+            Self {
+                getter: Box::new(getter),
+            },
+            ids,
+        ))
+    }
+
+    /// Create an attribute file whose content never changes.
+    pub fn new_const(ids: &InodeIds, value: String) -> Arc<dyn INode> {
+        Self::new(ids, move || value.clone())
+    }
+}
+
+impl ProcINode for AttrINode {
+    fn generate_data_in_bytes(&self) -> vfs::Result<Vec<u8>> {
+        let mut data = (self.getter)();
+        data.push('\n');
+        Ok(data.into_bytes())
+    }
+}
@@ -0,0 +1,49 @@
+use super::*;
+use crate::config::LIBOS_CONFIG;
+use crate::sched::AVAIL_CPUSET;
+use crate::vm::USER_SPACE_VM_MANAGER;
+
+// The default period of the CPU bandwidth control in microseconds
+const CPU_MAX_PERIOD_US: usize = 100_000;
+
+// The cgroup v2 style files in /sys/fs/cgroup.
+//
+// There is only one cgroup, i.e., the root cgroup, which contains all the
+// processes in the enclave. Its limits reflect the configuration of the
+// enclave, e.g.,
+// - memory.max: the user_space_max_size in the resource limits
+// - cpu.max: the quota is the count of the CPUs available to the enclave
+
+pub fn init_cgroup_dir(cgroup_dir: &Arc<Dir<LockedStaticDirINode>>, ids: &InodeIds) {
+    let cgroup = cgroup_dir.inner();
+    cgroup.add_entry(
+        "cgroup.controllers",
+        AttrINode::new_const(ids, String::from("cpu memory")),
+    );
+    cgroup.add_entry(
+        "cpu.max",
+        AttrINode::new_const(
+            ids,
+            format!(
+                "{} {}",
+                AVAIL_CPUSET.cpu_count() * CPU_MAX_PERIOD_US,
+                CPU_MAX_PERIOD_US
+            ),
+        ),
+    );
+    cgroup.add_entry(
+        "memory.max",
+        AttrINode::new_const(
+            ids,
+            LIBOS_CONFIG.resource_limits.user_space_max_size.to_string(),
+        ),
+    );
+    cgroup.add_entry(
+        "memory.current",
+        AttrINode::new(ids, || {
+            let total_size = USER_SPACE_VM_MANAGER.get_total_size();
+            let free_size = USER_SPACE_VM_MANAGER.get_precise_free_size();
+            (total_size - free_size).to_string()
+        }),
+    );
+}
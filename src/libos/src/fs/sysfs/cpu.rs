@@ -0,0 +1,233 @@
+use super::*;
+use crate::exception::get_cpuid;
+use crate::sched::{AVAIL_CPUSET, NCORES};
+
+// This file is to implement the /sys/devices/system/cpu directory.
+//
+// The CPU topology can not be obtained directly in the enclave, since CPUID
+// only reports the APIC ID of the CPU it is executed on. Thus the topology is
+// derived from the CPUID leaves 0xB and 0x4 with the assumption that the CPUs
+// are enumerated linearly, i.e., the logical processors sharing a core (or a
+// cache) have consecutive indexes.
+
+// CPUID leaf of the deterministic cache parameters
+const CPUID_LEAF_CACHE: u32 = 0x4;
+// CPUID leaf of the extended topology enumeration
+const CPUID_LEAF_TOPOLOGY: u32 = 0xB;
+
+pub fn init_cpu_dir(cpu_dir: &Arc<Dir<LockedStaticDirINode>>, ids: &InodeIds) {
+    let ncores = *NCORES;
+    let cpu = cpu_dir.inner();
+    cpu.add_entry(
+        "online",
+        AttrINode::new_const(ids, format_cpu_list(available_cpus())),
+    );
+    cpu.add_entry(
+        "offline",
+        AttrINode::new_const(
+            ids,
+            format_cpu_list((0..ncores).filter(|idx| !AVAIL_CPUSET[*idx])),
+        ),
+    );
+    cpu.add_entry(
+        "possible",
+        AttrINode::new_const(ids, format_cpu_list(0..ncores)),
+    );
+    cpu.add_entry(
+        "present",
+        AttrINode::new_const(ids, format_cpu_list(0..ncores)),
+    );
+    cpu.add_entry(
+        "kernel_max",
+        AttrINode::new_const(ids, (ncores - 1).to_string()),
+    );
+
+    let topology = CpuTopology::new();
+    let caches = CacheInfo::get_all();
+    for idx in 0..ncores {
+        let cpu_n_dir = new_sub_dir(cpu_dir, &format!("cpu{}", idx), ids);
+        let online = if AVAIL_CPUSET[idx] { "1" } else { "0" };
+        cpu_n_dir
+            .inner()
+            .add_entry("online", AttrINode::new_const(ids, String::from(online)));
+        init_topology_dir(
+            &new_sub_dir(&cpu_n_dir, "topology", ids),
+            idx,
+            &topology,
+            ids,
+        );
+        if !caches.is_empty() {
+            init_cache_dir(&new_sub_dir(&cpu_n_dir, "cache", ids), idx, &caches, ids);
+        }
+    }
+}
+
+fn available_cpus() -> impl Iterator<Item = usize> {
+    AVAIL_CPUSET
+        .iter()
+        .enumerate()
+        .filter(|(_, available)| **available)
+        .map(|(idx, _)| idx)
+}
+
+fn init_topology_dir(
+    topology_dir: &Arc<Dir<LockedStaticDirINode>>,
+    idx: usize,
+    topology: &CpuTopology,
+    ids: &InodeIds,
+) {
+    let dir = topology_dir.inner();
+    dir.add_entry(
+        "physical_package_id",
+        AttrINode::new_const(ids, topology.package_id(idx).to_string()),
+    );
+    dir.add_entry(
+        "core_id",
+        AttrINode::new_const(ids, topology.core_id(idx).to_string()),
+    );
+    dir.add_entry(
+        "thread_siblings_list",
+        AttrINode::new_const(ids, format_cpu_list(topology.thread_siblings(idx))),
+    );
+    dir.add_entry(
+        "core_siblings_list",
+        AttrINode::new_const(ids, format_cpu_list(topology.core_siblings(idx))),
+    );
+}
+
+fn init_cache_dir(
+    cache_dir: &Arc<Dir<LockedStaticDirINode>>,
+    idx: usize,
+    caches: &[CacheInfo],
+    ids: &InodeIds,
+) {
+    for (cache_idx, cache) in caches.iter().enumerate() {
+        let index_dir = new_sub_dir(cache_dir, &format!("index{}", cache_idx), ids);
+        let dir = index_dir.inner();
+        dir.add_entry("level", AttrINode::new_const(ids, cache.level.to_string()));
+        dir.add_entry(
+            "type",
+            AttrINode::new_const(ids, String::from(cache.type_name())),
+        );
+        dir.add_entry(
+            "size",
+            AttrINode::new_const(ids, format!("{}K", cache.size() / 1024)),
+        );
+        dir.add_entry(
+            "ways_of_associativity",
+            AttrINode::new_const(ids, cache.ways.to_string()),
+        );
+        dir.add_entry(
+            "coherency_line_size",
+            AttrINode::new_const(ids, cache.line_size.to_string()),
+        );
+        dir.add_entry(
+            "number_of_sets",
+            AttrINode::new_const(ids, cache.sets.to_string()),
+        );
+        dir.add_entry(
+            "shared_cpu_list",
+            AttrINode::new_const(ids, format_cpu_list(group_of(idx, cache.shared_cpus))),
+        );
+    }
+}
+
+/// The indexes of the CPUs in the same group with the CPU, if every
+/// `group_size` CPUs form a group.
+fn group_of(idx: usize, group_size: usize) -> impl Iterator<Item = usize> {
+    let start = idx / group_size * group_size;
+    let end = (start + group_size).min(*NCORES);
+    start..end
+}
+
+struct CpuTopology {
+    // The number of logical processors in a core
+    threads_per_core: usize,
+    // The number of logical processors in a physical package
+    threads_per_package: usize,
+}
+
+impl CpuTopology {
+    fn new() -> Self {
+        // The level type in ECX[15:8] of sub-leaf 0 is SMT, and the one of
+        // sub-leaf 1 is core. EBX[15:0] is the number of logical processors
+        // at the level.
+        let smt_level = get_cpuid(CPUID_LEAF_TOPOLOGY, 0);
+        let core_level = get_cpuid(CPUID_LEAF_TOPOLOGY, 1);
+        let threads_per_core = ((smt_level.ebx & 0xFFFF) as usize).max(1);
+        let threads_per_package = if (core_level.ecx & 0xFF00) >> 8 == 2 {
+            ((core_level.ebx & 0xFFFF) as usize).max(threads_per_core)
+        } else {
+            // The topology leaf is not supported, treat all CPUs as one package
+            *NCORES
+        };
+        Self {
+            threads_per_core,
+            threads_per_package,
+        }
+    }
+
+    fn package_id(&self, idx: usize) -> usize {
+        idx / self.threads_per_package
+    }
+
+    fn core_id(&self, idx: usize) -> usize {
+        idx % self.threads_per_package / self.threads_per_core
+    }
+
+    fn thread_siblings(&self, idx: usize) -> impl Iterator<Item = usize> {
+        group_of(idx, self.threads_per_core)
+    }
+
+    fn core_siblings(&self, idx: usize) -> impl Iterator<Item = usize> {
+        group_of(idx, self.threads_per_package)
+    }
+}
+
+struct CacheInfo {
+    level: u32,
+    // 1 for data cache, 2 for instruction cache and 3 for unified cache
+    type_: u32,
+    ways: usize,
+    partitions: usize,
+    line_size: usize,
+    sets: usize,
+    // The number of logical processors sharing the cache
+    shared_cpus: usize,
+}
+
+impl CacheInfo {
+    fn get_all() -> Vec<CacheInfo> {
+        let mut caches = Vec::new();
+        for subleaf in 0.. {
+            let result = get_cpuid(CPUID_LEAF_CACHE, subleaf);
+            let type_ = result.eax & 0x1F;
+            // Cache type 0 means no more caches
+            if type_ == 0 {
+                break;
+            }
+            caches.push(CacheInfo {
+                level: (result.eax >> 5) & 0x7,
+                type_,
+                ways: ((result.ebx >> 22) & 0x3FF) as usize + 1,
+                partitions: ((result.ebx >> 12) & 0x3FF) as usize + 1,
+                line_size: (result.ebx & 0xFFF) as usize + 1,
+                sets: result.ecx as usize + 1,
+                shared_cpus: ((result.eax >> 14) & 0xFFF) as usize + 1,
+            });
+        }
+        caches
+    }
+
+    fn size(&self) -> usize {
+        self.ways * self.partitions * self.line_size * self.sets
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self.type_ {
+            1 => "Data",
+            2 => "Instruction",
+            _ => "Unified",
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! A minimal sysfs.
+//!
+//! Many runtimes, e.g., JVM, Go and OpenMP, read the CPU topology and the
+//! resource limits from sysfs to decide the size of their thread pools and
+//! heaps. This file system provides the read-only files for them:
+//! - /sys/devices/system/cpu: the CPU sets, and the topology and caches of each CPU
+//! - /sys/fs/cgroup: the cgroup v2 style CPU and memory limits of the enclave
+use super::procfs::proc_inode::{Dir, File, InodeIds, LockedStaticDirINode, ProcINode};
+use super::*;
+use alloc::sync::{Arc, Weak};
+use rcore_fs::vfs;
+
+use self::attr::AttrINode;
+
+mod attr;
+mod cgroup;
+mod cpu;
+
+// Same with the sysfs on Linux
+const SYSFS_MAGIC: usize = 0x6265_6572;
+
+// Same with the sysfs on Linux
+const SYSFS_ROOT_INO: usize = 1;
+
+/// Sys file system
+pub struct SysFS {
+    root: Arc<Dir<LockedStaticDirINode>>,
+}
+
+impl FileSystem for SysFS {
+    fn sync(&self) -> vfs::Result<()> {
+        Ok(())
+    }
+
+    fn root_inode(&self) -> Arc<dyn INode> {
+        Arc::clone(&self.root) as _
+    }
+
+    fn info(&self) -> vfs::FsInfo {
+        vfs::FsInfo {
+            magic: SYSFS_MAGIC,
+            bsize: 4096,
+            frsize: 4096,
+            blocks: 0,
+            bfree: 0,
+            bavail: 0,
+            files: 0,
+            ffree: 0,
+            namemax: 255,
+        }
+    }
+}
+
+impl SysFS {
+    /// Create a new `SysFS`, which has its own device ID.
+    pub fn new() -> Arc<SysFS> {
+        let ids = InodeIds::new(SYSFS_ROOT_INO);
+        let root = LockedStaticDirINode::new_root(&ids);
+
+        let devices_dir = new_sub_dir(&root, "devices", &ids);
+        let system_dir = new_sub_dir(&devices_dir, "system", &ids);
+        let cpu_dir = new_sub_dir(&system_dir, "cpu", &ids);
+        cpu::init_cpu_dir(&cpu_dir, &ids);
+
+        let fs_dir = new_sub_dir(&root, "fs", &ids);
+        let cgroup_dir = new_sub_dir(&fs_dir, "cgroup", &ids);
+        cgroup::init_cgroup_dir(&cgroup_dir, &ids);
+
+        Arc::new(SysFS { root })
+    }
+}
+
+/// Create a directory with the name in the parent directory.
+fn new_sub_dir(
+    parent: &Arc<Dir<LockedStaticDirINode>>,
+    name: &str,
+    ids: &InodeIds,
+) -> Arc<Dir<LockedStaticDirINode>> {
+    let dir = LockedStaticDirINode::new(Arc::downgrade(parent) as _, ids);
+    parent.inner().add_entry(name, Arc::clone(&dir) as _);
+    dir
+}
+
+/// Format the CPU indexes in the kernel's list format, e.g., "0-3,6,8-9".
+///
+/// The indexes must be in ascending order.
+fn format_cpu_list(cpus: impl Iterator<Item = usize>) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == cpu => *end = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
@@ -3,5 +3,5 @@ use super::*;
 mod shm;
 mod syscalls;
 
-pub use self::shm::{key_t, shmids_t, SYSTEM_V_SHM_MANAGER};
+pub use self::shm::{key_t, set_shmmax, shmids_t, shmmax, shmmni, SYSTEM_V_SHM_MANAGER};
 pub use self::syscalls::{do_shmat, do_shmctl, do_shmdt, do_shmget};
@@ -8,6 +8,7 @@ use crate::vm::{
     USER_SPACE_VM_MANAGER,
 };
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[allow(non_camel_case_types)]
 pub type key_t = u32;
@@ -22,6 +23,30 @@ const SHMMAX: usize = (usize::MAX - (1_usize << 24));
 // also indicates the max shmid - 1 in Occlum
 const SHMMNI: ShmId = 4096;
 
+// max shared seg size (bytes) at runtime, which can be changed by /proc/sys/kernel/shmmax
+static SHM_MAX_SIZE: AtomicUsize = AtomicUsize::new(SHMMAX);
+
+/// Get the max size of a shared memory segment.
+pub fn shmmax() -> usize {
+    SHM_MAX_SIZE.load(Ordering::Relaxed)
+}
+
+/// Set the max size of the shared memory segments created later.
+pub fn set_shmmax(size: usize) -> Result<()> {
+    if size < SHMMIN || size > SHMMAX {
+        return_errno!(EINVAL, "invalid shmmax");
+    }
+    SHM_MAX_SIZE.store(size, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Get the max number of the shared memory segments.
+///
+/// The value is fixed since the shmids are allocated within it.
+pub fn shmmni() -> u32 {
+    SHMMNI
+}
+
 const IPC_PRIVATE: key_t = 0;
 
 // For cmd in shmctl()
@@ -379,7 +404,7 @@ impl ShmManager {
         );
 
         // Check the size from user for shm creation
-        if shmflg.contains(ShmFlags::IPC_CREAT) && (size < SHMMIN || size > SHMMAX) {
+        if shmflg.contains(ShmFlags::IPC_CREAT) && (size < SHMMIN || size > shmmax()) {
             return_errno!(EINVAL, "invalid size");
         }
 
@@ -10,4 +10,7 @@ mod uname;
 pub use self::random::{do_getrandom, get_random, RandFlags};
 pub use self::rlimit::{do_prlimit, resource_t, rlimit_t, ResourceLimits};
 pub use self::sysinfo::{do_sysinfo, sysinfo_t};
-pub use self::uname::{do_uname, init_nodename, utsname_t};
+pub use self::uname::{
+    do_uname, get_nodename, get_release, get_sysname, get_version_string, init_nodename,
+    set_nodename, utsname_t,
+};
@@ -41,6 +41,16 @@ lazy_static! {
     static ref DOMAINNAME: CString = CString::new("").unwrap();
 }
 
+/// Get the version information in the format of /proc/version.
+pub fn get_version_string() -> String {
+    format!(
+        "{} version {} {}\n",
+        SYSNAME.to_string_lossy(),
+        RELEASE.to_string_lossy(),
+        VERSION.to_string_lossy()
+    )
+}
+
 fn copy_from_cstr_to_u8_array(src: &CStr, dst: &mut [u8]) {
     let src: &[u8] = src.to_bytes_with_nul();
     let len = min(dst.len() - 1, src.len());
@@ -53,3 +63,27 @@ pub fn init_nodename(nodename_str: &str) {
     let mut nodename = NODENAME.write().unwrap();
     *nodename = nodename_cstr;
 }
+
+pub fn get_nodename() -> String {
+    NODENAME.read().unwrap().to_string_lossy().into_owned()
+}
+
+/// Set the nodename at runtime, e.g., by writing /proc/sys/kernel/hostname.
+pub fn set_nodename(nodename_str: &str) -> Result<()> {
+    // The nodename must fit in the utsname_t with the terminating null byte
+    if nodename_str.len() >= 65 {
+        return_errno!(EINVAL, "the nodename is too long");
+    }
+    let nodename_cstr =
+        CString::new(nodename_str).map_err(|_| errno!(EINVAL, "invalid nodename"))?;
+    *NODENAME.write().unwrap() = nodename_cstr;
+    Ok(())
+}
+
+pub fn get_sysname() -> String {
+    SYSNAME.to_string_lossy().into_owned()
+}
+
+pub fn get_release() -> String {
+    RELEASE.to_string_lossy().into_owned()
+}
@@ -2,6 +2,7 @@ use std::any::Any;
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
 use std::mem::{self, MaybeUninit};
+use std::ptr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Weak;
 use std::time::Duration;
@@ -12,18 +13,18 @@ use super::epoll_waiter::EpollWaiter;
 use super::host_file_epoller::HostFileEpoller;
 use super::{EpollCtl, EpollEvent, EpollFlags};
 use crate::events::{Observer, Waiter, WaiterQueue};
-use crate::fs::{
-    AtomicIoEvents, File, FileTableEvent, FileTableNotifier, HostFd, IoEvents, IoNotifier,
-};
+use crate::fs::{AtomicIoEvents, File, HostFd, IoEvents, IoNotifier};
 use crate::prelude::*;
 
-// TODO: Prevent two epoll files from monitoring each other, which may cause
-// deadlock in the current implementation.
-// TODO: Fix unreliable EpollFiles after process spawning. EpollFile is connected
-// with the current process's file table by regitering itself as an observer
-// to the file table. But if an EpollFile is cloned or inherited by a child
-// process, then this EpollFile still has connection with the parent process's
-// file table, which is problematic.
+// The max depth of the epoll files nested in an epoll file, same with Linux
+const EP_MAX_NESTS: usize = 4;
+
+lazy_static! {
+    // Serialize the additions of epoll files to epoll files, so that two epoll
+    // files cannot be added to each other concurrently without the loop check
+    // noticing it.
+    static ref EPOLL_NESTING_LOCK: SgxMutex<()> = SgxMutex::new(());
+}
 
 /// A file that provides epoll API.
 ///
@@ -50,6 +51,15 @@ use crate::prelude::*;
 ///
 /// This way, both LibOS files and host files can notify the `EpollFile` about
 /// their events.
+///
+/// The interesting files are held weakly. Same with Linux, a file is removed
+/// from the interest list once the file is closed, i.e., all the file descriptors
+/// referring to it are closed in all processes, rather than once one of the file
+/// descriptors is closed. Thus an `EpollFile` inherited by a spawned child process
+/// keeps working no matter whether the parent closes its file descriptors or exits.
+///
+/// An epoll file can monitor other epoll files, as long as they do not monitor
+/// each other directly or indirectly and they are not nested too deep.
 pub struct EpollFile {
     // All interesting entries.
     interest: SgxMutex<HashMap<FileDesc, Arc<EpollEntry>>>,
@@ -61,6 +71,8 @@ pub struct EpollFile {
     notifier: IoNotifier,
     // A helper to poll the events on the interesting host files.
     host_file_epoller: HostFileEpoller,
+    // The epoll files that monitor this epoll file.
+    parents: SgxMutex<Vec<Weak<EpollFile>>>,
     // Any EpollFile is wrapped with Arc when created.
     weak_self: Weak<Self>,
     // Host events
@@ -74,22 +86,21 @@ impl EpollFile {
         let waiters = WaiterQueue::new();
         let notifier = IoNotifier::new();
         let host_file_epoller = HostFileEpoller::new();
+        let parents = Default::default();
         let weak_self = Default::default();
         let host_events = Atomic::new(IoEvents::empty());
 
-        let arc_self = Self {
+        Self {
             interest,
             ready,
             waiters,
             notifier,
             host_file_epoller,
+            parents,
             weak_self,
             host_events,
         }
-        .wrap_self();
-
-        arc_self.register_to_file_table();
-        arc_self
+        .wrap_self()
     }
 
     fn wrap_self(self) -> Arc<Self> {
@@ -105,32 +116,21 @@ impl EpollFile {
         strong_self
     }
 
-    fn register_to_file_table(&self) {
-        let weak_observer = self.weak_self.clone() as Weak<dyn Observer<_>>;
-        let thread = current!();
-        let file_table = thread.files().lock().unwrap();
-        file_table.notifier().register(weak_observer, None, None);
-    }
-
-    fn unregister_from_file_table(&self) {
-        let weak_observer = self.weak_self.clone() as Weak<dyn Observer<_>>;
-        let thread = current!();
-        let file_table = thread.files().lock().unwrap();
-        file_table.notifier().unregister(&weak_observer);
-    }
-
     pub fn control(&self, cmd: &EpollCtl) -> Result<()> {
         debug!("epoll control: cmd = {:?}", cmd);
 
         match cmd {
             EpollCtl::Add(fd, event, flags) => {
-                self.add_interest(*fd, *event, *flags)?;
+                let file = current!().file(*fd)?;
+                self.add_interest(*fd, file, *event, self.prepare_flags(*flags))?;
             }
             EpollCtl::Del(fd) => {
-                self.del_interest(*fd)?;
+                let file = current!().file(*fd)?;
+                self.del_interest(*fd, &file)?;
             }
             EpollCtl::Mod(fd, event, flags) => {
-                self.mod_interest(*fd, *event, *flags)?;
+                let file = current!().file(*fd)?;
+                self.mod_interest(*fd, &file, *event, self.prepare_flags(*flags))?;
             }
         }
         Ok(())
@@ -176,10 +176,13 @@ impl EpollFile {
                         continue;
                     }
 
-                    // Poll the file that corresponds to the entry
+                    // Poll the file that corresponds to the entry, unless it has been closed
+                    let file = match ep_entry.file() {
+                        None => continue,
+                        Some(file) => file,
+                    };
                     let mut inner = ep_entry.inner.lock().unwrap();
                     let mask = inner.event.mask();
-                    let file = &ep_entry.file;
                     let events = file.poll_new() & mask;
                     if events.is_empty() {
                         continue;
@@ -204,7 +207,7 @@ impl EpollFile {
                         drop(inner);
 
                         // Host files should not be reinserted into the ready list
-                        if ep_entry.file.host_fd().is_none() {
+                        if !ep_entry.is_host_file {
                             reinsert.push_back(ep_entry);
                         }
                     }
@@ -234,41 +237,78 @@ impl EpollFile {
         }
     }
 
-    fn add_interest(&self, fd: FileDesc, mut event: EpollEvent, flags: EpollFlags) -> Result<()> {
-        let file = current!().file(fd)?;
-
+    fn add_interest(
+        &self,
+        fd: FileDesc,
+        file: FileRef,
+        mut event: EpollEvent,
+        flags: EpollFlags,
+    ) -> Result<()> {
         let arc_self = self.weak_self.upgrade().unwrap();
         if Arc::ptr_eq(&(arc_self as Arc<dyn File>), &file) {
             return_errno!(EINVAL, "a epoll file cannot epoll itself");
         }
 
-        self.check_flags(&flags);
+        if flags.contains(EpollFlags::EXCLUSIVE) {
+            if file.as_epoll_file().is_ok() {
+                return_errno!(EINVAL, "an epoll file cannot be added exclusively");
+            }
+            let exclusive_events = IoEvents::IN | IoEvents::OUT | IoEvents::ERR | IoEvents::HUP;
+            if flags.contains(EpollFlags::ONE_SHOT) || !exclusive_events.contains(event.mask) {
+                return_errno!(EINVAL, "the events cannot be used with EPOLLEXCLUSIVE");
+            }
+        }
         self.prepare_event(&mut event);
 
-        let ep_entry = Arc::new(EpollEntry::new(fd, file, event, flags));
+        // Hold the lock until the epoll file is added, if it is one
+        let _nesting_guard = match file.as_epoll_file() {
+            Ok(epoll_file) => {
+                let nesting_guard = EPOLL_NESTING_LOCK.lock().unwrap();
+                self.check_loop(epoll_file)?;
+                Some(nesting_guard)
+            }
+            Err(_) => None,
+        };
+
+        let ep_entry = Arc::new(EpollEntry::new(fd, &file, event, flags));
 
         // A critical section protected by the lock of self.interest
         {
-            let notifier = ep_entry
-                .file
+            let notifier = file
                 .notifier()
                 .ok_or_else(|| errno!(EINVAL, "a file must has an associated notifier"))?;
 
             let mut interest_entries = self.interest.lock().unwrap();
-            if interest_entries.get(&fd).is_some() {
-                return_errno!(EEXIST, "fd is already registered");
+            if let Some(old_ep_entry) = interest_entries.get(&fd) {
+                if old_ep_entry.is_file(&file) {
+                    return_errno!(EEXIST, "fd is already registered");
+                }
+                // The file that was registered with the fd has been closed, or
+                // at least the fd no longer refers to it
+                let old_ep_entry = interest_entries.remove(&fd).unwrap();
+                self.release_entry(&old_ep_entry);
             }
             interest_entries.insert(fd, ep_entry.clone());
+            if let Ok(epoll_file) = file.as_epoll_file() {
+                let mut parents = epoll_file.parents.lock().unwrap();
+                parents.retain(|parent| parent.strong_count() > 0);
+                parents.push(self.weak_self.clone());
+            }
 
             // Start observing events on the target file.
             let weak_observer = self.weak_self.clone() as Weak<dyn Observer<_>>;
             let weak_ep_entry = Arc::downgrade(&ep_entry);
-            notifier.register(weak_observer, Some(IoEvents::all()), Some(weak_ep_entry));
+            if flags.contains(EpollFlags::EXCLUSIVE) {
+                // Only one of the epoll files that monitor the file exclusively is
+                // notified of an event, which avoids the thundering herd problem
+                notifier.register_exclusive(weak_observer, Some(event.mask), Some(weak_ep_entry));
+            } else {
+                notifier.register(weak_observer, Some(IoEvents::all()), Some(weak_ep_entry));
+            }
 
             // Handle host file
-            if ep_entry.file.host_fd().is_some() {
-                self.host_file_epoller
-                    .add_file(ep_entry.file.clone(), event, flags);
+            if ep_entry.is_host_file {
+                self.host_file_epoller.add_file(&file, event, flags);
                 return Ok(());
             }
         }
@@ -278,30 +318,30 @@ impl EpollFile {
         Ok(())
     }
 
-    fn del_interest(&self, fd: FileDesc) -> Result<()> {
+    fn del_interest(&self, fd: FileDesc, file: &FileRef) -> Result<()> {
         // A critical section protected by the lock of self.interest
         {
             let mut interest_entries = self.interest.lock().unwrap();
-            // There is a data-dependency, so this cannot be re-ordered,
-            // `Relaxed` should be enough.
-            let ep_entry = interest_entries
-                .remove(&fd)
-                .ok_or_else(|| errno!(ENOENT, "fd is not added"))?;
-            ep_entry.is_deleted.store(true, Ordering::Relaxed);
-
-            let notifier = ep_entry.file.notifier().unwrap();
-            let weak_observer = self.weak_self.clone() as Weak<dyn Observer<_>>;
-            notifier.unregister(&weak_observer);
-
-            if ep_entry.file.host_fd().is_some() {
-                self.host_file_epoller.del_file(&ep_entry.file);
+            match interest_entries.get(&fd) {
+                Some(ep_entry) if ep_entry.is_file(file) => {}
+                _ => return_errno!(ENOENT, "fd is not added"),
             }
+            let ep_entry = interest_entries.remove(&fd).unwrap();
+            self.release_entry(&ep_entry);
         }
         Ok(())
     }
 
-    fn mod_interest(&self, fd: FileDesc, mut event: EpollEvent, flags: EpollFlags) -> Result<()> {
-        self.check_flags(&flags);
+    fn mod_interest(
+        &self,
+        fd: FileDesc,
+        file: &FileRef,
+        mut event: EpollEvent,
+        flags: EpollFlags,
+    ) -> Result<()> {
+        if flags.contains(EpollFlags::EXCLUSIVE) {
+            return_errno!(EINVAL, "EPOLLEXCLUSIVE can only be used when adding a file");
+        }
         self.prepare_event(&mut event);
 
         // A critical section protected by the lock of self.interest
@@ -309,17 +349,20 @@ impl EpollFile {
             let mut interest_entries = self.interest.lock().unwrap();
             let ep_entry = interest_entries
                 .get(&fd)
+                .filter(|ep_entry| ep_entry.is_file(file))
                 .ok_or_else(|| errno!(ENOENT, "fd is not added"))?
                 .clone();
 
             let new_ep_inner = EpollEntryInner { event, flags };
             let mut old_ep_inner = ep_entry.inner.lock().unwrap();
+            if old_ep_inner.flags.contains(EpollFlags::EXCLUSIVE) {
+                return_errno!(EINVAL, "a file added exclusively cannot be modified");
+            }
             *old_ep_inner = new_ep_inner;
             drop(old_ep_inner);
 
-            if ep_entry.file.host_fd().is_some() {
-                self.host_file_epoller
-                    .mod_file(&ep_entry.file, event, flags);
+            if ep_entry.is_host_file {
+                self.host_file_epoller.mod_file(file, event, flags);
                 return Ok(());
             }
 
@@ -331,7 +374,96 @@ impl EpollFile {
         Ok(())
     }
 
-    fn push_ready(&self, ep_entry: Arc<EpollEntry>) {
+    /// Stop monitoring the file of an entry that has been removed from the
+    /// interest list.
+    fn release_entry(&self, ep_entry: &EpollEntry) {
+        // There is a data-dependency, so this cannot be re-ordered,
+        // `Relaxed` should be enough.
+        ep_entry.is_deleted.store(true, Ordering::Relaxed);
+
+        let file = match ep_entry.file() {
+            Some(file) => file,
+            None => {
+                // The host fd of a closed host file has been removed from the
+                // host epoll file by the host OS
+                if ep_entry.is_host_file {
+                    self.host_file_epoller.del_closed_files();
+                }
+                return;
+            }
+        };
+
+        if let Some(notifier) = file.notifier() {
+            let weak_observer = self.weak_self.clone() as Weak<dyn Observer<_>>;
+            notifier.unregister(&weak_observer);
+        }
+
+        if let Ok(epoll_file) = file.as_epoll_file() {
+            let mut parents = epoll_file.parents.lock().unwrap();
+            if let Some(idx) = parents
+                .iter()
+                .position(|parent| Weak::ptr_eq(parent, &self.weak_self))
+            {
+                parents.swap_remove(idx);
+            }
+        }
+
+        if ep_entry.is_host_file {
+            self.host_file_epoller.del_file(&file);
+        }
+    }
+
+    /// Check whether adding `epoll_file` to this epoll file would make them
+    /// monitor each other or nest too deep. Same with Linux, the depth counts
+    /// both the epoll files monitoring this one and the ones nested in
+    /// `epoll_file`.
+    fn check_loop(&self, epoll_file: &EpollFile) -> Result<()> {
+        fn visit(epoll_file: &EpollFile, inserting_into: &EpollFile, depth: usize) -> Result<()> {
+            if ptr::eq(epoll_file, inserting_into) {
+                return_errno!(ELOOP, "epoll files cannot monitor each other");
+            }
+            if depth > EP_MAX_NESTS {
+                return_errno!(ELOOP, "epoll files are nested too deep");
+            }
+            for nested_file in epoll_file.nested_epoll_files() {
+                visit(nested_file.as_epoll_file()?, inserting_into, depth + 1)?;
+            }
+            Ok(())
+        }
+
+        visit(epoll_file, self, self.upward_depth())
+    }
+
+    /// Returns the max depth of the epoll files that monitor this epoll file
+    /// directly or indirectly.
+    fn upward_depth(&self) -> usize {
+        let parents: Vec<Arc<EpollFile>> = self
+            .parents
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|parent| parent.upgrade())
+            .collect();
+        parents
+            .iter()
+            .map(|parent| parent.upward_depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the epoll files that are monitored by this epoll file.
+    fn nested_epoll_files(&self) -> Vec<FileRef> {
+        let interest_entries = self.interest.lock().unwrap();
+        interest_entries
+            .values()
+            .filter_map(|ep_entry| ep_entry.file())
+            .filter(|file| file.as_epoll_file().is_ok())
+            .collect()
+    }
+
+    /// Push the entry to the ready list, and return whether any waiter has
+    /// been woken up.
+    fn push_ready(&self, ep_entry: Arc<EpollEntry>) -> bool {
         // Fast path to avoid locking
         if ep_entry.is_ready.load(Ordering::Relaxed) {
             // Concurrency note:
@@ -342,13 +474,13 @@ impl EpollFile {
             // `false`, then the `EpollEntry` must be popped out of the ready list and its
             // corresponding file must be polled in the `wait` method. This means that we have
             // taken into account any interesting events happened on the file so far.
-            return;
+            return false;
         }
 
-        self.push_ready_iter(std::iter::once(ep_entry));
+        self.push_ready_iter(std::iter::once(ep_entry))
     }
 
-    fn push_ready_iter<I: Iterator<Item = Arc<EpollEntry>>>(&self, ep_entries: I) {
+    fn push_ready_iter<I: Iterator<Item = Arc<EpollEntry>>>(&self, ep_entries: I) -> bool {
         let mut has_pushed_any = false;
 
         // A critical section protected by self.ready.lock()
@@ -366,9 +498,7 @@ impl EpollFile {
             }
         }
 
-        if has_pushed_any {
-            self.mark_ready();
-        }
+        has_pushed_any && self.mark_ready() > 0
     }
 
     fn pop_ready(&self, max_count: usize) -> VecDeque<Arc<EpollEntry>> {
@@ -386,15 +516,18 @@ impl EpollFile {
         }
     }
 
-    fn mark_ready(&self) {
+    /// Notify the observers and wake up the waiters. Returns the number of
+    /// waiters woken up.
+    fn mark_ready(&self) -> usize {
         self.notifier.broadcast(&IoEvents::IN);
-        self.waiters.dequeue_and_wake_all();
+        self.waiters.dequeue_and_wake_all()
     }
 
-    fn check_flags(&self, flags: &EpollFlags) {
-        if flags.intersects(EpollFlags::EXCLUSIVE | EpollFlags::WAKE_UP) {
-            warn!("{:?} contains unsupported flags", flags);
-        }
+    fn prepare_flags(&self, flags: EpollFlags) -> EpollFlags {
+        // EPOLLWAKEUP keeps the system from suspending, which never happens to
+        // an enclave. So it is ignored, as Linux does for the processes without
+        // CAP_BLOCK_SUSPEND.
+        flags - EpollFlags::WAKE_UP
     }
 
     fn prepare_event(&self, event: &mut EpollEvent) {
@@ -451,12 +584,12 @@ impl Drop for EpollFile {
         // Unregister ourself from all interesting files' notifiers
         let mut interest_entries = self.interest.lock().unwrap();
         interest_entries.drain().for_each(|(_, ep_entry)| {
-            if let Some(notifier) = ep_entry.file.notifier() {
-                notifier.unregister(&self_observer);
+            if let Some(file) = ep_entry.file() {
+                if let Some(notifier) = file.notifier() {
+                    notifier.unregister(&self_observer);
+                }
             }
         });
-
-        self.unregister_from_file_table();
     }
 }
 
@@ -473,12 +606,24 @@ impl Observer<IoEvents> for EpollFile {
 
         self.push_ready(ep_entry);
     }
-}
 
-impl Observer<FileTableEvent> for EpollFile {
-    fn on_event(&self, event: &FileTableEvent, _metadata: &Option<Weak<dyn Any + Send + Sync>>) {
-        let FileTableEvent::Del(fd) = event;
-        let _ = self.del_interest(*fd);
+    fn on_exclusive_event(
+        &self,
+        _events: &IoEvents,
+        metadata: &Option<Weak<dyn Any + Send + Sync>>,
+    ) -> bool {
+        let ep_entry_opt = metadata
+            .as_ref()
+            .and_then(|weak_any| weak_any.upgrade())
+            .and_then(|strong_any| strong_any.downcast().ok());
+        let ep_entry: Arc<EpollEntry> = match ep_entry_opt {
+            None => return false,
+            Some(ep_entry) => ep_entry,
+        };
+
+        // Same as Linux, the event is passed on to the next epoll file if no
+        // thread is waiting on this one
+        self.push_ready(ep_entry)
     }
 }
 
@@ -511,7 +656,10 @@ impl AsEpollFile for FileRef {
 #[derive(Debug)]
 struct EpollEntry {
     fd: FileDesc,
-    file: FileRef,
+    // The file is held weakly, so that closing it removes it from the interest list
+    file: Weak<dyn File>,
+    // Whether the file is monitored through the host file epoller
+    is_host_file: bool,
     inner: SgxMutex<EpollEntryInner>,
     // Whether the entry is in the ready list
     is_ready: AtomicBool,
@@ -520,18 +668,33 @@ struct EpollEntry {
 }
 
 impl EpollEntry {
-    pub fn new(fd: FileDesc, file: FileRef, event: EpollEvent, flags: EpollFlags) -> Self {
+    pub fn new(fd: FileDesc, file: &FileRef, event: EpollEvent, flags: EpollFlags) -> Self {
+        let is_host_file = file.host_fd().is_some();
+        let file = Arc::downgrade(file);
         let is_ready = Default::default();
         let is_deleted = Default::default();
         let inner = SgxMutex::new(EpollEntryInner { event, flags });
         Self {
             fd,
             file,
+            is_host_file,
             inner,
             is_ready,
             is_deleted,
         }
     }
+
+    /// Returns the file, or `None` if the file has been closed.
+    pub fn file(&self) -> Option<FileRef> {
+        self.file.upgrade()
+    }
+
+    pub fn is_file(&self, file: &FileRef) -> bool {
+        ptr::eq(
+            self.file.as_ptr() as *const u8,
+            Arc::as_ptr(file) as *const u8,
+        )
+    }
 }
 
 #[derive(Debug, PartialEq)]
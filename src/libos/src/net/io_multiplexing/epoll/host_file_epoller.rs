@@ -1,17 +1,19 @@
 use std::mem::MaybeUninit;
 use std::ptr;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Weak;
 
 use super::{EpollCtl, EpollEvent, EpollFlags};
-use crate::fs::{HostFd, IoEvents};
+use crate::fs::{File, HostFd, IoEvents};
 use crate::prelude::*;
 
 /// An epoll-based helper type to poll the states of a set of host files.
 #[derive(Debug)]
 pub struct HostFileEpoller {
     /// A map from host fd to HostFile, which maintains the set of the interesting
-    /// host files and their interesting events.
-    host_files_and_events: SgxMutex<HashMap<FileDesc, (FileRef, IoEvents)>>,
+    /// host files and their interesting events. The host files are held weakly,
+    /// so that they can be closed while being monitored.
+    host_files_and_events: SgxMutex<HashMap<FileDesc, (Weak<dyn File>, IoEvents)>>,
     /// The number of the interesting host files.
     count: AtomicUsize,
     /// The host fd of the underlying host epoll file.
@@ -41,23 +43,32 @@ impl HostFileEpoller {
         }
     }
 
-    pub fn add_file(&self, host_file: FileRef, event: EpollEvent, flags: EpollFlags) -> Result<()> {
+    pub fn add_file(
+        &self,
+        host_file: &FileRef,
+        event: EpollEvent,
+        flags: EpollFlags,
+    ) -> Result<()> {
         let mut host_files_and_events = self.host_files_and_events.lock().unwrap();
         let host_fd = host_file.host_fd().unwrap().to_raw();
-        let already_added = host_files_and_events
-            .insert(host_fd, (host_file.clone(), event.mask))
-            .is_some();
-        if already_added {
-            // TODO: handle the case where one host file is somehow to be added more than once.
-            warn!(
-                "Cannot handle the case of adding the same host file twice in a robust way.
-                This can happen if the same `HostFile` is accessible via two different LibOS fds."
-            );
-            return Ok(());
+        match host_files_and_events.get(&host_fd) {
+            Some((old_host_file, _)) if old_host_file.strong_count() > 0 => {
+                // TODO: handle the case where one host file is somehow to be added more than once.
+                warn!(
+                    "Cannot handle the case of adding the same host file twice in a robust way.
+                    This can happen if the same `HostFile` is accessible via two different LibOS fds."
+                );
+                return Ok(());
+            }
+            // The host fd of a closed host file is reused
+            Some(_) => {}
+            None => {
+                self.count.fetch_add(1, Ordering::Relaxed);
+            }
         }
+        host_files_and_events.insert(host_fd, (Arc::downgrade(host_file), event.mask));
 
-        self.count.fetch_add(1, Ordering::Relaxed);
-        self.do_epoll_ctl(libc::EPOLL_CTL_ADD, &host_file, Some((event, flags)))
+        self.do_epoll_ctl(libc::EPOLL_CTL_ADD, host_file, Some((event, flags)))
 
         // Concurrency note:
         // The lock on self.host_files_and_events must be hold while invoking
@@ -98,6 +109,17 @@ impl HostFileEpoller {
         self.do_epoll_ctl(libc::EPOLL_CTL_DEL, &host_file, None)
     }
 
+    /// Forget the host files that have been closed.
+    ///
+    /// There is no need to delete them from the host epoll file, since the
+    /// host OS does so when closing their host fds.
+    pub fn del_closed_files(&self) {
+        let mut host_files_and_events = self.host_files_and_events.lock().unwrap();
+        host_files_and_events.retain(|_, (host_file, _)| host_file.strong_count() > 0);
+        self.count
+            .store(host_files_and_events.len(), Ordering::Relaxed);
+    }
+
     fn do_epoll_ctl(
         &self,
         raw_cmd: i32,
@@ -162,13 +184,16 @@ impl HostFileEpoller {
             let io_events = IoEvents::from_raw(raw_event.events as u32);
             let host_fd = raw_event.u64 as u32;
 
-            let (host_file, mask) = match host_files_and_events.get(&host_fd) {
+            let (host_file, mask) = match host_files_and_events
+                .get(&host_fd)
+                .and_then(|(host_file, mask)| Some((host_file.upgrade()?, mask)))
+            {
                 None => {
                     count -= 1;
-                    // The corresponding host file may be deleted
+                    // The corresponding host file may be deleted or closed
                     continue;
                 }
-                Some(host_file) => host_file,
+                Some(host_file_and_mask) => host_file_and_mask,
             };
 
             host_file.update_host_events(&io_events, mask, true);
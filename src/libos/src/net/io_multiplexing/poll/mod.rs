@@ -9,8 +9,7 @@ use self::event_monitor::{EventMonitor, EventMonitorBuilder};
 
 mod event_monitor;
 
-// TODO: rename this to do_poll after the old version is removed
-pub fn do_poll_new(poll_fds: &[PollFd], mut timeout: Option<&mut Duration>) -> Result<usize> {
+pub fn do_poll(poll_fds: &[PollFd], mut timeout: Option<&mut Duration>) -> Result<usize> {
     debug!("poll: poll_fds: {:?}, timeout: {:?}", poll_fds, timeout);
 
     // Always clear the revents fields first
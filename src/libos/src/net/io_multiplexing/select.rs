@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use super::poll_new::{do_poll_new, PollFd};
+use super::poll::{do_poll, PollFd};
 use crate::fs::IoEvents;
 use crate::prelude::*;
 
@@ -68,7 +68,7 @@ pub fn do_select(
     }
 
     // Do the poll syscall that is equivalent to the select syscall
-    let num_ready_fds = do_poll_new(&poll_fds, timeout)?;
+    let num_ready_fds = do_poll(&poll_fds, timeout)?;
     if num_ready_fds == 0 {
         return Ok(0);
     }
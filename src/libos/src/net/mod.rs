@@ -2,17 +2,18 @@ use super::*;
 use std;
 use untrusted::{SliceAsMutPtrAndLen, SliceAsPtrAndLen, UntrustedSlice, UntrustedSliceAlloc};
 
-pub use self::io_multiplexing::{
-    clear_notifier_status, notify_thread, wait_for_notification, EpollEvent, IoEvent, PollEvent,
-    PollEventFlags, PollFd, THREAD_NOTIFIERS,
-};
+pub use self::io_multiplexing::{EpollEvent, PollFd};
 pub use self::socket::{
-    mmsghdr, msghdr, msghdr_mut, socketpair, unix_socket, AddressFamily, AsUnixSocket, FileFlags,
-    HostSocket, HostSocketType, HowToShut, Iovs, IovsMut, MsgHdr, MsgHdrFlags, MsgHdrMut,
-    RecvFlags, SendFlags, SliceAsLibcIovec, SockAddr, SocketType, UnixAddr,
+    init_host_interfaces, mmsghdr, mmsghdr_mut, msghdr, msghdr_mut, netlink_socket,
+    recvmmsg_one_by_one, restrict_backlog, rmem_max, sendmmsg_one_by_one, set_rmem_max,
+    set_somaxconn, set_wmem_max, socketpair, somaxconn, unix_socket, wmem_max, AddressFamily,
+    AsNetlinkSocket, AsUnixSocket, FileFlags, HostSocket, HostSocketType, HowToShut, Iovs, IovsMut,
+    MsgHdr, MsgHdrFlags, MsgHdrMut, NetlinkAddr, NetlinkSocket, RecvFlags, SendFlags,
+    SliceAsLibcIovec, SockAddr, SocketType, UnixAddr,
 };
 pub use self::syscalls::*;
 
 mod io_multiplexing;
+mod policy;
 mod socket;
 mod syscalls;
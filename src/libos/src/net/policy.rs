@@ -0,0 +1,181 @@
+//! The network policy of the host sockets given in Occlum.json.
+//!
+//! If the egress (or ingress) allowlist is given, connecting or sending to a
+//! remote address (or binding and listening on a local address) is allowed
+//! only if one of the rules matches. The AF_INET and AF_INET6 addresses are
+//! checked against the hosts, ports and protocols of the rules, and the unix
+//! sockets bridged to the host are checked against the paths of the rules.
+//! The other sockets never leave the enclave.
+
+use super::*;
+use crate::config::{ConfigNetHost, ConfigNetProtocol, ConfigNetRule};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::Path;
+
+/// Check whether the host socket may connect or send to the address.
+pub fn check_egress(socket: &HostSocket, addr: &SockAddr) -> Result<()> {
+    let rules = config::LIBOS_CONFIG.network.egress.as_ref();
+    check_rules(rules, socket, addr, "egress to")
+}
+
+/// Check whether the host socket may bind or listen on the address.
+pub fn check_ingress(socket: &HostSocket, addr: &SockAddr) -> Result<()> {
+    let rules = config::LIBOS_CONFIG.network.ingress.as_ref();
+    check_rules(rules, socket, addr, "ingress on")
+}
+
+/// Check whether the unix socket may be bridged to connect to the host path.
+///
+/// The path is the LibOS path of the socket with the symlinks resolved.
+pub fn check_unix_egress(path: &Path) -> Result<()> {
+    let rules = config::LIBOS_CONFIG.network.egress.as_ref();
+    check_unix_rules(rules, path, "egress to")
+}
+
+/// Check whether the unix socket may be bridged to bind on the host path.
+///
+/// The path is the LibOS path of the socket with the symlinks resolved.
+pub fn check_unix_ingress(path: &Path) -> Result<()> {
+    let rules = config::LIBOS_CONFIG.network.ingress.as_ref();
+    check_unix_rules(rules, path, "ingress on")
+}
+
+fn check_unix_rules(
+    rules: Option<&Vec<ConfigNetRule>>,
+    path: &Path,
+    direction: &str,
+) -> Result<()> {
+    let rules = match rules {
+        Some(rules) => rules,
+        None => return Ok(()),
+    };
+    let process = current!().process().clone();
+    let exec_path = Path::new(process.exec_path());
+
+    if rules.iter().any(|rule| {
+        entry_point_matches(rule, exec_path)
+            && rule.paths.iter().any(|prefix| path.starts_with(prefix))
+    }) {
+        return Ok(());
+    }
+    warn!(
+        "{} {} by {} is denied by the network policy",
+        direction,
+        path.display(),
+        exec_path.display()
+    );
+    return_errno!(EACCES, "denied by the network policy");
+}
+
+fn check_rules(
+    rules: Option<&Vec<ConfigNetRule>>,
+    socket: &HostSocket,
+    addr: &SockAddr,
+    direction: &str,
+) -> Result<()> {
+    let rules = match rules {
+        Some(rules) => rules,
+        None => return Ok(()),
+    };
+    let (ip, port) = match addr.inet_ip_and_port() {
+        Some((ip, port)) => (canonical_ip(ip), port),
+        None => return Ok(()),
+    };
+    let protocol = match socket.socket_type() {
+        SocketType::STREAM => Some(ConfigNetProtocol::Tcp),
+        SocketType::DGRAM => Some(ConfigNetProtocol::Udp),
+        _ => None,
+    };
+    let process = current!().process().clone();
+    let exec_path = Path::new(process.exec_path());
+
+    if rules
+        .iter()
+        .any(|rule| rule_matches(rule, ip, port, protocol, exec_path))
+    {
+        return Ok(());
+    }
+    warn!(
+        "{} {} by {} is denied by the network policy",
+        direction,
+        SocketAddr::new(ip, port),
+        exec_path.display()
+    );
+    return_errno!(EACCES, "denied by the network policy");
+}
+
+fn rule_matches(
+    rule: &ConfigNetRule,
+    ip: IpAddr,
+    port: u16,
+    protocol: Option<ConfigNetProtocol>,
+    exec_path: &Path,
+) -> bool {
+    if !rule.paths.is_empty() || !entry_point_matches(rule, exec_path) {
+        return false;
+    }
+    if !rule.protocols.is_empty()
+        && !protocol.map_or(false, |protocol| rule.protocols.contains(&protocol))
+    {
+        return false;
+    }
+    if !rule.ports.is_empty()
+        && !rule
+            .ports
+            .iter()
+            .any(|(start, end)| *start <= port && port <= *end)
+    {
+        return false;
+    }
+    rule.hosts.is_empty()
+        || rule.hosts.iter().any(|host| match host {
+            ConfigNetHost::Prefix(prefix, prefix_len) => prefix_matches(*prefix, *prefix_len, ip),
+        })
+}
+
+fn entry_point_matches(rule: &ConfigNetRule, exec_path: &Path) -> bool {
+    rule.entry_points.is_empty()
+        || rule
+            .entry_points
+            .iter()
+            .any(|entry_point| exec_path.starts_with(entry_point))
+}
+
+fn prefix_matches(prefix: IpAddr, prefix_len: u8, ip: IpAddr) -> bool {
+    match (prefix, ip) {
+        (IpAddr::V4(prefix), IpAddr::V4(ip)) => {
+            bits_match(&prefix.octets(), &ip.octets(), prefix_len)
+        }
+        (IpAddr::V6(prefix), IpAddr::V6(ip)) => {
+            bits_match(&prefix.octets(), &ip.octets(), prefix_len)
+        }
+        _ => false,
+    }
+}
+
+fn bits_match(prefix: &[u8], ip: &[u8], prefix_len: u8) -> bool {
+    let num_bytes = prefix_len as usize / 8;
+    let num_bits = prefix_len as usize % 8;
+    if prefix[..num_bytes] != ip[..num_bytes] {
+        return false;
+    }
+    num_bits == 0 || (prefix[num_bytes] ^ ip[num_bytes]) >> (8 - num_bits) == 0
+}
+
+/// Treat the IPv4-mapped IPv6 addresses as the IPv4 ones, so that the IPv4
+/// rules cannot be bypassed with a dual-stack socket.
+fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(ipv6) => {
+            let octets = ipv6.octets();
+            if octets[..10].iter().all(|byte| *byte == 0) && octets[10..12] == [0xff, 0xff] {
+                IpAddr::V4(Ipv4Addr::new(
+                    octets[12], octets[13], octets[14], octets[15],
+                ))
+            } else {
+                ip
+            }
+        }
+        IpAddr::V4(_) => ip,
+    }
+}
@@ -0,0 +1,26 @@
+use super::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// The default upper limit of the backlog of a listening socket, same with Linux.
+const SOMAXCONN: u32 = 4096;
+
+static MAX_BACKLOG: AtomicU32 = AtomicU32::new(SOMAXCONN);
+
+/// Get the upper limit of the backlog, which is shown in /proc/sys/net/core/somaxconn.
+pub fn somaxconn() -> u32 {
+    MAX_BACKLOG.load(Ordering::Relaxed)
+}
+
+/// Set the upper limit of the backlog for the sockets to listen later.
+pub fn set_somaxconn(max_backlog: u32) -> Result<()> {
+    if max_backlog == 0 || max_backlog > i32::max_value() as u32 {
+        return_errno!(EINVAL, "invalid somaxconn");
+    }
+    MAX_BACKLOG.store(max_backlog, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Restrict the backlog given by listen() according to somaxconn.
+pub fn restrict_backlog(backlog: i32) -> i32 {
+    backlog.min(somaxconn() as i32)
+}
@@ -0,0 +1,42 @@
+use super::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The default upper limit of the socket buffer sizes set by SO_SNDBUF and
+/// SO_RCVBUF, same with Linux.
+const DEFAULT_MEM_MAX: usize = 208 * 1024;
+
+// The socket buffers are allocated from the enclave heap, so the sizes are
+// limited to avoid exhausting the heap, even for SO_SNDBUFFORCE and SO_RCVBUFFORCE.
+pub const MEM_MAX_LIMIT: usize = 16 * 1024 * 1024;
+
+static WMEM_MAX: AtomicUsize = AtomicUsize::new(DEFAULT_MEM_MAX);
+static RMEM_MAX: AtomicUsize = AtomicUsize::new(DEFAULT_MEM_MAX);
+
+/// Get the upper limit of SO_SNDBUF, which is shown in /proc/sys/net/core/wmem_max.
+pub fn wmem_max() -> usize {
+    WMEM_MAX.load(Ordering::Relaxed)
+}
+
+/// Set the upper limit of SO_SNDBUF.
+pub fn set_wmem_max(size: usize) -> Result<()> {
+    WMEM_MAX.store(check_mem_max(size)?, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Get the upper limit of SO_RCVBUF, which is shown in /proc/sys/net/core/rmem_max.
+pub fn rmem_max() -> usize {
+    RMEM_MAX.load(Ordering::Relaxed)
+}
+
+/// Set the upper limit of SO_RCVBUF.
+pub fn set_rmem_max(size: usize) -> Result<()> {
+    RMEM_MAX.store(check_mem_max(size)?, Ordering::Relaxed);
+    Ok(())
+}
+
+fn check_mem_max(size: usize) -> Result<usize> {
+    if size == 0 || size > MEM_MAX_LIMIT {
+        return_errno!(EINVAL, "invalid socket buffer size");
+    }
+    Ok(size)
+}
@@ -7,6 +7,28 @@ impl HostSocket {
             return self.ioctl_getifconf(arg_ref);
         }
 
+        if let Some(stream) = self.loopback_stream() {
+            match cmd {
+                IoctlCmd::FIONREAD(arg) => {
+                    **arg = stream.bytes_to_read().min(std::i32::MAX as usize) as i32;
+                    return Ok(0);
+                }
+                // Keep the host socket in sync for the file status flags
+                IoctlCmd::FIONBIO(nonblocking) => stream.set_nonblocking(**nonblocking != 0),
+                _ => {}
+            }
+        }
+        if let Some(datagram) = self.loopback_datagram() {
+            // The length of the first datagram, which is on the host if none
+            // is queued in the enclave
+            if let IoctlCmd::FIONREAD(arg) = cmd {
+                if let Some(len) = datagram.bytes_to_read() {
+                    **arg = len.min(std::i32::MAX as usize) as i32;
+                    return Ok(0);
+                }
+            }
+        }
+
         let cmd_num = cmd.cmd_num() as c_int;
         let cmd_arg_ptr = cmd.arg_ptr() as *mut c_void;
         let ret = try_libc!({
@@ -0,0 +1,505 @@
+use super::*;
+use crate::events::{Observer, Waiter, WaiterQueueObserver};
+use crate::net::socket::loopback::{
+    inet_port, is_loopback_addr, set_inet_ip_unspecified, set_inet_port, LoopbackDatagram,
+    LoopbackListener, LoopbackStream, MAX_DATAGRAM_LEN,
+};
+use crate::time::{timespec_t, timeval_t};
+use std::sync::Weak;
+use std::time::Duration;
+
+const SO_RCVTIMEO: i32 = 20;
+const SO_SNDTIMEO: i32 = 21;
+
+/// The state of the in-enclave loopback transport of a host socket.
+#[derive(Debug)]
+pub(super) enum Loopback {
+    // All the operations go through the host
+    Disabled,
+    // Listening on the host, while accepting LibOS connections in the enclave too
+    Listening(Arc<LoopbackListener>),
+    // Connected to a LibOS socket in the enclave. The host socket is idle.
+    Connected(Arc<LoopbackStream>),
+    // Bound on the host, while receiving datagrams from LibOS sockets in the enclave too
+    Bound(Arc<LoopbackDatagram>),
+}
+
+impl HostSocket {
+    fn supports_loopback(&self) -> bool {
+        (self.socket_type == SocketType::STREAM || self.socket_type == SocketType::DGRAM)
+            && (self.domain == AddressFamily::INET || self.domain == AddressFamily::INET6)
+    }
+
+    pub(super) fn loopback_stream(&self) -> Option<Arc<LoopbackStream>> {
+        match &*self.loopback.lock().unwrap() {
+            Loopback::Connected(stream) => Some(stream.clone()),
+            _ => None,
+        }
+    }
+
+    pub(super) fn loopback_listener(&self) -> Option<Arc<LoopbackListener>> {
+        match &*self.loopback.lock().unwrap() {
+            Loopback::Listening(listener) => Some(listener.clone()),
+            _ => None,
+        }
+    }
+
+    pub(super) fn loopback_datagram(&self) -> Option<Arc<LoopbackDatagram>> {
+        match &*self.loopback.lock().unwrap() {
+            Loopback::Bound(datagram) => Some(datagram.clone()),
+            _ => None,
+        }
+    }
+
+    /// Start accepting the connections from LibOS sockets in the enclave
+    /// after the host socket starts listening.
+    pub(super) fn listen_loopback(&self, backlog: i32) {
+        if !self.supports_loopback() {
+            return;
+        }
+        // Same as Linux, a negative backlog means the maximum one
+        let backlog = if backlog < 0 {
+            somaxconn() as usize
+        } else {
+            backlog.max(1) as usize
+        };
+
+        let mut loopback = self.loopback.lock().unwrap();
+        match &*loopback {
+            Loopback::Disabled => {}
+            Loopback::Listening(listener) => {
+                listener.set_backlog(backlog);
+                return;
+            }
+            Loopback::Connected(_) | Loopback::Bound(_) => return,
+        }
+
+        let addr = match self.host_addr() {
+            Ok(addr) => addr,
+            Err(e) => {
+                warn!("failed to get the listening address: {:?}", e);
+                return;
+            }
+        };
+        if let Some(listener) = LoopbackListener::register(&addr, backlog, &self.notifier) {
+            debug!("listen on the loopback transport: {:?}", listener);
+            *loopback = Loopback::Listening(listener);
+        }
+    }
+
+    /// Try to connect to a LibOS socket listening on a loopback address.
+    ///
+    /// Returns whether the connection is established in the enclave. If not,
+    /// the caller should connect through the host.
+    pub(super) fn connect_loopback(&self, peer_addr: &SockAddr) -> Result<bool> {
+        if self.socket_type != SocketType::STREAM {
+            return Ok(false);
+        }
+        let mut loopback = self.loopback.lock().unwrap();
+        match &*loopback {
+            Loopback::Disabled => {}
+            Loopback::Connected(_) => return_errno!(EISCONN, "already connected"),
+            Loopback::Listening(_) | Loopback::Bound(_) => return Ok(false),
+        }
+
+        if !self.supports_loopback()
+            || peer_addr.family().ok() != Some(self.domain)
+            || !is_loopback_addr(peer_addr)
+        {
+            return Ok(false);
+        }
+        let listener = match LoopbackListener::lookup(peer_addr) {
+            Some(listener) => listener,
+            None => return Ok(false),
+        };
+        // A socket being polled or epolled is monitored through its host fd,
+        // which cannot be switched to the enclave transport on the fly.
+        if self.notifier.has_observers() {
+            return Ok(false);
+        }
+        // Let the host report the error of connecting a connected socket
+        if self.host_peer_addr().is_ok() {
+            return Ok(false);
+        }
+
+        // Bind the host socket to reserve the local port, just like an implicit
+        // bind on connect.
+        let mut addr = self.host_addr()?;
+        if inet_port(&addr) == Some(0) {
+            let mut bind_addr = *peer_addr;
+            set_inet_port(&mut bind_addr, 0);
+            self.bind(&bind_addr)?;
+            addr = self.host_addr()?;
+        }
+
+        let stream = match listener.connect(&addr, peer_addr) {
+            Ok(stream) => Arc::new(stream),
+            Err(e) if e.errno() == EAGAIN => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let nonblocking = self.status_flags()?.contains(StatusFlags::O_NONBLOCK);
+        stream.set_nonblocking(nonblocking);
+        stream.attach(&self.notifier);
+
+        debug!("connect on the loopback transport: {:?}", stream);
+        *loopback = Loopback::Connected(stream);
+        Ok(true)
+    }
+
+    /// Accept a connection either from a LibOS socket in the enclave or from the host.
+    pub(super) fn accept_loopback_or_host(
+        &self,
+        listener: &LoopbackListener,
+        flags: FileFlags,
+    ) -> Result<(Self, Option<SockAddr>)> {
+        self.wait_loopback_or_host(false, |waiter| {
+            if let Some(stream) = listener.pop_incoming() {
+                return self.new_loopback_socket(stream, flags).map(Some);
+            }
+            if self.wait_host_readable(waiter, Some(Duration::from_secs(0)))? {
+                return self.accept_host(flags).map(Some);
+            }
+            Ok(None)
+        })
+    }
+
+    /// Start receiving the datagrams from LibOS sockets in the enclave once
+    /// the host datagram socket is bound, explicitly or implicitly.
+    pub(super) fn bind_loopback(&self) {
+        if self.socket_type != SocketType::DGRAM || !self.supports_loopback() {
+            return;
+        }
+        let mut loopback = self.loopback.lock().unwrap();
+        if !matches!(&*loopback, Loopback::Disabled) {
+            return;
+        }
+
+        let addr = match self.host_addr() {
+            Ok(addr) if inet_port(&addr) != Some(0) => addr,
+            Ok(_) => return,
+            Err(e) => {
+                warn!("failed to get the bound address: {:?}", e);
+                return;
+            }
+        };
+        if let Some(datagram) = LoopbackDatagram::register(&addr, &self.notifier) {
+            debug!("bind on the loopback transport: {:?}", datagram);
+            *loopback = Loopback::Bound(datagram);
+        }
+    }
+
+    /// Keep the peer of a datagram socket after the host socket is connected
+    /// or disconnected, so that the datagrams from other LibOS sockets are
+    /// filtered out and the datagrams without an address go to the peer.
+    pub(super) fn connect_loopback_datagram(&self, peer_addr: &Option<SockAddr>) {
+        if self.socket_type != SocketType::DGRAM {
+            return;
+        }
+        // The socket is bound implicitly by connect
+        self.bind_loopback();
+        if let Some(datagram) = self.loopback_datagram() {
+            // Connecting to an AF_UNSPEC address dissolves the association
+            let peer_addr = peer_addr.filter(|addr| inet_port(addr).is_some());
+            datagram.connect(peer_addr);
+        }
+    }
+
+    /// Find the LibOS socket that a datagram to `name`, or to the peer if
+    /// `name` is `None`, should go to in the enclave.
+    ///
+    /// Returns the receiver and the destination address.
+    pub(super) fn loopback_receiver(
+        &self,
+        name: Option<&[u8]>,
+    ) -> Option<(Arc<LoopbackDatagram>, SockAddr)> {
+        if self.socket_type != SocketType::DGRAM || !self.supports_loopback() {
+            return None;
+        }
+        let dest_addr = match name {
+            // Let the host report the error of an invalid address
+            Some(name) => unsafe {
+                SockAddr::try_from_raw(name.as_ptr() as *const libc::sockaddr, name.len() as u32)
+            }
+            .ok()?,
+            None => self.loopback_datagram()?.peer()?,
+        };
+        if dest_addr.family().ok() != Some(self.domain) || !is_loopback_addr(&dest_addr) {
+            return None;
+        }
+        let receiver = LoopbackDatagram::lookup(&dest_addr)?;
+        Some((receiver, dest_addr))
+    }
+
+    /// Try to send a datagram to a LibOS socket bound to a loopback address.
+    ///
+    /// Returns `None` if the datagram should be sent through the host.
+    pub(super) fn sendmsg_loopback(
+        &self,
+        data: &[&[u8]],
+        name: Option<&[u8]>,
+        control: Option<&[u8]>,
+    ) -> Result<Option<usize>> {
+        // The ancillary data, e.g., IP_PKTINFO, is left to the host
+        if control.is_some() {
+            return Ok(None);
+        }
+        let (receiver, dest_addr) = match self.loopback_receiver(name) {
+            Some(res) => res,
+            None => return Ok(None),
+        };
+        let len: usize = data.iter().map(|buf| buf.len()).sum();
+        if len > MAX_DATAGRAM_LEN {
+            return Ok(None);
+        }
+
+        let datagram = match self.loopback_datagram() {
+            Some(datagram) => datagram,
+            None => {
+                // Bind the host socket to get the source port, just like an
+                // implicit bind on send. A socket bound to other addresses
+                // sends through the host.
+                let mut addr = self.host_addr()?;
+                if inet_port(&addr) != Some(0) {
+                    return Ok(None);
+                }
+                set_inet_ip_unspecified(&mut addr);
+                self.bind(&addr)?;
+                match self.loopback_datagram() {
+                    Some(datagram) => datagram,
+                    None => return Ok(None),
+                }
+            }
+        };
+        let src_addr = datagram.source_addr(&dest_addr);
+        Ok(Some(receiver.deliver(&src_addr, data)))
+    }
+
+    /// Receive a datagram either from a LibOS socket in the enclave or from the host.
+    pub(super) fn recvmsg_loopback_or_host(
+        &self,
+        datagram: &LoopbackDatagram,
+        data: &mut [&mut [u8]],
+        flags: RecvFlags,
+        mut name: Option<&mut [u8]>,
+        mut control: Option<&mut [u8]>,
+    ) -> Result<(usize, usize, usize, MsgHdrFlags)> {
+        let dontwait = flags.contains(RecvFlags::MSG_DONTWAIT);
+        self.wait_loopback_or_host(dontwait, |_waiter| {
+            let peek = flags.contains(RecvFlags::MSG_PEEK);
+            if let Some((bytes_copied, len, src_addr)) = datagram.try_recv(data, peek) {
+                let name_len = name
+                    .as_deref_mut()
+                    .map_or(0, |name| src_addr.copy_to_slice(name));
+                let mut flags_recvd = MsgHdrFlags::empty();
+                if bytes_copied < len {
+                    flags_recvd |= MsgHdrFlags::MSG_TRUNC;
+                }
+                // For MSG_TRUNC the real length of the datagram is returned
+                let bytes_recvd = if flags.contains(RecvFlags::MSG_TRUNC) {
+                    len
+                } else {
+                    bytes_copied
+                };
+                return Ok(Some((bytes_recvd, name_len, 0, flags_recvd)));
+            }
+
+            match self.do_recvmsg_host(
+                data,
+                flags | RecvFlags::MSG_DONTWAIT,
+                name.as_deref_mut(),
+                control.as_deref_mut(),
+            ) {
+                Ok(res) => Ok(Some(res)),
+                Err(e) if e.errno() == EAGAIN => Ok(None),
+                Err(e) => Err(e),
+            }
+        })
+    }
+
+    /// Retry `try_op` until it is done, waiting for the in-enclave transport
+    /// or the host socket to become readable in between. `try_op` returns
+    /// `None` if it would block.
+    ///
+    /// Same as Linux, the wait is limited by SO_RCVTIMEO.
+    fn wait_loopback_or_host<T>(
+        &self,
+        dontwait: bool,
+        mut try_op: impl FnMut(&Waiter) -> Result<Option<T>>,
+    ) -> Result<T> {
+        let observer = WaiterQueueObserver::new();
+        let weak_observer = Arc::downgrade(&observer) as Weak<dyn Observer<_>>;
+        self.notifier
+            .register(weak_observer.clone(), Some(IoEvents::IN), None);
+        let waiter = Waiter::new();
+        let mut deadline = None;
+
+        let res = loop {
+            observer.waiter_queue().reset_and_enqueue(&waiter);
+
+            match try_op(&waiter) {
+                Ok(Some(res)) => break Ok(res),
+                Ok(None) => {}
+                Err(e) => break Err(e),
+            }
+            if dontwait {
+                break Err(errno!(EAGAIN, "the operation would block"));
+            }
+            match self.status_flags() {
+                Ok(status_flags) if status_flags.contains(StatusFlags::O_NONBLOCK) => {
+                    break Err(errno!(EAGAIN, "the operation would block"));
+                }
+                Ok(_) => {}
+                Err(e) => break Err(e),
+            }
+
+            let now = crate::time::up_time::get().unwrap();
+            if deadline.is_none() {
+                match self.host_recv_timeout() {
+                    Ok(timeout) => deadline = Some(timeout.map(|timeout| now + timeout)),
+                    Err(e) => break Err(e),
+                }
+            }
+            let timeout = match deadline.unwrap() {
+                Some(deadline) if deadline <= now => {
+                    break Err(errno!(EAGAIN, "timed out"));
+                }
+                Some(deadline) => Some(deadline - now),
+                None => None,
+            };
+            // The polling above may have consumed the wakeup of the waiter
+            if waiter.is_woken() {
+                continue;
+            }
+            if let Err(e) = self.wait_host_readable(&waiter, timeout) {
+                break Err(e);
+            }
+        };
+
+        self.notifier.unregister(&weak_observer);
+        res
+    }
+
+    pub(super) fn host_recv_timeout(&self) -> Result<Option<Duration>> {
+        self.host_timeout(SO_RCVTIMEO)
+    }
+
+    pub(super) fn host_send_timeout(&self) -> Result<Option<Duration>> {
+        self.host_timeout(SO_SNDTIMEO)
+    }
+
+    fn host_timeout(&self, optname: i32) -> Result<Option<Duration>> {
+        let mut timeval = timeval_t::default();
+        let mut len = std::mem::size_of::<timeval_t>() as u32;
+        try_libc!(libc::ocall::getsockopt(
+            self.raw_host_fd() as i32,
+            libc::SOL_SOCKET,
+            optname,
+            &mut timeval as *mut timeval_t as *mut c_void,
+            &mut len
+        ));
+        let timeout = timeval.as_duration();
+        if timeout == Duration::from_secs(0) {
+            Ok(None)
+        } else {
+            Ok(Some(timeout))
+        }
+    }
+
+    fn new_loopback_socket(
+        &self,
+        stream: LoopbackStream,
+        flags: FileFlags,
+    ) -> Result<(Self, Option<SockAddr>)> {
+        // The host socket of the accepted socket is never connected. It is
+        // only there for the socket options and the file status flags.
+        let socket = HostSocket::new(self.domain, self.socket_type, flags, 0)?;
+        let stream = Arc::new(stream);
+        stream.set_nonblocking(flags.contains(FileFlags::SOCK_NONBLOCK));
+        stream.attach(&socket.notifier);
+
+        let peer_addr = *stream.peer_addr();
+        debug!("accept on the loopback transport: {:?}", stream);
+        *socket.loopback.lock().unwrap() = Loopback::Connected(stream);
+        Ok((socket, Some(peer_addr)))
+    }
+
+    /// Wait until the host socket becomes readable or the waiter is woken up.
+    ///
+    /// Returns whether the host socket is readable.
+    fn wait_host_readable(&self, waiter: &Waiter, timeout: Option<Duration>) -> Result<bool> {
+        extern "C" {
+            fn occlum_ocall_poll_with_eventfd(
+                ret: *mut i32,
+                fds: *mut libc::pollfd,
+                nfds: u32,
+                timeout: *mut timespec_t,
+                eventfd_idx: i32,
+            ) -> sgx_status_t;
+        }
+
+        let mut pollfds = [
+            libc::pollfd {
+                fd: self.raw_host_fd() as i32,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: waiter.host_eventfd().host_fd() as i32,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+        let mut timeout = timeout.map(|timeout| timespec_t::from(timeout));
+        let timeout_ptr = timeout
+            .as_mut()
+            .map_or(std::ptr::null_mut(), |timeout| timeout as *mut _);
+
+        try_libc!({
+            let mut ret = 0;
+            let status = occlum_ocall_poll_with_eventfd(
+                &mut ret,
+                pollfds.as_mut_ptr(),
+                pollfds.len() as u32,
+                timeout_ptr,
+                1,
+            );
+            assert!(status == sgx_status_t::SGX_SUCCESS);
+            ret
+        });
+        Ok(pollfds[0].revents & libc::POLLIN != 0)
+    }
+
+    pub fn host_addr(&self) -> Result<SockAddr> {
+        let mut sockaddr = SockAddr::default();
+        let mut addr_len = sockaddr.len() as u32;
+        try_libc!(libc::ocall::getsockname(
+            self.raw_host_fd() as i32,
+            sockaddr.as_mut_ptr(),
+            &mut addr_len
+        ));
+        sockaddr.set_len(addr_len as usize)?;
+        Ok(sockaddr)
+    }
+
+    fn host_peer_addr(&self) -> Result<SockAddr> {
+        let mut sockaddr = SockAddr::default();
+        let mut addr_len = sockaddr.len() as u32;
+        try_libc!(libc::ocall::getpeername(
+            self.raw_host_fd() as i32,
+            sockaddr.as_mut_ptr(),
+            &mut addr_len
+        ));
+        sockaddr.set_len(addr_len as usize)?;
+        Ok(sockaddr)
+    }
+}
+
+impl Drop for HostSocket {
+    fn drop(&mut self) {
+        match &*self.loopback.lock().unwrap() {
+            Loopback::Listening(listener) => listener.shutdown(),
+            Loopback::Bound(datagram) => datagram.shutdown(),
+            _ => {}
+        }
+    }
+}
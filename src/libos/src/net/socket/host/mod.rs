@@ -11,8 +11,12 @@ use crate::fs::{
 };
 
 use crate::process::IO_BUF_SIZE;
+use crate::untrusted::IoRing;
+
+use self::loopback::Loopback;
 
 mod ioctl_impl;
+mod loopback;
 mod recv;
 mod send;
 mod socket_file;
@@ -22,7 +26,10 @@ mod socket_file;
 pub struct HostSocket {
     host_fd: HostFd,
     host_events: Atomic<IoEvents>,
-    notifier: IoNotifier,
+    notifier: Arc<IoNotifier>,
+    domain: AddressFamily,
+    socket_type: SocketType,
+    loopback: SgxMutex<Loopback>,
 }
 
 impl HostSocket {
@@ -38,16 +45,24 @@ impl HostSocket {
             protocol
         )) as FileDesc;
         let host_fd = HostFd::new(raw_host_fd);
-        Ok(HostSocket::from_host_fd(host_fd)?)
+        Ok(HostSocket::from_host_fd(host_fd, domain, socket_type)?)
     }
 
-    fn from_host_fd(host_fd: HostFd) -> Result<HostSocket> {
+    fn from_host_fd(
+        host_fd: HostFd,
+        domain: AddressFamily,
+        socket_type: SocketType,
+    ) -> Result<HostSocket> {
         let host_events = Atomic::new(IoEvents::empty());
-        let notifier = IoNotifier::new();
+        let notifier = Arc::new(IoNotifier::new());
+        let loopback = SgxMutex::new(Loopback::Disabled);
         Ok(Self {
             host_fd,
             host_events,
             notifier,
+            domain,
+            socket_type,
+            loopback,
         })
     }
 
@@ -59,15 +74,25 @@ impl HostSocket {
             addr_ptr as *const libc::sockaddr,
             addr_len as u32
         ));
+        self.bind_loopback();
         Ok(())
     }
 
     pub fn listen(&self, backlog: i32) -> Result<()> {
+        let backlog = restrict_backlog(backlog);
         let ret = try_libc!(libc::ocall::listen(self.raw_host_fd() as i32, backlog));
+        self.listen_loopback(backlog);
         Ok(())
     }
 
     pub fn accept(&self, flags: FileFlags) -> Result<(Self, Option<SockAddr>)> {
+        if let Some(listener) = self.loopback_listener() {
+            return self.accept_loopback_or_host(&listener, flags);
+        }
+        self.accept_host(flags)
+    }
+
+    fn accept_host(&self, flags: FileFlags) -> Result<(Self, Option<SockAddr>)> {
         let mut sockaddr = SockAddr::default();
         let mut addr_len = sockaddr.len();
 
@@ -85,12 +110,21 @@ impl HostSocket {
         } else {
             None
         };
-        Ok((HostSocket::from_host_fd(host_fd)?, addr_option))
+        Ok((
+            HostSocket::from_host_fd(host_fd, self.domain, self.socket_type)?,
+            addr_option,
+        ))
     }
 
     pub fn connect(&self, addr: &Option<SockAddr>) -> Result<()> {
         debug!("connect: host_fd: {}, addr {:?}", self.raw_host_fd(), addr);
 
+        if let Some(peer_addr) = addr {
+            if self.connect_loopback(peer_addr)? {
+                return Ok(());
+            }
+        }
+
         let (addr_ptr, addr_len) = if let Some(sock_addr) = addr {
             sock_addr.as_ptr_and_len()
         } else {
@@ -102,6 +136,7 @@ impl HostSocket {
             addr_ptr,
             addr_len as u32
         ));
+        self.connect_loopback_datagram(addr);
         Ok(())
     }
 
@@ -131,11 +166,40 @@ impl HostSocket {
         Ok((bytes_recv, addr_option))
     }
 
+    pub fn socket_type(&self) -> SocketType {
+        self.socket_type
+    }
+
     pub fn raw_host_fd(&self) -> FileDesc {
         self.host_fd.to_raw()
     }
 
+    /// The I/O ring if the async I/O is enabled for the type of the socket.
+    fn io_ring(&self) -> Option<&'static IoRing> {
+        let config = config::LIBOS_CONFIG.async_io.as_ref()?;
+        let enabled = match self.socket_type {
+            SocketType::STREAM => config.stream_sockets,
+            SocketType::DGRAM => config.dgram_sockets,
+            _ => false,
+        };
+        if enabled {
+            IoRing::get()
+        } else {
+            None
+        }
+    }
+
+    /// The local and peer addresses of a socket connected in the enclave,
+    /// whose host socket knows nothing about the connection.
+    pub fn loopback_addrs(&self) -> Option<(SockAddr, SockAddr)> {
+        self.loopback_stream()
+            .map(|stream| (*stream.addr(), *stream.peer_addr()))
+    }
+
     pub fn shutdown(&self, how: HowToShut) -> Result<()> {
+        if let Some(stream) = self.loopback_stream() {
+            return stream.shutdown(how);
+        }
         try_libc!(libc::ocall::shutdown(self.raw_host_fd() as i32, how.bits()));
         Ok(())
     }
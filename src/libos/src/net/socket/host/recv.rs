@@ -1,5 +1,8 @@
+use super::send::{as_bytes, as_bytes_mut};
 use super::*;
+use crate::time::timespec_t;
 use crate::untrusted::{SliceAsMutPtrAndLen, SliceAsPtrAndLen, UntrustedSliceAlloc};
+use std::time::Duration;
 
 impl HostSocket {
     pub fn recv(&self, buf: &mut [u8], flags: RecvFlags) -> Result<usize> {
@@ -11,9 +14,15 @@ impl HostSocket {
         // Do OCall-based recvmsg
         let (bytes_recvd, namelen_recvd, controllen_recvd, flags_recvd) = {
             // Acquire mutable references to the name and control buffers
-            let (iovs, name, control) = msg.get_iovs_name_and_control_mut();
+            let (iovs, name, mut control) = msg.get_iovs_name_and_control_mut();
             // Fill the data, the name, and the control buffers
-            self.do_recvmsg(iovs.as_slices_mut(), flags, name, control)?
+            let (bytes_recvd, namelen_recvd, mut controllen_recvd, mut flags_recvd) =
+                self.do_recvmsg(iovs.as_slices_mut(), flags, name, control.as_deref_mut())?;
+            if let Some(control) = control {
+                controllen_recvd =
+                    self.discard_host_fds(&mut control[..controllen_recvd], &mut flags_recvd);
+            }
+            (bytes_recvd, namelen_recvd, controllen_recvd, flags_recvd)
         };
 
         // Update the output lengths and flags
@@ -24,7 +33,185 @@ impl HostSocket {
         Ok(bytes_recvd)
     }
 
+    /// Receive multiple messages with a single OCall.
+    ///
+    /// Returns the number of bytes received for each of the messages received.
+    /// The remaining time is written back to the timeout.
+    pub fn recvmmsg(
+        &self,
+        msgs: &mut [MsgHdrMut],
+        flags: RecvFlags,
+        timeout: Option<&mut Duration>,
+    ) -> Result<Vec<usize>> {
+        if self.loopback_stream().is_some() || self.loopback_datagram().is_some() {
+            return recvmmsg_one_by_one(msgs, flags, timeout, |msg, flags| {
+                self.recvmsg(msg, flags)
+            });
+        }
+        if msgs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // The headers and the iovecs go first to keep them aligned
+        let num_iovs: usize = msgs
+            .iter()
+            .map(|msg| msg.get_iovs().as_slices().len())
+            .sum();
+        let hdrs_len = msgs.len() * mem::size_of::<mmsghdr_mut>();
+        let iovs_len = num_iovs * mem::size_of::<libc::iovec>();
+        let bufs_len: usize = msgs
+            .iter()
+            .map(|msg| {
+                msg.get_name_max_len() + msg.get_iovs().total_bytes() + msg.get_control_max_len()
+            })
+            .sum();
+        let ocall_alloc = UntrustedSliceAlloc::new(hdrs_len + iovs_len + bufs_len)?;
+        let u_allocator = ocall_alloc.guard();
+        let mut u_hdrs = u_allocator.new_slice_mut(hdrs_len)?;
+        let mut u_iovs = u_allocator.new_slice_mut(iovs_len)?;
+
+        // Allocate the buffers in the untrusted memory and fill the headers
+        let mut hdrs: Vec<mmsghdr_mut> = Vec::with_capacity(msgs.len());
+        let mut iovs: Vec<libc::iovec> = Vec::with_capacity(num_iovs);
+        let mut u_msgs = Vec::with_capacity(msgs.len());
+        for msg in msgs.iter() {
+            let mut hdr: mmsghdr_mut = unsafe { mem::zeroed() };
+            let mut u_name = u_allocator.new_slice_mut(msg.get_name_max_len())?;
+            let (msg_name, msg_namelen) = u_name.as_mut().as_mut_ptr_and_len();
+            hdr.msg_hdr.msg_name = msg_name as *mut c_void;
+            hdr.msg_hdr.msg_namelen = msg_namelen as libc::socklen_t;
+            hdr.msg_hdr.msg_iov =
+                unsafe { (u_iovs.as_mut_ptr() as *mut libc::iovec).add(iovs.len()) };
+            hdr.msg_hdr.msg_iovlen = msg.get_iovs().as_slices().len();
+            let mut u_bufs = Vec::with_capacity(hdr.msg_hdr.msg_iovlen);
+            for buf in msg.get_iovs().as_slices() {
+                let u_buf = u_allocator.new_slice_mut(buf.len())?;
+                iovs.push(u_buf.as_ref().as_libc_iovec());
+                u_bufs.push(u_buf);
+            }
+            let mut u_control = u_allocator.new_slice_mut(msg.get_control_max_len())?;
+            let (msg_control, msg_controllen) = u_control.as_mut().as_mut_ptr_and_len();
+            hdr.msg_hdr.msg_control = msg_control as *mut c_void;
+            hdr.msg_hdr.msg_controllen = msg_controllen;
+            hdrs.push(hdr);
+            u_msgs.push((u_name, u_bufs, u_control));
+        }
+        u_hdrs.read_from_slice(unsafe { as_bytes(&hdrs) })?;
+        u_iovs.read_from_slice(unsafe { as_bytes(&iovs) })?;
+
+        // Do OCall
+        let host_fd = self.raw_host_fd() as i32;
+        let mut ts = timeout.as_ref().map(|timeout| timespec_t::from(**timeout));
+        let num_recvd = try_libc!({
+            let mut retval: i32 = 0;
+            let status = occlum_ocall_recvmmsg(
+                &mut retval as *mut i32,
+                host_fd,
+                u_hdrs.as_mut_ptr() as *mut c_void,
+                msgs.len() as c_uint,
+                flags.bits(),
+                ts.as_mut()
+                    .map_or(std::ptr::null_mut(), |ts| ts as *mut timespec_t),
+            );
+            assert!(status == sgx_status_t::SGX_SUCCESS);
+            retval
+        }) as usize;
+        assert!(num_recvd <= msgs.len());
+        if let (Some(timeout), Some(ts)) = (timeout, ts) {
+            if ts.validate().is_ok() {
+                *timeout = ts.as_duration().min(*timeout);
+            }
+        }
+
+        // Check the values returned from outside the enclave and copy the
+        // received data back
+        hdrs.truncate(num_recvd);
+        u_hdrs.write_to_slice(unsafe { as_bytes_mut(&mut hdrs) })?;
+        let mut bytes_recvd = Vec::with_capacity(num_recvd);
+        for ((hdr, msg), (u_name, u_bufs, u_control)) in hdrs.iter().zip(msgs).zip(u_msgs) {
+            let mut flags_recvd = MsgHdrFlags::from_bits_truncate(hdr.msg_hdr.msg_flags);
+            let msg_len = hdr.msg_len as usize;
+            let max_msg_len = msg.get_iovs().total_bytes();
+            // For MSG_TRUNC recvmmsg returns the real length of the datagram
+            if flags.contains(RecvFlags::MSG_TRUNC) && msg_len > max_msg_len {
+                assert!(flags_recvd.contains(MsgHdrFlags::MSG_TRUNC));
+            } else {
+                assert!(msg_len <= max_msg_len);
+            }
+            let namelen_recvd = hdr.msg_hdr.msg_namelen as usize;
+            assert!(namelen_recvd <= msg.get_name_max_len());
+            let mut controllen_recvd = hdr.msg_hdr.msg_controllen;
+            assert!(controllen_recvd <= msg.get_control_max_len());
+
+            let (iovs, name, control) = msg.get_iovs_name_and_control_mut();
+            let mut remain = msg_len.min(max_msg_len);
+            for (buf, u_buf) in iovs.as_slices_mut().iter_mut().zip(u_bufs) {
+                let len = remain.min(buf.len());
+                u_buf.write_to_slice(&mut buf[..len])?;
+                remain -= len;
+            }
+            if let Some(name) = name {
+                u_name.write_to_slice(&mut name[..namelen_recvd])?;
+            }
+            if let Some(control) = control {
+                u_control.write_to_slice(&mut control[..controllen_recvd])?;
+                controllen_recvd =
+                    self.discard_host_fds(&mut control[..controllen_recvd], &mut flags_recvd);
+            }
+            msg.set_name_len(namelen_recvd)?;
+            msg.set_control_len(controllen_recvd)?;
+            msg.set_flags(flags_recvd);
+            bytes_recvd.push(msg_len);
+        }
+        Ok(bytes_recvd)
+    }
+
+    /// The fds passed by the host peer of a unix socket mean nothing inside
+    /// the enclave, so they are closed on the host and the control messages
+    /// are dropped as if truncated.
+    ///
+    /// Returns the length of the control messages kept.
+    fn discard_host_fds(&self, control: &mut [u8], flags_recvd: &mut MsgHdrFlags) -> usize {
+        if self.domain != AddressFamily::LOCAL {
+            return control.len();
+        }
+        let mut has_fds = false;
+        for cmsg in CMessages::from_bytes(control) {
+            if let CmsgData::ScmRights(scm_rights) = cmsg {
+                for host_fd in scm_rights.iter_fds() {
+                    unsafe {
+                        libc::ocall::close(host_fd as i32);
+                    }
+                }
+                has_fds = true;
+            }
+        }
+        if !has_fds {
+            return control.len();
+        }
+        warn!("the file descriptors passed from the host are discarded");
+        *flags_recvd |= MsgHdrFlags::MSG_CTRUNC;
+        0
+    }
+
     pub(super) fn do_recvmsg(
+        &self,
+        data: &mut [&mut [u8]],
+        flags: RecvFlags,
+        name: Option<&mut [u8]>,
+        control: Option<&mut [u8]>,
+    ) -> Result<(usize, usize, usize, MsgHdrFlags)> {
+        if let Some(stream) = self.loopback_stream() {
+            let bytes_recvd = stream.recvmsg(data, flags, &|| self.host_recv_timeout())?;
+            return Ok((bytes_recvd, 0, 0, MsgHdrFlags::empty()));
+        }
+        if let Some(datagram) = self.loopback_datagram() {
+            return self.recvmsg_loopback_or_host(&datagram, data, flags, name, control);
+        }
+        self.do_recvmsg_host(data, flags, name, control)
+    }
+
+    pub(super) fn do_recvmsg_host(
         &self,
         data: &mut [&mut [u8]],
         flags: RecvFlags,
@@ -74,6 +261,86 @@ impl HostSocket {
         mut name: Option<&mut [u8]>,
         mut control: Option<&mut [u8]>,
     ) -> Result<(usize, usize, usize, MsgHdrFlags)> {
+        let (retval, msg_namelen_recvd, msg_controllen_recvd, msg_flags_recvd) = match self
+            .async_recvmsg(data, flags, &mut name, control.is_some())?
+        {
+            Some(retval) => retval,
+            None => self.ocall_recvmsg(data, flags, name.as_deref_mut(), control.as_deref_mut())?,
+        };
+        let msg_namelen = name.map_or(0, |name| name.len());
+        let msg_controllen = control.map_or(0, |control| control.len());
+
+        let flags_recvd = MsgHdrFlags::from_bits(msg_flags_recvd).unwrap();
+
+        // Check values returned from outside the enclave
+        let bytes_recvd = {
+            // Check bytes_recvd returned from outside the enclave
+            let max_bytes_recvd = data.iter().map(|x| x.len()).sum();
+
+            // For MSG_TRUNC recvmsg returns the real length of the packet or datagram,
+            // even when it was longer than the passed buffer.
+            if flags.contains(RecvFlags::MSG_TRUNC) && retval > max_bytes_recvd {
+                assert!(flags_recvd.contains(MsgHdrFlags::MSG_TRUNC));
+            } else {
+                assert!(retval <= max_bytes_recvd);
+            }
+            retval
+        };
+        assert!(msg_namelen_recvd <= msg_namelen);
+        assert!(msg_controllen_recvd <= msg_controllen);
+        Ok((
+            bytes_recvd,
+            msg_namelen_recvd,
+            msg_controllen_recvd,
+            flags_recvd,
+        ))
+    }
+
+    /// Receive through the I/O ring if enabled, which never blocks the host
+    /// workers. Returns `None` if the message should be received by the OCall.
+    fn async_recvmsg(
+        &self,
+        data: &[UntrustedSlice],
+        flags: RecvFlags,
+        name: &mut Option<&mut [u8]>,
+        has_control: bool,
+    ) -> Result<Option<(usize, usize, usize, i32)>> {
+        let ring = match self.io_ring() {
+            Some(ring) => ring,
+            None => return Ok(None),
+        };
+        let name_len = name.as_ref().map_or(0, |name| name.len());
+        if has_control
+            || flags.contains(RecvFlags::MSG_WAITALL)
+            || !IoRing::supports_msg(data.len(), name_len)
+        {
+            return Ok(None);
+        }
+
+        let host_fd = self.raw_host_fd() as i32;
+        let raw_iovs: Vec<libc::iovec> = data
+            .iter()
+            .map(|slice| slice.as_ref().as_libc_iovec())
+            .collect();
+        let raw_flags = (flags | RecvFlags::MSG_DONTWAIT).bits();
+        match ring.recvmsg(host_fd, &raw_iovs, name.as_deref_mut(), raw_flags) {
+            Ok(Some((bytes_recvd, name_len_recvd, msg_flags))) => {
+                Ok(Some((bytes_recvd, name_len_recvd, 0, msg_flags)))
+            }
+            // The ring is busy
+            Ok(None) => Ok(None),
+            Err(e) if e.errno() == EAGAIN && !flags.contains(RecvFlags::MSG_DONTWAIT) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn ocall_recvmsg(
+        &self,
+        data: &mut [UntrustedSlice],
+        flags: RecvFlags,
+        mut name: Option<&mut [u8]>,
+        mut control: Option<&mut [u8]>,
+    ) -> Result<(usize, usize, usize, i32)> {
         // Prepare the arguments for OCall
         // Host socket fd
         let host_fd = self.raw_host_fd() as i32;
@@ -118,40 +385,26 @@ impl HostSocket {
             // untrusted OCall? We reset the potentially tampered buffers.
             retval
         });
-
-        let flags_recvd = MsgHdrFlags::from_bits(msg_flags_recvd).unwrap();
-
-        // Check values returned from outside the enclave
-        let bytes_recvd = {
-            // Guarantted by try_libc!
-            debug_assert!(retval >= 0);
-            let retval = retval as usize;
-
-            // Check bytes_recvd returned from outside the enclave
-            let max_bytes_recvd = data.iter().map(|x| x.len()).sum();
-
-            // For MSG_TRUNC recvmsg returns the real length of the packet or datagram,
-            // even when it was longer than the passed buffer.
-            if flags.contains(RecvFlags::MSG_TRUNC) && retval > max_bytes_recvd {
-                assert!(flags_recvd.contains(MsgHdrFlags::MSG_TRUNC));
-            } else {
-                assert!(retval <= max_bytes_recvd);
-            }
-            retval
-        };
-        let msg_namelen_recvd = msg_namelen_recvd as usize;
-        assert!(msg_namelen_recvd <= msg_namelen);
-        assert!(msg_controllen_recvd <= msg_controllen);
+        // Guaranteed by try_libc!
+        debug_assert!(retval >= 0);
         Ok((
-            bytes_recvd,
-            msg_namelen_recvd,
+            retval as usize,
+            msg_namelen_recvd as usize,
             msg_controllen_recvd,
-            flags_recvd,
+            msg_flags_recvd,
         ))
     }
 }
 
 extern "C" {
+    fn occlum_ocall_recvmmsg(
+        ret: *mut c_int,
+        fd: c_int,
+        msgvec: *mut c_void,
+        vlen: c_uint,
+        flags: c_int,
+        timeout: *mut timespec_t,
+    ) -> sgx_status_t;
     fn occlum_ocall_recvmsg(
         ret: *mut ssize_t,
         fd: c_int,
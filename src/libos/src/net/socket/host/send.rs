@@ -6,6 +6,7 @@ impl HostSocket {
     }
 
     pub fn sendmsg<'a, 'b>(&self, msg: &'b MsgHdr<'a>, flags: SendFlags) -> Result<usize> {
+        self.check_control(msg.get_control())?;
         let msg_iov = msg.get_iovs();
 
         self.do_sendmsg(
@@ -16,6 +17,122 @@ impl HostSocket {
         )
     }
 
+    /// Send multiple messages with a single OCall.
+    ///
+    /// Returns the number of bytes sent for each of the messages sent.
+    pub fn sendmmsg(&self, msgs: &[MsgHdr], flags: SendFlags) -> Result<Vec<usize>> {
+        let has_loopback = self.loopback_stream().is_some()
+            || msgs
+                .iter()
+                .any(|msg| self.loopback_receiver(msg.get_name()).is_some());
+        if has_loopback {
+            return sendmmsg_one_by_one(msgs, flags, |msg, flags| self.sendmsg(msg, flags));
+        }
+        if msgs.is_empty() {
+            return Ok(Vec::new());
+        }
+        for msg in msgs {
+            self.check_control(msg.get_control())?;
+        }
+
+        // The headers and the iovecs go first to keep them aligned
+        let num_iovs: usize = msgs
+            .iter()
+            .map(|msg| msg.get_iovs().as_slices().len())
+            .sum();
+        let hdrs_len = msgs.len() * mem::size_of::<mmsghdr>();
+        let iovs_len = num_iovs * mem::size_of::<libc::iovec>();
+        let bufs_len: usize = msgs
+            .iter()
+            .map(|msg| {
+                msg.get_name().map_or(0, |name| name.len())
+                    + msg.get_iovs().total_bytes()
+                    + msg.get_control().map_or(0, |control| control.len())
+            })
+            .sum();
+        let ocall_alloc = UntrustedSliceAlloc::new(hdrs_len + iovs_len + bufs_len)?;
+        let u_allocator = ocall_alloc.guard();
+        let mut u_hdrs = u_allocator.new_slice_mut(hdrs_len)?;
+        let mut u_iovs = u_allocator.new_slice_mut(iovs_len)?;
+
+        // Copy the buffers to the untrusted memory and fill the headers
+        let mut hdrs: Vec<mmsghdr> = Vec::with_capacity(msgs.len());
+        let mut iovs: Vec<libc::iovec> = Vec::with_capacity(num_iovs);
+        for msg in msgs {
+            let mut hdr: mmsghdr = unsafe { mem::zeroed() };
+            if let Some(name) = msg.get_name() {
+                let u_name = u_allocator.new_slice(name)?;
+                let (msg_name, msg_namelen) = u_name.as_ref().as_ptr_and_len();
+                hdr.msg_hdr.msg_name = msg_name as *const c_void;
+                hdr.msg_hdr.msg_namelen = msg_namelen as libc::socklen_t;
+            }
+            hdr.msg_hdr.msg_iov =
+                unsafe { (u_iovs.as_ptr() as *const libc::iovec).add(iovs.len()) };
+            hdr.msg_hdr.msg_iovlen = msg.get_iovs().as_slices().len();
+            for buf in msg.get_iovs().as_slices() {
+                let u_buf = u_allocator.new_slice(buf)?;
+                iovs.push(u_buf.as_ref().as_libc_iovec());
+            }
+            if let Some(control) = msg.get_control() {
+                let u_control = u_allocator.new_slice(control)?;
+                let (msg_control, msg_controllen) = u_control.as_ref().as_ptr_and_len();
+                hdr.msg_hdr.msg_control = msg_control as *const c_void;
+                hdr.msg_hdr.msg_controllen = msg_controllen;
+            }
+            hdrs.push(hdr);
+        }
+        u_hdrs.read_from_slice(unsafe { as_bytes(&hdrs) })?;
+        u_iovs.read_from_slice(unsafe { as_bytes(&iovs) })?;
+
+        // Do OCall
+        let host_fd = self.raw_host_fd() as i32;
+        let mut retval: i32 = 0;
+        let status = unsafe {
+            occlum_ocall_sendmmsg(
+                &mut retval as *mut i32,
+                host_fd,
+                u_hdrs.as_mut_ptr() as *mut c_void,
+                msgs.len() as c_uint,
+                flags.bits(),
+            )
+        };
+        assert!(status == sgx_status_t::SGX_SUCCESS);
+        let num_sent = if flags.contains(SendFlags::MSG_NOSIGNAL) {
+            try_libc!(retval)
+        } else {
+            try_libc_may_epipe!(retval)
+        } as usize;
+        assert!(num_sent <= msgs.len());
+
+        // Check the lengths returned from outside the enclave
+        hdrs.truncate(num_sent);
+        u_hdrs.write_to_slice(unsafe { as_bytes_mut(&mut hdrs) })?;
+        let bytes_sent = hdrs
+            .iter()
+            .zip(msgs)
+            .map(|(hdr, msg)| {
+                let bytes_sent = hdr.msg_len as usize;
+                assert!(bytes_sent <= msg.get_iovs().total_bytes());
+                bytes_sent
+            })
+            .collect();
+        Ok(bytes_sent)
+    }
+
+    /// The fds inside the enclave mean nothing to the host peer of a unix
+    /// socket, so passing them is refused.
+    fn check_control(&self, control: Option<&[u8]>) -> Result<()> {
+        let control = match control {
+            Some(control) if self.domain == AddressFamily::LOCAL => control,
+            _ => return Ok(()),
+        };
+        let mut control = control.to_vec();
+        if CMessages::from_bytes(&mut control).any(|cmsg| matches!(cmsg, CmsgData::ScmRights(_))) {
+            return_errno!(EOPNOTSUPP, "cannot pass file descriptors to the host");
+        }
+        Ok(())
+    }
+
     pub(super) fn do_sendmsg(
         &self,
         data: &[&[u8]],
@@ -23,6 +140,19 @@ impl HostSocket {
         name: Option<&[u8]>,
         control: Option<&[u8]>,
     ) -> Result<usize> {
+        if let Some(stream) = self.loopback_stream() {
+            if control.is_some() {
+                return_errno!(
+                    EOPNOTSUPP,
+                    "ancillary data is not supported by the loopback transport"
+                );
+            }
+            return stream.sendmsg(data, flags, &|| self.host_send_timeout());
+        }
+        if let Some(bytes_sent) = self.sendmsg_loopback(data, name, control)? {
+            return Ok(bytes_sent);
+        }
+
         let current = current!();
         let data_length = data.iter().map(|s| s.len()).sum();
         let mut ocall_alloc;
@@ -55,6 +185,79 @@ impl HostSocket {
         flags: SendFlags,
         name: Option<&[u8]>,
         control: Option<&[u8]>,
+    ) -> Result<usize> {
+        let mut raw_iovs: Vec<libc::iovec> = u_data
+            .iter()
+            .map(|slice| slice.as_ref().as_libc_iovec())
+            .collect();
+
+        let bytes_sent_async = match self.async_sendmsg(&mut raw_iovs, flags, name, control)? {
+            AsyncSent::All(bytes_sent) => return Ok(bytes_sent),
+            AsyncSent::Partial(bytes_sent) => bytes_sent,
+        };
+        match self.ocall_sendmsg(&raw_iovs, flags, name, control) {
+            Ok(bytes_sent) => Ok(bytes_sent_async + bytes_sent),
+            // Some data is already sent
+            Err(_) if bytes_sent_async > 0 => Ok(bytes_sent_async),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Send through the I/O ring if enabled, which never blocks the host
+    /// workers. The iovecs are advanced past the data sent, and the rest
+    /// should be sent by the OCall.
+    fn async_sendmsg(
+        &self,
+        raw_iovs: &mut Vec<libc::iovec>,
+        flags: SendFlags,
+        name: Option<&[u8]>,
+        control: Option<&[u8]>,
+    ) -> Result<AsyncSent> {
+        let ring = match self.io_ring() {
+            Some(ring) => ring,
+            None => return Ok(AsyncSent::Partial(0)),
+        };
+        let name_len = name.map_or(0, |name| name.len());
+        if control.is_some() || !IoRing::supports_msg(raw_iovs.len(), name_len) {
+            return Ok(AsyncSent::Partial(0));
+        }
+
+        let host_fd = self.raw_host_fd() as i32;
+        let raw_flags = (flags | SendFlags::MSG_DONTWAIT).bits();
+        let total_bytes: usize = raw_iovs.iter().map(|iov| iov.iov_len).sum();
+        match ring.sendmsg(host_fd, raw_iovs, name, raw_flags) {
+            Ok(Some(bytes_sent))
+                if bytes_sent >= total_bytes || flags.contains(SendFlags::MSG_DONTWAIT) =>
+            {
+                Ok(AsyncSent::All(bytes_sent.min(total_bytes)))
+            }
+            // The ring is busy
+            Ok(None) => Ok(AsyncSent::Partial(0)),
+            Ok(Some(bytes_sent)) => {
+                advance_iovs(raw_iovs, bytes_sent);
+                Ok(AsyncSent::Partial(bytes_sent))
+            }
+            Err(e) if e.errno() == EAGAIN && !flags.contains(SendFlags::MSG_DONTWAIT) => {
+                Ok(AsyncSent::Partial(0))
+            }
+            Err(e) => {
+                if e.errno() == EPIPE && !flags.contains(SendFlags::MSG_NOSIGNAL) {
+                    crate::signal::do_tkill(
+                        current!().tid(),
+                        crate::signal::SIGPIPE.as_u8() as i32,
+                    );
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn ocall_sendmsg(
+        &self,
+        raw_iovs: &[libc::iovec],
+        flags: SendFlags,
+        name: Option<&[u8]>,
+        control: Option<&[u8]>,
     ) -> Result<usize> {
         // Prepare the arguments for OCall
         let mut retval: isize = 0;
@@ -64,11 +267,7 @@ impl HostSocket {
         let (msg_name, msg_namelen) = name.as_ptr_and_len();
         let msg_name = msg_name as *const c_void;
         // Iovs
-        let raw_iovs: Vec<libc::iovec> = u_data
-            .iter()
-            .map(|slice| slice.as_ref().as_libc_iovec())
-            .collect();
-        let (msg_iov, msg_iovlen) = raw_iovs.as_slice().as_ptr_and_len();
+        let (msg_iov, msg_iovlen) = raw_iovs.as_ptr_and_len();
         // Control
         let (msg_control, msg_controllen) = control.as_ptr_and_len();
         let msg_control = msg_control as *const c_void;
@@ -101,7 +300,55 @@ impl HostSocket {
     }
 }
 
+enum AsyncSent {
+    // All the data is sent, or as much as possible if nonblocking
+    All(usize),
+    // The rest of the data is left to the OCall
+    Partial(usize),
+}
+
+/// Skip the first `len` bytes of the iovecs.
+fn advance_iovs(raw_iovs: &mut Vec<libc::iovec>, mut len: usize) {
+    let num_done = raw_iovs
+        .iter()
+        .take_while(|iov| {
+            if len >= iov.iov_len {
+                len -= iov.iov_len;
+                true
+            } else {
+                false
+            }
+        })
+        .count();
+    raw_iovs.drain(..num_done);
+    if let Some(iov) = raw_iovs.first_mut() {
+        iov.iov_base = unsafe { (iov.iov_base as *mut u8).add(len) } as *mut c_void;
+        iov.iov_len -= len;
+    }
+}
+
+/// View a slice of C structs as bytes.
+///
+/// Safety: the structs should have no padding or have been zeroed.
+pub(super) unsafe fn as_bytes<T: Copy>(slice: &[T]) -> &[u8] {
+    std::slice::from_raw_parts(slice.as_ptr() as *const u8, mem::size_of_val(slice))
+}
+
+/// View a mutable slice of C structs as bytes.
+///
+/// Safety: any bytes written should make valid structs.
+pub(super) unsafe fn as_bytes_mut<T: Copy>(slice: &mut [T]) -> &mut [u8] {
+    std::slice::from_raw_parts_mut(slice.as_mut_ptr() as *mut u8, mem::size_of_val(slice))
+}
+
 extern "C" {
+    fn occlum_ocall_sendmmsg(
+        ret: *mut c_int,
+        fd: c_int,
+        msgvec: *mut c_void,
+        vlen: c_uint,
+        flags: c_int,
+    ) -> sgx_status_t;
     fn occlum_ocall_sendmsg(
         ret: *mut ssize_t,
         fd: c_int,
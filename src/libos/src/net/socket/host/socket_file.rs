@@ -3,6 +3,7 @@ use std::io::{Read, Seek, SeekFrom, Write};
 
 use atomic::{Atomic, Ordering};
 
+use super::loopback::Loopback;
 use super::*;
 use crate::fs::{
     occlum_ocall_ioctl, AccessMode, AtomicIoEvents, CreationFlags, File, FileRef, HostFd, IoEvents,
@@ -69,14 +70,27 @@ impl File for HostSocket {
             libc::F_SETFL,
             raw_status_flags as c_int
         ));
+        if let Some(stream) = self.loopback_stream() {
+            stream.set_nonblocking(new_status_flags.contains(StatusFlags::O_NONBLOCK));
+        }
         Ok(())
     }
 
     fn poll_new(&self) -> IoEvents {
-        self.host_events.load(Ordering::Acquire)
+        let host_events = self.host_events.load(Ordering::Acquire);
+        match &*self.loopback.lock().unwrap() {
+            Loopback::Disabled => host_events,
+            Loopback::Listening(listener) => host_events | listener.poll(),
+            Loopback::Bound(datagram) => host_events | datagram.poll(),
+            Loopback::Connected(stream) => stream.poll(),
+        }
     }
 
     fn host_fd(&self) -> Option<&HostFd> {
+        // A socket connected in the enclave is polled as a LibOS file
+        if self.loopback_stream().is_some() {
+            return None;
+        }
         Some(&self.host_fd)
     }
 
@@ -86,3 +86,31 @@ impl SliceAsLibcIovec for &mut [u8] {
         libc::iovec { iov_base, iov_len }
     }
 }
+
+/// Copy the bytes to the buffers in order, returning the number of bytes copied.
+pub fn copy_to_bufs(src: &[u8], bufs: &mut [&mut [u8]]) -> usize {
+    let mut copied = 0;
+    for buf in bufs.iter_mut() {
+        let len = buf.len().min(src.len() - copied);
+        buf[..len].copy_from_slice(&src[copied..copied + len]);
+        copied += len;
+        if copied == src.len() {
+            break;
+        }
+    }
+    copied
+}
+
+/// Returns the remaining parts of the buffers after skipping the given number of bytes.
+pub fn skip_bytes<'a>(bufs: &'a mut [&mut [u8]], mut skip: usize) -> Vec<&'a mut [u8]> {
+    let mut remaining = Vec::new();
+    for buf in bufs.iter_mut() {
+        if skip >= buf.len() {
+            skip -= buf.len();
+            continue;
+        }
+        remaining.push(&mut buf[skip..]);
+        skip = 0;
+    }
+    remaining
+}
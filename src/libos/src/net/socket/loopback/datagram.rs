@@ -0,0 +1,196 @@
+use super::*;
+use fs::{IoEvents, IoNotifier};
+use std::mem;
+
+// Same as the default receive buffer size of UDP sockets
+const DEFAULT_BUF_SIZE: usize = 208 * 1024;
+// The memory taken by each queued datagram besides its data, so that the
+// empty datagrams are limited by the receive buffer too
+const DATAGRAM_OVERHEAD: usize = mem::size_of::<SockAddr>();
+// The max payload of UDP over IPv4. Larger datagrams are left to the host.
+pub const MAX_DATAGRAM_LEN: usize = 65507;
+
+lazy_static! {
+    // The LibOS datagram sockets bound to loopback or wildcard addresses
+    static ref RECEIVERS: SgxMutex<HashMap<InetKey, Arc<LoopbackDatagram>>> =
+        SgxMutex::new(HashMap::new());
+}
+
+/// The in-enclave part of a bound host datagram socket, which holds the
+/// datagrams sent from LibOS sockets over loopback addresses.
+pub struct LoopbackDatagram {
+    key: InetKey,
+    addr: SockAddr,
+    // The peer of a connected socket, which only accepts the datagrams from it
+    peer: SgxMutex<Option<SockAddr>>,
+    queue: SgxMutex<DatagramQueue>,
+    // The notifier of the bound socket
+    notifier: Arc<IoNotifier>,
+}
+
+struct DatagramQueue {
+    datagrams: VecDeque<(SockAddr, Vec<u8>)>,
+    // The memory taken by the datagrams
+    size: usize,
+    is_shutdown: bool,
+}
+
+impl LoopbackDatagram {
+    /// Register a receiver for a socket bound to `addr`.
+    ///
+    /// Returns `None` if the address is neither a loopback address nor a
+    /// wildcard address, or if another LibOS socket is already bound to it
+    /// (e.g., with SO_REUSEADDR). Datagrams to such sockets go through the host.
+    pub fn register(addr: &SockAddr, notifier: &Arc<IoNotifier>) -> Option<Arc<Self>> {
+        let key = InetKey::from_addr(addr)?;
+        if !key.is_loopback() && !key.is_unspecified() {
+            return None;
+        }
+
+        let mut receivers = RECEIVERS.lock().unwrap();
+        if receivers.contains_key(&key) {
+            return None;
+        }
+        let datagram = Arc::new(Self {
+            key,
+            addr: *addr,
+            peer: SgxMutex::new(None),
+            queue: SgxMutex::new(DatagramQueue {
+                datagrams: VecDeque::new(),
+                size: 0,
+                is_shutdown: false,
+            }),
+            notifier: notifier.clone(),
+        });
+        receivers.insert(key, datagram.clone());
+        Some(datagram)
+    }
+
+    /// Find the LibOS receiver that a datagram to `dest_addr` should go to.
+    pub fn lookup(dest_addr: &SockAddr) -> Option<Arc<Self>> {
+        let key = InetKey::from_addr(dest_addr)?;
+        if !key.is_loopback() {
+            return None;
+        }
+
+        let receivers = RECEIVERS.lock().unwrap();
+        receivers
+            .get(&key)
+            .or_else(|| receivers.get(&key.to_unspecified()))
+            .cloned()
+    }
+
+    /// The source address of the datagrams sent to `dest_addr`.
+    ///
+    /// A socket bound to a wildcard address sends from the destination
+    /// loopback address, like Linux does.
+    pub fn source_addr(&self, dest_addr: &SockAddr) -> SockAddr {
+        if !self.key.is_unspecified() {
+            return self.addr;
+        }
+        let mut addr = *dest_addr;
+        set_inet_port(&mut addr, self.key.port);
+        addr
+    }
+
+    pub fn peer(&self) -> Option<SockAddr> {
+        *self.peer.lock().unwrap()
+    }
+
+    /// Set or reset the peer when the socket is connected or disconnected.
+    pub fn connect(&self, peer: Option<SockAddr>) {
+        *self.peer.lock().unwrap() = peer;
+    }
+
+    /// Queue a datagram sent from `src_addr`, returning its length.
+    ///
+    /// Same as UDP, the datagram is dropped silently if the receive buffer is
+    /// full or if the socket is connected to another address.
+    pub fn deliver(&self, src_addr: &SockAddr, bufs: &[&[u8]]) -> usize {
+        let len: usize = bufs.iter().map(|buf| buf.len()).sum();
+        if let Some(peer) = self.peer() {
+            if InetKey::from_addr(&peer) != InetKey::from_addr(src_addr) {
+                return len;
+            }
+        }
+
+        {
+            let mut queue = self.queue.lock().unwrap();
+            let size = len + DATAGRAM_OVERHEAD;
+            if queue.is_shutdown || queue.size + size > DEFAULT_BUF_SIZE {
+                return len;
+            }
+            queue.datagrams.push_back((*src_addr, bufs.concat()));
+            queue.size += size;
+        }
+
+        self.notifier.broadcast(&IoEvents::IN);
+        len
+    }
+
+    /// Receive the first datagram without blocking, leaving it in the queue
+    /// if `peek` is true.
+    ///
+    /// Returns the number of bytes copied, the length of the datagram and
+    /// its source address.
+    pub fn try_recv(&self, bufs: &mut [&mut [u8]], peek: bool) -> Option<(usize, usize, SockAddr)> {
+        let (res, remaining) = {
+            let mut queue = self.queue.lock().unwrap();
+            let (src_addr, data) = queue.datagrams.front()?;
+            let res = (copy_to_bufs(data, bufs), data.len(), *src_addr);
+            if !peek {
+                let (_, data) = queue.datagrams.pop_front().unwrap();
+                queue.size -= data.len() + DATAGRAM_OVERHEAD;
+            }
+            (res, queue.datagrams.len())
+        };
+
+        // Host files are not put back to the ready list of epoll after being
+        // polled, so notify again for the datagrams left.
+        if remaining > 0 {
+            self.notifier.broadcast(&IoEvents::IN);
+        }
+        Some(res)
+    }
+
+    /// The length of the first datagram.
+    pub fn bytes_to_read(&self) -> Option<usize> {
+        let queue = self.queue.lock().unwrap();
+        queue.datagrams.front().map(|(_, data)| data.len())
+    }
+
+    pub fn poll(&self) -> IoEvents {
+        if self.queue.lock().unwrap().datagrams.is_empty() {
+            IoEvents::empty()
+        } else {
+            IoEvents::IN
+        }
+    }
+
+    /// Unregister the receiver and drop the datagrams not received yet.
+    pub fn shutdown(&self) {
+        {
+            let mut receivers = RECEIVERS.lock().unwrap();
+            let is_self = receivers
+                .get(&self.key)
+                .map_or(false, |datagram| std::ptr::eq(Arc::as_ptr(datagram), self));
+            if is_self {
+                receivers.remove(&self.key);
+            }
+        }
+
+        let mut queue = self.queue.lock().unwrap();
+        queue.is_shutdown = true;
+        queue.datagrams.clear();
+        queue.size = 0;
+    }
+}
+
+impl Debug for LoopbackDatagram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoopbackDatagram")
+            .field("key", &self.key)
+            .field("peer", &self.peer())
+            .finish()
+    }
+}
@@ -0,0 +1,143 @@
+use super::*;
+use fs::{IoEvents, IoNotifier};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+lazy_static! {
+    // The LibOS stream sockets listening on loopback or wildcard addresses
+    static ref LISTENERS: SgxMutex<HashMap<InetKey, Arc<LoopbackListener>>> =
+        SgxMutex::new(HashMap::new());
+}
+
+/// The in-enclave part of a listening host socket, which holds the incoming
+/// loopback connections that are not accepted yet.
+pub struct LoopbackListener {
+    key: InetKey,
+    incoming: SgxMutex<VecDeque<LoopbackStream>>,
+    backlog: AtomicUsize,
+    is_shutdown: AtomicBool,
+    // The notifier of the listening socket
+    notifier: Arc<IoNotifier>,
+}
+
+impl LoopbackListener {
+    /// Register a listener for a socket listening on `addr`.
+    ///
+    /// Returns `None` if the address is neither a loopback address nor a
+    /// wildcard address, or if another LibOS socket is already listening on it
+    /// (e.g., with SO_REUSEPORT). Connections to such sockets go through the host.
+    pub fn register(
+        addr: &SockAddr,
+        backlog: usize,
+        notifier: &Arc<IoNotifier>,
+    ) -> Option<Arc<Self>> {
+        let key = InetKey::from_addr(addr)?;
+        if !key.is_loopback() && !key.is_unspecified() {
+            return None;
+        }
+
+        let mut listeners = LISTENERS.lock().unwrap();
+        if listeners.contains_key(&key) {
+            return None;
+        }
+        let listener = Arc::new(Self {
+            key,
+            incoming: SgxMutex::new(VecDeque::new()),
+            backlog: AtomicUsize::new(backlog),
+            is_shutdown: AtomicBool::new(false),
+            notifier: notifier.clone(),
+        });
+        listeners.insert(key, listener.clone());
+        Some(listener)
+    }
+
+    /// Find the LibOS listener that a connection to `peer_addr` should go to.
+    pub fn lookup(peer_addr: &SockAddr) -> Option<Arc<Self>> {
+        let key = InetKey::from_addr(peer_addr)?;
+        if !key.is_loopback() {
+            return None;
+        }
+
+        let listeners = LISTENERS.lock().unwrap();
+        listeners
+            .get(&key)
+            .or_else(|| listeners.get(&key.to_unspecified()))
+            .cloned()
+    }
+
+    /// Connect the socket bound to `addr` to this listener.
+    ///
+    /// The connection is established immediately. If the backlog is full,
+    /// `EAGAIN` is returned and the caller may try the host path.
+    pub fn connect(&self, addr: &SockAddr, peer_addr: &SockAddr) -> Result<LoopbackStream> {
+        let (end_self, end_incoming) = LoopbackStream::pair(addr, peer_addr)?;
+        {
+            let mut incoming = self.incoming.lock().unwrap();
+            if self.is_shutdown.load(Ordering::Acquire) {
+                return_errno!(ECONNREFUSED, "the listener has been shutdown");
+            }
+            if incoming.len() >= self.backlog.load(Ordering::Relaxed) {
+                return_errno!(EAGAIN, "the backlog is full");
+            }
+            incoming.push_back(end_incoming);
+        }
+
+        self.notifier.broadcast(&IoEvents::IN);
+        Ok(end_self)
+    }
+
+    pub fn pop_incoming(&self) -> Option<LoopbackStream> {
+        let (stream, remaining) = {
+            let mut incoming = self.incoming.lock().unwrap();
+            let stream = incoming.pop_front()?;
+            (stream, incoming.len())
+        };
+
+        // Host files are not put back to the ready list of epoll after being
+        // polled, so notify again for the connections left.
+        if remaining > 0 {
+            self.notifier.broadcast(&IoEvents::IN);
+        }
+        Some(stream)
+    }
+
+    pub fn set_backlog(&self, backlog: usize) {
+        self.backlog.store(backlog, Ordering::Relaxed);
+    }
+
+    pub fn poll(&self) -> IoEvents {
+        if self.incoming.lock().unwrap().is_empty() {
+            IoEvents::empty()
+        } else {
+            IoEvents::IN
+        }
+    }
+
+    /// Unregister the listener and reset the connections not accepted yet,
+    /// whose peers get ECONNRESET like Linux.
+    pub fn shutdown(&self) {
+        {
+            let mut listeners = LISTENERS.lock().unwrap();
+            let is_self = listeners
+                .get(&self.key)
+                .map_or(false, |listener| std::ptr::eq(Arc::as_ptr(listener), self));
+            if is_self {
+                listeners.remove(&self.key);
+            }
+        }
+
+        let mut incoming = self.incoming.lock().unwrap();
+        self.is_shutdown.store(true, Ordering::Release);
+        for stream in incoming.drain(..) {
+            stream.reset();
+        }
+    }
+}
+
+impl Debug for LoopbackListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoopbackListener")
+            .field("key", &self.key)
+            .field("backlog", &self.backlog.load(Ordering::Relaxed))
+            .finish()
+    }
+}
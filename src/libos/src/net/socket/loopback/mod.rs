@@ -0,0 +1,126 @@
+//! In-enclave transport for TCP and UDP over loopback addresses.
+//!
+//! Every AF_INET/AF_INET6 socket is backed by a host socket. But when two LibOS
+//! processes talk to each other over a loopback address, there is no reason to
+//! send the traffic through the untrusted host kernel and to pay an enclave exit
+//! for each send and receive. Thus, a stream socket that listens on a loopback
+//! (or wildcard) address registers itself here, and a `connect` to a loopback
+//! address with a registered listener gets a pair of in-enclave channels instead,
+//! the same machinery used by unix stream sockets. Likewise, a datagram socket
+//! bound to a loopback (or wildcard) address registers itself here, and the
+//! datagrams sent to it from LibOS sockets are queued in the enclave.
+//!
+//! The host sockets are kept alive all the time so that port reservation, socket
+//! options and connections from outside the enclave keep working as usual. Any
+//! case that cannot be served in the enclave simply falls back to the host path.
+
+use super::*;
+
+mod datagram;
+mod listener;
+mod stream;
+
+pub use self::datagram::{LoopbackDatagram, MAX_DATAGRAM_LEN};
+pub use self::listener::LoopbackListener;
+pub use self::stream::LoopbackStream;
+
+/// The IP address and port of an AF_INET or AF_INET6 socket address.
+///
+/// IPv4 addresses are stored in the first four bytes of `ip`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct InetKey {
+    family: u16,
+    ip: [u8; 16],
+    port: u16,
+}
+
+impl InetKey {
+    fn from_addr(addr: &SockAddr) -> Option<Self> {
+        let family = unsafe { (*addr.as_ptr()).sa_family };
+        match AddressFamily::try_from(family).ok()? {
+            AddressFamily::INET if addr.len() >= std::mem::size_of::<libc::sockaddr_in>() => {
+                let sin = unsafe { &*(addr.as_ptr() as *const libc::sockaddr_in) };
+                let mut ip = [0; 16];
+                ip[..4].copy_from_slice(&sin.sin_addr.s_addr.to_ne_bytes());
+                Some(Self {
+                    family,
+                    ip,
+                    port: u16::from_be(sin.sin_port),
+                })
+            }
+            // sin6_scope_id is optional, see SockAddr::try_from_raw
+            AddressFamily::INET6 if addr.len() >= std::mem::size_of::<libc::sockaddr_in6>() - 4 => {
+                let sin6 = unsafe { &*(addr.as_ptr() as *const libc::sockaddr_in6) };
+                Some(Self {
+                    family,
+                    ip: sin6.sin6_addr.s6_addr,
+                    port: u16::from_be(sin6.sin6_port),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn is_loopback(&self) -> bool {
+        if self.family == AddressFamily::INET as u16 {
+            // 127.0.0.0/8
+            self.ip[0] == 127
+        } else {
+            // ::1
+            self.ip[..15].iter().all(|byte| *byte == 0) && self.ip[15] == 1
+        }
+    }
+
+    fn is_unspecified(&self) -> bool {
+        self.ip.iter().all(|byte| *byte == 0)
+    }
+
+    /// The same port on the wildcard address of the same family.
+    fn to_unspecified(&self) -> Self {
+        Self {
+            family: self.family,
+            ip: [0; 16],
+            port: self.port,
+        }
+    }
+}
+
+/// Whether the address is an IPv4 or IPv6 loopback address.
+pub fn is_loopback_addr(addr: &SockAddr) -> bool {
+    InetKey::from_addr(addr).map_or(false, |key| key.is_loopback())
+}
+
+/// Get the port of an AF_INET or AF_INET6 address.
+pub fn inet_port(addr: &SockAddr) -> Option<u16> {
+    InetKey::from_addr(addr).map(|key| key.port)
+}
+
+/// Set the port of an AF_INET or AF_INET6 address.
+pub fn set_inet_port(addr: &mut SockAddr, port: u16) {
+    match InetKey::from_addr(addr).map(|key| key.family) {
+        Some(family) if family == AddressFamily::INET as u16 => {
+            let sin = unsafe { &mut *(addr.as_mut_ptr() as *mut libc::sockaddr_in) };
+            sin.sin_port = port.to_be();
+        }
+        Some(_) => {
+            let sin6 = unsafe { &mut *(addr.as_mut_ptr() as *mut libc::sockaddr_in6) };
+            sin6.sin6_port = port.to_be();
+        }
+        None => {}
+    }
+}
+
+/// Set the IP of an AF_INET or AF_INET6 address to the wildcard address.
+pub fn set_inet_ip_unspecified(addr: &mut SockAddr) {
+    match InetKey::from_addr(addr).map(|key| key.family) {
+        Some(family) if family == AddressFamily::INET as u16 => {
+            let sin = unsafe { &mut *(addr.as_mut_ptr() as *mut libc::sockaddr_in) };
+            sin.sin_addr.s_addr = 0;
+        }
+        Some(_) => {
+            let sin6 = unsafe { &mut *(addr.as_mut_ptr() as *mut libc::sockaddr_in6) };
+            sin6.sin6_addr.s6_addr = [0; 16];
+        }
+        None => {}
+    }
+}
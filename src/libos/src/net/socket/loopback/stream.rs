@@ -0,0 +1,324 @@
+use super::*;
+use events::Observer;
+use fs::channel::{Channel, Consumer, Producer};
+use fs::{IoEvents, IoNotifier};
+use std::any::Any;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Weak;
+use std::time::Duration;
+
+// Same as the buffer size of unix stream sockets
+const DEFAULT_BUF_SIZE: usize = 208 * 1024;
+
+/// One end of an in-enclave loopback TCP connection.
+pub struct LoopbackStream {
+    reader: Consumer<u8>,
+    writer: Producer<u8>,
+    addr: SockAddr,
+    peer_addr: SockAddr,
+    // Whether the connection is reset and the error is not reported yet,
+    // shared by the two ends
+    is_reset: Arc<AtomicBool>,
+    // Relay the events of the channels to the notifier of the socket
+    relay: SgxMutex<Option<Arc<Relay>>>,
+}
+
+impl LoopbackStream {
+    /// Create a connected pair. The first one is bound to `addr` and the
+    /// second one is bound to `peer_addr`.
+    pub(super) fn pair(addr: &SockAddr, peer_addr: &SockAddr) -> Result<(Self, Self)> {
+        let (pro_a, con_a) = Channel::new(DEFAULT_BUF_SIZE)?.split();
+        let (pro_b, con_b) = Channel::new(DEFAULT_BUF_SIZE)?.split();
+
+        let is_reset = Arc::new(AtomicBool::new(false));
+        let end_a = Self::new(con_a, pro_b, *addr, *peer_addr, is_reset.clone());
+        let end_b = Self::new(con_b, pro_a, *peer_addr, *addr, is_reset);
+        Ok((end_a, end_b))
+    }
+
+    fn new(
+        reader: Consumer<u8>,
+        writer: Producer<u8>,
+        addr: SockAddr,
+        peer_addr: SockAddr,
+        is_reset: Arc<AtomicBool>,
+    ) -> Self {
+        Self {
+            reader,
+            writer,
+            addr,
+            peer_addr,
+            is_reset,
+            relay: SgxMutex::new(None),
+        }
+    }
+
+    /// Broadcast the events of this stream through the notifier of a socket.
+    pub fn attach(self: &Arc<Self>, notifier: &Arc<IoNotifier>) {
+        let relay = Arc::new(Relay {
+            notifier: notifier.clone(),
+            stream: Arc::downgrade(self),
+        });
+        for channel_notifier in &[self.reader.notifier(), self.writer.notifier()] {
+            channel_notifier.register(Arc::downgrade(&relay) as Weak<dyn Observer<_>>, None, None);
+        }
+        *self.relay.lock().unwrap() = Some(relay);
+    }
+
+    pub fn addr(&self) -> &SockAddr {
+        &self.addr
+    }
+
+    pub fn peer_addr(&self) -> &SockAddr {
+        &self.peer_addr
+    }
+
+    pub fn set_nonblocking(&self, nonblocking: bool) {
+        self.reader.set_nonblocking(nonblocking);
+        self.writer.set_nonblocking(nonblocking);
+    }
+
+    /// Send the bytes, waiting no longer than the SO_SNDTIMEO given by
+    /// `send_timeout`.
+    pub fn sendmsg(
+        &self,
+        bufs: &[&[u8]],
+        flags: SendFlags,
+        send_timeout: &dyn Fn() -> Result<Option<Duration>>,
+    ) -> Result<usize> {
+        self.take_reset()?;
+        let res = if flags.contains(SendFlags::MSG_DONTWAIT) {
+            self.writer.try_push_slices(bufs)
+        } else {
+            let mut timeout = Timeout::new(send_timeout);
+            match self.writer.try_push_slices(bufs) {
+                Err(e) if e.errno() == EAGAIN && !self.writer.is_nonblocking() => {
+                    let timeout = timeout.get()?;
+                    self.writer.push_slices_timeout(bufs, timeout)
+                }
+                res => res,
+            }
+        };
+
+        if let Err(e) = &res {
+            if e.errno() == EPIPE {
+                self.take_reset()?;
+                if !flags.contains(SendFlags::MSG_NOSIGNAL) {
+                    crate::signal::do_tkill(
+                        current!().tid(),
+                        crate::signal::SIGPIPE.as_u8() as i32,
+                    );
+                }
+            }
+        }
+        res.map_err(timeout_to_eagain)
+    }
+
+    /// Receive the bytes, waiting no longer than the SO_RCVTIMEO given by
+    /// `recv_timeout`.
+    pub fn recvmsg(
+        &self,
+        bufs: &mut [&mut [u8]],
+        flags: RecvFlags,
+        recv_timeout: &dyn Fn() -> Result<Option<Duration>>,
+    ) -> Result<usize> {
+        let nonblocking = flags.contains(RecvFlags::MSG_DONTWAIT);
+        let mut timeout = Timeout::new(recv_timeout);
+        if flags.contains(RecvFlags::MSG_PEEK) {
+            return self
+                .peek(bufs, nonblocking, &mut timeout)
+                .map_err(timeout_to_eagain);
+        }
+        if !flags.contains(RecvFlags::MSG_WAITALL) || nonblocking {
+            return self
+                .recv_once(bufs, nonblocking, &mut timeout)
+                .map_err(timeout_to_eagain);
+        }
+
+        let total_len: usize = bufs.iter().map(|buf| buf.len()).sum();
+        let mut recv_len = 0;
+        while recv_len < total_len {
+            let mut remaining = skip_bytes(bufs, recv_len);
+            let len = match self.recv_once(&mut remaining, false, &mut timeout) {
+                Ok(len) => len,
+                Err(_) if recv_len > 0 => break,
+                Err(e) => return Err(timeout_to_eagain(e)),
+            };
+            if len == 0 {
+                break;
+            }
+            recv_len += len;
+        }
+        Ok(recv_len)
+    }
+
+    fn recv_once(
+        &self,
+        bufs: &mut [&mut [u8]],
+        nonblocking: bool,
+        timeout: &mut Timeout,
+    ) -> Result<usize> {
+        self.take_reset()?;
+        // Reading from a socket that has been shutdown for reading gets EOF
+        if self.reader.is_self_shutdown() {
+            return Ok(0);
+        }
+
+        let res = match self.reader.try_pop_slices(bufs) {
+            Err(e) if e.errno() == EAGAIN && !nonblocking && !self.reader.is_nonblocking() => {
+                let timeout = timeout.get()?;
+                self.reader.pop_slices_timeout(bufs, timeout)
+            }
+            res => res,
+        };
+        // The EOF may come from a reset
+        if let Ok(0) = res {
+            self.take_reset()?;
+        }
+        res
+    }
+
+    /// Copy the bytes in the reader without consuming them, so they still
+    /// take up the receive buffer.
+    fn peek(
+        &self,
+        bufs: &mut [&mut [u8]],
+        nonblocking: bool,
+        timeout: &mut Timeout,
+    ) -> Result<usize> {
+        self.take_reset()?;
+        if self.reader.is_self_shutdown() {
+            return Ok(0);
+        }
+
+        let res = match self.reader.try_peek_slices(bufs) {
+            Err(e) if e.errno() == EAGAIN && !nonblocking && !self.reader.is_nonblocking() => {
+                let timeout = timeout.get()?;
+                self.reader.peek_slices_timeout(bufs, timeout)
+            }
+            res => res,
+        };
+        if let Ok(0) = res {
+            self.take_reset()?;
+        }
+        res
+    }
+
+    /// Reset the connection, e.g., when it is dropped by the listener before
+    /// being accepted. The peer gets ECONNRESET once, and then EOF or EPIPE.
+    pub(super) fn reset(&self) {
+        self.is_reset.store(true, Ordering::Release);
+        self.reader.shutdown();
+        self.writer.shutdown();
+    }
+
+    /// Report the reset of the connection, if any, only once like Linux does.
+    fn take_reset(&self) -> Result<()> {
+        if self.is_reset.load(Ordering::Acquire) && self.is_reset.swap(false, Ordering::AcqRel) {
+            return_errno!(ECONNRESET, "the connection is reset");
+        }
+        Ok(())
+    }
+
+    pub fn bytes_to_read(&self) -> usize {
+        self.reader.items_to_consume()
+    }
+
+    pub fn shutdown(&self, how: HowToShut) -> Result<()> {
+        if how.to_shut_read() {
+            self.reader.shutdown();
+        }
+        if how.to_shut_write() {
+            self.writer.shutdown();
+        }
+        Ok(())
+    }
+
+    pub fn poll(&self) -> IoEvents {
+        let mut events = IoEvents::empty();
+        let reader_events = self.reader.poll();
+        let writer_events = self.writer.poll();
+
+        if reader_events.contains(IoEvents::HUP) || self.reader.is_self_shutdown() {
+            events |= IoEvents::RDHUP | IoEvents::IN;
+            if writer_events.contains(IoEvents::ERR) || self.writer.is_self_shutdown() {
+                events |= IoEvents::HUP | IoEvents::OUT;
+            }
+        }
+        if self.is_reset.load(Ordering::Acquire) {
+            events |= IoEvents::ERR;
+        }
+        events |= (reader_events & IoEvents::IN) | (writer_events & IoEvents::OUT);
+        events
+    }
+}
+
+impl Debug for LoopbackStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoopbackStream")
+            .field("addr", &self.addr)
+            .field("peer_addr", &self.peer_addr)
+            .finish()
+    }
+}
+
+// A socket times out with EAGAIN, same with Linux
+fn timeout_to_eagain(e: Error) -> Error {
+    if e.errno() == ETIMEDOUT {
+        errno!(EAGAIN, "timed out")
+    } else {
+        e
+    }
+}
+
+/// The SO_RCVTIMEO or SO_SNDTIMEO of the socket. It is kept by the host
+/// socket, so it is fetched only when the operation is about to block.
+struct Timeout<'a> {
+    fetch: &'a dyn Fn() -> Result<Option<Duration>>,
+    // The remaining time, if fetched
+    remaining: Option<Option<Duration>>,
+}
+
+impl<'a> Timeout<'a> {
+    fn new(fetch: &'a dyn Fn() -> Result<Option<Duration>>) -> Self {
+        Self {
+            fetch,
+            remaining: None,
+        }
+    }
+
+    fn get(&mut self) -> Result<Option<&mut Duration>> {
+        if self.remaining.is_none() {
+            self.remaining = Some((self.fetch)()?);
+        }
+        Ok(self.remaining.as_mut().unwrap().as_mut())
+    }
+}
+
+/// An observer of the channels of a stream, which translates the events of
+/// the channels into the events of the stream, like the unix stream sockets do.
+struct Relay {
+    notifier: Arc<IoNotifier>,
+    stream: Weak<LoopbackStream>,
+}
+
+impl Observer<IoEvents> for Relay {
+    fn on_event(&self, event: &IoEvents, _metadata: &Option<Weak<dyn Any + Send + Sync>>) {
+        let stream = match self.stream.upgrade() {
+            Some(stream) => stream,
+            None => return,
+        };
+
+        let mut event = event.clone();
+        if event.contains(IoEvents::HUP) {
+            event -= IoEvents::HUP;
+            event |= IoEvents::RDHUP;
+        }
+        if event.contains(IoEvents::ERR) {
+            event -= IoEvents::ERR;
+            event |= IoEvents::HUP;
+        }
+
+        self.notifier.broadcast(&(stream.poll() & event));
+    }
+}
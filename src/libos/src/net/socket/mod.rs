@@ -1,21 +1,35 @@
 use super::*;
 
 mod address_family;
+mod backlog;
+mod buf_size;
 mod flags;
 mod host;
 mod iovs;
+mod loopback;
 mod msg;
+mod netlink;
 mod shutdown;
 mod socket_address;
 mod socket_type;
 mod unix;
 
 pub use self::address_family::AddressFamily;
+pub use self::backlog::{restrict_backlog, set_somaxconn, somaxconn};
+pub use self::buf_size::{rmem_max, set_rmem_max, set_wmem_max, wmem_max, MEM_MAX_LIMIT};
 pub use self::flags::{FileFlags, MsgHdrFlags, RecvFlags, SendFlags};
 pub use self::host::{HostSocket, HostSocketType};
-pub use self::iovs::{Iovs, IovsMut, SliceAsLibcIovec};
-pub use self::msg::{mmsghdr, msghdr, msghdr_mut, CMessages, CmsgData, MsgHdr, MsgHdrMut};
+pub use self::iovs::{copy_to_bufs, skip_bytes, Iovs, IovsMut, SliceAsLibcIovec};
+pub use self::msg::{
+    mmsghdr, mmsghdr_mut, msghdr, msghdr_mut, recvmmsg_one_by_one, sendmmsg_one_by_one, CMessages,
+    CmsgData, MsgHdr, MsgHdrMut,
+};
+pub use self::netlink::{
+    init_host_interfaces, netlink_socket, AsNetlinkSocket, NetlinkAddr, NetlinkSocket,
+};
 pub use self::shutdown::HowToShut;
 pub use self::socket_address::SockAddr;
 pub use self::socket_type::SocketType;
-pub use self::unix::{socketpair, unix_socket, AsUnixSocket, UnixAddr};
+pub use self::unix::{
+    bridge_to_host, host_addr_of, socketpair, unix_socket, AsUnixSocket, UnixAddr,
+};
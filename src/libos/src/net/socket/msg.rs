@@ -1,5 +1,6 @@
 /// Socket message and its flags.
 use super::*;
+use std::time::Duration;
 
 /// C struct for a socket message with const pointers
 #[repr(C)]
@@ -34,6 +35,13 @@ pub struct msghdr_mut {
     pub msg_flags: c_int,
 }
 
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct mmsghdr_mut {
+    pub msg_hdr: msghdr_mut,
+    pub msg_len: c_uint,
+}
+
 /// MsgHdr is a memory-safe, immutable wrapper of msghdr
 pub struct MsgHdr<'a> {
     name: Option<&'a [u8]>,
@@ -219,6 +227,72 @@ impl<'a> MsgHdrMut<'a> {
     }
 }
 
+/// Send the messages one by one for the sockets without batched sending.
+///
+/// Returns the number of bytes sent for each of the messages sent. Same as
+/// Linux, an error is returned only if no message is sent.
+pub fn sendmmsg_one_by_one<F>(
+    msgs: &[MsgHdr],
+    flags: SendFlags,
+    mut sendmsg: F,
+) -> Result<Vec<usize>>
+where
+    F: FnMut(&MsgHdr, SendFlags) -> Result<usize>,
+{
+    let mut bytes_sent = Vec::with_capacity(msgs.len());
+    for msg in msgs {
+        match sendmsg(msg, flags) {
+            Ok(len) => bytes_sent.push(len),
+            Err(e) if bytes_sent.is_empty() => return Err(e),
+            Err(_) => break,
+        }
+    }
+    Ok(bytes_sent)
+}
+
+/// Receive the messages one by one for the sockets without batched receiving.
+///
+/// Returns the number of bytes received for each of the messages received.
+/// Same as Linux, the timeout is only checked after a message is received,
+/// MSG_WAITFORONE turns on MSG_DONTWAIT after the first message, and an error
+/// is returned only if no message is received. The remaining time is written
+/// back to the timeout.
+pub fn recvmmsg_one_by_one<F>(
+    msgs: &mut [MsgHdrMut],
+    mut flags: RecvFlags,
+    timeout: Option<&mut Duration>,
+    mut recvmsg: F,
+) -> Result<Vec<usize>>
+where
+    F: FnMut(&mut MsgHdrMut, RecvFlags) -> Result<usize>,
+{
+    let deadline = timeout
+        .as_ref()
+        .map(|timeout| crate::time::up_time::get().unwrap() + **timeout);
+    let mut bytes_recvd = Vec::with_capacity(msgs.len());
+    for msg in msgs.iter_mut() {
+        match recvmsg(msg, flags - RecvFlags::MSG_WAITFORONE) {
+            Ok(len) => bytes_recvd.push(len),
+            Err(e) if bytes_recvd.is_empty() => return Err(e),
+            Err(_) => break,
+        }
+
+        if flags.contains(RecvFlags::MSG_WAITFORONE) {
+            flags |= RecvFlags::MSG_DONTWAIT;
+        }
+        if let Some(deadline) = deadline {
+            if crate::time::up_time::get().unwrap() >= deadline {
+                break;
+            }
+        }
+    }
+
+    if let (Some(timeout), Some(deadline)) = (timeout, deadline) {
+        *timeout = deadline.saturating_sub(crate::time::up_time::get().unwrap());
+    }
+    Ok(bytes_recvd)
+}
+
 /// This struct is used to iterate through the control messages.
 ///
 /// `cmsghdr` is a C struct for ancillary data object information of a unix socket.
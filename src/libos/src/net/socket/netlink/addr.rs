@@ -0,0 +1,68 @@
+use super::*;
+use std::mem;
+
+/// The address of a netlink socket, i.e., struct sockaddr_nl.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Addr {
+    // The port ID, which is zero for the kernel
+    pub port_id: u32,
+    // The bit mask of the multicast groups
+    pub groups: u32,
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct sockaddr_nl {
+    nl_family: libc::sa_family_t,
+    nl_pad: u16,
+    nl_pid: u32,
+    nl_groups: u32,
+}
+
+impl Addr {
+    /// Caller should guarantee the sockaddr and addr_len are valid.
+    pub unsafe fn try_from_raw(
+        sockaddr: *const libc::sockaddr,
+        addr_len: libc::socklen_t,
+    ) -> Result<Self> {
+        if (addr_len as usize) < mem::size_of::<sockaddr_nl>() {
+            return_errno!(EINVAL, "the address is too short");
+        }
+        if AddressFamily::try_from((*sockaddr).sa_family)? != AddressFamily::NETLINK {
+            return_errno!(EINVAL, "not a valid address for netlink socket");
+        }
+
+        let sockaddr = &*(sockaddr as *const sockaddr_nl);
+        Ok(Self {
+            port_id: sockaddr.nl_pid,
+            groups: sockaddr.nl_groups,
+        })
+    }
+
+    /// The address of the kernel.
+    pub fn kernel() -> Self {
+        Self::default()
+    }
+
+    pub fn copy_to_slice(&self, dst: &mut [u8]) -> usize {
+        let sockaddr = sockaddr_nl {
+            nl_family: AddressFamily::NETLINK as libc::sa_family_t,
+            nl_pad: 0,
+            nl_pid: self.port_id,
+            nl_groups: self.groups,
+        };
+        let src = unsafe {
+            std::slice::from_raw_parts(
+                &sockaddr as *const _ as *const u8,
+                mem::size_of::<sockaddr_nl>(),
+            )
+        };
+        let len = dst.len().min(src.len());
+        dst[..len].copy_from_slice(&src[..len]);
+        len
+    }
+
+    pub fn raw_len(&self) -> usize {
+        mem::size_of::<sockaddr_nl>()
+    }
+}
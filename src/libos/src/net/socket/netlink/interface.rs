@@ -0,0 +1,148 @@
+use super::*;
+use crate::config::{parse_hwaddr, parse_ip_prefix, ConfigInterface};
+use std::net::IpAddr;
+
+pub const IFF_UP: u32 = 0x1;
+pub const IFF_BROADCAST: u32 = 0x2;
+pub const IFF_LOOPBACK: u32 = 0x8;
+pub const IFF_RUNNING: u32 = 0x40;
+pub const IFF_MULTICAST: u32 = 0x1000;
+
+// Same as IFNAMSIZ - 1
+const MAX_NAME_LEN: usize = 15;
+
+lazy_static! {
+    // The snapshot of the host interfaces taken at the initialization
+    static ref HOST_INTERFACES: RwLock<Option<Vec<Interface>>> = RwLock::new(None);
+    // The interfaces seen by the LibOS
+    static ref INTERFACES: Vec<Interface> = {
+        let interfaces = if let Some(config_interfaces) = &config::LIBOS_CONFIG.network.interfaces {
+            config_interfaces
+                .iter()
+                .enumerate()
+                .map(|(idx, config)| Interface::from_config(idx as u32 + 1, config))
+                .collect()
+        } else if let Some(host_interfaces) = &*HOST_INTERFACES.read().unwrap() {
+            host_interfaces.clone()
+        } else {
+            vec![Interface::default_loopback()]
+        };
+        debug!("network interfaces: {:?}", interfaces);
+        interfaces
+    };
+}
+
+/// A network interface and its addresses.
+#[derive(Clone, Debug)]
+pub struct Interface {
+    pub index: u32,
+    pub name: String,
+    pub flags: u32,
+    pub mtu: u32,
+    pub hwaddr: Option<[u8; 6]>,
+    pub addrs: Vec<(IpAddr, u8)>,
+}
+
+impl Interface {
+    fn from_config(index: u32, config: &ConfigInterface) -> Self {
+        let is_loopback =
+            !config.addresses.is_empty() && config.addresses.iter().all(|(ip, _)| ip.is_loopback());
+        let flags = if is_loopback {
+            IFF_UP | IFF_RUNNING | IFF_LOOPBACK
+        } else {
+            IFF_UP | IFF_RUNNING | IFF_BROADCAST | IFF_MULTICAST
+        };
+        let mtu = config.mtu.unwrap_or(if is_loopback { 65536 } else { 1500 });
+        Self {
+            index,
+            name: config.name.clone(),
+            flags,
+            mtu,
+            hwaddr: config.mac,
+            addrs: config.addresses.clone(),
+        }
+    }
+
+    fn default_loopback() -> Self {
+        Self {
+            index: 1,
+            name: "lo".to_string(),
+            flags: IFF_UP | IFF_RUNNING | IFF_LOOPBACK,
+            mtu: 65536,
+            hwaddr: None,
+            addrs: vec![
+                ("127.0.0.1".parse().unwrap(), 8),
+                ("::1".parse().unwrap(), 128),
+            ],
+        }
+    }
+
+    /// Parse a line of the host snapshot, which is in the format of
+    /// `<index> <name> <flags> <mtu> <hwaddr or -> [<ip>/<prefix_len> ...]`.
+    fn from_snapshot_line(line: &str) -> Result<Self> {
+        let mut fields = line.split_whitespace();
+        let mut next_field = || {
+            fields
+                .next()
+                .ok_or_else(|| errno!(EINVAL, "too few fields"))
+        };
+
+        let index: u32 = next_field()?
+            .parse()
+            .map_err(|_| errno!(EINVAL, "invalid index"))?;
+        let name = next_field()?.to_string();
+        if index == 0 || name.len() > MAX_NAME_LEN {
+            return_errno!(EINVAL, "invalid index or name");
+        }
+        let flags = u32::from_str_radix(next_field()?.trim_start_matches("0x"), 16)
+            .map_err(|_| errno!(EINVAL, "invalid flags"))?;
+        let mtu: u32 = next_field()?
+            .parse()
+            .map_err(|_| errno!(EINVAL, "invalid mtu"))?;
+        let hwaddr = match next_field()? {
+            "-" => None,
+            hwaddr_str => Some(parse_hwaddr(hwaddr_str)?),
+        };
+        let addrs = fields
+            .map(|prefix_str| parse_ip_prefix(prefix_str))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            index,
+            name,
+            flags,
+            mtu,
+            hwaddr,
+            addrs,
+        })
+    }
+
+    pub fn is_loopback(&self) -> bool {
+        self.flags & IFF_LOOPBACK != 0
+    }
+}
+
+/// Record the snapshot of the host interfaces.
+///
+/// The snapshot comes from the untrusted host, so it is validated here and
+/// ignored entirely if malformed.
+pub fn init_host_interfaces(snapshot: &str) -> Result<()> {
+    let mut interfaces: Vec<Interface> = Vec::new();
+    for line in snapshot.lines().filter(|line| !line.trim().is_empty()) {
+        let interface = Interface::from_snapshot_line(line)?;
+        if interfaces
+            .iter()
+            .any(|i| i.index == interface.index || i.name == interface.name)
+        {
+            return_errno!(EINVAL, "duplicated interface");
+        }
+        interfaces.push(interface);
+    }
+    *HOST_INTERFACES.write().unwrap() = Some(interfaces);
+    Ok(())
+}
+
+/// Get the network interfaces seen by the LibOS.
+pub fn interfaces() -> &'static [Interface] {
+    &INTERFACES
+}
@@ -0,0 +1,333 @@
+//! The messages of NETLINK_ROUTE, see netlink(7) and rtnetlink(7).
+
+use super::interface::{interfaces, Interface, IFF_BROADCAST, IFF_RUNNING, IFF_UP};
+use super::*;
+use std::net::IpAddr;
+
+const NLMSG_HDR_LEN: usize = 16;
+const NLMSG_ALIGNTO: usize = 4;
+
+const NLMSG_NOOP: u16 = 1;
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+
+const RTM_NEWLINK: u16 = 16;
+const RTM_GETLINK: u16 = 18;
+const RTM_NEWADDR: u16 = 20;
+const RTM_GETADDR: u16 = 22;
+
+const NLM_F_REQUEST: u16 = 0x1;
+const NLM_F_MULTI: u16 = 0x2;
+const NLM_F_ACK: u16 = 0x4;
+const NLM_F_DUMP: u16 = 0x300;
+const NLM_F_CAPPED: u16 = 0x100;
+
+const IFLA_ADDRESS: u16 = 1;
+const IFLA_BROADCAST: u16 = 2;
+const IFLA_IFNAME: u16 = 3;
+const IFLA_MTU: u16 = 4;
+const IFLA_OPERSTATE: u16 = 16;
+
+const IFA_ADDRESS: u16 = 1;
+const IFA_LOCAL: u16 = 2;
+const IFA_LABEL: u16 = 3;
+const IFA_BROADCAST: u16 = 4;
+
+const IF_OPER_UNKNOWN: u8 = 0;
+const IF_OPER_DOWN: u8 = 2;
+const IF_OPER_UP: u8 = 6;
+
+const ARPHRD_ETHER: u16 = 1;
+const ARPHRD_LOOPBACK: u16 = 772;
+const ARPHRD_NONE: u16 = 0xfffe;
+
+const IFA_F_PERMANENT: u8 = 0x80;
+const RT_SCOPE_UNIVERSE: u8 = 0;
+const RT_SCOPE_LINK: u8 = 253;
+const RT_SCOPE_HOST: u8 = 254;
+
+// The max length of a datagram holding the replies of a dump
+const MAX_DATAGRAM_LEN: usize = 4096;
+
+/// Handle the requests in a datagram sent to the kernel.
+///
+/// Returns the datagrams of the replies, which are addressed to `port_id`.
+/// The datagrams of a dump are put into `dump` instead, which are received
+/// one by one as the receive buffer allows. Same as Linux, a dump request is
+/// refused with EBUSY if the previous dump is not finished yet.
+pub fn handle_requests(mut buf: &[u8], port_id: u32, dump: &mut VecDeque<Vec<u8>>) -> Vec<Vec<u8>> {
+    let mut datagrams = Vec::new();
+    // Same as Linux, the truncated messages are ignored
+    while buf.len() >= NLMSG_HDR_LEN {
+        let header = NlMsgHdr::from_bytes(buf);
+        let msg_len = header.len as usize;
+        if msg_len < NLMSG_HDR_LEN || msg_len > buf.len() {
+            break;
+        }
+        let payload = &buf[NLMSG_HDR_LEN..msg_len];
+        if header.flags & NLM_F_REQUEST != 0 {
+            if header.flags & NLM_F_DUMP != NLM_F_DUMP {
+                datagrams.extend(handle_request(&header, payload, port_id));
+            } else if dump.is_empty() {
+                dump.extend(handle_request(&header, payload, port_id));
+            } else {
+                datagrams.push(error_reply(&header, Errno::EBUSY as i32, port_id));
+            }
+        }
+        buf = &buf[align_up(msg_len, NLMSG_ALIGNTO).min(buf.len())..];
+    }
+    datagrams
+}
+
+fn handle_request(header: &NlMsgHdr, payload: &[u8], port_id: u32) -> Vec<Vec<u8>> {
+    let is_dump = header.flags & NLM_F_DUMP == NLM_F_DUMP;
+    let res = match header.type_ {
+        NLMSG_NOOP | NLMSG_DONE => Ok(Vec::new()),
+        RTM_GETLINK if is_dump => Ok(interfaces()
+            .iter()
+            .map(|interface| new_link(interface))
+            .collect()),
+        RTM_GETLINK => get_link(payload).map(|reply| vec![reply]),
+        RTM_GETADDR if is_dump => Ok(dump_addrs(payload)),
+        // Linux only supports dumping the addresses
+        RTM_GETADDR => Err(errno!(EOPNOTSUPP, "only dump is supported")),
+        _ => Err(errno!(EOPNOTSUPP, "unsupported request")),
+    };
+
+    let mut replies = match res {
+        Ok(replies) => replies,
+        Err(e) => {
+            debug!("netlink request {:?} failed: {:?}", header, e);
+            return vec![error_reply(header, e.errno() as i32, port_id)];
+        }
+    };
+    let reply_flags = if is_dump { NLM_F_MULTI } else { 0 };
+    for reply in replies.iter_mut() {
+        finish_msg(reply, reply_flags, header.seq, port_id);
+    }
+
+    if is_dump {
+        // The end of a dump carries an int of the error code
+        let mut done = new_msg(NLMSG_DONE);
+        done.extend_from_slice(&0_i32.to_ne_bytes());
+        finish_msg(&mut done, NLM_F_MULTI, header.seq, port_id);
+        replies.push(done);
+        pack_datagrams(replies)
+    } else {
+        if header.flags & NLM_F_ACK != 0 {
+            replies.push(error_reply(header, 0, port_id));
+        }
+        replies
+    }
+}
+
+/// Put as many messages as possible into one datagram, like Linux does for dumps.
+fn pack_datagrams(msgs: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    let mut datagrams: Vec<Vec<u8>> = Vec::new();
+    for msg in msgs {
+        match datagrams.last_mut() {
+            Some(datagram) if datagram.len() + msg.len() <= MAX_DATAGRAM_LEN => {
+                datagram.extend_from_slice(&msg);
+            }
+            _ => datagrams.push(msg),
+        }
+    }
+    datagrams
+}
+
+fn get_link(payload: &[u8]) -> Result<Vec<u8>> {
+    // struct ifinfomsg
+    if payload.len() < 16 {
+        return_errno!(EINVAL, "the message is too short");
+    }
+    let index = i32::from_ne_bytes(payload[4..8].try_into().unwrap());
+    if index <= 0 {
+        return_errno!(EINVAL, "only the lookup by index is supported");
+    }
+    let interface = interfaces()
+        .iter()
+        .find(|interface| interface.index == index as u32)
+        .ok_or_else(|| errno!(ENODEV, "no such interface"))?;
+    Ok(new_link(interface))
+}
+
+fn dump_addrs(payload: &[u8]) -> Vec<Vec<u8>> {
+    // The family is the first byte of both struct ifaddrmsg and struct rtgenmsg
+    let family = payload.first().copied().unwrap_or(0) as u16;
+    let mut replies = Vec::new();
+    for interface in interfaces() {
+        for (ip, prefix_len) in &interface.addrs {
+            let ip_family = match ip {
+                IpAddr::V4(_) => AddressFamily::INET as u16,
+                IpAddr::V6(_) => AddressFamily::INET6 as u16,
+            };
+            if family != AddressFamily::UNSPEC as u16 && family != ip_family {
+                continue;
+            }
+            replies.push(new_addr(interface, ip, *prefix_len));
+        }
+    }
+    replies
+}
+
+/// Build a RTM_NEWLINK message, whose header is finished by the caller.
+fn new_link(interface: &Interface) -> Vec<u8> {
+    let mut msg = new_msg(RTM_NEWLINK);
+    let dev_type = if interface.is_loopback() {
+        ARPHRD_LOOPBACK
+    } else if interface.hwaddr.is_some() {
+        ARPHRD_ETHER
+    } else {
+        ARPHRD_NONE
+    };
+    // struct ifinfomsg
+    msg.push(AddressFamily::UNSPEC as u8);
+    msg.push(0);
+    msg.extend_from_slice(&dev_type.to_ne_bytes());
+    msg.extend_from_slice(&(interface.index as i32).to_ne_bytes());
+    msg.extend_from_slice(&interface.flags.to_ne_bytes());
+    msg.extend_from_slice(&0_u32.to_ne_bytes());
+
+    push_attr(&mut msg, IFLA_IFNAME, interface.name.as_bytes(), true);
+    push_attr(&mut msg, IFLA_MTU, &interface.mtu.to_ne_bytes(), false);
+    let oper_state = if interface.is_loopback() {
+        IF_OPER_UNKNOWN
+    } else if interface.flags & (IFF_UP | IFF_RUNNING) == IFF_UP | IFF_RUNNING {
+        IF_OPER_UP
+    } else {
+        IF_OPER_DOWN
+    };
+    push_attr(&mut msg, IFLA_OPERSTATE, &[oper_state], false);
+    if interface.is_loopback() {
+        push_attr(&mut msg, IFLA_ADDRESS, &[0; 6], false);
+        push_attr(&mut msg, IFLA_BROADCAST, &[0; 6], false);
+    } else if let Some(hwaddr) = &interface.hwaddr {
+        push_attr(&mut msg, IFLA_ADDRESS, hwaddr, false);
+        push_attr(&mut msg, IFLA_BROADCAST, &[0xff; 6], false);
+    }
+    msg
+}
+
+/// Build a RTM_NEWADDR message, whose header is finished by the caller.
+fn new_addr(interface: &Interface, ip: &IpAddr, prefix_len: u8) -> Vec<u8> {
+    let mut msg = new_msg(RTM_NEWADDR);
+    let (family, scope) = match ip {
+        IpAddr::V4(ipv4) => {
+            let scope = if ipv4.is_loopback() {
+                RT_SCOPE_HOST
+            } else {
+                RT_SCOPE_UNIVERSE
+            };
+            (AddressFamily::INET, scope)
+        }
+        IpAddr::V6(ipv6) => {
+            let scope = if ipv6.is_loopback() {
+                RT_SCOPE_HOST
+            } else if ipv6.segments()[0] & 0xffc0 == 0xfe80 {
+                RT_SCOPE_LINK
+            } else {
+                RT_SCOPE_UNIVERSE
+            };
+            (AddressFamily::INET6, scope)
+        }
+    };
+    // struct ifaddrmsg
+    msg.push(family as u8);
+    msg.push(prefix_len);
+    msg.push(IFA_F_PERMANENT);
+    msg.push(scope);
+    msg.extend_from_slice(&interface.index.to_ne_bytes());
+
+    match ip {
+        IpAddr::V4(ipv4) => {
+            push_attr(&mut msg, IFA_ADDRESS, &ipv4.octets(), false);
+            push_attr(&mut msg, IFA_LOCAL, &ipv4.octets(), false);
+            if interface.flags & IFF_BROADCAST != 0 && prefix_len < 32 {
+                let host_mask = u32::MAX >> prefix_len;
+                let broadcast = u32::from(*ipv4) | host_mask;
+                push_attr(&mut msg, IFA_BROADCAST, &broadcast.to_be_bytes(), false);
+            }
+            push_attr(&mut msg, IFA_LABEL, interface.name.as_bytes(), true);
+        }
+        IpAddr::V6(ipv6) => {
+            push_attr(&mut msg, IFA_ADDRESS, &ipv6.octets(), false);
+        }
+    }
+    msg
+}
+
+fn error_reply(request: &NlMsgHdr, errno: i32, port_id: u32) -> Vec<u8> {
+    let mut msg = new_msg(NLMSG_ERROR);
+    // struct nlmsgerr, which carries the header of the request
+    msg.extend_from_slice(&(-errno).to_ne_bytes());
+    let mut request_header = vec![0; NLMSG_HDR_LEN];
+    request.write_to(&mut request_header);
+    msg.extend_from_slice(&request_header);
+
+    // Only the header of the request is carried back
+    finish_msg(&mut msg, NLM_F_CAPPED, request.seq, port_id);
+    msg
+}
+
+/// Start a message with a header of which only the type is set.
+fn new_msg(type_: u16) -> Vec<u8> {
+    let mut msg = vec![0; NLMSG_HDR_LEN];
+    msg[4..6].copy_from_slice(&type_.to_ne_bytes());
+    msg
+}
+
+/// Fill the rest of the header after the payload is complete.
+fn finish_msg(msg: &mut [u8], flags: u16, seq: u32, pid: u32) {
+    let type_ = NlMsgHdr::from_bytes(msg).type_;
+    NlMsgHdr {
+        len: msg.len() as u32,
+        type_,
+        flags,
+        seq,
+        pid,
+    }
+    .write_to(msg);
+}
+
+/// Append a struct rtattr and its payload, which is padded to 4 bytes.
+fn push_attr(msg: &mut Vec<u8>, type_: u16, payload: &[u8], with_nul: bool) {
+    let payload_len = payload.len() + with_nul as usize;
+    let attr_len = 4 + payload_len;
+    msg.extend_from_slice(&(attr_len as u16).to_ne_bytes());
+    msg.extend_from_slice(&type_.to_ne_bytes());
+    msg.extend_from_slice(payload);
+    if with_nul {
+        msg.push(0);
+    }
+    msg.resize(align_up(msg.len(), NLMSG_ALIGNTO), 0);
+}
+
+/// struct nlmsghdr
+#[derive(Debug)]
+struct NlMsgHdr {
+    len: u32,
+    type_: u16,
+    flags: u16,
+    seq: u32,
+    pid: u32,
+}
+
+impl NlMsgHdr {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            len: u32::from_ne_bytes(bytes[0..4].try_into().unwrap()),
+            type_: u16::from_ne_bytes(bytes[4..6].try_into().unwrap()),
+            flags: u16::from_ne_bytes(bytes[6..8].try_into().unwrap()),
+            seq: u32::from_ne_bytes(bytes[8..12].try_into().unwrap()),
+            pid: u32::from_ne_bytes(bytes[12..16].try_into().unwrap()),
+        }
+    }
+
+    fn write_to(&self, bytes: &mut [u8]) {
+        bytes[0..4].copy_from_slice(&self.len.to_ne_bytes());
+        bytes[4..6].copy_from_slice(&self.type_.to_ne_bytes());
+        bytes[6..8].copy_from_slice(&self.flags.to_ne_bytes());
+        bytes[8..12].copy_from_slice(&self.seq.to_ne_bytes());
+        bytes[12..16].copy_from_slice(&self.pid.to_ne_bytes());
+    }
+}
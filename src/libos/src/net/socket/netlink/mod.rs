@@ -0,0 +1,48 @@
+//! Netlink sockets served inside the LibOS.
+//!
+//! Only NETLINK_ROUTE is supported, which is enough for getifaddrs() in glibc
+//! and musl, as well as the interface discovery of language runtimes. The
+//! links and addresses are answered from the interfaces configured in
+//! Occlum.json, or a snapshot of the host interfaces taken at initialization.
+//! Thus, no netlink traffic ever goes to the host.
+
+use super::*;
+
+mod addr;
+mod interface;
+mod message;
+mod socket;
+
+pub use self::addr::Addr as NetlinkAddr;
+pub use self::interface::init_host_interfaces;
+pub use self::socket::Socket as NetlinkSocket;
+
+const NETLINK_ROUTE: i32 = 0;
+const SOL_NETLINK: i32 = 270;
+
+pub fn netlink_socket(
+    socket_type: SocketType,
+    flags: FileFlags,
+    protocol: i32,
+) -> Result<NetlinkSocket> {
+    if protocol != NETLINK_ROUTE {
+        return_errno!(EPROTONOSUPPORT, "only NETLINK_ROUTE is supported");
+    }
+
+    match socket_type {
+        SocketType::RAW | SocketType::DGRAM => Ok(NetlinkSocket::new(socket_type, flags)),
+        _ => return_errno!(ESOCKTNOSUPPORT, "only raw and datagram types are supported"),
+    }
+}
+
+pub trait AsNetlinkSocket {
+    fn as_netlink_socket(&self) -> Result<&NetlinkSocket>;
+}
+
+impl AsNetlinkSocket for FileRef {
+    fn as_netlink_socket(&self) -> Result<&NetlinkSocket> {
+        self.as_any()
+            .downcast_ref::<NetlinkSocket>()
+            .ok_or_else(|| errno!(EBADF, "not a netlink socket"))
+    }
+}
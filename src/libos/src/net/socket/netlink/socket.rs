@@ -0,0 +1,426 @@
+use super::addr::Addr;
+use super::message::handle_requests;
+use super::*;
+use crate::events::{Waiter, WaiterQueue};
+use fs::{AccessMode, IoEvents, IoNotifier, IoctlCmd, StatusFlags};
+use rcore_fs::vfs::{FileType, Metadata, Timespec};
+use std::any::Any;
+use std::collections::HashSet;
+
+// The same default and max as Linux
+const DEFAULT_BUF_SIZE: i32 = 212992;
+// The same min of the receive buffer as Linux on x86-64
+const MIN_RCVBUF_SIZE: usize = 2304;
+
+const SO_TYPE: i32 = 3;
+const SO_ERROR: i32 = 4;
+const SO_SNDBUF: i32 = 7;
+const SO_RCVBUF: i32 = 8;
+const SO_PROTOCOL: i32 = 38;
+const SO_DOMAIN: i32 = 39;
+
+lazy_static! {
+    // The port IDs of the bound netlink sockets
+    static ref PORT_IDS: SgxMutex<HashSet<u32>> = SgxMutex::new(HashSet::new());
+}
+
+/// A NETLINK_ROUTE socket served by the LibOS itself.
+///
+/// The socket only talks to the "kernel", which answers the link and address
+/// requests from the interface list of the LibOS. Nothing is sent to the
+/// multicast groups, so a socket bound to the groups simply gets no
+/// notifications.
+pub struct Socket {
+    socket_type: SocketType,
+    inner: SgxMutex<Inner>,
+    waiters: WaiterQueue,
+    notifier: IoNotifier,
+}
+
+struct Inner {
+    addr: Option<Addr>,
+    // The datagrams not received yet
+    replies: VecDeque<Vec<u8>>,
+    // The total length of the replies, which is capped by rcvbuf
+    replies_len: usize,
+    // The datagrams of the dump in progress, which are not queued as replies yet
+    dump: VecDeque<Vec<u8>>,
+    rcvbuf: usize,
+    nonblocking: bool,
+}
+
+impl Socket {
+    pub fn new(socket_type: SocketType, flags: FileFlags) -> Self {
+        Self {
+            socket_type,
+            inner: SgxMutex::new(Inner {
+                addr: None,
+                replies: VecDeque::new(),
+                replies_len: 0,
+                dump: VecDeque::new(),
+                rcvbuf: DEFAULT_BUF_SIZE as usize,
+                nonblocking: flags.contains(FileFlags::SOCK_NONBLOCK),
+            }),
+            waiters: WaiterQueue::new(),
+            notifier: IoNotifier::new(),
+        }
+    }
+
+    pub fn addr(&self) -> Addr {
+        self.inner.lock().unwrap().addr.unwrap_or_default()
+    }
+
+    pub fn peer_addr(&self) -> Addr {
+        Addr::kernel()
+    }
+
+    pub fn bind(&self, addr: &Addr) -> Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(bound_addr) = inner.addr.as_mut() {
+            if addr.port_id != 0 && addr.port_id != bound_addr.port_id {
+                return_errno!(EINVAL, "the socket is already bound");
+            }
+            bound_addr.groups = addr.groups;
+            return Ok(());
+        }
+
+        let port_id = if addr.port_id == 0 {
+            Self::alloc_port_id()
+        } else {
+            let mut port_ids = PORT_IDS.lock().unwrap();
+            if !port_ids.insert(addr.port_id) {
+                return_errno!(EADDRINUSE, "the port ID is in use");
+            }
+            addr.port_id
+        };
+        if addr.groups != 0 {
+            warn!("multicast groups of netlink sockets are not supported");
+        }
+        inner.addr = Some(Addr {
+            port_id,
+            groups: addr.groups,
+        });
+        Ok(())
+    }
+
+    pub fn connect(&self, addr: &Addr) -> Result<()> {
+        if addr.port_id != 0 {
+            return_errno!(ECONNREFUSED, "only the kernel can be connected");
+        }
+        self.autobind()
+    }
+
+    pub fn sendto(&self, buf: &[u8], flags: SendFlags, addr: &Option<Addr>) -> Result<usize> {
+        if let Some(addr) = addr {
+            if addr.port_id != 0 {
+                return_errno!(ECONNREFUSED, "only the kernel can be sent to");
+            }
+        }
+        self.autobind()?;
+
+        let port_id = self.addr().port_id;
+        let mut inner = self.inner.lock().unwrap();
+        let replies = handle_requests(buf, port_id, &mut inner.dump);
+        let len: usize = replies.iter().map(|datagram| datagram.len()).sum();
+        let is_full = inner.replies_len + len > inner.rcvbuf;
+        if !is_full {
+            inner.replies_len += len;
+            inner.replies.extend(replies);
+        }
+        inner.queue_dump();
+        let has_replies = !inner.replies.is_empty();
+        drop(inner);
+
+        if has_replies {
+            self.notifier.broadcast(&IoEvents::IN);
+            self.waiters.dequeue_and_wake_all();
+        }
+        if is_full {
+            return_errno!(ENOBUFS, "the receive buffer is full");
+        }
+        Ok(buf.len())
+    }
+
+    pub fn recvfrom(&self, buf: &mut [u8], flags: RecvFlags) -> Result<(usize, Option<Addr>)> {
+        let (recv_len, datagram_len) = self.recv(&mut [buf], flags)?;
+        let len = if flags.contains(RecvFlags::MSG_TRUNC) {
+            datagram_len
+        } else {
+            recv_len
+        };
+        Ok((len, Some(Addr::kernel())))
+    }
+
+    pub fn sendmsg(&self, msg_hdr: &MsgHdr, flags: SendFlags) -> Result<usize> {
+        let addr = match msg_hdr.get_name() {
+            Some(name) => Some(unsafe {
+                Addr::try_from_raw(
+                    name.as_ptr() as *const libc::sockaddr,
+                    name.len() as libc::socklen_t,
+                )?
+            }),
+            None => None,
+        };
+        if msg_hdr.get_control().is_some() {
+            warn!("sendmsg with msg_control is not supported");
+        }
+
+        let buf = msg_hdr.get_iovs().as_slices().concat();
+        self.sendto(&buf, flags, &addr)
+    }
+
+    pub fn recvmsg(&self, msg_hdr: &mut MsgHdrMut, flags: RecvFlags) -> Result<usize> {
+        let (recv_len, datagram_len) = self.recv(msg_hdr.get_iovs_mut().as_slices_mut(), flags)?;
+
+        if let Some(name) = msg_hdr.get_name_mut() {
+            let name_len = Addr::kernel().copy_to_slice(name);
+            msg_hdr.set_name_len(name_len)?;
+        }
+        msg_hdr.set_control_len(0)?;
+        let msg_flags = if recv_len < datagram_len {
+            MsgHdrFlags::MSG_TRUNC
+        } else {
+            MsgHdrFlags::empty()
+        };
+        msg_hdr.set_flags(msg_flags);
+
+        if flags.contains(RecvFlags::MSG_TRUNC) {
+            Ok(datagram_len)
+        } else {
+            Ok(recv_len)
+        }
+    }
+
+    /// Receive a datagram. Returns the length received and the length of the datagram.
+    fn recv(&self, bufs: &mut [&mut [u8]], flags: RecvFlags) -> Result<(usize, usize)> {
+        let nonblocking = flags.contains(RecvFlags::MSG_DONTWAIT) || self.nonblocking();
+        let waiter = Waiter::new();
+        loop {
+            self.waiters.reset_and_enqueue(&waiter);
+            {
+                let mut inner = self.inner.lock().unwrap();
+                if let Some(datagram) = inner.replies.front() {
+                    let datagram_len = datagram.len();
+                    let mut iovs = IovsMut::new(bufs.iter_mut().map(|buf| &mut **buf).collect());
+                    let recv_len = iovs.copy_from_iter(&mut datagram.iter());
+                    if !flags.contains(RecvFlags::MSG_PEEK) {
+                        inner.replies.pop_front();
+                        inner.replies_len -= datagram_len;
+                        inner.queue_dump();
+                    }
+                    return Ok((recv_len, datagram_len));
+                }
+            }
+            if nonblocking {
+                return_errno!(EAGAIN, "no reply to receive");
+            }
+            waiter.wait(None)?;
+        }
+    }
+
+    /// The length of the next datagram to receive.
+    pub fn bytes_to_read(&self) -> usize {
+        self.inner
+            .lock()
+            .unwrap()
+            .replies
+            .front()
+            .map_or(0, |datagram| datagram.len())
+    }
+
+    pub fn getsockopt(&self, level: i32, optname: i32) -> Result<i32> {
+        if level != libc::SOL_SOCKET {
+            return_errno!(ENOPROTOOPT, "unsupported option");
+        }
+        let optval = match optname {
+            SO_TYPE => self.socket_type as i32,
+            SO_DOMAIN => AddressFamily::NETLINK as i32,
+            SO_PROTOCOL => NETLINK_ROUTE,
+            SO_ERROR => 0,
+            SO_RCVBUF => self.inner.lock().unwrap().rcvbuf as i32,
+            SO_SNDBUF => DEFAULT_BUF_SIZE,
+            _ => return_errno!(ENOPROTOOPT, "unsupported option"),
+        };
+        Ok(optval)
+    }
+
+    pub fn setsockopt(&self, level: i32, optname: i32, optval: &[u8]) -> Result<()> {
+        if level == libc::SOL_SOCKET && optname == SO_RCVBUF {
+            if optval.len() < std::mem::size_of::<i32>() {
+                return_errno!(EINVAL, "optlen is too small");
+            }
+            let val = i32::from_ne_bytes(optval[..4].try_into().unwrap());
+            // Same as Linux, the value is capped and then doubled
+            let val = (val as u32).min(DEFAULT_BUF_SIZE as u32) as usize;
+            self.inner.lock().unwrap().rcvbuf = (val * 2).max(MIN_RCVBUF_SIZE);
+            return Ok(());
+        }
+        // The send buffer size and the netlink options, e.g., NETLINK_EXT_ACK,
+        // make no difference to the replies of the LibOS.
+        if level != libc::SOL_SOCKET && level != SOL_NETLINK {
+            return_errno!(ENOPROTOOPT, "unsupported option");
+        }
+        debug!("ignore option {} of level {}", optname, level);
+        Ok(())
+    }
+
+    pub fn poll(&self) -> IoEvents {
+        if self.inner.lock().unwrap().replies.is_empty() {
+            IoEvents::OUT
+        } else {
+            IoEvents::IN | IoEvents::OUT
+        }
+    }
+
+    fn nonblocking(&self) -> bool {
+        self.inner.lock().unwrap().nonblocking
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) {
+        self.inner.lock().unwrap().nonblocking = nonblocking;
+    }
+
+    /// Bind to a free port ID if not bound yet, like Linux does on sending.
+    fn autobind(&self) -> Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.addr.is_none() {
+            inner.addr = Some(Addr {
+                port_id: Self::alloc_port_id(),
+                groups: 0,
+            });
+        }
+        Ok(())
+    }
+
+    /// Same as Linux, the first socket of a process gets the process ID as
+    /// the port ID, and the others get negative ones.
+    fn alloc_port_id() -> u32 {
+        let mut port_ids = PORT_IDS.lock().unwrap();
+        let pid = current!().process().pid() as u32;
+        let port_id = if !port_ids.contains(&pid) {
+            pid
+        } else {
+            (1..=i32::MAX as u32)
+                .map(|n| (-(n as i32)) as u32)
+                .find(|port_id| !port_ids.contains(port_id))
+                .unwrap()
+        };
+        port_ids.insert(port_id);
+        port_id
+    }
+}
+
+impl Inner {
+    /// Queue the next datagrams of the dump in progress as replies.
+    ///
+    /// Same as Linux, a datagram is queued as long as the receive buffer is
+    /// not full yet, so a dump always makes progress even with a small rcvbuf.
+    fn queue_dump(&mut self) {
+        while self.replies_len < self.rcvbuf {
+            let datagram = match self.dump.pop_front() {
+                Some(datagram) => datagram,
+                None => break,
+            };
+            self.replies_len += datagram.len();
+            self.replies.push_back(datagram);
+        }
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        if let Some(addr) = self.inner.lock().unwrap().addr {
+            PORT_IDS.lock().unwrap().remove(&addr.port_id);
+        }
+    }
+}
+
+impl Debug for Socket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NetlinkSocket")
+            .field("socket_type", &self.socket_type)
+            .field("addr", &self.inner.lock().unwrap().addr)
+            .finish()
+    }
+}
+
+impl File for Socket {
+    fn read(&self, buf: &mut [u8]) -> Result<usize> {
+        self.readv(&mut [buf])
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize> {
+        self.sendto(buf, SendFlags::empty(), &None)
+    }
+
+    fn readv(&self, bufs: &mut [&mut [u8]]) -> Result<usize> {
+        self.recv(bufs, RecvFlags::empty())
+            .map(|(recv_len, _)| recv_len)
+    }
+
+    fn writev(&self, bufs: &[&[u8]]) -> Result<usize> {
+        self.write(&bufs.concat())
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<i32> {
+        match cmd {
+            IoctlCmd::FIONBIO(nonblocking) => {
+                self.set_nonblocking(**nonblocking != 0);
+            }
+            IoctlCmd::FIONREAD(arg) => {
+                **arg = self.bytes_to_read() as i32;
+            }
+            _ => return_errno!(EINVAL, "unknown ioctl cmd for netlink socket"),
+        }
+        Ok(0)
+    }
+
+    fn access_mode(&self) -> Result<AccessMode> {
+        Ok(AccessMode::O_RDWR)
+    }
+
+    fn status_flags(&self) -> Result<StatusFlags> {
+        if self.nonblocking() {
+            Ok(StatusFlags::O_NONBLOCK)
+        } else {
+            Ok(StatusFlags::empty())
+        }
+    }
+
+    fn set_status_flags(&self, new_status_flags: StatusFlags) -> Result<()> {
+        // Only O_NONBLOCK is supported
+        self.set_nonblocking(new_status_flags.contains(StatusFlags::O_NONBLOCK));
+        Ok(())
+    }
+
+    fn poll_new(&self) -> IoEvents {
+        self.poll()
+    }
+
+    fn notifier(&self) -> Option<&IoNotifier> {
+        Some(&self.notifier)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn metadata(&self) -> Result<Metadata> {
+        Ok(Metadata {
+            dev: 0,
+            inode: 0,
+            size: 0,
+            blk_size: 0,
+            blocks: 0,
+            atime: Timespec { sec: 0, nsec: 0 },
+            mtime: Timespec { sec: 0, nsec: 0 },
+            ctime: Timespec { sec: 0, nsec: 0 },
+            type_: FileType::Socket,
+            mode: 0o666,
+            nlinks: 1,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+        })
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::*;
 
 #[derive(Copy, Clone)]
@@ -63,6 +64,28 @@ impl SockAddr {
         })
     }
 
+    pub fn family(&self) -> Result<AddressFamily> {
+        AddressFamily::try_from(self.storage.ss_family)
+    }
+
+    /// Get the IP address and port of an AF_INET or AF_INET6 address.
+    pub fn inet_ip_and_port(&self) -> Option<(IpAddr, u16)> {
+        match self.family().ok()? {
+            AddressFamily::INET if self.len >= mem::size_of::<libc::sockaddr_in>() => {
+                let sin = unsafe { &*(self.as_ptr() as *const libc::sockaddr_in) };
+                let ip = Ipv4Addr::from(sin.sin_addr.s_addr.to_ne_bytes());
+                Some((IpAddr::V4(ip), u16::from_be(sin.sin_port)))
+            }
+            // sin6_scope_id is optional, see try_from_raw
+            AddressFamily::INET6 if self.len >= mem::size_of::<libc::sockaddr_in6>() - 4 => {
+                let sin6 = unsafe { &*(self.as_ptr() as *const libc::sockaddr_in6) };
+                let ip = Ipv6Addr::from(sin6.sin6_addr.s6_addr);
+                Some((IpAddr::V6(ip), u16::from_be(sin6.sin6_port)))
+            }
+            _ => None,
+        }
+    }
+
     pub fn as_ptr_and_len(&self) -> (*const libc::sockaddr, usize) {
         (self.as_ptr(), self.len())
     }
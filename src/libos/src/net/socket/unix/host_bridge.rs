@@ -0,0 +1,185 @@
+//! Bridge the unix sockets to the host.
+//!
+//! A unix socket that connects or binds to a path in a HostFS mount is
+//! replaced by a host unix socket, so that the apps inside the enclave can talk
+//! to the services on the host, and vice versa. The other paths and the
+//! abstract names stay in the LibOS.
+
+use super::*;
+use crate::fs::{File, HNode, INodeExt, StatusFlags, MAX_SYMLINKS, MOUNT_TABLE};
+use rcore_fs::vfs::{FileType, INode};
+use std::path::{Component, Path, PathBuf};
+use std::{mem, slice};
+
+/// The host address of a unix socket bridged to the host.
+pub struct HostAddr {
+    /// The path in the LibOS with the symlinks resolved
+    path: PathBuf,
+    /// The HostFS directory of the socket file
+    dir_inode: Arc<dyn INode>,
+    name: String,
+}
+
+impl HostAddr {
+    /// The path in the LibOS that the network policy is checked against.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Call `f` with the address to connect or bind to on the host.
+    ///
+    /// The address refers to the directory opened through the confined root
+    /// of HostFS, so the host symlinks can not redirect it out of the mount.
+    pub fn with_sock_addr<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&SockAddr) -> Result<T>,
+    {
+        let hnode = self.dir_inode.downcast_ref::<HNode>().unwrap();
+        hnode.with_entry_host_path(&self.name, |host_path| f(&to_sock_addr(host_path)?))
+    }
+}
+
+/// Returns the host address to connect or bind to if the path of the address
+/// is in a HostFS mount.
+///
+/// The path is resolved in the LibOS with the symlinks followed, so a symlink
+/// in HostFS can not point the socket to any other host path. Only the
+/// unbound sockets are bridged, since the sockets bound inside the enclave
+/// are known by their LibOS addresses.
+pub fn host_addr_of(stream: &Stream, addr: &Addr) -> Result<Option<HostAddr>> {
+    let unix_path = match addr {
+        Addr::File(_, unix_path) => unix_path,
+        Addr::Abstract(_) => return Ok(None),
+    };
+    if !stream.is_unbound() {
+        return Ok(None);
+    }
+    let abs_path = unix_path.absolute();
+    let path = match resolve_path(Path::new(&abs_path)) {
+        Ok(path) => path,
+        // The path that can not be resolved is left to the LibOS unix sockets
+        // unless it is in a HostFS mount
+        Err(e) => {
+            let in_hostfs = MOUNT_TABLE
+                .read()
+                .unwrap()
+                .hostfs_path(Path::new(&abs_path))
+                .is_some();
+            if in_hostfs {
+                return Err(e);
+            }
+            return Ok(None);
+        }
+    };
+    let (dir_path, name) = match (path.parent(), path.file_name()) {
+        (Some(dir_path), Some(name)) => (dir_path, name.to_string_lossy().into_owned()),
+        _ => return Ok(None),
+    };
+    let dir_inode = {
+        let current = current!();
+        let fs = current.fs().read().unwrap();
+        fs.lookup_inode(&dir_path.to_string_lossy())?
+    };
+    if dir_inode.downcast_ref::<HNode>().is_none() {
+        return Ok(None);
+    }
+    debug!("bridge unix socket {:?} to host via {:?}", unix_path, path);
+    Ok(Some(HostAddr {
+        path,
+        dir_inode,
+        name,
+    }))
+}
+
+/// Resolve the absolute path in the LibOS, following all the symlinks.
+///
+/// The last component may not exist, e.g., for bind.
+fn resolve_path(path: &Path) -> Result<PathBuf> {
+    let current = current!();
+    let fs = current.fs().read().unwrap();
+    let mut resolved = PathBuf::from("/");
+    let mut rest: VecDeque<PathBuf> = path.components().map(to_path).collect();
+    let mut num_symlinks = 0;
+    while let Some(component) = rest.pop_front() {
+        if component == Path::new("..") {
+            resolved.pop();
+            continue;
+        }
+        if component == Path::new("/") || component == Path::new(".") {
+            continue;
+        }
+        let next = resolved.join(&component);
+        let inode = match fs.lookup_inode_no_follow(&next.to_string_lossy()) {
+            Ok(inode) => inode,
+            Err(e) if e.errno() == ENOENT && rest.is_empty() => {
+                resolved = next;
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+        if inode.metadata()?.type_ != FileType::SymLink {
+            resolved = next;
+            continue;
+        }
+
+        num_symlinks += 1;
+        if num_symlinks > MAX_SYMLINKS {
+            return_errno!(ELOOP, "too many symlinks in the path of the unix socket");
+        }
+        let target = String::from_utf8(inode.read_as_vec()?)
+            .map_err(|_| errno!(ENOENT, "invalid symlink content"))?;
+        let target = Path::new(&target);
+        if target.is_absolute() {
+            resolved = PathBuf::from("/");
+        }
+        for component in target.components().rev() {
+            rest.push_front(to_path(component));
+        }
+    }
+    Ok(resolved)
+}
+
+fn to_path(component: Component) -> PathBuf {
+    Path::new(component.as_os_str()).to_path_buf()
+}
+
+/// Replace the unix socket at `fd` with a new host unix socket, which is then
+/// connected or bound by `setup_fn`.
+pub fn bridge_to_host<F>(fd: FileDesc, file_ref: &FileRef, setup_fn: F) -> Result<()>
+where
+    F: FnOnce(&HostSocket) -> Result<()>,
+{
+    let stream = file_ref.as_unix_socket()?;
+    let flags = if stream.status_flags()?.contains(StatusFlags::O_NONBLOCK) {
+        FileFlags::SOCK_NONBLOCK
+    } else {
+        FileFlags::empty()
+    };
+    let host_socket = HostSocket::new(AddressFamily::LOCAL, SocketType::STREAM, flags, 0)?;
+    setup_fn(&host_socket)?;
+    current!()
+        .files()
+        .lock()
+        .unwrap()
+        .replace(fd, file_ref, Arc::new(host_socket))
+}
+
+fn to_sock_addr(host_path: &Path) -> Result<SockAddr> {
+    let path = host_path.to_string_lossy();
+    let mut raw_addr: libc::sockaddr_un = unsafe { mem::zeroed() };
+    // Leave room for the null terminator
+    if path.len() >= raw_addr.sun_path.len() {
+        return_errno!(ENAMETOOLONG, "the host path of the unix socket is too long");
+    }
+    raw_addr.sun_family = AddressFamily::LOCAL as libc::sa_family_t;
+    for (dst, src) in raw_addr.sun_path.iter_mut().zip(path.as_bytes()) {
+        *dst = *src as _;
+    }
+
+    let addr_len = memoffset::offset_of!(libc::sockaddr_un, sun_path) + path.len() + 1;
+    let raw_bytes = unsafe { slice::from_raw_parts(&raw_addr as *const _ as *const u8, addr_len) };
+    let mut sock_addr = SockAddr::default();
+    sock_addr.as_mut_slice()[..addr_len].copy_from_slice(raw_bytes);
+    sock_addr.set_len(addr_len)?;
+    Ok(sock_addr)
+}
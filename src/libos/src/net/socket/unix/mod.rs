@@ -2,9 +2,11 @@ use self::addr::Addr;
 use super::*;
 
 mod addr;
+mod host_bridge;
 mod stream;
 
 pub use self::addr::Addr as UnixAddr;
+pub use self::host_bridge::{bridge_to_host, host_addr_of};
 pub use self::stream::Stream;
 
 //TODO: rewrite this file when a new kind of uds is added
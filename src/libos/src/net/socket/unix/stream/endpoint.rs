@@ -3,7 +3,9 @@ use events::{Event, EventFilter, Notifier, Observer};
 use fs::channel::{Channel, Consumer, Producer};
 use fs::{IoEvents, IoNotifier};
 use std::any::Any;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 
 pub type Endpoint = Arc<Inner>;
 
@@ -18,6 +20,8 @@ pub fn end_pair(nonblocking: bool) -> Result<(Endpoint, Endpoint)> {
         writer: pro_b,
         peer: Weak::default(),
         ancillary: RwLock::new(None),
+        send_buf_size: AtomicUsize::new(DEFAULT_BUF_SIZE),
+        recv_buf_size: AtomicUsize::new(DEFAULT_BUF_SIZE),
     });
     let end_b = Arc::new(Inner {
         addr: RwLock::new(None),
@@ -25,6 +29,8 @@ pub fn end_pair(nonblocking: bool) -> Result<(Endpoint, Endpoint)> {
         writer: pro_a,
         peer: Arc::downgrade(&end_a),
         ancillary: RwLock::new(None),
+        send_buf_size: AtomicUsize::new(DEFAULT_BUF_SIZE),
+        recv_buf_size: AtomicUsize::new(DEFAULT_BUF_SIZE),
     });
 
     unsafe {
@@ -44,6 +50,8 @@ pub struct Inner {
     writer: Producer<u8>,
     peer: Weak<Self>,
     ancillary: RwLock<Option<Ancillary>>,
+    send_buf_size: AtomicUsize,
+    recv_buf_size: AtomicUsize,
 }
 
 impl Inner {
@@ -70,26 +78,116 @@ impl Inner {
         assert_eq!(cons_nonblocking, prod_nonblocking);
         cons_nonblocking
     }
-    pub fn read(&self, buf: &mut [u8]) -> Result<usize> {
-        self.reader.pop_slice(buf)
+    pub fn sendmsg(
+        &self,
+        bufs: &[&[u8]],
+        flags: SendFlags,
+        mut timeout: Option<Duration>,
+    ) -> Result<usize> {
+        let res = if flags.contains(SendFlags::MSG_DONTWAIT) {
+            self.writer.try_push_slices(bufs)
+        } else {
+            self.writer.push_slices_timeout(bufs, timeout.as_mut())
+        };
+
+        if let Err(e) = &res {
+            if e.errno() == EPIPE && !flags.contains(SendFlags::MSG_NOSIGNAL) {
+                crate::signal::do_tkill(current!().tid(), crate::signal::SIGPIPE.as_u8() as i32);
+            }
+        }
+        res.map_err(timeout_to_eagain)
     }
 
-    pub fn write(&self, buf: &[u8]) -> Result<usize> {
-        self.writer.push_slice(buf)
+    pub fn recvmsg(
+        &self,
+        bufs: &mut [&mut [u8]],
+        flags: RecvFlags,
+        mut timeout: Option<Duration>,
+    ) -> Result<usize> {
+        let nonblocking = flags.contains(RecvFlags::MSG_DONTWAIT);
+        if flags.contains(RecvFlags::MSG_PEEK) {
+            return self
+                .peek(bufs, nonblocking, timeout.as_mut())
+                .map_err(timeout_to_eagain);
+        }
+        if !flags.contains(RecvFlags::MSG_WAITALL) || nonblocking {
+            return self
+                .recv_once(bufs, nonblocking, timeout.as_mut())
+                .map_err(timeout_to_eagain);
+        }
+
+        let total_len: usize = bufs.iter().map(|buf| buf.len()).sum();
+        let mut recv_len = 0;
+        while recv_len < total_len {
+            let mut remaining = skip_bytes(bufs, recv_len);
+            let len = match self.recv_once(&mut remaining, false, timeout.as_mut()) {
+                Ok(len) => len,
+                Err(_) if recv_len > 0 => break,
+                Err(e) => return Err(timeout_to_eagain(e)),
+            };
+            if len == 0 {
+                break;
+            }
+            recv_len += len;
+        }
+        Ok(recv_len)
     }
 
-    pub fn readv(&self, bufs: &mut [&mut [u8]]) -> Result<usize> {
-        self.reader.pop_slices(bufs)
+    fn recv_once(
+        &self,
+        bufs: &mut [&mut [u8]],
+        nonblocking: bool,
+        timeout: Option<&mut Duration>,
+    ) -> Result<usize> {
+        if nonblocking {
+            self.reader.try_pop_slices(bufs)
+        } else {
+            self.reader.pop_slices_timeout(bufs, timeout)
+        }
     }
 
-    pub fn writev(&self, bufs: &[&[u8]]) -> Result<usize> {
-        self.writer.push_slices(bufs)
+    /// Copy the bytes in the reader without consuming them, so they still
+    /// take up the receive buffer.
+    fn peek(
+        &self,
+        bufs: &mut [&mut [u8]],
+        nonblocking: bool,
+        timeout: Option<&mut Duration>,
+    ) -> Result<usize> {
+        if nonblocking {
+            self.reader.try_peek_slices(bufs)
+        } else {
+            self.reader.peek_slices_timeout(bufs, timeout)
+        }
     }
 
     pub fn bytes_to_read(&self) -> usize {
         self.reader.items_to_consume()
     }
 
+    pub fn send_buf_size(&self) -> usize {
+        self.send_buf_size.load(Ordering::Relaxed)
+    }
+
+    pub fn recv_buf_size(&self) -> usize {
+        self.recv_buf_size.load(Ordering::Relaxed)
+    }
+
+    /// Set the buffer sizes of this end and resize the channels accordingly.
+    ///
+    /// A channel is as large as the send buffer of its writer or the receive
+    /// buffer of its reader, whichever is larger.
+    pub fn set_buf_sizes(&self, send_buf_size: usize, recv_buf_size: usize) {
+        self.send_buf_size.store(send_buf_size, Ordering::Relaxed);
+        self.recv_buf_size.store(recv_buf_size, Ordering::Relaxed);
+        if let Some(peer) = self.peer.upgrade() {
+            self.writer
+                .resize(&peer.reader, send_buf_size.max(peer.recv_buf_size()));
+            peer.writer
+                .resize(&self.reader, recv_buf_size.max(peer.send_buf_size()));
+        }
+    }
+
     pub fn shutdown(&self, how: HowToShut) -> Result<()> {
         if !self.is_connected() {
             return_errno!(ENOTCONN, "The socket is not connected.");
@@ -165,10 +263,18 @@ impl Ancillary {
     }
 }
 
-// TODO: Add SO_SNDBUF and SO_RCVBUF to set/getsockopt to dynamcally change the size.
-// This value is got from /proc/sys/net/core/rmem_max and wmem_max that are same on linux.
+// This value is got from /proc/sys/net/core/rmem_default and wmem_default that are same on linux.
 pub const DEFAULT_BUF_SIZE: usize = 208 * 1024;
 
+// A socket times out with EAGAIN, same with Linux
+fn timeout_to_eagain(e: Error) -> Error {
+    if e.errno() == ETIMEDOUT {
+        errno!(EAGAIN, "timed out")
+    } else {
+        e
+    }
+}
+
 /// An observer used to observe both reader and writer of the endpoint. It also contains a
 /// notifier that relays the notification of the endpoint.
 pub(super) struct RelayNotifier {
@@ -0,0 +1,176 @@
+//! The socket options of stream unix sockets.
+//!
+//! Only the options at the `SOL_SOCKET` level make sense for unix sockets.
+//! The buffer sizes are backed by the capacities of the channels, and the
+//! timeouts are applied to the blocking sends and receives.
+
+use super::endpoint::DEFAULT_BUF_SIZE;
+use super::stream::Status;
+use super::*;
+use net::socket::MEM_MAX_LIMIT;
+use net::{rmem_max, wmem_max};
+use std::mem;
+use std::time::Duration;
+use time::timeval_t;
+
+const SO_TYPE: i32 = 3;
+const SO_ERROR: i32 = 4;
+const SO_SNDBUF: i32 = 7;
+const SO_RCVBUF: i32 = 8;
+const SO_RCVTIMEO: i32 = 20;
+const SO_SNDTIMEO: i32 = 21;
+const SO_ACCEPTCONN: i32 = 30;
+const SO_SNDBUFFORCE: i32 = 32;
+const SO_RCVBUFFORCE: i32 = 33;
+const SO_PROTOCOL: i32 = 38;
+const SO_DOMAIN: i32 = 39;
+
+// The lower bounds of the buffer sizes, same with Linux
+const MIN_SEND_BUF_SIZE: usize = 4608;
+const MIN_RECV_BUF_SIZE: usize = 2304;
+
+/// The options of a stream unix socket, which are kept in any status and
+/// inherited by the sockets accepted from a listening socket.
+#[derive(Debug, Clone)]
+pub struct SockOptions {
+    pub send_buf_size: usize,
+    pub recv_buf_size: usize,
+    pub send_timeout: Option<Duration>,
+    pub recv_timeout: Option<Duration>,
+}
+
+impl Default for SockOptions {
+    fn default() -> Self {
+        Self {
+            send_buf_size: DEFAULT_BUF_SIZE,
+            recv_buf_size: DEFAULT_BUF_SIZE,
+            send_timeout: None,
+            recv_timeout: None,
+        }
+    }
+}
+
+impl Stream {
+    /// Get the option into `optval`, returning the length of the option.
+    ///
+    /// Same with Linux, the option is truncated if `optval` is too short.
+    pub fn getsockopt(&self, level: i32, optname: i32, optval: &mut [u8]) -> Result<usize> {
+        if level != libc::SOL_SOCKET {
+            return_errno!(ENOPROTOOPT, "unsupported option");
+        }
+        let options = self.options().clone();
+        let val = match optname {
+            SO_SNDTIMEO => return Ok(copy_timeout(options.send_timeout, optval)),
+            SO_RCVTIMEO => return Ok(copy_timeout(options.recv_timeout, optval)),
+            SO_TYPE => SocketType::STREAM as i32,
+            SO_DOMAIN => AddressFamily::LOCAL as i32,
+            SO_PROTOCOL => 0,
+            // No error is pending on an in-enclave socket
+            SO_ERROR => 0,
+            SO_ACCEPTCONN => matches!(&*self.inner(), Status::Listening(_)) as i32,
+            SO_SNDBUF => options.send_buf_size as i32,
+            SO_RCVBUF => options.recv_buf_size as i32,
+            _ => return_errno!(ENOPROTOOPT, "unsupported option"),
+        };
+        Ok(copy_bytes(&val.to_ne_bytes(), optval))
+    }
+
+    pub fn setsockopt(&self, level: i32, optname: i32, optval: &[u8]) -> Result<()> {
+        if level != libc::SOL_SOCKET {
+            return_errno!(ENOPROTOOPT, "unsupported option");
+        }
+        match optname {
+            SO_SNDBUF | SO_SNDBUFFORCE => {
+                let max_size = if optname == SO_SNDBUF {
+                    wmem_max()
+                } else {
+                    MEM_MAX_LIMIT
+                };
+                let size = to_buf_size(parse_int(optval)?, max_size, MIN_SEND_BUF_SIZE);
+                self.options().send_buf_size = size;
+                self.apply_buf_sizes();
+            }
+            SO_RCVBUF | SO_RCVBUFFORCE => {
+                let max_size = if optname == SO_RCVBUF {
+                    rmem_max()
+                } else {
+                    MEM_MAX_LIMIT
+                };
+                let size = to_buf_size(parse_int(optval)?, max_size, MIN_RECV_BUF_SIZE);
+                self.options().recv_buf_size = size;
+                self.apply_buf_sizes();
+            }
+            SO_SNDTIMEO => self.options().send_timeout = parse_timeout(optval)?,
+            SO_RCVTIMEO => self.options().recv_timeout = parse_timeout(optval)?,
+            SO_TYPE | SO_DOMAIN | SO_PROTOCOL | SO_ERROR | SO_ACCEPTCONN => {
+                return_errno!(ENOPROTOOPT, "the option is read-only")
+            }
+            // The other options, e.g., SO_REUSEADDR and SO_PASSCRED, make no
+            // difference to the in-enclave sockets.
+            _ => debug!("ignore option {} of unix socket", optname),
+        }
+        Ok(())
+    }
+
+    /// Resize the channels of a connected socket after the buffer sizes change.
+    fn apply_buf_sizes(&self) {
+        let options = self.options().clone();
+        let status = (*self.inner()).clone();
+        if let Status::Connected(endpoint) = status {
+            endpoint.set_buf_sizes(options.send_buf_size, options.recv_buf_size);
+        }
+    }
+}
+
+/// Same with Linux, the size is doubled to count in the bookkeeping overhead.
+fn to_buf_size(val: i32, max_size: usize, min_size: usize) -> usize {
+    let size = (val.max(0) as usize).min(max_size);
+    (size * 2).max(min_size)
+}
+
+fn parse_int(optval: &[u8]) -> Result<i32> {
+    if optval.len() < mem::size_of::<i32>() {
+        return_errno!(EINVAL, "optlen is too small");
+    }
+    Ok(i32::from_ne_bytes(
+        optval[..mem::size_of::<i32>()].try_into().unwrap(),
+    ))
+}
+
+/// Parse the timeout, where zero means blocking forever.
+fn parse_timeout(optval: &[u8]) -> Result<Option<Duration>> {
+    if optval.len() < mem::size_of::<timeval_t>() {
+        return_errno!(EINVAL, "optlen is too small");
+    }
+    let timeval = unsafe { std::ptr::read_unaligned(optval.as_ptr() as *const timeval_t) };
+    if timeval.usec() < 0 || timeval.usec() >= 1_000_000 {
+        return_errno!(EDOM, "invalid timeout");
+    }
+    // Same with Linux, a negative timeout never blocks
+    if timeval.sec() < 0 {
+        return Ok(Some(Duration::from_secs(0)));
+    }
+    let timeout = timeval.as_duration();
+    if timeout == Duration::from_secs(0) {
+        Ok(None)
+    } else {
+        Ok(Some(timeout))
+    }
+}
+
+fn copy_timeout(timeout: Option<Duration>, optval: &mut [u8]) -> usize {
+    let timeval = timeout.map_or(timeval_t::default(), timeval_t::from);
+    let bytes = unsafe {
+        std::slice::from_raw_parts(
+            &timeval as *const timeval_t as *const u8,
+            mem::size_of::<timeval_t>(),
+        )
+    };
+    copy_bytes(bytes, optval)
+}
+
+fn copy_bytes(src: &[u8], dst: &mut [u8]) -> usize {
+    let len = src.len().min(dst.len());
+    dst[..len].copy_from_slice(&src[..len]);
+    len
+}
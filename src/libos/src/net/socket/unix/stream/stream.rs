@@ -1,5 +1,6 @@
 use super::address_space::ADDRESS_SPACE;
 use super::endpoint::{end_pair, Ancillary, Endpoint, RelayNotifier};
+use super::options::SockOptions;
 use super::*;
 use events::{Event, EventFilter, Notifier, Observer};
 use fs::channel::Channel;
@@ -20,6 +21,7 @@ pub struct Stream {
     // Use the internal notifier of RelayNotifier as the notifier of stream socket. It relays the
     // events of the endpoint, too.
     pub(super) notifier: Arc<RelayNotifier>,
+    options: SgxMutex<SockOptions>,
 }
 
 impl Stream {
@@ -29,6 +31,7 @@ impl Stream {
                 flags.contains(FileFlags::SOCK_NONBLOCK),
             ))),
             notifier: Arc::new(RelayNotifier::new()),
+            options: SgxMutex::new(SockOptions::default()),
         }
     }
 
@@ -43,11 +46,13 @@ impl Stream {
         let socket_a = Self {
             inner: SgxMutex::new(Status::Connected(end_a)),
             notifier: notifier_a,
+            options: SgxMutex::new(SockOptions::default()),
         };
 
         let socket_b = Self {
             inner: SgxMutex::new(Status::Connected(end_b)),
             notifier: notifier_b,
+            options: SgxMutex::new(SockOptions::default()),
         };
 
         Ok((socket_a, socket_b))
@@ -70,6 +75,11 @@ impl Stream {
         return_errno!(ENOTCONN, "the socket is not connected");
     }
 
+    /// Whether the socket is idle and not bound to any address.
+    pub fn is_unbound(&self) -> bool {
+        matches!(&*self.inner(), Status::Idle(info) if info.addr().is_none())
+    }
+
     pub fn bind(&self, addr: &mut Addr) -> Result<()> {
         if let Addr::File(inode_num, path) = addr {
             // create the corresponding file in the fs and fill Addr with its inode
@@ -111,11 +121,10 @@ impl Stream {
     }
 
     pub fn listen(&self, backlog: i32) -> Result<()> {
-        //TODO: restrict backlog accroding to /proc/sys/net/core/somaxconn
         if backlog < 0 {
             return_errno!(EINVAL, "negative backlog is not supported");
         }
-        let capacity = backlog as usize;
+        let capacity = restrict_backlog(backlog) as usize;
 
         let mut inner = self.inner();
         match &*inner {
@@ -164,6 +173,8 @@ impl Stream {
                 end_self.set_ancillary(Ancillary {
                     tid: current!().tid(),
                 });
+                let options = self.options().clone();
+                end_self.set_buf_sizes(options.send_buf_size, options.recv_buf_size);
 
                 ADDRESS_SPACE
                     .push_incoming(addr, end_incoming)
@@ -196,6 +207,9 @@ impl Stream {
                 endpoint.set_ancillary(Ancillary {
                     tid: current!().tid(),
                 });
+                // The accepted socket inherits the options of the listening socket
+                let options = self.options().clone();
+                endpoint.set_buf_sizes(options.send_buf_size, options.recv_buf_size);
                 let notifier = Arc::new(RelayNotifier::new());
                 notifier.observe_endpoint(&endpoint);
 
@@ -207,6 +221,7 @@ impl Stream {
                     Self {
                         inner: SgxMutex::new(Status::Connected(endpoint)),
                         notifier: notifier,
+                        options: SgxMutex::new(options),
                     },
                     peer_addr,
                 ))
@@ -215,14 +230,12 @@ impl Stream {
         }
     }
 
-    // TODO: handle flags
     pub fn sendto(&self, buf: &[u8], flags: SendFlags, addr: &Option<Addr>) -> Result<usize> {
-        self.write(buf)
+        self.send_slices(&[buf], flags)
     }
 
-    // TODO: handle flags
     pub fn recvfrom(&self, buf: &mut [u8], flags: RecvFlags) -> Result<(usize, Option<Addr>)> {
-        let data_len = self.read(buf)?;
+        let data_len = self.recv_slices(&mut [buf], flags)?;
         let addr = self.peer_addr().ok();
 
         debug!("recvfrom {:?}", addr);
@@ -231,35 +244,33 @@ impl Stream {
     }
 
     pub fn sendmsg(&self, msg_hdr: &MsgHdr, flags: SendFlags) -> Result<usize> {
-        if !flags.is_empty() {
-            warn!("unsupported flags: {:?}", flags);
-        }
-
         let bufs = msg_hdr.get_iovs().as_slices();
-        let mut data_len = self.writev(bufs)?;
+        let mut data_len = self.send_slices(bufs, flags)?;
 
         if let Some(msg_control) = msg_hdr.get_control() {
-            data_len += self.write(msg_control)?;
+            data_len += self.send_slices(&[msg_control], flags)?;
         }
         Ok(data_len)
     }
 
     pub fn recvmsg(&self, msg_hdr: &mut MsgHdrMut, flags: RecvFlags) -> Result<usize> {
-        if !flags.is_empty() {
-            warn!("unsupported flags: {:?}", flags);
-        }
-
         let bufs = msg_hdr.get_iovs_mut().as_slices_mut();
-        let mut data_len = self.readv(bufs)?;
+        let mut data_len = self.recv_slices(bufs, flags)?;
 
         // For stream socket, the msg_name is ignored. And other fields are not supported.
         msg_hdr.set_name_len(0);
 
+        // The control messages are consumed along with the data, so they are not peeked
+        if flags.contains(RecvFlags::MSG_PEEK) {
+            return Ok(data_len);
+        }
+
         if let Some(msg_control) = msg_hdr.get_control_mut() {
-            data_len += self.read(msg_control)?;
+            data_len += self.recv_slices(&mut [msg_control], flags)?;
 
             // For each control message that contains file descriptors (SOL_SOCKET and SCM_RIGHTS),
             // reassign each fd in the message in receive end.
+            let close_on_spawn = flags.contains(RecvFlags::MSG_CMSG_CLOEXEC);
             for cmsg in CMessages::from_bytes(msg_control) {
                 if let CmsgData::ScmRights(mut scm_rights) = cmsg {
                     let send_tid = self.peer_ancillary().unwrap().tid();
@@ -271,7 +282,7 @@ impl Stream {
                             .unwrap()
                             .get(send_fd)
                             .unwrap();
-                        current!().add_file(ipc_file.clone(), false)
+                        current!().add_file(ipc_file.clone(), close_on_spawn)
                     })
                 }
                 // Unix credentials need not to be handled here
@@ -280,6 +291,41 @@ impl Stream {
         Ok(data_len)
     }
 
+    /// Send the data with the flags, where MSG_DONTWAIT and MSG_NOSIGNAL take
+    /// effect and the other flags make no difference.
+    pub(super) fn send_slices(&self, bufs: &[&[u8]], flags: SendFlags) -> Result<usize> {
+        if flags.contains(SendFlags::MSG_OOB) {
+            return_errno!(EOPNOTSUPP, "MSG_OOB is not supported");
+        }
+        let status = (*self.inner()).clone();
+        match status {
+            Status::Connected(endpoint) => {
+                let timeout = self.options().send_timeout;
+                endpoint.sendmsg(bufs, flags, timeout)
+            }
+            _ => return_errno!(ENOTCONN, "unconnected socket"),
+        }
+    }
+
+    /// Receive the data with the flags, where MSG_DONTWAIT, MSG_PEEK and
+    /// MSG_WAITALL take effect and the other flags make no difference.
+    pub(super) fn recv_slices(&self, bufs: &mut [&mut [u8]], flags: RecvFlags) -> Result<usize> {
+        if flags.contains(RecvFlags::MSG_OOB) {
+            return_errno!(EOPNOTSUPP, "MSG_OOB is not supported");
+        }
+        if flags.contains(RecvFlags::MSG_ERRQUEUE) {
+            return_errno!(EAGAIN, "no error is queued");
+        }
+        let status = (*self.inner()).clone();
+        match status {
+            Status::Connected(endpoint) => {
+                let timeout = self.options().recv_timeout;
+                endpoint.recvmsg(bufs, flags, timeout)
+            }
+            _ => return_errno!(ENOTCONN, "unconnected socket"),
+        }
+    }
+
     /// perform shutdown on the socket.
     pub fn shutdown(&self, how: HowToShut) -> Result<()> {
         if let Status::Connected(ref end) = &*self.inner() {
@@ -312,6 +358,10 @@ impl Stream {
         self.inner.lock().unwrap()
     }
 
+    pub(super) fn options(&self) -> SgxMutexGuard<'_, SockOptions> {
+        self.options.lock().unwrap()
+    }
+
     fn ancillary(&self) -> Option<Ancillary> {
         match &*self.inner() {
             Status::Idle(_) => None,
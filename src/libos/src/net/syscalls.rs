@@ -4,6 +4,8 @@ use std::mem::MaybeUninit;
 use std::time::Duration;
 
 use super::io_multiplexing::{AsEpollFile, EpollCtl, EpollFile, EpollFlags, FdSetExt, PollFd};
+use super::policy::{check_egress, check_ingress, check_unix_egress, check_unix_ingress};
+use super::socket::{bridge_to_host, host_addr_of};
 use fs::{CreationFlags, File, FileDesc, FileRef};
 use misc::resource_t;
 use process::Process;
@@ -12,6 +14,9 @@ use std::convert::TryFrom;
 use time::{timespec_t, timeval_t};
 use util::mem_util::from_user;
 
+// The max number of messages of sendmmsg and recvmmsg
+const UIO_MAXIOV: c_uint = 1024;
+
 pub fn do_socket(domain: c_int, socket_type: c_int, protocol: c_int) -> Result<isize> {
     let sock_domain = AddressFamily::try_from(domain as u16)?;
     let file_flags = FileFlags::from_bits_truncate(socket_type);
@@ -22,6 +27,10 @@ pub fn do_socket(domain: c_int, socket_type: c_int, protocol: c_int) -> Result<i
             let unix_socket = unix_socket(sock_type, file_flags, protocol)?;
             Arc::new(unix_socket)
         }
+        AddressFamily::NETLINK => {
+            let netlink_socket = netlink_socket(sock_type, file_flags, protocol)?;
+            Arc::new(netlink_socket)
+        }
         _ => {
             let socket = HostSocket::new(sock_domain, sock_type, file_flags, protocol)?;
             Arc::new(socket)
@@ -43,11 +52,23 @@ pub fn do_bind(fd: c_int, addr: *const libc::sockaddr, addr_len: libc::socklen_t
     if let Ok(socket) = file_ref.as_host_socket() {
         let sock_addr = unsafe { SockAddr::try_from_raw(addr, addr_len)? };
         trace!("bind to addr: {:?}", sock_addr);
+        check_ingress(socket, &sock_addr)?;
         socket.bind(&sock_addr)?;
     } else if let Ok(unix_socket) = file_ref.as_unix_socket() {
         let mut unix_addr = unsafe { UnixAddr::try_from_raw(addr, addr_len)? };
         trace!("bind to addr: {:?}", unix_addr);
-        unix_socket.bind(&mut unix_addr)?;
+        if let Some(host_addr) = host_addr_of(unix_socket, &unix_addr)? {
+            check_unix_ingress(host_addr.path())?;
+            bridge_to_host(fd as FileDesc, &file_ref, |host_socket| {
+                host_addr.with_sock_addr(|addr| host_socket.bind(addr))
+            })?;
+        } else {
+            unix_socket.bind(&mut unix_addr)?;
+        }
+    } else if let Ok(netlink_socket) = file_ref.as_netlink_socket() {
+        let netlink_addr = unsafe { NetlinkAddr::try_from_raw(addr, addr_len)? };
+        trace!("bind to addr: {:?}", netlink_addr);
+        netlink_socket.bind(&netlink_addr)?;
     } else {
         return_errno!(ENOTSOCK, "not a socket");
     }
@@ -58,9 +79,15 @@ pub fn do_bind(fd: c_int, addr: *const libc::sockaddr, addr_len: libc::socklen_t
 pub fn do_listen(fd: c_int, backlog: c_int) -> Result<isize> {
     let file_ref = current!().file(fd as FileDesc)?;
     if let Ok(socket) = file_ref.as_host_socket() {
+        // The socket may be bound implicitly by listen
+        if let Ok(addr) = socket.host_addr() {
+            check_ingress(socket, &addr)?;
+        }
         socket.listen(backlog)?;
     } else if let Ok(unix_socket) = file_ref.as_unix_socket() {
         unix_socket.listen(backlog)?;
+    } else if let Ok(netlink_socket) = file_ref.as_netlink_socket() {
+        return_errno!(EOPNOTSUPP, "netlink socket does not support listen");
     } else {
         return_errno!(ENOTSOCK, "not a socket");
     }
@@ -88,6 +115,9 @@ pub fn do_connect(
         } else {
             None
         };
+        if let Some(addr) = &addr_option {
+            check_egress(socket, addr)?;
+        }
 
         socket.connect(&addr_option)?;
     } else if let Ok(unix_socket) = file_ref.as_unix_socket() {
@@ -98,7 +128,22 @@ pub fn do_connect(
             return_errno!(EINVAL, "invalid address");
         };
 
-        unix_socket.connect(&addr)?;
+        if let Some(host_addr) = host_addr_of(unix_socket, &addr)? {
+            check_unix_egress(host_addr.path())?;
+            bridge_to_host(fd as FileDesc, &file_ref, |host_socket| {
+                host_addr.with_sock_addr(|addr| host_socket.connect(&Some(*addr)))
+            })?;
+        } else {
+            unix_socket.connect(&addr)?;
+        }
+    } else if let Ok(netlink_socket) = file_ref.as_netlink_socket() {
+        let addr = if addr_set {
+            unsafe { NetlinkAddr::try_from_raw(addr, addr_len)? }
+        } else {
+            return_errno!(EINVAL, "invalid address");
+        };
+
+        netlink_socket.connect(&addr)?;
     } else {
         return_errno!(ENOTSOCK, "not a socket");
     }
@@ -170,6 +215,8 @@ pub fn do_accept4(
             }
         }
         Ok(new_fd as isize)
+    } else if let Ok(netlink_socket) = file_ref.as_netlink_socket() {
+        return_errno!(EOPNOTSUPP, "netlink socket does not support accept");
     } else {
         return_errno!(ENOTSOCK, "not a socket");
     }
@@ -184,6 +231,8 @@ pub fn do_shutdown(fd: c_int, how: c_int) -> Result<isize> {
         socket.shutdown(how)?;
     } else if let Ok(unix_socket) = file_ref.as_unix_socket() {
         unix_socket.shutdown(how)?;
+    } else if let Ok(netlink_socket) = file_ref.as_netlink_socket() {
+        return_errno!(EOPNOTSUPP, "netlink socket does not support shutdown");
     } else {
         return_errno!(EBADF, "not a host socket")
     }
@@ -213,7 +262,14 @@ pub fn do_setsockopt(
         ));
         Ok(ret as isize)
     } else if let Ok(unix_socket) = file_ref.as_unix_socket() {
-        warn!("setsockopt for unix socket is unimplemented");
+        from_user::check_array(optval as *const u8, optlen as usize)?;
+        let optval = unsafe { std::slice::from_raw_parts(optval as *const u8, optlen as usize) };
+        unix_socket.setsockopt(level, optname, optval)?;
+        Ok(0)
+    } else if let Ok(netlink_socket) = file_ref.as_netlink_socket() {
+        from_user::check_array(optval as *const u8, optlen as usize)?;
+        let optval = unsafe { std::slice::from_raw_parts(optval as *const u8, optlen as usize) };
+        netlink_socket.setsockopt(level, optname, optval)?;
         Ok(0)
     } else {
         return_errno!(ENOTSOCK, "not a socket")
@@ -244,7 +300,29 @@ pub fn do_getsockopt(
         ));
         Ok(ret as isize)
     } else if let Ok(unix_socket) = file_ref.as_unix_socket() {
-        warn!("getsockopt for unix socket is unimplemented");
+        from_user::check_mut_ptr(optlen)?;
+        let len = unsafe { *optlen } as usize;
+        from_user::check_mut_array(optval as *mut u8, len)?;
+        let optval = unsafe { std::slice::from_raw_parts_mut(optval as *mut u8, len) };
+
+        let val_len = unix_socket.getsockopt(level, optname, optval)?;
+        unsafe {
+            *optlen = val_len as u32;
+        }
+        Ok(0)
+    } else if let Ok(netlink_socket) = file_ref.as_netlink_socket() {
+        from_user::check_mut_ptr(optlen)?;
+        let len = unsafe { *optlen } as usize;
+        if len < std::mem::size_of::<c_int>() {
+            return_errno!(EINVAL, "optlen is too small");
+        }
+        from_user::check_mut_ptr(optval as *mut c_int)?;
+
+        let val = netlink_socket.getsockopt(level, optname)?;
+        unsafe {
+            *(optval as *mut c_int) = val;
+            *optlen = std::mem::size_of::<c_int>() as u32;
+        }
         Ok(0)
     } else {
         return_errno!(ENOTSOCK, "not a socket")
@@ -266,6 +344,16 @@ pub fn do_getpeername(
 
     let file_ref = current!().file(fd as FileDesc)?;
     if let Ok(socket) = file_ref.as_host_socket() {
+        if let Some((_, peer_addr)) = socket.loopback_addrs() {
+            let mut dst =
+                unsafe { std::slice::from_raw_parts_mut(addr as *mut u8, *addr_len as usize) };
+            peer_addr.copy_to_slice(&mut dst);
+            unsafe {
+                *addr_len = peer_addr.len() as u32;
+            }
+            return Ok(0);
+        }
+
         let ret = try_libc!(libc::ocall::getpeername(
             socket.raw_host_fd() as i32,
             addr,
@@ -282,6 +370,15 @@ pub fn do_getpeername(
             *addr_len = name.raw_len() as u32;
         }
         Ok(0)
+    } else if let Ok(netlink_socket) = file_ref.as_netlink_socket() {
+        let name = netlink_socket.peer_addr();
+        let mut dst =
+            unsafe { std::slice::from_raw_parts_mut(addr as *mut u8, *addr_len as usize) };
+        name.copy_to_slice(dst);
+        unsafe {
+            *addr_len = name.raw_len() as u32;
+        }
+        Ok(0)
     } else {
         return_errno!(ENOTSOCK, "not a socket")
     }
@@ -306,6 +403,16 @@ pub fn do_getsockname(
 
     let file_ref = current!().file(fd as FileDesc)?;
     if let Ok(socket) = file_ref.as_host_socket() {
+        if let Some((sock_addr, _)) = socket.loopback_addrs() {
+            let mut dst =
+                unsafe { std::slice::from_raw_parts_mut(addr as *mut u8, *addr_len as usize) };
+            sock_addr.copy_to_slice(&mut dst);
+            unsafe {
+                *addr_len = sock_addr.len() as u32;
+            }
+            return Ok(0);
+        }
+
         let ret = try_libc!(libc::ocall::getsockname(
             socket.raw_host_fd() as i32,
             addr,
@@ -329,6 +436,15 @@ pub fn do_getsockname(
             }
         }
         Ok(0)
+    } else if let Ok(netlink_socket) = file_ref.as_netlink_socket() {
+        let name = netlink_socket.addr();
+        let mut dst =
+            unsafe { std::slice::from_raw_parts_mut(addr as *mut u8, *addr_len as usize) };
+        name.copy_to_slice(dst);
+        unsafe {
+            *addr_len = name.raw_len() as u32;
+        }
+        Ok(0)
     } else {
         return_errno!(ENOTSOCK, "not a socket");
     }
@@ -367,6 +483,9 @@ pub fn do_sendto(
         } else {
             None
         };
+        if let Some(addr) = &addr_option {
+            check_egress(socket, addr)?;
+        }
 
         socket
             .sendto(buf, send_flags, &addr_option)
@@ -381,6 +500,16 @@ pub fn do_sendto(
         unix_socket
             .sendto(buf, send_flags, &addr_option)
             .map(|u| u as isize)
+    } else if let Ok(netlink_socket) = file_ref.as_netlink_socket() {
+        let addr_option = if addr_set {
+            Some(unsafe { NetlinkAddr::try_from_raw(addr, addr_len)? })
+        } else {
+            None
+        };
+
+        netlink_socket
+            .sendto(buf, send_flags, &addr_option)
+            .map(|u| u as isize)
     } else {
         return_errno!(EBADF, "unsupported file type");
     }
@@ -447,6 +576,23 @@ pub fn do_recvfrom(
             }
         }
         Ok(data_len as isize)
+    } else if let Ok(netlink_socket) = file_ref.as_netlink_socket() {
+        let (data_len, sock_addr_option) = netlink_socket.recvfrom(buf, recv_flags)?;
+        if addr_set {
+            if let Some(sock_addr) = sock_addr_option {
+                let mut buf =
+                    unsafe { std::slice::from_raw_parts_mut(addr as *mut u8, *addr_len as usize) };
+                sock_addr.copy_to_slice(&mut buf);
+                unsafe {
+                    *addr_len = sock_addr.raw_len() as u32;
+                }
+            } else {
+                unsafe {
+                    *addr_len = 0;
+                }
+            }
+        }
+        Ok(data_len as isize)
     } else {
         return_errno!(ENOTSOCK, "not a socket");
     }
@@ -503,6 +649,7 @@ pub fn do_sendmsg(fd: c_int, msg_ptr: *const msghdr, flags_c: c_int) -> Result<i
 
     let file_ref = current!().file(fd as FileDesc)?;
     if let Ok(socket) = file_ref.as_host_socket() {
+        check_msg_egress(socket, &msg_hdr)?;
         socket
             .sendmsg(&msg_hdr, flags)
             .map(|bytes_sent| bytes_sent as isize)
@@ -510,11 +657,29 @@ pub fn do_sendmsg(fd: c_int, msg_ptr: *const msghdr, flags_c: c_int) -> Result<i
         socket
             .sendmsg(&msg_hdr, flags)
             .map(|bytes_sent| bytes_sent as isize)
+    } else if let Ok(socket) = file_ref.as_netlink_socket() {
+        socket
+            .sendmsg(&msg_hdr, flags)
+            .map(|bytes_sent| bytes_sent as isize)
     } else {
         return_errno!(ENOTSOCK, "not a socket")
     }
 }
 
+/// Check the destination of a message sent by a host socket, if any.
+fn check_msg_egress(socket: &HostSocket, msg_hdr: &MsgHdr) -> Result<()> {
+    if let Some(name) = msg_hdr.get_name() {
+        let addr = unsafe {
+            SockAddr::try_from_raw(
+                name.as_ptr() as *const libc::sockaddr,
+                name.len() as libc::socklen_t,
+            )?
+        };
+        check_egress(socket, &addr)?;
+    }
+    Ok(())
+}
+
 pub fn do_recvmsg(fd: c_int, msg_mut_ptr: *mut msghdr_mut, flags_c: c_int) -> Result<isize> {
     debug!(
         "recvmsg: fd: {}, msg: {:?}, flags: 0x{:x}",
@@ -542,6 +707,10 @@ pub fn do_recvmsg(fd: c_int, msg_mut_ptr: *mut msghdr_mut, flags_c: c_int) -> Re
         socket
             .recvmsg(&mut msg_hdr_mut, flags)
             .map(|bytes_recvd| bytes_recvd as isize)
+    } else if let Ok(socket) = file_ref.as_netlink_socket() {
+        socket
+            .recvmsg(&mut msg_hdr_mut, flags)
+            .map(|bytes_recvd| bytes_recvd as isize)
     } else {
         return_errno!(ENOTSOCK, "not a socket")
     }
@@ -554,51 +723,127 @@ pub fn do_sendmmsg(
     flags_c: c_int,
 ) -> Result<isize> {
     debug!(
-        "sendmmsg: fd: {}, msg: {:?}, flags: 0x{:x}",
-        fd, msgvec_ptr, flags_c
+        "sendmmsg: fd: {}, msg: {:?}, vlen: {}, flags: 0x{:x}",
+        fd, msgvec_ptr, vlen, flags_c
     );
 
-    from_user::check_ptr(msgvec_ptr)?;
+    // Same as Linux, the number of messages is capped silently
+    let vlen = vlen.min(UIO_MAXIOV);
+    from_user::check_mut_array(msgvec_ptr, vlen as usize)?;
+    let msgvec = unsafe { std::slice::from_raw_parts_mut(msgvec_ptr, vlen as usize) };
+
+    // Send the messages until the first invalid one, if any
+    let mut msgs = Vec::with_capacity(msgvec.len());
+    for mmsg in msgvec.iter() {
+        let msg = match mmsg.msg_hdr.check_member_ptrs() {
+            Ok(()) => unsafe { MsgHdr::from_c(&mmsg.msg_hdr) },
+            Err(e) => Err(e),
+        };
+        match msg {
+            Ok(msg) => msgs.push(msg),
+            Err(e) if msgs.is_empty() => return Err(e),
+            Err(_) => break,
+        }
+    }
 
-    let mut msgvec = unsafe { std::slice::from_raw_parts_mut(msgvec_ptr, vlen as usize) };
     let flags = SendFlags::from_bits_truncate(flags_c);
-    let file_ref = current!().file(fd as FileDesc)?;
 
-    if let Ok(socket) = file_ref.as_host_socket() {
-        let mut send_count = 0;
-        for mmsg in (msgvec) {
-            if !mmsg.msg_hdr.check_member_ptrs().is_ok() {
-                break;
+    let file_ref = current!().file(fd as FileDesc)?;
+    let bytes_sent = if let Ok(socket) = file_ref.as_host_socket() {
+        // Send the messages until the first denied one, if any
+        let mut num_allowed = 0;
+        for msg in msgs.iter() {
+            match check_msg_egress(socket, msg) {
+                Ok(()) => num_allowed += 1,
+                Err(e) if num_allowed == 0 => return Err(e),
+                Err(_) => break,
             }
+        }
+        msgs.truncate(num_allowed);
+        socket.sendmmsg(&msgs, flags)?
+    } else if let Ok(socket) = file_ref.as_unix_socket() {
+        sendmmsg_one_by_one(&msgs, flags, |msg, flags| socket.sendmsg(msg, flags))?
+    } else if let Ok(socket) = file_ref.as_netlink_socket() {
+        sendmmsg_one_by_one(&msgs, flags, |msg, flags| socket.sendmsg(msg, flags))?
+    } else {
+        return_errno!(ENOTSOCK, "not a socket")
+    };
 
-            let msg = unsafe {
-                if let Ok(msg) = MsgHdr::from_c({ &mmsg.msg_hdr }) {
-                    msg
-                } else {
-                    break;
-                }
-            };
+    for (mmsg, bytes_sent) in msgvec.iter_mut().zip(bytes_sent.iter()) {
+        mmsg.msg_len = *bytes_sent as c_uint;
+    }
+    Ok(bytes_sent.len() as isize)
+}
 
-            if socket
-                .sendmsg(&msg, flags)
-                .map(|bytes_sent| {
-                    mmsg.msg_len = bytes_sent as u32;
-                    mmsg.msg_len
-                })
-                .is_ok()
-            {
-                send_count += 1;
-            } else {
-                break;
+pub fn do_recvmmsg(
+    fd: c_int,
+    msgvec_ptr: *mut mmsghdr_mut,
+    vlen: c_uint,
+    flags_c: c_int,
+    timeout_ptr: *mut timespec_t,
+) -> Result<isize> {
+    debug!(
+        "recvmmsg: fd: {}, msg: {:?}, vlen: {}, flags: 0x{:x}, timeout: {:?}",
+        fd, msgvec_ptr, vlen, flags_c, timeout_ptr
+    );
+
+    let mut timeout = if timeout_ptr.is_null() {
+        None
+    } else {
+        from_user::check_mut_ptr(timeout_ptr)?;
+        Some(timespec_t::from_raw_ptr(timeout_ptr)?.as_duration())
+    };
+
+    // Same as Linux, the number of messages is capped silently
+    let vlen = vlen.min(UIO_MAXIOV);
+    from_user::check_mut_array(msgvec_ptr, vlen as usize)?;
+    let msgvec = unsafe { std::slice::from_raw_parts_mut(msgvec_ptr, vlen as usize) };
+
+    // The headers are wrapped apart from the lengths to be set
+    let mut msgs = Vec::with_capacity(msgvec.len());
+    let mut msg_lens = Vec::with_capacity(msgvec.len());
+    for mmsg in msgvec.iter_mut() {
+        let mmsghdr_mut { msg_hdr, msg_len } = mmsg;
+        let msg = match msg_hdr.check_member_ptrs() {
+            Ok(()) => unsafe { MsgHdrMut::from_c(msg_hdr) },
+            Err(e) => Err(e),
+        };
+        match msg {
+            Ok(msg) => {
+                msgs.push(msg);
+                msg_lens.push(msg_len);
             }
+            Err(e) if msgs.is_empty() => return Err(e),
+            Err(_) => break,
         }
+    }
+
+    let flags = RecvFlags::from_bits_truncate(flags_c);
 
-        Ok(send_count as isize)
+    let file_ref = current!().file(fd as FileDesc)?;
+    let bytes_recvd = if let Ok(socket) = file_ref.as_host_socket() {
+        socket.recvmmsg(&mut msgs, flags, timeout.as_mut())?
     } else if let Ok(socket) = file_ref.as_unix_socket() {
-        return_errno!(EOPNOTSUPP, "does not support unix socket")
+        recvmmsg_one_by_one(&mut msgs, flags, timeout.as_mut(), |msg, flags| {
+            socket.recvmsg(msg, flags)
+        })?
+    } else if let Ok(socket) = file_ref.as_netlink_socket() {
+        recvmmsg_one_by_one(&mut msgs, flags, timeout.as_mut(), |msg, flags| {
+            socket.recvmsg(msg, flags)
+        })?
     } else {
         return_errno!(ENOTSOCK, "not a socket")
+    };
+
+    for (msg_len, bytes_recvd) in msg_lens.into_iter().zip(bytes_recvd.iter()) {
+        *msg_len = *bytes_recvd as c_uint;
+    }
+    if let Some(timeout) = timeout {
+        unsafe {
+            *timeout_ptr = timeout.into();
+        }
     }
+    Ok(bytes_recvd.len() as isize)
 }
 
 #[allow(non_camel_case_types)]
@@ -779,7 +1024,7 @@ fn do_poll_common(
         .map(|raw| PollFd::from_raw(raw))
         .collect();
 
-    let count = io_multiplexing::do_poll_new(&poll_fds, timeout)?;
+    let count = io_multiplexing::do_poll(&poll_fds, timeout)?;
 
     for (raw_poll_fd, poll_fd) in raw_poll_fds.iter_mut().zip(poll_fds.iter()) {
         raw_poll_fd.revents = poll_fd.revents().get().to_raw() as i16;
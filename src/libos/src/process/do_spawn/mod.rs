@@ -298,6 +298,11 @@ fn new_process_common(
             process_builder = process_builder.tid(reuse_tid);
         }
 
+        // The umask and the controlling terminal are inherited from the current
+        // process, which is replaced by the new one if it calls execve
+        let umask = process_ref.umask();
+        let ctty = process_ref.ctty();
+
         // Use specified parent process if any
         let parent = if let Some(parent) = parent_process {
             parent
@@ -308,7 +313,9 @@ fn new_process_common(
         let new_process = process_builder
             .vm(vm_ref)
             .exec_path(&elf_path)
-            .umask(parent.umask())
+            .environ(envp)
+            .umask(umask)
+            .ctty(ctty)
             .parent(parent)
             .task(task)
             .sched(sched_ref)
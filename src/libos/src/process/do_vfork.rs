@@ -52,7 +52,7 @@ pub fn do_vfork(mut context: *mut CpuContext) -> Result<isize> {
 
     // Generate a new pid for child process
     let child_pid = {
-        let new_tid = ThreadId::new();
+        let new_tid = ThreadId::new()?;
         new_tid.as_u32() as pid_t
     };
 
@@ -6,9 +6,10 @@ use super::super::{
     ProcessVMRef, ResourceLimitsRef, SchedAgentRef,
 };
 use super::{Process, ProcessInner};
-use crate::fs::FileMode;
+use crate::fs::{FileMode, Pty};
 use crate::prelude::*;
 use crate::signal::{SigDispositions, SigQueues, SigSet};
+use std::ffi::CString;
 
 #[derive(Debug)]
 pub struct ProcessBuilder {
@@ -19,7 +20,9 @@ pub struct ProcessBuilder {
     pgrp: Option<ProcessGrpRef>,
     // Optional fields, which have reasonable default values
     exec_path: Option<String>,
+    environ: Option<Vec<CString>>,
     umask: Option<FileMode>,
+    ctty: Option<Arc<Pty>>,
     parent: Option<ProcessRef>,
     no_parent: bool,
     sig_dispositions: Option<SigDispositions>,
@@ -34,7 +37,9 @@ impl ProcessBuilder {
             vm: None,
             pgrp: None,
             exec_path: None,
+            environ: None,
             umask: None,
+            ctty: None,
             parent: None,
             no_parent: false,
             sig_dispositions: None,
@@ -51,11 +56,21 @@ impl ProcessBuilder {
         self
     }
 
+    pub fn environ(mut self, environ: &[CString]) -> Self {
+        self.environ = Some(environ.to_vec());
+        self
+    }
+
     pub fn umask(mut self, umask: FileMode) -> Self {
         self.umask = Some(umask);
         self
     }
 
+    pub fn ctty(mut self, ctty: Option<Arc<Pty>>) -> Self {
+        self.ctty = ctty;
+        self
+    }
+
     pub fn parent(mut self, parent: ProcessRef) -> Self {
         self.parent = Some(parent);
         self
@@ -114,7 +129,10 @@ impl ProcessBuilder {
 
     pub fn build(mut self) -> Result<ProcessRef> {
         // Process's pid == Main thread's tid
-        let tid = self.tid.take().unwrap_or_else(|| ThreadId::new());
+        let tid = match self.tid.take() {
+            Some(tid) => tid,
+            None => ThreadId::new()?,
+        };
         let pid = tid.as_u32() as pid_t;
 
         // Check whether parent is given as expected
@@ -128,7 +146,9 @@ impl ProcessBuilder {
         // Build a new process
         let new_process = {
             let exec_path = self.exec_path.take().unwrap_or_default();
+            let environ = self.environ.take().unwrap_or_default();
             let umask = RwLock::new(self.umask.unwrap_or(FileMode::default_umask()));
+            let ctty = RwLock::new(self.ctty.take());
             let parent = self.parent.take().map(|parent| RwLock::new(parent));
             let pgrp = RwLock::new(self.pgrp.clone());
             let inner = SgxMutex::new(ProcessInner::new());
@@ -139,8 +159,10 @@ impl ProcessBuilder {
             Arc::new(Process {
                 pid,
                 exec_path,
+                environ,
                 start_time,
                 umask,
+                ctty,
                 parent,
                 pgrp,
                 inner,
@@ -1,9 +1,10 @@
+use std::ffi::CString;
 use std::fmt;
 use std::time::Duration;
 
 use super::wait::WaitQueue;
 use super::{ForcedExitStatus, ProcessGrpRef, ProcessRef, TermStatus, ThreadRef};
-use crate::fs::FileMode;
+use crate::fs::{FileMode, Pty};
 use crate::prelude::*;
 use crate::signal::{SigDispositions, SigNum, SigQueues};
 
@@ -17,12 +18,14 @@ pub struct Process {
     // Immutable info
     pid: pid_t,
     exec_path: String,
+    environ: Vec<CString>,
     start_time: Duration,
     // Mutable info
     parent: Option<RwLock<ProcessRef>>,
     pgrp: RwLock<Option<ProcessGrpRef>>,
     inner: SgxMutex<ProcessInner>,
     umask: RwLock<FileMode>,
+    ctty: RwLock<Option<Arc<Pty>>>,
     // Signal
     sig_dispositions: RwLock<SigDispositions>,
     sig_queues: RwLock<SigQueues>,
@@ -126,6 +129,11 @@ impl Process {
         &self.exec_path
     }
 
+    /// Get the environment variables given when the process is created
+    pub fn environ(&self) -> &[CString] {
+        &self.environ
+    }
+
     /// Get the time the process started after system boot
     ///
     /// The value is expressed in clock ticks
@@ -146,6 +154,16 @@ impl Process {
         old_mask
     }
 
+    /// Get the controlling terminal
+    pub fn ctty(&self) -> Option<Arc<Pty>> {
+        self.ctty.read().unwrap().clone()
+    }
+
+    /// Set the controlling terminal
+    pub fn set_ctty(&self, ctty: Option<Arc<Pty>>) {
+        *self.ctty.write().unwrap() = ctty;
+    }
+
     /// Get the signal queues for process-directed signals.
     pub fn sig_queues(&self) -> &RwLock<SigQueues> {
         &self.sig_queues
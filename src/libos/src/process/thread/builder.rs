@@ -117,7 +117,10 @@ impl ThreadBuilder {
         let task = self
             .task
             .ok_or_else(|| errno!(EINVAL, "task is mandatory"))?;
-        let tid = self.tid.unwrap_or_else(|| ThreadId::new());
+        let tid = match self.tid {
+            Some(tid) => tid,
+            None => ThreadId::new()?,
+        };
         let clear_ctid = RwLock::new(self.clear_ctid);
         let robust_list = RwLock::new(self.robust_list);
         let inner = SgxMutex::new(ThreadInner::new());
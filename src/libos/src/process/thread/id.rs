@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 use crate::prelude::*;
 
@@ -15,10 +16,11 @@ impl ThreadId {
     /// Create a new thread ID.
     ///
     /// The thread ID returned is guaranteed to have a value greater than zero.
-    pub fn new() -> ThreadId {
+    /// Fails with EAGAIN if all the IDs below pid_max are in use.
+    pub fn new() -> Result<ThreadId> {
         let mut alloc = THREAD_ID_ALLOC.lock().unwrap();
-        let tid = alloc.alloc();
-        Self { tid }
+        let tid = alloc.alloc()?;
+        Ok(Self { tid })
     }
 
     /// Create a "zero" thread ID.
@@ -49,10 +51,43 @@ lazy_static! {
     static ref THREAD_ID_ALLOC: SgxMutex<IdAlloc> = SgxMutex::new(IdAlloc::new());
 }
 
+// The range of the max PID/TID, same with Linux on 64-bit platforms
+const PID_MAX_MIN: u32 = 301;
+const PID_MAX_LIMIT: u32 = 4 * 1024 * 1024;
+
+// The IDs wrapped around only at u32::MAX before pid_max was tunable, so the
+// default is the largest value allowed to keep the IDs from being reused
+// early. It is also the default set by systemd on 64-bit Linux.
+static PID_MAX: AtomicU32 = AtomicU32::new(PID_MAX_LIMIT);
+
+/// Get the value at which the PID/TID wraps around, which is shown in /proc/sys/kernel/pid_max.
+pub fn pid_max() -> u32 {
+    PID_MAX.load(Ordering::Relaxed)
+}
+
+/// Set the value at which the PID/TID wraps around.
+///
+/// The value must be greater than all the IDs in use.
+pub fn set_pid_max(pid_max: u32) -> Result<()> {
+    if pid_max < PID_MAX_MIN || pid_max > PID_MAX_LIMIT {
+        return_errno!(EINVAL, "invalid pid_max");
+    }
+    // Hold the lock so that no greater ID is allocated meanwhile
+    let alloc = THREAD_ID_ALLOC.lock().unwrap();
+    if alloc
+        .max_used_id()
+        .map_or(false, |max_id| max_id >= pid_max)
+    {
+        return_errno!(EINVAL, "pid_max must be greater than the IDs in use");
+    }
+    PID_MAX.store(pid_max, Ordering::Relaxed);
+    Ok(())
+}
+
 /// PID/TID allocator.
 ///
 /// The allocation strategy is to start from the minimal value (here, 1) and increments
-/// each returned ID, until a maximum value (e.g., 2^32-1) is reached. After that, recycle
+/// each returned ID, until a maximum value (i.e., pid_max - 1) is reached. After that, recycle
 /// from the minimal value and see if it is still in use. If not, use the value; otherwise,
 /// increments again.
 ///
@@ -73,20 +108,26 @@ impl IdAlloc {
         }
     }
 
-    pub fn alloc(&mut self) -> u32 {
-        let new_id = loop {
+    pub fn alloc(&mut self) -> Result<u32> {
+        // Scan at most one full cycle of the IDs in [1, pid_max)
+        let pid_max = pid_max();
+        for _ in 1..pid_max {
             // Increments the ID and wrap around if necessary
             self.next_id = self.next_id.wrapping_add(1);
-            if self.next_id == 0 {
+            if self.next_id == 0 || self.next_id >= pid_max {
                 self.next_id = 1;
             }
 
             if !self.used_ids.contains(&self.next_id) {
-                break self.next_id;
+                self.used_ids.insert(self.next_id);
+                return Ok(self.next_id);
             }
-        };
-        self.used_ids.insert(new_id);
-        new_id
+        }
+        return_errno!(EAGAIN, "no free thread ID is available");
+    }
+
+    pub fn max_used_id(&self) -> Option<u32> {
+        self.used_ids.iter().max().copied()
     }
 
     pub fn free(&mut self, id: u32) -> Option<u32> {
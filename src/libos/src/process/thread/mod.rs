@@ -8,15 +8,13 @@ use super::{
     ResourceLimitsRef, RobustListHead, SchedAgentRef, TermStatus, ThreadRef,
 };
 use crate::events::HostEventFd;
-use crate::fs::{EventCreationFlags, EventFile};
-use crate::net::THREAD_NOTIFIERS;
 use crate::prelude::*;
 use crate::signal::{SigQueues, SigSet, SigStack};
 use crate::time::ThreadProfiler;
 use crate::untrusted::{UntrustedSliceAlloc, UntrustedSliceAllocGuard};
 
 pub use self::builder::ThreadBuilder;
-pub use self::id::ThreadId;
+pub use self::id::{pid_max, set_pid_max, ThreadId};
 pub use self::name::ThreadName;
 
 mod builder;
@@ -240,19 +238,6 @@ impl Thread {
             self.inner().start();
         }
 
-        let eventfd = EventFile::new(
-            0,
-            EventCreationFlags::EFD_CLOEXEC | EventCreationFlags::EFD_NONBLOCK,
-        )
-        .unwrap();
-
-        let event_file = THREAD_NOTIFIERS.lock().unwrap().insert(self.tid(), eventfd);
-
-        assert!(
-            event_file.is_none(),
-            "this thread should not have an eventfd before start"
-        );
-
         #[cfg(feature = "syscall_timing")]
         self.profiler()
             .lock()
@@ -273,12 +258,6 @@ impl Thread {
             .stop()
             .unwrap();
 
-        THREAD_NOTIFIERS
-            .lock()
-            .unwrap()
-            .remove(&self.tid())
-            .unwrap();
-
         self.sched().lock().unwrap().detach();
 
         // Remove this thread from its owner process
@@ -8,7 +8,7 @@ mod priority;
 mod sched_agent;
 mod syscalls;
 
-pub use cpu_set::NCORES;
+pub use cpu_set::{CpuSet, AVAIL_CPUSET, NCORES};
 pub use priority::NiceValue;
 pub use sched_agent::SchedAgent;
 pub use syscalls::*;
@@ -26,16 +26,17 @@ use crate::config::user_rootfs_config;
 use crate::exception::do_handle_exception;
 use crate::fs::{
     do_access, do_chdir, do_chmod, do_chown, do_close, do_creat, do_dup, do_dup2, do_dup3,
-    do_eventfd, do_eventfd2, do_faccessat, do_fallocate, do_fchdir, do_fchmod, do_fchmodat,
-    do_fchown, do_fchownat, do_fcntl, do_fdatasync, do_flock, do_fstat, do_fstatat, do_fstatfs,
-    do_fsync, do_ftruncate, do_futimesat, do_getcwd, do_getdents, do_getdents64, do_ioctl,
-    do_lchown, do_link, do_linkat, do_lseek, do_lstat, do_mkdir, do_mkdirat, do_mount,
-    do_mount_rootfs, do_open, do_openat, do_pipe, do_pipe2, do_pread, do_preadv, do_pwrite,
-    do_pwritev, do_read, do_readlink, do_readlinkat, do_readv, do_rename, do_renameat, do_rmdir,
-    do_sendfile, do_stat, do_statfs, do_symlink, do_symlinkat, do_sync, do_timerfd_create,
-    do_timerfd_gettime, do_timerfd_settime, do_truncate, do_umask, do_umount, do_unlink,
-    do_unlinkat, do_utime, do_utimensat, do_utimes, do_write, do_writev, iovec_t, utimbuf_t,
-    AsTimer, File, FileDesc, FileRef, HostStdioFds, Stat, Statfs,
+    do_eventfd, do_eventfd2, do_faccessat, do_fadvise64, do_fallocate, do_fchdir, do_fchmod,
+    do_fchmodat, do_fchown, do_fchownat, do_fcntl, do_fdatasync, do_flock, do_fstat, do_fstatat,
+    do_fstatfs, do_fsync, do_ftruncate, do_futimesat, do_getcwd, do_getdents, do_getdents64,
+    do_ioctl, do_lchown, do_link, do_linkat, do_lseek, do_lstat, do_mkdir, do_mkdirat, do_mknod,
+    do_mknodat, do_mount, do_mount_rootfs, do_open, do_openat, do_pipe, do_pipe2, do_pread,
+    do_preadv, do_pwrite, do_pwritev, do_read, do_readahead, do_readlink, do_readlinkat, do_readv,
+    do_rename, do_renameat, do_rmdir, do_sendfile, do_stat, do_statfs, do_symlink, do_symlinkat,
+    do_sync, do_sync_file_range, do_syncfs, do_timerfd_create, do_timerfd_gettime,
+    do_timerfd_settime, do_truncate, do_umask, do_umount, do_unlink, do_unlinkat, do_utime,
+    do_utimensat, do_utimes, do_write, do_writev, iovec_t, utimbuf_t, AsTimer, File, FileDesc,
+    FileRef, HostStdioFds, Stat, Statfs,
 };
 use crate::interrupt::{do_handle_interrupt, sgx_interrupt_info_t};
 use crate::ipc::{do_shmat, do_shmctl, do_shmdt, do_shmget, key_t, shmids_t};
@@ -43,8 +44,9 @@ use crate::misc::{resource_t, rlimit_t, sysinfo_t, utsname_t, RandFlags};
 use crate::net::{
     do_accept, do_accept4, do_bind, do_connect, do_epoll_create, do_epoll_create1, do_epoll_ctl,
     do_epoll_pwait, do_epoll_wait, do_getpeername, do_getsockname, do_getsockopt, do_listen,
-    do_poll, do_ppoll, do_recvfrom, do_recvmsg, do_select, do_sendmmsg, do_sendmsg, do_sendto,
-    do_setsockopt, do_shutdown, do_socket, do_socketpair, mmsghdr, msghdr, msghdr_mut,
+    do_poll, do_ppoll, do_recvfrom, do_recvmmsg, do_recvmsg, do_select, do_sendmmsg, do_sendmsg,
+    do_sendto, do_setsockopt, do_shutdown, do_socket, do_socketpair, mmsghdr, mmsghdr_mut, msghdr,
+    msghdr_mut,
 };
 use crate::process::{
     do_arch_prctl, do_clone, do_execve, do_exit, do_exit_group, do_futex, do_get_robust_list,
@@ -229,7 +231,7 @@ macro_rules! process_syscall_table_with_callback {
             (RtSigsuspend = 130) => handle_unsupported(),
             (Sigaltstack = 131) => do_sigaltstack(ss: *const stack_t, old_ss: *mut stack_t, context: *const CpuContext),
             (Utime = 132) => do_utime(path: *const i8, times: *const utimbuf_t),
-            (Mknod = 133) => handle_unsupported(),
+            (Mknod = 133) => do_mknod(path: *const i8, mode: u32, dev: u64),
             (Uselib = 134) => handle_unsupported(),
             (Personality = 135) => handle_unsupported(),
             (Ustat = 136) => handle_unsupported(),
@@ -283,7 +285,7 @@ macro_rules! process_syscall_table_with_callback {
             (Tuxcall = 184) => handle_unsupported(),
             (Security = 185) => handle_unsupported(),
             (Gettid = 186) => do_gettid(),
-            (Readahead = 187) => handle_unsupported(),
+            (Readahead = 187) => do_readahead(fd: FileDesc, offset: off_t, count: usize),
             (Setxattr = 188) => handle_unsupported(),
             (Lsetxattr = 189) => handle_unsupported(),
             (Fsetxattr = 190) => handle_unsupported(),
@@ -317,7 +319,7 @@ macro_rules! process_syscall_table_with_callback {
             (SetTidAddress = 218) => do_set_tid_address(tidptr: *mut pid_t),
             (RestartSysCall = 219) => handle_unsupported(),
             (Semtimedop = 220) => handle_unsupported(),
-            (Fadvise64 = 221) => handle_unsupported(),
+            (Fadvise64 = 221) => do_fadvise64(fd: FileDesc, offset: off_t, len: off_t, advice: i32),
             (TimerCreate = 222) => handle_unsupported(),
             (TimerSettime = 223) => handle_unsupported(),
             (TimerGettime = 224) => handle_unsupported(),
@@ -355,7 +357,7 @@ macro_rules! process_syscall_table_with_callback {
             (MigratePages = 256) => handle_unsupported(),
             (Openat = 257) => do_openat(dirfd: i32, path: *const i8, flags: u32, mode: u16),
             (Mkdirat = 258) => do_mkdirat(dirfd: i32, path: *const i8, mode: u16),
-            (Mknodat = 259) => handle_unsupported(),
+            (Mknodat = 259) => do_mknodat(dirfd: i32, path: *const i8, mode: u32, dev: u64),
             (Fchownat = 260) => do_fchownat(dirfd: i32, path: *const i8, uid: u32, gid: u32, flags: i32),
             (Futimesat = 261) => do_futimesat(dirfd: i32, path: *const i8, times: *const timeval_t),
             (Fstatat = 262) => do_fstatat(dirfd: i32, path: *const i8, stat_buf: *mut Stat, flags: u32),
@@ -373,7 +375,7 @@ macro_rules! process_syscall_table_with_callback {
             (GetRobustList = 274) => do_get_robust_list(tid: pid_t, list_head_ptr_ptr: *mut *mut RobustListHead, len_ptr: *mut usize),
             (Splice = 275) => handle_unsupported(),
             (Tee = 276) => handle_unsupported(),
-            (SyncFileRange = 277) => handle_unsupported(),
+            (SyncFileRange = 277) => do_sync_file_range(fd: FileDesc, offset: off_t, nbytes: off_t, flags: u32),
             (Vmsplice = 278) => handle_unsupported(),
             (MovePages = 279) => handle_unsupported(),
             (Utimensat = 280) => do_utimensat(dirfd: i32, path: *const i8, times: *const timespec_t, flags: i32),
@@ -395,14 +397,14 @@ macro_rules! process_syscall_table_with_callback {
             (Pwritev = 296) => do_pwritev(fd: FileDesc, iov: *const iovec_t, count: i32, offset: off_t),
             (RtTgsigqueueinfo = 297) => handle_unsupported(),
             (PerfEventOpen = 298) => handle_unsupported(),
-            (Recvmmsg = 299) => handle_unsupported(),
+            (Recvmmsg = 299) => do_recvmmsg(fd: c_int, msg_ptr: *mut mmsghdr_mut, vlen: c_uint, flags_c: c_int, timeout: *mut timespec_t),
             (FanotifyInit = 300) => handle_unsupported(),
             (FanotifyMark = 301) => handle_unsupported(),
             (Prlimit64 = 302) => do_prlimit(pid: pid_t, resource: u32, new_limit: *const rlimit_t, old_limit: *mut rlimit_t),
             (NameToHandleAt = 303) => handle_unsupported(),
             (OpenByHandleAt = 304) => handle_unsupported(),
             (ClockAdjtime = 305) => handle_unsupported(),
-            (Syncfs = 306) => handle_unsupported(),
+            (Syncfs = 306) => do_syncfs(fd: FileDesc),
             (Sendmmsg = 307) => do_sendmmsg(fd: c_int, msg_ptr: *mut mmsghdr, vlen: c_uint, flags_c: c_int),
             (Setns = 308) => handle_unsupported(),
             (Getcpu = 309) => do_getcpu(cpu_ptr: *mut u32, node_ptr: *mut u32),
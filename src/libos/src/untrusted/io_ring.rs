@@ -0,0 +1,567 @@
+//! An I/O ring shared with the host worker threads
+//!
+//! The LibOS puts the I/O requests into the ring in the untrusted memory and
+//! the worker threads started by the PAL serve them, so the requests are done
+//! without exiting the enclave. The thread waiting for a request spins on its
+//! completion first, and sleeps on its host eventfd only if the request takes
+//! long. Once a request is completed, the worker writes to the eventfd only if
+//! the thread sleeps, and then releases the entry. The entries not released
+//! yet are never reused.
+//!
+//! The layout of the ring must be kept in sync with pal_io_ring.h.
+use super::*;
+use crate::events::{HostEventFd, Waiter, WaiterQueue};
+use std::alloc::{Allocator, Layout};
+use std::hint;
+use std::mem;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+const OP_PREAD: u32 = 1;
+const OP_PWRITE: u32 = 2;
+const OP_SENDMSG: u32 = 3;
+const OP_RECVMSG: u32 = 4;
+
+// The number of times to check the completion before going to sleep, which
+// covers a request served by the workers without blocking on the host
+const SPIN_COUNT: usize = 20000;
+// How long to wait for a free entry before falling back to the OCall
+const ALLOC_TIMEOUT: Duration = Duration::from_millis(10);
+
+const MSG_DONTWAIT: i32 = 0x40;
+
+/// The max number of iovecs of a message sent or received through the ring
+pub const MAX_MSG_IOVS: usize = 8;
+/// The max length of the address of a message sent or received through the ring
+pub const MAX_MSG_NAME_LEN: usize = 128;
+
+#[repr(C)]
+struct RingHeader {
+    num_entries: u32,
+    need_wakeup: AtomicU32,
+    head: AtomicU64,
+    tail: AtomicU64,
+    reserved: u64,
+}
+
+#[repr(C)]
+struct Entry {
+    opcode: u32,
+    fd: i32,
+    flags: i32,
+    eventfd: i32,
+    addr: u64,
+    len: u64,
+    offset: i64,
+    result: i64,
+    completed: AtomicU32,
+    released: AtomicU32,
+    // Set by the thread before sleeping on the eventfd
+    waiting: AtomicU32,
+    reserved: u32,
+}
+
+#[repr(C)]
+struct RawMsgHdr {
+    msg_name: *mut c_void,
+    msg_namelen: u32,
+    msg_iov: *mut libc::iovec,
+    msg_iovlen: usize,
+    msg_control: *mut c_void,
+    msg_controllen: usize,
+    msg_flags: i32,
+}
+
+// The msghdr of SENDMSG or RECVMSG and the iovecs and the address it points
+// to. Each entry has one, which is placed after all the entries and is only
+// known to the LibOS.
+#[repr(C)]
+struct MsgArea {
+    hdr: RawMsgHdr,
+    iovs: [libc::iovec; MAX_MSG_IOVS],
+    name: [u8; MAX_MSG_NAME_LEN],
+}
+
+/// The I/O ring, which is never freed once set up as the workers keep
+/// accessing it until the enclave is destroyed.
+pub struct IoRing {
+    ptr: NonNull<u8>,
+    sq: *mut u32,
+    entries: *mut Entry,
+    msgs: *mut MsgArea,
+    num_entries: u32,
+    inner: SgxMutex<Inner>,
+    // The threads waiting for a free entry
+    free_waiters: WaiterQueue,
+}
+
+struct Inner {
+    free_entries: Vec<u32>,
+    // The entries not released by the workers when freed
+    orphans: Vec<Orphan>,
+    // The ring is untrusted, so the tail is tracked inside the enclave
+    tail: u64,
+}
+
+// A completed entry which may still be accessed by the worker, along with the
+// eventfd to write, which is kept until the entry is released
+struct Orphan {
+    idx: u32,
+    _eventfd: Arc<HostEventFd>,
+}
+
+unsafe impl Send for IoRing {}
+unsafe impl Sync for IoRing {}
+
+lazy_static! {
+    static ref IO_RING: Option<IoRing> =
+        config::LIBOS_CONFIG.async_io.as_ref().and_then(|config| {
+            match IoRing::new(config.ring_size, config.num_workers) {
+                Ok(ring) => Some(ring),
+                Err(e) => {
+                    error!("failed to set up the I/O ring: {:?}", e);
+                    None
+                }
+            }
+        });
+}
+
+impl IoRing {
+    /// Get the I/O ring, which is set up on the first use. Returns `None` if
+    /// the async I/O is not enabled in the config.
+    pub fn get() -> Option<&'static IoRing> {
+        IO_RING.as_ref()
+    }
+
+    /// Whether a message can be sent or received through the ring.
+    pub fn supports_msg(num_iovs: usize, name_len: usize) -> bool {
+        num_iovs <= MAX_MSG_IOVS && name_len <= MAX_MSG_NAME_LEN
+    }
+
+    fn new(num_entries: u32, num_workers: u32) -> Result<Self> {
+        if cfg!(feature = "hyper_mode") {
+            return_errno!(ENOSYS, "the I/O ring is not supported in hyper mode");
+        }
+        debug_assert!(num_entries.is_power_of_two() && num_entries >= 2);
+
+        let n = num_entries as usize;
+        let sq_offset = mem::size_of::<RingHeader>();
+        let entries_offset = sq_offset + n * mem::size_of::<u32>();
+        let msgs_offset = entries_offset + n * mem::size_of::<Entry>();
+        let size = msgs_offset + n * mem::size_of::<MsgArea>();
+        let layout = Layout::from_size_align(size, mem::align_of::<MsgArea>())?;
+        let ptr = UNTRUSTED_ALLOC.allocate(layout)?.cast::<u8>();
+
+        let base = ptr.as_ptr();
+        unsafe {
+            ptr::write_bytes(base, 0, size);
+            (*(base as *mut RingHeader)).num_entries = num_entries;
+        }
+        let ring = Self {
+            ptr,
+            sq: unsafe { base.add(sq_offset) } as *mut u32,
+            entries: unsafe { base.add(entries_offset) } as *mut Entry,
+            msgs: unsafe { base.add(msgs_offset) } as *mut MsgArea,
+            num_entries,
+            inner: SgxMutex::new(Inner {
+                free_entries: (0..num_entries).rev().collect(),
+                orphans: Vec::new(),
+                tail: 0,
+            }),
+            free_waiters: WaiterQueue::new(),
+        };
+
+        let mut retval: i32 = 0;
+        let status =
+            unsafe { occlum_ocall_io_ring_setup(&mut retval, base as *mut c_void, num_workers) };
+        assert!(status == sgx_status_t::SGX_SUCCESS);
+        if retval < 0 {
+            let errno = unsafe { libc::errno() };
+            unsafe { UNTRUSTED_ALLOC.deallocate(ptr, layout) };
+            return_errno!(
+                Errno::from(errno as u32),
+                "failed to start the I/O ring workers"
+            );
+        }
+        info!(
+            "the I/O ring is set up with {} entries and {} workers",
+            num_entries, num_workers
+        );
+        Ok(ring)
+    }
+
+    /// Read from a host file at the offset.
+    ///
+    /// Returns `None` if no entry gets free in time, then the caller should
+    /// read by the OCall instead. The errors are the errnos of the host.
+    ///
+    /// The waiting for the completion is not interruptible, since the request
+    /// can not be cancelled once submitted. It never takes long, as reading
+    /// a host file does not block.
+    pub fn pread(&self, fd: i32, buf: &mut [u8], offset: usize) -> Result<Option<usize>> {
+        if buf.is_empty() {
+            return Ok(Some(0));
+        }
+        let u_buf = match UntrustedBuf::new(buf.len()) {
+            Ok(u_buf) => u_buf,
+            Err(_) => return Ok(None),
+        };
+        let (len, u_buf) = match self.submit_and_wait(OP_PREAD, fd, u_buf, offset as i64)? {
+            Some(completed) => completed,
+            None => return Ok(None),
+        };
+        // The host may return a length larger than the buffer
+        let len = len.min(buf.len());
+        u_buf.copy_to(&mut buf[..len]);
+        Ok(Some(len))
+    }
+
+    /// Write to a host file at the offset.
+    ///
+    /// Same as `pread`, returns `None` if the caller should write by the
+    /// OCall instead. The result is always the one of the write done by the
+    /// host, which is never left in flight.
+    pub fn pwrite(&self, fd: i32, buf: &[u8], offset: usize) -> Result<Option<usize>> {
+        if buf.is_empty() {
+            return Ok(Some(0));
+        }
+        let mut u_buf = match UntrustedBuf::new(buf.len()) {
+            Ok(u_buf) => u_buf,
+            Err(_) => return Ok(None),
+        };
+        u_buf.copy_from(buf);
+        let completed = self.submit_and_wait(OP_PWRITE, fd, u_buf, offset as i64)?;
+        Ok(completed.map(|(len, _)| len.min(buf.len())))
+    }
+
+    /// Send a message on a host socket.
+    ///
+    /// The iovecs must point to the untrusted memory. The message is always
+    /// sent with MSG_DONTWAIT. Returns `None` if no entry gets free in time,
+    /// then the caller should send by the OCall instead.
+    pub fn sendmsg(
+        &self,
+        fd: i32,
+        iovs: &[libc::iovec],
+        name: Option<&[u8]>,
+        flags: i32,
+    ) -> Result<Option<usize>> {
+        let name_len = name.map_or(0, |name| name.len());
+        if !Self::supports_msg(iovs.len(), name_len) {
+            return_errno!(
+                EINVAL,
+                "too many iovecs or too long address for the I/O ring"
+            );
+        }
+        let prepare = |msg: &mut MsgArea| {
+            msg.iovs[..iovs.len()].copy_from_slice(iovs);
+            if let Some(name) = name {
+                msg.name[..name.len()].copy_from_slice(name);
+            }
+            msg.hdr = RawMsgHdr {
+                msg_name: if name.is_some() {
+                    msg.name.as_mut_ptr() as *mut c_void
+                } else {
+                    ptr::null_mut()
+                },
+                msg_namelen: name_len as u32,
+                msg_iov: msg.iovs.as_mut_ptr(),
+                msg_iovlen: iovs.len(),
+                msg_control: ptr::null_mut(),
+                msg_controllen: 0,
+                msg_flags: 0,
+            };
+        };
+        self.submit_and_wait_msg(OP_SENDMSG, fd, flags, &prepare, &mut |_| {})
+    }
+
+    /// Receive a message from a host socket.
+    ///
+    /// The iovecs must point to the untrusted memory. The message is always
+    /// received with MSG_DONTWAIT. Returns the length received, the length of
+    /// the address and the flags of the message, or `None` if the caller
+    /// should receive by the OCall instead.
+    pub fn recvmsg(
+        &self,
+        fd: i32,
+        iovs: &[libc::iovec],
+        name: Option<&mut [u8]>,
+        flags: i32,
+    ) -> Result<Option<(usize, usize, i32)>> {
+        let name_len = name.as_ref().map_or(0, |name| name.len());
+        if !Self::supports_msg(iovs.len(), name_len) {
+            return_errno!(
+                EINVAL,
+                "too many iovecs or too long address for the I/O ring"
+            );
+        }
+        let has_name = name.is_some();
+        let prepare = |msg: &mut MsgArea| {
+            msg.iovs[..iovs.len()].copy_from_slice(iovs);
+            msg.hdr = RawMsgHdr {
+                msg_name: if has_name {
+                    msg.name.as_mut_ptr() as *mut c_void
+                } else {
+                    ptr::null_mut()
+                },
+                msg_namelen: name_len as u32,
+                msg_iov: msg.iovs.as_mut_ptr(),
+                msg_iovlen: iovs.len(),
+                msg_control: ptr::null_mut(),
+                msg_controllen: 0,
+                msg_flags: 0,
+            };
+        };
+        let mut name_len_recvd = 0;
+        let mut msg_flags = 0;
+        let mut finish = |msg: &MsgArea| {
+            // Check the values returned from outside the enclave
+            name_len_recvd = (msg.hdr.msg_namelen as usize).min(name_len);
+            msg_flags = msg.hdr.msg_flags;
+            if let Some(name) = name.as_mut() {
+                name[..name_len_recvd].copy_from_slice(&msg.name[..name_len_recvd]);
+            }
+        };
+        let len = self.submit_and_wait_msg(OP_RECVMSG, fd, flags, &prepare, &mut finish)?;
+        Ok(len.map(|len| (len, name_len_recvd, msg_flags)))
+    }
+
+    // Wait until the request is completed even if interrupted by signals, so
+    // that the result returned is the one of the request done by the host.
+    fn submit_and_wait(
+        &self,
+        opcode: u32,
+        fd: i32,
+        u_buf: UntrustedBuf,
+        offset: i64,
+    ) -> Result<Option<(usize, UntrustedBuf)>> {
+        let idx = match self.alloc_entry() {
+            Some(idx) => idx,
+            None => return Ok(None),
+        };
+        let eventfd = current!().host_eventfd().clone();
+        let addr = u_buf.as_ptr() as u64;
+        self.submit(idx, opcode, fd, 0, addr, u_buf.len(), offset, &eventfd);
+
+        self.wait_completion(idx, &eventfd);
+        let result = self.take_result(idx);
+        self.free_entry(idx, eventfd);
+        Ok(Some((result?, u_buf)))
+    }
+
+    // Same as `submit_and_wait`, the waiting is not interruptible. It never
+    // takes long, as the host does not block on the messages sent or received
+    // with MSG_DONTWAIT.
+    fn submit_and_wait_msg(
+        &self,
+        opcode: u32,
+        fd: i32,
+        flags: i32,
+        prepare: &dyn Fn(&mut MsgArea),
+        finish: &mut dyn FnMut(&MsgArea),
+    ) -> Result<Option<usize>> {
+        let idx = match self.alloc_entry() {
+            Some(idx) => idx,
+            None => return Ok(None),
+        };
+        let eventfd = current!().host_eventfd().clone();
+        let msg = unsafe { &mut *self.msgs.add(idx as usize) };
+        prepare(msg);
+        let addr = &msg.hdr as *const RawMsgHdr as u64;
+        let flags = flags | MSG_DONTWAIT;
+        self.submit(idx, opcode, fd, flags, addr, 0, 0, &eventfd);
+
+        self.wait_completion(idx, &eventfd);
+        let result = self.take_result(idx);
+        if result.is_ok() {
+            finish(msg);
+        }
+        self.free_entry(idx, eventfd);
+        result.map(Some)
+    }
+
+    fn submit(
+        &self,
+        idx: u32,
+        opcode: u32,
+        fd: i32,
+        flags: i32,
+        addr: u64,
+        len: usize,
+        offset: i64,
+        eventfd: &HostEventFd,
+    ) {
+        let entry = unsafe { &mut *self.entries.add(idx as usize) };
+        entry.opcode = opcode;
+        entry.fd = fd;
+        entry.flags = flags;
+        entry.eventfd = eventfd.host_fd() as i32;
+        entry.addr = addr;
+        entry.len = len as u64;
+        entry.offset = offset;
+        entry.result = 0;
+        entry.completed.store(0, Ordering::Relaxed);
+        entry.released.store(0, Ordering::Relaxed);
+        entry.waiting.store(0, Ordering::Relaxed);
+
+        let header = self.header();
+        {
+            let mut inner = self.inner.lock().unwrap();
+            let mask = (self.num_entries - 1) as u64;
+            unsafe {
+                ptr::write_volatile(self.sq.add((inner.tail & mask) as usize), idx);
+            }
+            inner.tail += 1;
+            header.tail.store(inner.tail, Ordering::SeqCst);
+        }
+        // Either the sleeping workers have set need_wakeup, or they will see
+        // the new tail before going to sleep
+        if header.need_wakeup.load(Ordering::SeqCst) != 0 {
+            unsafe {
+                let status = occlum_ocall_io_ring_wakeup();
+                assert!(status == sgx_status_t::SGX_SUCCESS);
+            }
+        }
+    }
+
+    // Wait until the entry is completed, ignoring the signals.
+    //
+    // The eventfd is polled only after spinning for a while. Either the worker
+    // sees `waiting` set here and writes to the eventfd, or the completion is
+    // seen here before sleeping.
+    fn wait_completion(&self, idx: u32, eventfd: &HostEventFd) {
+        let entry = self.entry(idx);
+        let mut spin_count = 0;
+        while entry.completed.load(Ordering::Acquire) == 0 {
+            if spin_count < SPIN_COUNT {
+                spin_count += 1;
+                hint::spin_loop();
+                continue;
+            }
+            entry.waiting.store(1, Ordering::SeqCst);
+            if entry.completed.load(Ordering::SeqCst) != 0 {
+                break;
+            }
+            // Interrupted or woken up spuriously, check again
+            let _ = eventfd.poll(None);
+        }
+    }
+
+    fn take_result(&self, idx: u32) -> Result<usize> {
+        let result = unsafe { ptr::read_volatile(&self.entry(idx).result) };
+        if result < 0 {
+            let errno = (-result).min(i32::MAX as i64) as u32;
+            return_errno!(Errno::from(errno), "async I/O failed");
+        }
+        Ok(result as usize)
+    }
+
+    // Wait for a free entry for a while. Returns `None` if timed out or
+    // interrupted, then the request should be done by the OCall.
+    fn alloc_entry(&self) -> Option<u32> {
+        let waiter = Waiter::new();
+        let mut timeout = ALLOC_TIMEOUT;
+        loop {
+            self.free_waiters.reset_and_enqueue(&waiter);
+            {
+                let mut inner = self.inner.lock().unwrap();
+                self.reclaim_orphans(&mut inner);
+                if let Some(idx) = inner.free_entries.pop() {
+                    return Some(idx);
+                }
+            }
+            if waiter.wait_mut(Some(&mut timeout)).is_err() {
+                return None;
+            }
+        }
+    }
+
+    // Free the completed entry if released by the worker. Otherwise, the entry
+    // is kept as an orphan along with the eventfd that the worker may still
+    // write to, until the worker releases it.
+    fn free_entry(&self, idx: u32, eventfd: Arc<HostEventFd>) {
+        let mut inner = self.inner.lock().unwrap();
+        if self.entry(idx).released.load(Ordering::Acquire) == 0 {
+            inner.orphans.push(Orphan {
+                idx,
+                _eventfd: eventfd,
+            });
+            return;
+        }
+        inner.free_entries.push(idx);
+        drop(inner);
+        self.free_waiters.dequeue_and_wake_one();
+    }
+
+    fn reclaim_orphans(&self, inner: &mut Inner) {
+        let Inner {
+            free_entries,
+            orphans,
+            ..
+        } = inner;
+        orphans.retain(|orphan| {
+            let released = self.entry(orphan.idx).released.load(Ordering::Acquire) != 0;
+            if released {
+                free_entries.push(orphan.idx);
+            }
+            !released
+        });
+    }
+
+    fn entry(&self, idx: u32) -> &Entry {
+        unsafe { &*self.entries.add(idx as usize) }
+    }
+
+    fn header(&self) -> &RingHeader {
+        unsafe { &*(self.ptr.as_ptr() as *const RingHeader) }
+    }
+}
+
+// A buffer in the untrusted memory owned by a request
+struct UntrustedBuf {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+impl UntrustedBuf {
+    fn new(len: usize) -> Result<Self> {
+        let layout = Layout::from_size_align(len, 1)?;
+        let ptr = UNTRUSTED_ALLOC.allocate(layout)?.cast::<u8>();
+        Ok(Self { ptr, len })
+    }
+
+    fn as_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn copy_from(&mut self, src: &[u8]) {
+        assert!(src.len() <= self.len);
+        unsafe { ptr::copy_nonoverlapping(src.as_ptr(), self.as_ptr(), src.len()) };
+    }
+
+    fn copy_to(&self, dst: &mut [u8]) {
+        assert!(dst.len() <= self.len);
+        unsafe { ptr::copy_nonoverlapping(self.as_ptr(), dst.as_mut_ptr(), dst.len()) };
+    }
+}
+
+impl Drop for UntrustedBuf {
+    fn drop(&mut self) {
+        let layout = Layout::from_size_align(self.len, 1).unwrap();
+        unsafe { UNTRUSTED_ALLOC.deallocate(self.ptr, layout) };
+    }
+}
+
+extern "C" {
+    fn occlum_ocall_io_ring_setup(
+        ret: *mut c_int,
+        ring: *mut c_void,
+        num_workers: c_uint,
+    ) -> sgx_status_t;
+    fn occlum_ocall_io_ring_wakeup() -> sgx_status_t;
+}
@@ -0,0 +1,61 @@
+//! Base64 encoding of RFC 4648, in the standard and the URL-safe alphabets.
+
+const STANDARD_CHARS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE_CHARS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encode in the standard base64 with padding, e.g., for PEM.
+pub fn encode(data: &[u8]) -> String {
+    encode_with(data, STANDARD_CHARS, true)
+}
+
+/// Encode in the URL and filename safe base64 without padding.
+pub fn encode_url_safe(data: &[u8]) -> String {
+    encode_with(data, URL_SAFE_CHARS, false)
+}
+
+/// Decode the URL and filename safe base64 without padding.
+///
+/// Only the canonical encoding is accepted, so that any data has exactly one
+/// encoding.
+pub fn decode_url_safe(encoded: &str) -> Option<Vec<u8>> {
+    // A single char can not encode a full byte
+    if encoded.len() % 4 == 1 {
+        return None;
+    }
+    let mut data = Vec::with_capacity(encoded.len() * 3 / 4);
+    for chunk in encoded.as_bytes().chunks(4) {
+        let mut val = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let idx = URL_SAFE_CHARS.iter().position(|&x| x == c)? as u32;
+            val |= idx << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            data.push((val >> (16 - 8 * i)) as u8);
+        }
+    }
+    if encode_url_safe(&data) != encoded {
+        return None;
+    }
+    Some(data)
+}
+
+fn encode_with(data: &[u8], chars: &[u8; 64], pad: bool) -> String {
+    let mut encoded = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let val = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |val, (i, &b)| val | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let idx = (val >> (18 - 6 * i)) & 0x3f;
+                encoded.push(chars[idx as usize] as char);
+            } else if pad {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -12,6 +12,8 @@ pub struct host_file_buffer {
     hosts_buf_size: u32,
     pub hostname_buf: *const c_char,
     hostname_buf_size: u32,
+    pub interfaces_buf: *const c_char,
+    interfaces_buf_size: u32,
 }
 
 pub enum HostFile {
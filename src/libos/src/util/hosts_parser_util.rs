@@ -21,6 +21,10 @@ pub struct Hosts {
     pub entries: Vec<HostEntry>,
 }
 
+pub fn is_valid_hostname(hostname: &str) -> bool {
+    HOSTNAME_RE.is_match(hostname)
+}
+
 impl FromStr for HostEntry {
     type Err = error::Error;
     fn from_str(line: &str) -> Result<Self> {
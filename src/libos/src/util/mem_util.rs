@@ -172,3 +172,13 @@ pub mod from_untrusted {
         Ok(cstrings)
     }
 }
+
+/// Overwrite the secret in memory with zeros, which is never optimized away.
+pub fn zeroize(buf: &mut [u8]) {
+    for byte in buf.iter_mut() {
+        unsafe {
+            ptr::write_volatile(byte, 0);
+        }
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
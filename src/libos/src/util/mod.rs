@@ -1,5 +1,6 @@
 use super::*;
 
+pub mod base64;
 pub mod dirty;
 pub mod host_file_util;
 pub mod hosts_parser_util;
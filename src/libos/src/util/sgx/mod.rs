@@ -5,24 +5,39 @@ use super::*;
 use sgx_tse::*;
 use sgx_types::*;
 
-#[cfg(feature = "dcap")]
+#[cfg(all(feature = "dcap", not(feature = "sim_quote")))]
 mod dcap;
 mod epid;
+#[cfg(feature = "dcap")]
+mod ratls;
 mod sgx_key;
 mod sgx_report;
+mod sgx_seal;
+#[cfg(feature = "sim_quote")]
+mod sim_quote;
+
+#[cfg(all(feature = "sim_quote", not(feature = "dcap")))]
+compile_error!("the simulated quotes require the DCAP support");
 
 pub use sgx_types::{
     sgx_create_report, sgx_epid_group_id_t, sgx_quote_nonce_t, sgx_quote_sign_type_t, sgx_quote_t,
     sgx_report_data_t, sgx_self_target, sgx_spid_t, sgx_target_info_t, sgx_verify_report,
 };
 
-#[cfg(feature = "dcap")]
+#[cfg(all(feature = "dcap", not(feature = "sim_quote")))]
 pub use self::dcap::{
     QuoteGenerator as SgxDCAPQuoteGenerator, QuoteVerifier as SgxDCAPQuoteVerifier,
 };
 pub use self::epid::AttestationAgent as SgxEPIDAttestationAgent;
+#[cfg(feature = "dcap")]
+pub use self::ratls::{generate_ratls_cert, RaTlsCert};
 pub use self::sgx_key::get_key;
 pub use self::sgx_report::{create_report, get_self_target, verify_report};
+pub use self::sgx_seal::{seal_data, sealed_data_size, unseal_data};
+#[cfg(feature = "sim_quote")]
+pub use self::sim_quote::{
+    QuoteGenerator as SgxDCAPQuoteGenerator, QuoteVerifier as SgxDCAPQuoteVerifier,
+};
 
 pub fn allow_debug() -> bool {
     let self_report = create_report(None, None).expect("create a self report should never fail");
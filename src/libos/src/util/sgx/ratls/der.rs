@@ -0,0 +1,117 @@
+//! A minimal DER encoder, just enough for the RA-TLS certificates and keys.
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_UTF8_STRING: u8 = 0x0c;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_CONTEXT_SPECIFIC: u8 = 0xa0;
+
+/// Encode the value given in parts. The exact capacity is reserved and no
+/// temporary copy is made, so that no copy of a private key is left behind.
+fn tlv(tag: u8, value_parts: &[&[u8]]) -> Vec<u8> {
+    let len: usize = value_parts.iter().map(|part| part.len()).sum();
+    let len_bytes = (len as u64).to_be_bytes();
+    let skip = len_bytes.iter().take_while(|&&byte| byte == 0).count();
+
+    let mut encoded = Vec::with_capacity(2 + len_bytes.len() - skip + len);
+    encoded.push(tag);
+    if len < 0x80 {
+        encoded.push(len as u8);
+    } else {
+        encoded.push(0x80 | (len_bytes.len() - skip) as u8);
+        encoded.extend_from_slice(&len_bytes[skip..]);
+    }
+    for part in value_parts {
+        encoded.extend_from_slice(part);
+    }
+    encoded
+}
+
+pub fn sequence(items: &[&[u8]]) -> Vec<u8> {
+    tlv(TAG_SEQUENCE, items)
+}
+
+pub fn set(items: &[&[u8]]) -> Vec<u8> {
+    tlv(TAG_SET, items)
+}
+
+/// An explicitly tagged value, i.e., `[tag_num] EXPLICIT`
+pub fn explicit(tag_num: u8, value: &[u8]) -> Vec<u8> {
+    tlv(TAG_CONTEXT_SPECIFIC | tag_num, &[value])
+}
+
+/// An OID, given the encoded content
+pub fn oid(encoded_oid: &[u8]) -> Vec<u8> {
+    tlv(TAG_OID, &[encoded_oid])
+}
+
+/// A non-negative integer, given the big-endian bytes
+pub fn integer(be_bytes: &[u8]) -> Vec<u8> {
+    let skip = be_bytes.iter().take_while(|&&byte| byte == 0).count();
+    let bytes = &be_bytes[skip.min(be_bytes.len().saturating_sub(1))..];
+    if bytes.is_empty() || bytes[0] & 0x80 != 0 {
+        tlv(TAG_INTEGER, &[&[0], bytes])
+    } else {
+        tlv(TAG_INTEGER, &[bytes])
+    }
+}
+
+pub fn bit_string(bytes: &[u8]) -> Vec<u8> {
+    // No unused bits
+    tlv(TAG_BIT_STRING, &[&[0], bytes])
+}
+
+pub fn octet_string(bytes: &[u8]) -> Vec<u8> {
+    tlv(TAG_OCTET_STRING, &[bytes])
+}
+
+pub fn utf8_string(s: &str) -> Vec<u8> {
+    tlv(TAG_UTF8_STRING, &[s.as_bytes()])
+}
+
+/// The time in UTCTime before 2050, or GeneralizedTime since then, as RFC 5280
+pub fn time(unix_secs: u64) -> Vec<u8> {
+    let (year, month, day) = civil_from_days(unix_secs / 86400);
+    let secs_of_day = unix_secs % 86400;
+    let (hour, minute, second) = (secs_of_day / 3600, secs_of_day / 60 % 60, secs_of_day % 60);
+    if year < 2050 {
+        let time_str = format!(
+            "{:02}{:02}{:02}{:02}{:02}{:02}Z",
+            year % 100,
+            month,
+            day,
+            hour,
+            minute,
+            second
+        );
+        tlv(TAG_UTC_TIME, &[time_str.as_bytes()])
+    } else {
+        let time_str = format!(
+            "{:04}{:02}{:02}{:02}{:02}{:02}Z",
+            year, month, day, hour, minute, second
+        );
+        tlv(TAG_GENERALIZED_TIME, &[time_str.as_bytes()])
+    }
+}
+
+/// Convert the days since 1970-01-01 to the date in the Gregorian calendar
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    // The days since 0000-03-01, whose era (400 years) starts after a leap day
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // The month starting from March
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
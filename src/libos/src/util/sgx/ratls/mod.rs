@@ -0,0 +1,173 @@
+//! RA-TLS certificates generated inside the LibOS.
+//!
+//! An RA-TLS certificate is a self-signed X.509 certificate of an ephemeral
+//! ECDSA P-256 key, which carries the DCAP quote of the enclave in the
+//! extension of `RATLS_QUOTE_OID`. The first 32 bytes of the report data in
+//! the quote are SHA256 of the DER-encoded public key (SubjectPublicKeyInfo),
+//! which binds the key to the enclave. This is the same as the RA-TLS
+//! certificates of grpc_ratls, so they can be verified in the same way.
+
+use super::*;
+
+use crate::util::base64;
+use crate::util::mem_util::zeroize;
+use sgx_tcrypto::{rsgx_sha256_slice, SgxEccHandle};
+
+mod der;
+
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+const OID_PRIME256V1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const OID_ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+const OID_ORGANIZATION_NAME: &[u8] = &[0x55, 0x04, 0x0a];
+// 1.2.840.113741.1, the same as grpc_ratls
+const RATLS_QUOTE_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x01];
+
+const RATLS_ORGANIZATION_NAME: &str = "Occlum";
+const RATLS_COMMON_NAME: &str = "Occlum RA-TLS";
+// The certificate is valid for 20 years, the same as grpc_ratls
+const RATLS_CERT_VALIDITY_SECS: u64 = 630720000;
+
+/// An RA-TLS certificate and its private key, both in PEM
+pub struct RaTlsCert {
+    pub cert_pem: String,
+    pub key_pem: String,
+}
+
+/// Generate an RA-TLS certificate with a new key pair and a DCAP quote.
+pub fn generate_ratls_cert(quote_generator: &SgxDCAPQuoteGenerator) -> Result<RaTlsCert> {
+    let ecc_handle = SgxEccHandle::new();
+    ecc_handle
+        .open()
+        .map_err(|_| errno!(EINVAL, "failed to open the ECC context"))?;
+    let (private_key, public_key) = ecc_handle
+        .create_key_pair()
+        .map_err(|_| errno!(EINVAL, "failed to create the key pair"))?;
+    let private_key = PrivateKey(private_key);
+
+    // The uncompressed point of the public key in big-endian
+    let mut public_point = vec![0x04];
+    public_point.extend(public_key.gx.iter().rev());
+    public_point.extend(public_key.gy.iter().rev());
+    let key_algorithm = der::sequence(&[&der::oid(OID_EC_PUBLIC_KEY), &der::oid(OID_PRIME256V1)]);
+    let subject_public_key_info = der::sequence(&[&key_algorithm, &der::bit_string(&public_point)]);
+
+    let mut report_data = sgx_report_data_t::default();
+    let key_hash = rsgx_sha256_slice(&subject_public_key_info)
+        .map_err(|_| errno!(EINVAL, "failed to calculate SHA256"))?;
+    report_data.d[..key_hash.len()].copy_from_slice(&key_hash);
+    let quote = quote_generator.generate_quote(&report_data)?;
+
+    let mut serial_number = [0u8; 16];
+    crate::misc::get_random(&mut serial_number)?;
+    // The serial number must be positive
+    serial_number[0] = serial_number[0] & 0x7f | 0x01;
+    let signature_algorithm = der::sequence(&[&der::oid(OID_ECDSA_WITH_SHA256)]);
+    let name = der::sequence(&[
+        &der::set(&[&der::sequence(&[
+            &der::oid(OID_ORGANIZATION_NAME),
+            &der::utf8_string(RATLS_ORGANIZATION_NAME),
+        ])]),
+        &der::set(&[&der::sequence(&[
+            &der::oid(OID_COMMON_NAME),
+            &der::utf8_string(RATLS_COMMON_NAME),
+        ])]),
+    ]);
+    let now = time::do_gettimeofday().as_duration().as_secs();
+    let validity = der::sequence(&[&der::time(now), &der::time(now + RATLS_CERT_VALIDITY_SECS)]);
+    let extensions = der::explicit(
+        3,
+        &der::sequence(&[&der::sequence(&[
+            &der::oid(RATLS_QUOTE_OID),
+            &der::octet_string(&quote),
+        ])]),
+    );
+    let tbs_certificate = der::sequence(&[
+        // Version 3
+        &der::explicit(0, &der::integer(&[2])),
+        &der::integer(&serial_number),
+        &signature_algorithm,
+        &name,
+        &validity,
+        &name,
+        &subject_public_key_info,
+        &extensions,
+    ]);
+
+    let signature = ecc_handle
+        .ecdsa_sign_slice(&tbs_certificate, &private_key.0)
+        .map_err(|_| errno!(EINVAL, "failed to sign the certificate"))?;
+    let _ = ecc_handle.close();
+    let (r, s) = (to_be_bytes(&signature.x), to_be_bytes(&signature.y));
+    let signature_value = der::sequence(&[&der::integer(&r), &der::integer(&s)]);
+    let certificate = der::sequence(&[
+        &tbs_certificate,
+        &signature_algorithm,
+        &der::bit_string(&signature_value),
+    ]);
+
+    // The private key in PKCS #8, wrapping the one in SEC 1
+    let private_key_be = SecretBuf(private_key.0.r.iter().rev().cloned().collect());
+    let private_key_octets = SecretBuf(der::octet_string(&private_key_be.0));
+    let ec_private_key = SecretBuf(der::sequence(&[
+        &der::integer(&[1]),
+        &private_key_octets.0,
+        &der::explicit(1, &der::bit_string(&public_point)),
+    ]));
+    let ec_private_key_octets = SecretBuf(der::octet_string(&ec_private_key.0));
+    let private_key_info = SecretBuf(der::sequence(&[
+        &der::integer(&[0]),
+        &key_algorithm,
+        &ec_private_key_octets.0,
+    ]));
+
+    Ok(RaTlsCert {
+        cert_pem: to_pem("CERTIFICATE", &certificate),
+        key_pem: to_pem("PRIVATE KEY", &private_key_info.0),
+    })
+}
+
+/// Convert the little-endian words of SGX crypto library to big-endian bytes
+fn to_be_bytes(words: &[u32; 8]) -> Vec<u8> {
+    words
+        .iter()
+        .rev()
+        .flat_map(|word| word.to_be_bytes())
+        .collect()
+}
+
+/// Encode in PEM. The capacity is reserved up front, so that no copy of the
+/// private key is left behind by reallocations.
+fn to_pem(label: &str, der: &[u8]) -> String {
+    let begin = format!("-----BEGIN {}-----\n", label);
+    let end = format!("-----END {}-----\n", label);
+    let encoded = SecretBuf(base64::encode(der).into_bytes());
+    let num_lines = (encoded.0.len() + 63) / 64;
+
+    let mut pem = String::with_capacity(begin.len() + encoded.0.len() + num_lines + end.len());
+    pem.push_str(&begin);
+    for line in encoded.0.chunks(64) {
+        pem.push_str(std::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str(&end);
+    pem
+}
+
+/// The private key, which is zeroized when dropped.
+struct PrivateKey(sgx_ec256_private_t);
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        zeroize(&mut self.0.r);
+    }
+}
+
+/// A buffer holding (a part of) the private key, which is zeroized when dropped.
+struct SecretBuf(Vec<u8>);
+
+impl Drop for SecretBuf {
+    fn drop(&mut self) {
+        zeroize(&mut self.0);
+    }
+}
@@ -0,0 +1,114 @@
+use super::*;
+
+use std::ptr;
+
+/// Get the size of the `sgx_sealed_data_t` blob sealing the given data.
+pub fn sealed_data_size(plaintext_len: usize, additional_mac_text_len: usize) -> Result<usize> {
+    let plaintext_len =
+        u32::try_from(plaintext_len).map_err(|_| errno!(EINVAL, "too much data to seal"))?;
+    let additional_mac_text_len = u32::try_from(additional_mac_text_len)
+        .map_err(|_| errno!(EINVAL, "too much additional MAC text"))?;
+    let size = unsafe { sgx_calc_sealed_data_size(additional_mac_text_len, plaintext_len) };
+    if size == u32::MAX {
+        return_errno!(EINVAL, "too much data to seal");
+    }
+    Ok(size as usize)
+}
+
+/// Seal the data into a `sgx_sealed_data_t` blob.
+///
+/// The sealing key is derived by the key policy, i.e., MRENCLAVE or MRSIGNER,
+/// and bound to the current ISVSVN and CPUSVN, which are recorded in the blob.
+/// The additional MAC text is integrity protected, but not encrypted.
+pub fn seal_data(key_policy: u16, plaintext: &[u8], additional_mac_text: &[u8]) -> Result<Vec<u8>> {
+    if key_policy != SGX_KEYPOLICY_MRENCLAVE && key_policy != SGX_KEYPOLICY_MRSIGNER {
+        return_errno!(
+            EINVAL,
+            "the key policy must be either MRENCLAVE or MRSIGNER"
+        );
+    }
+    if plaintext.is_empty() {
+        return_errno!(EINVAL, "no data to seal");
+    }
+    let sealed_data_size = sealed_data_size(plaintext.len(), additional_mac_text.len())?;
+    let mut sealed_data = vec![0; sealed_data_size];
+    let attribute_mask = sgx_attributes_t {
+        flags: TSEAL_DEFAULT_FLAGSMASK,
+        xfrm: 0,
+    };
+    let sgx_status = unsafe {
+        sgx_seal_data_ex(
+            key_policy,
+            attribute_mask,
+            TSEAL_DEFAULT_MISCMASK,
+            additional_mac_text.len() as u32,
+            if additional_mac_text.is_empty() {
+                ptr::null()
+            } else {
+                additional_mac_text.as_ptr()
+            },
+            plaintext.len() as u32,
+            plaintext.as_ptr(),
+            sealed_data_size as u32,
+            sealed_data.as_mut_ptr() as *mut sgx_sealed_data_t,
+        )
+    };
+    match sgx_status {
+        sgx_status_t::SGX_SUCCESS => Ok(sealed_data),
+        sgx_status_t::SGX_ERROR_INVALID_PARAMETER => return_errno!(EINVAL, "invalid parameters"),
+        sgx_status_t::SGX_ERROR_OUT_OF_MEMORY => return_errno!(ENOMEM, "out of memory"),
+        _ => {
+            error!("sgx_seal_data_ex return {:?}", sgx_status);
+            return_errno!(EINVAL, "unexpected SGX error")
+        }
+    }
+}
+
+/// Unseal the `sgx_sealed_data_t` blob, returning the plaintext and the
+/// additional MAC text.
+pub fn unseal_data(sealed_data: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    if sealed_data.len() < std::mem::size_of::<sgx_sealed_data_t>() {
+        return_errno!(EINVAL, "the sealed data is too short");
+    }
+    let sealed_data_ptr = sealed_data.as_ptr() as *const sgx_sealed_data_t;
+    let mut plaintext_len = unsafe { sgx_get_encrypt_txt_len(sealed_data_ptr) };
+    let mut additional_mac_text_len = unsafe { sgx_get_add_mac_txt_len(sealed_data_ptr) };
+    if plaintext_len == u32::MAX
+        || additional_mac_text_len == u32::MAX
+        || sealed_data_size(plaintext_len as usize, additional_mac_text_len as usize)?
+            > sealed_data.len()
+    {
+        return_errno!(EINVAL, "the sealed data is corrupted");
+    }
+
+    let mut plaintext = vec![0; plaintext_len as usize];
+    let mut additional_mac_text = vec![0; additional_mac_text_len as usize];
+    let sgx_status = unsafe {
+        sgx_unseal_data(
+            sealed_data_ptr,
+            if additional_mac_text.is_empty() {
+                ptr::null_mut()
+            } else {
+                additional_mac_text.as_mut_ptr()
+            },
+            &mut additional_mac_text_len,
+            plaintext.as_mut_ptr(),
+            &mut plaintext_len,
+        )
+    };
+    match sgx_status {
+        sgx_status_t::SGX_SUCCESS => Ok((plaintext, additional_mac_text)),
+        sgx_status_t::SGX_ERROR_MAC_MISMATCH => {
+            return_errno!(EINVAL, "sealed data MAC mismatch")
+        }
+        sgx_status_t::SGX_ERROR_INVALID_CPUSVN | sgx_status_t::SGX_ERROR_INVALID_ISVSVN => {
+            return_errno!(EPERM, "the data is sealed with a newer SVN")
+        }
+        sgx_status_t::SGX_ERROR_INVALID_PARAMETER => return_errno!(EINVAL, "invalid parameters"),
+        sgx_status_t::SGX_ERROR_OUT_OF_MEMORY => return_errno!(ENOMEM, "out of memory"),
+        _ => {
+            error!("sgx_unseal_data return {:?}", sgx_status);
+            return_errno!(EINVAL, "unexpected SGX error")
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Simulated DCAP quotes for the SGX simulation mode.
+//!
+//! WARNING: the simulated quotes are INSECURE and only for testing. They are
+//! in the format of the DCAP v3 quotes, but signed by a well-known test
+//! attestation key instead of the Quoting Enclave, so anyone can forge them.
+//! The quotes are marked by `SIM_QUOTE_MARK` in the `user_data` field of the
+//! quote header, and the verifier accepts nothing but them.
+
+use super::*;
+
+use sgx_tcrypto::{rsgx_ecc256_pub_from_priv, rsgx_sha256_slice, SgxEccHandle};
+use std::mem::size_of;
+
+mod quote_generator;
+mod quote_verifier;
+
+pub use quote_generator::QuoteGenerator;
+pub use quote_verifier::QuoteVerifier;
+
+// Anyone can forge the simulated quotes, so never build them for the hardware
+// mode, even if the feature is enabled by mistake
+const _: () = assert!(
+    is_sim_mode(option_env!("SGX_MODE")),
+    "the simulated quotes are only for SGX_MODE=SIM"
+);
+
+const fn is_sim_mode(mode: Option<&str>) -> bool {
+    match mode {
+        Some(mode) => {
+            let mode = mode.as_bytes();
+            mode.len() == 3 && mode[0] == b'S' && mode[1] == b'I' && mode[2] == b'M'
+        }
+        None => false,
+    }
+}
+
+/// The well-known private key to sign the simulated quotes
+const SIM_ATTESTATION_KEY: sgx_ec256_private_t = sgx_ec256_private_t {
+    r: *b"occlum-simulated-attestation-key",
+};
+
+/// The mark of the simulated quotes in the `user_data` field of the header
+const SIM_QUOTE_MARK: &[u8; QUOTE_USER_DATA_SIZE] = b"OCCLUM-SIM-INSECURE\0";
+
+// The layout of the DCAP v3 quote. See sgx_quote_3.h in Intel SGX SDK.
+const QUOTE_VERSION: u16 = 3;
+const QUOTE_ATT_KEY_TYPE_ECDSA_P256: u16 = 2;
+const QUOTE_QE_VENDOR_ID: [u8; 16] = [
+    0x93, 0x9a, 0x72, 0x33, 0xf7, 0x9c, 0x4c, 0xa9, 0x94, 0x0a, 0x0d, 0xb3, 0x95, 0x7f, 0x06, 0x07,
+];
+const QUOTE_USER_DATA_SIZE: usize = 20;
+const QUOTE_HEADER_SIZE: usize = 48;
+const REPORT_BODY_SIZE: usize = size_of::<sgx_report_body_t>();
+const REPORT_DATA_OFFSET: usize = REPORT_BODY_SIZE - size_of::<sgx_report_data_t>();
+// The header and the report body are signed by the attestation key
+const QUOTE_SIGNED_SIZE: usize = QUOTE_HEADER_SIZE + REPORT_BODY_SIZE;
+const ECDSA_SIG_SIZE: usize = 64;
+const ECDSA_PUB_KEY_SIZE: usize = 64;
+const AUTH_DATA_SIZE: usize = 32;
+const CERT_TYPE_PPID_CLEARTEXT: u16 = 1;
+// PPID, CPUSVN, PCESVN and PCEID, all zeros
+const CERT_DATA_SIZE: usize = 36;
+const QUOTE_SIG_DATA_SIZE: usize = ECDSA_SIG_SIZE
+    + ECDSA_PUB_KEY_SIZE
+    + REPORT_BODY_SIZE
+    + ECDSA_SIG_SIZE
+    + size_of::<u16>()
+    + AUTH_DATA_SIZE
+    + size_of::<u16>()
+    + size_of::<u32>()
+    + CERT_DATA_SIZE;
+const QUOTE_SIZE: usize = QUOTE_SIGNED_SIZE + size_of::<u32>() + QUOTE_SIG_DATA_SIZE;
+
+/// The public key of the test attestation key in big-endian, as in the quote
+fn sim_attestation_pub_key() -> Result<[u8; ECDSA_PUB_KEY_SIZE]> {
+    let pub_key = rsgx_ecc256_pub_from_priv(&SIM_ATTESTATION_KEY)
+        .map_err(|_| errno!(EINVAL, "failed to calculate the public key"))?;
+    let mut pub_key_buf = [0; ECDSA_PUB_KEY_SIZE];
+    pub_key_buf[..32].copy_from_slice(&pub_key.gx);
+    pub_key_buf[32..].copy_from_slice(&pub_key.gy);
+    // SGX crypto library uses little-endian, while the quote uses big-endian
+    pub_key_buf[..32].reverse();
+    pub_key_buf[32..].reverse();
+    Ok(pub_key_buf)
+}
+
+/// Sign the data with the test attestation key, returning the signature in big-endian
+fn sim_sign(data: &[u8]) -> Result<[u8; ECDSA_SIG_SIZE]> {
+    let ecc_handle = SgxEccHandle::new();
+    ecc_handle
+        .open()
+        .map_err(|_| errno!(EINVAL, "failed to open the ECC context"))?;
+    let signature = ecc_handle
+        .ecdsa_sign_slice(data, &SIM_ATTESTATION_KEY)
+        .map_err(|_| errno!(EINVAL, "failed to sign the simulated quote"))?;
+    let _ = ecc_handle.close();
+
+    let mut sig_buf = [0; ECDSA_SIG_SIZE];
+    for (i, word) in signature.x.iter().chain(signature.y.iter()).enumerate() {
+        sig_buf[i * 4..(i + 1) * 4].copy_from_slice(&word.to_le_bytes());
+    }
+    sig_buf[..32].reverse();
+    sig_buf[32..].reverse();
+    Ok(sig_buf)
+}
+
+/// Verify the big-endian signature of the data with the test attestation key
+fn sim_verify(data: &[u8], sig: &[u8]) -> Result<bool> {
+    let pub_key = rsgx_ecc256_pub_from_priv(&SIM_ATTESTATION_KEY)
+        .map_err(|_| errno!(EINVAL, "failed to calculate the public key"))?;
+
+    let mut sig_buf = [0; ECDSA_SIG_SIZE];
+    sig_buf.copy_from_slice(sig);
+    sig_buf[..32].reverse();
+    sig_buf[32..].reverse();
+    let mut signature = sgx_ec256_signature_t::default();
+    for (i, word) in signature
+        .x
+        .iter_mut()
+        .chain(signature.y.iter_mut())
+        .enumerate()
+    {
+        let mut word_buf = [0; 4];
+        word_buf.copy_from_slice(&sig_buf[i * 4..(i + 1) * 4]);
+        *word = u32::from_le_bytes(word_buf);
+    }
+
+    let ecc_handle = SgxEccHandle::new();
+    ecc_handle
+        .open()
+        .map_err(|_| errno!(EINVAL, "failed to open the ECC context"))?;
+    let is_valid = ecc_handle
+        .ecdsa_verify_slice(data, &pub_key, &signature)
+        .map_err(|_| errno!(EINVAL, "failed to verify the simulated quote"))?;
+    let _ = ecc_handle.close();
+    Ok(is_valid)
+}
+
+fn sha256(data: &[u8]) -> Result<sgx_sha256_hash_t> {
+    rsgx_sha256_slice(data).map_err(|_| errno!(EINVAL, "failed to calculate SHA256"))
+}
+
+fn report_body_as_bytes(body: &sgx_report_body_t) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(body as *const _ as *const u8, REPORT_BODY_SIZE) }
+}
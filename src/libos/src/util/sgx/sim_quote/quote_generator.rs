@@ -0,0 +1,68 @@
+use super::*;
+
+/// The generator of the simulated DCAP quotes, with the same interface as the
+/// real one.
+#[derive(Copy, Clone)]
+pub struct QuoteGenerator {
+    attest_pub_key: [u8; ECDSA_PUB_KEY_SIZE],
+}
+
+impl QuoteGenerator {
+    pub fn new() -> Option<Self> {
+        warn!("DCAP quotes are simulated, which are INSECURE and only for testing");
+        match sim_attestation_pub_key() {
+            Ok(attest_pub_key) => Some(Self { attest_pub_key }),
+            Err(e) => {
+                error!("Init simulated quote generator failed: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn get_quote_size(&self) -> u32 {
+        QUOTE_SIZE as u32
+    }
+
+    pub fn generate_quote(&self, report_data: &sgx_report_data_t) -> Result<Vec<u8>> {
+        let app_report = create_report(None, Some(report_data))?;
+        let mut quote = Vec::with_capacity(QUOTE_SIZE);
+
+        // Header
+        quote.extend_from_slice(&QUOTE_VERSION.to_le_bytes());
+        quote.extend_from_slice(&QUOTE_ATT_KEY_TYPE_ECDSA_P256.to_le_bytes());
+        // Reserved
+        quote.extend_from_slice(&0u32.to_le_bytes());
+        // QE SVN and PCE SVN
+        quote.extend_from_slice(&0u16.to_le_bytes());
+        quote.extend_from_slice(&0u16.to_le_bytes());
+        quote.extend_from_slice(&QUOTE_QE_VENDOR_ID);
+        quote.extend_from_slice(SIM_QUOTE_MARK);
+        // Report body
+        quote.extend_from_slice(report_body_as_bytes(&app_report.body));
+        let quote_sig = sim_sign(&quote)?;
+
+        // Signature data
+        quote.extend_from_slice(&(QUOTE_SIG_DATA_SIZE as u32).to_le_bytes());
+        quote.extend_from_slice(&quote_sig);
+        quote.extend_from_slice(&self.attest_pub_key);
+        // As the real QE, bind the attestation key to the QE report by
+        // SHA256(attestation public key || authentication data). There is no
+        // QE in the simulation mode, so the report of this enclave is used.
+        let auth_data = [0u8; AUTH_DATA_SIZE];
+        let mut qe_report_data = sgx_report_data_t::default();
+        let key_hash = sha256(&[&self.attest_pub_key[..], &auth_data[..]].concat())?;
+        qe_report_data.d[..key_hash.len()].copy_from_slice(&key_hash);
+        let qe_report = create_report(None, Some(&qe_report_data))?;
+        let qe_report_body = report_body_as_bytes(&qe_report.body);
+        quote.extend_from_slice(qe_report_body);
+        quote.extend_from_slice(&sim_sign(qe_report_body)?);
+        quote.extend_from_slice(&(AUTH_DATA_SIZE as u16).to_le_bytes());
+        quote.extend_from_slice(&auth_data);
+        quote.extend_from_slice(&CERT_TYPE_PPID_CLEARTEXT.to_le_bytes());
+        quote.extend_from_slice(&(CERT_DATA_SIZE as u32).to_le_bytes());
+        quote.extend_from_slice(&[0u8; CERT_DATA_SIZE]);
+
+        debug_assert!(quote.len() == QUOTE_SIZE);
+        Ok(quote)
+    }
+}
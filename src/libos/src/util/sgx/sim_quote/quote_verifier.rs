@@ -0,0 +1,72 @@
+use super::*;
+
+/// The verifier of the simulated DCAP quotes, with the same interface as the
+/// real one. It accepts the simulated quotes only.
+#[derive(Copy, Clone)]
+pub struct QuoteVerifier {
+    attest_pub_key: [u8; ECDSA_PUB_KEY_SIZE],
+}
+
+impl QuoteVerifier {
+    pub fn new() -> Option<Self> {
+        match sim_attestation_pub_key() {
+            Ok(attest_pub_key) => Some(Self { attest_pub_key }),
+            Err(e) => {
+                error!("Init simulated quote verifier failed: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn get_supplemental_data_size(&self) -> u32 {
+        size_of::<sgx_ql_qv_supplemental_t>() as u32
+    }
+
+    pub fn verify_quote(&self, quote: &[u8]) -> Result<(u32, sgx_ql_qv_result_t, Vec<u8>)> {
+        if quote.len() < QUOTE_SIZE {
+            return_errno!(EINVAL, "the quote is too short");
+        }
+        let header = &quote[..QUOTE_HEADER_SIZE];
+        if header[..2] != QUOTE_VERSION.to_le_bytes()
+            || header[2..4] != QUOTE_ATT_KEY_TYPE_ECDSA_P256.to_le_bytes()
+            || header[QUOTE_HEADER_SIZE - QUOTE_USER_DATA_SIZE..] != SIM_QUOTE_MARK[..]
+        {
+            return_errno!(EINVAL, "not a simulated quote");
+        }
+
+        let sig_data_size = &quote[QUOTE_SIGNED_SIZE..QUOTE_SIGNED_SIZE + size_of::<u32>()];
+        if sig_data_size != (QUOTE_SIG_DATA_SIZE as u32).to_le_bytes() {
+            return_errno!(EINVAL, "invalid signature data size");
+        }
+
+        let sig_data = &quote[QUOTE_SIGNED_SIZE + size_of::<u32>()..];
+        let (quote_sig, sig_data) = sig_data.split_at(ECDSA_SIG_SIZE);
+        let (attest_pub_key, sig_data) = sig_data.split_at(ECDSA_PUB_KEY_SIZE);
+        let (qe_report_body, sig_data) = sig_data.split_at(REPORT_BODY_SIZE);
+        let (qe_report_sig, sig_data) = sig_data.split_at(ECDSA_SIG_SIZE);
+        let auth_data = &sig_data[size_of::<u16>()..][..AUTH_DATA_SIZE];
+
+        if attest_pub_key != &self.attest_pub_key[..] {
+            return_errno!(EINVAL, "unknown attestation key");
+        }
+        if !sim_verify(&quote[..QUOTE_SIGNED_SIZE], quote_sig)? {
+            return_errno!(EINVAL, "invalid quote signature");
+        }
+        if !sim_verify(qe_report_body, qe_report_sig)? {
+            return_errno!(EINVAL, "invalid QE report signature");
+        }
+        let key_hash = sha256(&[attest_pub_key, auth_data].concat())?;
+        if qe_report_body[REPORT_DATA_OFFSET..][..key_hash.len()] != key_hash[..] {
+            return_errno!(EINVAL, "the attestation key is not bound to the QE report");
+        }
+
+        warn!("a simulated quote is accepted, which is INSECURE and only for testing");
+        let collateral_expiration_status = 0;
+        let supplemental_data = vec![0; self.get_supplemental_data_size() as usize];
+        Ok((
+            collateral_expiration_status,
+            sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OK,
+            supplemental_data,
+        ))
+    }
+}
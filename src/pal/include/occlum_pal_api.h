@@ -100,6 +100,8 @@ struct host_file_buffer_t {
     unsigned int hosts_buf_size;
     char *hostname_buf;
     unsigned int hostname_buf_size;
+    char *interfaces_buf;
+    unsigned int interfaces_buf_size;
 };
 
 /*
@@ -1,7 +1,11 @@
 #include "ocalls.h"
 #include <errno.h>
+#include <fcntl.h>
+#include <stdio.h>
 #include <net/if.h>
 #include <unistd.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
 #include <sys/ioctl.h>
 #include <sys/vfs.h>
 
@@ -45,4 +49,51 @@ int occlum_ocall_ioctl(int fd, int request, void *arg, size_t len) {
 
 int occlum_ocall_statfs(const char *path, struct statfs *buf) {
     return statfs(path, buf);
-}
\ No newline at end of file
+}
+
+int occlum_ocall_openat(int dirfd, const char *path, int flags, unsigned int mode) {
+    return openat(dirfd, path, flags | O_CLOEXEC, (mode_t)mode);
+}
+
+int occlum_ocall_fstatat(int dirfd, const char *path, void *buf, size_t buf_len,
+                         int flags) {
+    if (buf_len != sizeof(struct stat)) {
+        errno = EINVAL;
+        return -1;
+    }
+    return fstatat(dirfd, path, (struct stat *)buf, flags);
+}
+
+ssize_t occlum_ocall_readlinkat(int dirfd, const char *path, char *buf, size_t buf_len) {
+    return readlinkat(dirfd, path, buf, buf_len);
+}
+
+int occlum_ocall_mkdirat(int dirfd, const char *path, unsigned int mode) {
+    return mkdirat(dirfd, path, (mode_t)mode);
+}
+
+int occlum_ocall_unlinkat(int dirfd, const char *path, int flags) {
+    return unlinkat(dirfd, path, flags);
+}
+
+int occlum_ocall_renameat(int old_dirfd, const char *old_path, int new_dirfd,
+                          const char *new_path) {
+    return renameat(old_dirfd, old_path, new_dirfd, new_path);
+}
+
+int occlum_ocall_linkat(int old_dirfd, const char *old_path, int new_dirfd,
+                        const char *new_path, int flags) {
+    return linkat(old_dirfd, old_path, new_dirfd, new_path, flags);
+}
+
+int occlum_ocall_symlinkat(const char *target, int new_dirfd, const char *link_path) {
+    return symlinkat(target, new_dirfd, link_path);
+}
+
+int occlum_ocall_fchmodat(int dirfd, const char *path, unsigned int mode) {
+    return fchmodat(dirfd, path, (mode_t)mode, 0);
+}
+
+ssize_t occlum_ocall_getdents64(int fd, void *buf, size_t buf_len) {
+    return syscall(__NR_getdents64, fd, buf, buf_len);
+}
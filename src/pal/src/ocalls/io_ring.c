@@ -0,0 +1,10 @@
+#include "ocalls.h"
+#include "../pal_io_ring.h"
+
+int occlum_ocall_io_ring_setup(void *ring, unsigned int num_workers) {
+    return pal_io_ring_start((struct pal_io_ring *)ring, num_workers);
+}
+
+void occlum_ocall_io_ring_wakeup(void) {
+    pal_io_ring_wakeup();
+}
@@ -4,6 +4,7 @@
 #include "pal_error.h"
 #include "pal_load_file.h"
 #include "pal_interrupt_thread.h"
+#include "pal_io_ring.h"
 #include "pal_log.h"
 #include "pal_sig_handler.h"
 #include "pal_syscall.h"
@@ -112,10 +113,12 @@ int occlum_pal_init(const struct occlum_pal_attr *attr) {
     load_file_t hostname_ptr = {0, NULL};
     load_file_t hosts_ptr = {0, NULL};
     load_file_t resolv_conf_ptr = {0, NULL};
+    load_file_t interfaces_ptr = {0, NULL};
 
     pal_load_file(eid, "/etc/hostname", &hostname_ptr);
     pal_load_file(eid, "/etc/hosts", &hosts_ptr);
     pal_load_file(eid, "/etc/resolv.conf", &resolv_conf_ptr);
+    pal_load_interfaces(eid, &interfaces_ptr);
 
     struct host_file_buffer_t file_buffer = {
         .hostname_buf = hostname_ptr.buffer,
@@ -124,6 +127,8 @@ int occlum_pal_init(const struct occlum_pal_attr *attr) {
         .hosts_buf_size = hosts_ptr.size,
         .resolv_conf_buf = resolv_conf_ptr.buffer,
         .resolv_conf_buf_size = resolv_conf_ptr.size,
+        .interfaces_buf = interfaces_ptr.buffer,
+        .interfaces_buf_size = interfaces_ptr.size,
     };
 
     sgx_status_t ecall_status = occlum_ecall_init(eid, &ecall_ret, attr->log_level,
@@ -289,6 +294,11 @@ int occlum_pal_destroy(void) {
         PAL_WARN("Cannot stop the interrupt thread: %s", errno2str(errno));
     }
 
+    if (pal_io_ring_stop() < 0) {
+        ret = -1;
+        PAL_WARN("Cannot stop the I/O ring workers");
+    }
+
     if (pal_destroy_enclave() < 0) {
         ret = -1;
         PAL_WARN("Cannot destroy the enclave");
@@ -0,0 +1,179 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <pthread.h>
+#include <stdlib.h>
+#include <unistd.h>
+#include <sys/socket.h>
+#include "pal_io_ring.h"
+#include "pal_log.h"
+#include "errno2str.h"
+
+// The number of times to check the ring before going to sleep
+#define SPIN_COUNT  1000
+#define MAX_NUM_WORKERS 64
+
+static struct pal_io_ring *ring = NULL;
+static pthread_t workers[MAX_NUM_WORKERS];
+static unsigned int num_workers = 0;
+static int is_stopping = 0;
+// Protect the head of the ring and the sleeping of the workers
+static pthread_mutex_t lock = PTHREAD_MUTEX_INITIALIZER;
+static pthread_cond_t cond = PTHREAD_COND_INITIALIZER;
+
+static inline uint32_t *ring_sq(void) {
+    return (uint32_t *)(ring + 1);
+}
+
+static inline struct pal_io_ring_entry *ring_entries(void) {
+    return (struct pal_io_ring_entry *)(ring_sq() + ring->num_entries);
+}
+
+static inline int ring_is_empty(void) {
+    return ring->head == __atomic_load_n(&ring->tail, __ATOMIC_SEQ_CST);
+}
+
+static void do_request(struct pal_io_ring_entry *entry) {
+    ssize_t ret;
+    void *addr = (void *)entry->addr;
+    switch (entry->opcode) {
+        case PAL_IO_RING_OP_PREAD:
+            ret = pread(entry->fd, addr, entry->len, entry->offset);
+            break;
+        case PAL_IO_RING_OP_PWRITE:
+            ret = pwrite(entry->fd, addr, entry->len, entry->offset);
+            break;
+        case PAL_IO_RING_OP_SENDMSG:
+            ret = sendmsg(entry->fd, (struct msghdr *)addr, entry->flags);
+            break;
+        case PAL_IO_RING_OP_RECVMSG:
+            ret = recvmsg(entry->fd, (struct msghdr *)addr, entry->flags);
+            break;
+        default:
+            ret = -1;
+            errno = EINVAL;
+            break;
+    }
+
+    // The entry must not be accessed once released, but the LibOS may take
+    // the result as soon as completed
+    int eventfd = entry->eventfd;
+    entry->result = ret < 0 ? -errno : ret;
+    // Either the LibOS sees the completion before sleeping, or it is seen
+    // waiting here and woken up
+    __atomic_store_n(&entry->completed, 1, __ATOMIC_SEQ_CST);
+    if (__atomic_load_n(&entry->waiting, __ATOMIC_SEQ_CST)) {
+        uint64_t val = 1;
+        write(eventfd, &val, sizeof(val));
+    }
+    __atomic_store_n(&entry->released, 1, __ATOMIC_RELEASE);
+}
+
+static void *worker_func(void *_data) {
+    int spin_count = 0;
+
+    pthread_mutex_lock(&lock);
+    while (!is_stopping) {
+        if (!ring_is_empty()) {
+            uint64_t head = ring->head;
+            uint32_t idx = ring_sq()[head & (ring->num_entries - 1)];
+            __atomic_store_n(&ring->head, head + 1, __ATOMIC_RELEASE);
+            __atomic_store_n(&ring->need_wakeup, 0, __ATOMIC_SEQ_CST);
+            pthread_mutex_unlock(&lock);
+
+            if (idx < ring->num_entries) {
+                do_request(&ring_entries()[idx]);
+            } else {
+                PAL_WARN("Invalid index of the I/O ring entry: %u", idx);
+            }
+            spin_count = 0;
+
+            pthread_mutex_lock(&lock);
+            continue;
+        }
+
+        // Spin for a while before going to sleep
+        if (spin_count < SPIN_COUNT) {
+            spin_count++;
+            pthread_mutex_unlock(&lock);
+            __builtin_ia32_pause();
+            pthread_mutex_lock(&lock);
+            continue;
+        }
+
+        // Check the ring again after setting need_wakeup, so that either a
+        // new submission is seen here, or the LibOS sees need_wakeup and
+        // wakes up the workers.
+        __atomic_store_n(&ring->need_wakeup, 1, __ATOMIC_SEQ_CST);
+        if (ring_is_empty()) {
+            pthread_cond_wait(&cond, &lock);
+        }
+        spin_count = 0;
+    }
+    pthread_mutex_unlock(&lock);
+    return NULL;
+}
+
+int pal_io_ring_start(struct pal_io_ring *new_ring, unsigned int new_num_workers) {
+    if (new_ring == NULL || new_ring->num_entries == 0 ||
+            (new_ring->num_entries & (new_ring->num_entries - 1)) != 0 ||
+            new_num_workers == 0 || new_num_workers > MAX_NUM_WORKERS) {
+        errno = EINVAL;
+        return -1;
+    }
+
+    pthread_mutex_lock(&lock);
+    if (ring != NULL) {
+        pthread_mutex_unlock(&lock);
+        errno = EEXIST;
+        return -1;
+    }
+    ring = new_ring;
+    is_stopping = 0;
+    pthread_mutex_unlock(&lock);
+
+    for (num_workers = 0; num_workers < new_num_workers; num_workers++) {
+        int ret = pthread_create(&workers[num_workers], NULL, worker_func, NULL);
+        if (ret != 0) {
+            PAL_ERROR("Failed to start the I/O ring worker: %s", errno2str(ret));
+            pal_io_ring_stop();
+            errno = ret;
+            return -1;
+        }
+    }
+    return 0;
+}
+
+void pal_io_ring_wakeup(void) {
+    pthread_mutex_lock(&lock);
+    if (ring != NULL) {
+        __atomic_store_n(&ring->need_wakeup, 0, __ATOMIC_SEQ_CST);
+    }
+    pthread_cond_broadcast(&cond);
+    pthread_mutex_unlock(&lock);
+}
+
+int pal_io_ring_stop(void) {
+    pthread_mutex_lock(&lock);
+    if (ring == NULL) {
+        pthread_mutex_unlock(&lock);
+        return 0;
+    }
+    is_stopping = 1;
+    pthread_cond_broadcast(&cond);
+    pthread_mutex_unlock(&lock);
+
+    int ret = 0;
+    for (unsigned int i = 0; i < num_workers; i++) {
+        int err = pthread_join(workers[i], NULL);
+        if (err != 0) {
+            PAL_WARN("Failed to join the I/O ring worker: %s", errno2str(err));
+            ret = -1;
+        }
+    }
+    num_workers = 0;
+
+    pthread_mutex_lock(&lock);
+    ring = NULL;
+    pthread_mutex_unlock(&lock);
+    return ret;
+}
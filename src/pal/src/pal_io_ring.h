@@ -0,0 +1,61 @@
+#ifndef __PAL_IO_RING_H__
+#define __PAL_IO_RING_H__
+
+#include <stdint.h>
+
+// The I/O ring shared with the LibOS, through which the LibOS submits the I/O
+// requests to the host worker threads without exiting the enclave.
+//
+// The layout must be kept in sync with the LibOS. The header is followed by
+// the submission queue of `num_entries` entry indexes, which is followed by
+// the `num_entries` entries.
+
+#define PAL_IO_RING_OP_PREAD    1
+#define PAL_IO_RING_OP_PWRITE   2
+#define PAL_IO_RING_OP_SENDMSG  3
+#define PAL_IO_RING_OP_RECVMSG  4
+
+struct pal_io_ring_entry {
+    uint32_t opcode;
+    int32_t fd;
+    int32_t flags;
+    // The host eventfd to write after the request is completed, if the LibOS
+    // thread waiting for it sleeps
+    int32_t eventfd;
+    // The buffer, or the msghdr for SENDMSG and RECVMSG
+    uint64_t addr;
+    uint64_t len;
+    int64_t offset;
+    // The return value, or the negative errno on error
+    int64_t result;
+    uint32_t completed;
+    // Set after the worker no longer accesses the entry, the buffer and the
+    // eventfd, so that the LibOS can reuse the entry
+    uint32_t released;
+    // Set by the LibOS thread before sleeping on the eventfd
+    uint32_t waiting;
+    uint32_t reserved;
+};
+
+struct pal_io_ring {
+    // Must be a power of two
+    uint32_t num_entries;
+    // Set by the workers before going to sleep
+    uint32_t need_wakeup;
+    // The next submission to be consumed by the workers
+    uint64_t head;
+    // The next submission to be produced by the LibOS
+    uint64_t tail;
+    uint64_t reserved;
+};
+
+// Start the worker threads serving the ring
+int pal_io_ring_start(struct pal_io_ring *ring, unsigned int num_workers);
+
+// Wake up the sleeping worker threads
+void pal_io_ring_wakeup(void);
+
+// Stop the worker threads if started
+int pal_io_ring_stop(void);
+
+#endif /* __PAL_IO_RING_H__ */
@@ -1,5 +1,13 @@
 #include <stdio.h>
 #include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <ifaddrs.h>
+#include <net/if.h>
+#include <arpa/inet.h>
+#include <linux/if_packet.h>
+#include <sys/ioctl.h>
+#include <sys/socket.h>
 #include "Enclave_u.h"
 #include "pal_log.h"
 #include "pal_load_file.h"
@@ -35,6 +43,130 @@ void pal_load_file(const sgx_enclave_id_t eid, const char *filename,
     fclose(fp);
 }
 
+static int netmask_to_prefix_len(const struct sockaddr *netmask) {
+    const unsigned char *bytes;
+    size_t len;
+    if (netmask == NULL) {
+        return 0;
+    }
+    if (netmask->sa_family == AF_INET) {
+        bytes = (const unsigned char *) &((const struct sockaddr_in *)netmask)->sin_addr;
+        len = sizeof(struct in_addr);
+    } else {
+        bytes = (const unsigned char *) &((const struct sockaddr_in6 *)netmask)->sin6_addr;
+        len = sizeof(struct in6_addr);
+    }
+
+    int prefix_len = 0;
+    for (size_t i = 0; i < len; i++) {
+        prefix_len += __builtin_popcount(bytes[i]);
+    }
+    return prefix_len;
+}
+
+static void print_interface(FILE *fp, int sock, struct ifaddrs *ifaddrs,
+                            const struct if_nameindex *name) {
+    unsigned int flags = 0;
+    int mtu = 0;
+    struct ifreq ifr;
+    memset(&ifr, 0, sizeof(ifr));
+    strncpy(ifr.ifr_name, name->if_name, IFNAMSIZ - 1);
+    if (sock >= 0 && ioctl(sock, SIOCGIFFLAGS, &ifr) == 0) {
+        flags = (unsigned short)ifr.ifr_flags;
+    }
+    if (sock >= 0 && ioctl(sock, SIOCGIFMTU, &ifr) == 0) {
+        mtu = ifr.ifr_mtu;
+    }
+    fprintf(fp, "%u %s 0x%x %d", name->if_index, name->if_name, flags, mtu);
+
+    char hwaddr[18] = "-";
+    for (struct ifaddrs *ifa = ifaddrs; ifa != NULL; ifa = ifa->ifa_next) {
+        if (ifa->ifa_addr == NULL || ifa->ifa_addr->sa_family != AF_PACKET ||
+                strcmp(ifa->ifa_name, name->if_name) != 0) {
+            continue;
+        }
+        const struct sockaddr_ll *sll = (const struct sockaddr_ll *)ifa->ifa_addr;
+        if (sll->sll_halen == 6) {
+            snprintf(hwaddr, sizeof(hwaddr), "%02x:%02x:%02x:%02x:%02x:%02x",
+                     sll->sll_addr[0], sll->sll_addr[1], sll->sll_addr[2],
+                     sll->sll_addr[3], sll->sll_addr[4], sll->sll_addr[5]);
+        }
+    }
+    fprintf(fp, " %s", hwaddr);
+
+    for (struct ifaddrs *ifa = ifaddrs; ifa != NULL; ifa = ifa->ifa_next) {
+        if (ifa->ifa_addr == NULL || strcmp(ifa->ifa_name, name->if_name) != 0) {
+            continue;
+        }
+        char ip[INET6_ADDRSTRLEN];
+        if (ifa->ifa_addr->sa_family == AF_INET) {
+            inet_ntop(AF_INET, &((struct sockaddr_in *)ifa->ifa_addr)->sin_addr, ip, sizeof(ip));
+        } else if (ifa->ifa_addr->sa_family == AF_INET6) {
+            inet_ntop(AF_INET6, &((struct sockaddr_in6 *)ifa->ifa_addr)->sin6_addr, ip,
+                      sizeof(ip));
+        } else {
+            continue;
+        }
+        fprintf(fp, " %s/%d", ip, netmask_to_prefix_len(ifa->ifa_netmask));
+    }
+    fprintf(fp, "\n");
+}
+
+// Take a snapshot of the host network interfaces, which is passed to the LibOS
+// as text. Each line describes one interface in the format of
+//
+//     <index> <name> <flags> <mtu> <hwaddr or -> [<ip>/<prefix_len> ...]
+void pal_load_interfaces(const sgx_enclave_id_t eid, load_file_t *load_file) {
+#ifndef SGX_MODE_HYPER
+    UNUSED(eid);
+#endif
+    struct ifaddrs *ifaddrs = NULL;
+    if (getifaddrs(&ifaddrs) < 0) {
+        PAL_WARN("Warning: Failed to get the network interfaces");
+        return;
+    }
+    struct if_nameindex *names = if_nameindex();
+    if (names == NULL) {
+        PAL_WARN("Warning: Failed to get the network interface names");
+        freeifaddrs(ifaddrs);
+        return;
+    }
+
+    char *text = NULL;
+    size_t text_size = 0;
+    FILE *fp = open_memstream(&text, &text_size);
+    if (fp == NULL) {
+        PAL_WARN("Warning: Failed to open memstream for network interfaces");
+        goto out;
+    }
+    int sock = socket(AF_INET, SOCK_DGRAM, 0);
+    for (struct if_nameindex *name = names; name->if_index != 0; name++) {
+        print_interface(fp, sock, ifaddrs, name);
+    }
+    if (sock >= 0) {
+        close(sock);
+    }
+    fclose(fp);
+
+#ifndef SGX_MODE_HYPER
+    load_file->buffer = malloc(text_size + 1);
+#else
+    load_file->buffer = sgx_ecall_ms_buffer_alloc(eid, text_size + 1);
+#endif
+    if (load_file->buffer == NULL) {
+        PAL_WARN("Warning: Failed to malloc buffer for network interfaces");
+        goto out;
+    }
+    memcpy(load_file->buffer, text, text_size);
+    load_file->buffer[text_size] = '\0';
+    load_file->size = text_size + 1;
+
+out:
+    free(text);
+    if_freenameindex(names);
+    freeifaddrs(ifaddrs);
+}
+
 void free_host_file_buffer(const sgx_enclave_id_t eid,
                            struct host_file_buffer_t *file_buffer) {
 #ifndef SGX_MODE_HYPER
@@ -48,6 +180,9 @@ void free_host_file_buffer(const sgx_enclave_id_t eid,
     if (file_buffer->resolv_conf_buf) {
         free((void *)file_buffer->resolv_conf_buf);
     }
+    if (file_buffer->interfaces_buf) {
+        free((void *)file_buffer->interfaces_buf);
+    }
 #else
     if (file_buffer->hostname_buf) {
         sgx_ecall_ms_buffer_free(eid);
@@ -58,6 +193,9 @@ void free_host_file_buffer(const sgx_enclave_id_t eid,
     if (file_buffer->resolv_conf_buf) {
         sgx_ecall_ms_buffer_free(eid);
     }
+    if (file_buffer->interfaces_buf) {
+        sgx_ecall_ms_buffer_free(eid);
+    }
 #endif
     file_buffer->hostname_buf = NULL;
     file_buffer->hostname_buf_size = 0;
@@ -65,4 +203,6 @@ void free_host_file_buffer(const sgx_enclave_id_t eid,
     file_buffer->hosts_buf_size = 0;
     file_buffer->resolv_conf_buf = NULL;
     file_buffer->resolv_conf_buf_size = 0;
+    file_buffer->interfaces_buf = NULL;
+    file_buffer->interfaces_buf_size = 0;
 }
@@ -11,6 +11,7 @@ typedef struct {
 
 void pal_load_file(const sgx_enclave_id_t eid, const char *filename,
                    load_file_t *load_file);
+void pal_load_interfaces(const sgx_enclave_id_t eid, load_file_t *load_file);
 void free_host_file_buffer(const sgx_enclave_id_t eid,
                            struct host_file_buffer_t *file_buffer);
 
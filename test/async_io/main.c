@@ -0,0 +1,398 @@
+#include <sys/mount.h>
+#include <sys/socket.h>
+#include <sys/stat.h>
+#include <sys/uio.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <pthread.h>
+#include <signal.h>
+#include <string.h>
+#include <unistd.h>
+#include "test_fs.h"
+
+// The async I/O is enabled in Occlum.json with a small ring, so that the
+// concurrent requests also fall back to the OCalls when the ring is busy.
+
+#define HOST_DIR            "/host/async_io_test"
+#define MNT_DIR             "/async_io_mnt"
+#define FILE_PATH           MNT_DIR "/test_file"
+// Larger than the I/O buffer of the thread
+#define LARGE_LEN           (256 * 1024)
+#define NUM_THREADS         8
+#define NUM_ROUNDS          64
+#define CHUNK_LEN           4096
+#define NUM_IOVS            4
+
+// ============================================================================
+// Helper function
+// ============================================================================
+
+static void fill_pattern(char *buf, size_t len, size_t offset) {
+    for (size_t i = 0; i < len; i++) {
+        buf[i] = (char)((offset + i) % 251);
+    }
+}
+
+static int check_pattern(const char *buf, size_t len, size_t offset) {
+    for (size_t i = 0; i < len; i++) {
+        if (buf[i] != (char)((offset + i) % 251)) {
+            return -1;
+        }
+    }
+    return 0;
+}
+
+// Connect to the wildcard address rather than 127.0.0.1, so that the
+// connection goes through the host instead of the in-enclave loopback
+static void init_addr(struct sockaddr_in *addr, in_port_t port) {
+    memset(addr, 0, sizeof(*addr));
+    addr->sin_family = AF_INET;
+    addr->sin_addr.s_addr = htonl(INADDR_ANY);
+    addr->sin_port = htons(port);
+}
+
+static int bind_any_port(int sock, struct sockaddr_in *addr) {
+    socklen_t addr_len = sizeof(*addr);
+    init_addr(addr, 0);
+    if (bind(sock, (struct sockaddr *)addr, sizeof(*addr)) < 0) {
+        THROW_ERROR("failed to bind");
+    }
+    if (getsockname(sock, (struct sockaddr *)addr, &addr_len) < 0) {
+        THROW_ERROR("failed to get the bound address");
+    }
+    init_addr(addr, ntohs(addr->sin_port));
+    return 0;
+}
+
+// ============================================================================
+// Test cases for HostFS
+// ============================================================================
+
+static int test_mount_hostfs() {
+    if (mkdir(HOST_DIR, 00775) < 0 && errno != EEXIST) {
+        THROW_ERROR("failed to create the host dir");
+    }
+    if (mkdir(MNT_DIR, 00775) < 0 && errno != EEXIST) {
+        THROW_ERROR("failed to create the mount point");
+    }
+    if (mount("hostfs", MNT_DIR, "hostfs", 0, "dir=./async_io_test,async_io") < 0) {
+        THROW_ERROR("failed to mount hostfs with async_io");
+    }
+    return 0;
+}
+
+static int test_hostfs_read_write() {
+    static char write_buf[LARGE_LEN];
+    static char read_buf[LARGE_LEN];
+    size_t lens[] = { 1, 100, CHUNK_LEN, LARGE_LEN };
+
+    int fd = open(FILE_PATH, O_RDWR | O_CREAT | O_TRUNC, 00666);
+    if (fd < 0) {
+        THROW_ERROR("failed to create the file");
+    }
+    for (int i = 0; i < ARRAY_SIZE(lens); i++) {
+        size_t len = lens[i];
+        fill_pattern(write_buf, len, i);
+        if (pwrite(fd, write_buf, len, i) != len) {
+            close(fd);
+            THROW_ERROR("failed to write %zu bytes", len);
+        }
+        memset(read_buf, 0, len);
+        if (pread(fd, read_buf, len, i) != len) {
+            close(fd);
+            THROW_ERROR("failed to read %zu bytes", len);
+        }
+        if (check_pattern(read_buf, len, i) < 0) {
+            close(fd);
+            THROW_ERROR("the data read is different from the data written");
+        }
+    }
+
+    // Read past the end of the file
+    if (pread(fd, read_buf, CHUNK_LEN, LARGE_LEN * 2) != 0) {
+        close(fd);
+        THROW_ERROR("read past the end of the file should return 0");
+    }
+    close(fd);
+    return 0;
+}
+
+static void *hostfs_thread_func(void *arg) {
+    long id = (long)arg;
+    char buf[CHUNK_LEN];
+    off_t offset = id * CHUNK_LEN;
+
+    int fd = open(FILE_PATH, O_RDWR);
+    if (fd < 0) {
+        return (void *)-1;
+    }
+    for (int i = 0; i < NUM_ROUNDS; i++) {
+        fill_pattern(buf, CHUNK_LEN, id + i);
+        if (pwrite(fd, buf, CHUNK_LEN, offset) != CHUNK_LEN) {
+            break;
+        }
+        memset(buf, 0, CHUNK_LEN);
+        if (pread(fd, buf, CHUNK_LEN, offset) != CHUNK_LEN ||
+                check_pattern(buf, CHUNK_LEN, id + i) < 0) {
+            break;
+        }
+        if (i == NUM_ROUNDS - 1) {
+            close(fd);
+            return NULL;
+        }
+    }
+    close(fd);
+    return (void *)-1;
+}
+
+static int test_hostfs_concurrent_read_write() {
+    pthread_t threads[NUM_THREADS];
+    int ret = 0;
+
+    for (long i = 0; i < NUM_THREADS; i++) {
+        if (pthread_create(&threads[i], NULL, hostfs_thread_func, (void *)i) != 0) {
+            THROW_ERROR("failed to create the thread");
+        }
+    }
+    for (int i = 0; i < NUM_THREADS; i++) {
+        void *retval;
+        if (pthread_join(threads[i], &retval) != 0 || retval != NULL) {
+            ret = -1;
+        }
+    }
+    if (ret < 0) {
+        THROW_ERROR("failed to read or write concurrently");
+    }
+    return 0;
+}
+
+static volatile int is_writing = 0;
+
+static void signal_handler(int sig) {
+}
+
+static void *signal_thread_func(void *arg) {
+    pthread_t writer = *(pthread_t *)arg;
+    while (is_writing) {
+        pthread_kill(writer, SIGUSR1);
+        usleep(100);
+    }
+    return NULL;
+}
+
+// The requests in the ring cannot be cancelled, so the reads and writes are
+// never interrupted by signals halfway
+static int test_hostfs_read_write_with_signals() {
+    static char write_buf[LARGE_LEN];
+    static char read_buf[LARGE_LEN];
+    struct sigaction sa = { .sa_handler = signal_handler };
+    struct sigaction old_sa;
+    pthread_t signal_thread;
+    pthread_t self = pthread_self();
+    int ret = -1;
+
+    // Without SA_RESTART
+    if (sigaction(SIGUSR1, &sa, &old_sa) < 0) {
+        THROW_ERROR("failed to set the signal handler");
+    }
+    int fd = open(FILE_PATH, O_RDWR | O_CREAT | O_TRUNC, 00666);
+    if (fd < 0) {
+        sigaction(SIGUSR1, &old_sa, NULL);
+        THROW_ERROR("failed to create the file");
+    }
+    is_writing = 1;
+    if (pthread_create(&signal_thread, NULL, signal_thread_func, &self) != 0) {
+        is_writing = 0;
+        close(fd);
+        sigaction(SIGUSR1, &old_sa, NULL);
+        THROW_ERROR("failed to create the thread");
+    }
+
+    for (int round = 0; round < NUM_ROUNDS; round++) {
+        fill_pattern(write_buf, LARGE_LEN, round);
+        if (pwrite(fd, write_buf, LARGE_LEN, 0) != LARGE_LEN) {
+            printf("the write is interrupted\n");
+            goto out;
+        }
+        if (pread(fd, read_buf, LARGE_LEN, 0) != LARGE_LEN) {
+            printf("the read is interrupted\n");
+            goto out;
+        }
+        if (check_pattern(read_buf, LARGE_LEN, round) < 0) {
+            printf("the data read is different from the data written\n");
+            goto out;
+        }
+    }
+    ret = 0;
+out:
+    is_writing = 0;
+    pthread_join(signal_thread, NULL);
+    close(fd);
+    sigaction(SIGUSR1, &old_sa, NULL);
+    return ret;
+}
+
+static int test_umount_hostfs() {
+    if (unlink(FILE_PATH) < 0) {
+        THROW_ERROR("failed to remove the file");
+    }
+    if (umount(MNT_DIR) < 0) {
+        THROW_ERROR("failed to umount hostfs");
+    }
+    return 0;
+}
+
+// ============================================================================
+// Test cases for sockets
+// ============================================================================
+
+static int test_stream_socket() {
+    struct sockaddr_in addr;
+    char send_buf[CHUNK_LEN * NUM_IOVS];
+    char recv_buf[CHUNK_LEN * NUM_IOVS];
+    struct iovec iovs[NUM_IOVS];
+    int ret = -1;
+
+    int listen_fd = socket(AF_INET, SOCK_STREAM, 0);
+    if (listen_fd < 0) {
+        THROW_ERROR("failed to create the listening socket");
+    }
+    int client_fd = socket(AF_INET, SOCK_STREAM, 0);
+    if (client_fd < 0) {
+        close(listen_fd);
+        THROW_ERROR("failed to create the client socket");
+    }
+    int server_fd = -1;
+    if (bind_any_port(listen_fd, &addr) < 0 || listen(listen_fd, 1) < 0) {
+        printf("failed to listen\n");
+        goto out;
+    }
+    if (connect(client_fd, (struct sockaddr *)&addr, sizeof(addr)) < 0) {
+        printf("failed to connect\n");
+        goto out;
+    }
+    server_fd = accept(listen_fd, NULL, NULL);
+    if (server_fd < 0) {
+        printf("failed to accept\n");
+        goto out;
+    }
+
+    for (int round = 0; round < NUM_ROUNDS; round++) {
+        fill_pattern(send_buf, sizeof(send_buf), round);
+        for (int i = 0; i < NUM_IOVS; i++) {
+            iovs[i].iov_base = send_buf + i * CHUNK_LEN;
+            iovs[i].iov_len = CHUNK_LEN;
+        }
+        struct msghdr msg = { .msg_iov = iovs, .msg_iovlen = NUM_IOVS };
+        if (sendmsg(client_fd, &msg, 0) != sizeof(send_buf)) {
+            printf("failed to send\n");
+            goto out;
+        }
+
+        size_t len_recvd = 0;
+        while (len_recvd < sizeof(recv_buf)) {
+            ssize_t len = recv(server_fd, recv_buf + len_recvd,
+                               sizeof(recv_buf) - len_recvd, 0);
+            if (len <= 0) {
+                printf("failed to receive\n");
+                goto out;
+            }
+            len_recvd += len;
+        }
+        if (check_pattern(recv_buf, sizeof(recv_buf), round) < 0) {
+            printf("the data received is different from the data sent\n");
+            goto out;
+        }
+    }
+
+    // Nothing to receive
+    if (recv(server_fd, recv_buf, sizeof(recv_buf), MSG_DONTWAIT) != -1 ||
+            errno != EAGAIN) {
+        printf("recv with MSG_DONTWAIT should return EAGAIN\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    if (server_fd >= 0) {
+        close(server_fd);
+    }
+    close(client_fd);
+    close(listen_fd);
+    return ret;
+}
+
+static int test_dgram_socket() {
+    struct sockaddr_in recv_addr, send_addr, src_addr;
+    socklen_t src_addr_len;
+    char send_buf[CHUNK_LEN];
+    char recv_buf[CHUNK_LEN];
+    int ret = -1;
+
+    int recv_fd = socket(AF_INET, SOCK_DGRAM, 0);
+    if (recv_fd < 0) {
+        THROW_ERROR("failed to create the receiving socket");
+    }
+    int send_fd = socket(AF_INET, SOCK_DGRAM, 0);
+    if (send_fd < 0) {
+        close(recv_fd);
+        THROW_ERROR("failed to create the sending socket");
+    }
+    if (bind_any_port(recv_fd, &recv_addr) < 0 || bind_any_port(send_fd, &send_addr) < 0) {
+        printf("failed to bind\n");
+        goto out;
+    }
+
+    for (int round = 0; round < NUM_ROUNDS; round++) {
+        fill_pattern(send_buf, sizeof(send_buf), round);
+        if (sendto(send_fd, send_buf, sizeof(send_buf), 0, (struct sockaddr *)&recv_addr,
+                   sizeof(recv_addr)) != sizeof(send_buf)) {
+            printf("failed to send\n");
+            goto out;
+        }
+        src_addr_len = sizeof(src_addr);
+        if (recvfrom(recv_fd, recv_buf, sizeof(recv_buf), 0, (struct sockaddr *)&src_addr,
+                     &src_addr_len) != sizeof(recv_buf)) {
+            printf("failed to receive\n");
+            goto out;
+        }
+        if (src_addr_len != sizeof(src_addr) || src_addr.sin_port != send_addr.sin_port) {
+            printf("the source address is wrong\n");
+            goto out;
+        }
+        if (check_pattern(recv_buf, sizeof(recv_buf), round) < 0) {
+            printf("the data received is different from the data sent\n");
+            goto out;
+        }
+    }
+
+    // Nothing to receive
+    if (recv(recv_fd, recv_buf, sizeof(recv_buf), MSG_DONTWAIT) != -1 || errno != EAGAIN) {
+        printf("recv with MSG_DONTWAIT should return EAGAIN\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(send_fd);
+    close(recv_fd);
+    return ret;
+}
+
+// ============================================================================
+// Test suite main
+// ============================================================================
+
+static test_case_t test_cases[] = {
+    TEST_CASE(test_mount_hostfs),
+    TEST_CASE(test_hostfs_read_write),
+    TEST_CASE(test_hostfs_concurrent_read_write),
+    TEST_CASE(test_hostfs_read_write_with_signals),
+    TEST_CASE(test_umount_hostfs),
+    TEST_CASE(test_stream_socket),
+    TEST_CASE(test_dgram_socket),
+};
+
+int main(int argc, const char *argv[]) {
+    return test_suite_run(test_cases, ARRAY_SIZE(test_cases));
+}
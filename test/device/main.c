@@ -188,6 +188,31 @@ int test_dev_fd() {
     close(fd);
     return 0;
 }
+
+int test_dev_inode_numbers() {
+    struct stat null_stat, zero_stat, random_stat, urandom_stat, proc_stat;
+
+    if (stat("/dev/null", &null_stat) < 0 || stat("/dev/zero", &zero_stat) < 0 ||
+            stat("/dev/random", &random_stat) < 0 || stat("/dev/urandom", &urandom_stat) < 0 ||
+            stat("/proc/self", &proc_stat) < 0) {
+        THROW_ERROR("failed to stat the files");
+    }
+    if (null_stat.st_ino == zero_stat.st_ino || null_stat.st_ino == random_stat.st_ino) {
+        THROW_ERROR("different devices should have different inode numbers");
+    }
+    // The random and urandom are the same device
+    if (random_stat.st_ino != urandom_stat.st_ino || random_stat.st_nlink < 2) {
+        THROW_ERROR("random and urandom should be the same inode");
+    }
+    if (null_stat.st_dev != zero_stat.st_dev) {
+        THROW_ERROR("the devices should be in the same file system");
+    }
+    if (null_stat.st_dev == proc_stat.st_dev) {
+        THROW_ERROR("DevFS and procfs should have different device IDs");
+    }
+    return 0;
+}
+
 // ============================================================================
 // Test suite
 // ============================================================================
@@ -202,6 +227,7 @@ static test_case_t test_cases[] = {
     TEST_CASE(test_dev_arandom),
     TEST_CASE(test_dev_shm),
     TEST_CASE(test_dev_fd),
+    TEST_CASE(test_dev_inode_numbers),
 };
 
 int main() {
@@ -0,0 +1,232 @@
+#define _GNU_SOURCE
+#include <sys/stat.h>
+#include <sys/mount.h>
+#include <dirent.h>
+#include <errno.h>
+#include <fcntl.h>
+#include "test_fs.h"
+
+#define PLAIN_MNT_DIR           "/mnt_encfs"
+#define OBFUSCATED_MNT_DIR      "/mnt_encfs_obfuscated"
+#define PLAIN_HOST_DIR          "/host/encfs_test/plain"
+#define OBFUSCATED_HOST_DIR     "/host/encfs_test/obfuscated"
+
+static const char *test_content = "Hello from the EncFS\n";
+
+// ============================================================================
+// Helper function
+// ============================================================================
+
+static int write_file(const char *file_path, const char *content) {
+    int fd = open(file_path, O_WRONLY | O_CREAT | O_TRUNC, 00666);
+    if (fd < 0) {
+        THROW_ERROR("failed to open a file to write");
+    }
+    if (write(fd, content, strlen(content)) != strlen(content)) {
+        THROW_ERROR("failed to write");
+    }
+    // Flush the file so that the host sees the encrypted content
+    if (fsync(fd) < 0) {
+        THROW_ERROR("failed to fsync");
+    }
+    close(fd);
+    return 0;
+}
+
+static int check_dir_has_entry(const char *dir_path, const char *name, bool expected) {
+    struct dirent *dp;
+    bool found = false;
+
+    DIR *dirp = opendir(dir_path);
+    if (dirp == NULL) {
+        THROW_ERROR("failed to open directory: %s", dir_path);
+    }
+    while ((dp = readdir(dirp)) != NULL) {
+        if (strcmp(dp->d_name, name) == 0) {
+            found = true;
+            break;
+        }
+    }
+    closedir(dirp);
+
+    if (found != expected) {
+        THROW_ERROR("the entry %s is %s in %s", name, found ? "found" : "not found",
+                    dir_path);
+    }
+    return 0;
+}
+
+// ============================================================================
+// Test cases for encfs
+// ============================================================================
+
+static int test_mount_encfs() {
+    if (mkdir(PLAIN_MNT_DIR, 00775) < 0) {
+        THROW_ERROR("failed to mkdir: %s", PLAIN_MNT_DIR);
+    }
+    if (mount("encfs", PLAIN_MNT_DIR, "encfs", 0, "dir=./encfs_test/plain") < 0) {
+        THROW_ERROR("failed to mount encfs");
+    }
+
+    if (mkdir(OBFUSCATED_MNT_DIR, 00775) < 0) {
+        THROW_ERROR("failed to mkdir: %s", OBFUSCATED_MNT_DIR);
+    }
+    if (mount("encfs", OBFUSCATED_MNT_DIR, "encfs", 0,
+              "dir=./encfs_test/obfuscated,key=c7-32-b3-ed-44-df-ec-7b-25-2d-9a-32-38-8d-58-61,obfuscate_names")
+            < 0) {
+        THROW_ERROR("failed to mount encfs with obfuscated names");
+    }
+    return 0;
+}
+
+static int test_write_read_file() {
+    const char *file_path = PLAIN_MNT_DIR "/test_write_read.txt";
+    struct stat stat_buf;
+
+    if (write_file(file_path, test_content) < 0) {
+        return -1;
+    }
+    if (fs_check_file_content(file_path, test_content) < 0) {
+        THROW_ERROR("failed to check file content");
+    }
+    if (stat(file_path, &stat_buf) < 0) {
+        THROW_ERROR("failed to stat file");
+    }
+    if (stat_buf.st_size != strlen(test_content)) {
+        THROW_ERROR("the file size should be the size of the plaintext");
+    }
+    return 0;
+}
+
+static int test_content_encrypted_on_host() {
+    const char *file_path = PLAIN_MNT_DIR "/test_encrypted.txt";
+    const char *host_path = PLAIN_HOST_DIR "/test_encrypted.txt";
+    char buf[4096] = { 0 };
+
+    if (write_file(file_path, test_content) < 0) {
+        return -1;
+    }
+
+    int fd = open(host_path, O_RDONLY);
+    if (fd < 0) {
+        THROW_ERROR("the file should be visible on the host with the same name");
+    }
+    int len = read(fd, buf, sizeof(buf));
+    close(fd);
+    if (len < 0) {
+        THROW_ERROR("failed to read the file on the host");
+    }
+    if (memmem(buf, len, test_content, strlen(test_content)) != NULL) {
+        THROW_ERROR("the content on the host should be encrypted");
+    }
+    return 0;
+}
+
+static int test_names_obfuscated_on_host() {
+    const char *dir_path = OBFUSCATED_MNT_DIR "/secret_dir";
+    const char *file_path = OBFUSCATED_MNT_DIR "/secret_dir/secret_name.txt";
+
+    if (mkdir(dir_path, 00775) < 0) {
+        THROW_ERROR("failed to mkdir: %s", dir_path);
+    }
+    if (write_file(file_path, test_content) < 0) {
+        return -1;
+    }
+    if (fs_check_file_content(file_path, test_content) < 0) {
+        THROW_ERROR("failed to check file content");
+    }
+
+    if (check_dir_has_entry(OBFUSCATED_MNT_DIR, "secret_dir", true) < 0 ||
+            check_dir_has_entry(dir_path, "secret_name.txt", true) < 0) {
+        THROW_ERROR("the names should be visible inside the enclave");
+    }
+    if (check_dir_has_entry(OBFUSCATED_HOST_DIR, "secret_dir", false) < 0) {
+        THROW_ERROR("the names should be hidden on the host");
+    }
+    return 0;
+}
+
+static int test_rename() {
+    const char *old_path = OBFUSCATED_MNT_DIR "/secret_dir/secret_name.txt";
+    const char *new_path = OBFUSCATED_MNT_DIR "/renamed.txt";
+    struct stat stat_buf;
+
+    if (rename(old_path, new_path) < 0) {
+        THROW_ERROR("failed to rename");
+    }
+    if (stat(old_path, &stat_buf) == 0 || errno != ENOENT) {
+        THROW_ERROR("the old file should not exist");
+    }
+    if (fs_check_file_content(new_path, test_content) < 0) {
+        THROW_ERROR("failed to check the content of the renamed file");
+    }
+    if (unlink(new_path) < 0) {
+        THROW_ERROR("failed to unlink the renamed file");
+    }
+    return 0;
+}
+
+static int test_symlink() {
+    const char *target = "secret_dir";
+    const char *link_path = OBFUSCATED_MNT_DIR "/secret_link";
+    char buf[PATH_MAX] = { 0 };
+    struct stat stat_buf;
+
+    if (symlink(target, link_path) < 0) {
+        THROW_ERROR("failed to create symlink");
+    }
+    ssize_t len = readlink(link_path, buf, sizeof(buf));
+    if (len != strlen(target) || strncmp(buf, target, len) != 0) {
+        THROW_ERROR("failed to readlink");
+    }
+    if (lstat(link_path, &stat_buf) < 0 || stat_buf.st_size != strlen(target)) {
+        THROW_ERROR("the size of symlink should be the length of the target");
+    }
+    if (stat(link_path, &stat_buf) < 0 || !S_ISDIR(stat_buf.st_mode)) {
+        THROW_ERROR("failed to follow the symlink");
+    }
+    if (unlink(link_path) < 0) {
+        THROW_ERROR("failed to unlink the symlink");
+    }
+    return 0;
+}
+
+static int test_link_not_supported() {
+    const char *file_path = PLAIN_MNT_DIR "/test_write_read.txt";
+    const char *link_path = PLAIN_MNT_DIR "/test_hard_link.txt";
+
+    int ret = link(file_path, link_path);
+    if (!(ret < 0 && errno == EPERM)) {
+        THROW_ERROR("hard link should not be supported");
+    }
+    return 0;
+}
+
+static int test_umount_encfs() {
+    if (umount(PLAIN_MNT_DIR) < 0) {
+        THROW_ERROR("failed to umount encfs");
+    }
+    if (umount(OBFUSCATED_MNT_DIR) < 0) {
+        THROW_ERROR("failed to umount encfs with obfuscated names");
+    }
+    return 0;
+}
+
+// ============================================================================
+// Test suite main
+// ============================================================================
+
+static test_case_t test_cases[] = {
+    TEST_CASE(test_mount_encfs),
+    TEST_CASE(test_write_read_file),
+    TEST_CASE(test_content_encrypted_on_host),
+    TEST_CASE(test_names_obfuscated_on_host),
+    TEST_CASE(test_rename),
+    TEST_CASE(test_symlink),
+    TEST_CASE(test_link_not_supported),
+    TEST_CASE(test_umount_encfs),
+};
+
+int main(int argc, const char *argv[]) {
+    return test_suite_run(test_cases, ARRAY_SIZE(test_cases));
+}
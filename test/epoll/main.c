@@ -14,6 +14,7 @@
 #include <string.h>
 #include <errno.h>
 #include <stdarg.h>
+#include <stdbool.h>
 #include <pthread.h>
 #include "test.h"
 
@@ -163,6 +164,321 @@ int test_epoll_ctl_eventfd() {
     return 0;
 }
 
+int test_epoll_nested() {
+    int ret = -1;
+    int epfds[4];
+    for (int i = 0; i < 4; i++) {
+        epfds[i] = epoll_create1(0);
+        if (epfds[i] < 0) {
+            THROW_ERROR("epoll_create failed");
+        }
+    }
+
+    // Chain the epoll files as epfds[0] -> epfds[1] -> epfds[2] -> epfds[3]
+    struct epoll_event event = { .events = EPOLLIN };
+    for (int i = 0; i < 3; i++) {
+        if (epoll_ctl(epfds[i], EPOLL_CTL_ADD, epfds[i + 1], &event) < 0) {
+            printf("failed to nest epoll files at level %d\n", i);
+            goto out;
+        }
+    }
+
+    // The epoll files cannot monitor each other
+    if (epoll_ctl(epfds[2], EPOLL_CTL_ADD, epfds[1], &event) != -1 || errno != ELOOP) {
+        printf("a loop of epoll files should be rejected\n");
+        goto out;
+    }
+    if (epoll_ctl(epfds[3], EPOLL_CTL_ADD, epfds[0], &event) != -1 || errno != ELOOP) {
+        printf("an indirect loop of epoll files should be rejected\n");
+        goto out;
+    }
+
+    // An event is propagated through the nested epoll files
+    int event_fd = eventfd(0, EFD_NONBLOCK);
+    if (event_fd < 0) {
+        printf("failed to create an eventfd\n");
+        goto out;
+    }
+    uint64_t data = TEST_DATA;
+    if (epoll_ctl(epfds[2], EPOLL_CTL_ADD, event_fd, &event) < 0 ||
+            write(event_fd, &data, sizeof(data)) < 0) {
+        printf("failed to make the eventfd ready\n");
+        close(event_fd);
+        goto out;
+    }
+    struct epoll_event events[MAXEVENTS] = {0};
+    if (epoll_wait(epfds[1], events, MAXEVENTS, 1000) != 1) {
+        printf("the nested epoll file should be ready\n");
+        close(event_fd);
+        goto out;
+    }
+    close(event_fd);
+    ret = 0;
+out:
+    for (int i = 0; i < 4; i++) {
+        close(epfds[i]);
+    }
+    return ret;
+}
+
+// Nest seven epoll files, so that the last nesting would nest six levels.
+// The nesting is done from the innermost one or from the outermost one.
+static int __test_epoll_too_deep(bool inner_first) {
+    int ret = -1;
+    int epfds[7];
+    for (int i = 0; i < 7; i++) {
+        epfds[i] = epoll_create1(0);
+        if (epfds[i] < 0) {
+            THROW_ERROR("epoll_create failed");
+        }
+    }
+
+    struct epoll_event event = { .events = EPOLLIN };
+    for (int n = 0; n < 5; n++) {
+        int i = inner_first ? 5 - n : n;
+        if (epoll_ctl(epfds[i], EPOLL_CTL_ADD, epfds[i + 1], &event) < 0) {
+            printf("failed to nest epoll files at level %d\n", i);
+            goto out;
+        }
+    }
+    int last = inner_first ? 0 : 5;
+    if (epoll_ctl(epfds[last], EPOLL_CTL_ADD, epfds[last + 1], &event) != -1 ||
+            errno != ELOOP) {
+        printf("too deep nesting of epoll files should be rejected\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    for (int i = 0; i < 7; i++) {
+        close(epfds[i]);
+    }
+    return ret;
+}
+
+int test_epoll_too_deep() {
+    if (__test_epoll_too_deep(true) < 0) {
+        THROW_ERROR("failed to nest from the innermost epoll file");
+    }
+    if (__test_epoll_too_deep(false) < 0) {
+        THROW_ERROR("failed to nest from the outermost epoll file");
+    }
+    return 0;
+}
+
+int test_epoll_exclusive() {
+    int ret = -1;
+    int epfd = epoll_create1(0);
+    int other_epfd = epoll_create1(0);
+    int event_fd = eventfd(0, EFD_NONBLOCK);
+    if (epfd < 0 || other_epfd < 0 || event_fd < 0) {
+        THROW_ERROR("failed to create the files");
+    }
+
+    struct epoll_event event = { .events = EPOLLIN | EPOLLEXCLUSIVE | EPOLLONESHOT };
+    if (epoll_ctl(epfd, EPOLL_CTL_ADD, event_fd, &event) != -1 || errno != EINVAL) {
+        printf("EPOLLONESHOT cannot be used with EPOLLEXCLUSIVE\n");
+        goto out;
+    }
+    event.events = EPOLLIN | EPOLLEXCLUSIVE;
+    if (epoll_ctl(epfd, EPOLL_CTL_ADD, other_epfd, &event) != -1 || errno != EINVAL) {
+        printf("an epoll file cannot be added exclusively\n");
+        goto out;
+    }
+    // EPOLLWAKEUP is accepted but makes no difference
+    event.events = EPOLLIN | EPOLLEXCLUSIVE | EPOLLWAKEUP;
+    if (epoll_ctl(epfd, EPOLL_CTL_ADD, event_fd, &event) < 0 ||
+            epoll_ctl(other_epfd, EPOLL_CTL_ADD, event_fd, &event) < 0) {
+        printf("failed to add the eventfd exclusively\n");
+        goto out;
+    }
+    event.events = EPOLLIN;
+    if (epoll_ctl(epfd, EPOLL_CTL_MOD, event_fd, &event) != -1 || errno != EINVAL) {
+        printf("a file added exclusively cannot be modified\n");
+        goto out;
+    }
+
+    uint64_t data = TEST_DATA;
+    if (write(event_fd, &data, sizeof(data)) < 0) {
+        printf("failed to write the eventfd\n");
+        goto out;
+    }
+    struct epoll_event events[MAXEVENTS] = {0};
+    if (epoll_wait(epfd, events, MAXEVENTS, 1000) + epoll_wait(other_epfd, events, MAXEVENTS,
+            1000) < 1) {
+        printf("the eventfd should be ready in an epoll file\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(event_fd);
+    close(other_epfd);
+    close(epfd);
+    return ret;
+}
+
+static void *wait_exclusive_thread(void *arg) {
+    int epfd = *(int *)arg;
+    struct epoll_event events[MAXEVENTS] = {0};
+    long nfds = epoll_wait(epfd, events, MAXEVENTS, 5000);
+    return (void *)nfds;
+}
+
+// Same with Linux, an event goes to the next epoll file monitoring the file
+// exclusively if no thread is waiting on the previous one
+int test_epoll_exclusive_wakeup() {
+    int ret = -1;
+    int idle_epfd = epoll_create1(0);
+    int epfd = epoll_create1(0);
+    int event_fd = eventfd(0, EFD_NONBLOCK);
+    if (idle_epfd < 0 || epfd < 0 || event_fd < 0) {
+        THROW_ERROR("failed to create the files");
+    }
+
+    struct epoll_event event = { .events = EPOLLIN | EPOLLEXCLUSIVE };
+    if (epoll_ctl(idle_epfd, EPOLL_CTL_ADD, event_fd, &event) < 0 ||
+            epoll_ctl(epfd, EPOLL_CTL_ADD, event_fd, &event) < 0) {
+        printf("failed to add the eventfd exclusively\n");
+        goto out;
+    }
+
+    pthread_t tid;
+    if (pthread_create(&tid, NULL, wait_exclusive_thread, &epfd) != 0) {
+        printf("failed to create the thread\n");
+        goto out;
+    }
+    // Let the thread wait on the epoll file
+    usleep(100 * 1000);
+    uint64_t data = TEST_DATA;
+    if (write(event_fd, &data, sizeof(data)) < 0) {
+        printf("failed to write the eventfd\n");
+        pthread_join(tid, NULL);
+        goto out;
+    }
+    void *nfds;
+    pthread_join(tid, &nfds);
+    if ((long)nfds != 1) {
+        printf("the waiting thread should be woken up\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(event_fd);
+    close(epfd);
+    close(idle_epfd);
+    return ret;
+}
+
+// Same with Linux, the interest is kept until all the fds of the file are closed
+int test_epoll_close_dup() {
+    int ret = -1;
+    int epfd = epoll_create1(0);
+    int sockets[2];
+    if (epfd < 0 || socketpair(AF_UNIX, SOCK_STREAM, 0, sockets) < 0) {
+        THROW_ERROR("failed to create the files");
+    }
+    int dup_fd = dup(sockets[0]);
+    if (dup_fd < 0) {
+        THROW_ERROR("failed to dup");
+    }
+
+    struct epoll_event event = { .events = EPOLLIN, .data.fd = sockets[0] };
+    if (epoll_ctl(epfd, EPOLL_CTL_ADD, sockets[0], &event) < 0) {
+        printf("failed to add the socket\n");
+        goto out;
+    }
+    close(sockets[0]);
+
+    char buf = 'a';
+    if (write(sockets[1], &buf, 1) != 1) {
+        printf("failed to write the socket\n");
+        goto out;
+    }
+    struct epoll_event events[MAXEVENTS] = {0};
+    if (epoll_wait(epfd, events, MAXEVENTS, 1000) != 1 || events[0].data.fd != sockets[0]) {
+        printf("the socket should be monitored until all its fds are closed\n");
+        goto out;
+    }
+
+    close(dup_fd);
+    dup_fd = -1;
+    if (epoll_wait(epfd, events, MAXEVENTS, 100) != 0) {
+        printf("the closed socket should not be monitored\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    if (dup_fd >= 0) {
+        close(dup_fd);
+    }
+    close(sockets[1]);
+    close(epfd);
+    return ret;
+}
+
+// The epoll file inherited by a child keeps working after the parent closes it
+int test_epoll_spawn_inherit() {
+    int ret = -1;
+    int epfd = epoll_create1(0);
+    int event_fd = eventfd(0, EFD_NONBLOCK);
+    if (epfd < 0 || event_fd < 0) {
+        THROW_ERROR("failed to create the files");
+    }
+
+    struct epoll_event event = { .events = EPOLLIN, .data.fd = event_fd };
+    if (epoll_ctl(epfd, EPOLL_CTL_ADD, event_fd, &event) < 0) {
+        printf("failed to add the eventfd\n");
+        goto out;
+    }
+
+    char epfd_buf[16];
+    char event_fd_buf[16];
+    snprintf(epfd_buf, sizeof(epfd_buf), "%d", epfd);
+    snprintf(event_fd_buf, sizeof(event_fd_buf), "%d", event_fd);
+    const char *child_argv[4] = { "epoll", epfd_buf, event_fd_buf, NULL };
+    int child_pid;
+    if (posix_spawn(&child_pid, "/bin/epoll", NULL, NULL, (char *const *)child_argv,
+                    NULL) != 0) {
+        printf("failed to spawn a child process\n");
+        goto out;
+    }
+    close(epfd);
+    epfd = -1;
+
+    uint64_t data = TEST_DATA;
+    if (write(event_fd, &data, sizeof(data)) < 0) {
+        printf("failed to write the eventfd\n");
+        goto out;
+    }
+    int status = 0;
+    if (wait4(child_pid, &status, 0, NULL) < 0) {
+        printf("failed to wait4 the child process\n");
+        goto out;
+    }
+    if (!WIFEXITED(status) || WEXITSTATUS(status) != 0) {
+        printf("the child process failed to wait on the inherited epoll file\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(event_fd);
+    if (epfd >= 0) {
+        close(epfd);
+    }
+    return ret;
+}
+
+// Run by the child process spawned in test_epoll_spawn_inherit
+static int child_wait_inherited_epoll(int epfd, int event_fd) {
+    struct epoll_event events[MAXEVENTS] = {0};
+    if (epoll_wait(epfd, events, MAXEVENTS, 5000) != 1) {
+        THROW_ERROR("the eventfd should be ready in the inherited epoll file");
+    }
+    if (events[0].data.fd != event_fd || !(events[0].events & EPOLLIN)) {
+        THROW_ERROR("the event is not as expected");
+    }
+    return 0;
+}
+
 // ============================================================================
 // Test suite main
 // ============================================================================
@@ -170,8 +486,17 @@ int test_epoll_ctl_eventfd() {
 static test_case_t test_cases[] = {
     TEST_CASE(test_epoll_ctl_eventfd),
     TEST_CASE(test_epoll_ctl_uds),
+    TEST_CASE(test_epoll_nested),
+    TEST_CASE(test_epoll_too_deep),
+    TEST_CASE(test_epoll_exclusive),
+    TEST_CASE(test_epoll_exclusive_wakeup),
+    TEST_CASE(test_epoll_close_dup),
+    TEST_CASE(test_epoll_spawn_inherit),
 };
 
-int main() {
+int main(int argc, const char *argv[]) {
+    if (argc == 3) {
+        return child_wait_inherited_epoll(atoi(argv[1]), atoi(argv[2]));
+    }
     return test_suite_run(test_cases, ARRAY_SIZE(test_cases));
 }
@@ -0,0 +1,224 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <sys/stat.h>
+#include <sys/types.h>
+#include <sys/sysmacros.h>
+#include <fcntl.h>
+#include <pthread.h>
+#include <unistd.h>
+#include <stdlib.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "test.h"
+
+// ============================================================================
+// Helper function
+// ============================================================================
+static const char *fifo_paths[] = {
+    "/root/test_fifo",      // SEFS
+    "/dev/shm/test_fifo",   // RamFS
+};
+
+static const char *msg = "Hello FIFO!";
+
+static int create_fifo(const char *path) {
+    unlink(path);
+    if (mkfifo(path, 0666) < 0) {
+        THROW_ERROR("failed to create the fifo: %s", path);
+    }
+    return 0;
+}
+
+static void *open_and_write_fifo(void *arg) {
+    const char *path = (const char *)arg;
+    int fd = open(path, O_WRONLY);
+    if (fd < 0) {
+        printf("failed to open the fifo for write\n");
+        return (void *) -1;
+    }
+    if (write(fd, msg, strlen(msg)) != strlen(msg)) {
+        printf("failed to write the fifo\n");
+        close(fd);
+        return (void *) -1;
+    }
+    close(fd);
+    return NULL;
+}
+
+// ============================================================================
+// Test cases
+// ============================================================================
+static int __test_mkfifo(const char *path) {
+    struct stat stat_buf;
+    if (create_fifo(path) < 0) {
+        return -1;
+    }
+    if (stat(path, &stat_buf) < 0) {
+        THROW_ERROR("failed to stat the fifo");
+    }
+    if (!S_ISFIFO(stat_buf.st_mode)) {
+        THROW_ERROR("failed to check the fifo st_mode");
+    }
+    if (mkfifo(path, 0666) == 0 || errno != EEXIST) {
+        THROW_ERROR("create an existing fifo should fail with EEXIST");
+    }
+    if (unlink(path) < 0) {
+        THROW_ERROR("failed to unlink the fifo");
+    }
+    return 0;
+}
+
+static int __test_nonblocking_open(const char *path) {
+    if (create_fifo(path) < 0) {
+        return -1;
+    }
+
+    int wr_fd = open(path, O_WRONLY | O_NONBLOCK);
+    if (wr_fd >= 0 || errno != ENXIO) {
+        THROW_ERROR("open a fifo without reader for write should fail with ENXIO");
+    }
+
+    int rd_fd = open(path, O_RDONLY | O_NONBLOCK);
+    if (rd_fd < 0) {
+        THROW_ERROR("failed to open the fifo for read");
+    }
+    char buf[32] = {0};
+    if (read(rd_fd, buf, sizeof(buf)) != 0) {
+        THROW_ERROR("read a fifo without writer should return EOF");
+    }
+
+    wr_fd = open(path, O_WRONLY | O_NONBLOCK);
+    if (wr_fd < 0) {
+        THROW_ERROR("failed to open the fifo for write");
+    }
+    if (read(rd_fd, buf, sizeof(buf)) >= 0 || errno != EAGAIN) {
+        THROW_ERROR("read an empty fifo should fail with EAGAIN");
+    }
+    if (write(wr_fd, msg, strlen(msg)) != strlen(msg)) {
+        THROW_ERROR("failed to write the fifo");
+    }
+    close(wr_fd);
+
+    if (read(rd_fd, buf, sizeof(buf)) != strlen(msg) || strcmp(buf, msg) != 0) {
+        THROW_ERROR("the data read from the fifo is not as expected");
+    }
+    if (read(rd_fd, buf, sizeof(buf)) != 0) {
+        THROW_ERROR("read a fifo whose writer is closed should return EOF");
+    }
+    close(rd_fd);
+    unlink(path);
+    return 0;
+}
+
+static int __test_blocking_open(const char *path) {
+    pthread_t writer;
+    void *ret = NULL;
+    if (create_fifo(path) < 0) {
+        return -1;
+    }
+
+    if (pthread_create(&writer, NULL, open_and_write_fifo, (void *)path) != 0) {
+        THROW_ERROR("failed to create the writer thread");
+    }
+    // Block until the writer thread opens the fifo
+    int rd_fd = open(path, O_RDONLY);
+    if (rd_fd < 0) {
+        THROW_ERROR("failed to open the fifo for read");
+    }
+
+    char buf[32] = {0};
+    size_t len = 0;
+    while (len < strlen(msg)) {
+        ssize_t n = read(rd_fd, buf + len, sizeof(buf) - len - 1);
+        if (n <= 0) {
+            THROW_ERROR("failed to read the fifo");
+        }
+        len += n;
+    }
+    if (strcmp(buf, msg) != 0) {
+        THROW_ERROR("the data read from the fifo is not as expected");
+    }
+
+    pthread_join(writer, &ret);
+    if (ret != NULL) {
+        THROW_ERROR("the writer thread failed");
+    }
+    close(rd_fd);
+    unlink(path);
+    return 0;
+}
+
+static int __test_open_rdwr(const char *path) {
+    if (create_fifo(path) < 0) {
+        return -1;
+    }
+    // Opening a fifo for read and write should never block
+    int fd = open(path, O_RDWR);
+    if (fd < 0) {
+        THROW_ERROR("failed to open the fifo for read and write");
+    }
+    char buf[32] = {0};
+    if (write(fd, msg, strlen(msg)) != strlen(msg)) {
+        THROW_ERROR("failed to write the fifo");
+    }
+    if (read(fd, buf, sizeof(buf)) != strlen(msg) || strcmp(buf, msg) != 0) {
+        THROW_ERROR("the data read from the fifo is not as expected");
+    }
+    close(fd);
+    unlink(path);
+    return 0;
+}
+
+typedef int(*test_fifo_func_t)(const char *);
+
+static int test_fifo_framework(test_fifo_func_t fn) {
+    for (int i = 0; i < ARRAY_SIZE(fifo_paths); i++) {
+        if (fn(fifo_paths[i]) < 0) {
+            return -1;
+        }
+    }
+    return 0;
+}
+
+static int test_mkfifo() {
+    return test_fifo_framework(__test_mkfifo);
+}
+
+static int test_nonblocking_open() {
+    return test_fifo_framework(__test_nonblocking_open);
+}
+
+static int test_blocking_open() {
+    return test_fifo_framework(__test_blocking_open);
+}
+
+static int test_open_rdwr() {
+    return test_fifo_framework(__test_open_rdwr);
+}
+
+static int test_mknod_device() {
+    const char *path = "/root/test_mknod_device";
+    if (mknod(path, S_IFCHR | 0666, makedev(1, 3)) == 0 || errno != EPERM) {
+        THROW_ERROR("create a char device should fail with EPERM");
+    }
+    if (mknod(path, S_IFBLK | 0666, makedev(8, 0)) == 0 || errno != EPERM) {
+        THROW_ERROR("create a block device should fail with EPERM");
+    }
+    return 0;
+}
+
+// ============================================================================
+// Test suite
+// ============================================================================
+static test_case_t test_cases[] = {
+    TEST_CASE(test_mkfifo),
+    TEST_CASE(test_nonblocking_open),
+    TEST_CASE(test_blocking_open),
+    TEST_CASE(test_open_rdwr),
+    TEST_CASE(test_mknod_device),
+};
+
+int main(int argc, const char *argv[]) {
+    return test_suite_run(test_cases, ARRAY_SIZE(test_cases));
+}
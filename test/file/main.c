@@ -1,6 +1,7 @@
 #define _GNU_SOURCE
 #include <sys/stat.h>
 #include <sys/uio.h>
+#include <sys/socket.h>
 #include <errno.h>
 #include <fcntl.h>
 #include <stdlib.h>
@@ -468,6 +469,132 @@ static int __test_fallocate_collapse_range(const char *file_path) {
     return 0;
 }
 
+static int __test_sync_file_range(const char *file_path) {
+    char *write_str = "Hello World\n";
+    int fd, pipe_fds[2];
+
+    fd = open(file_path, O_WRONLY);
+    if (fd < 0) {
+        THROW_ERROR("failed to open a file to write");
+    }
+    if (write(fd, write_str, strlen(write_str)) <= 0) {
+        THROW_ERROR("failed to write");
+    }
+    unsigned int flags = SYNC_FILE_RANGE_WAIT_BEFORE | SYNC_FILE_RANGE_WRITE |
+                         SYNC_FILE_RANGE_WAIT_AFTER;
+    if (sync_file_range(fd, 0, strlen(write_str), flags) < 0) {
+        THROW_ERROR("failed to sync the file range");
+    }
+    // The zero nbytes means to the end of the file
+    if (sync_file_range(fd, 0, 0, SYNC_FILE_RANGE_WRITE) < 0) {
+        THROW_ERROR("failed to sync the file range to the end");
+    }
+    if (sync_file_range(fd, -1, 0, SYNC_FILE_RANGE_WRITE) == 0 || errno != EINVAL) {
+        THROW_ERROR("sync_file_range with negative offset should fail with EINVAL");
+    }
+    if (sync_file_range(fd, 0, 0, 0x8) == 0 || errno != EINVAL) {
+        THROW_ERROR("sync_file_range with invalid flags should fail with EINVAL");
+    }
+    close(fd);
+
+    if (pipe(pipe_fds) < 0) {
+        THROW_ERROR("failed to create a pipe");
+    }
+    int ret = sync_file_range(pipe_fds[1], 0, 0, SYNC_FILE_RANGE_WRITE);
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+    if (!(ret < 0 && errno == ESPIPE)) {
+        THROW_ERROR("sync_file_range on a pipe should fail with ESPIPE");
+    }
+
+    if (fs_check_file_content(file_path, write_str) < 0) {
+        THROW_ERROR("failed to check file content");
+    }
+    return 0;
+}
+
+static int __test_syncfs(const char *file_path) {
+    char *write_str = "Hello World\n";
+    int fd, socket_fd;
+
+    fd = open(file_path, O_WRONLY);
+    if (fd < 0) {
+        THROW_ERROR("failed to open a file to write");
+    }
+    if (write(fd, write_str, strlen(write_str)) <= 0) {
+        THROW_ERROR("failed to write");
+    }
+    if (syncfs(fd) < 0) {
+        THROW_ERROR("failed to sync the file system of the file");
+    }
+    close(fd);
+
+    // There is nothing to sync for a socket, but it is not an error
+    socket_fd = socket(AF_UNIX, SOCK_STREAM, 0);
+    if (socket_fd < 0) {
+        THROW_ERROR("failed to create a socket");
+    }
+    if (syncfs(socket_fd) < 0) {
+        THROW_ERROR("failed to syncfs with a socket");
+    }
+    close(socket_fd);
+
+    if (syncfs(-1) == 0 || errno != EBADF) {
+        THROW_ERROR("syncfs with an invalid fd should fail with EBADF");
+    }
+    return 0;
+}
+
+static int __test_fadvise_readahead(const char *file_path) {
+    int len = 64 * KB;
+    int fd;
+
+    fd = open(file_path, O_RDWR);
+    if (fd < 0) {
+        THROW_ERROR("failed to open a file");
+    }
+    if (fill_file_with_repeated_bytes(fd, len, 0xfe) < 0) {
+        THROW_ERROR("failed to fill the file");
+    }
+    if (posix_fadvise(fd, 0, 0, POSIX_FADV_SEQUENTIAL) != 0) {
+        THROW_ERROR("failed to advise sequential access");
+    }
+    if (posix_fadvise(fd, 0, len, POSIX_FADV_WILLNEED) != 0) {
+        THROW_ERROR("failed to advise the range will be needed");
+    }
+    // Prefetching the range beyond the end of file is fine
+    if (posix_fadvise(fd, len * 2, len, POSIX_FADV_WILLNEED) != 0) {
+        THROW_ERROR("failed to advise the range beyond the end");
+    }
+    if (posix_fadvise(fd, 0, 0, 100) != EINVAL) {
+        THROW_ERROR("posix_fadvise with invalid advice should fail with EINVAL");
+    }
+    if (readahead(fd, 0, len) < 0) {
+        THROW_ERROR("failed to readahead");
+    }
+    // The file position is not changed
+    if (lseek(fd, 0, SEEK_CUR) != len) {
+        THROW_ERROR("readahead should not change the file offset");
+    }
+    if (lseek(fd, 0, SEEK_SET) < 0) {
+        THROW_ERROR("failed to lseek");
+    }
+    if (check_file_with_repeated_bytes(fd, len, 0xfe) < 0) {
+        THROW_ERROR("failed to check the file content");
+    }
+    close(fd);
+
+    fd = open(file_path, O_WRONLY);
+    if (fd < 0) {
+        THROW_ERROR("failed to open a file to write");
+    }
+    if (readahead(fd, 0, len) == 0 || errno != EBADF) {
+        THROW_ERROR("readahead on a write-only file should fail with EBADF");
+    }
+    close(fd);
+    return 0;
+}
+
 typedef int(*test_file_func_t)(const char *);
 
 static int test_file_framework(test_file_func_t fn) {
@@ -529,6 +656,18 @@ static int test_fallocate_collapse_range() {
     return test_file_framework(__test_fallocate_collapse_range);
 }
 
+static int test_sync_file_range() {
+    return test_file_framework(__test_sync_file_range);
+}
+
+static int test_syncfs() {
+    return test_file_framework(__test_syncfs);
+}
+
+static int test_fadvise_readahead() {
+    return test_file_framework(__test_fadvise_readahead);
+}
+
 // ============================================================================
 // Test suite main
 // ============================================================================
@@ -545,6 +684,9 @@ static test_case_t test_cases[] = {
     TEST_CASE(test_fallocate_zero_range),
     TEST_CASE(test_fallocate_insert_range),
     TEST_CASE(test_fallocate_collapse_range),
+    TEST_CASE(test_sync_file_range),
+    TEST_CASE(test_syncfs),
+    TEST_CASE(test_fadvise_readahead),
 };
 
 int main(int argc, const char *argv[]) {
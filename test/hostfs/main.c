@@ -1,3 +1,4 @@
+#include <sys/mount.h>
 #include <sys/stat.h>
 #include <errno.h>
 #include <fcntl.h>
@@ -169,6 +170,45 @@ static int __test_truncate(const char *file_path) {
     return 0;
 }
 
+static int __test_symlink(const char *file_path) {
+    const char *link_path = "/host/hostfs_test_link";
+    const char *target = "hostfs_test.txt";
+    char *write_str = "Write to hostfs through symlink!";
+    char buf[PATH_MAX] = { 0 };
+    struct stat stat_buf;
+
+    if (symlink(target, link_path) < 0) {
+        THROW_ERROR("failed to create the symlink");
+    }
+    if (readlink(link_path, buf, sizeof(buf)) != strlen(target) || strcmp(buf, target) != 0) {
+        THROW_ERROR("failed to check the target of the symlink");
+    }
+    if (lstat(link_path, &stat_buf) < 0 || !S_ISLNK(stat_buf.st_mode)) {
+        THROW_ERROR("failed to check the symlink with lstat");
+    }
+    if (stat(link_path, &stat_buf) < 0 || !S_ISREG(stat_buf.st_mode)) {
+        THROW_ERROR("failed to check the target file with stat");
+    }
+    int fd = open(link_path, O_WRONLY);
+    if (fd < 0) {
+        THROW_ERROR("failed to open the file through the symlink");
+    }
+    if (write(fd, write_str, strlen(write_str)) <= 0) {
+        THROW_ERROR("failed to write to the file through the symlink");
+    }
+    close(fd);
+    if (fs_check_file_content(file_path, write_str) < 0) {
+        THROW_ERROR("failed to check file content");
+    }
+    if (unlink(link_path) < 0) {
+        THROW_ERROR("failed to unlink the symlink");
+    }
+    if (stat(file_path, &stat_buf) < 0) {
+        THROW_ERROR("the target file should not be removed");
+    }
+    return 0;
+}
+
 typedef int(*test_hostfs_func_t)(const char *);
 
 static int test_hostfs_framework(test_hostfs_func_t fn) {
@@ -210,6 +250,51 @@ static int test_truncate() {
     return test_hostfs_framework(__test_truncate);
 }
 
+static int test_symlink() {
+    return test_hostfs_framework(__test_symlink);
+}
+
+// The read-only attributes of sysfs, which is also mounted read-only in the
+// containers, cannot be opened for write on the host even by root, unlike the
+// files of mode 0444. So they tell whether a file opened for read only is
+// opened with read-only access on the host.
+#define RO_MNT_DIR      "/hostfs_ro_mnt"
+#define RO_HOST_DIR     "/sys/devices/system/cpu"
+#define RO_FILE_PATH    RO_MNT_DIR "/online"
+
+static int test_read_only_file() {
+    char read_buf[64] = { 0 };
+    int ret = -1;
+
+    if (mkdir(RO_MNT_DIR, 00775) < 0 && errno != EEXIST) {
+        THROW_ERROR("failed to create the mount point");
+    }
+    if (mount("hostfs", RO_MNT_DIR, "hostfs", 0, "dir=" RO_HOST_DIR) < 0) {
+        THROW_ERROR("failed to mount the read-only host dir");
+    }
+    int fd = open(RO_FILE_PATH, O_RDONLY);
+    if (fd < 0) {
+        printf("failed to open the read-only file\n");
+        goto out;
+    }
+    if (read(fd, read_buf, sizeof(read_buf) - 1) <= 0) {
+        close(fd);
+        printf("failed to read the read-only file\n");
+        goto out;
+    }
+    close(fd);
+    if (open(RO_FILE_PATH, O_WRONLY) >= 0) {
+        printf("the read-only file should not be opened for write\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    if (umount(RO_MNT_DIR) < 0) {
+        THROW_ERROR("failed to umount the read-only host dir");
+    }
+    return ret;
+}
+
 static int test_mkdir_then_rmdir() {
     const char *dir_path = "/host/hostfs_dir/";
     struct stat stat_buf;
@@ -241,6 +326,41 @@ static int test_mkdir_then_rmdir() {
     return 0;
 }
 
+static int test_dotdot_not_escape() {
+    const char *dir_path = "/host/hostfs_dir";
+    const char *link_path = "/host/hostfs_dir_link";
+    struct stat root_stat, host_stat, stat_buf;
+
+    if (stat("/", &root_stat) < 0 || stat("/host", &host_stat) < 0) {
+        THROW_ERROR("failed to stat the root or the mount point");
+    }
+    // The ".." of the mount root must not go to the parent dir on the host
+    if (stat("/host/..", &stat_buf) < 0) {
+        THROW_ERROR("failed to stat the parent of the mount point");
+    }
+    if (stat_buf.st_ino != root_stat.st_ino || stat_buf.st_dev != root_stat.st_dev) {
+        THROW_ERROR("the parent of the mount point is not the root");
+    }
+
+    if (mkdir(dir_path, 00775) < 0) {
+        THROW_ERROR("failed to create the dir");
+    }
+    if (symlink("hostfs_dir", link_path) < 0) {
+        THROW_ERROR("failed to create the symlink to the dir");
+    }
+    // The symlink is resolved in LibOS, so the ".." is the parent of the target
+    if (stat("/host/hostfs_dir_link/..", &stat_buf) < 0) {
+        THROW_ERROR("failed to stat the parent through the symlink");
+    }
+    if (stat_buf.st_ino != host_stat.st_ino || stat_buf.st_dev != host_stat.st_dev) {
+        THROW_ERROR("the parent of the symlink target is wrong");
+    }
+    if (unlink(link_path) < 0 || rmdir(dir_path) < 0) {
+        THROW_ERROR("failed to remove the symlink and the dir");
+    }
+    return 0;
+}
+
 // ============================================================================
 // Test suite main
 // ============================================================================
@@ -253,6 +373,9 @@ static test_case_t test_cases[] = {
     TEST_CASE(test_readdir),
     TEST_CASE(test_truncate),
     TEST_CASE(test_mkdir_then_rmdir),
+    TEST_CASE(test_symlink),
+    TEST_CASE(test_read_only_file),
+    TEST_CASE(test_dotdot_not_escape),
 };
 
 int main(int argc, const char *argv[]) {
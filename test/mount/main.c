@@ -1,5 +1,6 @@
 #include <sys/stat.h>
 #include <sys/mount.h>
+#include <sys/statfs.h>
 #include <errno.h>
 #include <fcntl.h>
 #include "test_fs.h"
@@ -213,6 +214,232 @@ static int test_umount_ramfs() {
     return test_mount_framework(__test_umount_fs, target, false);
 }
 
+static int is_in_proc_mounts(const char *target) {
+    char buf[4096] = { 0 };
+    char pattern[PATH_MAX] = { 0 };
+    int fd = open("/proc/mounts", O_RDONLY);
+    if (fd < 0) {
+        THROW_ERROR("failed to open /proc/mounts");
+    }
+    int len = 0, n;
+    while ((n = read(fd, buf + len, sizeof(buf) - 1 - len)) > 0) {
+        len += n;
+    }
+    close(fd);
+    if (n < 0) {
+        THROW_ERROR("failed to read /proc/mounts");
+    }
+    snprintf(pattern, sizeof(pattern), " %s ", target);
+    return strstr(buf, pattern) != NULL;
+}
+
+static int test_umount_unnormalized_path() {
+    const char *mnt_dir = "/mnt_unnormalized";
+    const char *umount_path = "/mnt_unnormalized/../mnt_unnormalized/";
+
+    if (create_dir(mnt_dir) < 0) {
+        THROW_ERROR("failed to create the mnt dir");
+    }
+    if (mount("ramfs", mnt_dir, "ramfs", 0, NULL) < 0) {
+        THROW_ERROR("failed to mount ramfs");
+    }
+    if (is_in_proc_mounts(mnt_dir) != 1) {
+        THROW_ERROR("the mount is not in /proc/mounts");
+    }
+    if (umount(umount_path) < 0) {
+        THROW_ERROR("failed to umount with an unnormalized path");
+    }
+    if (is_in_proc_mounts(mnt_dir) != 0) {
+        THROW_ERROR("the mount is still in /proc/mounts after umount");
+    }
+    return 0;
+}
+
+// ============================================================================
+// Test cases for the multi-layer unionfs
+// ============================================================================
+
+static int test_multi_layer_unionfs() {
+    const char *mnt_dir = "/mnt_multi_unionfs";
+    const char *addon_path = "/mnt_multi_unionfs/addon.txt";
+    const char *host_addon_path = "/host/mnt_test/mnt_multi_unionfs/addon/addon.txt";
+    const char *addon_content = "Hello from the add-on layer\n";
+    const char *new_content = "Hello from the writable layer\n";
+
+    if (create_dir(mnt_dir) < 0) {
+        THROW_ERROR("failed to create unionfs mnt dir");
+    }
+    // The RO layers from the uppermost to the lowest: HostFS, SEFS and RamFS
+    if (mount("unionfs", mnt_dir, "unionfs", 0,
+              "lowerdir=./mnt_test/mnt_multi_unionfs/addon:./mnt_test/mnt_multi_unionfs/lower:,"
+              "lowertype=hostfs:sefs:ramfs,upperdir=./mnt_test/mnt_multi_unionfs/upper") < 0) {
+        THROW_ERROR("failed to mount multi-layer unionfs");
+    }
+    if (fs_check_file_content(addon_path, addon_content) < 0) {
+        THROW_ERROR("the file in the add-on layer should be visible");
+    }
+
+    // The file is copied up to the writable layer when modified
+    int fd = open(addon_path, O_WRONLY | O_TRUNC);
+    if (fd < 0) {
+        THROW_ERROR("failed to open the file in the add-on layer to write");
+    }
+    if (write(fd, new_content, strlen(new_content)) != strlen(new_content)) {
+        THROW_ERROR("failed to write");
+    }
+    close(fd);
+    if (fs_check_file_content(addon_path, new_content) < 0) {
+        THROW_ERROR("the modified file should be visible");
+    }
+    if (fs_check_file_content(host_addon_path, addon_content) < 0) {
+        THROW_ERROR("the file in the read-only layer should not be modified");
+    }
+
+    // The file is hidden by the whiteout in the writable layer when removed
+    if (remove_file(addon_path) < 0) {
+        return -1;
+    }
+    if (check_file_no_exists(addon_path) < 0) {
+        return -1;
+    }
+    if (fs_check_file_content(host_addon_path, addon_content) < 0) {
+        THROW_ERROR("the file in the read-only layer should not be removed");
+    }
+
+    if (umount(mnt_dir) < 0) {
+        THROW_ERROR("failed to umount multi-layer unionfs");
+    }
+    return 0;
+}
+
+// ============================================================================
+// Test cases for the limits of ramfs
+// ============================================================================
+
+#define SIZED_RAMFS_BLOCK_SIZE      4096
+#define SIZED_RAMFS_BLOCKS          16
+#define SIZED_RAMFS_INODES          4
+
+static int check_ramfs_free(const char *mnt_dir, long bfree, long ffree) {
+    struct statfs statfs_buf;
+
+    if (statfs(mnt_dir, &statfs_buf) < 0) {
+        THROW_ERROR("failed to statfs: %s", mnt_dir);
+    }
+    if (statfs_buf.f_bsize != SIZED_RAMFS_BLOCK_SIZE ||
+            statfs_buf.f_blocks != SIZED_RAMFS_BLOCKS ||
+            statfs_buf.f_files != SIZED_RAMFS_INODES) {
+        THROW_ERROR("the limits in statfs are wrong");
+    }
+    if (statfs_buf.f_bfree != bfree || statfs_buf.f_ffree != ffree) {
+        THROW_ERROR("the free blocks or inodes in statfs are wrong");
+    }
+    return 0;
+}
+
+static int test_sized_ramfs() {
+    const char *mnt_dir = "/mnt_sized_ramfs";
+    const char *file_path = "/mnt_sized_ramfs/test_size.txt";
+    char buf[SIZED_RAMFS_BLOCK_SIZE * SIZED_RAMFS_BLOCKS] = { 0 };
+    char file_name[PATH_MAX] = { 0 };
+
+    if (create_dir(mnt_dir) < 0) {
+        THROW_ERROR("failed to create ramfs mnt dir");
+    }
+    if (mount("ramfs", mnt_dir, "ramfs", 0, "size=64k,nr_inodes=4") < 0) {
+        THROW_ERROR("failed to mount ramfs with limits");
+    }
+    // The root directory takes one inode
+    if (check_ramfs_free(mnt_dir, SIZED_RAMFS_BLOCKS, SIZED_RAMFS_INODES - 1) < 0) {
+        return -1;
+    }
+
+    int fd = open(file_path, O_RDWR | O_CREAT | O_TRUNC, 00666);
+    if (fd < 0) {
+        THROW_ERROR("failed to open a file to write");
+    }
+    if (write(fd, buf, sizeof(buf)) != sizeof(buf)) {
+        THROW_ERROR("failed to write the file up to the size limit");
+    }
+    if (check_ramfs_free(mnt_dir, 0, SIZED_RAMFS_INODES - 2) < 0) {
+        return -1;
+    }
+    int ret = write(fd, buf, 1);
+    if (!(ret < 0 && errno == ENOSPC)) {
+        THROW_ERROR("the write beyond the size limit should fail");
+    }
+    ret = ftruncate(fd, sizeof(buf) + 1);
+    if (!(ret < 0 && errno == ENOSPC)) {
+        THROW_ERROR("the truncate beyond the size limit should fail");
+    }
+    if (ftruncate(fd, SIZED_RAMFS_BLOCK_SIZE) < 0) {
+        THROW_ERROR("failed to shrink the file");
+    }
+    if (check_ramfs_free(mnt_dir, SIZED_RAMFS_BLOCKS - 1, SIZED_RAMFS_INODES - 2) < 0) {
+        return -1;
+    }
+    close(fd);
+    if (remove_file(file_path) < 0) {
+        return -1;
+    }
+    if (check_ramfs_free(mnt_dir, SIZED_RAMFS_BLOCKS, SIZED_RAMFS_INODES - 1) < 0) {
+        return -1;
+    }
+
+    for (int i = 0; i < SIZED_RAMFS_INODES; i++) {
+        snprintf(file_name, sizeof(file_name), "%s/test_inode_%d", mnt_dir, i);
+        fd = open(file_name, O_RDWR | O_CREAT, 00666);
+        if (i < SIZED_RAMFS_INODES - 1) {
+            if (fd < 0) {
+                THROW_ERROR("failed to create a file within the inode limit");
+            }
+            close(fd);
+        } else if (!(fd < 0 && errno == ENOSPC)) {
+            THROW_ERROR("the creation beyond the inode limit should fail");
+        }
+    }
+
+    if (umount(mnt_dir) < 0) {
+        THROW_ERROR("failed to umount ramfs with limits");
+    }
+    return 0;
+}
+
+// Same as tmpfs, the zero limits mean unlimited
+static int test_unlimited_ramfs() {
+    const char *mnt_dir = "/mnt_unlimited_ramfs";
+    const char *file_path = "/mnt_unlimited_ramfs/test_size.txt";
+    char buf[SIZED_RAMFS_BLOCK_SIZE * SIZED_RAMFS_BLOCKS] = { 0 };
+    struct statfs statfs_buf;
+
+    if (create_dir(mnt_dir) < 0) {
+        THROW_ERROR("failed to create ramfs mnt dir");
+    }
+    if (mount("ramfs", mnt_dir, "ramfs", 0, "size=0,nr_inodes=0") < 0) {
+        THROW_ERROR("failed to mount ramfs with zero limits");
+    }
+
+    int fd = open(file_path, O_RDWR | O_CREAT | O_TRUNC, 00666);
+    if (fd < 0) {
+        THROW_ERROR("failed to open a file to write");
+    }
+    if (write(fd, buf, sizeof(buf)) != sizeof(buf)) {
+        THROW_ERROR("failed to write the file");
+    }
+    close(fd);
+    if (statfs(mnt_dir, &statfs_buf) < 0) {
+        THROW_ERROR("failed to statfs: %s", mnt_dir);
+    }
+    if (statfs_buf.f_blocks != 0 || statfs_buf.f_files != 0) {
+        THROW_ERROR("the unlimited numbers should be shown as zeros");
+    }
+
+    if (umount(mnt_dir) < 0) {
+        THROW_ERROR("failed to umount ramfs with zero limits");
+    }
+    return 0;
+}
+
 // ============================================================================
 // Test suite main
 // ============================================================================
@@ -227,6 +454,10 @@ static test_case_t test_cases[] = {
     TEST_CASE(test_umount_hostfs),
     TEST_CASE(test_mount_ramfs),
     TEST_CASE(test_umount_ramfs),
+    TEST_CASE(test_umount_unnormalized_path),
+    TEST_CASE(test_multi_layer_unionfs),
+    TEST_CASE(test_sized_ramfs),
+    TEST_CASE(test_unlimited_ramfs),
 };
 
 int main(int argc, const char *argv[]) {
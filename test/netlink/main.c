@@ -0,0 +1,248 @@
+#include <sys/socket.h>
+#include <linux/netlink.h>
+#include <linux/rtnetlink.h>
+#include <net/if.h>
+#include <ifaddrs.h>
+#include <errno.h>
+#include <stdio.h>
+#include <string.h>
+#include <unistd.h>
+#include "test.h"
+
+// ============================================================================
+// Helper functions
+// ============================================================================
+
+static int open_route_socket(void) {
+    int sock = socket(AF_NETLINK, SOCK_RAW | SOCK_CLOEXEC, NETLINK_ROUTE);
+    if (sock < 0) {
+        THROW_ERROR("failed to create a netlink socket");
+    }
+    return sock;
+}
+
+static ssize_t __send_dump_request(int sock, int type) {
+    struct {
+        struct nlmsghdr hdr;
+        struct rtgenmsg gen;
+    } req;
+    memset(&req, 0, sizeof(req));
+    req.hdr.nlmsg_len = NLMSG_LENGTH(sizeof(struct rtgenmsg));
+    req.hdr.nlmsg_type = type;
+    req.hdr.nlmsg_flags = NLM_F_REQUEST | NLM_F_DUMP;
+    req.hdr.nlmsg_seq = 1;
+    req.gen.rtgen_family = AF_UNSPEC;
+
+    struct sockaddr_nl kernel = { .nl_family = AF_NETLINK };
+    ssize_t ret = sendto(sock, &req, req.hdr.nlmsg_len, 0, (struct sockaddr *)&kernel,
+                         sizeof(kernel));
+    return ret < 0 ? ret : ret - req.hdr.nlmsg_len;
+}
+
+static int send_dump_request(int sock, int type) {
+    if (__send_dump_request(sock, type) != 0) {
+        THROW_ERROR("failed to send the dump request");
+    }
+    return 0;
+}
+
+// Receive the replies of a dump until NLMSG_DONE and count the messages of the
+// given type. Set *found_lo if a link or an address of "lo" is seen.
+static int recv_dump(int sock, int type, int *num_msgs, int *found_lo) {
+    char buf[8192];
+    *num_msgs = 0;
+    *found_lo = 0;
+    while (1) {
+        ssize_t len = recv(sock, buf, sizeof(buf), 0);
+        if (len <= 0) {
+            THROW_ERROR("failed to receive the dump replies");
+        }
+        for (struct nlmsghdr *hdr = (struct nlmsghdr *)buf; NLMSG_OK(hdr, len);
+                hdr = NLMSG_NEXT(hdr, len)) {
+            if (hdr->nlmsg_seq != 1) {
+                THROW_ERROR("unexpected sequence number");
+            }
+            if (hdr->nlmsg_type == NLMSG_DONE) {
+                return 0;
+            }
+            if (hdr->nlmsg_type == NLMSG_ERROR) {
+                THROW_ERROR("the dump request failed");
+            }
+            if (hdr->nlmsg_type != type || !(hdr->nlmsg_flags & NLM_F_MULTI)) {
+                THROW_ERROR("unexpected reply");
+            }
+            (*num_msgs)++;
+
+            struct rtattr *attr;
+            int attr_len;
+            int name_type;
+            if (type == RTM_NEWLINK) {
+                struct ifinfomsg *ifi = NLMSG_DATA(hdr);
+                attr = IFLA_RTA(ifi);
+                attr_len = IFLA_PAYLOAD(hdr);
+                name_type = IFLA_IFNAME;
+            } else {
+                struct ifaddrmsg *ifa = NLMSG_DATA(hdr);
+                attr = IFA_RTA(ifa);
+                attr_len = IFA_PAYLOAD(hdr);
+                name_type = IFA_LABEL;
+            }
+            for (; RTA_OK(attr, attr_len); attr = RTA_NEXT(attr, attr_len)) {
+                if (attr->rta_type == name_type && strcmp(RTA_DATA(attr), "lo") == 0) {
+                    *found_lo = 1;
+                }
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Test cases for netlink sockets
+// ============================================================================
+
+static int test_unsupported_protocol() {
+    int sock = socket(AF_NETLINK, SOCK_RAW, NETLINK_GENERIC);
+    if (sock >= 0 || errno != EPROTONOSUPPORT) {
+        THROW_ERROR("NETLINK_GENERIC should not be supported");
+    }
+    return 0;
+}
+
+static int test_getsockname() {
+    int sock = open_route_socket();
+    if (sock < 0) {
+        return -1;
+    }
+
+    struct sockaddr_nl addr = { .nl_family = AF_NETLINK };
+    if (bind(sock, (struct sockaddr *)&addr, sizeof(addr)) < 0) {
+        close(sock);
+        THROW_ERROR("failed to bind the netlink socket");
+    }
+    socklen_t addr_len = sizeof(addr);
+    memset(&addr, 0, sizeof(addr));
+    if (getsockname(sock, (struct sockaddr *)&addr, &addr_len) < 0) {
+        close(sock);
+        THROW_ERROR("failed to get the socket name");
+    }
+    close(sock);
+
+    if (addr_len != sizeof(addr) || addr.nl_family != AF_NETLINK ||
+            addr.nl_pid != getpid()) {
+        THROW_ERROR("unexpected socket name");
+    }
+    return 0;
+}
+
+static int test_dump_links() {
+    int sock = open_route_socket();
+    if (sock < 0) {
+        return -1;
+    }
+
+    int num_msgs, found_lo;
+    if (send_dump_request(sock, RTM_GETLINK) < 0 ||
+            recv_dump(sock, RTM_NEWLINK, &num_msgs, &found_lo) < 0) {
+        close(sock);
+        return -1;
+    }
+    close(sock);
+
+    if (num_msgs == 0 || !found_lo) {
+        THROW_ERROR("the loopback link is not found");
+    }
+    return 0;
+}
+
+static int test_dump_addrs() {
+    int sock = open_route_socket();
+    if (sock < 0) {
+        return -1;
+    }
+
+    int num_msgs, found_lo;
+    if (send_dump_request(sock, RTM_GETADDR) < 0 ||
+            recv_dump(sock, RTM_NEWADDR, &num_msgs, &found_lo) < 0) {
+        close(sock);
+        return -1;
+    }
+    close(sock);
+
+    if (num_msgs == 0 || !found_lo) {
+        THROW_ERROR("the loopback address is not found");
+    }
+    return 0;
+}
+
+// The dumps are received in parts as the receive buffer allows
+static int test_dump_with_small_recv_buf() {
+    int sock = open_route_socket();
+    if (sock < 0) {
+        return -1;
+    }
+
+    int rcvbuf = 0;
+    socklen_t optlen = sizeof(rcvbuf);
+    if (setsockopt(sock, SOL_SOCKET, SO_RCVBUF, &rcvbuf, sizeof(rcvbuf)) < 0 ||
+            getsockopt(sock, SOL_SOCKET, SO_RCVBUF, &rcvbuf, &optlen) < 0) {
+        close(sock);
+        THROW_ERROR("failed to set the receive buffer size");
+    }
+    if (rcvbuf <= 0 || rcvbuf >= 212992) {
+        close(sock);
+        THROW_ERROR("the receive buffer size is not changed");
+    }
+
+    int num_msgs, found_lo;
+    if (send_dump_request(sock, RTM_GETLINK) < 0 ||
+            recv_dump(sock, RTM_NEWLINK, &num_msgs, &found_lo) < 0 ||
+            send_dump_request(sock, RTM_GETADDR) < 0 ||
+            recv_dump(sock, RTM_NEWADDR, &num_msgs, &found_lo) < 0) {
+        close(sock);
+        return -1;
+    }
+    close(sock);
+
+    if (!found_lo) {
+        THROW_ERROR("the loopback address is not found");
+    }
+    return 0;
+}
+
+static int test_getifaddrs() {
+    struct ifaddrs *ifaddrs;
+    if (getifaddrs(&ifaddrs) < 0) {
+        THROW_ERROR("getifaddrs failed");
+    }
+
+    int found_lo = 0;
+    for (struct ifaddrs *ifa = ifaddrs; ifa != NULL; ifa = ifa->ifa_next) {
+        if (strcmp(ifa->ifa_name, "lo") == 0 && ifa->ifa_addr != NULL &&
+                ifa->ifa_addr->sa_family == AF_INET && (ifa->ifa_flags & IFF_LOOPBACK)) {
+            found_lo = 1;
+        }
+    }
+    freeifaddrs(ifaddrs);
+
+    if (!found_lo) {
+        THROW_ERROR("the IPv4 address of lo is not found");
+    }
+    return 0;
+}
+
+// ============================================================================
+// Test suite main
+// ============================================================================
+
+static test_case_t test_cases[] = {
+    TEST_CASE(test_unsupported_protocol),
+    TEST_CASE(test_getsockname),
+    TEST_CASE(test_dump_links),
+    TEST_CASE(test_dump_addrs),
+    TEST_CASE(test_dump_with_small_recv_buf),
+    TEST_CASE(test_getifaddrs),
+};
+
+int main() {
+    return test_suite_run(test_cases, ARRAY_SIZE(test_cases));
+}
@@ -0,0 +1,359 @@
+#define _GNU_SOURCE
+#include <sys/socket.h>
+#include <sys/un.h>
+#include <sys/wait.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+#include <errno.h>
+#include <poll.h>
+#include <spawn.h>
+#include <string.h>
+#include <unistd.h>
+#include "test.h"
+
+// The network policy is given in Occlum.json:
+//
+// egress:
+//   0.0.0.0/0 with the ports other than 9990-9999
+//   127.0.0.1:9990 for TCP only
+//   policy.test:9991, where policy.test is 127.0.0.2 in the network hosts
+//   port 9992 for /bin/client only
+//   /host/unix_socket_host_path
+// ingress:
+//   0.0.0.0/0 with the ports other than 9990-9991
+//   [::1]:0 for TCP only
+//   /host/unix_socket_host_path
+
+#define TCP_ONLY_PORT       9990
+#define NAMED_HOST_PORT     9991
+#define CLIENT_ONLY_PORT    9992
+#define DENIED_PORT         9993
+#define NAMED_HOST_ADDR     "127.0.0.2"
+#define DENIED_HOST_SOCK    "/host/network_policy.sock"
+#define MSG                 "network policy"
+
+// ============================================================================
+// Helper functions
+// ============================================================================
+
+static void init_addr(struct sockaddr_in *addr, const char *ip, in_port_t port) {
+    memset(addr, 0, sizeof(*addr));
+    addr->sin_family = AF_INET;
+    addr->sin_port = htons(port);
+    inet_pton(AF_INET, ip, &addr->sin_addr);
+}
+
+static void init_addr6(struct sockaddr_in6 *addr, const char *ip, in_port_t port) {
+    memset(addr, 0, sizeof(*addr));
+    addr->sin6_family = AF_INET6;
+    addr->sin6_port = htons(port);
+    inet_pton(AF_INET6, ip, &addr->sin6_addr);
+}
+
+// Connect a new socket to the address, and return the errno, or 0 on success
+static int connect_errno(int domain, int type, const struct sockaddr *addr,
+                         socklen_t addr_len) {
+    int sock = socket(domain, type, 0);
+    if (sock < 0) {
+        THROW_ERROR("failed to create the socket");
+    }
+    int err = connect(sock, addr, addr_len) < 0 ? errno : 0;
+    close(sock);
+    return err;
+}
+
+// ============================================================================
+// Test cases for egress
+// ============================================================================
+
+static int test_connect() {
+    struct sockaddr_in addr;
+
+    // Denied by the protocols
+    init_addr(&addr, "127.0.0.1", TCP_ONLY_PORT);
+    if (connect_errno(AF_INET, SOCK_DGRAM, (struct sockaddr *)&addr, sizeof(addr)) != EACCES) {
+        THROW_ERROR("UDP connect to a TCP only port should be denied");
+    }
+    // Allowed, but no one is listening
+    if (connect_errno(AF_INET, SOCK_STREAM, (struct sockaddr *)&addr, sizeof(addr)) == EACCES) {
+        THROW_ERROR("TCP connect to a TCP only port should be allowed");
+    }
+    // Denied by the hosts
+    init_addr(&addr, "127.0.0.3", TCP_ONLY_PORT);
+    if (connect_errno(AF_INET, SOCK_STREAM, (struct sockaddr *)&addr, sizeof(addr)) != EACCES) {
+        THROW_ERROR("connect to a denied host should be denied");
+    }
+    // Denied by the ports
+    init_addr(&addr, "127.0.0.1", DENIED_PORT);
+    if (connect_errno(AF_INET, SOCK_STREAM, (struct sockaddr *)&addr, sizeof(addr)) != EACCES) {
+        THROW_ERROR("connect to a denied port should be denied");
+    }
+    return 0;
+}
+
+static int test_connect_ipv4_mapped() {
+    struct sockaddr_in6 addr;
+
+    init_addr6(&addr, "::ffff:127.0.0.1", TCP_ONLY_PORT);
+    if (connect_errno(AF_INET6, SOCK_STREAM, (struct sockaddr *)&addr, sizeof(addr)) == EACCES) {
+        THROW_ERROR("the IPv4-mapped address should be allowed by the IPv4 rule");
+    }
+    init_addr6(&addr, "::ffff:127.0.0.1", DENIED_PORT);
+    if (connect_errno(AF_INET6, SOCK_STREAM, (struct sockaddr *)&addr, sizeof(addr)) != EACCES) {
+        THROW_ERROR("the IPv4-mapped address should be denied as the IPv4 one");
+    }
+    // Not the IPv4 address of the rule
+    init_addr6(&addr, "::ffff:127.0.0.3", TCP_ONLY_PORT);
+    if (connect_errno(AF_INET6, SOCK_STREAM, (struct sockaddr *)&addr, sizeof(addr)) != EACCES) {
+        THROW_ERROR("the IPv4-mapped address of a denied host should be denied");
+    }
+    // No IPv6 egress rule matches
+    init_addr6(&addr, "::1", TCP_ONLY_PORT);
+    if (connect_errno(AF_INET6, SOCK_STREAM, (struct sockaddr *)&addr, sizeof(addr)) != EACCES) {
+        THROW_ERROR("connect to an IPv6 address should be denied");
+    }
+    return 0;
+}
+
+static int test_sendto() {
+    struct sockaddr_in addr;
+    int ret = -1;
+
+    int sock = socket(AF_INET, SOCK_DGRAM, 0);
+    if (sock < 0) {
+        THROW_ERROR("failed to create the socket");
+    }
+
+    init_addr(&addr, "127.0.0.1", TCP_ONLY_PORT);
+    if (sendto(sock, MSG, sizeof(MSG), 0, (struct sockaddr *)&addr, sizeof(addr)) != -1 ||
+            errno != EACCES) {
+        printf("sendto a denied address should fail with EACCES\n");
+        goto out;
+    }
+    struct iovec iov = { .iov_base = MSG, .iov_len = sizeof(MSG) };
+    struct msghdr msg = {
+        .msg_name = &addr,
+        .msg_namelen = sizeof(addr),
+        .msg_iov = &iov,
+        .msg_iovlen = 1,
+    };
+    if (sendmsg(sock, &msg, 0) != -1 || errno != EACCES) {
+        printf("sendmsg to a denied address should fail with EACCES\n");
+        goto out;
+    }
+    init_addr(&addr, NAMED_HOST_ADDR, NAMED_HOST_PORT);
+    if (sendto(sock, MSG, sizeof(MSG), 0, (struct sockaddr *)&addr, sizeof(addr)) != sizeof(MSG)) {
+        printf("sendto an allowed address should succeed\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(sock);
+    return ret;
+}
+
+static int test_sendmmsg() {
+    struct sockaddr_in allowed_addr, denied_addr;
+    struct iovec iov = { .iov_base = MSG, .iov_len = sizeof(MSG) };
+    struct mmsghdr msgs[2];
+    int ret = -1;
+
+    int sock = socket(AF_INET, SOCK_DGRAM, 0);
+    if (sock < 0) {
+        THROW_ERROR("failed to create the socket");
+    }
+    init_addr(&allowed_addr, NAMED_HOST_ADDR, NAMED_HOST_PORT);
+    init_addr(&denied_addr, "127.0.0.1", DENIED_PORT);
+    memset(msgs, 0, sizeof(msgs));
+    for (int i = 0; i < ARRAY_SIZE(msgs); i++) {
+        msgs[i].msg_hdr.msg_namelen = sizeof(struct sockaddr_in);
+        msgs[i].msg_hdr.msg_iov = &iov;
+        msgs[i].msg_hdr.msg_iovlen = 1;
+    }
+
+    // The messages are sent until the first denied one
+    msgs[0].msg_hdr.msg_name = &allowed_addr;
+    msgs[1].msg_hdr.msg_name = &denied_addr;
+    if (sendmmsg(sock, msgs, 2, 0) != 1 || msgs[0].msg_len != sizeof(MSG)) {
+        printf("sendmmsg should send the messages before the denied one\n");
+        goto out;
+    }
+    msgs[0].msg_hdr.msg_name = &denied_addr;
+    msgs[1].msg_hdr.msg_name = &allowed_addr;
+    if (sendmmsg(sock, msgs, 2, 0) != -1 || errno != EACCES) {
+        printf("sendmmsg should fail with EACCES if the first message is denied\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(sock);
+    return ret;
+}
+
+static int test_network_hosts() {
+    struct sockaddr_in addr;
+
+    // policy.test is resolved with the network hosts of Occlum.json
+    init_addr(&addr, NAMED_HOST_ADDR, NAMED_HOST_PORT);
+    if (connect_errno(AF_INET, SOCK_DGRAM, (struct sockaddr *)&addr, sizeof(addr)) != 0) {
+        THROW_ERROR("connect to the address of the host name should be allowed");
+    }
+    init_addr(&addr, "127.0.0.1", NAMED_HOST_PORT);
+    if (connect_errno(AF_INET, SOCK_DGRAM, (struct sockaddr *)&addr, sizeof(addr)) != EACCES) {
+        THROW_ERROR("connect to another address should be denied");
+    }
+    return 0;
+}
+
+static int test_entry_points() {
+    struct sockaddr_in addr;
+    struct pollfd poll_fd;
+    int ret = -1;
+    int status;
+
+    // Only /bin/client may connect to the port
+    init_addr(&addr, "127.0.0.1", CLIENT_ONLY_PORT);
+    if (connect_errno(AF_INET, SOCK_STREAM, (struct sockaddr *)&addr, sizeof(addr)) != EACCES) {
+        THROW_ERROR("connect to a port of another entry point should be denied");
+    }
+
+    int listen_fd = socket(AF_INET, SOCK_STREAM, 0);
+    if (listen_fd < 0) {
+        THROW_ERROR("failed to create the listening socket");
+    }
+    if (bind(listen_fd, (struct sockaddr *)&addr, sizeof(addr)) < 0 || listen(listen_fd, 1) < 0) {
+        close(listen_fd);
+        THROW_ERROR("failed to listen");
+    }
+
+    int child_pid;
+    char port_string[8];
+    snprintf(port_string, sizeof(port_string), "%d", CLIENT_ONLY_PORT);
+    char *client_argv[] = {"client", "127.0.0.1", port_string, NULL};
+    if (posix_spawn(&child_pid, "/bin/client", NULL, NULL, client_argv, NULL) < 0) {
+        close(listen_fd);
+        THROW_ERROR("failed to spawn the client");
+    }
+
+    poll_fd.fd = listen_fd;
+    poll_fd.events = POLLIN;
+    if (poll(&poll_fd, 1, 5000) != 1) {
+        printf("the client should be allowed to connect\n");
+        goto out;
+    }
+    int server_fd = accept(listen_fd, NULL, NULL);
+    if (server_fd < 0) {
+        printf("failed to accept\n");
+        goto out;
+    }
+    close(server_fd);
+    ret = 0;
+out:
+    close(listen_fd);
+    // The client fails as no message is exchanged, which does not matter
+    waitpid(child_pid, &status, 0);
+    return ret;
+}
+
+static int test_unix_socket_egress() {
+    struct sockaddr_un addr = { .sun_family = AF_UNIX };
+    strncpy(addr.sun_path, DENIED_HOST_SOCK, sizeof(addr.sun_path) - 1);
+
+    // The path in HostFS is bridged to the host, so it is checked
+    if (connect_errno(AF_UNIX, SOCK_STREAM, (struct sockaddr *)&addr, sizeof(addr)) != EACCES) {
+        THROW_ERROR("connect to a denied host path should be denied");
+    }
+    return 0;
+}
+
+// ============================================================================
+// Test cases for ingress
+// ============================================================================
+
+static int test_bind() {
+    struct sockaddr_in addr;
+    int ret = -1;
+
+    int sock = socket(AF_INET, SOCK_STREAM, 0);
+    if (sock < 0) {
+        THROW_ERROR("failed to create the socket");
+    }
+    init_addr(&addr, "127.0.0.1", TCP_ONLY_PORT);
+    if (bind(sock, (struct sockaddr *)&addr, sizeof(addr)) != -1 || errno != EACCES) {
+        printf("bind to a denied port should fail with EACCES\n");
+        goto out;
+    }
+    init_addr(&addr, "127.0.0.1", 0);
+    if (bind(sock, (struct sockaddr *)&addr, sizeof(addr)) < 0) {
+        printf("bind to an allowed port should succeed\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(sock);
+    return ret;
+}
+
+static int test_listen() {
+    struct sockaddr_in6 addr;
+    int ret = -1;
+
+    int sock = socket(AF_INET6, SOCK_STREAM, 0);
+    if (sock < 0) {
+        THROW_ERROR("failed to create the socket");
+    }
+    // Binding to port 0 is allowed, but the port picked is not
+    init_addr6(&addr, "::1", 0);
+    if (bind(sock, (struct sockaddr *)&addr, sizeof(addr)) < 0) {
+        printf("bind to an allowed address should succeed\n");
+        goto out;
+    }
+    if (listen(sock, 1) != -1 || errno != EACCES) {
+        printf("listen on a denied address should fail with EACCES\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(sock);
+    return ret;
+}
+
+static int test_unix_socket_ingress() {
+    struct sockaddr_un addr = { .sun_family = AF_UNIX };
+    strncpy(addr.sun_path, DENIED_HOST_SOCK, sizeof(addr.sun_path) - 1);
+    int ret = -1;
+
+    int sock = socket(AF_UNIX, SOCK_STREAM, 0);
+    if (sock < 0) {
+        THROW_ERROR("failed to create the socket");
+    }
+    if (bind(sock, (struct sockaddr *)&addr, sizeof(addr)) != -1 || errno != EACCES) {
+        printf("bind to a denied host path should fail with EACCES\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(sock);
+    return ret;
+}
+
+// ============================================================================
+// Test suite main
+// ============================================================================
+
+static test_case_t test_cases[] = {
+    TEST_CASE(test_connect),
+    TEST_CASE(test_connect_ipv4_mapped),
+    TEST_CASE(test_sendto),
+    TEST_CASE(test_sendmmsg),
+    TEST_CASE(test_network_hosts),
+    TEST_CASE(test_entry_points),
+    TEST_CASE(test_unix_socket_egress),
+    TEST_CASE(test_bind),
+    TEST_CASE(test_listen),
+    TEST_CASE(test_unix_socket_ingress),
+};
+
+int main(int argc, const char *argv[]) {
+    return test_suite_run(test_cases, ARRAY_SIZE(test_cases));
+}
@@ -28,6 +28,50 @@ static void free_pipe(int *pipe) {
 // ============================================================================
 // Test cases
 // ============================================================================
+int test_fcntl_pipe_size() {
+    int pipe_fds[2];
+    char buf[8192] = { 0 };
+    int ret = -1;
+
+    if (pipe(pipe_fds) < 0) {
+        THROW_ERROR("failed to create a pipe");
+    }
+    // Same as Linux, the default size is 64KiB
+    if (fcntl(pipe_fds[0], F_GETPIPE_SZ) != 65536) {
+        printf("wrong default pipe size\n");
+        goto out;
+    }
+    // The size is rounded up to a power of two pages
+    if (fcntl(pipe_fds[1], F_SETPIPE_SZ, 100000) != 131072 ||
+            fcntl(pipe_fds[0], F_GETPIPE_SZ) != 131072) {
+        printf("failed to set the pipe size\n");
+        goto out;
+    }
+    if (write(pipe_fds[1], buf, sizeof(buf)) != sizeof(buf)) {
+        printf("failed to write the pipe\n");
+        goto out;
+    }
+    // The size cannot be less than the data in the pipe
+    if (fcntl(pipe_fds[0], F_SETPIPE_SZ, 4096) != -1 || errno != EBUSY) {
+        printf("shrinking the pipe below its data should fail with EBUSY\n");
+        goto out;
+    }
+    if (fcntl(pipe_fds[0], F_SETPIPE_SZ, 8192) != 8192 ||
+            read(pipe_fds[0], buf, sizeof(buf)) != sizeof(buf)) {
+        printf("the data should be kept after shrinking the pipe\n");
+        goto out;
+    }
+    // The size is limited by /proc/sys/fs/pipe-max-size, 1MiB by default
+    if (fcntl(pipe_fds[0], F_SETPIPE_SZ, 2 * 1024 * 1024) != -1 || errno != EPERM) {
+        printf("the pipe size beyond pipe-max-size should fail with EPERM\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    free_pipe(pipe_fds);
+    return ret;
+}
+
 int test_fstat() {
     int pipe_fds[2];
     struct stat stat_bufs[2];
@@ -392,6 +436,7 @@ static test_case_t test_cases[] = {
     TEST_CASE(test_fstat),
     TEST_CASE(test_fcntl_get_flags),
     TEST_CASE(test_fcntl_set_flags),
+    TEST_CASE(test_fcntl_pipe_size),
     TEST_CASE(test_create_with_flags),
     TEST_CASE(test_select_timeout),
     TEST_CASE(test_poll_timeout),
@@ -1,6 +1,7 @@
 #define _GNU_SOURCE
 #include <sys/types.h>
 #include <sys/vfs.h>
+#include <sys/stat.h>
 #include <fcntl.h>
 #include <limits.h>
 #include <stdlib.h>
@@ -215,6 +216,226 @@ static int test_read_from_proc_stat() {
     return 0;
 }
 
+static int test_read_from_proc_self_status() {
+    const char *proc_self_status = "/proc/self/status";
+    FILE *fp = fopen(proc_self_status, "r");
+    if (fp == NULL) {
+        THROW_ERROR("failed to fopen: %s", proc_self_status);
+    }
+
+    char line[256] = { 0 };
+    int pid = -1, threads = -1;
+    while (fgets(line, sizeof(line), fp) != NULL) {
+        sscanf(line, "Pid: %d", &pid);
+        sscanf(line, "Threads: %d", &threads);
+    }
+    fclose(fp);
+    if (pid != getpid() || threads != 1) {
+        THROW_ERROR("failed to check the result in %s", proc_self_status);
+    }
+    return 0;
+}
+
+static int test_read_from_proc_self_environ() {
+    const char *proc_environ = "/proc/self/environ";
+    const char *expected_env = "OCCLUM=yes";
+    char buf[4096] = { 0 };
+
+    int fd = open(proc_environ, O_RDONLY);
+    if (fd < 0) {
+        THROW_ERROR("failed to open file: %s", proc_environ);
+    }
+    int len = read(fd, buf, sizeof(buf) - 1);
+    close(fd);
+    if (len <= 0) {
+        THROW_ERROR("failed to read: %s", proc_environ);
+    }
+    // The environment variables are separated by null bytes
+    for (char *env = buf; env < buf + len; env += strlen(env) + 1) {
+        if (strcmp(env, expected_env) == 0) {
+            return 0;
+        }
+    }
+    THROW_ERROR("failed to find %s in %s", expected_env, proc_environ);
+}
+
+static int test_read_from_proc_self_limits() {
+    const char *proc_limits = "/proc/self/limits";
+
+    if (test_read_from_procfs(proc_limits) < 0) {
+        THROW_ERROR("failed to read the limits");
+    }
+    return 0;
+}
+
+static int test_read_from_proc_mounts() {
+    const char *proc_mounts = "/proc/mounts";
+    const char *proc_mountinfo = "/proc/self/mountinfo";
+
+    if (test_read_from_procfs(proc_mounts) < 0) {
+        THROW_ERROR("failed to read the mounts");
+    }
+    if (test_read_from_procfs(proc_mountinfo) < 0) {
+        THROW_ERROR("failed to read the mountinfo");
+    }
+    return 0;
+}
+
+static int test_read_from_proc_uptime() {
+    const char *proc_uptime = "/proc/uptime";
+    FILE *fp = fopen(proc_uptime, "r");
+    if (fp == NULL) {
+        THROW_ERROR("failed to fopen: %s", proc_uptime);
+    }
+
+    double uptime, idle_time;
+    int ret = fscanf(fp, "%lf %lf", &uptime, &idle_time);
+    fclose(fp);
+    if (ret != 2 || uptime <= 0) {
+        THROW_ERROR("failed to check the result in %s", proc_uptime);
+    }
+    return 0;
+}
+
+static int test_read_from_proc_loadavg() {
+    const char *proc_loadavg = "/proc/loadavg";
+    FILE *fp = fopen(proc_loadavg, "r");
+    if (fp == NULL) {
+        THROW_ERROR("failed to fopen: %s", proc_loadavg);
+    }
+
+    double avg1, avg5, avg15;
+    int running, total, last_pid;
+    int ret = fscanf(fp, "%lf %lf %lf %d/%d %d", &avg1, &avg5, &avg15, &running, &total,
+                     &last_pid);
+    fclose(fp);
+    if (ret != 6 || running < 1 || total < running || last_pid < getpid()) {
+        THROW_ERROR("failed to check the result in %s", proc_loadavg);
+    }
+    return 0;
+}
+
+static int test_read_from_proc_version() {
+    const char *proc_version = "/proc/version";
+
+    if (test_read_from_procfs(proc_version) < 0) {
+        THROW_ERROR("failed to read the version");
+    }
+    return 0;
+}
+
+static int write_to_procfs(const char *proc_inode, const char *val) {
+    int fd = open(proc_inode, O_WRONLY | O_TRUNC);
+    if (fd < 0) {
+        THROW_ERROR("failed to open file: %s", proc_inode);
+    }
+    int ret = write(fd, val, strlen(val));
+    close(fd);
+    return ret < 0 ? -1 : 0;
+}
+
+static int test_read_from_proc_sys() {
+    const char *proc_sys_files[] = {
+        "/proc/sys/kernel/hostname",
+        "/proc/sys/kernel/pid_max",
+        "/proc/sys/kernel/shmmax",
+        "/proc/sys/kernel/shmmni",
+        "/proc/sys/fs/pipe-max-size",
+        "/proc/sys/fs/sgx-file-cache-max",
+        "/proc/sys/fs/sgx-file-cache-state",
+        "/proc/sys/net/core/somaxconn",
+    };
+
+    for (int i = 0; i < ARRAY_SIZE(proc_sys_files); i++) {
+        if (test_read_from_procfs(proc_sys_files[i]) < 0) {
+            THROW_ERROR("failed to read %s", proc_sys_files[i]);
+        }
+    }
+    return 0;
+}
+
+static int test_write_to_proc_sys() {
+    const char *proc_pipe_max_size = "/proc/sys/fs/pipe-max-size";
+    char old_val[32] = { 0 };
+    int fds[2];
+
+    int fd = open(proc_pipe_max_size, O_RDONLY);
+    if (fd < 0 || read(fd, old_val, sizeof(old_val) - 1) <= 0) {
+        THROW_ERROR("failed to read %s", proc_pipe_max_size);
+    }
+    close(fd);
+
+    if (write_to_procfs(proc_pipe_max_size, "65536\n") < 0) {
+        THROW_ERROR("failed to write %s", proc_pipe_max_size);
+    }
+    if (fs_check_file_content(proc_pipe_max_size, "65536\n") < 0) {
+        THROW_ERROR("failed to check the new value of %s", proc_pipe_max_size);
+    }
+    // The pipes can be resized up to the new size
+    if (pipe(fds) < 0) {
+        THROW_ERROR("failed to create a pipe");
+    }
+    if (fcntl(fds[0], F_SETPIPE_SZ, 65536) != 65536 ||
+            fcntl(fds[0], F_SETPIPE_SZ, 65536 * 2) != -1 || errno != EPERM) {
+        close(fds[0]);
+        close(fds[1]);
+        THROW_ERROR("the pipe size should be limited by %s", proc_pipe_max_size);
+    }
+    close(fds[0]);
+    close(fds[1]);
+
+    if (write_to_procfs(proc_pipe_max_size, "invalid") == 0 || errno != EINVAL) {
+        THROW_ERROR("write an invalid value should fail with EINVAL");
+    }
+    if (write_to_procfs("/proc/sys/kernel/shmmni", "1024") == 0) {
+        THROW_ERROR("write a read-only sysctl should fail");
+    }
+    if (write_to_procfs(proc_pipe_max_size, old_val) < 0) {
+        THROW_ERROR("failed to restore %s", proc_pipe_max_size);
+    }
+    return 0;
+}
+
+static int test_sgx_file_cache_sysctl() {
+    const char *proc_cache_max = "/proc/sys/fs/sgx-file-cache-max";
+    const char *proc_cache_state = "/proc/sys/fs/sgx-file-cache-state";
+    char old_val[32] = { 0 };
+    char state[128] = { 0 };
+    unsigned long hits, misses, evictions, cached_files;
+
+    int fd = open(proc_cache_max, O_RDONLY);
+    if (fd < 0 || read(fd, old_val, sizeof(old_val) - 1) <= 0) {
+        THROW_ERROR("failed to read %s", proc_cache_max);
+    }
+    close(fd);
+
+    if (write_to_procfs(proc_cache_max, "1024\n") < 0) {
+        THROW_ERROR("failed to write %s", proc_cache_max);
+    }
+    if (fs_check_file_content(proc_cache_max, "1024\n") < 0) {
+        THROW_ERROR("failed to check the new value of %s", proc_cache_max);
+    }
+    if (write_to_procfs(proc_cache_max, "0") == 0 || errno != EINVAL) {
+        THROW_ERROR("the SGX file cache can not be disabled by zero");
+    }
+    if (write_to_procfs(proc_cache_max, old_val) < 0) {
+        THROW_ERROR("failed to restore %s", proc_cache_max);
+    }
+
+    fd = open(proc_cache_state, O_RDONLY);
+    if (fd < 0 || read(fd, state, sizeof(state) - 1) <= 0) {
+        THROW_ERROR("failed to read %s", proc_cache_state);
+    }
+    close(fd);
+    if (sscanf(state, "%lu %lu %lu %lu", &hits, &misses, &evictions, &cached_files) != 4) {
+        THROW_ERROR("the format of %s is wrong: %s", proc_cache_state, state);
+    }
+    if (write_to_procfs(proc_cache_state, "0") == 0) {
+        THROW_ERROR("write a read-only sysctl should fail");
+    }
+    return 0;
+}
+
 #define PROC_SUPER_MAGIC 0x9fa0
 static int test_statfs() {
     const char *file_path = "/proc/cpuinfo";
@@ -235,14 +456,19 @@ static int test_readdir_root() {
     const char *root = "/proc";
     char pid[NAME_MAX] = { 0 };
     snprintf(pid, sizeof(pid), "%d", getpid());
-    char expected_entries[4][NAME_MAX] = {
+    char expected_entries[9][NAME_MAX] = {
         "self",
+        "sys",
         "meminfo",
         "cpuinfo",
+        "mounts",
+        "uptime",
+        "loadavg",
+        "version",
         { *pid },
     };
 
-    if (check_readdir_with_expected_entries(root, expected_entries, 4) < 0) {
+    if (check_readdir_with_expected_entries(root, expected_entries, 9) < 0) {
         THROW_ERROR("failed to test readdir %s", root);
     }
 
@@ -251,22 +477,44 @@ static int test_readdir_root() {
 
 static int test_readdir_self() {
     const char *self = "/proc/self";
-    char expected_entries[6][NAME_MAX] = {
+    char expected_entries[11][NAME_MAX] = {
         "exe",
         "cwd",
         "root",
         "fd",
         "comm",
         "cmdline",
+        "status",
+        "environ",
+        "limits",
+        "mountinfo",
+        "task",
     };
 
-    if (check_readdir_with_expected_entries(self, expected_entries, 6) < 0) {
+    if (check_readdir_with_expected_entries(self, expected_entries, 11) < 0) {
         THROW_ERROR("failed to test readdir %s", self);
     }
 
     return 0;
 }
 
+static int test_readdir_self_task() {
+    const char *self_task = "/proc/self/task";
+    char expected_entries[1][NAME_MAX] = { 0 };
+    snprintf(expected_entries[0], NAME_MAX, "%d", getpid());
+
+    if (check_readdir_with_expected_entries(self_task, expected_entries, 1) < 0) {
+        THROW_ERROR("failed to test readdir %s", self_task);
+    }
+
+    char task_comm[PATH_MAX] = { 0 };
+    snprintf(task_comm, sizeof(task_comm), "/proc/self/task/%d/comm", getpid());
+    if (test_read_from_procfs(task_comm) < 0) {
+        THROW_ERROR("failed to read the comm of the task");
+    }
+    return 0;
+}
+
 static int test_readdir_self_fd() {
     const char *self_fd = "/proc/self/fd";
     char expected_entries[3][NAME_MAX] = {
@@ -282,6 +530,78 @@ static int test_readdir_self_fd() {
     return 0;
 }
 
+static int test_inode_numbers() {
+    const char *paths[] = {
+        "/proc",
+        "/proc/self",
+        "/proc/self/status",
+        "/proc/self/stat",
+        "/proc/self/fd",
+        "/proc/self/task",
+        "/proc/cpuinfo",
+        "/proc/meminfo",
+        "/proc/sys",
+        "/proc/sys/kernel/hostname",
+    };
+    struct stat stat_bufs[ARRAY_SIZE(paths)];
+
+    for (int i = 0; i < ARRAY_SIZE(paths); i++) {
+        if (stat(paths[i], &stat_bufs[i]) < 0) {
+            THROW_ERROR("failed to stat %s", paths[i]);
+        }
+        for (int j = 0; j < i; j++) {
+            if (stat_bufs[i].st_ino == stat_bufs[j].st_ino) {
+                THROW_ERROR("%s and %s have the same inode number", paths[i], paths[j]);
+            }
+        }
+    }
+
+    // The inode number should be stable
+    struct stat stat_buf;
+    if (stat("/proc/self/status", &stat_buf) < 0) {
+        THROW_ERROR("failed to stat /proc/self/status again");
+    }
+    if (stat_buf.st_ino != stat_bufs[2].st_ino) {
+        THROW_ERROR("the inode number of /proc/self/status is not stable");
+    }
+
+    // The procfs should have its own device ID
+    if (stat("/", &stat_buf) < 0) {
+        THROW_ERROR("failed to stat the root");
+    }
+    if (stat_buf.st_dev == stat_bufs[0].st_dev) {
+        THROW_ERROR("procfs should have a different device ID from the root");
+    }
+    return 0;
+}
+
+static int test_dir_nlinks() {
+    struct stat stat_buf;
+
+    // The fd and task are the subdirectories
+    if (stat("/proc/self", &stat_buf) < 0) {
+        THROW_ERROR("failed to stat /proc/self");
+    }
+    if (stat_buf.st_nlink != 4) {
+        THROW_ERROR("the nlink of /proc/self is wrong");
+    }
+    // There is only one thread in the process
+    if (stat("/proc/self/task", &stat_buf) < 0) {
+        THROW_ERROR("failed to stat /proc/self/task");
+    }
+    if (stat_buf.st_nlink != 3) {
+        THROW_ERROR("the nlink of /proc/self/task is wrong");
+    }
+    // There is no subdirectory in /proc/sys/kernel
+    if (stat("/proc/sys/kernel", &stat_buf) < 0) {
+        THROW_ERROR("failed to stat /proc/sys/kernel");
+    }
+    if (stat_buf.st_nlink != 2) {
+        THROW_ERROR("the nlink of /proc/sys/kernel is wrong");
+    }
+    return 0;
+}
+
 // ============================================================================
 // Test suite main
 // ============================================================================
@@ -294,6 +614,16 @@ static test_case_t test_cases[] = {
     TEST_CASE(test_read_from_proc_self_cmdline),
     TEST_CASE(test_read_from_proc_self_comm),
     TEST_CASE(test_read_from_proc_self_stat),
+    TEST_CASE(test_read_from_proc_self_status),
+    TEST_CASE(test_read_from_proc_self_environ),
+    TEST_CASE(test_read_from_proc_self_limits),
+    TEST_CASE(test_read_from_proc_mounts),
+    TEST_CASE(test_read_from_proc_uptime),
+    TEST_CASE(test_read_from_proc_loadavg),
+    TEST_CASE(test_read_from_proc_version),
+    TEST_CASE(test_read_from_proc_sys),
+    TEST_CASE(test_write_to_proc_sys),
+    TEST_CASE(test_sgx_file_cache_sysctl),
     TEST_CASE(test_read_from_proc_meminfo),
     TEST_CASE(test_read_from_proc_cpuinfo),
     TEST_CASE(test_read_from_proc_stat),
@@ -301,7 +631,10 @@ static test_case_t test_cases[] = {
     TEST_CASE(test_readdir_root),
     TEST_CASE(test_readdir_self),
     TEST_CASE(test_readdir_self_fd),
+    TEST_CASE(test_readdir_self_task),
     TEST_CASE(test_read_from_proc_self_maps),
+    TEST_CASE(test_inode_numbers),
+    TEST_CASE(test_dir_nlinks),
 };
 
 int main(int argc, const char *argv[]) {
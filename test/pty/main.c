@@ -0,0 +1,391 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <fcntl.h>
+#include <signal.h>
+#include <stdlib.h>
+#include <stdio.h>
+#include <string.h>
+#include <termios.h>
+#include <unistd.h>
+#include <sys/ioctl.h>
+#include <sys/stat.h>
+#include <sys/sysmacros.h>
+
+#include "test.h"
+
+// ============================================================================
+// Helper function
+// ============================================================================
+
+static int open_pty(int *master, int *slave) {
+    *master = posix_openpt(O_RDWR | O_NOCTTY);
+    if (*master < 0) {
+        THROW_ERROR("failed to open the master");
+    }
+    if (grantpt(*master) < 0 || unlockpt(*master) < 0) {
+        close(*master);
+        THROW_ERROR("failed to unlock the slave");
+    }
+    char *slave_path = ptsname(*master);
+    if (slave_path == NULL) {
+        close(*master);
+        THROW_ERROR("failed to get the path of the slave");
+    }
+    *slave = open(slave_path, O_RDWR | O_NOCTTY);
+    if (*slave < 0) {
+        close(*master);
+        THROW_ERROR("failed to open the slave: %s", slave_path);
+    }
+    return 0;
+}
+
+static void close_pty(int master, int slave) {
+    close(slave);
+    close(master);
+}
+
+static int check_read(int fd, const char *expected) {
+    char buf[256] = { 0 };
+    ssize_t len = read(fd, buf, sizeof(buf) - 1);
+    if (len != strlen(expected) || memcmp(buf, expected, len) != 0) {
+        THROW_ERROR("read %zd bytes \"%s\", expect \"%s\"", len, buf, expected);
+    }
+    return 0;
+}
+
+static int check_nothing_to_read(int fd) {
+    char buf[16];
+    int flags = fcntl(fd, F_GETFL);
+    fcntl(fd, F_SETFL, flags | O_NONBLOCK);
+    ssize_t len = read(fd, buf, sizeof(buf));
+    fcntl(fd, F_SETFL, flags);
+    if (!(len < 0 && errno == EAGAIN)) {
+        THROW_ERROR("there should be nothing to read");
+    }
+    return 0;
+}
+
+static int write_str(int fd, const char *str) {
+    if (write(fd, str, strlen(str)) != strlen(str)) {
+        THROW_ERROR("failed to write \"%s\"", str);
+    }
+    return 0;
+}
+
+// ============================================================================
+// Test cases for PTY
+// ============================================================================
+
+static int test_open_pty() {
+    int master, slave;
+    struct stat stat_buf;
+    unsigned int index;
+
+    if (open_pty(&master, &slave) < 0) {
+        return -1;
+    }
+    if (!isatty(master) || !isatty(slave)) {
+        close_pty(master, slave);
+        THROW_ERROR("the master and the slave should be terminals");
+    }
+    if (ioctl(master, TIOCGPTN, &index) < 0) {
+        close_pty(master, slave);
+        THROW_ERROR("failed to get the index of PTY");
+    }
+    if (fstat(slave, &stat_buf) < 0 || !S_ISCHR(stat_buf.st_mode) ||
+            major(stat_buf.st_rdev) != 136 || minor(stat_buf.st_rdev) != index) {
+        close_pty(master, slave);
+        THROW_ERROR("wrong stat of the slave");
+    }
+    char *name = ttyname(slave);
+    if (name == NULL || strcmp(name, ptsname(master)) != 0) {
+        close_pty(master, slave);
+        THROW_ERROR("wrong ttyname of the slave");
+    }
+    close_pty(master, slave);
+    return 0;
+}
+
+static int test_open_locked_slave() {
+    int master = posix_openpt(O_RDWR | O_NOCTTY);
+    if (master < 0) {
+        THROW_ERROR("failed to open the master");
+    }
+    int slave = open(ptsname(master), O_RDWR | O_NOCTTY);
+    close(master);
+    if (!(slave < 0 && errno == EIO)) {
+        THROW_ERROR("the locked slave should not be opened");
+    }
+    return 0;
+}
+
+static int test_canonical_mode() {
+    int master, slave;
+    int ret = -1;
+
+    if (open_pty(&master, &slave) < 0) {
+        return -1;
+    }
+    // Erase the 'o' with DEL, and then the line is completed by CR
+    if (write_str(master, "helo\x7flo\r") < 0) {
+        goto out;
+    }
+    if (check_read(slave, "hello\n") < 0) {
+        goto out;
+    }
+    // The erased character is echoed as "\b \b", and NL as "\r\n"
+    if (check_read(master, "helo\b \blo\r\n") < 0) {
+        goto out;
+    }
+    // The incomplete line can not be read until the line is completed
+    if (write_str(master, "abc") < 0 || check_nothing_to_read(slave) < 0) {
+        goto out;
+    }
+    if (write_str(master, "\n") < 0 || check_read(slave, "abc\n") < 0) {
+        goto out;
+    }
+    // EOF on an empty line makes read return 0
+    if (write_str(master, "\x04") < 0 || check_read(slave, "") < 0) {
+        goto out;
+    }
+    ret = 0;
+out:
+    close_pty(master, slave);
+    return ret;
+}
+
+static int test_output_processing() {
+    int master, slave;
+    int ret = -1;
+
+    if (open_pty(&master, &slave) < 0) {
+        return -1;
+    }
+    if (write_str(slave, "line1\nline2\n") < 0) {
+        goto out;
+    }
+    if (check_read(master, "line1\r\nline2\r\n") < 0) {
+        goto out;
+    }
+    ret = 0;
+out:
+    close_pty(master, slave);
+    return ret;
+}
+
+static int test_raw_mode() {
+    int master, slave;
+    struct termios termios;
+    int ret = -1;
+
+    if (open_pty(&master, &slave) < 0) {
+        return -1;
+    }
+    if (tcgetattr(slave, &termios) < 0) {
+        close_pty(master, slave);
+        THROW_ERROR("failed to get the termios");
+    }
+    cfmakeraw(&termios);
+    if (tcsetattr(slave, TCSANOW, &termios) < 0) {
+        close_pty(master, slave);
+        THROW_ERROR("failed to set the termios");
+    }
+    // No line editing, no echo and no output processing
+    if (write_str(master, "ab\x7f\x03\r") < 0 || check_read(slave, "ab\x7f\x03\r") < 0) {
+        goto out;
+    }
+    if (check_nothing_to_read(master) < 0) {
+        goto out;
+    }
+    if (write_str(slave, "\n") < 0 || check_read(master, "\n") < 0) {
+        goto out;
+    }
+    ret = 0;
+out:
+    close_pty(master, slave);
+    return ret;
+}
+
+static int set_min_time(int fd, struct termios *termios, cc_t min, cc_t time) {
+    termios->c_cc[VMIN] = min;
+    termios->c_cc[VTIME] = time;
+    if (tcsetattr(fd, TCSANOW, termios) < 0) {
+        THROW_ERROR("failed to set MIN and TIME");
+    }
+    return 0;
+}
+
+// The reads in the non-canonical mode are completed by MIN and TIME
+static int test_read_min_time() {
+    int master, slave;
+    struct termios termios;
+    int ret = -1;
+
+    if (open_pty(&master, &slave) < 0) {
+        return -1;
+    }
+    if (tcgetattr(slave, &termios) < 0) {
+        close_pty(master, slave);
+        THROW_ERROR("failed to get the termios");
+    }
+    cfmakeraw(&termios);
+    // Return immediately even if there is nothing to read
+    if (set_min_time(slave, &termios, 0, 0) < 0 || check_read(slave, "") < 0) {
+        goto out;
+    }
+    // Return nothing after the timeout of 0.1s
+    if (set_min_time(slave, &termios, 0, 1) < 0 || check_read(slave, "") < 0) {
+        goto out;
+    }
+    // Return less than MIN bytes after the timeout between the bytes
+    if (set_min_time(slave, &termios, 3, 1) < 0 || write_str(master, "ab") < 0 ||
+            check_read(slave, "ab") < 0) {
+        goto out;
+    }
+    // Return all the bytes available if there are at least MIN bytes
+    if (set_min_time(slave, &termios, 2, 0) < 0 || write_str(master, "abc") < 0 ||
+            check_read(slave, "abc") < 0) {
+        goto out;
+    }
+    ret = 0;
+out:
+    close_pty(master, slave);
+    return ret;
+}
+
+static int test_winsize() {
+    int master, slave;
+    struct winsize winsize = { .ws_row = 24, .ws_col = 80 };
+    struct winsize got_winsize = { 0 };
+
+    if (open_pty(&master, &slave) < 0) {
+        return -1;
+    }
+    if (ioctl(master, TIOCSWINSZ, &winsize) < 0) {
+        close_pty(master, slave);
+        THROW_ERROR("failed to set the window size");
+    }
+    if (ioctl(slave, TIOCGWINSZ, &got_winsize) < 0) {
+        close_pty(master, slave);
+        THROW_ERROR("failed to get the window size");
+    }
+    close_pty(master, slave);
+    if (got_winsize.ws_row != winsize.ws_row || got_winsize.ws_col != winsize.ws_col) {
+        THROW_ERROR("wrong window size");
+    }
+    return 0;
+}
+
+static int test_read_master_after_slave_closed() {
+    int master, slave;
+    char buf[16];
+
+    if (open_pty(&master, &slave) < 0) {
+        return -1;
+    }
+    close(slave);
+    ssize_t len = read(master, buf, sizeof(buf));
+    close(master);
+    if (!(len < 0 && errno == EIO)) {
+        THROW_ERROR("read the master should fail with EIO");
+    }
+    return 0;
+}
+
+static volatile sig_atomic_t sigint_received = 0;
+
+static void sigint_handler(int signum) {
+    sigint_received = 1;
+}
+
+static int test_controlling_terminal() {
+    int master, slave;
+    int ret = -1;
+
+    if (open_pty(&master, &slave) < 0) {
+        return -1;
+    }
+    if (ioctl(slave, TIOCSCTTY, 0) < 0) {
+        close_pty(master, slave);
+        THROW_ERROR("failed to set the controlling terminal");
+    }
+    if (tcgetpgrp(slave) != getpgrp()) {
+        printf("the foreground process group should be the current one\n");
+        goto out;
+    }
+
+    // /dev/tty is the controlling terminal
+    int tty = open("/dev/tty", O_RDWR);
+    if (tty < 0) {
+        printf("failed to open /dev/tty\n");
+        goto out;
+    }
+    if (write_str(tty, "tty\n") < 0 || check_read(master, "tty\r\n") < 0) {
+        close(tty);
+        goto out;
+    }
+    close(tty);
+
+    // ^C sends SIGINT to the foreground process group
+    signal(SIGINT, sigint_handler);
+    if (write_str(master, "\x03") < 0) {
+        goto out;
+    }
+    for (int i = 0; i < 100 && !sigint_received; i++) {
+        usleep(1000);
+    }
+    signal(SIGINT, SIG_DFL);
+    if (!sigint_received) {
+        printf("SIGINT should be received\n");
+        goto out;
+    }
+    if (check_read(master, "^C") < 0) {
+        goto out;
+    }
+    ret = 0;
+out:
+    // Give up the controlling terminal so that closing the master does not
+    // hang up the current process
+    ioctl(slave, TIOCNOTTY);
+    close_pty(master, slave);
+    if (ret < 0) {
+        THROW_ERROR("failed to test the controlling terminal");
+    }
+    return 0;
+}
+
+static int test_stdio_devices() {
+    const char *paths[] = { "/dev/stdin", "/dev/stdout", "/dev/stderr" };
+    char buf[64];
+
+    for (int fd = 0; fd < 3; fd++) {
+        char expected[64];
+        snprintf(expected, sizeof(expected), "/proc/self/fd/%d", fd);
+        ssize_t len = readlink(paths[fd], buf, sizeof(buf));
+        if (len != strlen(expected) || strncmp(buf, expected, len) != 0) {
+            THROW_ERROR("wrong link of %s", paths[fd]);
+        }
+    }
+    return 0;
+}
+
+// ============================================================================
+// Test suite main
+// ============================================================================
+
+static test_case_t test_cases[] = {
+    TEST_CASE(test_open_pty),
+    TEST_CASE(test_open_locked_slave),
+    TEST_CASE(test_canonical_mode),
+    TEST_CASE(test_output_processing),
+    TEST_CASE(test_raw_mode),
+    TEST_CASE(test_read_min_time),
+    TEST_CASE(test_winsize),
+    TEST_CASE(test_read_master_after_slave_closed),
+    TEST_CASE(test_controlling_terminal),
+    TEST_CASE(test_stdio_devices),
+};
+
+int main(int argc, const char *argv[]) {
+    return test_suite_run(test_cases, ARRAY_SIZE(test_cases));
+}
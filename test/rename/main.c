@@ -223,6 +223,48 @@ static int test_rename_file_as_dir() {
     return 0;
 }
 
+// The files of SEFS are cached by the storage once opened, so the content
+// read back after the renames must not come from a stale cache entry
+static int test_rename_cached_file() {
+    const char *path = "/root/test_rename_cached.txt";
+    const char *other_path = "/root/test_rename_cached_other.txt";
+    const char *new_msg = "Hello Again";
+
+    if (create_file_with_content(path, WRITE_MSG) < 0 ||
+            create_file_with_content(other_path, new_msg) < 0) {
+        THROW_ERROR("failed to create the files");
+    }
+    // Read both files to get them cached
+    if (fs_check_file_content(path, WRITE_MSG) < 0 ||
+            fs_check_file_content(other_path, new_msg) < 0) {
+        THROW_ERROR("failed to check file content");
+    }
+
+    // Replace the cached file
+    if (rename(other_path, path) < 0) {
+        THROW_ERROR("failed to rename the file");
+    }
+    if (fs_check_file_content(path, new_msg) < 0) {
+        THROW_ERROR("the replaced file should not be read");
+    }
+    // Rename it back and create a new file with the name
+    if (rename(path, other_path) < 0) {
+        THROW_ERROR("failed to rename the file back");
+    }
+    if (create_file_with_content(path, WRITE_MSG) < 0) {
+        THROW_ERROR("failed to create the file again");
+    }
+    if (fs_check_file_content(path, WRITE_MSG) < 0 ||
+            fs_check_file_content(other_path, new_msg) < 0) {
+        THROW_ERROR("failed to check file content after renaming back");
+    }
+
+    if (unlink(path) < 0 || unlink(other_path) < 0) {
+        THROW_ERROR("failed to remove the files");
+    }
+    return 0;
+}
+
 // ============================================================================
 // Test suite main
 // ============================================================================
@@ -235,6 +277,7 @@ static test_case_t test_cases[] = {
     TEST_CASE(test_rename_dir),
     TEST_CASE(test_rename_dir_to_subdir),
     TEST_CASE(test_rename_file_as_dir),
+    TEST_CASE(test_rename_cached_file),
 };
 
 int main(int argc, const char *argv[]) {
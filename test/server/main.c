@@ -9,6 +9,7 @@
 #include <unistd.h>
 #include <arpa/inet.h>
 #include <netinet/in.h>
+#include <sys/ioctl.h>
 #include <sys/types.h>
 #include <sys/socket.h>
 #include <sys/wait.h>
@@ -413,6 +414,464 @@ int test_poll() {
     return 0;
 }
 
+static int listen_on_loopback(int port) {
+    int listen_fd = socket(AF_INET, SOCK_STREAM, 0);
+    if (listen_fd < 0) {
+        THROW_ERROR("create socket error");
+    }
+    int reuse = 1;
+    if (setsockopt(listen_fd, SOL_SOCKET, SO_REUSEADDR, &reuse, sizeof(reuse)) < 0) {
+        THROW_ERROR("setsockopt port to reuse failed");
+    }
+
+    struct sockaddr_in servaddr;
+    memset(&servaddr, 0, sizeof(servaddr));
+    servaddr.sin_family = AF_INET;
+    servaddr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+    servaddr.sin_port = htons(port);
+    if (bind(listen_fd, (struct sockaddr *) &servaddr, sizeof(servaddr)) < 0) {
+        close(listen_fd);
+        THROW_ERROR("bind socket failed");
+    }
+    if (listen(listen_fd, 1) < 0) {
+        close(listen_fd);
+        THROW_ERROR("listen socket error");
+    }
+    return listen_fd;
+}
+
+static int connect_to_loopback(int port) {
+    int fd = socket(AF_INET, SOCK_STREAM, 0);
+    if (fd < 0) {
+        THROW_ERROR("create socket error");
+    }
+
+    struct sockaddr_in servaddr;
+    memset(&servaddr, 0, sizeof(servaddr));
+    servaddr.sin_family = AF_INET;
+    servaddr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+    servaddr.sin_port = htons(port);
+    if (connect(fd, (struct sockaddr *) &servaddr, sizeof(servaddr)) < 0) {
+        close(fd);
+        THROW_ERROR("connect error");
+    }
+    return fd;
+}
+
+// Connections between LibOS sockets over loopback addresses are established
+// in the enclave, so the connect and the accept can be done in one thread.
+static int test_loopback_in_enclave() {
+    int port = 8806;
+    int listen_fd = listen_on_loopback(port);
+    if (listen_fd < 0) {
+        THROW_ERROR("listen failed");
+    }
+    int client_fd = connect_to_loopback(port);
+    if (client_fd < 0) {
+        THROW_ERROR("connect failed");
+    }
+
+    struct sockaddr_in peer_addr, client_addr;
+    socklen_t addr_len = sizeof(peer_addr);
+    int server_fd = accept(listen_fd, (struct sockaddr *) &peer_addr, &addr_len);
+    if (server_fd < 0) {
+        THROW_ERROR("accept socket error");
+    }
+    close(listen_fd);
+
+    addr_len = sizeof(client_addr);
+    if (getsockname(client_fd, (struct sockaddr *) &client_addr, &addr_len) < 0) {
+        THROW_ERROR("getsockname failed");
+    }
+    if (addr_len != sizeof(client_addr) || client_addr.sin_port != peer_addr.sin_port ||
+            client_addr.sin_addr.s_addr != htonl(INADDR_LOOPBACK)) {
+        THROW_ERROR("the accepted address mismatches the client address");
+    }
+    addr_len = sizeof(peer_addr);
+    if (getpeername(client_fd, (struct sockaddr *) &peer_addr, &addr_len) < 0) {
+        THROW_ERROR("getpeername failed");
+    }
+    if (peer_addr.sin_port != htons(port)) {
+        THROW_ERROR("the peer address of the client mismatches");
+    }
+
+    if (send(client_fd, ECHO_MSG, strlen(ECHO_MSG), 0) != strlen(ECHO_MSG)) {
+        THROW_ERROR("send failed");
+    }
+
+    char buf[64] = {0};
+    if (recv(server_fd, buf, sizeof(buf), MSG_PEEK) != strlen(ECHO_MSG)) {
+        THROW_ERROR("recv with MSG_PEEK failed");
+    }
+    int nbytes = 0;
+    if (ioctl(server_fd, FIONREAD, &nbytes) < 0 || nbytes != strlen(ECHO_MSG)) {
+        THROW_ERROR("peeked bytes should still be readable");
+    }
+    memset(buf, 0, sizeof(buf));
+    if (recv(server_fd, buf, sizeof(buf), 0) != strlen(ECHO_MSG) ||
+            strcmp(buf, ECHO_MSG) != 0) {
+        THROW_ERROR("msg recv mismatch");
+    }
+
+    struct pollfd polls[] = {
+        { .fd = server_fd, .events = POLLIN }
+    };
+    if (poll(polls, 1, 0) != 0) {
+        THROW_ERROR("nothing should be readable");
+    }
+
+    // Ancillary data cannot be passed in the enclave
+    char control[CMSG_SPACE(sizeof(int))] = {0};
+    struct cmsghdr *cmsg = (struct cmsghdr *)control;
+    cmsg->cmsg_level = SOL_SOCKET;
+    cmsg->cmsg_type = SCM_RIGHTS;
+    cmsg->cmsg_len = CMSG_LEN(sizeof(int));
+    *(int *)CMSG_DATA(cmsg) = server_fd;
+    struct iovec iov = { .iov_base = ECHO_MSG, .iov_len = strlen(ECHO_MSG) };
+    struct msghdr msg = {
+        .msg_iov = &iov,
+        .msg_iovlen = 1,
+        .msg_control = control,
+        .msg_controllen = sizeof(control),
+    };
+    if (sendmsg(client_fd, &msg, 0) != -1 || errno != EOPNOTSUPP) {
+        THROW_ERROR("sendmsg with ancillary data should fail with EOPNOTSUPP");
+    }
+
+    if (shutdown(client_fd, SHUT_WR) < 0) {
+        THROW_ERROR("shutdown failed");
+    }
+    if (poll(polls, 1, -1) != 1 || !(polls[0].revents & POLLIN)) {
+        THROW_ERROR("EOF should be readable");
+    }
+    if (recv(server_fd, buf, sizeof(buf), 0) != 0) {
+        THROW_ERROR("recv should get EOF");
+    }
+
+    close(client_fd);
+    close(server_fd);
+    return 0;
+}
+
+static int test_loopback_nonblocking_accept() {
+    int port = 8807;
+    int listen_fd = listen_on_loopback(port);
+    if (listen_fd < 0) {
+        THROW_ERROR("listen failed");
+    }
+    if (fcntl(listen_fd, F_SETFL, O_NONBLOCK) < 0) {
+        THROW_ERROR("fcntl setfl failed");
+    }
+
+    if (accept(listen_fd, NULL, NULL) >= 0 || errno != EAGAIN) {
+        THROW_ERROR("accept should return EAGAIN");
+    }
+
+    int client_fd = connect_to_loopback(port);
+    if (client_fd < 0) {
+        THROW_ERROR("connect failed");
+    }
+    struct pollfd polls[] = {
+        { .fd = listen_fd, .events = POLLIN }
+    };
+    if (poll(polls, 1, -1) != 1 || !(polls[0].revents & POLLIN)) {
+        THROW_ERROR("the listening socket should be readable");
+    }
+    int server_fd = accept4(listen_fd, NULL, NULL, SOCK_NONBLOCK);
+    if (server_fd < 0) {
+        THROW_ERROR("accept socket error");
+    }
+    if (recv(server_fd, NULL, 0, 0) < 0 && errno != EAGAIN) {
+        THROW_ERROR("recv should not block");
+    }
+
+    close(server_fd);
+    close(client_fd);
+    close(listen_fd);
+    return 0;
+}
+
+// SO_RCVTIMEO and SO_SNDTIMEO apply to the connections in the enclave too
+static int test_loopback_timeout() {
+    int port = 8810;
+    char buf[64 * 1024] = {0};
+    int listen_fd = listen_on_loopback(port);
+    if (listen_fd < 0) {
+        THROW_ERROR("listen failed");
+    }
+    int client_fd = connect_to_loopback(port);
+    if (client_fd < 0) {
+        THROW_ERROR("connect failed");
+    }
+    int server_fd = accept(listen_fd, NULL, NULL);
+    if (server_fd < 0) {
+        THROW_ERROR("accept socket error");
+    }
+    close(listen_fd);
+
+    struct timeval timeout = { .tv_sec = 0, .tv_usec = 100 * 1000 };
+    if (setsockopt(server_fd, SOL_SOCKET, SO_RCVTIMEO, &timeout, sizeof(timeout)) < 0) {
+        THROW_ERROR("setsockopt error");
+    }
+    if (recv(server_fd, buf, sizeof(buf), 0) != -1 || errno != EAGAIN) {
+        THROW_ERROR("recv should time out");
+    }
+
+    if (setsockopt(client_fd, SOL_SOCKET, SO_SNDTIMEO, &timeout, sizeof(timeout)) < 0) {
+        THROW_ERROR("setsockopt error");
+    }
+    // Fill up the buffer until the send times out
+    for (int i = 0; ; i++) {
+        if (i == 64) {
+            THROW_ERROR("send should time out when the buffer is full");
+        }
+        if (send(client_fd, buf, sizeof(buf), 0) < 0) {
+            if (errno != EAGAIN) {
+                THROW_ERROR("send should time out with EAGAIN");
+            }
+            break;
+        }
+    }
+
+    close(client_fd);
+    close(server_fd);
+    return 0;
+}
+
+// The connections not accepted yet are reset when the listener is closed
+static int test_loopback_listener_reset() {
+    int port = 8811;
+    char buf[16];
+    int listen_fd = listen_on_loopback(port);
+    if (listen_fd < 0) {
+        THROW_ERROR("listen failed");
+    }
+    int client_fd = connect_to_loopback(port);
+    if (client_fd < 0) {
+        THROW_ERROR("connect failed");
+    }
+    close(listen_fd);
+
+    if (recv(client_fd, buf, sizeof(buf), 0) != -1 || errno != ECONNRESET) {
+        THROW_ERROR("recv should fail with ECONNRESET");
+    }
+    // The error is reported only once
+    if (recv(client_fd, buf, sizeof(buf), 0) != 0) {
+        THROW_ERROR("recv should get EOF after the reset");
+    }
+    close(client_fd);
+    return 0;
+}
+
+#define NUM_UDP_MSGS 3
+
+static int test_sendmmsg_recvmmsg_udp() {
+    int port = 8808;
+    const char *msgs[NUM_UDP_MSGS] = {"first", "second message", "3rd"};
+    int ret = -1;
+
+    int recv_fd = socket(AF_INET, SOCK_DGRAM, 0);
+    int send_fd = socket(AF_INET, SOCK_DGRAM, 0);
+    if (recv_fd < 0 || send_fd < 0) {
+        THROW_ERROR("create socket error");
+    }
+
+    struct sockaddr_in addr;
+    memset(&addr, 0, sizeof(addr));
+    addr.sin_family = AF_INET;
+    addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+    addr.sin_port = htons(port);
+    if (bind(recv_fd, (struct sockaddr *)&addr, sizeof(addr)) < 0 ||
+            connect(send_fd, (struct sockaddr *)&addr, sizeof(addr)) < 0) {
+        printf("bind or connect error\n");
+        goto out;
+    }
+
+    struct mmsghdr send_msgs[NUM_UDP_MSGS];
+    struct iovec send_iovs[NUM_UDP_MSGS];
+    memset(send_msgs, 0, sizeof(send_msgs));
+    for (int i = 0; i < NUM_UDP_MSGS; i++) {
+        send_iovs[i].iov_base = (void *)msgs[i];
+        send_iovs[i].iov_len = strlen(msgs[i]);
+        send_msgs[i].msg_hdr.msg_iov = &send_iovs[i];
+        send_msgs[i].msg_hdr.msg_iovlen = 1;
+    }
+    if (sendmmsg(send_fd, send_msgs, NUM_UDP_MSGS, 0) != NUM_UDP_MSGS) {
+        printf("sendmmsg error\n");
+        goto out;
+    }
+    for (int i = 0; i < NUM_UDP_MSGS; i++) {
+        if (send_msgs[i].msg_len != strlen(msgs[i])) {
+            printf("wrong msg_len of sendmmsg\n");
+            goto out;
+        }
+    }
+
+    // Ask for one more message than sent, which should not block with
+    // MSG_WAITFORONE
+    char bufs[NUM_UDP_MSGS + 1][32];
+    struct iovec recv_iovs[NUM_UDP_MSGS + 1];
+    struct sockaddr_in names[NUM_UDP_MSGS + 1];
+    struct mmsghdr recv_msgs[NUM_UDP_MSGS + 1];
+    memset(recv_msgs, 0, sizeof(recv_msgs));
+    for (int i = 0; i < NUM_UDP_MSGS + 1; i++) {
+        recv_iovs[i].iov_base = bufs[i];
+        recv_iovs[i].iov_len = sizeof(bufs[i]);
+        recv_msgs[i].msg_hdr.msg_iov = &recv_iovs[i];
+        recv_msgs[i].msg_hdr.msg_iovlen = 1;
+        recv_msgs[i].msg_hdr.msg_name = &names[i];
+        recv_msgs[i].msg_hdr.msg_namelen = sizeof(names[i]);
+    }
+    struct timespec timeout = { .tv_sec = 1, .tv_nsec = 0 };
+    int num_recvd = 0;
+    // The datagrams may not arrive all at once
+    while (num_recvd < NUM_UDP_MSGS) {
+        int n = recvmmsg(recv_fd, recv_msgs + num_recvd, NUM_UDP_MSGS + 1 - num_recvd,
+                         MSG_WAITFORONE, &timeout);
+        if (n <= 0) {
+            printf("recvmmsg error\n");
+            goto out;
+        }
+        num_recvd += n;
+    }
+    if (num_recvd != NUM_UDP_MSGS) {
+        printf("received more messages than sent\n");
+        goto out;
+    }
+    for (int i = 0; i < NUM_UDP_MSGS; i++) {
+        if (recv_msgs[i].msg_len != strlen(msgs[i]) ||
+                strncmp(bufs[i], msgs[i], strlen(msgs[i])) != 0) {
+            printf("wrong message received by recvmmsg\n");
+            goto out;
+        }
+        if (recv_msgs[i].msg_hdr.msg_namelen != sizeof(struct sockaddr_in) ||
+                names[i].sin_addr.s_addr != htonl(INADDR_LOOPBACK)) {
+            printf("wrong address received by recvmmsg\n");
+            goto out;
+        }
+    }
+
+    if (recvmmsg(recv_fd, recv_msgs, NUM_UDP_MSGS, MSG_DONTWAIT, NULL) != -1 ||
+            errno != EAGAIN) {
+        printf("recvmmsg should fail with EAGAIN\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(recv_fd);
+    close(send_fd);
+    return ret;
+}
+
+// Datagrams between LibOS sockets over loopback addresses are queued in the
+// enclave, and the sockets keep receiving from the host as well.
+static int test_loopback_udp_in_enclave() {
+    int port = 8809;
+    int ret = -1;
+    char buf[64] = {0};
+
+    int recv_fd = socket(AF_INET, SOCK_DGRAM, 0);
+    int send_fd = socket(AF_INET, SOCK_DGRAM, 0);
+    if (recv_fd < 0 || send_fd < 0) {
+        THROW_ERROR("create socket error");
+    }
+
+    struct sockaddr_in recv_addr, send_addr, src_addr;
+    memset(&recv_addr, 0, sizeof(recv_addr));
+    recv_addr.sin_family = AF_INET;
+    recv_addr.sin_addr.s_addr = htonl(INADDR_ANY);
+    recv_addr.sin_port = htons(port);
+    if (bind(recv_fd, (struct sockaddr *)&recv_addr, sizeof(recv_addr)) < 0) {
+        printf("bind error\n");
+        goto out;
+    }
+    recv_addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+
+    // The sending socket is bound implicitly
+    if (sendto(send_fd, ECHO_MSG, strlen(ECHO_MSG), 0, (struct sockaddr *)&recv_addr,
+               sizeof(recv_addr)) != strlen(ECHO_MSG)) {
+        printf("sendto error\n");
+        goto out;
+    }
+    socklen_t addr_len = sizeof(send_addr);
+    if (getsockname(send_fd, (struct sockaddr *)&send_addr, &addr_len) < 0 ||
+            send_addr.sin_port == 0) {
+        printf("the sending socket should be bound\n");
+        goto out;
+    }
+
+    struct pollfd polls[] = {
+        { .fd = recv_fd, .events = POLLIN }
+    };
+    if (poll(polls, 1, -1) != 1 || !(polls[0].revents & POLLIN)) {
+        printf("the receiving socket should be readable\n");
+        goto out;
+    }
+    int nbytes = 0;
+    if (ioctl(recv_fd, FIONREAD, &nbytes) < 0 || nbytes != strlen(ECHO_MSG)) {
+        printf("FIONREAD should get the length of the datagram\n");
+        goto out;
+    }
+    if (recv(recv_fd, buf, sizeof(buf), MSG_PEEK) != strlen(ECHO_MSG)) {
+        printf("recv with MSG_PEEK error\n");
+        goto out;
+    }
+    // The rest of a truncated datagram is discarded
+    if (recv(recv_fd, buf, 3, MSG_TRUNC) != strlen(ECHO_MSG)) {
+        printf("recv with MSG_TRUNC should return the length of the datagram\n");
+        goto out;
+    }
+    if (recv(recv_fd, buf, sizeof(buf), MSG_DONTWAIT) != -1 || errno != EAGAIN) {
+        printf("recv should fail with EAGAIN\n");
+        goto out;
+    }
+
+    // Reply to the source address
+    if (sendto(send_fd, ECHO_MSG, strlen(ECHO_MSG), 0, (struct sockaddr *)&recv_addr,
+               sizeof(recv_addr)) != strlen(ECHO_MSG)) {
+        printf("sendto error\n");
+        goto out;
+    }
+    addr_len = sizeof(src_addr);
+    if (recvfrom(recv_fd, buf, sizeof(buf), 0, (struct sockaddr *)&src_addr,
+                 &addr_len) != strlen(ECHO_MSG)) {
+        printf("recvfrom error\n");
+        goto out;
+    }
+    if (addr_len != sizeof(src_addr) || src_addr.sin_port != send_addr.sin_port ||
+            src_addr.sin_addr.s_addr != htonl(INADDR_LOOPBACK)) {
+        printf("wrong source address\n");
+        goto out;
+    }
+    if (sendto(recv_fd, RESPONSE, strlen(RESPONSE), 0, (struct sockaddr *)&src_addr,
+               addr_len) != strlen(RESPONSE)) {
+        printf("sendto error\n");
+        goto out;
+    }
+    memset(buf, 0, sizeof(buf));
+    if (recv(send_fd, buf, sizeof(buf), 0) != strlen(RESPONSE) ||
+            strcmp(buf, RESPONSE) != 0) {
+        printf("wrong response received\n");
+        goto out;
+    }
+
+    // The receive timeout applies
+    struct timeval timeout = { .tv_sec = 0, .tv_usec = 100 * 1000 };
+    if (setsockopt(recv_fd, SOL_SOCKET, SO_RCVTIMEO, &timeout, sizeof(timeout)) < 0) {
+        printf("setsockopt error\n");
+        goto out;
+    }
+    if (recv(recv_fd, buf, sizeof(buf), 0) != -1 || errno != EAGAIN) {
+        printf("recv should time out\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(recv_fd);
+    close(send_fd);
+    return ret;
+}
+
 // This is a testcase mocking pyspark exit procedure. Client process is receiving and blocking.
 // One of server process' child thread waits for the client to exit and the main thread calls exit_group.
 static int test_exit_group() {
@@ -511,6 +970,12 @@ static test_case_t test_cases[] = {
     TEST_CASE(test_fcntl_setfl_and_getfl),
     TEST_CASE(test_poll),
     TEST_CASE(test_poll_events_unchanged),
+    TEST_CASE(test_loopback_in_enclave),
+    TEST_CASE(test_loopback_nonblocking_accept),
+    TEST_CASE(test_loopback_timeout),
+    TEST_CASE(test_loopback_listener_reset),
+    TEST_CASE(test_loopback_udp_in_enclave),
+    TEST_CASE(test_sendmmsg_recvmmsg_udp),
     TEST_CASE(test_exit_group),
 };
 
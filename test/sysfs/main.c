@@ -0,0 +1,218 @@
+#define _GNU_SOURCE
+#include <sys/types.h>
+#include <sys/vfs.h>
+#include <fcntl.h>
+#include <limits.h>
+#include <sched.h>
+#include <stdlib.h>
+#include <errno.h>
+#include "test_fs.h"
+
+// ============================================================================
+// Helper function
+// ============================================================================
+
+static int read_from_sysfs(const char *path, char *buf, int buf_size) {
+    int fd = open(path, O_RDONLY);
+    if (fd < 0) {
+        THROW_ERROR("failed to open file: %s", path);
+    }
+    int len = read(fd, buf, buf_size - 1);
+    if (len < 0) {
+        THROW_ERROR("failed to read: %s", path);
+    }
+    close(fd);
+    buf[len] = '\0';
+    if (len == 0 || buf[len - 1] != '\n') {
+        THROW_ERROR("the content of %s should end with a newline", path);
+    }
+    return 0;
+}
+
+// Count the CPUs in the list format, e.g., "0-3,6,8-9"
+static int count_cpu_list(const char *list) {
+    int count = 0;
+    const char *p = list;
+    while (*p != '\0' && *p != '\n') {
+        char *end;
+        long start = strtol(p, &end, 10);
+        long last = start;
+        if (end == p) {
+            return -1;
+        }
+        if (*end == '-') {
+            p = end + 1;
+            last = strtol(p, &end, 10);
+            if (end == p || last < start) {
+                return -1;
+            }
+        }
+        count += last - start + 1;
+        p = end;
+        if (*p == ',') {
+            p++;
+        }
+    }
+    return count;
+}
+
+// ============================================================================
+// Test cases for sysfs
+// ============================================================================
+
+static int test_cpu_online() {
+    char buf[256] = { 0 };
+    cpu_set_t cpuset;
+
+    if (read_from_sysfs("/sys/devices/system/cpu/online", buf, sizeof(buf)) < 0) {
+        return -1;
+    }
+    if (sched_getaffinity(0, sizeof(cpuset), &cpuset) < 0) {
+        THROW_ERROR("failed to call sched_getaffinity");
+    }
+    if (count_cpu_list(buf) != CPU_COUNT(&cpuset)) {
+        THROW_ERROR("the online CPUs are not the available ones: %s", buf);
+    }
+    return 0;
+}
+
+static int test_cpu_possible_and_present() {
+    char online[256] = { 0 };
+    char possible[256] = { 0 };
+    char present[256] = { 0 };
+
+    if (read_from_sysfs("/sys/devices/system/cpu/online", online, sizeof(online)) < 0 ||
+            read_from_sysfs("/sys/devices/system/cpu/possible", possible, sizeof(possible)) < 0 ||
+            read_from_sysfs("/sys/devices/system/cpu/present", present, sizeof(present)) < 0) {
+        return -1;
+    }
+    if (strcmp(possible, present) != 0) {
+        THROW_ERROR("the possible CPUs should be the present ones");
+    }
+    if (strncmp(possible, "0", 1) != 0 || count_cpu_list(possible) < count_cpu_list(online)) {
+        THROW_ERROR("the possible CPUs are wrong: %s", possible);
+    }
+    return 0;
+}
+
+static int test_cpu_topology() {
+    const char *files[] = {
+        "/sys/devices/system/cpu/cpu0/topology/core_id",
+        "/sys/devices/system/cpu/cpu0/topology/physical_package_id",
+        "/sys/devices/system/cpu/cpu0/topology/core_siblings_list",
+        "/sys/devices/system/cpu/cpu0/topology/thread_siblings_list",
+    };
+    char buf[256] = { 0 };
+
+    for (int i = 0; i < ARRAY_SIZE(files); i++) {
+        if (read_from_sysfs(files[i], buf, sizeof(buf)) < 0) {
+            return -1;
+        }
+    }
+    // The buffer holds the content of the last file, i.e., thread_siblings_list
+    if (strncmp(buf, "0", 1) != 0 || count_cpu_list(buf) <= 0) {
+        THROW_ERROR("cpu0 should be the sibling of itself: %s", buf);
+    }
+    return 0;
+}
+
+static int test_cgroup_memory() {
+    char buf[64] = { 0 };
+
+    if (read_from_sysfs("/sys/fs/cgroup/memory.max", buf, sizeof(buf)) < 0) {
+        return -1;
+    }
+    unsigned long memory_max = strtoul(buf, NULL, 10);
+    if (memory_max == 0) {
+        THROW_ERROR("the memory.max is wrong: %s", buf);
+    }
+    if (read_from_sysfs("/sys/fs/cgroup/memory.current", buf, sizeof(buf)) < 0) {
+        return -1;
+    }
+    unsigned long memory_current = strtoul(buf, NULL, 10);
+    if (memory_current == 0 || memory_current > memory_max) {
+        THROW_ERROR("the memory.current is wrong: %s", buf);
+    }
+    return 0;
+}
+
+static int test_cgroup_cpu() {
+    char buf[64] = { 0 };
+    unsigned long quota, period;
+    cpu_set_t cpuset;
+
+    if (read_from_sysfs("/sys/fs/cgroup/cpu.max", buf, sizeof(buf)) < 0) {
+        return -1;
+    }
+    if (sscanf(buf, "%lu %lu", &quota, &period) != 2 || period == 0) {
+        THROW_ERROR("the cpu.max is wrong: %s", buf);
+    }
+    if (sched_getaffinity(0, sizeof(cpuset), &cpuset) < 0) {
+        THROW_ERROR("failed to call sched_getaffinity");
+    }
+    if (quota / period != CPU_COUNT(&cpuset)) {
+        THROW_ERROR("the cpu quota does not match the available CPUs: %s", buf);
+    }
+    return 0;
+}
+
+static int test_write_to_sysfs() {
+    const char *path = "/sys/fs/cgroup/memory.max";
+    const char *val = "1024";
+    int fd = open(path, O_WRONLY);
+    if (fd < 0) {
+        // The permission check may fail the open, which is also acceptable
+        return 0;
+    }
+    if (write(fd, val, strlen(val)) >= 0) {
+        THROW_ERROR("write to a read-only file in sysfs should fail");
+    }
+    close(fd);
+    return 0;
+}
+
+#define SYSFS_MAGIC 0x62656572
+static int test_statfs() {
+    const char *file_path = "/sys/devices/system/cpu/online";
+    struct statfs statfs_buf;
+
+    if (statfs(file_path, &statfs_buf) < 0) {
+        THROW_ERROR("failed to statfs the file");
+    }
+    if (statfs_buf.f_type != SYSFS_MAGIC) {
+        THROW_ERROR("failed to check the f_type");
+    }
+    return 0;
+}
+
+static int test_readdir_root() {
+    const char *root = "/sys";
+    char expected_entries[2][NAME_MAX] = {
+        "devices",
+        "fs",
+    };
+
+    if (check_readdir_with_expected_entries(root, expected_entries, 2) < 0) {
+        THROW_ERROR("failed to test readdir %s", root);
+    }
+    return 0;
+}
+
+// ============================================================================
+// Test suite main
+// ============================================================================
+
+static test_case_t test_cases[] = {
+    TEST_CASE(test_cpu_online),
+    TEST_CASE(test_cpu_possible_and_present),
+    TEST_CASE(test_cpu_topology),
+    TEST_CASE(test_cgroup_memory),
+    TEST_CASE(test_cgroup_cpu),
+    TEST_CASE(test_write_to_sysfs),
+    TEST_CASE(test_statfs),
+    TEST_CASE(test_readdir_root),
+};
+
+int main(int argc, const char *argv[]) {
+    return test_suite_run(test_cases, ARRAY_SIZE(test_cases));
+}
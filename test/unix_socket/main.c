@@ -1,7 +1,9 @@
+#define _GNU_SOURCE
 #include <sys/syscall.h>
 #include <sys/wait.h>
 #include <sys/socket.h>
 #include <sys/un.h>
+#include <sys/stat.h>
 #include <sys/ioctl.h>
 #include <poll.h>
 #include <unistd.h>
@@ -11,6 +13,7 @@
 #include <string.h>
 #include <sys/epoll.h>
 #include <pthread.h>
+#include <errno.h>
 
 #include "test.h"
 
@@ -558,6 +561,316 @@ int test_sendmsg_recvmsg() {
     return ret;
 }
 
+int test_sendmmsg_recvmmsg() {
+    int ret = -1;
+    int socks[2];
+    char *msgs[] = {"hello", "world"};
+    char bufs[3][5];
+
+    if (socketpair(AF_UNIX, SOCK_STREAM, 0, socks) < 0) {
+        THROW_ERROR("socket pair create failed");
+    }
+
+    struct iovec iovs[3];
+    struct mmsghdr mmsgs[3];
+    memset(mmsgs, 0, sizeof(mmsgs));
+    for (int i = 0; i < 2; i++) {
+        iovs[i].iov_base = msgs[i];
+        iovs[i].iov_len = strlen(msgs[i]);
+        mmsgs[i].msg_hdr.msg_iov = &iovs[i];
+        mmsgs[i].msg_hdr.msg_iovlen = 1;
+    }
+    if (sendmmsg(socks[0], mmsgs, 2, 0) != 2 || mmsgs[0].msg_len != 5 ||
+            mmsgs[1].msg_len != 5) {
+        printf("sendmmsg failed\n");
+        goto out;
+    }
+
+    // With MSG_WAITFORONE, the third message should not block
+    memset(mmsgs, 0, sizeof(mmsgs));
+    for (int i = 0; i < 3; i++) {
+        iovs[i].iov_base = bufs[i];
+        iovs[i].iov_len = sizeof(bufs[i]);
+        mmsgs[i].msg_hdr.msg_iov = &iovs[i];
+        mmsgs[i].msg_hdr.msg_iovlen = 1;
+    }
+    int n = recvmmsg(socks[1], mmsgs, 3, MSG_WAITFORONE, NULL);
+    if (n != 2) {
+        printf("recvmmsg failed\n");
+        goto out;
+    }
+    for (int i = 0; i < 2; i++) {
+        if (mmsgs[i].msg_len != 5 || strncmp(bufs[i], msgs[i], 5) != 0) {
+            printf("wrong message received by recvmmsg\n");
+            goto out;
+        }
+    }
+    ret = 0;
+out:
+    close(socks[0]);
+    close(socks[1]);
+    return ret;
+}
+
+#define HOST_SOCK_PATH "/host/unix_socket_host_path"
+
+int test_hostfs_path() {
+    int ret = -1;
+    int sockets[2];
+
+    // The sockets are bridged to the host, so they also work across the host
+    unlink(HOST_SOCK_PATH);
+    if (create_connected_sockets(sockets, HOST_SOCK_PATH) < 0) {
+        THROW_ERROR("failed to create the sockets on the host");
+    }
+    if (access(HOST_SOCK_PATH, F_OK) < 0) {
+        printf("the socket file is not found on the host\n");
+        goto out;
+    }
+    if (verify_connection(sockets[0], sockets[1]) < 0 ||
+            verify_connection(sockets[1], sockets[0]) < 0) {
+        printf("failed to communicate through the host\n");
+        goto out;
+    }
+
+    // The fds in the enclave cannot be passed to the host
+    char data = 'a';
+    struct iovec iov = { .iov_base = &data, .iov_len = 1 };
+    char control[CMSG_SPACE(sizeof(int))];
+    struct msghdr msg;
+    memset(&msg, 0, sizeof(msg));
+    memset(control, 0, sizeof(control));
+    msg.msg_iov = &iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control;
+    msg.msg_controllen = sizeof(control);
+    struct cmsghdr *cmsg = CMSG_FIRSTHDR(&msg);
+    cmsg->cmsg_level = SOL_SOCKET;
+    cmsg->cmsg_type = SCM_RIGHTS;
+    cmsg->cmsg_len = CMSG_LEN(sizeof(int));
+    *(int *)CMSG_DATA(cmsg) = sockets[0];
+    if (sendmsg(sockets[0], &msg, 0) != -1 || errno != EOPNOTSUPP) {
+        printf("passing fds to the host should fail\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(sockets[0]);
+    close(sockets[1]);
+    unlink(HOST_SOCK_PATH);
+    return ret;
+}
+
+#define HOST_SOCK_LINK "/host/unix_socket_host_link"
+
+int test_hostfs_symlink_path() {
+    int ret = -1;
+    int sockets[2];
+
+    // The symlink is resolved inside the LibOS, so the socket is bound on the
+    // target of the symlink in HostFS rather than on the symlink itself
+    unlink(HOST_SOCK_PATH);
+    unlink(HOST_SOCK_LINK);
+    if (symlink("unix_socket_host_path", HOST_SOCK_LINK) < 0) {
+        THROW_ERROR("failed to create the symlink");
+    }
+    if (create_connected_sockets(sockets, HOST_SOCK_LINK) < 0) {
+        unlink(HOST_SOCK_LINK);
+        THROW_ERROR("failed to create the sockets via the symlink");
+    }
+    struct stat stat_buf;
+    if (stat(HOST_SOCK_PATH, &stat_buf) < 0 || !S_ISSOCK(stat_buf.st_mode)) {
+        printf("the socket file is not found at the target of the symlink\n");
+        goto out;
+    }
+    if (verify_connection(sockets[0], sockets[1]) < 0) {
+        printf("failed to communicate via the symlink\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(sockets[0]);
+    close(sockets[1]);
+    unlink(HOST_SOCK_LINK);
+    unlink(HOST_SOCK_PATH);
+    return ret;
+}
+
+int test_sockopt() {
+    int ret = -1;
+    int listen_fd = socket(AF_UNIX, SOCK_STREAM, 0);
+    if (listen_fd < 0) {
+        THROW_ERROR("failed to create a unix socket");
+    }
+
+    int val = 0;
+    socklen_t len = sizeof(val);
+    if (getsockopt(listen_fd, SOL_SOCKET, SO_TYPE, &val, &len) < 0 ||
+            val != SOCK_STREAM || len != sizeof(val)) {
+        printf("wrong SO_TYPE\n");
+        goto out;
+    }
+    if (getsockopt(listen_fd, SOL_SOCKET, SO_ERROR, &val, &len) < 0 || val != 0) {
+        printf("wrong SO_ERROR\n");
+        goto out;
+    }
+    if (getsockopt(listen_fd, SOL_SOCKET, SO_ACCEPTCONN, &val, &len) < 0 || val != 0) {
+        printf("wrong SO_ACCEPTCONN before listen\n");
+        goto out;
+    }
+
+    struct sockaddr_un addr = {0};
+    addr.sun_family = AF_UNIX;
+    strcpy(addr.sun_path, "unix_socket_sockopt");
+    socklen_t addr_len = strlen(addr.sun_path) + sizeof(addr.sun_family) + 1;
+    if (bind(listen_fd, (struct sockaddr *)&addr, addr_len) < 0 ||
+            listen(listen_fd, 5) < 0) {
+        printf("failed to listen\n");
+        goto out;
+    }
+    if (getsockopt(listen_fd, SOL_SOCKET, SO_ACCEPTCONN, &val, &len) < 0 || val != 1) {
+        printf("wrong SO_ACCEPTCONN after listen\n");
+        goto out;
+    }
+
+    // The buffer size is doubled, same with Linux
+    val = 32 * 1024;
+    if (setsockopt(listen_fd, SOL_SOCKET, SO_SNDBUF, &val, sizeof(val)) < 0) {
+        printf("failed to set SO_SNDBUF\n");
+        goto out;
+    }
+    if (getsockopt(listen_fd, SOL_SOCKET, SO_SNDBUF, &val, &len) < 0 ||
+            val != 64 * 1024) {
+        printf("wrong SO_SNDBUF\n");
+        goto out;
+    }
+
+    // SO_TYPE is read-only
+    if (setsockopt(listen_fd, SOL_SOCKET, SO_TYPE, &val, sizeof(val)) == 0 ||
+            errno != ENOPROTOOPT) {
+        printf("setting SO_TYPE should fail\n");
+        goto out;
+    }
+    ret = 0;
+out:
+    close(listen_fd);
+    return ret;
+}
+
+#define SMALL_BUF_SIZE 8192
+
+int test_sockopt_buf_size() {
+    int socks[2];
+    if (socketpair(AF_UNIX, SOCK_STREAM, 0, socks) < 0) {
+        THROW_ERROR("socketpair failed");
+    }
+
+    int val = SMALL_BUF_SIZE;
+    if (setsockopt(socks[0], SOL_SOCKET, SO_SNDBUF, &val, sizeof(val)) < 0 ||
+            setsockopt(socks[1], SOL_SOCKET, SO_RCVBUF, &val, sizeof(val)) < 0) {
+        THROW_ERROR("failed to set the buffer sizes");
+    }
+
+    // Fill the buffer until it is full
+    char buf[1024] = {0};
+    size_t total_len = 0;
+    while (1) {
+        ssize_t len = send(socks[0], buf, sizeof(buf), MSG_DONTWAIT);
+        if (len < 0) {
+            if (errno != EAGAIN) {
+                THROW_ERROR("failed to send");
+            }
+            break;
+        }
+        total_len += len;
+    }
+    if (total_len == 0 || total_len > SMALL_BUF_SIZE * 2) {
+        THROW_ERROR("the buffer size does not take effect");
+    }
+
+    close(socks[0]);
+    close(socks[1]);
+    return 0;
+}
+
+int test_recv_timeout() {
+    int socks[2];
+    if (socketpair(AF_UNIX, SOCK_STREAM, 0, socks) < 0) {
+        THROW_ERROR("socketpair failed");
+    }
+
+    struct timeval timeout = { .tv_sec = 0, .tv_usec = 100 * 1000 };
+    if (setsockopt(socks[1], SOL_SOCKET, SO_RCVTIMEO, &timeout, sizeof(timeout)) < 0) {
+        THROW_ERROR("failed to set SO_RCVTIMEO");
+    }
+    struct timeval ret_timeout = {0};
+    socklen_t len = sizeof(ret_timeout);
+    if (getsockopt(socks[1], SOL_SOCKET, SO_RCVTIMEO, &ret_timeout, &len) < 0 ||
+            ret_timeout.tv_sec != timeout.tv_sec || ret_timeout.tv_usec != timeout.tv_usec) {
+        THROW_ERROR("wrong SO_RCVTIMEO");
+    }
+
+    char buf[16];
+    if (recv(socks[1], buf, sizeof(buf), 0) != -1 || errno != EAGAIN) {
+        THROW_ERROR("recv should time out");
+    }
+
+    close(socks[0]);
+    close(socks[1]);
+    return 0;
+}
+
+int test_recv_flags() {
+    int socks[2];
+    if (socketpair(AF_UNIX, SOCK_STREAM, 0, socks) < 0) {
+        THROW_ERROR("socketpair failed");
+    }
+
+    char buf[32] = {0};
+    if (recv(socks[1], buf, sizeof(buf), MSG_DONTWAIT) != -1 || errno != EAGAIN) {
+        THROW_ERROR("recv with MSG_DONTWAIT should not block");
+    }
+
+    if (send(socks[0], ECHO_MSG, sizeof(ECHO_MSG), 0) != sizeof(ECHO_MSG)) {
+        THROW_ERROR("failed to send");
+    }
+    // The peeked data is still there to receive
+    if (recv(socks[1], buf, sizeof(buf), MSG_PEEK) != sizeof(ECHO_MSG) ||
+            strcmp(buf, ECHO_MSG) != 0) {
+        THROW_ERROR("failed to peek");
+    }
+    int data_len_ready = 0;
+    if (ioctl(socks[1], FIONREAD, &data_len_ready) < 0 ||
+            data_len_ready != sizeof(ECHO_MSG)) {
+        THROW_ERROR("the peeked data is not ready to read");
+    }
+    memset(buf, 0, sizeof(buf));
+    if (recv(socks[1], buf, sizeof(buf), 0) != sizeof(ECHO_MSG) ||
+            strcmp(buf, ECHO_MSG) != 0) {
+        THROW_ERROR("failed to receive the peeked data");
+    }
+
+    close(socks[0]);
+    close(socks[1]);
+    return 0;
+}
+
+int test_send_nosignal() {
+    int socks[2];
+    if (socketpair(AF_UNIX, SOCK_STREAM, 0, socks) < 0) {
+        THROW_ERROR("socketpair failed");
+    }
+    close(socks[1]);
+
+    // No SIGPIPE is raised, or the process is killed
+    if (send(socks[0], ECHO_MSG, sizeof(ECHO_MSG), MSG_NOSIGNAL) != -1 || errno != EPIPE) {
+        THROW_ERROR("send to a closed socket should fail with EPIPE");
+    }
+
+    close(socks[0]);
+    return 0;
+}
+
 static test_case_t test_cases[] = {
     TEST_CASE(test_unix_socket_inter_process),
     TEST_CASE(test_socketpair_inter_process),
@@ -568,6 +881,14 @@ static test_case_t test_cases[] = {
     TEST_CASE(test_unix_socket_rename),
     TEST_CASE(test_epoll_wait),
     TEST_CASE(test_sendmsg_recvmsg),
+    TEST_CASE(test_sendmmsg_recvmmsg),
+    TEST_CASE(test_hostfs_path),
+    TEST_CASE(test_hostfs_symlink_path),
+    TEST_CASE(test_sockopt),
+    TEST_CASE(test_sockopt_buf_size),
+    TEST_CASE(test_recv_timeout),
+    TEST_CASE(test_recv_flags),
+    TEST_CASE(test_send_nosignal),
 };
 
 int main(int argc, const char *argv[]) {
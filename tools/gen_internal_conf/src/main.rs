@@ -493,6 +493,8 @@ fn main() {
             },
             env: occlum_config.env,
             app: app_config,
+            network: occlum_config.network,
+            async_io: occlum_config.async_io,
         };
 
         let occlum_json_str = serde_json::to_string_pretty(&occlum_json_config).unwrap();
@@ -607,6 +609,10 @@ fn gen_app_config(
                     "target": "/proc",
                     "type": "procfs"
                 },
+                {
+                    "target": "/sys",
+                    "type": "sysfs"
+                },
                 {
                     "target": "/dev",
                     "type": "devfs"
@@ -621,6 +627,10 @@ fn gen_app_config(
                     "target": "/proc",
                     "type": "procfs"
                 },
+                {
+                    "target": "/sys",
+                    "type": "sysfs"
+                },
                 {
                     "target": "/dev",
                     "type": "devfs"
@@ -684,6 +694,10 @@ struct OcclumConfiguration {
     env: serde_json::Value,
     metadata: OcclumMetadata,
     mount: Vec<OcclumMount>,
+    #[serde(default)]
+    network: Option<serde_json::Value>,
+    #[serde(default)]
+    async_io: Option<serde_json::Value>,
 }
 
 #[derive(Debug, PartialEq, Deserialize)]
@@ -751,6 +765,14 @@ struct OcclumMountOptions {
     pub temporary: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cache_size: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confined: Option<bool>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub obfuscate_names: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nr_inodes: Option<usize>,
 }
 
 #[inline]
@@ -810,4 +832,8 @@ struct InternalOcclumJson {
     process: OcclumProcess,
     env: serde_json::Value,
     app: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    network: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    async_io: Option<serde_json::Value>,
 }
@@ -97,6 +97,39 @@ impl DcapDemo {
         quote_verification_result
     }
 
+    fn dcap_seal_and_unseal(&mut self, secret: &str) -> bool {
+        // Get the size of the sealed data first
+        let mut sealed_size: u32 = 0;
+        let mut seal_arg = IoctlSealDataArg {
+            key_policy: SGX_KEYPOLICY_MRSIGNER,
+            plaintext: secret.as_ptr(),
+            plaintext_len: secret.len() as u32,
+            additional_mac_text: std::ptr::null(),
+            additional_mac_text_len: 0,
+            sealed_data: std::ptr::null_mut(),
+            sealed_data_len: &mut sealed_size,
+        };
+        self.dcap_quote.seal_data(&mut seal_arg).unwrap();
+
+        let mut sealed_buf: Vec<u8> = vec![0; sealed_size as usize];
+        seal_arg.sealed_data = sealed_buf.as_mut_ptr();
+        self.dcap_quote.seal_data(&mut seal_arg).unwrap();
+
+        let mut plaintext_buf: Vec<u8> = vec![0; secret.len()];
+        let mut plaintext_size = plaintext_buf.len() as u32;
+        let mut unseal_arg = IoctlUnsealDataArg {
+            sealed_data: sealed_buf.as_ptr(),
+            sealed_data_len: sealed_size,
+            plaintext: plaintext_buf.as_mut_ptr(),
+            plaintext_len: &mut plaintext_size,
+            additional_mac_text: std::ptr::null_mut(),
+            additional_mac_text_len: std::ptr::null_mut(),
+        };
+        self.dcap_quote.unseal_data(&mut unseal_arg).unwrap();
+
+        &plaintext_buf[..plaintext_size as usize] == secret.as_bytes()
+    }
+
     fn dcap_dump_quote_info(&mut self) {
         let report_body_ptr = self.dcap_quote_get_report_body().unwrap();
 
@@ -181,4 +214,10 @@ fn main() {
             result
         ),
     }
+
+    if dcap_demo.dcap_seal_and_unseal("Dcap demo secret") {
+        println!("Succeed to seal and unseal the data!");
+    } else {
+        println!("Unsealed data doesn't match !!!");
+    }
 }
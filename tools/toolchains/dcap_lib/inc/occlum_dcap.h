@@ -27,6 +27,28 @@ int32_t dcap_verify_quote(void *handle,
                           uint32_t supplemental_data_size,
                           uint8_t *supplemental_data);
 
+// Seal the data into a sgx_sealed_data_t blob with the key policy
+// SGX_KEYPOLICY_MRENCLAVE or SGX_KEYPOLICY_MRSIGNER. If sealed_data is NULL,
+// only the size of the blob is returned in sealed_data_len.
+int32_t dcap_seal_data(void *handle,
+                       uint16_t key_policy,
+                       const uint8_t *plaintext,
+                       uint32_t plaintext_len,
+                       const uint8_t *additional_mac_text,
+                       uint32_t additional_mac_text_len,
+                       uint8_t *sealed_data,
+                       uint32_t *sealed_data_len);
+
+// Unseal a sgx_sealed_data_t blob. If plaintext is NULL, only the size of
+// the plaintext is returned in plaintext_len. additional_mac_text and
+// additional_mac_text_len are optional.
+int32_t dcap_unseal_data(void *handle,
+                         const uint8_t *sealed_data,
+                         uint32_t sealed_data_len,
+                         uint8_t *plaintext,
+                         uint32_t *plaintext_len,
+                         uint8_t *additional_mac_text,
+                         uint32_t *additional_mac_text_len);
 
 void dcap_quote_close(void *handle);
 
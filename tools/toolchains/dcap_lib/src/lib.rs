@@ -78,6 +78,64 @@ pub extern "C" fn dcap_verify_quote(
     dcap.verify_quote(&mut verify_arg).unwrap_or(-1)
 }
 
+#[no_mangle]
+pub extern "C" fn dcap_seal_data(
+    handle: *mut c_void,
+    key_policy: u16,
+    plaintext: *const u8,
+    plaintext_len: u32,
+    additional_mac_text: *const u8,
+    additional_mac_text_len: u32,
+    sealed_data: *mut u8,
+    sealed_data_len: *mut u32,
+) -> i32 {
+    if handle.is_null() {
+        return -1;
+    }
+
+    let dcap = unsafe { &mut *(handle as *mut DcapQuote) };
+
+    let mut seal_arg = IoctlSealDataArg {
+        key_policy: key_policy,
+        plaintext: plaintext,
+        plaintext_len: plaintext_len,
+        additional_mac_text: additional_mac_text,
+        additional_mac_text_len: additional_mac_text_len,
+        sealed_data: sealed_data,
+        sealed_data_len: sealed_data_len,
+    };
+
+    dcap.seal_data(&mut seal_arg).unwrap_or(-1)
+}
+
+#[no_mangle]
+pub extern "C" fn dcap_unseal_data(
+    handle: *mut c_void,
+    sealed_data: *const u8,
+    sealed_data_len: u32,
+    plaintext: *mut u8,
+    plaintext_len: *mut u32,
+    additional_mac_text: *mut u8,
+    additional_mac_text_len: *mut u32,
+) -> i32 {
+    if handle.is_null() {
+        return -1;
+    }
+
+    let dcap = unsafe { &mut *(handle as *mut DcapQuote) };
+
+    let mut unseal_arg = IoctlUnsealDataArg {
+        sealed_data: sealed_data,
+        sealed_data_len: sealed_data_len,
+        plaintext: plaintext,
+        plaintext_len: plaintext_len,
+        additional_mac_text: additional_mac_text,
+        additional_mac_text_len: additional_mac_text_len,
+    };
+
+    dcap.unseal_data(&mut unseal_arg).unwrap_or(-1)
+}
+
 #[no_mangle]
 pub extern "C" fn dcap_quote_close(handle: *mut c_void) {
     if handle.is_null() {
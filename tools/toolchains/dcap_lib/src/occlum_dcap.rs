@@ -5,6 +5,8 @@ const SGXIOC_GET_DCAP_QUOTE_SIZE: u64 = 0x80047307;
 const SGXIOC_GEN_DCAP_QUOTE: u64 = 0xc0187308;
 const SGXIOC_GET_DCAP_SUPPLEMENTAL_SIZE: u64 = 0x80047309;
 const SGXIOC_VER_DCAP_QUOTE: u64 = 0xc030730a;
+const SGXIOC_SEAL_DATA: u64 = 0xc038730c;
+const SGXIOC_UNSEAL_DATA: u64 = 0xc030730d;
 
 cfg_if::cfg_if! {
     if #[cfg(target_env = "musl")] {
@@ -12,11 +14,15 @@ cfg_if::cfg_if! {
         const IOCTL_GEN_DCAP_QUOTE: i32 = SGXIOC_GEN_DCAP_QUOTE as i32;
         const IOCTL_GET_DCAP_SUPPLEMENTAL_SIZE: i32 = SGXIOC_GET_DCAP_SUPPLEMENTAL_SIZE as i32;
         const IOCTL_VER_DCAP_QUOTE: i32 = SGXIOC_VER_DCAP_QUOTE as i32;
+        const IOCTL_SEAL_DATA: i32 = SGXIOC_SEAL_DATA as i32;
+        const IOCTL_UNSEAL_DATA: i32 = SGXIOC_UNSEAL_DATA as i32;
     } else {
         const IOCTL_GET_DCAP_QUOTE_SIZE: u64 = SGXIOC_GET_DCAP_QUOTE_SIZE;
         const IOCTL_GEN_DCAP_QUOTE: u64 = SGXIOC_GEN_DCAP_QUOTE;
         const IOCTL_GET_DCAP_SUPPLEMENTAL_SIZE: u64 = SGXIOC_GET_DCAP_SUPPLEMENTAL_SIZE;
         const IOCTL_VER_DCAP_QUOTE: u64 = SGXIOC_VER_DCAP_QUOTE;
+        const IOCTL_SEAL_DATA: u64 = SGXIOC_SEAL_DATA;
+        const IOCTL_UNSEAL_DATA: u64 = SGXIOC_UNSEAL_DATA;
     }
 }
 
@@ -41,6 +47,31 @@ pub struct IoctlVerDCAPQuoteArg {
     pub supplemental_data: *mut u8,                         // Output (optional)
 }
 
+// Copy from occlum/src/libos/src/fs/dev_fs/dev_sgx/mod.rs
+//#[allow(dead_code)]
+#[repr(C)]
+pub struct IoctlSealDataArg {
+    pub key_policy: u16,                // Input
+    pub plaintext: *const u8,           // Input
+    pub plaintext_len: u32,             // Input
+    pub additional_mac_text: *const u8, // Input (optional)
+    pub additional_mac_text_len: u32,   // Input (optional)
+    pub sealed_data: *mut u8,           // Output (optional)
+    pub sealed_data_len: *mut u32,      // Input/output
+}
+
+// Copy from occlum/src/libos/src/fs/dev_fs/dev_sgx/mod.rs
+//#[allow(dead_code)]
+#[repr(C)]
+pub struct IoctlUnsealDataArg {
+    pub sealed_data: *const u8,            // Input
+    pub sealed_data_len: u32,              // Input
+    pub plaintext: *mut u8,                // Output (optional)
+    pub plaintext_len: *mut u32,           // Input/output
+    pub additional_mac_text: *mut u8,      // Output (optional)
+    pub additional_mac_text_len: *mut u32, // Input/output (optional)
+}
+
 pub struct DcapQuote {
     fd: c_int,
     quote_size: u32,
@@ -122,6 +153,32 @@ impl DcapQuote {
         }
     }
 
+    // Seal the data into a sgx_sealed_data_t blob. If `sealed_data` is NULL,
+    // only the size of the blob is returned in `sealed_data_len`.
+    pub fn seal_data(&mut self, seal_arg: *mut IoctlSealDataArg) -> Result<i32, Error> {
+        let ret = unsafe { libc::ioctl(self.fd, IOCTL_SEAL_DATA, seal_arg) };
+        if ret < 0 {
+            let os_err = Error::last_os_error();
+            println!("OS error: {os_err:?}");
+            Err(os_err)
+        } else {
+            Ok(0)
+        }
+    }
+
+    // Unseal a sgx_sealed_data_t blob. If `plaintext` is NULL, only the size
+    // of the plaintext is returned in `plaintext_len`.
+    pub fn unseal_data(&mut self, unseal_arg: *mut IoctlUnsealDataArg) -> Result<i32, Error> {
+        let ret = unsafe { libc::ioctl(self.fd, IOCTL_UNSEAL_DATA, unseal_arg) };
+        if ret < 0 {
+            let os_err = Error::last_os_error();
+            println!("OS error: {os_err:?}");
+            Err(os_err)
+        } else {
+            Ok(0)
+        }
+    }
+
     pub fn close(&mut self) {
         unsafe { libc::close(self.fd) };
     }
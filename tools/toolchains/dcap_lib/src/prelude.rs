@@ -5,4 +5,5 @@ pub use std::io::Error;
 // Defined in "occlum/deps/rust-sgx-sdk/sgx_types"
 pub use sgx_types::{
     sgx_ql_qv_result_t, sgx_quote3_t, sgx_quote_header_t, sgx_report_body_t, sgx_report_data_t,
+    SGX_KEYPOLICY_MRENCLAVE, SGX_KEYPOLICY_MRSIGNER,
 };